@@ -9,15 +9,22 @@ fn main() -> io::Result<()> {
         .exec()
         .expect("Failed to get cargo metadata");
 
-    let workspace_root = metadata.workspace_root;
+    let workspace_root = metadata.workspace_root.into_std_path_buf();
+    let shaders_directory = workspace_root.join("shaders");
+    let output_directory = workspace_root.join("intermediate").join("shaders");
 
-    let dir = std::format!("{}/shaders/programs", workspace_root);
-    println!("cargo:rerun-if-changed={}/shaders", workspace_root);
+    let dir = shaders_directory.join("programs");
+    println!("cargo:rerun-if-changed={}", shaders_directory.display());
 
     let sdk_path = env::var("VULKAN_SDK")
         .expect("VULKAN_SDK environment variable not found. Is the Vulkan SDK installed?");
 
-    let sdk_bin = PathBuf::from(sdk_path).join("Bin");
+    // NOTE: Windows SDK names the directory `Bin`, Linux and macOS ones `bin`.
+    let sdk_bin = PathBuf::from(sdk_path).join(if cfg!(target_os = "windows") {
+        "Bin"
+    } else {
+        "bin"
+    });
 
     let exe_suffix = if cfg!(target_os = "windows") {
         ".exe"
@@ -28,7 +35,7 @@ fn main() -> io::Result<()> {
     let slangc_path = sdk_bin.join(format!("slangc{}", exe_suffix));
     let spirv_opt_path = sdk_bin.join(format!("spirv-opt{}", exe_suffix));
 
-    fs::create_dir_all(std::format!("{}/intermediate/shaders", workspace_root))?;
+    fs::create_dir_all(&output_directory)?;
 
     let mut file_paths = Vec::new();
     for entry in fs::read_dir(dir)? {
@@ -45,11 +52,11 @@ fn main() -> io::Result<()> {
 
     for path in file_paths {
         let filename = path.file_name().unwrap().to_str().unwrap();
-        let output_filename = format!("{}/intermediate/shaders/{}.spv", workspace_root, filename);
+        let output_filename = output_directory.join(format!("{}.spv", filename));
 
         let slang_status = Command::new(&slangc_path)
             .arg("-I")
-            .arg(std::format!("{}/shaders", workspace_root))
+            .arg(&shaders_directory)
             .arg(format!("-DMATERIAL_TYPE={}", "UnlitMaterial"))
             .arg("-target")
             .arg("spirv")
@@ -82,14 +89,19 @@ fn main() -> io::Result<()> {
 
     let sysroot = get_sysroot();
 
-    let bin_path = sysroot.join("bin");
-
-    let file_prefix = "std-";
+    // NOTE: Dynamic std is next to rustc on Windows and in `lib` of the sysroot elsewhere.
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    let (std_directory, file_prefix, file_extension) = match target_os.as_str() {
+        "windows" => ("bin", "std-", "dll"),
+        "macos" => ("lib", "libstd-", "dylib"),
+        _ => ("lib", "libstd-", "so"),
+    };
+    let bin_path = sysroot.join(std_directory);
 
-    let out_dir = PathBuf::from(std::format!("{}/target/debug", workspace_root));
+    let out_dir = workspace_root.join("target").join("debug");
 
-    if let Err(e) = find_and_copy(&bin_path, out_dir.as_path(), file_prefix) {
-        println!("cargo:warning=Could not copy std dll: {}", e);
+    if let Err(e) = find_and_copy(&bin_path, out_dir.as_path(), file_prefix, file_extension) {
+        println!("cargo:warning=Could not copy std library: {}", e);
     }
 
     Ok(())
@@ -110,7 +122,12 @@ fn get_sysroot() -> PathBuf {
     PathBuf::from(sysroot_str)
 }
 
-fn find_and_copy(src_dir: &Path, dst_dir: &Path, prefix: &str) -> std::io::Result<()> {
+fn find_and_copy(
+    src_dir: &Path,
+    dst_dir: &Path,
+    prefix: &str,
+    extension: &str,
+) -> std::io::Result<()> {
     if !src_dir.exists() {
         return Ok(());
     }
@@ -122,7 +139,7 @@ fn find_and_copy(src_dir: &Path, dst_dir: &Path, prefix: &str) -> std::io::Resul
         if path.is_file()
             && let Some(filename) = path.file_name().and_then(|s| s.to_str())
             && filename.starts_with(prefix)
-            && filename.ends_with(".dll")
+            && path.extension().and_then(|s| s.to_str()) == Some(extension)
         {
             let dest_path = dst_dir.join(filename);
 
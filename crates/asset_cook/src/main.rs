@@ -0,0 +1,67 @@
+use std::time::Instant;
+
+use engine::engine::{AssetCooker, EngineConfig, ImportSettings};
+
+const USAGE: &str = "Usage: asset_cook [--assets <path>] [--intermediate <path>] [--force] \
+    [--weld] [--remove-degenerates]";
+
+/// `--assets <path>`, `--intermediate <path>`, `--force`, `--weld`, `--remove-degenerates`.
+/// Roots default to the ones of `engine.toml`, import settings must match the runtime ones,
+/// otherwise the runtime imports the meshes on demand again.
+fn parse_cooker() -> Result<AssetCooker, String> {
+    let mut asset_cooker = AssetCooker::new(&EngineConfig::load());
+    let mut import_settings = ImportSettings::default();
+
+    let mut arguments = std::env::args().skip(1);
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "--assets" => {
+                let asset_root = arguments.next().ok_or("Missing value of --assets")?;
+                asset_cooker = asset_cooker.with_asset_root(asset_root);
+            }
+            "--intermediate" => {
+                let intermediate_root =
+                    arguments.next().ok_or("Missing value of --intermediate")?;
+                asset_cooker = asset_cooker.with_intermediate_root(intermediate_root);
+            }
+            "--force" => asset_cooker = asset_cooker.with_forced(true),
+            "--weld" => import_settings.is_welding_enabled = true,
+            "--remove-degenerates" => import_settings.is_degenerate_removal_enabled = true,
+            argument => return Err(std::format!("Unknown argument {}", argument)),
+        }
+    }
+
+    Ok(asset_cooker.with_import_settings(import_settings))
+}
+
+fn main() {
+    let asset_cooker = match parse_cooker() {
+        Ok(asset_cooker) => asset_cooker,
+        Err(error) => {
+            eprintln!("{}\n{}", error, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let cook_started_at = Instant::now();
+    let cook_report = asset_cooker.cook();
+
+    println!(
+        "Processed {} models in {:.1}s | Models: {} cooked, {} up to date | Meshes: {}, LODs: {} | Textures: {} cooked, {} up to date",
+        cook_report.models_count,
+        cook_started_at.elapsed().as_secs_f64(),
+        cook_report.cooked_models_count,
+        cook_report.skipped_models_count,
+        cook_report.meshes_count,
+        cook_report.lods_count,
+        cook_report.cooked_textures_count,
+        cook_report.skipped_textures_count,
+    );
+    for (model_path, error) in cook_report.failed_models.iter() {
+        println!("Failed: {} | {}", model_path.display(), error);
+    }
+
+    if !cook_report.failed_models.is_empty() {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,314 @@
+//! Renders a scene for a number of frames and prints CPU frame times, GPU pass times and upload
+//! throughput as JSON. Engine is headless, so neither a window nor a display is required.
+
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use bevy_ecs::system::Commands;
+use engine::{
+    engine::{
+        GoldenImageError, GpuPassTimes, GpuProfiler, ImageTolerance, RenderStatistics,
+        UploadStatistics, assert_golden_image,
+    },
+    prelude::{
+        Camera, Engine, EngineConfig, GamePlugin, LoadModelEvent, LocalTransform, Quat, Vec3,
+    },
+};
+use serde::Serialize;
+
+struct BenchArguments {
+    engine_config: EngineConfig,
+    model_path: PathBuf,
+    frames_count: usize,
+    warmup_frames_count: usize,
+    /// Frame after the warmup is compared against the golden image instead of the benchmark.
+    golden_path: Option<PathBuf>,
+}
+
+impl BenchArguments {
+    const USAGE: &str =
+        "Usage: bench [--model <path>] [--frames <count>] [--warmup <count>] [--golden <path>]";
+
+    /// `--model <path>`, `--frames <count>`, `--warmup <count>`, `--golden <path>`.
+    fn parse() -> Result<Self, String> {
+        let engine_config = EngineConfig::load();
+
+        let mut bench_arguments = Self {
+            model_path: engine_config.get_asset_path("planet.glb"),
+            engine_config,
+            frames_count: 1_000,
+            warmup_frames_count: 100,
+            golden_path: None,
+        };
+
+        let mut arguments = std::env::args().skip(1);
+        while let Some(argument) = arguments.next() {
+            match (argument.as_str(), arguments.next()) {
+                ("--model", Some(model_path)) => bench_arguments.model_path = model_path.into(),
+                ("--frames", Some(frames_count)) => {
+                    bench_arguments.frames_count = Self::parse_count(&argument, &frames_count)?
+                }
+                ("--warmup", Some(warmup_frames_count)) => {
+                    bench_arguments.warmup_frames_count =
+                        Self::parse_count(&argument, &warmup_frames_count)?
+                }
+                ("--golden", Some(golden_path)) => {
+                    bench_arguments.golden_path = Some(golden_path.into())
+                }
+                ("--model" | "--frames" | "--warmup" | "--golden", None) => {
+                    return Err(std::format!("Missing value of {}", argument));
+                }
+                (argument, _) => return Err(std::format!("Unknown argument {}", argument)),
+            }
+        }
+
+        Ok(bench_arguments)
+    }
+
+    fn parse_count(argument: &str, value: &str) -> Result<usize, String> {
+        value
+            .parse()
+            .map_err(|_| std::format!("Invalid count of {}: {}", argument, value))
+    }
+}
+
+struct BenchScene {
+    model_path: PathBuf,
+}
+
+impl GamePlugin for BenchScene {
+    fn add_systems_init(&self, schedule: &mut bevy_ecs::schedule::Schedule) {
+        let model_path = self.model_path.clone();
+
+        schedule.add_systems(move |mut commands: Commands| {
+            let camera_transform = LocalTransform {
+                local_position: Vec3::new(0.0, 0.0, 50.0),
+                local_rotation: Quat::IDENTITY,
+                local_scale: Vec3::ONE,
+            };
+            commands.spawn((Camera::default(), camera_transform));
+
+            let model_entity = commands.spawn(LocalTransform::IDENTITY).id();
+            commands.trigger(LoadModelEvent {
+                path: model_path.clone(),
+                parent_entity: Some(model_entity),
+            });
+        });
+    }
+
+    fn add_systems_update(&self, _schedule: &mut bevy_ecs::schedule::Schedule) {}
+}
+
+#[derive(Default, Serialize)]
+struct FrameTimeReport {
+    average: f64,
+    min: f64,
+    max: f64,
+    p50: f64,
+    p95: f64,
+    p99: f64,
+}
+
+impl FrameTimeReport {
+    /// Values are in milliseconds.
+    fn new(mut frame_times: Vec<f64>) -> Self {
+        if frame_times.is_empty() {
+            return Default::default();
+        }
+
+        frame_times.sort_by(f64::total_cmp);
+        let percentile = |percent: f64| {
+            let index = ((frame_times.len() - 1) as f64 * percent).round() as usize;
+
+            frame_times[index]
+        };
+
+        Self {
+            average: frame_times.iter().sum::<f64>() / frame_times.len() as f64,
+            min: frame_times[0],
+            max: frame_times[frame_times.len() - 1],
+            p50: percentile(0.5),
+            p95: percentile(0.95),
+            p99: percentile(0.99),
+        }
+    }
+}
+
+#[derive(Default, Serialize)]
+struct GpuPassTimesReport {
+    background: f64,
+    meshes: f64,
+    blit: f64,
+    frame: f64,
+}
+
+impl GpuPassTimesReport {
+    fn new(gpu_pass_times: &[GpuPassTimes]) -> Self {
+        if gpu_pass_times.is_empty() {
+            return Default::default();
+        }
+
+        let average = |get_time: fn(&GpuPassTimes) -> f32| {
+            gpu_pass_times
+                .iter()
+                .map(|pass_times| get_time(pass_times) as f64)
+                .sum::<f64>()
+                / gpu_pass_times.len() as f64
+        };
+
+        Self {
+            background: average(|pass_times| pass_times.background_time),
+            meshes: average(|pass_times| pass_times.meshes_time),
+            blit: average(|pass_times| pass_times.blit_time),
+            frame: average(|pass_times| pass_times.frame_time),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct UploadReport {
+    uploaded_bytes_count: u64,
+    upload_time: f64,
+    throughput_bytes_per_second: f64,
+}
+
+impl From<UploadStatistics> for UploadReport {
+    fn from(upload_statistics: UploadStatistics) -> Self {
+        Self {
+            uploaded_bytes_count: upload_statistics.uploaded_bytes_count,
+            upload_time: upload_statistics.upload_duration.as_secs_f64() * 1_000.0,
+            throughput_bytes_per_second: upload_statistics.get_throughput(),
+        }
+    }
+}
+
+/// Times are in milliseconds.
+#[derive(Serialize)]
+struct BenchReport {
+    model_path: String,
+    frames_count: usize,
+    startup_time: f64,
+    cpu_frame_time: FrameTimeReport,
+    gpu_pass_times: GpuPassTimesReport,
+    upload: UploadReport,
+    instances_count: usize,
+    materials_count: usize,
+}
+
+struct Bench {
+    bench_arguments: BenchArguments,
+    engine: Engine,
+    startup_duration: Duration,
+    cpu_frame_times: Vec<f64>,
+    gpu_pass_times: Vec<GpuPassTimes>,
+}
+
+impl Bench {
+    fn new(bench_arguments: BenchArguments) -> Self {
+        let frames_count = bench_arguments.frames_count;
+        let engine_config = bench_arguments.engine_config.clone();
+
+        let startup_started_at = Instant::now();
+        let mut engine = Engine::new_headless(engine_config.get_surface_size(), engine_config);
+        engine.init_game(&BenchScene {
+            model_path: bench_arguments.model_path.clone(),
+        });
+        let startup_duration = startup_started_at.elapsed();
+
+        Self {
+            bench_arguments,
+            engine,
+            startup_duration,
+            cpu_frame_times: Vec::with_capacity(frames_count),
+            gpu_pass_times: Vec::with_capacity(frames_count),
+        }
+    }
+
+    fn run(mut self) -> BenchReport {
+        for _ in 0..self.bench_arguments.warmup_frames_count {
+            self.engine.update();
+        }
+
+        for _ in 0..self.bench_arguments.frames_count {
+            let frame_started_at = Instant::now();
+            self.engine.update();
+            self.cpu_frame_times
+                .push(frame_started_at.elapsed().as_secs_f64() * 1_000.0);
+            self.gpu_pass_times.push(
+                self.engine
+                    .get_resource::<GpuProfiler>()
+                    .unwrap()
+                    .get_last_pass_times(),
+            );
+        }
+
+        let report = self.create_report();
+        self.engine.shutdown();
+
+        report
+    }
+
+    /// Frame after the warmup is captured, the capture is read back a few frames later.
+    fn check_golden_image(mut self, golden_path: &Path) -> Result<(), GoldenImageError> {
+        for _ in 0..self.bench_arguments.warmup_frames_count {
+            self.engine.update();
+        }
+
+        self.engine.request_frame_capture(Default::default());
+        let captured_frame = loop {
+            self.engine.update();
+
+            if let Some(captured_frame) = self.engine.take_captured_frame() {
+                break captured_frame;
+            }
+        };
+        self.engine.shutdown();
+
+        assert_golden_image(&captured_frame, golden_path, ImageTolerance::default())
+    }
+
+    fn create_report(&self) -> BenchReport {
+        let render_statistics = self.engine.get_resource::<RenderStatistics>().unwrap();
+
+        BenchReport {
+            model_path: self.bench_arguments.model_path.display().to_string(),
+            frames_count: self.cpu_frame_times.len(),
+            startup_time: self.startup_duration.as_secs_f64() * 1_000.0,
+            cpu_frame_time: FrameTimeReport::new(self.cpu_frame_times.clone()),
+            gpu_pass_times: GpuPassTimesReport::new(&self.gpu_pass_times),
+            upload: self.engine.get_upload_statistics().into(),
+            instances_count: render_statistics.instances_count
+                + render_statistics.static_instances_count,
+            materials_count: render_statistics.materials_count,
+        }
+    }
+}
+
+fn main() {
+    let bench_arguments = match BenchArguments::parse() {
+        Ok(bench_arguments) => bench_arguments,
+        Err(error) => {
+            // NOTE: Report goes to the standard output, so errors don't mix with its JSON.
+            eprintln!("{}\n{}", error, BenchArguments::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let golden_path = bench_arguments.golden_path.clone();
+    let bench = Bench::new(bench_arguments);
+    match golden_path {
+        Some(golden_path) => match bench.check_golden_image(&golden_path) {
+            Ok(()) => println!("Frame matches {}", golden_path.display()),
+            Err(error) => {
+                println!("Frame doesn't match {}: {}", golden_path.display(), error);
+                std::process::exit(1);
+            }
+        },
+        None => {
+            let report = bench.run();
+            println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use std::{env, path::PathBuf};
+
+fn main() {
+    println!("cargo:rerun-if-changed=native/draco_decoder.cpp");
+    println!("cargo:rerun-if-env-changed=DRACO_DIR");
+    if env::var_os("CARGO_FEATURE_DRACO").is_none() {
+        return;
+    }
+
+    // NOTE: `DRACO_DIR` is an install prefix, for platforms without pkg-config like Windows.
+    let (include_paths, link_paths, libraries) = match env::var_os("DRACO_DIR") {
+        Some(draco_directory) => {
+            let draco_directory = PathBuf::from(draco_directory);
+
+            (
+                vec![draco_directory.join("include")],
+                vec![draco_directory.join("lib")],
+                vec!["draco".to_string()],
+            )
+        }
+        None => {
+            let draco = pkg_config::Config::new()
+                .cargo_metadata(false)
+                .probe("draco")
+                .expect("Draco wasn't found, set DRACO_DIR or disable the `draco` feature");
+
+            (draco.include_paths, draco.link_paths, draco.libs)
+        }
+    };
+
+    cc::Build::new()
+        .cpp(true)
+        .std("c++17")
+        .includes(&include_paths)
+        .file("native/draco_decoder.cpp")
+        .compile("draco_decoder");
+
+    // NOTE: Linked after the shim, static linkers resolve symbols in order.
+    for link_path in link_paths {
+        println!("cargo:rustc-link-search=native={}", link_path.display());
+    }
+    for library in libraries {
+        println!("cargo:rustc-link-lib={library}");
+    }
+}
@@ -614,7 +614,6 @@ fn serialize_model_asset(
                             metallic_texture_index,
                             roughness_texture_index,
                         ),
-                        sampler_index: Default::default(),
                     };
                     let material_data_raw = bytemuck::bytes_of(&material_data);
                     serialize_material(
@@ -113,6 +113,9 @@ pub struct MaterialTextures {
     pub albedo_texture_index: u32,
     pub metallic_texture_index: u32,
     pub roughness_texture_index: u32,
+    pub albedo_sampler_index: u32,
+    pub metallic_sampler_index: u32,
+    pub roughness_sampler_index: u32,
 }
 
 impl MaterialTextures {
@@ -125,8 +128,24 @@ impl MaterialTextures {
             albedo_texture_index,
             metallic_texture_index,
             roughness_texture_index,
+            albedo_sampler_index: Default::default(),
+            metallic_sampler_index: Default::default(),
+            roughness_sampler_index: Default::default(),
         }
     }
+
+    pub fn with_sampler_indices(
+        mut self,
+        albedo_sampler_index: u32,
+        metallic_sampler_index: u32,
+        roughness_sampler_index: u32,
+    ) -> Self {
+        self.albedo_sampler_index = albedo_sampler_index;
+        self.metallic_sampler_index = metallic_sampler_index;
+        self.roughness_sampler_index = roughness_sampler_index;
+
+        self
+    }
 }
 
 #[repr(C)]
@@ -134,7 +153,6 @@ impl MaterialTextures {
 pub struct MaterialData {
     pub material_properties: MaterialProperties,
     pub material_textures: MaterialTextures,
-    pub sampler_index: u32,
 }
 
 #[repr(C)]
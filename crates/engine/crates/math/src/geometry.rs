@@ -0,0 +1,277 @@
+use general::{Mat4, Vec3, Vec4};
+
+/// Plane in the `normal.dot(point) + distance = 0` form, normal points to the inner side.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Plane {
+    pub normal: Vec3,
+    pub distance: f32,
+}
+
+impl Plane {
+    #[inline(always)]
+    pub fn new(normal: Vec3, distance: f32) -> Self {
+        Self { normal, distance }
+    }
+
+    pub fn from_point_normal(point: Vec3, normal: Vec3) -> Self {
+        let normal = normal.normalize_or_zero();
+
+        Self {
+            normal,
+            distance: -normal.dot(point),
+        }
+    }
+
+    /// `None` if the coefficients describe no plane, e.g. far plane of an infinite projection.
+    pub fn from_coefficients(coefficients: Vec4) -> Option<Self> {
+        let normal = coefficients.truncate();
+        let length = normal.length();
+        if length <= f32::EPSILON {
+            return None;
+        }
+
+        Some(Self {
+            normal: normal / length,
+            distance: coefficients.w / length,
+        })
+    }
+
+    #[inline(always)]
+    pub fn signed_distance(&self, point: Vec3) -> f32 {
+        self.normal.dot(point) + self.distance
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize_or_zero(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_point(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    /// Distance along the ray to the intersection, if there is one in front of the origin.
+    pub fn intersect_plane(&self, plane: &Plane) -> Option<f32> {
+        let denominator = plane.normal.dot(self.direction);
+        if denominator.abs() <= f32::EPSILON {
+            return None;
+        }
+
+        let distance = -plane.signed_distance(self.origin) / denominator;
+
+        (distance >= 0.0).then_some(distance)
+    }
+
+    pub fn intersect_sphere(&self, sphere: &Sphere) -> Option<f32> {
+        let to_origin = self.origin - sphere.center;
+        let b = to_origin.dot(self.direction);
+        let c = to_origin.length_squared() - sphere.radius * sphere.radius;
+
+        let discriminant = b * b - c;
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let discriminant_sqrt = discriminant.sqrt();
+        let near_distance = -b - discriminant_sqrt;
+        if near_distance >= 0.0 {
+            return Some(near_distance);
+        }
+
+        // NOTE: Origin is inside of the sphere.
+        let far_distance = -b + discriminant_sqrt;
+
+        (far_distance >= 0.0).then_some(0.0)
+    }
+
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let inverse_direction = self.direction.recip();
+
+        let t0 = (aabb.min - self.origin) * inverse_direction;
+        let t1 = (aabb.max - self.origin) * inverse_direction;
+
+        let t_enter = t0.min(t1).max_element().max(0.0);
+        let t_exit = t0.max(t1).min_element();
+
+        (t_enter <= t_exit).then_some(t_enter)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    #[inline(always)]
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_center_half_extents(center: Vec3, half_extents: Vec3) -> Self {
+        Self {
+            min: center - half_extents,
+            max: center + half_extents,
+        }
+    }
+
+    /// `None` for an empty iterator.
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first_point = points.next()?;
+
+        Some(points.fold(Self::new(first_point, first_point), |aabb, point| {
+            Self::new(aabb.min.min(point), aabb.max.max(point))
+        }))
+    }
+
+    #[inline(always)]
+    pub fn get_center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    #[inline(always)]
+    pub fn get_half_extents(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    pub fn merge(&self, other: &Aabb) -> Aabb {
+        Self::new(self.min.min(other.min), self.max.max(other.max))
+    }
+
+    /// Bounds of the box after the transformation, they're not tight for rotations.
+    pub fn transform(&self, matrix: &Mat4) -> Aabb {
+        let center = matrix.transform_point3(self.get_center());
+        let half_extents = self.get_half_extents();
+
+        let absolute_matrix = general::Mat3::from_cols(
+            matrix.x_axis.truncate().abs(),
+            matrix.y_axis.truncate().abs(),
+            matrix.z_axis.truncate().abs(),
+        );
+
+        Self::from_center_half_extents(center, absolute_matrix * half_extents)
+    }
+
+    #[inline(always)]
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
+    #[inline(always)]
+    pub fn intersects_aabb(&self, other: &Aabb) -> bool {
+        self.min.cmple(other.max).all() && self.max.cmpge(other.min).all()
+    }
+
+    pub fn intersects_sphere(&self, sphere: &Sphere) -> bool {
+        let closest_point = sphere.center.clamp(self.min, self.max);
+
+        closest_point.distance_squared(sphere.center) <= sphere.radius * sphere.radius
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Sphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl Sphere {
+    #[inline(always)]
+    pub fn new(center: Vec3, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    pub fn from_aabb(aabb: &Aabb) -> Self {
+        Self {
+            center: aabb.get_center(),
+            radius: aabb.get_half_extents().length(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.center.distance_squared(point) <= self.radius * self.radius
+    }
+
+    #[inline(always)]
+    pub fn intersects_sphere(&self, other: &Sphere) -> bool {
+        let radius_sum = self.radius + other.radius;
+
+        self.center.distance_squared(other.center) <= radius_sum * radius_sum
+    }
+}
+
+/// Planes are extracted for Vulkan clip space (depth in `0..1`), reversed depth works as well.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Frustum {
+    pub planes: [Plane; 6],
+    pub planes_count: usize,
+}
+
+impl Frustum {
+    pub fn from_view_projection(view_projection: &Mat4) -> Self {
+        let row_x = view_projection.row(0);
+        let row_y = view_projection.row(1);
+        let row_z = view_projection.row(2);
+        let row_w = view_projection.row(3);
+
+        let mut frustum = Self::default();
+        [
+            row_w + row_x,
+            row_w - row_x,
+            row_w + row_y,
+            row_w - row_y,
+            row_z,
+            row_w - row_z,
+        ]
+        .into_iter()
+        .filter_map(Plane::from_coefficients)
+        .for_each(|plane| {
+            frustum.planes[frustum.planes_count] = plane;
+            frustum.planes_count += 1;
+        });
+
+        frustum
+    }
+
+    #[inline(always)]
+    pub fn get_planes(&self) -> &[Plane] {
+        &self.planes[..self.planes_count]
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.get_planes()
+            .iter()
+            .all(|plane| plane.signed_distance(point) >= 0.0)
+    }
+
+    pub fn intersects_sphere(&self, sphere: &Sphere) -> bool {
+        self.get_planes()
+            .iter()
+            .all(|plane| plane.signed_distance(sphere.center) >= -sphere.radius)
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        let center = aabb.get_center();
+        let half_extents = aabb.get_half_extents();
+
+        self.get_planes().iter().all(|plane| {
+            let projected_radius = half_extents.dot(plane.normal.abs());
+
+            plane.signed_distance(center) >= -projected_radius
+        })
+    }
+}
@@ -1,5 +1,9 @@
+pub mod geometry;
+pub mod procedural;
 pub mod random;
 
+pub use geometry::*;
+pub use procedural::*;
 pub use random::*;
 
 pub use general::*;
@@ -0,0 +1,91 @@
+use std::ops::{Add, Mul, Sub};
+
+use general::Quat;
+
+/// One dimensional Perlin noise, it's in the range from -1 to 1 and zero at the integers.
+/// Different seeds give uncorrelated curves, e.g. for the axes of a shake.
+pub fn gradient_noise(x: f32, seed: u32) -> f32 {
+    let cell = x.floor();
+    let fraction = x - cell;
+    let cell = cell as i32;
+
+    let left = get_lattice_gradient(cell, seed) * fraction;
+    let right = get_lattice_gradient(cell.wrapping_add(1), seed) * (fraction - 1.0);
+    // NOTE: Quintic fade keeps the second derivative continuous, so the curve has no kinks.
+    let fade = fraction * fraction * fraction * (fraction * (fraction * 6.0 - 15.0) + 10.0);
+
+    // NOTE: Gradients are in the range from -1 to 1, so the raw value doesn't exceed a half.
+    (left + (right - left) * fade) * 2.0
+}
+
+#[inline(always)]
+fn get_lattice_gradient(cell: i32, seed: u32) -> f32 {
+    let mut hash = (cell as u32) ^ seed.wrapping_mul(0x9E37_79B9);
+    hash = (hash ^ (hash >> 16)).wrapping_mul(0x7FEB_352D);
+    hash = (hash ^ (hash >> 15)).wrapping_mul(0x846C_A68B);
+    hash ^= hash >> 16;
+
+    hash as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+/// Frame rate independent exponential approach of the target, `sharpness` is per second.
+#[inline(always)]
+pub fn damp<T>(current: T, target: T, sharpness: f32, delta_time: f32) -> T
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
+{
+    current + (target - current) * (1.0 - (-sharpness * delta_time).exp())
+}
+
+/// Same as `damp`, but the rotation goes the shortest way.
+#[inline(always)]
+pub fn damp_rotation(current: Quat, target: Quat, sharpness: f32, delta_time: f32) -> Quat {
+    current.slerp(target, 1.0 - (-sharpness * delta_time).exp())
+}
+
+/// Critically damped spring, it follows a moving target without an overshoot.
+/// Velocity is kept between the updates, so the motion stays smooth, when the target jumps.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct SpringDamper<T> {
+    /// Seconds to reach the target approximately.
+    pub smooth_time: f32,
+    velocity: T,
+}
+
+impl<T> SpringDamper<T>
+where
+    T: Default + Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
+{
+    const MIN_SMOOTH_TIME: f32 = 1e-4;
+
+    pub fn new(smooth_time: f32) -> Self {
+        Self {
+            smooth_time,
+            velocity: Default::default(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_velocity(&self) -> T {
+        self.velocity
+    }
+
+    /// Velocity is dropped, e.g. after a teleport.
+    #[inline(always)]
+    pub fn reset(&mut self) {
+        self.velocity = Default::default();
+    }
+
+    /// Returns the next value, the exponent is approximated as in "Game Programming Gems 4".
+    pub fn update(&mut self, current: T, target: T, delta_time: f32) -> T {
+        let omega = 2.0 / self.smooth_time.max(Self::MIN_SMOOTH_TIME);
+        let x = omega * delta_time;
+        let exponent = 1.0 / (1.0 + x + 0.48 * x * x + 0.235 * x * x * x);
+
+        let change = current - target;
+        let temporary = (self.velocity + change * omega) * delta_time;
+        self.velocity = (self.velocity - temporary * omega) * exponent;
+
+        target + (change + temporary) * exponent
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
@@ -10,25 +11,160 @@ use rand::distr::StandardUniform;
 use rand::distr::uniform::*;
 use rand_xoshiro::Xoshiro256PlusPlus;
 
+/// Snapshot of `Random` and all of its streams, that can be stored and restored later.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RandomState {
+    pub seed: u64,
+    pub state: u64,
+    /// Values drawn since the generator was seeded from `state`.
+    pub words_count: u64,
+    pub streams: Vec<(String, RandomState)>,
+}
+
+impl RandomState {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write_bytes(&mut bytes);
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut cursor = bytes;
+        let random_state = Self::read_bytes(&mut cursor)?;
+
+        cursor.is_empty().then_some(random_state)
+    }
+
+    fn write_bytes(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&self.state.to_le_bytes());
+        bytes.extend_from_slice(&self.words_count.to_le_bytes());
+        bytes.extend_from_slice(&(self.streams.len() as u64).to_le_bytes());
+
+        for (name, stream_state) in self.streams.iter() {
+            bytes.extend_from_slice(&(name.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(name.as_bytes());
+            stream_state.write_bytes(bytes);
+        }
+    }
+
+    fn read_bytes(cursor: &mut &[u8]) -> Option<Self> {
+        let seed = read_u64(cursor)?;
+        let state = read_u64(cursor)?;
+        let words_count = read_u64(cursor)?;
+        let streams_count = read_u64(cursor)? as usize;
+
+        let mut streams = Vec::with_capacity(streams_count.min(cursor.len()));
+        for _ in 0..streams_count {
+            let name_length = read_u64(cursor)? as usize;
+            let name = String::from_utf8(cursor.get(..name_length)?.to_vec()).ok()?;
+            *cursor = &cursor[name_length..];
+
+            streams.push((name, Self::read_bytes(cursor)?));
+        }
+
+        Some(Self {
+            seed,
+            state,
+            words_count,
+            streams,
+        })
+    }
+}
+
+/// Xoshiro doesn't expose its state, so it's kept as the value the generator was seeded from
+/// and the count of values drawn since then. Generator is reseeded from its next value every
+/// `MAX_REPLAYED_WORDS_COUNT` values, so restoring a state replays a bounded count of them.
 #[derive(Resource, Component)]
 pub struct Random {
+    seed: u64,
     xoshiro256_plus_plus: Xoshiro256PlusPlus,
+    state: u64,
+    words_count: u64,
+    streams: HashMap<String, Random>,
 }
 
 impl Random {
+    const MAX_REPLAYED_WORDS_COUNT: u64 = 1 << 16;
+
     #[inline(always)]
     pub fn new() -> Self {
         Default::default()
     }
 
     #[inline(always)]
-    pub fn set_seed(&mut self, state: u64) {
-        self.xoshiro256_plus_plus = Xoshiro256PlusPlus::seed_from_u64(state);
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            xoshiro256_plus_plus: Xoshiro256PlusPlus::seed_from_u64(seed),
+            state: seed,
+            words_count: Default::default(),
+            streams: HashMap::new(),
+        }
+    }
+
+    /// Reseeds the generator, streams are reseeded as well, since they're derived from the seed.
+    #[inline(always)]
+    pub fn set_seed(&mut self, seed: u64) {
+        *self = Self::with_seed(seed);
+    }
+
+    #[inline(always)]
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Independent generator, that depends only on the seed and the name of the stream,
+    /// so the order of draws from other streams doesn't affect it.
+    pub fn stream(&mut self, name: &str) -> &mut Random {
+        let seed = self.seed;
+
+        self.streams
+            .entry(name.to_owned())
+            .or_insert_with(|| Random::with_seed(get_stream_seed(seed, name)))
+    }
+
+    /// Generator isn't affected, so saving doesn't change the values drawn after it.
+    pub fn save_state(&self) -> RandomState {
+        let mut streams = self
+            .streams
+            .iter()
+            .map(|(name, stream)| (name.clone(), stream.save_state()))
+            .collect::<Vec<_>>();
+        streams.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        RandomState {
+            seed: self.seed,
+            state: self.state,
+            words_count: self.words_count,
+            streams,
+        }
+    }
+
+    pub fn load_state(&mut self, random_state: &RandomState) {
+        self.seed = random_state.seed;
+        self.state = random_state.state;
+        self.words_count = random_state.words_count;
+        self.xoshiro256_plus_plus = Xoshiro256PlusPlus::seed_from_u64(random_state.state);
+        for _ in 0..random_state.words_count {
+            self.xoshiro256_plus_plus.random::<u64>();
+        }
+        self.streams = random_state
+            .streams
+            .iter()
+            .map(|(name, stream_state)| {
+                let mut stream = Random::with_seed(stream_state.seed);
+                stream.load_state(stream_state);
+
+                (name.clone(), stream)
+            })
+            .collect();
     }
 
     #[inline(always)]
     pub fn range<T: SampleUniform, R: SampleRange<T>>(&mut self, range: R) -> T {
-        self.xoshiro256_plus_plus.random_range(range)
+        self.draw(|xoshiro256_plus_plus| xoshiro256_plus_plus.random_range(range))
     }
 
     #[inline(always)]
@@ -36,12 +172,32 @@ impl Random {
     where
         StandardUniform: rand::distr::Distribution<T>,
     {
-        self.xoshiro256_plus_plus.random()
+        self.draw(|xoshiro256_plus_plus| xoshiro256_plus_plus.random())
     }
 
     #[inline(always)]
     pub fn random_bool(&mut self, propability: f32) -> bool {
-        self.xoshiro256_plus_plus.random_bool(propability as _)
+        self.draw(|xoshiro256_plus_plus| xoshiro256_plus_plus.random_bool(propability as _))
+    }
+
+    /// Counts values the draw took, sampling may reject some of them, so their count varies.
+    #[inline(always)]
+    fn draw<T>(&mut self, draw: impl FnOnce(&mut Xoshiro256PlusPlus) -> T) -> T {
+        let mut previous_xoshiro256_plus_plus = self.xoshiro256_plus_plus.clone();
+        let value = draw(&mut self.xoshiro256_plus_plus);
+        while previous_xoshiro256_plus_plus != self.xoshiro256_plus_plus {
+            previous_xoshiro256_plus_plus.random::<u64>();
+            self.words_count += 1;
+        }
+
+        // NOTE: Reseeding happens at the same values of the sequence, so it stays deterministic.
+        if self.words_count >= Self::MAX_REPLAYED_WORDS_COUNT {
+            self.state = self.xoshiro256_plus_plus.random::<u64>();
+            self.xoshiro256_plus_plus = Xoshiro256PlusPlus::seed_from_u64(self.state);
+            self.words_count = Default::default();
+        }
+
+        value
     }
 
     #[inline(always)]
@@ -66,9 +222,7 @@ impl Random {
 impl Default for Random {
     #[inline(always)]
     fn default() -> Self {
-        Self {
-            xoshiro256_plus_plus: get_new_xoshiro256_plus_plus_instance(),
-        }
+        Self::with_seed(get_time_seed())
     }
 }
 
@@ -84,31 +238,50 @@ impl ThreadedRandom {
     }
 
     #[inline(always)]
-    pub fn spawn_random(&mut self) -> Random {
-        let child_xoshiro256_plus_plus = self.master_xoshiro256_plus_plus.clone();
-        self.master_xoshiro256_plus_plus.jump();
-
-        Random {
-            xoshiro256_plus_plus: child_xoshiro256_plus_plus,
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            master_xoshiro256_plus_plus: Xoshiro256PlusPlus::seed_from_u64(seed),
         }
     }
+
+    /// Seed of every child is drawn from the master generator, so children differ in their streams
+    /// as well, and the same seed spawns the same children in the same order.
+    #[inline(always)]
+    pub fn spawn_random(&mut self) -> Random {
+        Random::with_seed(self.master_xoshiro256_plus_plus.random())
+    }
 }
 
 impl Default for ThreadedRandom {
     #[inline(always)]
     fn default() -> Self {
-        Self {
-            master_xoshiro256_plus_plus: get_new_xoshiro256_plus_plus_instance(),
-        }
+        Self::with_seed(get_time_seed())
     }
 }
 
 #[inline(always)]
-fn get_new_xoshiro256_plus_plus_instance() -> Xoshiro256PlusPlus {
-    let state = SystemTime::now()
+fn get_time_seed() -> u64 {
+    SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
-        .as_nanos() as u64;
+        .as_nanos() as u64
+}
+
+// NOTE: FNV-1a, std hashers aren't guaranteed to be stable between runs and versions.
+fn get_stream_seed(seed: u64, name: &str) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    let name_hash = name.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    });
+
+    seed ^ name_hash
+}
+
+fn read_u64(cursor: &mut &[u8]) -> Option<u64> {
+    let (bytes, rest) = cursor.split_first_chunk::<8>()?;
+    *cursor = rest;
 
-    Xoshiro256PlusPlus::seed_from_u64(state)
+    Some(u64::from_le_bytes(*bytes))
 }
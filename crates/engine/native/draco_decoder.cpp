@@ -0,0 +1,85 @@
+// C interface over the Draco decoder, glTF accessors are rebuilt from it on the Rust side.
+
+#include <cstddef>
+#include <cstdint>
+#include <memory>
+
+#include "draco/compression/decode.h"
+#include "draco/mesh/mesh.h"
+
+namespace {
+
+template <typename T>
+bool read_attribute_values(const draco::Mesh *mesh, const draco::PointAttribute *attribute,
+                           int8_t components_count, void *values) {
+    T *destination = static_cast<T *>(values);
+    for (draco::PointIndex point_index(0); point_index < mesh->num_points(); ++point_index) {
+        if (!attribute->ConvertValue<T>(attribute->mapped_index(point_index), components_count,
+                                        destination)) {
+            return false;
+        }
+        destination += components_count;
+    }
+
+    return true;
+}
+
+}  // namespace
+
+extern "C" {
+
+draco::Mesh *draco_decode_mesh(const uint8_t *data, size_t size) {
+    draco::DecoderBuffer decoder_buffer;
+    decoder_buffer.Init(reinterpret_cast<const char *>(data), size);
+
+    draco::Decoder decoder;
+    auto status_or_mesh = decoder.DecodeMeshFromBuffer(&decoder_buffer);
+    if (!status_or_mesh.ok()) {
+        return nullptr;
+    }
+
+    return std::move(status_or_mesh).value().release();
+}
+
+void draco_free_mesh(draco::Mesh *mesh) { delete mesh; }
+
+uint32_t draco_get_points_count(const draco::Mesh *mesh) { return mesh->num_points(); }
+
+uint32_t draco_get_faces_count(const draco::Mesh *mesh) { return mesh->num_faces(); }
+
+void draco_read_indices(const draco::Mesh *mesh, uint32_t *indices) {
+    for (draco::FaceIndex face_index(0); face_index < mesh->num_faces(); ++face_index) {
+        const draco::Mesh::Face &face = mesh->face(face_index);
+        for (const draco::PointIndex point_index : face) {
+            *indices++ = point_index.value();
+        }
+    }
+}
+
+// NOTE: Component types are glTF ones, Draco converts and dequantizes the values into them.
+bool draco_read_attribute(const draco::Mesh *mesh, uint32_t unique_id, uint32_t component_type,
+                          uint8_t components_count, void *values) {
+    const draco::PointAttribute *attribute = mesh->GetAttributeByUniqueId(unique_id);
+    if (attribute == nullptr) {
+        return false;
+    }
+
+    const int8_t count = static_cast<int8_t>(components_count);
+    switch (component_type) {
+        case 5120:
+            return read_attribute_values<int8_t>(mesh, attribute, count, values);
+        case 5121:
+            return read_attribute_values<uint8_t>(mesh, attribute, count, values);
+        case 5122:
+            return read_attribute_values<int16_t>(mesh, attribute, count, values);
+        case 5123:
+            return read_attribute_values<uint16_t>(mesh, attribute, count, values);
+        case 5125:
+            return read_attribute_values<uint32_t>(mesh, attribute, count, values);
+        case 5126:
+            return read_attribute_values<float>(mesh, attribute, count, values);
+        default:
+            return false;
+    }
+}
+}
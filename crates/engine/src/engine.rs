@@ -1,6 +1,12 @@
+// NOTE: Batch cooking is reachable only by the tool binaries, the runtime cooks models on demand.
+#[cfg_attr(not(feature = "tools"), allow(dead_code))]
+mod cook;
 mod ecs;
 mod events;
 mod general;
+#[cfg(feature = "tools")]
+mod golden_image;
+mod prefab;
 mod setup;
 mod utils;
 
@@ -9,14 +15,20 @@ use ecs::*;
 
 use bevy_ecs::{
     entity_disabling::Disabled,
-    schedule::{IntoScheduleConfigs, ScheduleLabel, Schedules},
+    resource::Resource,
+    schedule::{ExecutorKind, IntoScheduleConfigs, ScheduleLabel, Schedules},
     world::World,
 };
 use importer::Importer;
 use information::Information;
 use loader::Loader;
-use math::Random;
-use winit::{event::ElementState, keyboard::KeyCode, window::Window};
+use winit::{
+    dpi::{PhysicalSize, Size},
+    event::{ElementState, Ime},
+    keyboard::KeyCode,
+    monitor::{Fullscreen, VideoMode},
+    window::{ImeCapabilities, ImeEnableRequest, ImeRequest, ImeRequestData, Window},
+};
 
 use crate::{
     GamePlugin,
@@ -24,7 +36,12 @@ use crate::{
         ecs::{
             buffers_pool::BuffersPool,
             general::{
-                check_audio_state, physics_tick, propogate_disabled_to_new_children, update_time,
+                animate_materials, apply_audio_volumes, check_audio_state, dump_scene,
+                execute_console_commands, hot_reload_assets, notify_cvar_changes, physics_tick,
+                process_snapshot_requests, propogate_disabled_to_new_children, run_scripts,
+                stream_world_cells, update_camera_rigs, update_camera_shakes,
+                update_entity_inspector, update_impostors, update_replay, update_sun_occlusion,
+                update_terrain_chunks, update_time, update_time_of_day,
             },
             samplers_pool::SamplersPool,
             setup::{
@@ -39,20 +56,105 @@ use crate::{
 };
 
 pub use audio::*;
-pub use components::camera::{Camera, ClippingPlanes};
-pub use components::local_transform::LocalTransform;
-pub use components::mesh::Mesh;
+pub use components::billboard::{Billboard, BillboardMode};
+pub use components::camera::{
+    Camera, CameraViewport, ClearBehavior, ClippingPlanes, DepthOfField, ReflectionPlane,
+};
+pub use components::camera_rig::{CameraRig, CameraRigMode, OrbitRig};
+pub use components::camera_shake::CameraShake;
+pub use components::euler_tracker::EulerTracker;
+pub use components::foliage::FoliageScatter;
+pub use components::impostor::Impostor;
+pub use components::loaded_model::LoadedModel;
+pub use components::local_transform::{LocalTransform, TransformError};
+pub use components::material::MaterialProperties;
+pub use components::material_animation::{AnimateMaterial, MaterialCurve};
+pub use components::mesh::{Mesh, StaticInstance};
+pub use components::outline::{Highlighted, Selected};
+pub use components::persistent::Persistent;
+pub use components::point_light::PointLight;
+pub use components::script_vars::{ScriptValue, ScriptVars};
+pub use components::terrain::Terrain;
 pub use components::time::Time;
-pub use events::LoadModelEvent;
+pub use components::visibility_query::VisibilityQuery;
+pub use components::water::WaterSettings;
+pub use components::world_cell::WorldCell;
+#[cfg(feature = "tools")]
+pub use cook::{AssetCooker, CookReport};
+pub use events::{
+    BufferCreatedEvent, BufferDestroyedEvent, BufferMovedEvent, CVarChangedEvent,
+    ConsoleCommandEvent, DescriptorBufferResizedEvent, DescriptorIndexAssignedEvent,
+    LoadLensFlareTexturesEvent, LoadModelEvent, TextureCreatedEvent, TextureDestroyedEvent,
+};
+#[cfg(feature = "tools")]
+pub use golden_image::{
+    GoldenImageError, ImageComparison, ImageTolerance, UPDATE_GOLDEN_IMAGES_VARIABLE,
+    assert_golden_image, compare_images,
+};
 pub use math;
+pub use math::{Random, RandomState};
 pub use physics::{Collider, RigidBody};
+pub use prefab::{
+    Prefab, TransformOverride, spawn_prefab, spawn_prefab_batch, spawn_prefab_batch_with,
+};
 pub use queries::transform::*;
+pub use resources::AssetHotReload;
+pub use resources::Clipboard;
+pub use resources::CullingSettings;
+pub use resources::DebugOverlay;
+pub use resources::DefragmentationSettings;
+pub use resources::EngineConfig;
+pub use resources::EngineState;
+pub use resources::FrameCapture;
+pub use resources::FrameGraphCapture;
+pub use resources::Gizmos;
+pub use resources::ImportSettings;
 pub use resources::Input;
+pub use resources::LatencyStats;
+pub use resources::RenderStatistics;
+pub use resources::SceneDumpFormat;
+pub use resources::TextInput;
+pub use resources::TextureUsage;
+pub use resources::buffers_pool::UploadStatistics;
+pub use resources::texture_atlas::{TextureAtlas, TextureAtlasBuilder, TextureAtlasEntry, UvRect};
+pub use resources::textures_pool::TextureReference;
+pub use resources::{AtmosphericScattering, Fog, FogMode};
+pub use resources::{
+    AutoExposure, LensFlare, LensFlareSprite, LightShafts, PostEffects, PostProcessSettings,
+    ScreenSpaceReflections,
+};
+pub use resources::{CVar, CVarError, CVarValue, CVars};
+pub use resources::{ComponentRegistration, TypeRegistry};
+pub use resources::{Console, ConsoleCommandInfo};
+pub use resources::{CubemapCapture, CubemapCaptureRequest};
+pub use resources::{DepthPickResult, DepthPicking};
+pub use resources::{DynamicResolution, RenderScaleSettings};
+pub use resources::{
+    EntityInspector, InspectorComponent, InspectorComponentEdit, InspectorEdit, InspectorNode,
+    InspectorSelection,
+};
+pub use resources::{EnvironmentMode, EnvironmentSettings};
+pub use resources::{FrameAllocator, FrameArena, FrameVec};
+pub use resources::{FrameError, FrameErrors};
+pub use resources::{FrameTimeGraph, FrameTimeSample};
+pub use resources::{GpuPassTimes, GpuProfiler};
+pub use resources::{LatencyMode, WindowMode, WindowSettings};
+pub use resources::{MemoryAllocation, MemoryOverlay, MemoryPool, MemoryReport};
+pub use resources::{OutlineSettings, OutlineStyle};
+pub use resources::{Replay, ReplayFrame, ReplayInputEvent, ReplayMode, ReplayRecording};
+pub use resources::{ScriptError, ScriptHost};
+pub use resources::{SkyColors, TimeOfDay};
+pub use resources::{SnapshotError, Snapshots};
+pub use resources::{Task, TaskPool, TaskScope};
 pub use system_params::physics::*;
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, ScheduleLabel, Debug)]
 struct SchedulerWorldUpdate;
 
+/// Runs after the game logic, so engine systems see its changes of this frame.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ScheduleLabel, Debug)]
+struct SchedulerLateUpdate;
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash, ScheduleLabel, Debug)]
 struct SchedulerRendererSetup;
 
@@ -73,23 +175,85 @@ pub struct Engine {
 }
 
 impl Engine {
-    pub fn new(window: &dyn Window) -> Self {
+    pub fn new(window: &dyn Window, engine_config: EngineConfig) -> Self {
+        Self::create(Some(window), window.surface_size(), engine_config)
+    }
+
+    /// Renders into the draw texture without the surface, neither a window nor a display is needed.
+    /// Swapchain image isn't acquired and presented, frames are read through the frame capture.
+    pub fn new_headless(surface_size: PhysicalSize<u32>, engine_config: EngineConfig) -> Self {
+        Self::create(None, surface_size, engine_config)
+    }
+
+    fn create(
+        window: Option<&dyn Window>,
+        surface_size: PhysicalSize<u32>,
+        engine_config: EngineConfig,
+    ) -> Self {
         let mut world: World = World::new();
         world.register_disabling_component::<Disabled>();
 
-        let vulkan_context_resource = Self::create_vulkan_context(window);
+        let (vulkan_context_resource, device_capabilities_resource) =
+            Self::create_vulkan_context(window, surface_size, &engine_config);
         world.insert_resource(vulkan_context_resource);
+        world.insert_resource(device_capabilities_resource);
 
         let device_properties_resource = Self::create_device_properties(&world);
         world.insert_resource(device_properties_resource);
 
-        let render_context = Self::create_renderer_context(window, &world);
+        let render_context =
+            Self::create_renderer_context(surface_size, &world, engine_config.frame_overlap);
+        let gpu_profiler = GpuProfiler::new(
+            &world.resource::<VulkanContextResource>().device,
+            render_context.frame_overlap,
+            world
+                .resource::<DevicePropertiesResource>()
+                .timestamp_period,
+        );
         world.insert_resource(render_context);
+        world.insert_resource(gpu_profiler);
 
         Self::prepare_renderer_resources(&mut world);
 
         let frame_context = FrameContext::default();
         world.insert_resource(frame_context);
+        world.init_resource::<RenderStatistics>();
+        world.init_resource::<LatencyStats>();
+        world.init_resource::<FrameArena>();
+        world.insert_resource(FrameErrors::new(engine_config.panic_on_frame_errors));
+        world.init_resource::<DeletionQueue>();
+        world.init_resource::<TransientResourcesPool>();
+        let mut render_scale_settings = RenderScaleSettings::default();
+        render_scale_settings.set_render_scale(engine_config.render_scale);
+        // NOTE: Draw textures aren't created yet, so there is nothing to recreate.
+        _ = render_scale_settings.take_is_dirty();
+        world.insert_resource(render_scale_settings);
+        world.init_resource::<EnvironmentSettings>();
+        world.init_resource::<PostProcessSettings>();
+        world.init_resource::<CullingSettings>();
+        world.init_resource::<TimeOfDay>();
+        world.init_resource::<DefragmentationSettings>();
+        world.init_resource::<ImportSettings>();
+        world.init_resource::<Snapshots>();
+        let mut type_registry = TypeRegistry::default();
+        type_registry.register::<ScriptVars>("ScriptVars");
+        world.insert_resource(type_registry);
+        world.init_resource::<ScriptHost>();
+        world.init_resource::<FrameGraphCapture>();
+        world.init_resource::<FrameTimeGraph>();
+        world.init_resource::<MemoryOverlay>();
+        world.init_resource::<TextureUsage>();
+        world.init_resource::<CubemapCapture>();
+        world.init_resource::<FrameCapture>();
+        world.init_resource::<DepthPicking>();
+        world.init_resource::<DebugOverlay>();
+        world.init_resource::<Gizmos>();
+        world.init_resource::<OutlineSettings>();
+        world.init_resource::<EntityInspector>();
+        world.init_resource::<Console>();
+        let mut asset_hot_reload = AssetHotReload::default();
+        asset_hot_reload.is_enabled = engine_config.hot_reload;
+        world.insert_resource(asset_hot_reload);
 
         world.init_resource::<Schedules>();
         world.insert_resource(Information::new());
@@ -111,19 +275,60 @@ impl Engine {
                 .chain(),
         );
 
+        // NOTE: Systems without an ordering run in parallel, the executor still serializes conflicting
+        // accesses of resources and components, so the order is declared only where it matters.
         let scheduler_world_update = schedulers.entry(SchedulerWorldUpdate);
+        scheduler_world_update.set_executor_kind(ExecutorKind::MultiThreaded);
+        scheduler_world_update.add_systems(
+            (
+                update_time::update_time_system,
+                update_replay::update_replay_system,
+            )
+                .chain(),
+        );
         scheduler_world_update.add_systems(
             (
+                execute_console_commands::execute_console_commands_system,
+                notify_cvar_changes::notify_cvar_changes_system,
+            )
+                .chain(),
+        );
+        scheduler_world_update.add_systems(
+            (
+                process_snapshot_requests::process_snapshot_requests_system,
                 propogate_disabled_to_new_children::propagate_disabled_to_new_children_system,
                 physics_tick::physics_tick_system,
                 physics_tick::physics_update_global_transforms,
                 physics_tick::physics_update_local_transforms,
+                #[cfg(feature = "debug-tools")]
+                (
+                    update_entity_inspector::update_entity_inspector_system,
+                    update_entity_inspector::update_inspector_components_system,
+                ),
+                run_scripts::run_scripts_system,
+                update_time_of_day::update_time_of_day_system,
+                update_camera_shakes::update_camera_shakes_system,
+                update_sun_occlusion::update_sun_occlusion_system,
                 propogate_transforms_system,
+                (
+                    (
+                        stream_world_cells::stream_world_cells_system,
+                        hot_reload_assets::hot_reload_assets_system,
+                    )
+                        .chain(),
+                    update_terrain_chunks::update_terrain_chunks_system,
+                    update_impostors::update_impostors_system,
+                ),
             )
-                .chain(),
+                .chain()
+                .after(update_replay::update_replay_system),
         );
-
-        scheduler_world_update.add_systems(update_time::update_time_system);
+        let animate_materials_system =
+            animate_materials::animate_materials_system.after(update_replay::update_replay_system);
+        #[cfg(feature = "debug-tools")]
+        let animate_materials_system =
+            animate_materials_system.after(update_entity_inspector::update_entity_inspector_system);
+        scheduler_world_update.add_systems(animate_materials_system);
 
         let scheduler_renderer_setup = schedulers.entry(SchedulerRendererSetup);
         scheduler_renderer_setup.add_systems(
@@ -135,43 +340,109 @@ impl Engine {
                 .chain(),
         );
 
+        // NOTE: Present is pinned to the main thread, see `present_system`.
         let scheduler_renderer_update = schedulers.entry(SchedulerRendererUpdate);
+        scheduler_renderer_update.set_executor_kind(ExecutorKind::MultiThreaded);
+        scheduler_renderer_update.add_systems((
+            apply_audio_volumes::apply_audio_volumes_system,
+            check_audio_state::check_audio_state_system,
+        ));
         scheduler_renderer_update.add_systems(
             (
-                check_audio_state::check_audio_state_system,
+                defragment_memory::defragment_memory_system,
+                retire_descriptor_buffers::retire_descriptor_buffers_system,
+                #[cfg(feature = "capture")]
+                update_cubemap_capture::update_cubemap_capture_system,
+                #[cfg(feature = "capture")]
+                update_frame_capture::update_frame_capture_system,
+                notify_gpu_resource_events::notify_gpu_resource_events_system,
                 prepare_frame::prepare_frame_system,
-                collect_instance_objects::collect_instance_objects_system,
-                update_resources::update_resources_system,
-                begin_rendering::begin_rendering_system,
-                render_meshes::render_meshes_system,
-                end_rendering::end_rendering_system,
-                present::present_system,
+                (
+                    release_resources::release_resources_system,
+                    (
+                        collect_instance_objects::collect_instance_objects_system,
+                        update_render_scale::update_render_scale_system,
+                        #[cfg(feature = "profiler")]
+                        update_frame_time_graph::update_frame_time_graph_system,
+                        #[cfg(feature = "debug-tools")]
+                        update_memory_overlay::update_memory_overlay_system,
+                        #[cfg(feature = "debug-tools")]
+                        update_gizmos::update_gizmos_system,
+                    ),
+                    update_visibility_queries::update_visibility_queries_system,
+                    update_depth_picks::update_depth_picks_system,
+                    update_texture_usage::update_texture_usage_system,
+                    update_depth_pyramid::update_depth_pyramid_system,
+                    acquire_transient_resources::acquire_transient_resources_system,
+                    update_resources::update_resources_system,
+                    begin_rendering::begin_rendering_system,
+                    render_meshes::render_meshes_system,
+                    end_rendering::end_rendering_system,
+                    present::present_system,
+                )
+                    .chain()
+                    .distributive_run_if(prepare_frame::is_swapchain_image_acquired),
+            )
+                .chain(),
+        );
+
+        schedulers.entry(SchedulerLateUpdate).add_systems(
+            (
+                propogate_transforms_system,
+                update_camera_rigs::update_camera_rigs_system,
             )
                 .chain(),
         );
 
         schedulers.entry(SchedulerGameInit);
-        schedulers.entry(SchedulerGameUpdate);
+        schedulers
+            .entry(SchedulerGameUpdate)
+            .set_executor_kind(ExecutorKind::MultiThreaded);
 
         world.add_observer(on_load_model::on_load_model_system);
+        world.add_observer(on_load_terrain::on_load_terrain_system);
+        world.add_observer(on_load_water_normal_map::on_load_water_normal_map_system);
+        world.add_observer(on_load_foliage_maps::on_load_foliage_maps_system);
+        world.add_observer(on_load_lens_flare_textures::on_load_lens_flare_textures_system);
+        world.add_observer(on_bake_impostor::on_bake_impostor_system);
+        world.add_observer(on_console_command::on_console_command_system);
+        world.add_observer(on_cvar_changed::on_cvar_changed_system);
+        world.add_observer(on_script_events::on_console_command_script_system);
+        world.add_observer(on_script_events::on_cvar_changed_script_system);
         world.add_observer(on_spawn_model::on_spawn_mesh_system);
 
         world.insert_resource(Time::new());
         world.insert_resource(Input::new());
+        world.init_resource::<TextInput>();
+        world.insert_non_send_resource(Clipboard::new());
+        world.init_resource::<Replay>();
+        world.init_resource::<EngineState>();
+        world.insert_resource(WindowSettings::new(
+            (surface_size.width, surface_size.height),
+            engine_config.vsync,
+            engine_config.get_latency_mode(),
+        ));
+        let mut cvars = CVars::default();
+        cvars.register_engine_cvars(
+            world.resource::<RenderScaleSettings>(),
+            world.resource::<PostProcessSettings>(),
+            world.resource::<WindowSettings>(),
+            world.resource::<CullingSettings>(),
+            world.resource::<DefragmentationSettings>(),
+            world.resource::<Gizmos>(),
+        );
+        // NOTE: Persisted values reach the settings through `CVarChangedEvent` in the first update.
+        cvars.apply_persisted(&engine_config.cvars);
+        world.insert_resource(cvars);
+        world.insert_resource(engine_config);
         world.insert_resource(Random::new());
+        world.insert_resource(TaskPool::new());
         world.insert_resource(physics::PhysicsManager::new());
 
         world.run_schedule(SchedulerEngineStartup);
         world.run_schedule(SchedulerRendererSetup);
         world.flush();
 
-        // TODO: In future, we need to fix this. Awful code.
-        let mut exe_path = std::env::current_exe().unwrap();
-
-        exe_path.pop();
-        exe_path.pop();
-        exe_path.pop();
-
         Self { world }
     }
 
@@ -186,18 +457,233 @@ impl Engine {
 
     #[inline(always)]
     pub fn update(&mut self) {
+        self.world.resource_mut::<LatencyStats>().begin_frame();
+        self.world.resource_mut::<FrameArena>().reset();
+
         self.world.run_schedule(SchedulerWorldUpdate);
         self.world.run_schedule(SchedulerGameUpdate);
         self.world.flush();
+        self.world.run_schedule(SchedulerLateUpdate);
+
+        let mut render_scale_settings = unsafe {
+            self.world
+                .get_resource_mut::<RenderScaleSettings>()
+                .unwrap_unchecked()
+        };
+        if render_scale_settings.take_is_dirty() {
+            self.recreate_draw_textures();
+        }
+
+        let renderer_context = self.world.resource::<RendererContext>();
+        match renderer_context.swapchain_status {
+            SwapchainStatus::Valid => (),
+            SwapchainStatus::OutOfDate => {
+                let draw_extent = renderer_context.draw_extent;
+                let window_settings = self.world.resource::<WindowSettings>();
+                let vsync = window_settings.is_vsync_enabled();
+                let latency_mode = window_settings.get_latency_mode();
+                // NOTE: Surface reports its own extent, the draw extent matters only where it doesn't.
+                self.recreate_swapchain(
+                    PhysicalSize::new(draw_extent.width, draw_extent.height),
+                    vsync,
+                    latency_mode,
+                );
+            }
+            SwapchainStatus::SurfaceLost => {
+                *self.world.resource_mut::<EngineState>() = EngineState::Paused;
+            }
+        }
 
-        self.world.run_schedule(SchedulerRendererUpdate);
+        if *self.world.resource::<EngineState>() == EngineState::Running {
+            self.world.run_schedule(SchedulerRendererUpdate);
+
+            // NOTE: Abandoned frame may keep its swapchain image, the recreation releases it.
+            let frame_number = self.world.resource::<RendererContext>().frame_number;
+            if self
+                .world
+                .resource_mut::<FrameErrors>()
+                .resolve_frame(frame_number)
+            {
+                let mut renderer_context = self.world.resource_mut::<RendererContext>();
+                if renderer_context.swapchain_status == SwapchainStatus::Valid {
+                    renderer_context.swapchain_status = SwapchainStatus::OutOfDate;
+                }
+            }
+        }
 
         let mut input = unsafe { self.world.get_resource_mut::<Input>().unwrap_unchecked() };
         input.reset();
+        let mut text_input = unsafe {
+            self.world
+                .get_resource_mut::<TextInput>()
+                .unwrap_unchecked()
+        };
+        text_input.reset();
+
+        // NOTE: Sleep goes before the event loop, so the next frame gets the freshest input.
+        let latency_mode = self.world.resource::<WindowSettings>().get_latency_mode();
+        let pacing_duration = self
+            .world
+            .resource_mut::<LatencyStats>()
+            .end_frame(latency_mode);
+        if !pacing_duration.is_zero() {
+            std::thread::sleep(pacing_duration);
+        }
     }
 
     #[inline(always)]
-    pub fn process_input(&mut self, key_code: KeyCode, state: ElementState) {
+    /// Repeats reach only the text editing, toggles, `Input` and replay take the first press.
+    pub fn process_input(&mut self, key_code: KeyCode, state: ElementState, is_repeat: bool) {
+        let mut text_input = unsafe {
+            self.world
+                .get_resource_mut::<TextInput>()
+                .unwrap_unchecked()
+        };
+        text_input.process_key(key_code, state == ElementState::Pressed);
+        let is_shortcut_modifier_pressed = text_input.is_shortcut_modifier_pressed();
+
+        let mut console = unsafe { self.world.get_resource_mut::<Console>().unwrap_unchecked() };
+        if console.get_toggle_key() == Some(key_code) {
+            if state == ElementState::Pressed && !is_repeat {
+                console.toggle();
+            }
+
+            // NOTE: Keys held while the console was opened won't get their release events.
+            let mut input = unsafe { self.world.get_resource_mut::<Input>().unwrap_unchecked() };
+            input.release_all();
+
+            return;
+        }
+        // NOTE: Open console takes all the keys, text comes through `process_text`.
+        if console.is_open() {
+            if state == ElementState::Pressed {
+                match key_code {
+                    KeyCode::Enter | KeyCode::NumpadEnter => console.submit_input(),
+                    KeyCode::Backspace => console.erase_last_character(),
+                    KeyCode::ArrowUp => console.select_previous_history_line(),
+                    KeyCode::ArrowDown => console.select_next_history_line(),
+                    KeyCode::Escape => console.set_open(false),
+                    KeyCode::KeyV if is_shortcut_modifier_pressed => {
+                        let text = unsafe {
+                            self.world
+                                .get_non_send_resource_mut::<Clipboard>()
+                                .unwrap_unchecked()
+                        }
+                        .get_text();
+
+                        if let Some(text) = text {
+                            let mut console = unsafe {
+                                self.world.get_resource_mut::<Console>().unwrap_unchecked()
+                            };
+                            console.push_text(&text);
+                        }
+                    }
+                    KeyCode::KeyC if is_shortcut_modifier_pressed => {
+                        let line = console.get_input().to_string();
+
+                        let mut clipboard = unsafe {
+                            self.world
+                                .get_non_send_resource_mut::<Clipboard>()
+                                .unwrap_unchecked()
+                        };
+                        clipboard.set_text(line);
+                    }
+                    KeyCode::Tab => {
+                        let cvars =
+                            unsafe { self.world.get_resource::<CVars>().unwrap_unchecked() };
+                        let cvar_names: Vec<_> =
+                            cvars.iter().map(|(name, _)| name.to_string()).collect();
+
+                        let mut console =
+                            unsafe { self.world.get_resource_mut::<Console>().unwrap_unchecked() };
+                        console.autocomplete(cvar_names.iter().map(String::as_str));
+                    }
+                    _ => (),
+                }
+            }
+
+            return;
+        }
+        if is_repeat {
+            return;
+        }
+
+        let mut window_settings = unsafe {
+            self.world
+                .get_resource_mut::<WindowSettings>()
+                .unwrap_unchecked()
+        };
+        if window_settings.get_fullscreen_toggle_key() == Some(key_code) {
+            if state == ElementState::Pressed {
+                window_settings.toggle_fullscreen();
+            }
+
+            return;
+        }
+
+        let mut frame_graph_capture = unsafe {
+            self.world
+                .get_resource_mut::<FrameGraphCapture>()
+                .unwrap_unchecked()
+        };
+        if frame_graph_capture.get_dump_key() == Some(key_code) {
+            if state == ElementState::Pressed {
+                frame_graph_capture.request_dump(FrameGraphCapture::DEFAULT_PATH);
+            }
+
+            return;
+        }
+
+        let mut entity_inspector = unsafe {
+            self.world
+                .get_resource_mut::<EntityInspector>()
+                .unwrap_unchecked()
+        };
+        if entity_inspector.get_toggle_key() == Some(key_code) {
+            if state == ElementState::Pressed {
+                entity_inspector.toggle();
+            }
+
+            return;
+        }
+
+        let mut frame_time_graph = unsafe {
+            self.world
+                .get_resource_mut::<FrameTimeGraph>()
+                .unwrap_unchecked()
+        };
+        if frame_time_graph.get_toggle_key() == Some(key_code) {
+            if state == ElementState::Pressed {
+                frame_time_graph.toggle();
+            }
+
+            return;
+        }
+
+        let mut memory_overlay = unsafe {
+            self.world
+                .get_resource_mut::<MemoryOverlay>()
+                .unwrap_unchecked()
+        };
+        if memory_overlay.get_toggle_key() == Some(key_code) {
+            if state == ElementState::Pressed {
+                memory_overlay.toggle();
+            }
+
+            return;
+        }
+
+        let mut replay = unsafe { self.world.get_resource_mut::<Replay>().unwrap_unchecked() };
+        // NOTE: Played back input replaces the live one.
+        if replay.is_playing_back() {
+            return;
+        }
+        replay.record_input_event(if state == ElementState::Pressed {
+            ReplayInputEvent::KeyPressed(key_code)
+        } else {
+            ReplayInputEvent::KeyReleased(key_code)
+        });
+
         let mut input = unsafe { self.world.get_resource_mut::<Input>().unwrap_unchecked() };
         if state == ElementState::Pressed {
             input.press(key_code);
@@ -206,43 +692,237 @@ impl Engine {
         }
     }
 
+    /// Text typed with the last key press, it goes to the console input line while it's open
+    /// and to `TextInput` otherwise.
+    pub fn process_text(&mut self, text: &str) {
+        if self.world.resource_mut::<Console>().push_text(text) {
+            return;
+        }
+
+        self.world.resource_mut::<TextInput>().push_text(text);
+    }
+
+    /// Composition goes to the preedit of `TextInput`, committed text is typed like `process_text`.
+    pub fn process_ime(&mut self, ime: Ime) {
+        let mut text_input = self.world.resource_mut::<TextInput>();
+        match ime {
+            Ime::Preedit(preedit, preedit_cursor) => {
+                text_input.set_preedit(preedit, preedit_cursor)
+            }
+            Ime::Commit(text) => {
+                text_input.clear_preedit();
+                self.process_text(&text);
+            }
+            Ime::Disabled => text_input.clear_preedit(),
+            _ => (),
+        }
+    }
+
+    pub fn is_console_open(&self) -> bool {
+        self.world.resource::<Console>().is_open()
+    }
+
+    /// Writes passes, resources and barriers of the next frame as Graphviz DOT.
+    pub fn dump_frame_graph(&mut self, path: impl Into<std::path::PathBuf>) {
+        self.world
+            .resource_mut::<FrameGraphCapture>()
+            .request_dump(path);
+    }
+
+    /// Entities of the scene with their names, transforms and resource references.
+    pub fn dump_scene(&mut self, scene_dump_format: SceneDumpFormat) -> String {
+        dump_scene::dump_scene(&mut self.world, scene_dump_format)
+    }
+
+    pub fn resize(&mut self, surface_size: PhysicalSize<u32>) {
+        let mut window_settings = self.world.resource_mut::<WindowSettings>();
+        window_settings.sync_resolution(surface_size.width, surface_size.height);
+        let vsync = window_settings.is_vsync_enabled();
+        let latency_mode = window_settings.get_latency_mode();
+
+        self.recreate_swapchain(surface_size, vsync, latency_mode);
+    }
+
+    /// IME is enabled while the console is open or the game has an active text field.
+    pub fn apply_text_input(&mut self, window: &dyn Window) {
+        let is_console_open = self.world.resource::<Console>().is_open();
+        let mut text_input = self.world.resource_mut::<TextInput>();
+        let is_ime_enabled = is_console_open || text_input.is_active();
+        if !text_input.set_ime_enabled(is_ime_enabled) {
+            return;
+        }
+
+        let ime_request = if is_ime_enabled {
+            let Some(ime_enable_request) =
+                ImeEnableRequest::new(ImeCapabilities::new(), ImeRequestData::default())
+            else {
+                return;
+            };
+
+            ImeRequest::Enable(ime_enable_request)
+        } else {
+            ImeRequest::Disable
+        };
+        _ = window.request_ime_update(ime_request);
+    }
+
+    /// Winit calls stay out of the schedules, it's called from the event loop on the main thread.
+    pub fn apply_window_settings(&mut self, window: &dyn Window) {
+        let mut window_settings = self.world.resource_mut::<WindowSettings>();
+        if !window_settings.take_is_dirty() {
+            return;
+        }
+
+        let window_mode = window_settings.get_window_mode();
+        let (width, height) = window_settings.get_resolution();
+        let vsync = window_settings.is_vsync_enabled();
+        let latency_mode = window_settings.get_latency_mode();
+
+        match window_mode {
+            WindowMode::Windowed => {
+                window.set_fullscreen(None);
+                window.set_decorations(true);
+                _ = window.request_surface_size(Size::Physical(PhysicalSize::new(width, height)));
+            }
+            WindowMode::Borderless => {
+                window.set_fullscreen(Some(Fullscreen::Borderless(window.current_monitor())));
+            }
+            WindowMode::Fullscreen => {
+                let monitor = window.current_monitor();
+                let video_mode = monitor.as_ref().and_then(|monitor| {
+                    monitor
+                        .video_modes()
+                        .filter(|video_mode| {
+                            let size = video_mode.size();
+                            size.width == width && size.height == height
+                        })
+                        .max_by_key(VideoMode::refresh_rate_millihertz)
+                });
+
+                match (monitor, video_mode) {
+                    (Some(monitor), Some(video_mode)) => {
+                        window.set_fullscreen(Some(Fullscreen::Exclusive(monitor, video_mode)));
+                    }
+                    (monitor, _) => {
+                        println!(
+                            "No video mode {}x{} is available, falling back to borderless.",
+                            width, height
+                        );
+                        window.set_fullscreen(Some(Fullscreen::Borderless(monitor)));
+                    }
+                }
+            }
+        }
+
+        // NOTE: Mode switch fires resize event anyway, but vsync and latency changes don't.
+        self.recreate_swapchain(window.surface_size(), vsync, latency_mode);
+
+        // NOTE: Keys held while the window was switched won't get their release events.
+        let mut input = unsafe { self.world.get_resource_mut::<Input>().unwrap_unchecked() };
+        input.release_all();
+    }
+
     #[inline(always)]
     pub fn process_mouse(&mut self, mouse_delta: (f32, f32)) {
+        let mut replay = unsafe { self.world.get_resource_mut::<Replay>().unwrap_unchecked() };
+        if replay.is_playing_back() {
+            return;
+        }
+        replay.record_input_event(ReplayInputEvent::MouseMoved {
+            x: mouse_delta.0,
+            y: mouse_delta.1,
+        });
+
         let mut input = unsafe { self.world.get_resource_mut::<Input>().unwrap_unchecked() };
         input.set_mouse_delta(mouse_delta);
     }
-}
 
-impl Drop for Engine {
-    fn drop(&mut self) {
-        let mut vulkan_context_resource = self
-            .world
-            .remove_resource::<VulkanContextResource>()
-            .unwrap();
+    pub fn get_resource<R: Resource>(&self) -> Option<&R> {
+        self.world.get_resource::<R>()
+    }
+
+    /// Frame rendered after the `delay_frames_count` frames is captured, see `FrameCapture`.
+    pub fn request_frame_capture(&mut self, delay_frames_count: u32) {
+        self.world
+            .resource_mut::<FrameCapture>()
+            .request_capture(delay_frames_count);
+    }
+
+    /// It's available by the update after the captured frame.
+    pub fn take_captured_frame(&mut self) -> Option<image::RgbaImage> {
+        self.world
+            .resource_mut::<FrameCapture>()
+            .take_captured_frame()
+    }
+
+    pub fn get_upload_statistics(&self) -> UploadStatistics {
+        self.world.resource::<BuffersPool>().get_upload_statistics()
+    }
+
+    pub fn start_replay_recording(&mut self, path: impl Into<std::path::PathBuf>) {
+        self.world.resource_mut::<Replay>().start_recording(path);
+    }
+
+    pub fn start_replay_playback(&mut self, path: impl Into<std::path::PathBuf>) {
+        let mut replay = self.world.resource_mut::<Replay>();
+        if let Err(error) = replay.start_playback(path) {
+            println!("Failed to load replay: {}", error);
+        }
+    }
+
+    /// Waits for the GPU and destroys everything in dependency order, the window must still be alive.
+    /// Dropping the engine does the same, but drop order of the owner isn't always controlled.
+    pub fn shutdown(mut self) {
+        self.destroy();
+    }
+
+    fn destroy(&mut self) {
+        // NOTE: Engine was already shut down.
+        let Some(mut vulkan_context_resource) =
+            self.world.remove_resource::<VulkanContextResource>()
+        else {
+            return;
+        };
+
+        self.world.resource_mut::<Replay>().stop_recording();
+
         let render_context_resource = self.world.remove_resource::<RendererContext>().unwrap();
         let mut buffers_pool = self.world.remove_resource::<BuffersPool>().unwrap();
         let mut textures_pool = self.world.remove_resource::<TexturesPool>().unwrap();
         let mut samplers_pool = self.world.remove_resource::<SamplersPool>().unwrap();
         let renderer_resources = self.world.remove_resource::<RendererResources>().unwrap();
         let descriptor_set_handle = self.world.remove_resource::<DescriptorSetHandle>().unwrap();
+        let gpu_profiler = self.world.remove_resource::<GpuProfiler>().unwrap();
 
         let device = vulkan_context_resource.device;
 
         device.wait_idle().unwrap();
 
         unsafe {
-            buffers_pool.free_allocations();
-            textures_pool.free_allocations();
-            samplers_pool.destroy_samplers();
+            // NOTE: Shader objects are created with the descriptor set layouts, so they go first.
+            renderer_resources
+                .shader_objects
+                .iter()
+                .for_each(|shader_object| device.destroy_shader_ext(shader_object.shader));
             descriptor_set_handle.destroy();
 
+            samplers_pool.destroy_samplers();
+            textures_pool.free_allocations();
+            buffers_pool.free_allocations();
+            gpu_profiler.destroy(&device);
+
+            match vulkan_context_resource.allocator.calculate_statistics() {
+                Ok(total_statistics) => {
+                    let allocations_count = total_statistics.total.statistics.allocationCount;
+                    if allocations_count != 0 {
+                        println!("VMA allocations leaked on shutdown: {}", allocations_count);
+                    }
+                    debug_assert_eq!(allocations_count, 0, "VMA allocations leaked on shutdown");
+                }
+                Err(error) => println!("Failed to calculate VMA statistics: {:?}", error),
+            }
             vulkan_context_resource.allocator.drop();
 
-            device.destroy_shader_ext(renderer_resources.gradient_compute_shader_object.shader);
-            device.destroy_shader_ext(renderer_resources.mesh_shader_object.shader);
-            device.destroy_shader_ext(renderer_resources.task_shader_object.shader);
-            device.destroy_shader_ext(renderer_resources.fragment_shader_object.shader);
-
             device.destroy_command_pool(Some(
                 render_context_resource
                     .upload_context
@@ -259,33 +939,49 @@ impl Drop for Engine {
                 .for_each(|frame_data| {
                     device.destroy_command_pool(Some(frame_data.command_group.command_pool));
                     device.destroy_fence(Some(frame_data.command_group.fence));
-                    device.destroy_semaphore(Some(frame_data.render_semaphore));
                     device.destroy_semaphore(Some(frame_data.swapchain_semaphore));
+                    if let Some(async_compute_command_group) =
+                        frame_data.async_compute_command_group
+                    {
+                        device.destroy_command_pool(Some(async_compute_command_group.command_pool));
+                    }
+                });
+            render_context_resource
+                .render_semaphores
+                .iter()
+                .for_each(|render_semaphore| {
+                    device.destroy_semaphore(Some(*render_semaphore));
                 });
+            if let Some(async_compute_semaphore) = render_context_resource.async_compute_semaphore {
+                device.destroy_semaphore(Some(async_compute_semaphore));
+            }
 
             render_context_resource
                 .image_views
                 .iter()
                 .for_each(|image_view| {
-                    vulkan_context_resource
-                        .device
-                        .destroy_image_view(Some(*image_view));
+                    device.destroy_image_view(Some(*image_view));
                 });
 
+            device.destroy_swapchain_khr(vulkan_context_resource.swapchain);
+            device.destroy();
+
+            vulkan_context_resource
+                .instance
+                .destroy_surface_khr(vulkan_context_resource.surface);
+            // NOTE: Messenger goes last, so it still reports leaks of the device and the surface.
             if let Some(debug_utils_messenger) = vulkan_context_resource.debug_utils_messenger {
                 vulkan_context_resource
                     .instance
                     .destroy_debug_utils_messenger_ext(Some(debug_utils_messenger));
             }
-
-            vulkan_context_resource
-                .device
-                .destroy_swapchain_khr(Some(vulkan_context_resource.swapchain));
-            vulkan_context_resource.device.destroy();
-            vulkan_context_resource
-                .instance
-                .destroy_surface_khr(Some(vulkan_context_resource.surface));
             vulkan_context_resource.instance.destroy();
         }
     }
 }
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        self.destroy();
+    }
+}
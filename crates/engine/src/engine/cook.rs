@@ -0,0 +1,752 @@
+pub mod cooked_model;
+
+use std::{
+    collections::{HashMap, HashSet},
+    io::Cursor,
+    path::{Path, PathBuf},
+};
+
+use asset_importer::{Matrix4x4, node::Node};
+use image::{EncodableLayout, ImageReader};
+use ktx2_rw::Ktx2Texture;
+use math::{Mat4, Vec3, Vec4};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use vulkanite::vk::Format;
+use walkdir::WalkDir;
+
+use crate::engine::{
+    cook::cooked_model::{CookedMaterial, CookedModel, CookedModelWriter, build_mesh_lods},
+    ecs::{
+        mesh_buffers_pool::MeshStatistics,
+        model_loader::ModelLoader,
+        on_load_model::build_mesh_geometry,
+        textures_pool::{TextureMetadata, TexturesPool},
+    },
+    resources::{CullingSettings, EngineConfig, ImportSettings, Vertex},
+};
+
+fn get_fnv_hash(bytes: &[u8]) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Hash of the settings that change the cooked geometry, models of other settings are recooked.
+pub(crate) fn get_cook_settings_hash(import_settings: &ImportSettings, cone_weight: f32) -> u64 {
+    let values = [
+        import_settings.is_welding_enabled as u32,
+        import_settings.weld_position_epsilon.to_bits(),
+        import_settings.weld_normal_epsilon.to_bits(),
+        import_settings.weld_uv_epsilon.to_bits(),
+        import_settings.is_degenerate_removal_enabled as u32,
+        cone_weight.to_bits(),
+    ];
+
+    get_fnv_hash(bytemuck::cast_slice::<u32, u8>(&values))
+}
+
+/// Path of the source relative to the asset root with `/` separators, artifacts are keyed by it,
+/// so sources of the same name in other folders don't collide. Source outside of the asset root
+/// is keyed by its whole path.
+pub(crate) fn get_asset_key(asset_root: &Path, source_path: &Path) -> String {
+    let relative_path = source_path
+        .strip_prefix(asset_root)
+        .ok()
+        .map(Path::to_path_buf)
+        .or_else(|| {
+            let asset_root = std::fs::canonicalize(asset_root).ok()?;
+            let source_path = std::fs::canonicalize(source_path).ok()?;
+
+            source_path
+                .strip_prefix(asset_root)
+                .ok()
+                .map(Path::to_path_buf)
+        })
+        .unwrap_or_else(|| source_path.to_path_buf());
+
+    relative_path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// File stem keeps the artifact recognizable, the hash of the asset key makes it unique.
+fn get_artifact_name(asset_key: &str) -> String {
+    let file_stem = Path::new(asset_key)
+        .file_stem()
+        .and_then(|file_stem| file_stem.to_str())
+        .unwrap_or_default();
+
+    std::format!("{}_{:016x}", file_stem, get_fnv_hash(asset_key.as_bytes()))
+}
+
+pub(crate) fn get_cooked_model_path(intermediate_root: &Path, asset_key: &str) -> PathBuf {
+    intermediate_root
+        .join("models")
+        .join(std::format!("{}.model", get_artifact_name(asset_key)))
+}
+
+pub(crate) fn get_cooked_texture_path(
+    intermediate_root: &Path,
+    asset_key: &str,
+    texture_name: &str,
+) -> PathBuf {
+    intermediate_root
+        .join("textures")
+        .join(get_artifact_name(asset_key))
+        .join(texture_name)
+}
+
+/// Name of the embedded texture, as it's used for its artifact.
+fn get_texture_name(
+    texture: &asset_importer::Texture,
+    model_name: &str,
+    texture_index: usize,
+) -> String {
+    texture
+        .filename()
+        .unwrap_or(std::format!("{model_name}_texture_{texture_index}"))
+}
+
+/// Artifact older than its source is outdated, missing source means the cooked data is shipped alone.
+fn is_artifact_outdated(source_path: &Path, artifact_path: &Path) -> bool {
+    let get_modified_time =
+        |path: &Path| std::fs::metadata(path).and_then(|metadata| metadata.modified());
+
+    match (
+        get_modified_time(source_path),
+        get_modified_time(artifact_path),
+    ) {
+        (_, Err(_)) => true,
+        (Ok(source_time), Ok(artifact_time)) => artifact_time < source_time,
+        (Err(_), Ok(_)) => false,
+    }
+}
+
+/// Unreadable or outdated artifact is a miss.
+pub(crate) fn read_cooked_texture(
+    path: &Path,
+    source_path: &Path,
+) -> Option<(TextureMetadata, Vec<u8>)> {
+    if is_artifact_outdated(source_path, path) {
+        return None;
+    }
+
+    let texture = match Ktx2Texture::from_file(path) {
+        Ok(texture) => texture,
+        Err(error) => {
+            println!("Invalid cooked texture {}: {:?}", path.display(), error);
+
+            return None;
+        }
+    };
+    let texture_metadata_raw = texture.get_metadata(stringify!(TextureMetadata)).ok()?;
+    let texture_metadata =
+        bytemuck::try_pod_read_unaligned::<TextureMetadata>(&texture_metadata_raw).ok()?;
+
+    let mut texture_data = Vec::new();
+    for mip_level_index in 0..texture_metadata.mip_levels_count {
+        texture_data.extend_from_slice(texture.get_image_data(mip_level_index, 0, 0).ok()?);
+    }
+
+    Some((texture_metadata, texture_data))
+}
+
+fn write_cooked_texture(ktx_texture: &Ktx2Texture, path: &Path) {
+    write_artifact(path, |temporary_path| {
+        ktx_texture
+            .write_to_file(temporary_path)
+            .map_err(|error| std::format!("{:?}", error))
+    });
+}
+
+/// Written to a temporary file first and renamed over the artifact, so an interrupted write
+/// never leaves a truncated artifact behind.
+fn write_artifact(path: &Path, write: impl FnOnce(&Path) -> Result<(), String>) {
+    let mut temporary_path = path.as_os_str().to_owned();
+    temporary_path.push(".tmp");
+    let temporary_path = PathBuf::from(temporary_path);
+
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .map_err(|error| error.to_string())
+        .and_then(|_| write(&temporary_path))
+        .and_then(|_| std::fs::rename(&temporary_path, path).map_err(|error| error.to_string()));
+
+    if let Err(error) = result {
+        println!("Failed to write artifact {}: {}", path.display(), error);
+        let _ = std::fs::remove_file(&temporary_path);
+    }
+}
+
+#[derive(Default, Debug)]
+#[non_exhaustive]
+pub struct CookReport {
+    pub models_count: usize,
+    pub cooked_models_count: usize,
+    /// Models, that are up to date.
+    pub skipped_models_count: usize,
+    pub meshes_count: usize,
+    /// Simplified levels, the full detail ones aren't counted.
+    pub lods_count: usize,
+    pub cooked_textures_count: usize,
+    pub skipped_textures_count: usize,
+    pub failed_models: Vec<(PathBuf, String)>,
+}
+
+/// Offline import of the models in the asset root into the intermediate folder: hierarchy, materials
+/// and meshes, that are preprocessed, optimized, split into meshlets and simplified into LODs,
+/// go into a `.model` file, base color textures are Basis compressed into KTX2.
+/// Runtime loads only these artifacts, models that aren't cooked are cooked on demand,
+/// if `EngineConfig::import_fallback` allows it, so Assimp and meshopt stay out of shipping loads.
+pub struct AssetCooker {
+    asset_root: PathBuf,
+    intermediate_root: PathBuf,
+    import_settings: ImportSettings,
+    meshlet_cone_weight: f32,
+    /// Up to date artifacts are cooked again as well.
+    is_forced: bool,
+}
+
+impl AssetCooker {
+    pub const MODEL_EXTENSIONS: [&str; 2] = ["glb", "gltf"];
+
+    /// Roots and settings are the ones the runtime uses, so their artifacts match.
+    pub fn new(engine_config: &EngineConfig) -> Self {
+        Self {
+            asset_root: engine_config.get_asset_root(),
+            intermediate_root: engine_config.get_intermediate_root(),
+            import_settings: Default::default(),
+            meshlet_cone_weight: CullingSettings::default().meshlet_cone_weight,
+            is_forced: false,
+        }
+    }
+
+    pub fn with_asset_root(mut self, asset_root: impl Into<PathBuf>) -> Self {
+        self.asset_root = asset_root.into();
+
+        self
+    }
+
+    pub fn with_intermediate_root(mut self, intermediate_root: impl Into<PathBuf>) -> Self {
+        self.intermediate_root = intermediate_root.into();
+
+        self
+    }
+
+    pub fn with_import_settings(mut self, import_settings: ImportSettings) -> Self {
+        self.import_settings = import_settings;
+
+        self
+    }
+
+    pub fn with_meshlet_cone_weight(mut self, meshlet_cone_weight: f32) -> Self {
+        self.meshlet_cone_weight = meshlet_cone_weight;
+
+        self
+    }
+
+    pub fn with_forced(mut self, is_forced: bool) -> Self {
+        self.is_forced = is_forced;
+
+        self
+    }
+
+    /// Cooks every model under the asset root, failed models don't stop the others.
+    pub fn cook(&self) -> CookReport {
+        let mut cook_report = CookReport::default();
+
+        let model_paths = WalkDir::new(&self.asset_root)
+            .into_iter()
+            .filter_map(|dir_entry| dir_entry.ok())
+            .filter(|dir_entry| dir_entry.file_type().is_file())
+            .map(|dir_entry| dir_entry.into_path())
+            .filter(|path| {
+                path.extension()
+                    .and_then(|extension| extension.to_str())
+                    .is_some_and(|extension| {
+                        Self::MODEL_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        for model_path in model_paths {
+            println!("Cooking {}", model_path.display());
+            if let Err(error) = self.cook_model(&model_path, &mut cook_report) {
+                println!("Failed to cook {}: {}", model_path.display(), error);
+                cook_report.failed_models.push((model_path, error));
+            }
+        }
+
+        cook_report
+    }
+
+    pub fn cook_model(
+        &self,
+        model_path: &Path,
+        cook_report: &mut CookReport,
+    ) -> Result<(), String> {
+        let model_name = model_path
+            .file_stem()
+            .and_then(|file_stem| file_stem.to_str())
+            .ok_or_else(|| "Invalid model name".to_string())?;
+        cook_report.models_count += 1;
+
+        let asset_key = get_asset_key(&self.asset_root, model_path);
+        let settings_hash = get_cook_settings_hash(&self.import_settings, self.meshlet_cone_weight);
+        let cooked_model_path = get_cooked_model_path(&self.intermediate_root, &asset_key);
+        if !self.is_forced
+            && CookedModel::open(&cooked_model_path, model_path, &asset_key, settings_hash)
+                .is_some()
+        {
+            cook_report.skipped_models_count += 1;
+
+            return Ok(());
+        }
+
+        let scene = ModelLoader::new().try_load_model(model_path.to_str().unwrap())?;
+        let mut cooked_model_writer = CookedModelWriter::new();
+
+        for (name, parent_index, matrix, mesh_indices) in get_nodes(&scene)? {
+            cooked_model_writer.push_node(&name, parent_index, matrix, &mesh_indices);
+        }
+
+        let raw_meshes = (0..scene.num_meshes())
+            .map(|mesh_index| {
+                let mesh = scene.mesh(mesh_index).unwrap();
+
+                (
+                    mesh.name(),
+                    mesh.material_index() as u32,
+                    get_raw_mesh(&mesh),
+                )
+            })
+            .collect::<Vec<_>>();
+        let import_settings = self.import_settings;
+        let cone_weight = self.meshlet_cone_weight;
+        let cooked_meshes = raw_meshes
+            .into_par_iter()
+            .map(|(mesh_name, material_index, (vertices, indices))| {
+                let (vertices, indices) =
+                    preprocess_mesh(&mesh_name, vertices, indices, import_settings);
+                let mesh_geometry = build_mesh_geometry(vertices, indices, cone_weight);
+                log_mesh_statistics(&mesh_name, mesh_geometry.mesh_statistics);
+                let lods = build_mesh_lods(&mesh_geometry);
+
+                (mesh_name, material_index, mesh_geometry, lods)
+            })
+            .collect::<Vec<_>>();
+        for (mesh_name, material_index, mesh_geometry, lods) in cooked_meshes.iter() {
+            cooked_model_writer.push_mesh(mesh_name, *material_index, mesh_geometry, lods);
+            cook_report.lods_count += lods.len();
+        }
+        cook_report.meshes_count += cooked_meshes.len();
+
+        // NOTE: Textures are written before the model, so a valid model always finds its textures.
+        let mut cooked_texture_indices = HashSet::new();
+        for material_index in 0..scene.num_materials() {
+            let material = scene.material(material_index).unwrap();
+            let (cooked_material, texture_index) = get_cooked_material(&material);
+
+            let texture_name = match texture_index {
+                Some(texture_index) => {
+                    let texture = scene.texture(texture_index).unwrap();
+                    let texture_name = get_texture_name(&texture, model_name, texture_index);
+                    if cooked_texture_indices.insert(texture_index) {
+                        self.cook_texture(
+                            &texture,
+                            &texture_name,
+                            &asset_key,
+                            model_path,
+                            cook_report,
+                        )?;
+                    }
+
+                    Some(texture_name)
+                }
+                None => None,
+            };
+
+            cooked_model_writer.push_material(cooked_material, texture_name.as_deref());
+        }
+
+        cooked_model_writer.write(&cooked_model_path, &asset_key, settings_hash);
+        cook_report.cooked_models_count += 1;
+
+        Ok(())
+    }
+
+    fn cook_texture(
+        &self,
+        texture: &asset_importer::Texture,
+        texture_name: &str,
+        asset_key: &str,
+        model_path: &Path,
+        cook_report: &mut CookReport,
+    ) -> Result<(), String> {
+        let cooked_texture_path =
+            get_cooked_texture_path(&self.intermediate_root, asset_key, texture_name);
+        if !self.is_forced && !is_artifact_outdated(model_path, &cooked_texture_path) {
+            cook_report.skipped_textures_count += 1;
+
+            return Ok(());
+        }
+
+        let ktx_texture = encode_texture(texture)?;
+        write_cooked_texture(&ktx_texture, &cooked_texture_path);
+        cook_report.cooked_textures_count += 1;
+
+        Ok(())
+    }
+}
+
+/// Name, parent index, local matrix and mesh indices, parents come before their children.
+fn get_nodes(
+    scene: &asset_importer::Scene,
+) -> Result<Vec<(String, Option<usize>, Mat4, Vec<u32>)>, String> {
+    let get_mesh_indices = |node: &Node| {
+        if node.num_meshes() > Default::default() {
+            node.mesh_indices()
+                .map(|mesh_index| mesh_index as u32)
+                .collect()
+        } else {
+            Vec::new()
+        }
+    };
+
+    let root_node = scene
+        .root_node()
+        .ok_or_else(|| "Model without a root node".to_string())?;
+    let mut nodes = vec![(
+        root_node.name(),
+        None,
+        get_matrix(root_node.transformation()),
+        get_mesh_indices(&root_node),
+    )];
+
+    let mut stack: Vec<(Node, usize)> = vec![(root_node, Default::default())];
+    while let Some((parent_node, parent_index)) = stack.pop() {
+        for child_index in (0..parent_node.num_children()).rev() {
+            let child_node = parent_node.child(child_index).unwrap();
+
+            nodes.push((
+                child_node.name(),
+                Some(parent_index),
+                get_matrix(child_node.transformation()),
+                get_mesh_indices(&child_node),
+            ));
+            stack.push((child_node, nodes.len() - 1));
+        }
+    }
+
+    Ok(nodes)
+}
+
+fn get_matrix(transformation: Matrix4x4) -> Mat4 {
+    Mat4::from_cols(
+        Vec4::new(
+            transformation.x_axis.x,
+            transformation.x_axis.y,
+            transformation.x_axis.z,
+            transformation.x_axis.w,
+        ),
+        Vec4::new(
+            transformation.y_axis.x,
+            transformation.y_axis.y,
+            transformation.y_axis.z,
+            transformation.y_axis.w,
+        ),
+        Vec4::new(
+            transformation.z_axis.x,
+            transformation.z_axis.y,
+            transformation.z_axis.z,
+            transformation.z_axis.w,
+        ),
+        Vec4::new(
+            transformation.w_axis.x,
+            transformation.w_axis.y,
+            transformation.w_axis.z,
+            transformation.w_axis.w,
+        ),
+    )
+}
+
+/// Material with the index of its embedded base color texture.
+fn get_cooked_material(material: &asset_importer::Material) -> (CookedMaterial, Option<usize>) {
+    let texture_type = asset_importer::TextureType::BaseColor;
+
+    let is_blended = material
+        .get_property_raw_ref(c"$mat.gltf.alphaMode", None, 0)
+        .and_then(|alpha_mode| std::str::from_utf8(alpha_mode).ok())
+        .is_some_and(|alpha_mode| alpha_mode.contains("BLEND"));
+    // NOTE: Extensions are absent from most of the materials, their defaults disable them.
+    let emissive_strength =
+        get_material_property_f32::<1>(material, c"$mat.emissiveIntensity").map_or(1.0, |[x]| x);
+    let emissive_color = get_material_property_f32::<3>(material, c"$clr.emissive")
+        .map_or([0.0; 3], |emissive_color| {
+            emissive_color.map(|component| component * emissive_strength)
+        });
+    let clearcoat = get_material_property_f32::<1>(material, c"$mat.clearcoat.factor")
+        .map_or(0.0, |[x]| x.clamp(0.0, 1.0));
+    let clearcoat_roughness =
+        get_material_property_f32::<1>(material, c"$mat.clearcoat.roughnessFactor")
+            .map_or(0.0, |[x]| x.clamp(0.0, 1.0));
+    let transmission = get_material_property_f32::<1>(material, c"$mat.transmission.factor")
+        .map_or(0.0, |[x]| x.clamp(0.0, 1.0));
+    let is_transparent = is_blended || transmission > 0.0;
+    let base_color = material.base_color().map_or([1.0; 4], |base_color| {
+        [base_color.x, base_color.y, base_color.z, base_color.w]
+    });
+
+    let texture_index = (material.texture_count(texture_type) > Default::default())
+        .then(|| material.texture(texture_type, Default::default()).unwrap())
+        .and_then(|texture_info| texture_info.path.get(1..)?.parse::<usize>().ok());
+    let get_sampler_value = |key: &std::ffi::CStr| {
+        get_texture_property_i32(material, key, texture_type).unwrap_or(CookedMaterial::UNSET)
+    };
+
+    let cooked_material = CookedMaterial {
+        base_color,
+        metallic: material.metallic_factor().unwrap_or(0.0),
+        roughness: material.roughness_factor().unwrap_or(0.0),
+        is_transparent: is_transparent as _,
+        base_color_texture: Default::default(),
+        mag_filter: get_sampler_value(c"$tex.mappingfiltermag"),
+        min_filter: get_sampler_value(c"$tex.mappingfiltermin"),
+        wrap_u: get_sampler_value(c"$tex.mapmodeu"),
+        wrap_v: get_sampler_value(c"$tex.mapmodev"),
+        emissive_color,
+        clearcoat,
+        clearcoat_roughness,
+        transmission,
+    };
+
+    (cooked_material, texture_index)
+}
+
+/// Colors are stored with three or four components, the leading ones are taken.
+fn get_material_property_f32<const N: usize>(
+    material: &asset_importer::Material,
+    key: &std::ffi::CStr,
+) -> Option<[f32; N]> {
+    let raw_value = material.get_property_raw_ref(key, None, 0)?;
+    let raw_value = raw_value.get(..std::mem::size_of::<[f32; N]>())?;
+
+    let mut value = [0.0; N];
+    for (component, bytes) in value
+        .iter_mut()
+        .zip(raw_value.chunks_exact(std::mem::size_of::<f32>()))
+    {
+        *component = f32::from_le_bytes(bytes.try_into().unwrap());
+    }
+
+    Some(value)
+}
+
+fn get_texture_property_i32(
+    material: &asset_importer::Material,
+    key: &std::ffi::CStr,
+    texture_type: asset_importer::TextureType,
+) -> Option<i32> {
+    let raw_value = material.get_property_raw_ref(key, Some(texture_type), 0)?;
+
+    raw_value
+        .get(..std::mem::size_of::<i32>())
+        .map(|bytes| i32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn get_raw_mesh(mesh: &asset_importer::Mesh) -> (Vec<Vertex>, Vec<u32>) {
+    let mut indices = Vec::with_capacity(mesh.faces().len() * 3);
+
+    for face in mesh.faces() {
+        for index in face.indices() {
+            indices.push(*index);
+        }
+    }
+
+    let positions: Vec<Vec3> = mesh
+        .vertices_iter()
+        .map(|v| Vec3::new(v.x, v.y, v.z))
+        .collect();
+    let colors: Vec<Vec3> = mesh
+        .vertex_colors(Default::default())
+        .map(|colors| {
+            colors
+                .iter()
+                .map(|color| Vec3::new(color.x, color.y, color.z))
+                .collect()
+        })
+        .unwrap_or_else(|| vec![Vec3::ZERO; positions.len()]);
+    let normals: Vec<Vec3> = mesh
+        .normals()
+        .map(|ns| ns.iter().map(|n| Vec3::new(n.x, n.y, n.z)).collect())
+        .unwrap_or_else(|| vec![Vec3::ZERO; positions.len()]);
+
+    let uvs: Vec<Vec2> = if mesh.has_texture_coords(0) {
+        mesh.texture_coords_iter(0)
+            .map(|uv| Vec2::new(uv.x, uv.y))
+            .collect()
+    } else {
+        vec![Vec2::ZERO; positions.len()]
+    };
+
+    let mut vertices = Vec::with_capacity(positions.len());
+    for i in 0..positions.len() {
+        vertices.push(Vertex {
+            position: positions[i].to_array(),
+            normal: normals[i].to_array(),
+            uv: uvs[i].to_array(),
+            color: colors[i].to_array(),
+            ..Default::default()
+        });
+    }
+
+    (vertices, indices)
+}
+
+fn log_mesh_statistics(mesh_name: &str, mesh_statistics: MeshStatistics) {
+    let MeshStatistics {
+        acmr,
+        atvr,
+        overdraw,
+        overfetch,
+    } = mesh_statistics;
+    let warning = if mesh_statistics.is_pathological() {
+        " | Pathological"
+    } else {
+        ""
+    };
+
+    println!(
+        "Mesh: {} | ACMR: {:.3} | ATVR: {:.3} | Overdraw: {:.3} | Overfetch: {:.3}{}",
+        mesh_name, acmr, atvr, overdraw, overfetch, warning,
+    );
+}
+
+/// Welds near-duplicate vertices and drops degenerate triangles, as enabled in `ImportSettings`.
+fn preprocess_mesh(
+    mesh_name: &str,
+    mut vertices: Vec<Vertex>,
+    mut indices: Vec<u32>,
+    import_settings: ImportSettings,
+) -> (Vec<Vertex>, Vec<u32>) {
+    if !import_settings.is_welding_enabled && !import_settings.is_degenerate_removal_enabled {
+        return (vertices, indices);
+    }
+
+    let vertices_count = vertices.len();
+    let triangles_count = indices.len() / 3;
+
+    if import_settings.is_welding_enabled {
+        (vertices, indices) = weld_vertices(&vertices, &indices, import_settings);
+    }
+    if import_settings.is_degenerate_removal_enabled {
+        indices = remove_degenerate_triangles(&vertices, &indices);
+    }
+
+    if vertices.len() < vertices_count || indices.len() / 3 < triangles_count {
+        println!(
+            "Mesh: {} | Vertices: {} -> {} | Triangles: {} -> {}",
+            mesh_name,
+            vertices_count,
+            vertices.len(),
+            triangles_count,
+            indices.len() / 3,
+        );
+    }
+
+    (vertices, indices)
+}
+
+/// Vertices in the same cell of the epsilon grid are merged into the first of them.
+/// Colors are compared exactly, they are rarely split by the precision.
+fn weld_vertices(
+    vertices: &[Vertex],
+    indices: &[u32],
+    import_settings: ImportSettings,
+) -> (Vec<Vertex>, Vec<u32>) {
+    let quantize = |value: f32, epsilon: f32| (value / epsilon.max(f32::EPSILON)).round() as i64;
+
+    let mut welded_indices = HashMap::with_capacity(vertices.len());
+    let mut welded_vertices = Vec::with_capacity(vertices.len());
+    let remap = vertices
+        .iter()
+        .map(|vertex| {
+            let position = vertex
+                .position
+                .map(|value| quantize(value, import_settings.weld_position_epsilon));
+            let normal = vertex
+                .normal
+                .map(|value| quantize(value, import_settings.weld_normal_epsilon));
+            let uv = vertex
+                .uv
+                .map(|value| quantize(value, import_settings.weld_uv_epsilon));
+            let color = vertex.color.map(f32::to_bits);
+
+            *welded_indices
+                .entry((position, normal, uv, color))
+                .or_insert_with(|| {
+                    welded_vertices.push(*vertex);
+
+                    welded_vertices.len() as u32 - 1
+                })
+        })
+        .collect::<Vec<_>>();
+
+    let indices = indices.iter().map(|&index| remap[index as usize]).collect();
+
+    (welded_vertices, indices)
+}
+
+fn remove_degenerate_triangles(vertices: &[Vertex], indices: &[u32]) -> Vec<u32> {
+    indices
+        .chunks_exact(3)
+        .filter(|triangle| {
+            let [i0, i1, i2] = [triangle[0], triangle[1], triangle[2]];
+            if i0 == i1 || i1 == i2 || i0 == i2 {
+                return false;
+            }
+
+            let [p0, p1, p2] =
+                [i0, i1, i2].map(|index| Vec3::from_array(vertices[index as usize].position));
+            let edge_a = p1 - p0;
+            let edge_b = p2 - p0;
+
+            // NOTE: Squared sine of the angle between the edges, so it doesn't depend on the scale.
+            edge_a.cross(edge_b).length_squared()
+                > f32::EPSILON * edge_a.length_squared() * edge_b.length_squared()
+        })
+        .flatten()
+        .copied()
+        .collect()
+}
+
+/// Embedded texture compressed as the runtime uploads it, mip chain included.
+fn encode_texture(texture: &asset_importer::Texture) -> Result<Ktx2Texture, String> {
+    let mut data = texture.data_bytes_ref().unwrap();
+
+    let image = ImageReader::new(Cursor::new(&mut data))
+        .with_guessed_format()
+        .map_err(|error| error.to_string())?
+        .decode()
+        .map_err(|error| error.to_string())?;
+
+    let texture_metadata = TextureMetadata {
+        width: image.width(),
+        height: image.height(),
+        mip_levels_count: TexturesPool::get_mip_levels_count(image.width(), image.height()),
+    };
+    let rgba_image = image.to_rgba8();
+    let mut image_bytes = rgba_image.as_bytes().to_vec();
+
+    Ok(TexturesPool::encode_ktx_texture(
+        &mut image_bytes,
+        texture_metadata,
+        Format::Bc1RgbSrgbBlock,
+    ))
+}
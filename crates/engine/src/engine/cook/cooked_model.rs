@@ -0,0 +1,444 @@
+use std::path::Path;
+
+use bytemuck::{Pod, Zeroable};
+use math::Mat4;
+use memmap2::Mmap;
+use meshopt::{SimplifyOptions, VertexDataAdapter, typed_to_bytes};
+
+use crate::engine::{
+    ecs::{
+        mesh_buffers_pool::MeshStatistics,
+        on_load_model::{MeshGeometry, get_aabb},
+    },
+    resources::{Meshlet, Vertex},
+};
+
+/// Byte range in the cooked model file.
+#[repr(C)]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub(crate) struct CookedRange {
+    pub offset: u32,
+    pub size: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct CookedModelHeader {
+    magic: [u8; 4],
+    version: u32,
+    settings_hash: u64,
+    /// Asset key of the source, artifacts are keyed by its hash, so a collision is detected by it.
+    asset_key: CookedRange,
+    nodes: CookedRange,
+    meshes: CookedRange,
+    materials: CookedRange,
+}
+
+impl CookedModelHeader {
+    const MAGIC: [u8; 4] = *b"MODL";
+    const VERSION: u32 = 3;
+}
+
+/// Nodes are in the order of the hierarchy traversal, parents come before their children.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub(crate) struct CookedNode {
+    pub name: CookedRange,
+    /// `u32` indices of the meshes.
+    pub mesh_indices: CookedRange,
+    /// `CookedNode::NO_PARENT` for the root.
+    pub parent_index: u32,
+    pub matrix: [f32; 16],
+}
+
+impl CookedNode {
+    pub(crate) const NO_PARENT: u32 = u32::MAX;
+
+    #[inline(always)]
+    pub(crate) fn get_parent_index(&self) -> Option<usize> {
+        (self.parent_index != Self::NO_PARENT).then_some(self.parent_index as _)
+    }
+
+    #[inline(always)]
+    pub(crate) fn get_matrix(&self) -> Mat4 {
+        Mat4::from_cols_array(&self.matrix)
+    }
+}
+
+/// Geometry is optimized and split into meshlets, as the mesh buffers take it.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub(crate) struct CookedMesh {
+    pub name: CookedRange,
+    pub material_index: u32,
+    pub vertices: CookedRange,
+    pub indices: CookedRange,
+    pub meshlets: CookedRange,
+    pub vertex_indices: CookedRange,
+    pub triangles: CookedRange,
+    /// `CookedLod` records, the first one is the full detail `indices`.
+    pub lods: CookedRange,
+    pub aabb_min: [f32; 3],
+    pub aabb_max: [f32; 3],
+    /// `acmr`, `atvr`, `overdraw` and `overfetch`.
+    pub mesh_statistics: [f32; 4],
+}
+
+impl CookedMesh {
+    #[inline(always)]
+    pub(crate) fn get_mesh_statistics(&self) -> MeshStatistics {
+        let [acmr, atvr, overdraw, overfetch] = self.mesh_statistics;
+
+        MeshStatistics {
+            acmr,
+            atvr,
+            overdraw,
+            overfetch,
+        }
+    }
+}
+
+/// Simplified indices of the same vertices.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub(crate) struct CookedLod {
+    pub indices: CookedRange,
+    /// Relative to the mesh extents, as meshopt reports it.
+    pub error: f32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub(crate) struct CookedMaterial {
+    pub base_color: [f32; 4],
+    pub metallic: f32,
+    pub roughness: f32,
+    pub is_transparent: u32,
+    /// Name of the cooked texture, it's empty without one.
+    pub base_color_texture: CookedRange,
+    /// glTF sampler values of the base color texture, `CookedMaterial::UNSET` if they aren't set.
+    pub mag_filter: i32,
+    pub min_filter: i32,
+    pub wrap_u: i32,
+    pub wrap_v: i32,
+    /// Multiplied by `KHR_materials_emissive_strength`.
+    pub emissive_color: [f32; 3],
+    /// `KHR_materials_clearcoat`, zero without the extension.
+    pub clearcoat: f32,
+    pub clearcoat_roughness: f32,
+    /// `KHR_materials_transmission`, transmissive materials are transparent.
+    pub transmission: f32,
+}
+
+impl CookedMaterial {
+    pub(crate) const UNSET: i32 = -1;
+
+    #[inline(always)]
+    pub(crate) fn get_value(value: i32) -> Option<i32> {
+        (value != Self::UNSET).then_some(value)
+    }
+}
+
+/// Cooked model, that's memory mapped, so geometry blobs are copied into staging straight from the file.
+/// Sections are aligned by 4 bytes and the mapping by the page, so records are read in place.
+pub(crate) struct CookedModel {
+    mmap: Mmap,
+    header: CookedModelHeader,
+}
+
+impl CookedModel {
+    /// Unreadable, outdated, cooked with other settings or from another source model is a miss,
+    /// as well as the one with ranges out of the file or misaligned for their records.
+    pub(crate) fn open(
+        path: &Path,
+        source_path: &Path,
+        asset_key: &str,
+        settings_hash: u64,
+    ) -> Option<Self> {
+        if super::is_artifact_outdated(source_path, path) {
+            return None;
+        }
+
+        let file = std::fs::File::open(path).ok()?;
+        // NOTE: Artifacts are replaced by a rename, so a mapped file is never written in place.
+        let mmap = unsafe { Mmap::map(&file) }.ok()?;
+        let header = bytemuck::try_pod_read_unaligned::<CookedModelHeader>(
+            mmap.get(..std::mem::size_of::<CookedModelHeader>())?,
+        )
+        .ok()?;
+        if header.magic != CookedModelHeader::MAGIC
+            || header.version != CookedModelHeader::VERSION
+            || header.settings_hash != settings_hash
+        {
+            return None;
+        }
+
+        let cooked_model = Self { mmap, header };
+        if !cooked_model.is_valid() {
+            println!("Invalid cooked model {}", path.display());
+
+            return None;
+        }
+        if cooked_model.get_str(header.asset_key) != asset_key {
+            return None;
+        }
+
+        Some(cooked_model)
+    }
+
+    /// Every range is checked once, so the getters never hand out a truncated section.
+    fn is_valid(&self) -> bool {
+        let header = self.header;
+        if !self.is_str_valid(header.asset_key)
+            || !self.is_range_valid::<CookedNode>(header.nodes)
+            || !self.is_range_valid::<CookedMesh>(header.meshes)
+            || !self.is_range_valid::<CookedMaterial>(header.materials)
+        {
+            return false;
+        }
+
+        let are_nodes_valid = self.get_nodes().iter().all(|cooked_node| {
+            self.is_str_valid(cooked_node.name)
+                && self.is_range_valid::<u32>(cooked_node.mesh_indices)
+        });
+        let are_meshes_valid = self.get_meshes().iter().all(|cooked_mesh| {
+            self.is_str_valid(cooked_mesh.name)
+                && self.is_range_valid::<Vertex>(cooked_mesh.vertices)
+                && self.is_range_valid::<u32>(cooked_mesh.indices)
+                && self.is_range_valid::<Meshlet>(cooked_mesh.meshlets)
+                && self.is_range_valid::<u32>(cooked_mesh.vertex_indices)
+                && self.is_range_valid::<u8>(cooked_mesh.triangles)
+                && self.is_range_valid::<CookedLod>(cooked_mesh.lods)
+                && self
+                    .get_slice::<CookedLod>(cooked_mesh.lods)
+                    .iter()
+                    .all(|cooked_lod| self.is_range_valid::<u32>(cooked_lod.indices))
+        });
+        let are_materials_valid = self
+            .get_materials()
+            .iter()
+            .all(|cooked_material| self.is_str_valid(cooked_material.base_color_texture));
+
+        are_nodes_valid && are_meshes_valid && are_materials_valid
+    }
+
+    // NOTE: Mapping is aligned by the page, so an aligned offset is an aligned pointer.
+    fn is_range_valid<T: Pod>(&self, range: CookedRange) -> bool {
+        let (offset, size) = (range.offset as usize, range.size as usize);
+
+        offset
+            .checked_add(size)
+            .is_some_and(|end| end <= self.mmap.len())
+            && offset.is_multiple_of(std::mem::align_of::<T>())
+            && size.is_multiple_of(std::mem::size_of::<T>())
+    }
+
+    fn is_str_valid(&self, range: CookedRange) -> bool {
+        self.is_range_valid::<u8>(range) && std::str::from_utf8(self.get_slice(range)).is_ok()
+    }
+
+    /// Ranges are validated on open, out of bounds or misaligned one is empty.
+    pub(crate) fn get_slice<T: Pod>(&self, range: CookedRange) -> &[T] {
+        let start = range.offset as usize;
+
+        self.mmap
+            .get(start..start + range.size as usize)
+            .and_then(|bytes| bytemuck::try_cast_slice(bytes).ok())
+            .unwrap_or_default()
+    }
+
+    #[inline(always)]
+    pub(crate) fn get_str(&self, range: CookedRange) -> &str {
+        std::str::from_utf8(self.get_slice(range)).unwrap_or_default()
+    }
+
+    #[inline(always)]
+    pub(crate) fn get_nodes(&self) -> &[CookedNode] {
+        self.get_slice(self.header.nodes)
+    }
+
+    #[inline(always)]
+    pub(crate) fn get_meshes(&self) -> &[CookedMesh] {
+        self.get_slice(self.header.meshes)
+    }
+
+    #[inline(always)]
+    pub(crate) fn get_materials(&self) -> &[CookedMaterial] {
+        self.get_slice(self.header.materials)
+    }
+}
+
+/// Header, blobs and then the tables of the records.
+pub(crate) struct CookedModelWriter {
+    bytes: Vec<u8>,
+    nodes: Vec<CookedNode>,
+    meshes: Vec<CookedMesh>,
+    materials: Vec<CookedMaterial>,
+}
+
+impl CookedModelWriter {
+    const ALIGNMENT: usize = 4;
+
+    pub(crate) fn new() -> Self {
+        Self {
+            bytes: vec![Default::default(); std::mem::size_of::<CookedModelHeader>()],
+            nodes: Default::default(),
+            meshes: Default::default(),
+            materials: Default::default(),
+        }
+    }
+
+    pub(crate) fn push_bytes(&mut self, bytes: &[u8]) -> CookedRange {
+        self.bytes.resize(
+            self.bytes.len().next_multiple_of(Self::ALIGNMENT),
+            Default::default(),
+        );
+        let range = CookedRange {
+            offset: self.bytes.len() as _,
+            size: bytes.len() as _,
+        };
+        self.bytes.extend_from_slice(bytes);
+
+        range
+    }
+
+    pub(crate) fn push_node(
+        &mut self,
+        name: &str,
+        parent_index: Option<usize>,
+        matrix: Mat4,
+        mesh_indices: &[u32],
+    ) {
+        let node = CookedNode {
+            name: self.push_bytes(name.as_bytes()),
+            mesh_indices: self.push_bytes(bytemuck::cast_slice(mesh_indices)),
+            parent_index: parent_index.map_or(CookedNode::NO_PARENT, |index| index as _),
+            matrix: matrix.to_cols_array(),
+        };
+        self.nodes.push(node);
+    }
+
+    pub(crate) fn push_mesh(
+        &mut self,
+        name: &str,
+        material_index: u32,
+        mesh_geometry: &MeshGeometry,
+        lods: &[(Vec<u32>, f32)],
+    ) {
+        let (aabb_min, aabb_max) = get_aabb(&mesh_geometry.vertices);
+        let mesh_statistics = mesh_geometry.mesh_statistics;
+
+        let name = self.push_bytes(name.as_bytes());
+        let vertices = self.push_bytes(bytemuck::cast_slice(&mesh_geometry.vertices));
+        let indices = self.push_bytes(bytemuck::cast_slice(&mesh_geometry.indices));
+        let meshlets = self.push_bytes(bytemuck::cast_slice(&mesh_geometry.meshlets));
+        let vertex_indices = self.push_bytes(bytemuck::cast_slice(&mesh_geometry.vertex_indices));
+        let triangles = self.push_bytes(&mesh_geometry.triangles);
+
+        let cooked_lods = std::iter::once(CookedLod {
+            indices,
+            error: Default::default(),
+        })
+        .chain(lods.iter().map(|(lod_indices, error)| CookedLod {
+            indices: self.push_bytes(bytemuck::cast_slice(lod_indices)),
+            error: *error,
+        }))
+        .collect::<Vec<_>>();
+        let lods = self.push_bytes(bytemuck::cast_slice(&cooked_lods));
+
+        self.meshes.push(CookedMesh {
+            name,
+            material_index,
+            vertices,
+            indices,
+            meshlets,
+            vertex_indices,
+            triangles,
+            lods,
+            aabb_min: aabb_min.to_array(),
+            aabb_max: aabb_max.to_array(),
+            mesh_statistics: [
+                mesh_statistics.acmr,
+                mesh_statistics.atvr,
+                mesh_statistics.overdraw,
+                mesh_statistics.overfetch,
+            ],
+        });
+    }
+
+    /// `base_color_texture` is the name of the cooked texture.
+    pub(crate) fn push_material(
+        &mut self,
+        mut material: CookedMaterial,
+        base_color_texture: Option<&str>,
+    ) {
+        material.base_color_texture = base_color_texture
+            .map(|texture_name| self.push_bytes(texture_name.as_bytes()))
+            .unwrap_or_default();
+        self.materials.push(material);
+    }
+
+    pub(crate) fn write(mut self, path: &Path, asset_key: &str, settings_hash: u64) {
+        let nodes = std::mem::take(&mut self.nodes);
+        let meshes = std::mem::take(&mut self.meshes);
+        let materials = std::mem::take(&mut self.materials);
+
+        let header = CookedModelHeader {
+            magic: CookedModelHeader::MAGIC,
+            version: CookedModelHeader::VERSION,
+            settings_hash,
+            asset_key: self.push_bytes(asset_key.as_bytes()),
+            nodes: self.push_bytes(bytemuck::cast_slice(&nodes)),
+            meshes: self.push_bytes(bytemuck::cast_slice(&meshes)),
+            materials: self.push_bytes(bytemuck::cast_slice(&materials)),
+        };
+        self.bytes[..std::mem::size_of::<CookedModelHeader>()]
+            .copy_from_slice(bytemuck::bytes_of(&header));
+
+        super::write_artifact(path, |temporary_path| {
+            std::fs::write(temporary_path, &self.bytes).map_err(|error| error.to_string())
+        });
+    }
+}
+
+/// Each level halves the triangles of the previous one, levels that barely simplify are dropped.
+pub(crate) fn build_mesh_lods(mesh_geometry: &MeshGeometry) -> Vec<(Vec<u32>, f32)> {
+    const MAX_LODS_COUNT: usize = 3;
+    const MIN_REDUCTION: f32 = 0.9;
+    const TARGET_ERROR: f32 = 0.05;
+
+    let vertex_data_adapter = VertexDataAdapter::new(
+        typed_to_bytes(&mesh_geometry.vertices),
+        std::mem::size_of::<Vertex>(),
+        std::mem::offset_of!(Vertex, position),
+    )
+    .unwrap();
+
+    let mut lods: Vec<(Vec<u32>, f32)> = Vec::with_capacity(MAX_LODS_COUNT);
+    for _ in 0..MAX_LODS_COUNT {
+        let previous_indices = lods
+            .last()
+            .map_or(mesh_geometry.indices.as_slice(), |(indices, _)| indices);
+        let target_count = (previous_indices.len() / 2 / 3) * 3;
+        if target_count < 3 {
+            break;
+        }
+
+        let mut error = 0.0;
+        let lod_indices = meshopt::simplify(
+            previous_indices,
+            &vertex_data_adapter,
+            target_count,
+            TARGET_ERROR,
+            SimplifyOptions::empty(),
+            Some(&mut error),
+        );
+        if lod_indices.len() as f32 > previous_indices.len() as f32 * MIN_REDUCTION {
+            break;
+        }
+
+        lods.push((lod_indices, error));
+    }
+
+    lods
+}
@@ -0,0 +1,60 @@
+use bevy_ecs::component::Component;
+use math::{Mat3, Mat4, Quat, Vec3};
+
+use crate::engine::components::local_transform::LocalTransform;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[non_exhaustive]
+pub enum BillboardMode {
+    /// Faces the camera from any direction, e.g. particles and markers.
+    #[default]
+    Spherical,
+    /// Turns only around its own up axis, e.g. trees, impostors and health bars.
+    Cylindrical,
+}
+
+/// Rotates the entity at the instance gather, so its local `+Z` faces the first active camera.
+/// Position and scale are kept, children don't inherit the rotation.
+#[derive(Component, Clone, Copy, Default, Debug)]
+#[require(LocalTransform)]
+pub struct Billboard {
+    pub mode: BillboardMode,
+}
+
+impl Billboard {
+    pub const SPHERICAL: Self = Self::new(BillboardMode::Spherical);
+    pub const CYLINDRICAL: Self = Self::new(BillboardMode::Cylindrical);
+
+    pub const fn new(mode: BillboardMode) -> Self {
+        Self { mode }
+    }
+
+    /// Replaces the rotation of the world matrix, it's kept if the camera is at the position.
+    pub fn get_model_matrix(&self, model_matrix: Mat4, camera_position: Vec3) -> Mat4 {
+        let (scale, rotation, position) = model_matrix.to_scale_rotation_translation();
+
+        let up = match self.mode {
+            BillboardMode::Spherical => Vec3::Y,
+            BillboardMode::Cylindrical => rotation * Vec3::Y,
+        };
+        let mut forward = camera_position - position;
+        if self.mode == BillboardMode::Cylindrical {
+            forward -= up * up.dot(forward);
+        }
+
+        let Some(forward) = forward.try_normalize() else {
+            return model_matrix;
+        };
+        // NOTE: Looking straight up or down, the camera's side is picked by the previous right axis.
+        let right = up.cross(forward).try_normalize().unwrap_or_else(|| {
+            (rotation * Vec3::X)
+                .reject_from(forward)
+                .normalize_or(Vec3::X)
+        });
+        let up = forward.cross(right);
+
+        let billboard_rotation = Quat::from_mat3(&Mat3::from_cols(right, up, forward));
+
+        Mat4::from_scale_rotation_translation(scale, billboard_rotation, position)
+    }
+}
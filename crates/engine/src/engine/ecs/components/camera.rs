@@ -1,6 +1,10 @@
 use bevy_ecs::component::Component;
+use math::{Mat4, Vec2, Vec3, Vec4};
 
-use crate::engine::LocalTransform;
+use crate::engine::{
+    LocalTransform,
+    resources::{EnvironmentMode, textures_pool::TextureReference},
+};
 
 #[derive(Default, Clone, Copy)]
 pub struct ClippingPlanes {
@@ -8,14 +12,173 @@ pub struct ClippingPlanes {
     pub far: f32,
 }
 
+/// Part of the render target the camera draws into, in `0.0..=1.0` from the top left corner.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CameraViewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for CameraViewport {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+impl CameraViewport {
+    pub const FULL: Self = Self::new(0.0, 0.0, 1.0, 1.0);
+
+    pub const fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+/// Plane of a mirror or water surface, points on it satisfy `dot(normal, point) == distance`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ReflectionPlane {
+    pub normal: Vec3,
+    pub distance: f32,
+}
+
+impl ReflectionPlane {
+    pub fn new(normal: Vec3, distance: f32) -> Self {
+        Self {
+            normal: normal.normalize(),
+            distance,
+        }
+    }
+
+    pub fn from_point_normal(point: Vec3, normal: Vec3) -> Self {
+        let normal = normal.normalize();
+
+        Self {
+            normal,
+            distance: normal.dot(point),
+        }
+    }
+
+    /// Mirrors world space points about the plane.
+    pub fn get_reflection_matrix(&self) -> Mat4 {
+        let Vec3 { x, y, z } = self.normal;
+        let translation = 2.0 * self.distance * self.normal;
+
+        Mat4::from_cols(
+            Vec4::new(1.0 - 2.0 * x * x, -2.0 * x * y, -2.0 * x * z, 0.0),
+            Vec4::new(-2.0 * x * y, 1.0 - 2.0 * y * y, -2.0 * y * z, 0.0),
+            Vec4::new(-2.0 * x * z, -2.0 * y * z, 1.0 - 2.0 * z * z, 0.0),
+            translation.extend(1.0),
+        )
+    }
+
+    /// Replaces the near plane of the reverse-Z projection with the reflection plane,
+    /// so geometry behind the mirror isn't drawn into the reflection.
+    pub(crate) fn get_oblique_projection(&self, projection: Mat4, view: Mat4) -> Mat4 {
+        let clip_plane = view.inverse().transpose() * self.normal.extend(-self.distance);
+        // NOTE: Frustum corner on the far plane, which is at zero depth with reverse-Z.
+        let corner = projection.inverse()
+            * Vec4::new(clip_plane.x.signum(), clip_plane.y.signum(), 0.0, 1.0);
+        let scale = projection.row(3).dot(corner) / clip_plane.dot(corner);
+
+        let mut transposed_projection = projection.transpose();
+        transposed_projection.z_axis = transposed_projection.w_axis - clip_plane * scale;
+
+        transposed_projection.transpose()
+    }
+}
+
+/// Background of the camera viewport, it's drawn before the meshes of the frame.
+#[derive(Default, Clone, Copy)]
+#[non_exhaustive]
+pub enum ClearBehavior {
+    /// Mode of `EnvironmentSettings`.
+    #[default]
+    Environment,
+    SolidColor(Vec4),
+    Gradient {
+        top_color: Vec4,
+        bottom_color: Vec4,
+    },
+    Skybox(TextureReference),
+    /// Viewport keeps what is under it, e.g. a UI camera on top of the 3D render.
+    Preserve,
+}
+
+impl ClearBehavior {
+    /// `None` if the viewport is preserved.
+    pub(crate) fn get_environment_mode(
+        self,
+        environment_mode: EnvironmentMode,
+    ) -> Option<EnvironmentMode> {
+        match self {
+            Self::Environment => Some(environment_mode),
+            Self::SolidColor(color) => Some(EnvironmentMode::SolidColor(color)),
+            Self::Gradient {
+                top_color,
+                bottom_color,
+            } => Some(EnvironmentMode::Gradient {
+                top_color,
+                bottom_color,
+            }),
+            Self::Skybox(texture_reference) => Some(EnvironmentMode::Skybox(texture_reference)),
+            Self::Preserve => None,
+        }
+    }
+}
+
+/// Thin lens parameters, objects away from the focal distance are blurred.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DepthOfField {
+    pub focal_distance: f32,
+    /// Diameter of the blur of a point at infinity, relative to the viewport height.
+    pub aperture: f32,
+    /// Upper bound of the blur radius in pixels, it's also the gather radius.
+    pub max_blur_radius: f32,
+}
+
+impl DepthOfField {
+    pub fn new(focal_distance: f32, aperture: f32) -> Self {
+        Self {
+            focal_distance,
+            aperture,
+            max_blur_radius: 16.0,
+        }
+    }
+
+    pub fn with_max_blur_radius(mut self, max_blur_radius: f32) -> Self {
+        self.max_blur_radius = max_blur_radius;
+
+        self
+    }
+}
+
 #[derive(Default, Component)]
 #[require(LocalTransform)]
 pub struct Camera {
     pub fov: f32,
     pub clipping_planes: ClippingPlanes,
+    pub viewport: CameraViewport,
+    /// Camera renders the scene mirrored about the plane into its viewport.
+    pub reflection_plane: Option<ReflectionPlane>,
+    /// Fog and atmospheric scattering of `EnvironmentSettings` aren't applied, e.g. for a map view.
+    pub is_fog_disabled: bool,
+    pub depth_of_field: Option<DepthOfField>,
+    pub clear_behavior: ClearBehavior,
+    /// Cameras are rendered from the lowest order, the first one drives the exposure, billboards
+    /// and the lens flare.
+    pub order: i32,
 }
 
 impl Camera {
+    /// Cameras above the limit aren't rendered.
+    pub const MAX_ACTIVE_CAMERAS_COUNT: usize = 4;
+
     pub fn new(fov: f32, near: f32, far: f32) -> Self {
         /*         let camera_rig = CameraRig::builder()
                    .with(Position::new(Vec3::new(85.45, -5.0, 2.52).to_array()))
@@ -26,7 +189,128 @@ impl Camera {
         Self {
             fov,
             clipping_planes: ClippingPlanes { near, far },
+            viewport: Default::default(),
+            reflection_plane: Default::default(),
+            is_fog_disabled: false,
+            depth_of_field: Default::default(),
+            clear_behavior: Default::default(),
+            order: Default::default(),
+        }
+    }
+
+    /// Cameras above `MAX_ACTIVE_CAMERAS_COUNT` by their order are dropped. Sort is stable,
+    /// so cameras of the same order are kept in the order of the query.
+    pub(crate) fn sort_active<'a, T>(
+        cameras: impl IntoIterator<Item = (&'a Camera, T)>,
+    ) -> Vec<(&'a Camera, T)> {
+        let mut cameras: Vec<_> = cameras.into_iter().collect();
+        cameras.sort_by_key(|(camera, _)| camera.order);
+        cameras.truncate(Self::MAX_ACTIVE_CAMERAS_COUNT);
+
+        cameras
+    }
+
+    pub fn with_viewport(mut self, viewport: CameraViewport) -> Self {
+        self.viewport = viewport;
+
+        self
+    }
+
+    pub fn without_fog(mut self) -> Self {
+        self.is_fog_disabled = true;
+
+        self
+    }
+
+    pub fn with_depth_of_field(mut self, depth_of_field: DepthOfField) -> Self {
+        self.depth_of_field = Some(depth_of_field);
+
+        self
+    }
+
+    pub fn with_clear_behavior(mut self, clear_behavior: ClearBehavior) -> Self {
+        self.clear_behavior = clear_behavior;
+
+        self
+    }
+
+    pub fn with_reflection_plane(mut self, reflection_plane: ReflectionPlane) -> Self {
+        self.reflection_plane = Some(reflection_plane);
+
+        self
+    }
+
+    pub fn with_order(mut self, order: i32) -> Self {
+        self.order = order;
+
+        self
+    }
+
+    /// Reverse-Z projection, the near plane is at the depth of one and the far one is at zero.
+    pub fn get_projection(&self, aspect_ratio: f32) -> Mat4 {
+        Mat4::perspective_rh(
+            self.fov.to_radians(),
+            aspect_ratio.max(f32::EPSILON),
+            self.clipping_planes.far,
+            self.clipping_planes.near,
+        )
+    }
+
+    /// World position of the texel at `screen_position` in pixels of the render target with its
+    /// `depth`, e.g. read back by `DepthPicking`. It's `None` for the background and positions out
+    /// of the viewport. Shake and the reflection plane aren't applied, `DepthPicking` reconstructs
+    /// with the matrices the frame was rendered with.
+    pub fn reconstruct_world_position(
+        &self,
+        camera_transform: &LocalTransform,
+        render_size: Vec2,
+        screen_position: Vec2,
+        depth: f32,
+    ) -> Option<Vec3> {
+        if depth <= 0.0 {
+            return None;
+        }
+
+        let (viewport_start, viewport_size) = self.get_viewport_bounds(render_size);
+        if viewport_size.min_element() <= 0.0 {
+            return None;
+        }
+
+        let uv = (screen_position.floor() + 0.5 - viewport_start) / viewport_size;
+        if uv.min_element() < 0.0 || uv.max_element() > 1.0 {
+            return None;
         }
+
+        let view = Mat4::from_rotation_translation(
+            camera_transform.get_local_rotation(),
+            camera_transform.get_local_position(),
+        )
+        .inverse();
+        let projection = self.get_projection(viewport_size.x / viewport_size.y);
+
+        // NOTE: Viewport is flipped, so the top of the image is +Y in NDC.
+        let clip_position = Vec4::new(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, depth, 1.0);
+        let world_position = (projection * view).inverse() * clip_position;
+
+        Some(world_position.truncate() / world_position.w)
+    }
+
+    /// Start and size of the viewport in pixels of the render target, they're truncated to whole
+    /// pixels, as they are for rendering.
+    pub(crate) fn get_viewport_bounds(&self, render_size: Vec2) -> (Vec2, Vec2) {
+        let viewport_start = (Vec2::new(self.viewport.x, self.viewport.y)
+            .clamp(Vec2::ZERO, Vec2::ONE)
+            * render_size)
+            .floor();
+        let viewport_end = (Vec2::new(
+            self.viewport.x + self.viewport.width,
+            self.viewport.y + self.viewport.height,
+        )
+        .clamp(Vec2::ZERO, Vec2::ONE)
+            * render_size)
+            .floor();
+
+        (viewport_start, viewport_end - viewport_start)
     }
 
     /*     pub fn get_position(&self) -> Vec3 {
@@ -0,0 +1,149 @@
+use bevy_ecs::{component::Component, entity::Entity};
+use math::{SpringDamper, Vec3};
+
+/// Circling around the target, angles are in degrees.
+#[derive(Clone, Copy, Debug)]
+pub struct OrbitRig {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    pub min_pitch: f32,
+    pub max_pitch: f32,
+}
+
+impl Default for OrbitRig {
+    fn default() -> Self {
+        Self {
+            yaw: Default::default(),
+            pitch: -20.0,
+            distance: 10.0,
+            min_distance: 2.0,
+            max_distance: 50.0,
+            min_pitch: -85.0,
+            max_pitch: 85.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum CameraRigMode {
+    /// Keeps the offset from the target, it's rotated by the yaw of the target.
+    Follow {
+        offset: Vec3,
+    },
+    Orbit(OrbitRig),
+}
+
+impl Default for CameraRigMode {
+    fn default() -> Self {
+        Self::Follow {
+            offset: Vec3::new(0.0, 3.0, 8.0),
+        }
+    }
+}
+
+/// Moves the camera after the game logic, so games don't write its transform themselves.
+/// Camera looks at the target, it's pulled in front of colliders between them, while the collision
+/// is enabled.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct CameraRig {
+    /// Rig is idle without the target or when the target lost its transform.
+    pub target: Option<Entity>,
+    pub mode: CameraRigMode,
+    /// Added to the target position, e.g. to look at the head instead of the feet.
+    pub look_at_offset: Vec3,
+    /// Gap to the colliders, `None` disables the collision.
+    pub collision_radius: Option<f32>,
+    position_spring: SpringDamper<Vec3>,
+    /// Cleared by the snap, the first placement by the rig isn't smoothed.
+    is_placed: bool,
+}
+
+impl Default for CameraRig {
+    fn default() -> Self {
+        Self {
+            target: Default::default(),
+            mode: Default::default(),
+            look_at_offset: Vec3::Y,
+            collision_radius: Some(0.2),
+            position_spring: SpringDamper::new(0.15),
+            is_placed: Default::default(),
+        }
+    }
+}
+
+impl CameraRig {
+    pub fn new(target: Entity) -> Self {
+        Self {
+            target: Some(target),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_mode(mut self, mode: CameraRigMode) -> Self {
+        self.mode = mode;
+
+        self
+    }
+
+    pub fn with_look_at_offset(mut self, look_at_offset: Vec3) -> Self {
+        self.look_at_offset = look_at_offset;
+
+        self
+    }
+
+    pub fn with_collision_radius(mut self, collision_radius: Option<f32>) -> Self {
+        self.collision_radius = collision_radius;
+
+        self
+    }
+
+    /// Seconds to catch up with the target, zero moves the camera instantly.
+    pub fn with_smooth_time(mut self, smooth_time: f32) -> Self {
+        self.position_spring.smooth_time = smooth_time;
+
+        self
+    }
+
+    /// Next update jumps to the target without smoothing, e.g. after a teleport.
+    pub fn snap(&mut self) {
+        self.is_placed = false;
+        self.position_spring.reset();
+    }
+
+    /// Degrees of the yaw and pitch, pitch is clamped by the limits.
+    /// It does nothing in the follow mode.
+    pub fn orbit(&mut self, yaw_delta: f32, pitch_delta: f32) {
+        if let CameraRigMode::Orbit(orbit_rig) = &mut self.mode {
+            orbit_rig.yaw = (orbit_rig.yaw + yaw_delta).rem_euclid(360.0);
+            orbit_rig.pitch =
+                (orbit_rig.pitch + pitch_delta).clamp(orbit_rig.min_pitch, orbit_rig.max_pitch);
+        }
+    }
+
+    /// Distance is clamped by the limits. It does nothing in the follow mode.
+    pub fn zoom(&mut self, distance_delta: f32) {
+        if let CameraRigMode::Orbit(orbit_rig) = &mut self.mode {
+            orbit_rig.distance = (orbit_rig.distance + distance_delta)
+                .clamp(orbit_rig.min_distance, orbit_rig.max_distance);
+        }
+    }
+
+    /// Smoothed position, the first update jumps to the desired one.
+    pub(crate) fn smooth_position(
+        &mut self,
+        current_position: Vec3,
+        desired_position: Vec3,
+        delta_time: f32,
+    ) -> Vec3 {
+        if !std::mem::replace(&mut self.is_placed, true) {
+            return desired_position;
+        }
+
+        self.position_spring
+            .update(current_position, desired_position, delta_time)
+    }
+}
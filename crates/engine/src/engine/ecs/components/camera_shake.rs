@@ -0,0 +1,118 @@
+use bevy_ecs::component::Component;
+use math::{EulerRot, Quat, Vec3, gradient_noise};
+
+/// Trauma based shake of a camera, the offsets are applied on top of its transform only in the
+/// view matrix, so gameplay code still owns `LocalTransform`. Shake is the trauma raised to
+/// `trauma_exponent`, the offsets follow noise curves, so they're smooth at any frame rate.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct CameraShake {
+    trauma: f32,
+    /// Trauma lost per second.
+    pub decay: f32,
+    /// Offsets at the full trauma, they're in the camera space.
+    pub max_translation: Vec3,
+    /// Degrees of the pitch, yaw and roll at the full trauma.
+    pub max_rotation: Vec3,
+    /// Noise samples per second, it's the speed of the shake.
+    pub frequency: f32,
+    pub trauma_exponent: f32,
+    seed: u32,
+    elapsed_time: f32,
+    translation: Vec3,
+    rotation: Quat,
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        Self {
+            trauma: Default::default(),
+            decay: 1.0,
+            max_translation: Vec3::splat(0.1),
+            max_rotation: Vec3::new(2.0, 2.0, 4.0),
+            frequency: 15.0,
+            trauma_exponent: 2.0,
+            seed: Default::default(),
+            elapsed_time: Default::default(),
+            translation: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+        }
+    }
+}
+
+impl CameraShake {
+    pub fn with_decay(mut self, decay: f32) -> Self {
+        self.decay = decay;
+
+        self
+    }
+
+    pub fn with_max_translation(mut self, max_translation: Vec3) -> Self {
+        self.max_translation = max_translation;
+
+        self
+    }
+
+    pub fn with_max_rotation(mut self, max_rotation: Vec3) -> Self {
+        self.max_rotation = max_rotation;
+
+        self
+    }
+
+    pub fn with_frequency(mut self, frequency: f32) -> Self {
+        self.frequency = frequency;
+
+        self
+    }
+
+    /// Cameras with different seeds don't shake in sync.
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.seed = seed;
+
+        self
+    }
+
+    #[inline(always)]
+    pub fn get_trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    #[inline(always)]
+    pub fn set_trauma(&mut self, trauma: f32) {
+        self.trauma = trauma.clamp(0.0, 1.0);
+    }
+
+    /// Hits stack up to the full trauma.
+    #[inline(always)]
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.set_trauma(self.trauma + trauma);
+    }
+
+    /// Offsets in the camera space of the current frame.
+    #[inline(always)]
+    pub fn get_offset(&self) -> (Vec3, Quat) {
+        (self.translation, self.rotation)
+    }
+
+    pub(crate) fn update(&mut self, delta_time: f32) {
+        self.elapsed_time += delta_time;
+        if self.trauma <= 0.0 {
+            self.translation = Vec3::ZERO;
+            self.rotation = Quat::IDENTITY;
+
+            return;
+        }
+
+        let shake = self.trauma.powf(self.trauma_exponent);
+        let x = self.elapsed_time * self.frequency;
+        let get_noise = |axis: u32| gradient_noise(x, self.seed.wrapping_mul(6).wrapping_add(axis));
+
+        self.translation =
+            Vec3::new(get_noise(0), get_noise(1), get_noise(2)) * self.max_translation * shake;
+        let angles = Vec3::new(get_noise(3), get_noise(4), get_noise(5))
+            * self.max_rotation.map(f32::to_radians)
+            * shake;
+        self.rotation = Quat::from_euler(EulerRot::YXZ, angles.y, angles.x, angles.z);
+
+        self.set_trauma(self.trauma - self.decay * delta_time);
+    }
+}
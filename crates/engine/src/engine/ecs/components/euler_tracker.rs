@@ -0,0 +1,59 @@
+use bevy_ecs::component::Component;
+use math::{EulerRot, Quat};
+
+/// Keeps yaw and pitch of the camera as is, instead of restoring them from the rotation
+/// every frame, which drifts and flips near the poles.
+#[derive(Clone, Copy, Component, Debug)]
+pub struct EulerTracker {
+    pub yaw_degrees: f32,
+    pub pitch_degrees: f32,
+    pub min_pitch_degrees: f32,
+    pub max_pitch_degrees: f32,
+}
+
+impl EulerTracker {
+    pub fn new(yaw_degrees: f32, pitch_degrees: f32) -> Self {
+        let mut euler_tracker = Self::default();
+        euler_tracker.rotate(yaw_degrees, pitch_degrees);
+
+        euler_tracker
+    }
+
+    pub fn from_rotation(rotation: Quat) -> Self {
+        let (yaw, pitch, _) = rotation.to_euler(EulerRot::YXZ);
+
+        Self::new(yaw.to_degrees(), pitch.to_degrees())
+    }
+
+    pub fn with_pitch_limits(mut self, min_pitch_degrees: f32, max_pitch_degrees: f32) -> Self {
+        self.min_pitch_degrees = min_pitch_degrees;
+        self.max_pitch_degrees = max_pitch_degrees;
+        self.pitch_degrees = self
+            .pitch_degrees
+            .clamp(min_pitch_degrees, max_pitch_degrees);
+
+        self
+    }
+
+    pub fn rotate(&mut self, yaw_delta_degrees: f32, pitch_delta_degrees: f32) {
+        self.yaw_degrees = (self.yaw_degrees + yaw_delta_degrees + 180.0).rem_euclid(360.0) - 180.0;
+        self.pitch_degrees = (self.pitch_degrees + pitch_delta_degrees)
+            .clamp(self.min_pitch_degrees, self.max_pitch_degrees);
+    }
+
+    pub fn get_rotation(&self) -> Quat {
+        Quat::from_rotation_y(self.yaw_degrees.to_radians())
+            * Quat::from_rotation_x(self.pitch_degrees.to_radians())
+    }
+}
+
+impl Default for EulerTracker {
+    fn default() -> Self {
+        Self {
+            yaw_degrees: Default::default(),
+            pitch_degrees: Default::default(),
+            min_pitch_degrees: -89.0,
+            max_pitch_degrees: 89.0,
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::path::PathBuf;
+
+use bevy_ecs::{component::Component, lifecycle::HookContext, world::DeferredWorld};
+use math::Vec2;
+
+use crate::engine::{
+    components::{
+        local_transform::LocalTransform,
+        mesh::{Mesh, release_mesh_resources, retain_mesh_resources},
+    },
+    ecs::{
+        DeferredResource, DeletionQueue,
+        textures_pool::{TextureReference, TexturesPool},
+    },
+    events::LoadFoliageMapsEvent,
+};
+
+/// Scatters instances of the mesh over a rectangle centered at the entity, they're generated
+/// on the GPU every frame for every camera, so dense grass and rocks don't spawn entities.
+/// Instances shrink into the ground between the fade distances and are culled by the camera frustum.
+#[derive(Component, Clone)]
+#[require(LocalTransform)]
+#[component(on_insert = on_foliage_scatter_insert, on_replace = on_foliage_scatter_replace)]
+pub struct FoliageScatter {
+    pub mesh: Mesh,
+    pub size: Vec2,
+    /// Instances per square world unit where the density map is white.
+    pub density: f32,
+    /// Red channel scales the density, the map covers the whole rectangle.
+    pub density_map_path: Option<PathBuf>,
+    /// Heights are read from the luminance as by `Terrain`, so the same heightmap places instances on it.
+    pub height_map_path: Option<PathBuf>,
+    pub height_scale: f32,
+    /// Every instance takes a random scale in the range and a random rotation around the up axis.
+    pub scale_range: Vec2,
+    /// Radius of the mesh around its origin at unit scale, it's used by frustum culling.
+    pub bounding_radius: f32,
+    pub fade_start: f32,
+    pub fade_end: f32,
+    density_map_texture_reference: Option<TextureReference>,
+    height_map_texture_reference: Option<TextureReference>,
+}
+
+impl FoliageScatter {
+    pub fn new(mesh: Mesh, size: Vec2, density: f32) -> Self {
+        Self {
+            mesh,
+            size,
+            density: density.max(f32::EPSILON),
+            density_map_path: Default::default(),
+            height_map_path: Default::default(),
+            height_scale: Default::default(),
+            scale_range: Vec2::new(0.8, 1.2),
+            bounding_radius: 1.0,
+            fade_start: 40.0,
+            fade_end: 60.0,
+            density_map_texture_reference: Default::default(),
+            height_map_texture_reference: Default::default(),
+        }
+    }
+
+    pub fn with_density_map(mut self, density_map_path: impl Into<PathBuf>) -> Self {
+        self.density_map_path = Some(density_map_path.into());
+
+        self
+    }
+
+    pub fn with_height_map(
+        mut self,
+        height_map_path: impl Into<PathBuf>,
+        height_scale: f32,
+    ) -> Self {
+        self.height_map_path = Some(height_map_path.into());
+        self.height_scale = height_scale;
+
+        self
+    }
+
+    pub fn with_scale_range(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.scale_range = Vec2::new(min_scale, max_scale.max(min_scale));
+
+        self
+    }
+
+    pub fn with_bounding_radius(mut self, bounding_radius: f32) -> Self {
+        self.bounding_radius = bounding_radius.max(0.0);
+
+        self
+    }
+
+    pub fn with_fade_distances(mut self, fade_start: f32, fade_end: f32) -> Self {
+        self.fade_start = fade_start;
+        self.fade_end = fade_end.max(fade_start + f32::EPSILON);
+
+        self
+    }
+
+    #[inline(always)]
+    pub(crate) fn get_density_map_texture_reference(&self) -> Option<TextureReference> {
+        self.density_map_texture_reference
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_density_map_texture_reference(
+        &mut self,
+        texture_reference: TextureReference,
+    ) {
+        self.density_map_texture_reference = Some(texture_reference);
+    }
+
+    #[inline(always)]
+    pub(crate) fn get_height_map_texture_reference(&self) -> Option<TextureReference> {
+        self.height_map_texture_reference
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_height_map_texture_reference(&mut self, texture_reference: TextureReference) {
+        self.height_map_texture_reference = Some(texture_reference);
+    }
+
+    fn get_texture_references(&self) -> impl Iterator<Item = TextureReference> {
+        self.density_map_texture_reference
+            .into_iter()
+            .chain(self.height_map_texture_reference)
+    }
+}
+
+fn on_foliage_scatter_insert(mut world: DeferredWorld, context: HookContext) {
+    let foliage_scatter = world.get::<FoliageScatter>(context.entity).unwrap().clone();
+
+    retain_mesh_resources(&mut world, foliage_scatter.mesh);
+
+    // NOTE: Cloned foliages share the uploaded maps.
+    if let Some(mut textures_pool) = world.get_resource_mut::<TexturesPool>() {
+        foliage_scatter
+            .get_texture_references()
+            .for_each(|texture_reference| textures_pool.retain_texture(texture_reference));
+    }
+
+    let is_density_map_missing = foliage_scatter.density_map_path.is_some()
+        && foliage_scatter.density_map_texture_reference.is_none();
+    let is_height_map_missing = foliage_scatter.height_map_path.is_some()
+        && foliage_scatter.height_map_texture_reference.is_none();
+    if is_density_map_missing || is_height_map_missing {
+        world.commands().trigger(LoadFoliageMapsEvent {
+            foliage_entity: context.entity,
+        });
+    }
+}
+
+fn on_foliage_scatter_replace(mut world: DeferredWorld, context: HookContext) {
+    let foliage_scatter = world.get::<FoliageScatter>(context.entity).unwrap().clone();
+
+    release_mesh_resources(&mut world, foliage_scatter.mesh);
+
+    let released_texture_references = world
+        .get_resource_mut::<TexturesPool>()
+        .map(|mut textures_pool| {
+            foliage_scatter
+                .get_texture_references()
+                .filter(|&texture_reference| textures_pool.release_texture(texture_reference))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if let Some(mut deletion_queue) = world.get_resource_mut::<DeletionQueue>() {
+        released_texture_references
+            .into_iter()
+            .for_each(|texture_reference| {
+                deletion_queue.push(DeferredResource::Texture(texture_reference))
+            });
+    }
+}
@@ -0,0 +1,55 @@
+use bevy_ecs::component::Component;
+use vulkanite::vk::DeviceAddress;
+
+use crate::engine::components::local_transform::LocalTransform;
+
+/// Bakes every mesh in the hierarchy of the entity into an octahedral atlas of billboard frames,
+/// instances farther than `distance` from the camera are drawn as a single billboard.
+/// Meshes are baked once and shared by every hierarchy with the same mesh, material and settings.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(LocalTransform)]
+pub struct Impostor {
+    pub distance: f32,
+    /// Frames are captured from directions spread over the whole sphere.
+    pub frames_per_side: u32,
+    /// Size of every frame in texels.
+    pub frame_resolution: u32,
+    is_baked: bool,
+}
+
+impl Impostor {
+    const DEFAULT_FRAMES_PER_SIDE: u32 = 8;
+    const DEFAULT_FRAME_RESOLUTION: u32 = 64;
+
+    pub fn new(distance: f32) -> Self {
+        Self {
+            distance: distance.max(0.0),
+            frames_per_side: Self::DEFAULT_FRAMES_PER_SIDE,
+            frame_resolution: Self::DEFAULT_FRAME_RESOLUTION,
+            is_baked: false,
+        }
+    }
+
+    pub fn with_frames(mut self, frames_per_side: u32, frame_resolution: u32) -> Self {
+        self.frames_per_side = frames_per_side.max(1);
+        self.frame_resolution = frame_resolution.max(1);
+
+        self
+    }
+
+    #[inline(always)]
+    pub fn is_baked(&self) -> bool {
+        self.is_baked
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_baked(&mut self, is_baked: bool) {
+        self.is_baked = is_baked;
+    }
+}
+
+/// Baked impostor of the `Mesh` on the same entity, it's inserted by the bake.
+#[derive(Component, Clone, Copy)]
+pub struct MeshImpostor {
+    pub(crate) device_address_impostor: DeviceAddress,
+}
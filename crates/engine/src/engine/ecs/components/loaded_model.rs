@@ -0,0 +1,9 @@
+use std::path::PathBuf;
+
+use bevy_ecs::component::Component;
+
+/// Parent of a model spawned by `LoadModelEvent`, the model is loaded again when a snapshot restores it.
+#[derive(Component, Clone, Debug)]
+pub struct LoadedModel {
+    pub path: PathBuf,
+}
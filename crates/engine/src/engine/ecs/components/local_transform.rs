@@ -1,5 +1,24 @@
+use std::fmt::Display;
+
 use bevy_ecs::{component::Component, name::Name};
-use math::{EulerRot, Mat4, Quat, Vec3};
+use math::{EulerRot, Mat3, Mat4, Quat, Vec3};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TransformError {
+    /// Own scale is zero on an axis, so the parent can't be recovered from the global transform.
+    DegenerateLocalScale,
+}
+
+impl Display for TransformError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DegenerateLocalScale => write!(f, "local scale is zero on an axis"),
+        }
+    }
+}
+
+impl std::error::Error for TransformError {}
 
 #[derive(Clone, Copy, Component, Debug)]
 #[require(GlobalTransform, Name)]
@@ -40,6 +59,16 @@ impl LocalTransform {
         self.local_rotation = rot;
     }
 
+    /// Rotates around own axes.
+    pub fn rotate_local(&mut self, rotation: Quat) {
+        self.local_rotation = (self.local_rotation * rotation).normalize();
+    }
+
+    /// Rotates around axes of the parent space.
+    pub fn rotate_parent(&mut self, rotation: Quat) {
+        self.local_rotation = (rotation * self.local_rotation).normalize();
+    }
+
     pub fn get_local_euler_angles(&self) -> Vec3 {
         let (y, x, z) = self.local_rotation.to_euler(EulerRot::YXZ);
         Vec3::new(x.to_degrees(), y.to_degrees(), z.to_degrees())
@@ -81,10 +110,37 @@ impl LocalTransform {
             return;
         }
 
-        let rotation_matrix = Mat4::look_at_rh(Vec3::ZERO, forward, world_up).inverse();
+        // NOTE: Looking along the up axis degenerates the basis, any perpendicular up will do.
+        let up = if forward.cross(world_up).length_squared() <= f32::EPSILON {
+            forward.any_orthonormal_vector()
+        } else {
+            world_up
+        };
+
+        let rotation_matrix = Mat4::look_at_rh(Vec3::ZERO, forward, up).inverse();
         self.local_rotation = Quat::from_mat4(&rotation_matrix);
     }
 
+    pub fn rotate_around(&mut self, point: Vec3, axis: Vec3, angle_degrees: f32) {
+        let axis = axis.normalize_or_zero();
+        if axis == Vec3::ZERO {
+            return;
+        }
+
+        let rotation = Quat::from_axis_angle(axis, angle_degrees.to_radians());
+        self.local_position = point + rotation * (self.local_position - point);
+        self.local_rotation = (rotation * self.local_rotation).normalize();
+    }
+
+    /// Position and scale are interpolated linearly, rotation spherically.
+    pub fn lerp(&self, other: &LocalTransform, t: f32) -> LocalTransform {
+        LocalTransform {
+            local_position: self.local_position.lerp(other.local_position, t),
+            local_rotation: self.local_rotation.slerp(other.local_rotation, t),
+            local_scale: self.local_scale.lerp(other.local_scale, t),
+        }
+    }
+
     #[inline(always)]
     pub fn local_to_world_matrix(&self) -> Mat4 {
         Mat4::from_scale_rotation_translation(
@@ -93,6 +149,87 @@ impl LocalTransform {
             self.local_position,
         )
     }
+
+    /// World matrix of the parent, that produced `global_matrix`.
+    pub fn get_parent_matrix(&self, global_matrix: &Mat4) -> Result<Mat4, TransformError> {
+        if !is_scale_invertible(self.local_scale) {
+            return Err(TransformError::DegenerateLocalScale);
+        }
+
+        Ok(*global_matrix * self.local_to_world_matrix().inverse())
+    }
+
+    /// Parent scaled to zero on an axis can't be inverted, so its decomposition is inverted per axis,
+    /// the flattened axis keeps the current local coordinate.
+    pub fn set_world_position(&mut self, position: Vec3, parent_matrix: &Mat4) {
+        let (parent_scale, parent_rotation, parent_position) =
+            to_scale_rotation_translation(parent_matrix);
+        if is_scale_invertible(parent_scale) {
+            self.local_position = parent_matrix.inverse().transform_point3(position);
+
+            return;
+        }
+
+        let scaled_position = parent_rotation.inverse() * (position - parent_position);
+        self.local_position = Vec3::select(
+            parent_scale.abs().cmpgt(Vec3::splat(f32::EPSILON)),
+            scaled_position / parent_scale,
+            self.local_position,
+        );
+    }
+
+    /// Scale of the parent isn't compensated, only its rotation.
+    pub fn set_world_rotation(&mut self, rotation: Quat, parent_matrix: &Mat4) {
+        let (_, parent_rotation, _) = to_scale_rotation_translation(parent_matrix);
+        self.local_rotation = (parent_rotation.inverse() * rotation).normalize();
+    }
+}
+
+#[inline(always)]
+fn is_scale_invertible(scale: Vec3) -> bool {
+    scale.abs().cmpgt(Vec3::splat(f32::EPSILON)).all()
+}
+
+/// Matrix scaled to zero on an axis gets the missing axes of its rotation rebuilt from the others,
+/// so the rotation stays valid, where `Mat4::to_scale_rotation_translation` gives NaNs.
+pub(crate) fn to_scale_rotation_translation(matrix: &Mat4) -> (Vec3, Quat, Vec3) {
+    let translation = matrix.w_axis.truncate();
+    let axes = [
+        matrix.x_axis.truncate(),
+        matrix.y_axis.truncate(),
+        matrix.z_axis.truncate(),
+    ];
+    if is_scale_invertible(Vec3::from_array(axes.map(Vec3::length))) {
+        return matrix.to_scale_rotation_translation();
+    }
+
+    let (x_axis, y_axis) = match axes.map(Vec3::try_normalize) {
+        [Some(x_axis), Some(y_axis), _] => (x_axis, y_axis),
+        [Some(x_axis), None, Some(z_axis)] => (x_axis, z_axis.cross(x_axis)),
+        [None, Some(y_axis), Some(z_axis)] => (y_axis.cross(z_axis), y_axis),
+        [Some(x_axis), None, None] => (x_axis, x_axis.any_orthonormal_vector()),
+        [None, Some(y_axis), None] => (y_axis.any_orthonormal_vector(), y_axis),
+        [None, None, Some(z_axis)] => {
+            let x_axis = z_axis.any_orthonormal_vector();
+
+            (x_axis, z_axis.cross(x_axis))
+        }
+        [None, None, None] => return (Vec3::ZERO, Quat::IDENTITY, translation),
+    };
+    let z_axis = x_axis
+        .cross(y_axis)
+        .try_normalize()
+        .unwrap_or_else(|| x_axis.any_orthonormal_vector());
+    let y_axis = z_axis.cross(x_axis);
+
+    let scale = Vec3::new(
+        axes[0].dot(x_axis),
+        axes[1].dot(y_axis),
+        axes[2].dot(z_axis),
+    );
+    let rotation = Quat::from_mat3(&Mat3::from_cols(x_axis, y_axis, z_axis)).normalize();
+
+    (scale, rotation, translation)
 }
 
 impl Default for LocalTransform {
@@ -104,6 +241,23 @@ impl Default for LocalTransform {
 #[derive(Component, Clone, Copy)]
 pub struct GlobalTransform(pub Mat4);
 
+impl GlobalTransform {
+    #[inline(always)]
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        self.0.transform_point3(point)
+    }
+
+    /// `None` if any axis of the transform is scaled to zero.
+    pub fn inverse_transform_point(&self, point: Vec3) -> Option<Vec3> {
+        is_invertible(&self.0).then(|| self.0.inverse().transform_point3(point))
+    }
+}
+
+#[inline(always)]
+pub(crate) fn is_invertible(matrix: &Mat4) -> bool {
+    matrix.determinant().abs() > f32::EPSILON
+}
+
 impl Default for GlobalTransform {
     #[inline(always)]
     fn default() -> Self {
@@ -114,3 +268,158 @@ impl Default for GlobalTransform {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use math::{EulerRot, Mat4, Quat, Vec3};
+
+    use super::{LocalTransform, TransformError, to_scale_rotation_translation};
+
+    const TOLERANCE: f32 = 1e-4;
+
+    fn assert_rotation_eq(actual: Quat, expected: Quat) {
+        // NOTE: Quaternion and its negation are the same rotation.
+        assert!(
+            actual.dot(expected).abs() > 1.0 - TOLERANCE,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    fn get_parent_matrix(scale: Vec3) -> Mat4 {
+        Mat4::from_scale_rotation_translation(
+            scale,
+            Quat::from_euler(EulerRot::YXZ, 0.7, -0.3, 1.2),
+            Vec3::new(4.0, -2.0, 9.0),
+        )
+    }
+
+    #[test]
+    fn world_position_round_trips_under_scaled_parents() {
+        let position = Vec3::new(1.5, -3.0, 7.25);
+        for parent_scale in [
+            Vec3::ONE,
+            Vec3::splat(0.01),
+            Vec3::new(3.0, 0.5, 2.0),
+            Vec3::new(-1.0, 2.0, 1.0),
+            Vec3::new(-2.0, -0.5, 4.0),
+        ] {
+            let parent_matrix = get_parent_matrix(parent_scale);
+            let mut local_transform = LocalTransform::IDENTITY;
+
+            local_transform.set_world_position(position, &parent_matrix);
+
+            let world_position = parent_matrix.transform_point3(local_transform.local_position);
+            assert!(
+                world_position.abs_diff_eq(position, TOLERANCE * position.length()),
+                "{parent_scale:?}: {world_position:?} != {position:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn world_transform_round_trips_through_parent_matrix() {
+        let parent_matrix = get_parent_matrix(Vec3::new(2.0, -1.0, 0.5));
+        let local_transform = LocalTransform::new(
+            Vec3::new(1.0, 2.0, 3.0),
+            Quat::from_rotation_y(0.4),
+            Vec3::new(0.5, 2.0, -1.0),
+        );
+        let global_matrix = parent_matrix * local_transform.local_to_world_matrix();
+
+        let recovered_parent_matrix = local_transform.get_parent_matrix(&global_matrix).unwrap();
+
+        assert!(recovered_parent_matrix.abs_diff_eq(parent_matrix, TOLERANCE));
+    }
+
+    #[test]
+    fn zero_scaled_parent_keeps_flattened_axis() {
+        let parent_matrix = get_parent_matrix(Vec3::new(2.0, 0.0, 1.0));
+        let mut local_transform = LocalTransform::IDENTITY;
+        local_transform.local_position = Vec3::new(0.0, 5.0, 0.0);
+
+        let position = parent_matrix.transform_point3(Vec3::new(1.0, 0.0, -2.0));
+        local_transform.set_world_position(position, &parent_matrix);
+
+        assert!(local_transform.local_position.is_finite());
+        assert!(
+            local_transform
+                .local_position
+                .abs_diff_eq(Vec3::new(1.0, 5.0, -2.0), TOLERANCE)
+        );
+    }
+
+    #[test]
+    fn zero_scaled_parent_keeps_valid_rotation() {
+        let rotation = Quat::from_rotation_x(0.5);
+        for parent_scale in [
+            Vec3::new(0.0, 1.0, 1.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::ZERO,
+        ] {
+            let parent_matrix = get_parent_matrix(parent_scale);
+            let mut local_transform = LocalTransform::IDENTITY;
+
+            local_transform.set_world_rotation(rotation, &parent_matrix);
+
+            assert!(
+                local_transform.local_rotation.is_normalized(),
+                "{parent_scale:?}: {:?}",
+                local_transform.local_rotation
+            );
+        }
+
+        let parent_matrix = get_parent_matrix(Vec3::new(1.0, 0.0, 1.0));
+        let (_, parent_rotation, _) = to_scale_rotation_translation(&parent_matrix);
+        assert_rotation_eq(
+            parent_rotation,
+            Quat::from_euler(EulerRot::YXZ, 0.7, -0.3, 1.2),
+        );
+    }
+
+    #[test]
+    fn zero_local_scale_is_an_error() {
+        let local_transform =
+            LocalTransform::new(Vec3::ZERO, Quat::IDENTITY, Vec3::new(1.0, 0.0, 1.0));
+
+        assert_eq!(
+            local_transform.get_parent_matrix(&Mat4::IDENTITY),
+            Err(TransformError::DegenerateLocalScale)
+        );
+    }
+
+    #[test]
+    fn near_gimbal_rotations_round_trip() {
+        let parent_matrix = get_parent_matrix(Vec3::new(1.0, 2.0, 3.0));
+        let (_, parent_rotation, _) = to_scale_rotation_translation(&parent_matrix);
+        for pitch_degrees in [89.9f32, 90.0, -89.9, -90.0] {
+            let rotation = Quat::from_euler(EulerRot::YXZ, 0.3, pitch_degrees.to_radians(), -0.2);
+            let mut local_transform = LocalTransform::IDENTITY;
+
+            local_transform.set_world_rotation(rotation, &parent_matrix);
+            assert_rotation_eq(parent_rotation * local_transform.local_rotation, rotation);
+
+            local_transform.local_rotation = rotation;
+            local_transform.set_local_euler_angles(local_transform.get_local_euler_angles());
+            assert_rotation_eq(local_transform.local_rotation, rotation);
+        }
+    }
+
+    #[test]
+    fn negative_scale_survives_local_matrix() {
+        let local_transform = LocalTransform::new(
+            Vec3::new(1.0, 2.0, 3.0),
+            Quat::from_rotation_z(0.3),
+            Vec3::new(-1.0, 1.0, 1.0),
+        );
+        let point = Vec3::new(1.0, 0.0, 0.0);
+
+        let transformed_point = local_transform
+            .local_to_world_matrix()
+            .transform_point3(point);
+
+        let expected_point = Vec3::new(1.0, 2.0, 3.0) + Quat::from_rotation_z(0.3) * -point;
+        assert!(transformed_point.abs_diff_eq(expected_point, TOLERANCE));
+    }
+}
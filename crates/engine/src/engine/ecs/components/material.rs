@@ -1,8 +1,8 @@
 use bytemuck::{Pod, Zeroable};
-use math::Vec4;
+use math::{Vec3, Vec4};
 use vulkanite::vk::DeviceAddress;
 
-#[derive(Default, Clone, Copy)]
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 #[repr(u8)]
 pub enum MaterialType {
     #[default]
@@ -21,6 +21,13 @@ pub struct MaterialProperties {
     pub base_color: [f32; 4],
     pub metallic_value: f32,
     pub roughness_value: f32,
+    /// Added to the lit color, it's not limited to one.
+    pub emissive_color: [f32; 3],
+    /// Strength of the glossy layer over the surface, zero disables it.
+    pub clearcoat: f32,
+    pub clearcoat_roughness: f32,
+    /// Share of the light passing through, it's only seen on transparent materials.
+    pub transmission: f32,
 }
 
 impl MaterialProperties {
@@ -29,8 +36,31 @@ impl MaterialProperties {
             base_color: base_color.to_array(),
             metallic_value,
             roughness_value,
+            emissive_color: Default::default(),
+            clearcoat: Default::default(),
+            clearcoat_roughness: Default::default(),
+            transmission: Default::default(),
         }
     }
+
+    pub fn with_emissive_color(mut self, emissive_color: Vec3) -> Self {
+        self.emissive_color = emissive_color.to_array();
+
+        self
+    }
+
+    pub fn with_clearcoat(mut self, clearcoat: f32, clearcoat_roughness: f32) -> Self {
+        self.clearcoat = clearcoat;
+        self.clearcoat_roughness = clearcoat_roughness;
+
+        self
+    }
+
+    pub fn with_transmission(mut self, transmission: f32) -> Self {
+        self.transmission = transmission;
+
+        self
+    }
 }
 
 #[repr(C)]
@@ -39,6 +69,9 @@ pub struct MaterialTextures {
     pub albedo_texture_index: u32,
     pub metallic_texture_index: u32,
     pub roughness_texture_index: u32,
+    pub albedo_sampler_index: u32,
+    pub metallic_sampler_index: u32,
+    pub roughness_sampler_index: u32,
 }
 
 impl MaterialTextures {
@@ -51,8 +84,66 @@ impl MaterialTextures {
             albedo_texture_index,
             metallic_texture_index,
             roughness_texture_index,
+            albedo_sampler_index: Default::default(),
+            metallic_sampler_index: Default::default(),
+            roughness_sampler_index: Default::default(),
         }
     }
+
+    pub fn with_sampler_indices(
+        mut self,
+        albedo_sampler_index: u32,
+        metallic_sampler_index: u32,
+        roughness_sampler_index: u32,
+    ) -> Self {
+        self.albedo_sampler_index = albedo_sampler_index;
+        self.metallic_sampler_index = metallic_sampler_index;
+        self.roughness_sampler_index = roughness_sampler_index;
+
+        self
+    }
+}
+
+/// Blends up to `MAX_LAYERS_COUNT` layer textures by the channels of the splat texture,
+/// layers are sampled with the albedo sampler and repeated `layer_tiling` times over the mesh.
+#[repr(C)]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct MaterialSplat {
+    pub splat_texture_index: u32,
+    pub layer_texture_indices: [u32; MaterialSplat::MAX_LAYERS_COUNT],
+    /// Zero for materials without splat mapping.
+    pub layers_count: u32,
+    pub layer_tiling: f32,
+}
+
+impl MaterialSplat {
+    pub const MAX_LAYERS_COUNT: usize = 4;
+
+    pub fn new(splat_texture_index: u32, layer_texture_indices: &[u32], layer_tiling: f32) -> Self {
+        let layers_count = layer_texture_indices.len().min(Self::MAX_LAYERS_COUNT);
+
+        let mut material_splat = Self {
+            splat_texture_index,
+            layers_count: layers_count as _,
+            layer_tiling,
+            ..Default::default()
+        };
+        material_splat.layer_texture_indices[..layers_count]
+            .copy_from_slice(&layer_texture_indices[..layers_count]);
+
+        material_splat
+    }
+}
+
+/// Octahedral atlas of the baked frames, every texel holds UVs of the mesh in red and green
+/// and the coverage in alpha, colors are still sampled from the albedo texture.
+#[repr(C)]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct MaterialImpostor {
+    pub atlas_texture_index: u32,
+    /// Zero for materials that aren't impostors.
+    pub frames_per_side: u32,
+    pub frame_resolution: u32,
 }
 
 #[repr(C)]
@@ -60,7 +151,8 @@ impl MaterialTextures {
 pub struct MaterialData {
     pub material_properties: MaterialProperties,
     pub material_textures: MaterialTextures,
-    pub sampler_index: u32,
+    pub material_splat: MaterialSplat,
+    pub material_impostor: MaterialImpostor,
 }
 
 pub struct Material {
@@ -0,0 +1,78 @@
+use std::ops::{Add, Mul};
+
+use bevy_ecs::component::Component;
+use math::{Vec3, Vec4};
+
+/// Keys are linearly interpolated by the elapsed time, a looped curve repeats after its last key.
+#[derive(Clone, Debug)]
+pub struct MaterialCurve<T> {
+    keys: Vec<(f32, T)>,
+    is_looped: bool,
+}
+
+impl<T> MaterialCurve<T>
+where
+    T: Copy + Add<Output = T> + Mul<f32, Output = T>,
+{
+    /// Keys are sorted by their time, so they can be passed in any order.
+    pub fn new(mut keys: Vec<(f32, T)>, is_looped: bool) -> Self {
+        keys.sort_by(|(time, _), (other_time, _)| time.total_cmp(other_time));
+
+        Self { keys, is_looped }
+    }
+
+    pub fn evaluate(&self, time: f32) -> Option<T> {
+        let (first_time, first_value) = *self.keys.first()?;
+        let (last_time, last_value) = *self.keys.last()?;
+
+        let duration = last_time - first_time;
+        let time = if self.is_looped && duration > f32::EPSILON {
+            first_time + (time - first_time).rem_euclid(duration)
+        } else {
+            time
+        };
+
+        if time <= first_time {
+            return Some(first_value);
+        }
+        if time >= last_time {
+            return Some(last_value);
+        }
+
+        let next_key_index = self.keys.partition_point(|&(key_time, _)| key_time <= time);
+        let (previous_time, previous_value) = self.keys[next_key_index - 1];
+        let (next_time, next_value) = self.keys[next_key_index];
+        let factor = (time - previous_time) / (next_time - previous_time).max(f32::EPSILON);
+
+        Some(previous_value * (1.0 - factor) + next_value * factor)
+    }
+}
+
+/// Animates properties of the material of the `Mesh` on the same entity,
+/// every mesh sharing the material is affected.
+#[derive(Component, Clone, Default, Debug)]
+pub struct AnimateMaterial {
+    pub base_color: Option<MaterialCurve<Vec4>>,
+    pub emissive_color: Option<MaterialCurve<Vec3>>,
+    pub roughness: Option<MaterialCurve<f32>>,
+}
+
+impl AnimateMaterial {
+    pub fn with_base_color(mut self, base_color: MaterialCurve<Vec4>) -> Self {
+        self.base_color = Some(base_color);
+
+        self
+    }
+
+    pub fn with_emissive_color(mut self, emissive_color: MaterialCurve<Vec3>) -> Self {
+        self.emissive_color = Some(emissive_color);
+
+        self
+    }
+
+    pub fn with_roughness(mut self, roughness: MaterialCurve<f32>) -> Self {
+        self.roughness = Some(roughness);
+
+        self
+    }
+}
@@ -1,8 +1,13 @@
-use bevy_ecs::component::Component;
+use bevy_ecs::{component::Component, lifecycle::HookContext, world::DeferredWorld};
 
 use crate::engine::{
     components::local_transform::LocalTransform,
-    ecs::{Vertex, materials_pool::MaterialReference, mesh_buffers_pool::MeshBufferReference},
+    ecs::{
+        DeferredResource, DeletionQueue, Vertex,
+        materials_pool::{MaterialReference, MaterialsPool},
+        mesh_buffers_pool::{MeshBufferReference, MeshBuffersPool},
+        textures_pool::TexturesPool,
+    },
 };
 
 #[derive(Component)]
@@ -11,9 +16,70 @@ pub struct MeshData {
     pub indices: Vec<u32>,
 }
 
+/// Every mesh holds a reference to its mesh buffer and material,
+/// the last removed mesh releases them and their GPU resources through `DeletionQueue`.
 #[derive(Component, Clone, Copy)]
 #[require(LocalTransform)]
+#[component(on_insert = on_mesh_insert, on_replace = on_mesh_replace)]
 pub struct Mesh {
     pub(crate) mesh_buffer_reference: MeshBufferReference,
     pub(crate) material_reference: MaterialReference,
 }
+
+fn on_mesh_insert(mut world: DeferredWorld, context: HookContext) {
+    let mesh = *world.get::<Mesh>(context.entity).unwrap();
+
+    retain_mesh_resources(&mut world, mesh);
+}
+
+fn on_mesh_replace(mut world: DeferredWorld, context: HookContext) {
+    let mesh = *world.get::<Mesh>(context.entity).unwrap();
+
+    release_mesh_resources(&mut world, mesh);
+}
+
+/// Retains the mesh buffer and the material, components holding a mesh call it from their insert hook.
+pub(crate) fn retain_mesh_resources(world: &mut DeferredWorld, mesh: Mesh) {
+    if let Some(mut mesh_buffers_pool) = world.get_resource_mut::<MeshBuffersPool>() {
+        mesh_buffers_pool.retain_mesh_buffer(mesh.mesh_buffer_reference);
+    }
+    if let Some(mut materials_pool) = world.get_resource_mut::<MaterialsPool>() {
+        materials_pool.retain_material(mesh.material_reference);
+    }
+}
+
+/// Releases the mesh buffer and the material, the last release frees them through `DeletionQueue`.
+pub(crate) fn release_mesh_resources(world: &mut DeferredWorld, mesh: Mesh) {
+    let mut deferred_resources = Vec::new();
+
+    if let Some(mut mesh_buffers_pool) = world.get_resource_mut::<MeshBuffersPool>()
+        && mesh_buffers_pool.release_mesh_buffer(mesh.mesh_buffer_reference)
+    {
+        deferred_resources.push(DeferredResource::MeshBuffer(mesh.mesh_buffer_reference));
+    }
+
+    let released_texture_references = world
+        .get_resource_mut::<MaterialsPool>()
+        .and_then(|mut materials_pool| materials_pool.release_material(mesh.material_reference));
+    if let Some(released_texture_references) = released_texture_references
+        && let Some(mut textures_pool) = world.get_resource_mut::<TexturesPool>()
+    {
+        deferred_resources.extend(
+            released_texture_references
+                .into_iter()
+                .filter(|&texture_reference| textures_pool.release_texture(texture_reference))
+                .map(DeferredResource::Texture),
+        );
+    }
+
+    if let Some(mut deletion_queue) = world.get_resource_mut::<DeletionQueue>() {
+        deferred_resources
+            .into_iter()
+            .for_each(|deferred_resource| deletion_queue.push(deferred_resource));
+    }
+}
+
+/// Marks a mesh that rarely moves, its instance is kept in a persistent buffer
+/// that is rebuilt only when static meshes are added, removed or moved.
+#[derive(Component, Default, Clone, Copy)]
+pub struct StaticInstance;
@@ -1,5 +1,22 @@
+pub mod billboard;
 pub mod camera;
+pub mod camera_rig;
+pub mod camera_shake;
+pub mod euler_tracker;
+pub mod foliage;
+pub mod impostor;
+pub mod loaded_model;
 pub mod local_transform;
 pub mod material;
+pub mod material_animation;
 pub mod mesh;
+pub mod outline;
+pub mod persistent;
+pub mod point_light;
+pub mod script_vars;
+pub mod sun_occlusion;
+pub mod terrain;
 pub mod time;
+pub mod visibility_query;
+pub mod water;
+pub mod world_cell;
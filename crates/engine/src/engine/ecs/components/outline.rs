@@ -0,0 +1,32 @@
+use bevy_ecs::component::Component;
+
+/// Mesh is outlined by the style of `OutlineSettings::selected`, it wins over `Highlighted`.
+#[derive(Component, Clone, Copy, Default, Debug)]
+pub struct Selected;
+
+/// Mesh is outlined by the style of `OutlineSettings::highlighted`, e.g. while it's hovered.
+#[derive(Component, Clone, Copy, Default, Debug)]
+pub struct Highlighted;
+
+/// Value of the outline mask, zero is left for meshes without an outline.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[repr(u8)]
+pub enum OutlineType {
+    #[default]
+    None,
+    Selected,
+    Highlighted,
+}
+
+impl OutlineType {
+    #[inline(always)]
+    pub fn new(is_selected: bool, is_highlighted: bool) -> Self {
+        if is_selected {
+            Self::Selected
+        } else if is_highlighted {
+            Self::Highlighted
+        } else {
+            Self::None
+        }
+    }
+}
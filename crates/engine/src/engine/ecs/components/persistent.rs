@@ -0,0 +1,13 @@
+use bevy_ecs::component::Component;
+
+/// Entity is saved into snapshots, the id matches it with the restored one, so it must be unique.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Persistent {
+    pub id: u64,
+}
+
+impl Persistent {
+    pub const fn new(id: u64) -> Self {
+        Self { id }
+    }
+}
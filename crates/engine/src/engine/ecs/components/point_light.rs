@@ -0,0 +1,23 @@
+use bevy_ecs::component::Component;
+use math::Vec3;
+
+use crate::engine::components::local_transform::LocalTransform;
+
+/// Light emitted from the position of the entity, it doesn't reach farther than the radius.
+#[derive(Component, Clone, Copy, Debug)]
+#[require(LocalTransform)]
+pub struct PointLight {
+    pub color: Vec3,
+    pub intensity: f32,
+    pub radius: f32,
+}
+
+impl PointLight {
+    pub fn new(color: Vec3, intensity: f32, radius: f32) -> Self {
+        Self {
+            color,
+            intensity,
+            radius,
+        }
+    }
+}
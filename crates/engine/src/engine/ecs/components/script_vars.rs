@@ -0,0 +1,48 @@
+use std::collections::BTreeMap;
+
+use bevy_ecs::component::Component;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ScriptValue {
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+/// Named values of the entity that scripts read and write, so designers can tune them
+/// without new components. They are registered in `TypeRegistry`, so snapshots keep them.
+#[derive(Component, Clone, Default, Debug, Serialize, Deserialize)]
+pub struct ScriptVars {
+    values: BTreeMap<String, ScriptValue>,
+}
+
+impl ScriptVars {
+    pub fn with_value(mut self, name: impl Into<String>, value: ScriptValue) -> Self {
+        self.values.insert(name.into(), value);
+
+        self
+    }
+
+    #[inline(always)]
+    pub fn get(&self, name: &str) -> Option<&ScriptValue> {
+        self.values.get(name)
+    }
+
+    #[inline(always)]
+    pub fn set(&mut self, name: impl Into<String>, value: ScriptValue) {
+        self.values.insert(name.into(), value);
+    }
+
+    #[inline(always)]
+    pub fn remove(&mut self, name: &str) -> Option<ScriptValue> {
+        self.values.remove(name)
+    }
+
+    /// Sorted by the names.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ScriptValue)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+}
@@ -0,0 +1,23 @@
+use bevy_ecs::component::Component;
+
+use crate::engine::components::visibility_query::VisibilityQuery;
+
+/// Visibility query of the sun, it's spawned by the engine while the lens flare is enabled.
+#[derive(Component, Clone, Copy, Default, Debug)]
+#[require(VisibilityQuery)]
+pub struct SunOcclusion {
+    visibility: f32,
+}
+
+impl SunOcclusion {
+    /// Fades between `0.0` when the sun is hidden and `1.0` when it's visible.
+    #[inline(always)]
+    pub fn get_visibility(&self) -> f32 {
+        self.visibility
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_visibility(&mut self, visibility: f32) {
+        self.visibility = visibility;
+    }
+}
@@ -0,0 +1,198 @@
+use std::path::PathBuf;
+
+use bevy_ecs::{component::Component, lifecycle::HookContext, world::DeferredWorld};
+use math::Vec3;
+
+use crate::engine::{
+    components::{local_transform::LocalTransform, material::MaterialSplat},
+    ecs::{
+        DeferredResource, DeletionQueue,
+        materials_pool::{MaterialReference, MaterialsPool},
+        mesh_buffers_pool::{MeshBufferReference, MeshBuffersPool},
+        textures_pool::TexturesPool,
+    },
+};
+
+/// Square terrain centered at the entity, heights are read from the luminance of the heightmap.
+/// It's split into chunks spawned as children, every chunk picks its level of detail
+/// by the distance to the closest camera and is hidden while it's outside of every camera frustum.
+#[derive(Component, Clone, Debug)]
+#[require(LocalTransform)]
+pub struct Terrain {
+    pub heightmap_path: PathBuf,
+    pub size: f32,
+    pub height_scale: f32,
+    pub chunks_per_side: u32,
+    /// Quads per chunk side at the most detailed level, every next level halves it.
+    pub chunk_resolution: u32,
+    /// Camera distances where the chunk switches to the next level of detail.
+    pub lod_distances: Vec<f32>,
+    /// Skirts hang from the chunk borders and hide cracks between neighbouring levels of detail.
+    pub skirt_depth: f32,
+    pub splat_map_path: Option<PathBuf>,
+    pub layer_texture_paths: Vec<PathBuf>,
+    pub layer_tiling: f32,
+    is_loaded: bool,
+}
+
+impl Terrain {
+    const DEFAULT_CHUNKS_PER_SIDE: u32 = 8;
+    const DEFAULT_CHUNK_RESOLUTION: u32 = 64;
+    const DEFAULT_LAYER_TILING: f32 = 32.0;
+
+    pub fn new(heightmap_path: impl Into<PathBuf>, size: f32, height_scale: f32) -> Self {
+        let chunk_size = size / Self::DEFAULT_CHUNKS_PER_SIDE as f32;
+
+        Self {
+            heightmap_path: heightmap_path.into(),
+            size,
+            height_scale,
+            chunks_per_side: Self::DEFAULT_CHUNKS_PER_SIDE,
+            chunk_resolution: Self::DEFAULT_CHUNK_RESOLUTION,
+            lod_distances: vec![chunk_size * 1.5, chunk_size * 3.0, chunk_size * 6.0],
+            skirt_depth: height_scale * 0.05,
+            splat_map_path: Default::default(),
+            layer_texture_paths: Default::default(),
+            layer_tiling: Self::DEFAULT_LAYER_TILING,
+            is_loaded: false,
+        }
+    }
+
+    pub fn with_chunks(mut self, chunks_per_side: u32, chunk_resolution: u32) -> Self {
+        self.chunks_per_side = chunks_per_side.max(1);
+        self.chunk_resolution = chunk_resolution.max(1);
+
+        self
+    }
+
+    pub fn with_lod_distances(mut self, lod_distances: Vec<f32>) -> Self {
+        self.lod_distances = lod_distances;
+
+        self
+    }
+
+    pub fn with_skirt_depth(mut self, skirt_depth: f32) -> Self {
+        self.skirt_depth = skirt_depth;
+
+        self
+    }
+
+    /// Channels of the splat map weight the layer textures in order, extra layers are ignored.
+    pub fn with_splat_map(
+        mut self,
+        splat_map_path: impl Into<PathBuf>,
+        layer_texture_paths: Vec<PathBuf>,
+        layer_tiling: f32,
+    ) -> Self {
+        self.splat_map_path = Some(splat_map_path.into());
+        self.layer_texture_paths = layer_texture_paths;
+        self.layer_texture_paths
+            .truncate(MaterialSplat::MAX_LAYERS_COUNT);
+        self.layer_tiling = layer_tiling;
+
+        self
+    }
+
+    #[inline(always)]
+    pub fn get_chunk_size(&self) -> f32 {
+        self.size / self.chunks_per_side as f32
+    }
+
+    #[inline(always)]
+    pub fn get_lods_count(&self) -> usize {
+        self.lod_distances.len() + 1
+    }
+
+    #[inline(always)]
+    pub fn is_loaded(&self) -> bool {
+        self.is_loaded
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_loaded(&mut self, is_loaded: bool) {
+        self.is_loaded = is_loaded;
+    }
+}
+
+/// Chunk holds every level of detail of its mesh, so swapping `Mesh` between them
+/// never frees a mesh buffer, they are released through `DeletionQueue` with the chunk.
+#[derive(Component, Clone)]
+#[require(LocalTransform)]
+#[component(on_insert = on_terrain_chunk_insert, on_replace = on_terrain_chunk_replace)]
+pub struct TerrainChunk {
+    pub(crate) lod_mesh_buffer_references: Vec<MeshBufferReference>,
+    pub(crate) material_reference: MaterialReference,
+    pub(crate) lod_distances: Vec<f32>,
+    /// Local bounds of the most detailed level including the skirts.
+    pub(crate) bounds_min: Vec3,
+    pub(crate) bounds_max: Vec3,
+    pub(crate) current_lod: Option<usize>,
+}
+
+impl TerrainChunk {
+    pub(crate) fn get_lod(&self, camera_distance: f32) -> usize {
+        self.lod_distances
+            .iter()
+            .position(|&lod_distance| camera_distance < lod_distance)
+            .unwrap_or(self.lod_distances.len())
+            .min(self.lod_mesh_buffer_references.len() - 1)
+    }
+}
+
+fn on_terrain_chunk_insert(mut world: DeferredWorld, context: HookContext) {
+    let terrain_chunk = world.get::<TerrainChunk>(context.entity).unwrap().clone();
+
+    if let Some(mut mesh_buffers_pool) = world.get_resource_mut::<MeshBuffersPool>() {
+        terrain_chunk
+            .lod_mesh_buffer_references
+            .iter()
+            .for_each(|&mesh_buffer_reference| {
+                mesh_buffers_pool.retain_mesh_buffer(mesh_buffer_reference)
+            });
+    }
+    if let Some(mut materials_pool) = world.get_resource_mut::<MaterialsPool>() {
+        materials_pool.retain_material(terrain_chunk.material_reference);
+    }
+}
+
+fn on_terrain_chunk_replace(mut world: DeferredWorld, context: HookContext) {
+    let terrain_chunk = world.get::<TerrainChunk>(context.entity).unwrap().clone();
+
+    let mut deferred_resources = Vec::new();
+
+    if let Some(mut mesh_buffers_pool) = world.get_resource_mut::<MeshBuffersPool>() {
+        deferred_resources.extend(
+            terrain_chunk
+                .lod_mesh_buffer_references
+                .iter()
+                .copied()
+                .filter(|&mesh_buffer_reference| {
+                    mesh_buffers_pool.release_mesh_buffer(mesh_buffer_reference)
+                })
+                .map(DeferredResource::MeshBuffer),
+        );
+    }
+
+    let released_texture_references =
+        world
+            .get_resource_mut::<MaterialsPool>()
+            .and_then(|mut materials_pool| {
+                materials_pool.release_material(terrain_chunk.material_reference)
+            });
+    if let Some(released_texture_references) = released_texture_references
+        && let Some(mut textures_pool) = world.get_resource_mut::<TexturesPool>()
+    {
+        deferred_resources.extend(
+            released_texture_references
+                .into_iter()
+                .filter(|&texture_reference| textures_pool.release_texture(texture_reference))
+                .map(DeferredResource::Texture),
+        );
+    }
+
+    if let Some(mut deletion_queue) = world.get_resource_mut::<DeletionQueue>() {
+        deferred_resources
+            .into_iter()
+            .for_each(|deferred_resource| deletion_queue.push(deferred_resource));
+    }
+}
@@ -6,7 +6,9 @@ use bevy_ecs::resource::Resource;
 #[derive(Resource)]
 pub struct Time {
     delta_time: f32,
+    elapsed_time: f32,
     last_frame: Instant,
+    frame_index: u64,
 }
 
 impl Default for Time {
@@ -19,7 +21,9 @@ impl Time {
     pub fn new() -> Self {
         Self {
             delta_time: Default::default(),
+            elapsed_time: Default::default(),
             last_frame: Instant::now(),
+            frame_index: Default::default(),
         }
     }
 
@@ -28,12 +32,32 @@ impl Time {
         self.delta_time
     }
 
+    /// Sum of the delta times, so it follows the delta time of a replay.
+    #[inline(always)]
+    pub fn get_elapsed_time(&self) -> f32 {
+        self.elapsed_time
+    }
+
+    /// Counts updates, so it's the same for the same frame of a replay.
+    #[inline(always)]
+    pub fn get_frame_index(&self) -> u64 {
+        self.frame_index
+    }
+
     #[inline(always)]
     pub fn update(&mut self) {
         let now = std::time::Instant::now();
         let duration = now.duration_since(self.last_frame);
 
         self.delta_time = duration.as_secs_f32();
+        self.elapsed_time += self.delta_time;
         self.last_frame = now;
+        self.frame_index += 1;
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_delta_time(&mut self, delta_time: f32) {
+        self.elapsed_time += delta_time - self.delta_time;
+        self.delta_time = delta_time;
     }
 }
@@ -0,0 +1,33 @@
+use bevy_ecs::component::Component;
+use math::Vec3;
+
+use crate::engine::components::local_transform::LocalTransform;
+
+/// Tests whether the point at `offset` from the entity is hidden by the depth of the first camera,
+/// e.g. for lens flares and markers. Results arrive once the GPU is done with the frame.
+#[derive(Component, Clone, Copy, Default, Debug)]
+#[require(LocalTransform)]
+pub struct VisibilityQuery {
+    pub offset: Vec3,
+    is_visible: Option<bool>,
+}
+
+impl VisibilityQuery {
+    pub fn new(offset: Vec3) -> Self {
+        Self {
+            offset,
+            is_visible: None,
+        }
+    }
+
+    /// Points outside of the view are never visible, `None` until the first result arrives.
+    #[inline(always)]
+    pub fn is_visible(&self) -> Option<bool> {
+        self.is_visible
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_visible(&mut self, is_visible: bool) {
+        self.is_visible = Some(is_visible);
+    }
+}
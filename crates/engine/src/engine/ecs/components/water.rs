@@ -0,0 +1,124 @@
+use std::path::PathBuf;
+
+use bevy_ecs::{component::Component, lifecycle::HookContext, world::DeferredWorld};
+use math::{Vec2, Vec3};
+
+use crate::engine::{
+    components::local_transform::LocalTransform,
+    ecs::{
+        DeferredResource, DeletionQueue,
+        textures_pool::{TextureReference, TexturesPool},
+    },
+    events::LoadWaterNormalMapEvent,
+};
+
+/// Horizontal water rectangle at the height of the entity, it's drawn by a screen space pass
+/// over the opaque scene, so only the first water surface in the world is rendered.
+/// Surface refracts the scene beneath it and reflects the environment, blended by Fresnel.
+#[derive(Component, Clone)]
+#[require(LocalTransform)]
+#[component(on_insert = on_water_settings_insert, on_replace = on_water_settings_replace)]
+pub struct WaterSettings {
+    pub size: Vec2,
+    pub color: Vec3,
+    /// Distance under the surface where the scene is fully hidden by the water color.
+    pub visibility_distance: f32,
+    /// Without a normal map the surface is perturbed by procedural waves.
+    pub normal_map_path: Option<PathBuf>,
+    /// World units covered by one repeat of the normal map.
+    pub normal_scale: f32,
+    pub normal_strength: f32,
+    /// Two layers of the normal map scroll with these velocities in world units per second.
+    pub scroll_velocities: [Vec2; 2],
+    /// Offset of the refracted scene in pixels at full normal strength.
+    pub refraction_distortion: f32,
+    pub reflection_intensity: f32,
+    normal_map_texture_reference: Option<TextureReference>,
+}
+
+impl WaterSettings {
+    pub fn new(size: Vec2) -> Self {
+        Self {
+            size,
+            color: Vec3::new(0.02, 0.12, 0.16),
+            visibility_distance: 4.0,
+            normal_map_path: Default::default(),
+            normal_scale: 8.0,
+            normal_strength: 0.4,
+            scroll_velocities: [Vec2::new(0.3, 0.1), Vec2::new(-0.2, 0.25)],
+            refraction_distortion: 24.0,
+            reflection_intensity: 1.0,
+            normal_map_texture_reference: Default::default(),
+        }
+    }
+
+    pub fn with_color(mut self, color: Vec3, visibility_distance: f32) -> Self {
+        self.color = color;
+        self.visibility_distance = visibility_distance.max(f32::EPSILON);
+
+        self
+    }
+
+    pub fn with_normal_map(
+        mut self,
+        normal_map_path: impl Into<PathBuf>,
+        normal_scale: f32,
+    ) -> Self {
+        self.normal_map_path = Some(normal_map_path.into());
+        self.normal_scale = normal_scale.max(f32::EPSILON);
+
+        self
+    }
+
+    pub fn with_scroll_velocities(mut self, scroll_velocities: [Vec2; 2]) -> Self {
+        self.scroll_velocities = scroll_velocities;
+
+        self
+    }
+
+    #[inline(always)]
+    pub(crate) fn get_normal_map_texture_reference(&self) -> Option<TextureReference> {
+        self.normal_map_texture_reference
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_normal_map_texture_reference(&mut self, texture_reference: TextureReference) {
+        self.normal_map_texture_reference = Some(texture_reference);
+    }
+}
+
+fn on_water_settings_insert(mut world: DeferredWorld, context: HookContext) {
+    let water_settings = world.get::<WaterSettings>(context.entity).unwrap();
+    let normal_map_path = water_settings.normal_map_path.clone();
+    let normal_map_texture_reference = water_settings.normal_map_texture_reference;
+
+    // NOTE: Cloned settings share the uploaded normal map.
+    if let Some(texture_reference) = normal_map_texture_reference {
+        if let Some(mut textures_pool) = world.get_resource_mut::<TexturesPool>() {
+            textures_pool.retain_texture(texture_reference);
+        }
+    } else if normal_map_path.is_some() {
+        world.commands().trigger(LoadWaterNormalMapEvent {
+            water_entity: context.entity,
+        });
+    }
+}
+
+fn on_water_settings_replace(mut world: DeferredWorld, context: HookContext) {
+    let Some(texture_reference) = world
+        .get::<WaterSettings>(context.entity)
+        .unwrap()
+        .normal_map_texture_reference
+    else {
+        return;
+    };
+
+    let is_texture_released = world
+        .get_resource_mut::<TexturesPool>()
+        .is_some_and(|mut textures_pool| textures_pool.release_texture(texture_reference));
+    if is_texture_released
+        && let Some(mut deletion_queue) = world.get_resource_mut::<DeletionQueue>()
+    {
+        deletion_queue.push(DeferredResource::Texture(texture_reference));
+    }
+}
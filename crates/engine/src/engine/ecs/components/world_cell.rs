@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+
+use bevy_ecs::component::Component;
+
+use crate::engine::components::local_transform::LocalTransform;
+
+/// Part of the world that is streamed in while a camera is within the load radius.
+/// Model is spawned as a child of the cell and despawned with its GPU resources once
+/// every camera is farther than the unload radius.
+#[derive(Component, Clone, Debug)]
+#[require(LocalTransform)]
+pub struct WorldCell {
+    pub model_path: PathBuf,
+    pub load_radius: f32,
+    /// Larger than the load radius, so a camera at the border doesn't reload the cell every frame.
+    pub unload_radius: f32,
+    is_loaded: bool,
+}
+
+impl WorldCell {
+    const UNLOAD_RADIUS_MULTIPLIER: f32 = 1.25;
+
+    pub fn new(model_path: impl Into<PathBuf>, load_radius: f32) -> Self {
+        Self {
+            model_path: model_path.into(),
+            load_radius,
+            unload_radius: load_radius * Self::UNLOAD_RADIUS_MULTIPLIER,
+            is_loaded: false,
+        }
+    }
+
+    pub fn with_unload_radius(mut self, unload_radius: f32) -> Self {
+        self.unload_radius = unload_radius.max(self.load_radius);
+
+        self
+    }
+
+    #[inline(always)]
+    pub fn is_loaded(&self) -> bool {
+        self.is_loaded
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_loaded(&mut self, is_loaded: bool) {
+        self.is_loaded = is_loaded;
+    }
+}
@@ -1,7 +1,12 @@
 use bevy_ecs::query::QueryData;
 use math::{EulerRot, Mat4, Quat, Vec3};
 
-use crate::engine::{LocalTransform, ecs::components::local_transform::GlobalTransform};
+use crate::engine::{
+    LocalTransform,
+    ecs::components::local_transform::{
+        GlobalTransform, TransformError, to_scale_rotation_translation,
+    },
+};
 
 #[derive(QueryData)]
 #[query_data(mutable)]
@@ -42,6 +47,21 @@ impl<'w, 's> TransformItem<'w, 's> {
         self.local.local_rotation = rot;
     }
 
+    pub fn rotate_local(&mut self, rotation: Quat) {
+        self.local.rotate_local(rotation);
+    }
+
+    /// Rotates around axes of the world, based on the last propagated state.
+    pub fn rotate_world(&mut self, rotation: Quat) {
+        let parent_rotation = self
+            .get_parent_matrix()
+            .map(|parent_matrix| to_scale_rotation_translation(&parent_matrix).1)
+            .unwrap_or(Quat::IDENTITY);
+
+        self.local
+            .rotate_parent(parent_rotation.inverse() * rotation * parent_rotation);
+    }
+
     pub fn get_local_euler_angles(&self) -> Vec3 {
         let (y, x, z) = self.local.local_rotation.to_euler(EulerRot::YXZ);
         Vec3::new(x.to_degrees(), y.to_degrees(), z.to_degrees())
@@ -80,13 +100,55 @@ impl<'w, 's> TransformItem<'w, 's> {
     }
 
     pub fn look_at(&mut self, target: Vec3, world_up: Vec3) {
-        let forward = (target - self.local.local_position).normalize_or_zero();
-        if forward == Vec3::ZERO {
-            return;
-        }
+        self.local.look_at(target, world_up);
+    }
+
+    pub fn rotate_around(&mut self, point: Vec3, axis: Vec3, angle_degrees: f32) {
+        self.local.rotate_around(point, axis, angle_degrees);
+    }
+
+    pub fn get_local_transform(&self) -> LocalTransform {
+        *self.local
+    }
+
+    pub fn set_local_transform(&mut self, local_transform: LocalTransform) {
+        *self.local = local_transform;
+    }
+
+    pub fn lerp_to(&mut self, target: &LocalTransform, t: f32) {
+        *self.local = self.local.lerp(target, t);
+    }
+
+    /// World space is known only after propagation, so it's based on the last propagated state.
+    /// Fails and keeps the transform, if its own scale is zero on an axis.
+    pub fn set_position(&mut self, position: Vec3) -> Result<(), TransformError> {
+        let parent_matrix = self.get_parent_matrix()?;
+        self.local.set_world_position(position, &parent_matrix);
+
+        Ok(())
+    }
+
+    /// Fails and keeps the transform, if its own scale is zero on an axis.
+    pub fn set_rotation(&mut self, rotation: Quat) -> Result<(), TransformError> {
+        let parent_matrix = self.get_parent_matrix()?;
+        self.local.set_world_rotation(rotation, &parent_matrix);
 
-        let rotation_matrix = Mat4::look_at_rh(Vec3::ZERO, forward, world_up).inverse();
-        self.local.local_rotation = Quat::from_mat4(&rotation_matrix);
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        self.global.transform_point(point)
+    }
+
+    #[inline(always)]
+    pub fn inverse_transform_point(&self, point: Vec3) -> Option<Vec3> {
+        self.global.inverse_transform_point(point)
+    }
+
+    #[inline(always)]
+    fn get_parent_matrix(&self) -> Result<Mat4, TransformError> {
+        self.local.get_parent_matrix(&self.global.0)
     }
 
     #[inline(always)]
@@ -146,6 +208,20 @@ impl<'w, 's> TransformReadOnlyItem<'w, 's> {
         self.local.local_rotation * Vec3::Y
     }
 
+    pub fn get_local_transform(&self) -> LocalTransform {
+        *self.local
+    }
+
+    #[inline(always)]
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        self.global.transform_point(point)
+    }
+
+    #[inline(always)]
+    pub fn inverse_transform_point(&self, point: Vec3) -> Option<Vec3> {
+        self.global.inverse_transform_point(point)
+    }
+
     #[inline(always)]
     pub fn local_to_world_matrix(&self) -> Mat4 {
         Mat4::from_scale_rotation_translation(
@@ -0,0 +1,138 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use bevy_ecs::resource::Resource;
+
+/// Source files of a model with their modification times, as they were by the last poll.
+struct WatchedModel {
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+/// Development reload of the models spawned by `LoadModelEvent` with a parent, sources are polled,
+/// changed ones are cooked again and respawned under the same `LoadedModel` parents.
+#[derive(Resource)]
+pub struct AssetHotReload {
+    pub is_enabled: bool,
+    /// Seconds between the polls of the source files.
+    pub poll_interval: f32,
+    elapsed_time: f32,
+    watched_models: HashMap<PathBuf, WatchedModel>,
+}
+
+impl Default for AssetHotReload {
+    fn default() -> Self {
+        Self {
+            is_enabled: cfg!(debug_assertions),
+            poll_interval: 0.5,
+            elapsed_time: Default::default(),
+            watched_models: Default::default(),
+        }
+    }
+}
+
+impl AssetHotReload {
+    /// Files next to a `.gltf` with these extensions are its buffers and textures.
+    const GLTF_DEPENDENCY_EXTENSIONS: [&str; 5] = ["bin", "png", "jpg", "jpeg", "ktx2"];
+
+    /// Whether the poll interval has passed since the last poll.
+    pub(crate) fn tick(&mut self, delta_time: f32) -> bool {
+        self.elapsed_time += delta_time;
+        if self.elapsed_time < self.poll_interval {
+            return false;
+        }
+        self.elapsed_time = Default::default();
+
+        true
+    }
+
+    #[inline(always)]
+    pub fn is_watched(&self, model_path: &Path) -> bool {
+        self.watched_models.contains_key(model_path)
+    }
+
+    /// Current times are the baseline, so a model isn't reloaded right after its first load.
+    pub fn watch_model(&mut self, model_path: &Path) {
+        if self.is_watched(model_path) {
+            return;
+        }
+
+        let files = Self::get_source_files(model_path)
+            .into_iter()
+            .map(|path| {
+                let modified_time = Self::get_modified_time(&path);
+
+                (path, modified_time)
+            })
+            .collect();
+        self.watched_models
+            .insert(model_path.to_path_buf(), WatchedModel { files });
+    }
+
+    /// Models that aren't loaded anymore stop being polled.
+    pub(crate) fn retain_models(&mut self, mut is_loaded: impl FnMut(&Path) -> bool) {
+        self.watched_models
+            .retain(|model_path, _| is_loaded(model_path));
+    }
+
+    /// Models with a source written since the last poll, their times become the new baseline.
+    pub(crate) fn poll_changed_models(&mut self) -> Vec<PathBuf> {
+        let mut changed_model_paths = Vec::new();
+        for (model_path, watched_model) in self.watched_models.iter_mut() {
+            let mut is_changed = false;
+            for (path, modified_time) in watched_model.files.iter_mut() {
+                let current_modified_time = Self::get_modified_time(path);
+                if current_modified_time != *modified_time {
+                    *modified_time = current_modified_time;
+                    is_changed = true;
+                }
+            }
+
+            if is_changed {
+                changed_model_paths.push(model_path.clone());
+            }
+        }
+
+        changed_model_paths
+    }
+
+    fn get_source_files(model_path: &Path) -> Vec<PathBuf> {
+        let mut files = vec![model_path.to_path_buf()];
+
+        let is_gltf = model_path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("gltf"));
+        let Some(directory) = model_path.parent().filter(|_| is_gltf) else {
+            return files;
+        };
+        let Ok(read_dir) = std::fs::read_dir(directory) else {
+            return files;
+        };
+
+        files.extend(
+            read_dir
+                .filter_map(|dir_entry| dir_entry.ok())
+                .map(|dir_entry| dir_entry.path())
+                .filter(|path| {
+                    path.extension()
+                        .and_then(|extension| extension.to_str())
+                        .is_some_and(|extension| {
+                            Self::GLTF_DEPENDENCY_EXTENSIONS
+                                .contains(&extension.to_lowercase().as_str())
+                        })
+                }),
+        );
+
+        files
+    }
+
+    #[inline(always)]
+    fn get_modified_time(path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+}
@@ -0,0 +1,30 @@
+/// System clipboard, it's a non-send resource, since some platforms bind it to the main thread.
+/// Without a clipboard, e.g. on a headless session, reads are empty and writes are dropped.
+pub struct Clipboard {
+    clipboard: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    pub(crate) fn new() -> Self {
+        let clipboard = arboard::Clipboard::new()
+            .inspect_err(|error| println!("Clipboard is unavailable: {}", error))
+            .ok();
+
+        Self { clipboard }
+    }
+
+    #[inline(always)]
+    pub fn is_available(&self) -> bool {
+        self.clipboard.is_some()
+    }
+
+    pub fn get_text(&mut self) -> Option<String> {
+        self.clipboard.as_mut()?.get_text().ok()
+    }
+
+    pub fn set_text(&mut self, text: impl Into<String>) -> bool {
+        self.clipboard
+            .as_mut()
+            .is_some_and(|clipboard| clipboard.set_text(text.into()).is_ok())
+    }
+}
@@ -0,0 +1,347 @@
+use std::collections::BTreeMap;
+
+use bevy_ecs::resource::Resource;
+use winit::keyboard::KeyCode;
+
+#[derive(Clone, Debug)]
+pub struct ConsoleCommandInfo {
+    /// Arguments hint shown by `help`, e.g. `<prefab> <count>`.
+    pub usage: String,
+    pub description: String,
+}
+
+/// Developer console: registered commands, input line, history and output log.
+/// Submitted lines are executed in the world update and triggered as `ConsoleCommandEvent`.
+#[derive(Resource)]
+pub struct Console {
+    is_open: bool,
+    toggle_key: Option<KeyCode>,
+    is_toggle_text_skipped: bool,
+    input: String,
+    history: Vec<String>,
+    history_cursor: Option<usize>,
+    output: Vec<String>,
+    commands: BTreeMap<String, ConsoleCommandInfo>,
+    submitted_lines: Vec<String>,
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        let mut console = Self {
+            is_open: false,
+            toggle_key: Some(KeyCode::Backquote),
+            is_toggle_text_skipped: false,
+            input: Default::default(),
+            history: Default::default(),
+            history_cursor: Default::default(),
+            output: Default::default(),
+            commands: Default::default(),
+            submitted_lines: Default::default(),
+        };
+
+        console.register_command("help", "", "Lists registered commands.");
+        console.register_command("clear", "", "Clears the console output.");
+        #[cfg(feature = "capture")]
+        {
+            console.register_command(
+                "dump_frame_graph",
+                "[path]",
+                "Writes the next frame graph as Graphviz DOT.",
+            );
+            console.register_command(
+                "capture_cubemap",
+                "<x> <y> <z> [path] [face_size]",
+                "Renders and saves an HDR environment cubemap as KTX2 from the position.",
+            );
+        }
+        console.register_command(
+            "dump_scene",
+            "[tree|json] [path]",
+            "Prints entities of the scene, or writes them to the path.",
+        );
+        #[cfg(feature = "debug-tools")]
+        {
+            console.register_command("inspector", "", "Toggles the entity inspector.");
+            console.register_command(
+                "memory",
+                "",
+                "Toggles the GPU memory overlay and prints the usage with the largest allocations.",
+            );
+            console.register_command(
+                "texture_usage",
+                "[on|off|reset]",
+                "Counts samples of the textures, unsampled ones are printed by `memory`.",
+            );
+        }
+        #[cfg(feature = "profiler")]
+        {
+            console.register_command(
+                "frame_graph",
+                "",
+                "Toggles the frame time graph and prints the average and 1% low.",
+            );
+        }
+        console.register_command("cvars", "[prefix]", "Lists cvars and their values.");
+        console.register_command("reset", "<cvar>", "Resets the cvar to its default value.");
+        console.register_command(
+            "snapshot_save",
+            "<path>",
+            "Saves persistent entities into the snapshot file.",
+        );
+        console.register_command(
+            "snapshot_load",
+            "<path>",
+            "Restores persistent entities from the snapshot file.",
+        );
+        console.register_command(
+            "script_load",
+            "<path>",
+            "Loads the Lua script, or reloads it.",
+        );
+        console.register_command("script_unload", "<path>", "Stops the Lua script.");
+        console.register_command(
+            "script_reload",
+            "",
+            "Loads all the Lua scripts from their files again.",
+        );
+        console.register_command(
+            "cvars_save",
+            "",
+            "Writes changed cvars into the engine config file.",
+        );
+
+        console
+    }
+}
+
+impl Console {
+    const MAX_HISTORY_LINES: usize = 64;
+    const MAX_OUTPUT_LINES: usize = 256;
+
+    /// Handlers observe `ConsoleCommandEvent` and match its name.
+    pub fn register_command(
+        &mut self,
+        name: impl Into<String>,
+        usage: impl Into<String>,
+        description: impl Into<String>,
+    ) {
+        self.commands.insert(
+            name.into(),
+            ConsoleCommandInfo {
+                usage: usage.into(),
+                description: description.into(),
+            },
+        );
+    }
+
+    pub fn is_command_registered(&self, name: &str) -> bool {
+        self.commands.contains_key(name)
+    }
+
+    /// Sorted by name.
+    pub fn get_commands(&self) -> impl Iterator<Item = (&str, &ConsoleCommandInfo)> {
+        self.commands
+            .iter()
+            .map(|(name, command_info)| (name.as_str(), command_info))
+    }
+
+    #[inline(always)]
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    pub fn set_open(&mut self, is_open: bool) {
+        self.is_open = is_open;
+        self.history_cursor = None;
+    }
+
+    pub fn toggle(&mut self) {
+        self.set_open(!self.is_open);
+        // NOTE: Toggle key also produces text, it mustn't end up in the input line.
+        self.is_toggle_text_skipped = true;
+    }
+
+    pub fn get_toggle_key(&self) -> Option<KeyCode> {
+        self.toggle_key
+    }
+
+    /// Key is handled by the engine and never reaches `Input`, `None` disables the toggle.
+    pub fn set_toggle_key(&mut self, toggle_key: Option<KeyCode>) {
+        self.toggle_key = toggle_key;
+    }
+
+    pub fn get_input(&self) -> &str {
+        &self.input
+    }
+
+    pub fn get_history(&self) -> &[String] {
+        &self.history
+    }
+
+    pub fn get_output(&self) -> &[String] {
+        &self.output
+    }
+
+    pub fn clear_output(&mut self) {
+        self.output.clear();
+    }
+
+    /// Line is printed to stdout as well, until there is a console overlay.
+    pub fn log(&mut self, line: impl Into<String>) {
+        let line = line.into();
+        println!("{}", line);
+
+        if self.output.len() >= Self::MAX_OUTPUT_LINES {
+            self.output.remove(Default::default());
+        }
+        self.output.push(line);
+    }
+
+    /// Queues the line as if it was typed, e.g. from game code or a startup script.
+    pub fn execute(&mut self, line: impl Into<String>) {
+        self.submitted_lines.push(line.into());
+    }
+
+    /// Whether the console took the text, the open console and its toggle key take all of it.
+    pub(crate) fn push_text(&mut self, text: &str) -> bool {
+        if std::mem::take(&mut self.is_toggle_text_skipped) {
+            return true;
+        }
+        if !self.is_open {
+            return false;
+        }
+
+        self.input
+            .extend(text.chars().filter(|character| !character.is_control()));
+
+        true
+    }
+
+    pub(crate) fn erase_last_character(&mut self) {
+        self.input.pop();
+    }
+
+    pub(crate) fn submit_input(&mut self) {
+        let line = std::mem::take(&mut self.input);
+        self.history_cursor = None;
+        if line.trim().is_empty() {
+            return;
+        }
+
+        if self.history.last() != Some(&line) {
+            if self.history.len() >= Self::MAX_HISTORY_LINES {
+                self.history.remove(Default::default());
+            }
+            self.history.push(line.clone());
+        }
+        self.submitted_lines.push(line);
+    }
+
+    pub(crate) fn select_previous_history_line(&mut self) {
+        if self.history.is_empty() {
+            return;
+        }
+
+        let history_cursor = match self.history_cursor {
+            Some(history_cursor) => history_cursor.saturating_sub(1),
+            None => self.history.len() - 1,
+        };
+        self.history_cursor = Some(history_cursor);
+        self.input = self.history[history_cursor].clone();
+    }
+
+    pub(crate) fn select_next_history_line(&mut self) {
+        let Some(history_cursor) = self.history_cursor else {
+            return;
+        };
+
+        if history_cursor + 1 < self.history.len() {
+            self.history_cursor = Some(history_cursor + 1);
+            self.input = self.history[history_cursor + 1].clone();
+        } else {
+            self.history_cursor = None;
+            self.input.clear();
+        }
+    }
+
+    /// Completes the first word of the input by command and cvar names.
+    /// Ambiguous input is extended to the common prefix, then the candidates are logged.
+    pub(crate) fn autocomplete<'a>(&mut self, cvar_names: impl Iterator<Item = &'a str>) {
+        if self.input.is_empty() || self.input.contains(char::is_whitespace) {
+            return;
+        }
+
+        let candidates: Vec<String> = self
+            .commands
+            .keys()
+            .map(String::as_str)
+            .chain(cvar_names)
+            .filter(|name| name.starts_with(self.input.as_str()))
+            .map(str::to_string)
+            .collect();
+        let Some(first_candidate) = candidates.first() else {
+            return;
+        };
+
+        if candidates.len() == 1 {
+            self.input = std::format!("{} ", first_candidate);
+            return;
+        }
+
+        let common_prefix_length = candidates
+            .iter()
+            .skip(1)
+            .map(|candidate| {
+                first_candidate
+                    .chars()
+                    .zip(candidate.chars())
+                    .take_while(|(a, b)| a == b)
+                    .map(|(character, _)| character.len_utf8())
+                    .sum::<usize>()
+            })
+            .min()
+            .unwrap_or(first_candidate.len());
+        if common_prefix_length > self.input.len() {
+            self.input = first_candidate[..common_prefix_length].to_string();
+        } else {
+            self.log(candidates.join("  "));
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn take_submitted_lines(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.submitted_lines)
+    }
+
+    /// Splits by whitespace, double quotes keep spaces inside of an argument.
+    pub(crate) fn parse_line(line: &str) -> Vec<String> {
+        let mut arguments = Vec::new();
+        let mut argument = String::new();
+        let mut is_quoted = false;
+        let mut has_argument = false;
+
+        for character in line.chars() {
+            match character {
+                '"' => {
+                    is_quoted = !is_quoted;
+                    has_argument = true;
+                }
+                character if character.is_whitespace() && !is_quoted => {
+                    if has_argument {
+                        arguments.push(std::mem::take(&mut argument));
+                        has_argument = false;
+                    }
+                }
+                character => {
+                    argument.push(character);
+                    has_argument = true;
+                }
+            }
+        }
+        if has_argument {
+            arguments.push(argument);
+        }
+
+        arguments
+    }
+}
@@ -0,0 +1,166 @@
+#[cfg(feature = "capture")]
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+use bevy_ecs::resource::Resource;
+#[cfg(feature = "capture")]
+use math::Quat;
+use math::Vec3;
+
+#[cfg(feature = "capture")]
+use crate::engine::{
+    LocalTransform, components::camera::Camera, resources::buffers_pool::BufferReference,
+};
+
+pub struct CubemapCaptureRequest {
+    pub position: Vec3,
+    pub path: PathBuf,
+    /// Clamped by the render extent, faces are rendered into its top left corner.
+    pub face_size: u32,
+}
+
+#[cfg(feature = "capture")]
+pub(crate) struct ActiveCubemapCapture {
+    pub(crate) request: CubemapCaptureRequest,
+    pub(crate) face_index: usize,
+    pub(crate) faces: Vec<Vec<u8>>,
+    pub(crate) readback_buffer_reference: BufferReference,
+    /// Face was copied into the readback buffer by the last frame.
+    pub(crate) is_face_copied: bool,
+}
+
+/// Bakes environment cubemaps for IBL probes into KTX2 files with the HDR color before tonemapping.
+/// There is no offscreen path, so the regular frame renders a face per frame instead of the cameras.
+/// Without the `capture` feature it's a no-op, requests are ignored.
+#[cfg(feature = "capture")]
+#[derive(Resource)]
+pub struct CubemapCapture {
+    requests: VecDeque<CubemapCaptureRequest>,
+    active_capture: Option<ActiveCubemapCapture>,
+    pub near: f32,
+    pub far: f32,
+}
+
+#[cfg(feature = "capture")]
+impl Default for CubemapCapture {
+    fn default() -> Self {
+        Self {
+            requests: Default::default(),
+            active_capture: Default::default(),
+            near: 0.1,
+            far: 1000.0,
+        }
+    }
+}
+
+impl CubemapCapture {
+    pub const FACES_COUNT: usize = 6;
+    pub const DEFAULT_FACE_SIZE: u32 = 256;
+    pub const DEFAULT_PATH: &str = "cubemap.ktx2";
+}
+
+#[cfg(feature = "capture")]
+impl CubemapCapture {
+    /// Texel of the draw image, it's `R16G16B16A16Sfloat`.
+    pub(crate) const TEXEL_SIZE: usize = 8;
+
+    /// Forward and up directions in the order of the cube faces: +X, -X, +Y, -Y, +Z, -Z.
+    const FACE_DIRECTIONS: [(Vec3, Vec3); Self::FACES_COUNT] = [
+        (Vec3::X, Vec3::NEG_Y),
+        (Vec3::NEG_X, Vec3::NEG_Y),
+        (Vec3::Y, Vec3::Z),
+        (Vec3::NEG_Y, Vec3::NEG_Z),
+        (Vec3::Z, Vec3::NEG_Y),
+        (Vec3::NEG_Z, Vec3::NEG_Y),
+    ];
+
+    /// Captures are done one after another, a face per frame.
+    pub fn request_capture(&mut self, position: Vec3, path: impl AsRef<Path>, face_size: u32) {
+        self.requests.push_back(CubemapCaptureRequest {
+            position,
+            path: path.as_ref().to_path_buf(),
+            face_size: face_size.max(1),
+        });
+    }
+
+    #[inline(always)]
+    pub fn is_capturing(&self) -> bool {
+        self.active_capture.is_some()
+    }
+
+    pub(crate) fn take_request(&mut self) -> Option<CubemapCaptureRequest> {
+        self.requests.pop_front()
+    }
+
+    pub(crate) fn get_active_capture_mut(&mut self) -> Option<&mut ActiveCubemapCapture> {
+        self.active_capture.as_mut()
+    }
+
+    pub(crate) fn set_active_capture(&mut self, active_capture: ActiveCubemapCapture) {
+        self.active_capture = Some(active_capture);
+    }
+
+    pub(crate) fn take_active_capture(&mut self) -> Option<ActiveCubemapCapture> {
+        self.active_capture.take()
+    }
+
+    /// Camera of the face rendered by this frame with its size in pixels.
+    pub(crate) fn get_face_camera(&self) -> Option<(Camera, LocalTransform, u32)> {
+        let active_capture = self.active_capture.as_ref()?;
+        let &(forward, up) = Self::FACE_DIRECTIONS.get(active_capture.face_index)?;
+
+        let position = active_capture.request.position;
+        let mut transform = LocalTransform::new(position, Quat::IDENTITY, Vec3::ONE);
+        transform.look_at(position + forward, up);
+
+        Some((
+            Camera::new(90.0, self.near, self.far),
+            transform,
+            active_capture.request.face_size,
+        ))
+    }
+
+    /// Readback buffer and the size of the face rendered by this frame.
+    pub(crate) fn get_face_readback(&self) -> Option<(BufferReference, u32)> {
+        self.active_capture.as_ref().map(|active_capture| {
+            (
+                active_capture.readback_buffer_reference,
+                active_capture.request.face_size,
+            )
+        })
+    }
+
+    pub(crate) fn mark_face_copied(&mut self) {
+        if let Some(active_capture) = self.active_capture.as_mut() {
+            active_capture.is_face_copied = true;
+        }
+    }
+}
+
+#[cfg(not(feature = "capture"))]
+#[derive(Resource)]
+pub struct CubemapCapture {
+    pub near: f32,
+    pub far: f32,
+}
+
+#[cfg(not(feature = "capture"))]
+impl Default for CubemapCapture {
+    fn default() -> Self {
+        Self {
+            near: 0.1,
+            far: 1000.0,
+        }
+    }
+}
+
+#[cfg(not(feature = "capture"))]
+impl CubemapCapture {
+    #[inline(always)]
+    pub fn request_capture(&mut self, _position: Vec3, _path: impl AsRef<Path>, _face_size: u32) {}
+
+    #[inline(always)]
+    pub fn is_capturing(&self) -> bool {
+        false
+    }
+}
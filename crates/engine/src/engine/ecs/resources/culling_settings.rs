@@ -0,0 +1,23 @@
+use bevy_ecs::resource::Resource;
+
+#[derive(Resource)]
+pub struct CullingSettings {
+    /// Task shader skips meshlets, that face away from the camera as a whole.
+    pub is_meshlet_cone_culling_enabled: bool,
+    /// Weight of the normal cones while meshlets are built, in `0.0..=1.0`.
+    /// Higher weight gives tighter cones at the cost of less compact meshlets, it applies to meshes loaded afterwards.
+    pub meshlet_cone_weight: f32,
+    /// Min depth pyramid is built after the opaque scene, for passes that read it through
+    /// `EnvironmentData`, e.g. occlusion culling, reflections and ambient occlusion.
+    pub is_depth_pyramid_enabled: bool,
+}
+
+impl Default for CullingSettings {
+    fn default() -> Self {
+        Self {
+            is_meshlet_cone_culling_enabled: true,
+            meshlet_cone_weight: 0.25,
+            is_depth_pyramid_enabled: false,
+        }
+    }
+}
@@ -0,0 +1,358 @@
+use std::{collections::BTreeMap, fmt::Display, path::Path};
+
+use bevy_ecs::resource::Resource;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::resources::{
+    CullingSettings, DefragmentationSettings, Gizmos, LatencyMode, PostProcessSettings,
+    RenderScaleSettings, WindowSettings,
+};
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CVarValue {
+    Bool(bool),
+    Int(i64),
+    Float(f32),
+    String(String),
+}
+
+impl CVarValue {
+    pub fn get_type_name(&self) -> &'static str {
+        match self {
+            Self::Bool(_) => "bool",
+            Self::Int(_) => "int",
+            Self::Float(_) => "float",
+            Self::String(_) => "string",
+        }
+    }
+
+    /// Converts the value to the type of `other`, ints are accepted by floats.
+    fn coerce_like(self, other: &CVarValue) -> Option<CVarValue> {
+        match (self, other) {
+            (Self::Bool(value), Self::Bool(_)) => Some(Self::Bool(value)),
+            (Self::Int(value), Self::Int(_)) => Some(Self::Int(value)),
+            (Self::Int(value), Self::Float(_)) => Some(Self::Float(value as _)),
+            (Self::Float(value), Self::Float(_)) => Some(Self::Float(value)),
+            (Self::String(value), Self::String(_)) => Some(Self::String(value)),
+            _ => None,
+        }
+    }
+
+    fn parse_like(text: &str, other: &CVarValue) -> Option<CVarValue> {
+        match other {
+            Self::Bool(_) => match text {
+                "1" | "true" | "on" => Some(Self::Bool(true)),
+                "0" | "false" | "off" => Some(Self::Bool(false)),
+                _ => None,
+            },
+            Self::Int(_) => text.parse().ok().map(Self::Int),
+            Self::Float(_) => text.parse().ok().map(Self::Float),
+            Self::String(_) => Some(Self::String(text.to_string())),
+        }
+    }
+}
+
+impl Display for CVarValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{}", *value as u8),
+            Self::Int(value) => write!(f, "{}", value),
+            Self::Float(value) => write!(f, "{}", value),
+            Self::String(value) => write!(f, "\"{}\"", value),
+        }
+    }
+}
+
+impl From<bool> for CVarValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i64> for CVarValue {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<f32> for CVarValue {
+    fn from(value: f32) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<&str> for CVarValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CVarError {
+    Unknown(String),
+    InvalidValue {
+        name: String,
+        type_name: &'static str,
+    },
+}
+
+impl Display for CVarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unknown(name) => write!(f, "Unknown cvar: {}", name),
+            Self::InvalidValue { name, type_name } => {
+                write!(f, "Cvar {} expects a value of type {}", name, type_name)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CVar {
+    pub value: CVarValue,
+    pub default_value: CVarValue,
+    pub description: String,
+}
+
+/// Typed runtime variables, every change triggers `CVarChangedEvent` in the world update.
+#[derive(Default, Resource)]
+pub struct CVars {
+    cvars: BTreeMap<String, CVar>,
+    changed_names: Vec<String>,
+}
+
+impl CVars {
+    pub const RENDER_SCALE: &str = "r_render_scale";
+    pub const DYNAMIC_RESOLUTION: &str = "r_dynamic_resolution";
+    pub const EXPOSURE: &str = "r_exposure";
+    pub const AUTO_EXPOSURE: &str = "r_auto_exposure";
+    pub const VSYNC: &str = "r_vsync";
+    pub const LOW_LATENCY: &str = "r_low_latency";
+    pub const MESHLET_CONE_CULLING: &str = "r_meshlet_cone_culling";
+    pub const DEPTH_PYRAMID: &str = "r_depth_pyramid";
+    pub const DEFRAGMENTATION: &str = "r_defragmentation";
+    pub const GIZMOS: &str = "debug_gizmos";
+
+    /// Registering an existing name keeps its current value, if the type matches.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        default_value: impl Into<CVarValue>,
+        description: impl Into<String>,
+    ) {
+        let default_value = default_value.into();
+        let description = description.into();
+
+        let cvar = self.cvars.entry(name.into()).or_insert_with(|| CVar {
+            value: default_value.clone(),
+            default_value: default_value.clone(),
+            description: Default::default(),
+        });
+        if let Some(value) = cvar.value.clone().coerce_like(&default_value) {
+            cvar.value = value;
+        } else {
+            cvar.value = default_value.clone();
+        }
+        cvar.default_value = default_value;
+        cvar.description = description;
+    }
+
+    pub fn get(&self, name: &str) -> Option<&CVar> {
+        self.cvars.get(name)
+    }
+
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        match self.cvars.get(name)?.value {
+            CVarValue::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_int(&self, name: &str) -> Option<i64> {
+        match self.cvars.get(name)?.value {
+            CVarValue::Int(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_float(&self, name: &str) -> Option<f32> {
+        match self.cvars.get(name)?.value {
+            CVarValue::Float(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_string(&self, name: &str) -> Option<&str> {
+        match &self.cvars.get(name)?.value {
+            CVarValue::String(value) => Some(value.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn set(&mut self, name: &str, value: impl Into<CVarValue>) -> Result<(), CVarError> {
+        let cvar = self
+            .cvars
+            .get_mut(name)
+            .ok_or_else(|| CVarError::Unknown(name.to_string()))?;
+        let value = value
+            .into()
+            .coerce_like(&cvar.default_value)
+            .ok_or_else(|| CVarError::InvalidValue {
+                name: name.to_string(),
+                type_name: cvar.default_value.get_type_name(),
+            })?;
+
+        if cvar.value != value {
+            cvar.value = value;
+            if !self
+                .changed_names
+                .iter()
+                .any(|changed_name| changed_name == name)
+            {
+                self.changed_names.push(name.to_string());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parses the text by the type of the cvar, bools accept `1/0`, `true/false` and `on/off`.
+    pub fn set_from_str(&mut self, name: &str, text: &str) -> Result<(), CVarError> {
+        let cvar = self
+            .cvars
+            .get(name)
+            .ok_or_else(|| CVarError::Unknown(name.to_string()))?;
+        let value = CVarValue::parse_like(text, &cvar.default_value).ok_or_else(|| {
+            CVarError::InvalidValue {
+                name: name.to_string(),
+                type_name: cvar.default_value.get_type_name(),
+            }
+        })?;
+
+        self.set(name, value)
+    }
+
+    pub fn reset(&mut self, name: &str) -> Result<(), CVarError> {
+        let default_value = self
+            .cvars
+            .get(name)
+            .ok_or_else(|| CVarError::Unknown(name.to_string()))?
+            .default_value
+            .clone();
+
+        self.set(name, default_value)
+    }
+
+    /// Sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &CVar)> {
+        self.cvars.iter().map(|(name, cvar)| (name.as_str(), cvar))
+    }
+
+    pub fn get_names_with_prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a str> {
+        self.cvars
+            .keys()
+            .filter(move |name| name.starts_with(prefix))
+            .map(String::as_str)
+    }
+
+    /// Applies persisted values, unknown names and mismatched types are reported and skipped.
+    pub fn apply_persisted(&mut self, persisted_cvars: &BTreeMap<String, CVarValue>) {
+        for (name, value) in persisted_cvars {
+            if let Err(error) = self.set(name, value.clone()) {
+                println!("Ignoring persisted cvar: {}", error);
+            }
+        }
+    }
+
+    /// Writes cvars that differ from their defaults into the `[cvars]` table of the config file.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut config_table = match std::fs::read_to_string(path) {
+            Ok(content) => toml::de::from_str::<toml::Table>(content.as_str())
+                .map_err(std::io::Error::other)?,
+            Err(_) => Default::default(),
+        };
+
+        let persisted_cvars: BTreeMap<_, _> = self
+            .cvars
+            .iter()
+            .filter(|(_, cvar)| cvar.value != cvar.default_value)
+            .map(|(name, cvar)| (name.clone(), cvar.value.clone()))
+            .collect();
+        config_table.insert(
+            "cvars".to_string(),
+            toml::Value::try_from(persisted_cvars).map_err(std::io::Error::other)?,
+        );
+
+        let content = toml::ser::to_string(&config_table).map_err(std::io::Error::other)?;
+
+        std::fs::write(path, content)
+    }
+
+    pub(crate) fn register_engine_cvars(
+        &mut self,
+        render_scale_settings: &RenderScaleSettings,
+        post_process_settings: &PostProcessSettings,
+        window_settings: &WindowSettings,
+        culling_settings: &CullingSettings,
+        defragmentation_settings: &DefragmentationSettings,
+        gizmos: &Gizmos,
+    ) {
+        self.register(
+            Self::RENDER_SCALE,
+            render_scale_settings.get_render_scale(),
+            "Resolution multiplier of the draw image.",
+        );
+        self.register(
+            Self::DYNAMIC_RESOLUTION,
+            render_scale_settings.get_dynamic_resolution().is_some(),
+            "Adjusts the render scale to keep the GPU frame time.",
+        );
+        self.register(
+            Self::EXPOSURE,
+            post_process_settings.exposure,
+            "Exposure multiplier, or the compensation with auto exposure.",
+        );
+        self.register(
+            Self::AUTO_EXPOSURE,
+            post_process_settings.auto_exposure.is_some(),
+            "Adapts exposure to the scene luminance.",
+        );
+        self.register(
+            Self::VSYNC,
+            window_settings.is_vsync_enabled(),
+            "Waits for the vertical blank on present.",
+        );
+        self.register(
+            Self::LOW_LATENCY,
+            window_settings.get_latency_mode() == LatencyMode::LowLatency,
+            "Trades the throughput for the input latency.",
+        );
+        self.register(
+            Self::MESHLET_CONE_CULLING,
+            culling_settings.is_meshlet_cone_culling_enabled,
+            "Skips meshlets facing away from the camera.",
+        );
+        self.register(
+            Self::DEPTH_PYRAMID,
+            culling_settings.is_depth_pyramid_enabled,
+            "Builds the min depth pyramid after the opaque scene.",
+        );
+        self.register(
+            Self::DEFRAGMENTATION,
+            defragmentation_settings.is_enabled,
+            "Compacts device memory while nothing is streamed.",
+        );
+        self.register(
+            Self::GIZMOS,
+            gizmos.is_scene_gizmos_enabled,
+            "Draws frusta of the cameras and the lights over the frame.",
+        );
+    }
+
+    #[inline(always)]
+    pub(crate) fn take_changed_names(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.changed_names)
+    }
+}
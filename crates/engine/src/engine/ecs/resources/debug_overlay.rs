@@ -0,0 +1,55 @@
+use bevy_ecs::resource::Resource;
+use math::{Vec2, Vec4};
+
+#[cfg(feature = "debug-tools")]
+use crate::engine::resources::{DebugOverlayRect, MAX_DEBUG_OVERLAY_RECTS_COUNT};
+
+/// Solid rects drawn over the presented image for a single frame, so they're drawn every frame.
+/// Positions and sizes are in pixels of the render extent, the origin is the top left corner.
+/// Pass covers the bounds of all the rects, so it's meant for small panels like graphs.
+/// Without the `debug-tools` feature it's a no-op and the pass isn't compiled.
+#[cfg(feature = "debug-tools")]
+#[derive(Default, Resource)]
+pub struct DebugOverlay {
+    rects: Vec<DebugOverlayRect>,
+}
+
+#[cfg(feature = "debug-tools")]
+impl DebugOverlay {
+    /// Rects beyond `MAX_DEBUG_OVERLAY_RECTS_COUNT` are skipped.
+    pub fn draw_rect(&mut self, position: Vec2, size: Vec2, color: Vec4) {
+        if self.rects.len() >= MAX_DEBUG_OVERLAY_RECTS_COUNT || size.min_element() <= 0.0 {
+            return;
+        }
+
+        self.rects.push(DebugOverlayRect {
+            position,
+            size,
+            color,
+        });
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    pub(crate) fn take_rects(&mut self) -> Vec<DebugOverlayRect> {
+        std::mem::take(&mut self.rects)
+    }
+}
+
+#[cfg(not(feature = "debug-tools"))]
+#[derive(Default, Resource)]
+pub struct DebugOverlay;
+
+#[cfg(not(feature = "debug-tools"))]
+impl DebugOverlay {
+    #[inline(always)]
+    pub fn draw_rect(&mut self, _position: Vec2, _size: Vec2, _color: Vec4) {}
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        true
+    }
+}
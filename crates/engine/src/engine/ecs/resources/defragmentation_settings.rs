@@ -0,0 +1,20 @@
+use bevy_ecs::resource::Resource;
+
+/// Device memory is compacted while nothing is uploaded or released, few allocations per frame.
+#[derive(Resource)]
+pub struct DefragmentationSettings {
+    pub is_enabled: bool,
+    /// Upper bound of bytes copied to the new places in one frame.
+    pub max_bytes_per_frame: u64,
+    pub max_allocations_per_frame: u32,
+}
+
+impl Default for DefragmentationSettings {
+    fn default() -> Self {
+        Self {
+            is_enabled: true,
+            max_bytes_per_frame: 16 * 1024 * 1024,
+            max_allocations_per_frame: 64,
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use std::collections::VecDeque;
+
+use bevy_ecs::resource::Resource;
+
+use crate::engine::resources::{
+    buffers_pool::BufferReference, mesh_buffers_pool::MeshBufferReference,
+    textures_pool::TextureReference,
+};
+
+#[derive(Clone, Copy)]
+pub enum DeferredResource {
+    Buffer(BufferReference),
+    Texture(TextureReference),
+    MeshBuffer(MeshBufferReference),
+}
+
+/// Resources that are released only after every frame in flight that could use them is finished.
+#[derive(Default, Resource)]
+pub struct DeletionQueue {
+    frame_index: usize,
+    pending_resources: VecDeque<(usize, DeferredResource)>,
+}
+
+impl DeletionQueue {
+    pub fn push(&mut self, deferred_resource: DeferredResource) {
+        self.pending_resources
+            .push_back((self.frame_index, deferred_resource));
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.pending_resources.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.pending_resources.is_empty()
+    }
+
+    /// Must be called once per frame, after the fence of the current frame is waited.
+    pub(crate) fn take_released_resources(
+        &mut self,
+        frame_overlap: usize,
+    ) -> Vec<DeferredResource> {
+        self.frame_index += 1;
+
+        let mut released_resources = Vec::new();
+        while let Some(&(frame_index, deferred_resource)) = self.pending_resources.front()
+            && frame_index + frame_overlap <= self.frame_index
+        {
+            self.pending_resources.pop_front();
+            released_resources.push(deferred_resource);
+        }
+
+        released_resources
+    }
+}
@@ -0,0 +1,53 @@
+use bevy_ecs::resource::Resource;
+use math::{Vec2, Vec3};
+
+/// Depth and world position of the picked texel, as it was rendered by the first camera.
+#[derive(Clone, Copy, Debug)]
+pub struct DepthPickResult {
+    /// Pixels of the render target, as it was requested.
+    pub screen_position: Vec2,
+    /// Reversed, zero is the background.
+    pub depth: f32,
+    /// `None` for the background and positions out of the viewport of the camera.
+    pub world_position: Option<Vec3>,
+}
+
+/// Exact depth under screen positions, e.g. for picking, decal placement and editor gizmos.
+/// Picks are read from the depth of the first camera by the GPU, results arrive once the GPU is
+/// done with the frame. Positions can be reconstructed from the depth on the host as well,
+/// see `Camera::reconstruct_world_position`.
+#[derive(Resource, Default)]
+pub struct DepthPicking {
+    requested_positions: Vec<Vec2>,
+    results: Vec<DepthPickResult>,
+}
+
+impl DepthPicking {
+    /// Requests above the limit of a frame wait for the next one.
+    pub const MAX_PICKS_COUNT: usize = 16;
+
+    /// `screen_position` is in pixels of the render target from the top left corner.
+    pub fn request_pick(&mut self, screen_position: Vec2) {
+        self.requested_positions.push(screen_position);
+    }
+
+    #[inline(always)]
+    pub fn is_picking(&self) -> bool {
+        !self.requested_positions.is_empty()
+    }
+
+    /// Results in the order of the requests, they are kept until taken.
+    pub fn take_results(&mut self) -> Vec<DepthPickResult> {
+        std::mem::take(&mut self.results)
+    }
+
+    pub(crate) fn take_requested_positions(&mut self) -> Vec<Vec2> {
+        let picks_count = self.requested_positions.len().min(Self::MAX_PICKS_COUNT);
+
+        self.requested_positions.drain(..picks_count).collect()
+    }
+
+    pub(crate) fn push_result(&mut self, result: DepthPickResult) {
+        self.results.push(result);
+    }
+}
@@ -0,0 +1,88 @@
+use bevy_ecs::resource::Resource;
+
+/// Optional features of the device, detected once on startup. Renderer subsystems are gated on it.
+#[derive(Resource, Default, Clone, Copy, Debug)]
+pub struct DeviceCapabilitiesResource {
+    pub is_mesh_shader_supported: bool,
+    pub is_descriptor_buffer_supported: bool,
+    pub is_shader_object_supported: bool,
+    /// Without it images still stay in the general layout, only slower on some devices.
+    pub is_unified_image_layouts_supported: bool,
+    /// Textures are written from the host directly, instead of a staging buffer.
+    pub is_host_image_copy_supported: bool,
+    /// Dedicated compute queue family exists and async compute is enabled by the config.
+    pub is_async_compute_supported: bool,
+    /// Vsync present mode that always takes the latest ready image, used by the low latency mode.
+    pub is_present_mode_fifo_latest_ready_supported: bool,
+    /// Without it samplers ignore their anisotropy, textures are blurrier at grazing angles.
+    pub is_sampler_anisotropy_supported: bool,
+}
+
+impl DeviceCapabilitiesResource {
+    /// Features every frame is built on, there's no fallback path for them.
+    pub fn get_missing_required_features(&self) -> Vec<&'static str> {
+        [
+            (self.is_mesh_shader_supported, "mesh shaders, geometry pass"),
+            (
+                self.is_descriptor_buffer_supported,
+                "descriptor buffer, bindless resources",
+            ),
+            (
+                self.is_shader_object_supported,
+                "shader objects, all passes",
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(is_supported, feature)| (!is_supported).then_some(feature))
+        .collect()
+    }
+
+    pub fn report(&self) {
+        let features = [
+            ("Mesh shaders", self.is_mesh_shader_supported, "required"),
+            (
+                "Descriptor buffer",
+                self.is_descriptor_buffer_supported,
+                "required",
+            ),
+            (
+                "Shader objects",
+                self.is_shader_object_supported,
+                "required",
+            ),
+            (
+                "Unified image layouts",
+                self.is_unified_image_layouts_supported,
+                "general layout without the guarantee of performance",
+            ),
+            (
+                "Host image copy",
+                self.is_host_image_copy_supported,
+                "textures are uploaded through staging buffers",
+            ),
+            (
+                "Async compute",
+                self.is_async_compute_supported,
+                "post-processing runs on the graphics queue",
+            ),
+            (
+                "FIFO latest ready present mode",
+                self.is_present_mode_fifo_latest_ready_supported,
+                "low latency mode falls back to mailbox",
+            ),
+            (
+                "Sampler anisotropy",
+                self.is_sampler_anisotropy_supported,
+                "textures are filtered without anisotropy",
+            ),
+        ];
+
+        for (feature, is_supported, fallback) in features {
+            if is_supported {
+                eprintln!("\x1b[92m[Vulkan]\x1b[0m {feature}: supported");
+            } else {
+                eprintln!("\x1b[93m[Vulkan]\x1b[0m {feature}: unsupported, {fallback}");
+            }
+        }
+    }
+}
@@ -1,7 +1,13 @@
 use bevy_ecs::resource::Resource;
-use vulkanite::vk::PhysicalDeviceDescriptorBufferPropertiesEXT;
+use vulkanite::vk::{MemoryPropertyFlags, PhysicalDeviceDescriptorBufferPropertiesEXT};
 
 #[derive(Resource)]
 pub struct DevicePropertiesResource {
     pub descriptor_buffer_properties: PhysicalDeviceDescriptorBufferPropertiesEXT<'static>,
+    pub max_sampler_anisotropy: f32,
+    pub max_push_constants_size: u32,
+    /// Nanoseconds per timestamp tick.
+    pub timestamp_period: f32,
+    /// Flags of the memory types by their indices.
+    pub memory_type_flags: Vec<MemoryPropertyFlags>,
 }
@@ -0,0 +1,193 @@
+use std::{collections::BTreeMap, path::PathBuf, str::FromStr};
+
+use bevy_ecs::resource::Resource;
+use serde::{Deserialize, Serialize};
+use winit::{dpi::PhysicalSize, window::WindowAttributes};
+
+use crate::engine::resources::{CVarValue, LatencyMode, VertexFormat};
+
+/// Startup settings, read once before the window and the engine are created.
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EngineConfig {
+    pub window_width: u32,
+    pub window_height: u32,
+    pub window_title: String,
+    pub vsync: bool,
+    /// Initial latency mode, it also lowers the priority of the transfer queue for the whole run.
+    pub low_latency: bool,
+    /// Validation layer is enabled only if it's also installed.
+    pub validation: bool,
+    /// Post-processing runs on a dedicated compute queue, if the device has one.
+    pub async_compute: bool,
+    pub render_scale: f32,
+    /// Imported meshes are uploaded as `CompressedVertex`, roughly half of the vertex memory.
+    pub compressed_vertices: bool,
+    /// Relative paths are resolved against the executable directory, `None` is the workspace `assets`.
+    pub asset_root: Option<PathBuf>,
+    /// Cooked artifacts of `asset_cook`, relative paths are resolved against the working directory
+    /// like the compiled shaders, `None` is `intermediate`.
+    pub intermediate_root: Option<PathBuf>,
+    /// Assets that aren't cooked are imported on demand, shipping builds load only cooked artifacts.
+    pub import_fallback: bool,
+    /// Loaded models are cooked and respawned when their sources change, see `AssetHotReload`.
+    pub hot_reload: bool,
+    /// Frames in flight, `None` is two, it's clamped to the swapchain images count.
+    pub frame_overlap: Option<usize>,
+    /// Failed Vulkan calls of renderer systems panic right away instead of abandoning the frame,
+    /// see `FrameErrors`.
+    pub panic_on_frame_errors: bool,
+    /// Values of `CVars` saved with `cvars_save`, applied after the engine registers its cvars.
+    pub cvars: BTreeMap<String, CVarValue>,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            window_width: 1700,
+            window_height: 900,
+            window_title: "Vulkan Engine".to_string(),
+            vsync: false,
+            low_latency: false,
+            validation: cfg!(debug_assertions),
+            async_compute: true,
+            render_scale: 1.0,
+            compressed_vertices: true,
+            asset_root: Default::default(),
+            intermediate_root: Default::default(),
+            import_fallback: cfg!(debug_assertions),
+            hot_reload: cfg!(debug_assertions),
+            frame_overlap: Default::default(),
+            panic_on_frame_errors: false,
+            cvars: Default::default(),
+        }
+    }
+}
+
+impl EngineConfig {
+    pub const FILE_NAME: &str = "engine.toml";
+
+    /// Reads `engine.toml` next to the executable, missing file keeps defaults.
+    /// `ENGINE_*` environment variables override the file (e.g. `ENGINE_WINDOW_WIDTH`).
+    pub fn load() -> Self {
+        let path = Self::get_path();
+
+        let mut engine_config = match std::fs::read_to_string(&path) {
+            Ok(content) => toml::de::from_str(content.as_str()).unwrap_or_else(|error| {
+                println!("Failed to parse {}: {}", path.display(), error);
+
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        };
+        engine_config.apply_env_overrides();
+
+        engine_config
+    }
+
+    pub fn get_path() -> PathBuf {
+        Self::get_executable_directory().join(Self::FILE_NAME)
+    }
+
+    pub fn get_window_attributes(&self) -> WindowAttributes {
+        WindowAttributes::default()
+            .with_title(self.window_title.as_str())
+            .with_surface_size(self.get_surface_size())
+    }
+
+    /// Size of the draw texture for `Engine::new_headless`.
+    pub fn get_surface_size(&self) -> PhysicalSize<u32> {
+        PhysicalSize::new(self.window_width, self.window_height)
+    }
+
+    pub fn get_latency_mode(&self) -> LatencyMode {
+        if self.low_latency {
+            LatencyMode::LowLatency
+        } else {
+            LatencyMode::Throughput
+        }
+    }
+
+    pub fn get_vertex_format(&self) -> VertexFormat {
+        if self.compressed_vertices {
+            VertexFormat::Compressed
+        } else {
+            VertexFormat::Full
+        }
+    }
+
+    pub fn get_asset_root(&self) -> PathBuf {
+        let executable_directory = Self::get_executable_directory();
+
+        match &self.asset_root {
+            Some(asset_root) => executable_directory.join(asset_root),
+            None => {
+                // NOTE: Executable lives in `target/<profile>`, assets are in the workspace root.
+                let mut workspace_directory = executable_directory;
+                workspace_directory.pop();
+                workspace_directory.pop();
+
+                workspace_directory.join("assets")
+            }
+        }
+    }
+
+    pub fn get_asset_path(&self, relative_path: &str) -> PathBuf {
+        self.get_asset_root().join(relative_path)
+    }
+
+    pub fn get_intermediate_root(&self) -> PathBuf {
+        self.intermediate_root
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("intermediate"))
+    }
+
+    fn apply_env_overrides(&mut self) {
+        Self::override_from_env("ENGINE_WINDOW_WIDTH", &mut self.window_width);
+        Self::override_from_env("ENGINE_WINDOW_HEIGHT", &mut self.window_height);
+        Self::override_from_env("ENGINE_WINDOW_TITLE", &mut self.window_title);
+        Self::override_from_env("ENGINE_VSYNC", &mut self.vsync);
+        Self::override_from_env("ENGINE_LOW_LATENCY", &mut self.low_latency);
+        Self::override_from_env("ENGINE_VALIDATION", &mut self.validation);
+        Self::override_from_env("ENGINE_ASYNC_COMPUTE", &mut self.async_compute);
+        Self::override_from_env("ENGINE_RENDER_SCALE", &mut self.render_scale);
+        Self::override_from_env("ENGINE_COMPRESSED_VERTICES", &mut self.compressed_vertices);
+
+        if let Ok(asset_root) = std::env::var("ENGINE_ASSET_ROOT") {
+            self.asset_root = Some(asset_root.into());
+        }
+        if let Ok(intermediate_root) = std::env::var("ENGINE_INTERMEDIATE_ROOT") {
+            self.intermediate_root = Some(intermediate_root.into());
+        }
+        Self::override_from_env("ENGINE_IMPORT_FALLBACK", &mut self.import_fallback);
+        Self::override_from_env("ENGINE_HOT_RELOAD", &mut self.hot_reload);
+        Self::override_from_env(
+            "ENGINE_PANIC_ON_FRAME_ERRORS",
+            &mut self.panic_on_frame_errors,
+        );
+
+        let mut frame_overlap = Default::default();
+        Self::override_from_env("ENGINE_FRAME_OVERLAP", &mut frame_overlap);
+        if frame_overlap > 0 {
+            self.frame_overlap = Some(frame_overlap);
+        }
+    }
+
+    fn override_from_env<T: FromStr>(name: &str, value: &mut T) {
+        let Ok(variable) = std::env::var(name) else {
+            return;
+        };
+
+        match variable.parse() {
+            Ok(parsed_value) => *value = parsed_value,
+            Err(_) => println!("Ignoring {}, invalid value: {}", name, variable),
+        }
+    }
+
+    fn get_executable_directory() -> PathBuf {
+        let mut executable_directory = std::env::current_exe().unwrap();
+        executable_directory.pop();
+
+        executable_directory
+    }
+}
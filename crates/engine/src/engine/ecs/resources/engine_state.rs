@@ -0,0 +1,10 @@
+use bevy_ecs::resource::Resource;
+
+/// Game systems keep running while paused, only rendering is skipped.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EngineState {
+    #[default]
+    Running,
+    /// Surface has zero extent (e.g. minimized window) or was lost (e.g. lock screen, suspend).
+    Paused,
+}
@@ -0,0 +1,244 @@
+use bevy_ecs::{entity::Entity, resource::Resource};
+use winit::keyboard::KeyCode;
+
+use crate::engine::components::{
+    camera::ClippingPlanes, local_transform::LocalTransform, material::MaterialProperties,
+};
+
+/// Entity of the hierarchy, named by its `Name` component.
+#[derive(Clone, Debug)]
+pub struct InspectorNode {
+    pub entity: Entity,
+    pub name: String,
+    pub children: Vec<InspectorNode>,
+}
+
+/// Current values of the selected entity, `None` if it doesn't have the component.
+#[derive(Clone, Copy)]
+pub struct InspectorSelection {
+    pub entity: Entity,
+    pub transform: Option<LocalTransform>,
+    pub camera: Option<(f32, ClippingPlanes)>,
+    /// Material is shared by every mesh that uses it, editing it affects all of them.
+    pub material_properties: Option<MaterialProperties>,
+}
+
+/// Component of `TypeRegistry` on the selected entity, `None` text if it has no TOML representation.
+#[derive(Clone, Debug)]
+pub struct InspectorComponent {
+    pub name: String,
+    pub text: Option<String>,
+}
+
+/// Edit of a component of `TypeRegistry` by its name.
+#[derive(Clone, Debug)]
+pub enum InspectorComponentEdit {
+    InsertDefault(String),
+    /// Inline TOML, it replaces the current value.
+    Set {
+        name: String,
+        text: String,
+    },
+    Remove(String),
+}
+
+#[derive(Clone, Copy)]
+pub enum InspectorEdit {
+    Transform(LocalTransform),
+    Camera {
+        fov: f32,
+        clipping_planes: ClippingPlanes,
+    },
+    MaterialProperties(MaterialProperties),
+}
+
+/// Engine side of the inspector panel: hierarchy, selection and edits that are applied
+/// before transforms are propagated, so they reach the GPU in the same frame.
+/// Without the `debug-tools` feature it's a no-op, it never opens.
+#[cfg(feature = "debug-tools")]
+#[derive(Resource)]
+pub struct EntityInspector {
+    is_open: bool,
+    toggle_key: Option<KeyCode>,
+    hierarchy: Vec<InspectorNode>,
+    selected_entity: Option<Entity>,
+    selection: Option<InspectorSelection>,
+    selected_components: Vec<InspectorComponent>,
+    pending_edits: Vec<InspectorEdit>,
+    pending_component_edits: Vec<InspectorComponentEdit>,
+}
+
+#[cfg(feature = "debug-tools")]
+impl Default for EntityInspector {
+    fn default() -> Self {
+        Self {
+            is_open: false,
+            // NOTE: Release builds have to opt in with `set_toggle_key`.
+            toggle_key: cfg!(debug_assertions).then_some(KeyCode::F10),
+            hierarchy: Default::default(),
+            selected_entity: Default::default(),
+            selection: Default::default(),
+            selected_components: Default::default(),
+            pending_edits: Default::default(),
+            pending_component_edits: Default::default(),
+        }
+    }
+}
+
+#[cfg(feature = "debug-tools")]
+impl EntityInspector {
+    #[inline(always)]
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    pub fn set_open(&mut self, is_open: bool) {
+        self.is_open = is_open;
+        if !is_open {
+            self.hierarchy.clear();
+            self.selection = None;
+            self.selected_components.clear();
+            self.pending_edits.clear();
+            self.pending_component_edits.clear();
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.set_open(!self.is_open);
+    }
+
+    pub fn get_toggle_key(&self) -> Option<KeyCode> {
+        self.toggle_key
+    }
+
+    /// Key is handled by the engine and never reaches `Input`, `None` disables the toggle.
+    pub fn set_toggle_key(&mut self, toggle_key: Option<KeyCode>) {
+        self.toggle_key = toggle_key;
+    }
+
+    /// Root entities, rebuilt every frame while the inspector is open.
+    pub fn get_hierarchy(&self) -> &[InspectorNode] {
+        &self.hierarchy
+    }
+
+    pub fn get_selected_entity(&self) -> Option<Entity> {
+        self.selected_entity
+    }
+
+    pub fn select(&mut self, selected_entity: Option<Entity>) {
+        if self.selected_entity != selected_entity {
+            self.selected_entity = selected_entity;
+            self.selection = None;
+            self.selected_components.clear();
+            self.pending_edits.clear();
+            self.pending_component_edits.clear();
+        }
+    }
+
+    /// Values of the last frame, `None` until the selection is refreshed.
+    pub fn get_selection(&self) -> Option<&InspectorSelection> {
+        self.selection.as_ref()
+    }
+
+    /// Registered components of the selected entity of the last frame.
+    pub fn get_selected_components(&self) -> &[InspectorComponent] {
+        &self.selected_components
+    }
+
+    /// Edit of the selected entity, ignored if nothing is selected or the inspector is closed.
+    pub fn edit(&mut self, inspector_edit: InspectorEdit) {
+        if self.is_open && self.selected_entity.is_some() {
+            self.pending_edits.push(inspector_edit);
+        }
+    }
+
+    /// Same as `edit`, unknown names and invalid texts are reported in the log.
+    pub fn edit_component(&mut self, inspector_component_edit: InspectorComponentEdit) {
+        if self.is_open && self.selected_entity.is_some() {
+            self.pending_component_edits.push(inspector_component_edit);
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_hierarchy(&mut self, hierarchy: Vec<InspectorNode>) {
+        self.hierarchy = hierarchy;
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_selection(&mut self, selection: Option<InspectorSelection>) {
+        if selection.is_none() {
+            self.selected_entity = None;
+        }
+        self.selection = selection;
+    }
+
+    #[inline(always)]
+    pub(crate) fn take_pending_edits(&mut self) -> Vec<InspectorEdit> {
+        std::mem::take(&mut self.pending_edits)
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_selected_components(&mut self, selected_components: Vec<InspectorComponent>) {
+        self.selected_components = selected_components;
+    }
+
+    #[inline(always)]
+    pub(crate) fn take_pending_component_edits(&mut self) -> Vec<InspectorComponentEdit> {
+        std::mem::take(&mut self.pending_component_edits)
+    }
+}
+
+#[cfg(not(feature = "debug-tools"))]
+#[derive(Default, Resource)]
+pub struct EntityInspector;
+
+#[cfg(not(feature = "debug-tools"))]
+impl EntityInspector {
+    #[inline(always)]
+    pub fn is_open(&self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    pub fn set_open(&mut self, _is_open: bool) {}
+
+    #[inline(always)]
+    pub fn toggle(&mut self) {}
+
+    #[inline(always)]
+    pub fn get_toggle_key(&self) -> Option<KeyCode> {
+        None
+    }
+
+    #[inline(always)]
+    pub fn set_toggle_key(&mut self, _toggle_key: Option<KeyCode>) {}
+
+    #[inline(always)]
+    pub fn get_hierarchy(&self) -> &[InspectorNode] {
+        &[]
+    }
+
+    #[inline(always)]
+    pub fn get_selected_entity(&self) -> Option<Entity> {
+        None
+    }
+
+    #[inline(always)]
+    pub fn select(&mut self, _selected_entity: Option<Entity>) {}
+
+    #[inline(always)]
+    pub fn get_selection(&self) -> Option<&InspectorSelection> {
+        None
+    }
+
+    #[inline(always)]
+    pub fn get_selected_components(&self) -> &[InspectorComponent] {
+        &[]
+    }
+
+    #[inline(always)]
+    pub fn edit(&mut self, _inspector_edit: InspectorEdit) {}
+
+    #[inline(always)]
+    pub fn edit_component(&mut self, _inspector_component_edit: InspectorComponentEdit) {}
+}
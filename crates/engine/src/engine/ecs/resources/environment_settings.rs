@@ -0,0 +1,113 @@
+use bevy_ecs::resource::Resource;
+use math::{Vec3, Vec4};
+
+use crate::engine::resources::textures_pool::TextureReference;
+
+#[derive(Clone, Copy)]
+#[non_exhaustive]
+pub enum EnvironmentMode {
+    /// Draw image is cleared with the color, no compute pass is dispatched.
+    SolidColor(Vec4),
+    /// Vertical gradient from the bottom to the top of the screen.
+    Gradient { top_color: Vec4, bottom_color: Vec4 },
+    /// Equirectangular texture sampled by the camera view direction.
+    Skybox(TextureReference),
+}
+
+impl Default for EnvironmentMode {
+    fn default() -> Self {
+        Self::Gradient {
+            top_color: Vec4::new(0.25, 0.45, 0.85, 1.0),
+            bottom_color: Vec4::new(0.75, 0.8, 0.9, 1.0),
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum FogMode {
+    /// Fades from `start` to `end` distance.
+    #[default]
+    Linear,
+    Exponential,
+    ExponentialSquared,
+}
+
+#[derive(Clone, Copy)]
+pub struct Fog {
+    pub mode: FogMode,
+    pub color: Vec4,
+    pub start: f32,
+    pub end: f32,
+    /// Used by the exponential modes only.
+    pub density: f32,
+}
+
+impl Default for Fog {
+    fn default() -> Self {
+        Self {
+            mode: Default::default(),
+            color: Vec4::new(0.75, 0.8, 0.9, 1.0),
+            start: 50.0,
+            end: 500.0,
+            density: 0.005,
+        }
+    }
+}
+
+/// Single scattering in a spherical atmosphere around the planet, lit by the directional light.
+#[derive(Clone, Copy)]
+pub struct AtmosphericScattering {
+    pub planet_center: Vec3,
+    pub planet_radius: f32,
+    pub atmosphere_radius: f32,
+    /// Per unit of distance, the default is tinted blue like the Earth sky.
+    pub rayleigh_coefficients: Vec3,
+    pub mie_coefficient: f32,
+    pub sun_intensity: f32,
+}
+
+impl Default for AtmosphericScattering {
+    fn default() -> Self {
+        Self {
+            planet_center: Vec3::ZERO,
+            planet_radius: 100.0,
+            atmosphere_radius: 110.0,
+            rayleigh_coefficients: Vec3::new(0.0058, 0.0135, 0.0331),
+            mie_coefficient: 0.002,
+            sun_intensity: 20.0,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct EnvironmentSettings {
+    pub mode: EnvironmentMode,
+    /// Direction to the sun, it's the direction of the directional light.
+    pub sun_direction: Vec3,
+    /// Color of the directional light.
+    pub sun_color: Vec3,
+    pub ambient_color: Vec4,
+    /// Applied to meshes of every camera, that doesn't disable it.
+    pub fog: Option<Fog>,
+    pub atmospheric_scattering: Option<AtmosphericScattering>,
+}
+
+impl Default for EnvironmentSettings {
+    fn default() -> Self {
+        Self {
+            mode: Default::default(),
+            sun_direction: Vec3::new(0.1, 0.5, 1.0).normalize(),
+            sun_color: Vec3::new(0.72, 0.72, 0.93),
+            ambient_color: Vec4::new(0.1, 0.1, 0.1, 1.0),
+            fog: Default::default(),
+            atmospheric_scattering: Default::default(),
+        }
+    }
+}
+
+impl EnvironmentSettings {
+    pub fn is_compute_pass_required(&self) -> bool {
+        !matches!(self.mode, EnvironmentMode::SolidColor(_))
+    }
+}
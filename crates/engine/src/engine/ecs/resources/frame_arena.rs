@@ -0,0 +1,69 @@
+use bevy_ecs::resource::Resource;
+use bumpalo::Bump;
+
+pub type FrameVec<'a, T> = bumpalo::collections::Vec<'a, T>;
+
+/// Bump allocations for transient CPU data, e.g. entity lists and copy regions of the frame.
+/// Memory is reused once the engine resets it at the start of the next frame.
+#[derive(Default, Resource)]
+pub struct FrameArena {
+    bump: Bump,
+    /// Size of the chunk reserved by the last reset.
+    capacity: usize,
+}
+
+// SAFETY: `Bump` is allocated from only through `&mut FrameArena`, and `FrameAllocator` isn't `Send`.
+unsafe impl Sync for FrameArena {}
+
+impl FrameArena {
+    /// Allocations can't outlive the borrow, so nothing is left to the reset.
+    #[inline(always)]
+    pub fn get_allocator(&mut self) -> FrameAllocator<'_> {
+        FrameAllocator { bump: &self.bump }
+    }
+
+    #[inline(always)]
+    pub fn get_allocated_bytes(&self) -> usize {
+        self.bump.allocated_bytes()
+    }
+
+    /// Reset keeps only the last chunk, so a frame that spilled over it gets a single bigger one.
+    /// Steady frames don't touch the global allocator then.
+    pub fn reset(&mut self) {
+        let allocated_bytes = self.bump.allocated_bytes();
+        if allocated_bytes > self.capacity {
+            self.bump = Bump::with_capacity(allocated_bytes);
+            self.capacity = self.bump.allocated_bytes();
+        } else {
+            self.bump.reset();
+        }
+    }
+}
+
+/// Typed allocations of the `FrameArena`, drops of the values don't free their memory.
+#[derive(Clone, Copy)]
+pub struct FrameAllocator<'a> {
+    bump: &'a Bump,
+}
+
+impl<'a> FrameAllocator<'a> {
+    #[inline(always)]
+    pub fn vec<T>(&self) -> FrameVec<'a, T> {
+        FrameVec::new_in(self.bump)
+    }
+
+    #[inline(always)]
+    pub fn vec_with_capacity<T>(&self, capacity: usize) -> FrameVec<'a, T> {
+        FrameVec::with_capacity_in(capacity, self.bump)
+    }
+
+    #[inline(always)]
+    pub fn collect<T>(&self, iter: impl IntoIterator<Item = T>) -> FrameVec<'a, T> {
+        FrameVec::from_iter_in(iter, self.bump)
+    }
+
+    #[inline(always)]
+    pub fn copy_slice<T: Copy>(&self, slice: &[T]) -> &'a mut [T] {
+        self.bump.alloc_slice_copy(slice)
+    }
+}
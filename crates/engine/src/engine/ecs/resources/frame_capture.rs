@@ -0,0 +1,122 @@
+use bevy_ecs::resource::Resource;
+use image::RgbaImage;
+
+#[cfg(feature = "capture")]
+use crate::engine::resources::buffers_pool::BufferReference;
+
+#[cfg(feature = "capture")]
+pub(crate) struct ActiveFrameCapture {
+    pub(crate) readback_buffer_reference: BufferReference,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    /// Frame was copied into the readback buffer by the last frame.
+    pub(crate) is_frame_copied: bool,
+}
+
+/// Readback of the rendered frame for the golden image comparison, see `compare_images`.
+/// Captured color is the draw image before the post-processing, it's clamped and encoded as sRGB,
+/// so the exposure and tonemapping don't make the captures differ between the runs.
+/// Without the `capture` feature it's a no-op, nothing is ever captured.
+#[cfg(feature = "capture")]
+#[derive(Resource, Default)]
+pub struct FrameCapture {
+    /// Frames to render before the captured one.
+    requested_delay: Option<u32>,
+    active_capture: Option<ActiveFrameCapture>,
+    captured_frame: Option<RgbaImage>,
+}
+
+#[cfg(feature = "capture")]
+impl FrameCapture {
+    /// Texel of the draw image, it's `R16G16B16A16Sfloat`.
+    pub(crate) const TEXEL_SIZE: usize = 8;
+
+    /// Frame rendered after `delay_frames_count` frames is captured, the last capture is dropped.
+    pub fn request_capture(&mut self, delay_frames_count: u32) {
+        self.requested_delay = Some(delay_frames_count);
+        self.captured_frame = None;
+    }
+
+    #[inline(always)]
+    pub fn is_capturing(&self) -> bool {
+        self.requested_delay.is_some() || self.active_capture.is_some()
+    }
+
+    #[inline(always)]
+    pub fn take_captured_frame(&mut self) -> Option<RgbaImage> {
+        self.captured_frame.take()
+    }
+
+    /// Returns `true`, if the frame rendered next has to be captured.
+    pub(crate) fn tick_request(&mut self) -> bool {
+        match self.requested_delay.as_mut() {
+            Some(0) => {
+                self.requested_delay = None;
+
+                true
+            }
+            Some(delay) => {
+                *delay -= 1;
+
+                false
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn get_active_capture_mut(&mut self) -> Option<&mut ActiveFrameCapture> {
+        self.active_capture.as_mut()
+    }
+
+    pub(crate) fn set_active_capture(&mut self, active_capture: ActiveFrameCapture) {
+        self.active_capture = Some(active_capture);
+    }
+
+    pub(crate) fn take_active_capture(&mut self) -> Option<ActiveFrameCapture> {
+        self.active_capture.take()
+    }
+
+    pub(crate) fn set_captured_frame(&mut self, captured_frame: RgbaImage) {
+        self.captured_frame = Some(captured_frame);
+    }
+
+    /// Readback buffer and the extent of the frame, that has to be copied by this frame.
+    pub(crate) fn get_frame_readback(&self) -> Option<(BufferReference, u32, u32)> {
+        self.active_capture
+            .as_ref()
+            .filter(|active_capture| !active_capture.is_frame_copied)
+            .map(|active_capture| {
+                (
+                    active_capture.readback_buffer_reference,
+                    active_capture.width,
+                    active_capture.height,
+                )
+            })
+    }
+
+    pub(crate) fn mark_frame_copied(&mut self) {
+        if let Some(active_capture) = self.active_capture.as_mut() {
+            active_capture.is_frame_copied = true;
+        }
+    }
+}
+
+#[cfg(not(feature = "capture"))]
+#[derive(Resource, Default)]
+pub struct FrameCapture;
+
+#[cfg(not(feature = "capture"))]
+impl FrameCapture {
+    #[inline(always)]
+    pub fn request_capture(&mut self, _delay_frames_count: u32) {}
+
+    #[inline(always)]
+    pub fn is_capturing(&self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    pub fn take_captured_frame(&mut self) -> Option<RgbaImage> {
+        None
+    }
+}
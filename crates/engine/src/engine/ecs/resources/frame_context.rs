@@ -1,14 +1,63 @@
 use bevy_ecs::resource::Resource;
-use math::Mat4;
-use vulkanite::vk::rs::CommandBuffer;
+use math::Vec4;
+use vulkanite::vk::{FrontFace, Rect2D, rs::CommandBuffer};
 
-use crate::engine::resources::textures_pool::TextureReference;
+use crate::engine::resources::{
+    AsyncComputeCommandGroup, GraphicsPushConstant, buffers_pool::BufferReference,
+    textures_pool::TextureReference,
+};
+
+/// Camera rendered in the frame, its index matches its scene data.
+#[derive(Clone, Copy)]
+pub struct CameraFrameData {
+    pub viewport: Rect2D,
+    /// Mirrored cameras flip the winding of every triangle.
+    pub front_face: FrontFace,
+    pub is_depth_of_field_enabled: bool,
+    /// Cameras that see the sun get the light shafts pass.
+    pub is_light_shafts_enabled: bool,
+    /// Cameras that don't preserve their viewport get the background pass.
+    pub is_background_drawn: bool,
+}
 
 #[derive(Default, Resource)]
 pub struct FrameContext {
+    /// Rest of the frame is skipped if the swapchain image wasn't acquired.
+    pub is_swapchain_image_acquired: bool,
     pub swapchain_image_index: u32,
     pub command_buffer: Option<CommandBuffer>,
+    /// Set when post-processing of the frame runs on the compute queue.
+    pub async_compute_command_group: Option<AsyncComputeCommandGroup>,
+    /// Pushed once per command buffer, passes of cameras override only its scene data.
+    pub push_constant: GraphicsPushConstant,
     pub draw_texture_reference: TextureReference,
     pub depth_texture_reference: TextureReference,
-    pub world_matrix: Mat4,
+    /// Target of the post effects pass, it's blitted instead of the draw image when they're enabled.
+    /// Acquired from `TransientResourcesPool` every frame, as the outline mask.
+    pub post_texture_reference: TextureReference,
+    /// Rendered cameras by their order, the first one is read by post-processing.
+    pub cameras: Vec<CameraFrameData>,
+    /// Set when every camera clears to the same color, the attachment is cleared instead of the pass.
+    pub background_clear_color: Option<Vec4>,
+    /// Lens flare of the first camera is composited by the post effects pass.
+    pub is_lens_flare_visible: bool,
+    pub is_water_enabled: bool,
+    /// Set while any mesh is `Selected` or `Highlighted`, the outline mask is written then.
+    pub is_outlined: bool,
+    /// Nearest outlined fragment of every pixel, it's acquired only while anything is outlined.
+    pub outline_mask_buffer_reference: BufferReference,
+    /// Cells of the largest foliage grid, the scattering pass is dispatched over them.
+    pub foliage_cells_count: [u32; 2],
+    /// Bounds of the debug overlay rects in the render extent, its pass is dispatched over them.
+    #[cfg(feature = "debug-tools")]
+    pub debug_overlay_bounds: Option<Rect2D>,
+}
+
+impl FrameContext {
+    #[inline(always)]
+    pub fn is_foliage_scattered(&self) -> bool {
+        self.foliage_cells_count
+            .iter()
+            .all(|&cells_count| cells_count > Default::default())
+    }
 }
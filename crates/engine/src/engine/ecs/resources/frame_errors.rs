@@ -0,0 +1,116 @@
+use bevy_ecs::resource::Resource;
+use vulkanite::vk::Status;
+
+/// Failed Vulkan call of a renderer system.
+#[derive(Clone, Copy, Debug)]
+pub struct FrameError {
+    /// What the system tried to do, e.g. "wait for the frame fence".
+    pub operation: &'static str,
+    /// `None` if a resource of the frame is missing, e.g. a stale reference while it's recreated.
+    pub status: Option<Status>,
+}
+
+impl FrameError {
+    pub const fn new(operation: &'static str, status: Status) -> Self {
+        Self {
+            operation,
+            status: Some(status),
+        }
+    }
+
+    pub const fn missing_resource(operation: &'static str) -> Self {
+        Self {
+            operation,
+            status: None,
+        }
+    }
+
+    /// Lost device and unknown errors don't go away by skipping frames.
+    /// Out of date and lost surface aren't errors, they're kept by `SwapchainStatus`.
+    pub fn is_recoverable(&self) -> bool {
+        matches!(
+            self.status,
+            None | Some(
+                Status::Timeout
+                    | Status::NotReady
+                    | Status::ErrorOutOfHostMemory
+                    | Status::ErrorOutOfDeviceMemory
+            )
+        )
+    }
+}
+
+impl std::fmt::Display for FrameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.status {
+            Some(status) => write!(f, "{}: {:?}", self.operation, status),
+            None => write!(f, "{}: resource is missing", self.operation),
+        }
+    }
+}
+
+/// Renderer systems record failed Vulkan calls here instead of panicking, the rest of the frame
+/// is skipped and the engine recreates the swapchain before the next one.
+#[derive(Default, Resource)]
+pub struct FrameErrors {
+    errors: Vec<FrameError>,
+    abandoned_frames_count: u32,
+    is_panicking: bool,
+}
+
+impl FrameErrors {
+    /// Frames abandoned in a row before a recoverable error is treated as unrecoverable.
+    pub const MAX_ABANDONED_FRAMES: u32 = 16;
+
+    pub fn new(is_panicking: bool) -> Self {
+        Self {
+            is_panicking,
+            ..Default::default()
+        }
+    }
+
+    pub fn record(&mut self, error: FrameError) {
+        if self.is_panicking {
+            panic!("Failed to {}", error);
+        }
+
+        self.errors.push(error);
+    }
+
+    #[inline(always)]
+    pub fn is_frame_abandoned(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn get_abandoned_frames_count(&self) -> u32 {
+        self.abandoned_frames_count
+    }
+
+    /// Every failed call panics right away, as it did before the errors were recorded.
+    pub fn set_panicking(&mut self, is_panicking: bool) {
+        self.is_panicking = is_panicking;
+    }
+
+    /// Clears errors of the frame, returns whether it was abandoned.
+    /// Panics on errors that can't be recovered.
+    pub fn resolve_frame(&mut self, frame_number: usize) -> bool {
+        if self.errors.is_empty() {
+            self.abandoned_frames_count = Default::default();
+
+            return false;
+        }
+
+        self.abandoned_frames_count += 1;
+        let is_exhausted = self.abandoned_frames_count > Self::MAX_ABANDONED_FRAMES;
+        for error in self.errors.drain(..) {
+            if is_exhausted || !error.is_recoverable() {
+                panic!("Failed to {} in frame {}", error, frame_number);
+            }
+
+            println!("Frame {} is abandoned, failed to {}", frame_number, error);
+        }
+
+        true
+    }
+}
@@ -0,0 +1,496 @@
+#[cfg(feature = "capture")]
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use bevy_ecs::resource::Resource;
+use vulkanite::vk::{
+    ImageAspectFlags, ImageLayout, PipelineStageFlags2,
+    rs::{CommandBuffer, Image},
+};
+use winit::keyboard::KeyCode;
+
+use crate::engine::{
+    resources::{
+        buffers_pool::BuffersPool,
+        image_state_tracker::ImageState,
+        textures_pool::{AllocatedImage, TextureReference, TexturesPool},
+    },
+    utils,
+};
+
+#[derive(Clone, Copy)]
+pub struct FrameGraphAccess {
+    pub resource_name: &'static str,
+    /// `None` for buffers.
+    pub image_layout: Option<ImageLayout>,
+}
+
+impl FrameGraphAccess {
+    pub fn image(resource_name: &'static str, image_layout: ImageLayout) -> Self {
+        Self {
+            resource_name,
+            image_layout: Some(image_layout),
+        }
+    }
+
+    pub fn buffer(resource_name: &'static str) -> Self {
+        Self {
+            resource_name,
+            image_layout: None,
+        }
+    }
+}
+
+/// Pass with the resources it accesses, it's recorded by the helper that records its commands.
+#[derive(Clone, Copy)]
+pub struct FrameGraphPassInfo<'a> {
+    pub name: &'static str,
+    pub reads: &'a [FrameGraphAccess],
+    pub writes: &'a [FrameGraphAccess],
+}
+
+impl<'a> FrameGraphPassInfo<'a> {
+    pub fn new(
+        name: &'static str,
+        reads: &'a [FrameGraphAccess],
+        writes: &'a [FrameGraphAccess],
+    ) -> Self {
+        Self {
+            name,
+            reads,
+            writes,
+        }
+    }
+}
+
+#[cfg(feature = "capture")]
+struct FrameGraphPass {
+    name: &'static str,
+    reads: Vec<FrameGraphAccess>,
+    writes: Vec<FrameGraphAccess>,
+}
+
+#[cfg(feature = "capture")]
+struct FrameGraphBarrier {
+    resource_name: &'static str,
+    old_layout: Option<ImageLayout>,
+    new_layout: Option<ImageLayout>,
+    src_stage_mask: PipelineStageFlags2,
+    dst_stage_mask: PipelineStageFlags2,
+    /// Source and destination queue families, if the barrier transfers ownership.
+    queue_family_indices: Option<(u32, u32)>,
+    /// Index of the pass the barrier is recorded before.
+    pass_index: usize,
+}
+
+/// Records passes and barriers of a single frame and dumps them as Graphviz DOT.
+/// Without the `capture` feature only the barriers are emitted, nothing is recorded or dumped.
+#[cfg(feature = "capture")]
+#[derive(Resource)]
+pub struct FrameGraphCapture {
+    requested_path: Option<PathBuf>,
+    recording_path: Option<PathBuf>,
+    dump_key: Option<KeyCode>,
+    passes: Vec<FrameGraphPass>,
+    barriers: Vec<FrameGraphBarrier>,
+}
+
+#[cfg(not(feature = "capture"))]
+#[derive(Default, Resource)]
+pub struct FrameGraphCapture;
+
+#[cfg(feature = "capture")]
+impl Default for FrameGraphCapture {
+    fn default() -> Self {
+        Self {
+            requested_path: Default::default(),
+            recording_path: Default::default(),
+            dump_key: Some(KeyCode::F9),
+            passes: Default::default(),
+            barriers: Default::default(),
+        }
+    }
+}
+
+impl FrameGraphCapture {
+    pub const DEFAULT_PATH: &str = "frame_graph.dot";
+}
+
+#[cfg(feature = "capture")]
+impl FrameGraphCapture {
+    /// The next rendered frame is recorded and written to the path.
+    pub fn request_dump(&mut self, path: impl Into<PathBuf>) {
+        self.requested_path = Some(path.into());
+    }
+
+    pub fn get_dump_key(&self) -> Option<KeyCode> {
+        self.dump_key
+    }
+
+    /// Key is handled by the engine and never reaches `Input`, `None` disables it.
+    pub fn set_dump_key(&mut self, dump_key: Option<KeyCode>) {
+        self.dump_key = dump_key;
+    }
+
+    #[inline(always)]
+    pub fn is_recording(&self) -> bool {
+        self.recording_path.is_some()
+    }
+
+    pub(crate) fn begin_frame(&mut self) {
+        self.recording_path = self.requested_path.take();
+        self.passes.clear();
+        self.barriers.clear();
+    }
+
+    pub(crate) fn record_pass(&mut self, pass_info: FrameGraphPassInfo) {
+        if !self.is_recording() {
+            return;
+        }
+
+        self.passes.push(FrameGraphPass {
+            name: pass_info.name,
+            reads: pass_info.reads.to_vec(),
+            writes: pass_info.writes.to_vec(),
+        });
+    }
+}
+
+impl FrameGraphCapture {
+    /// Emits the barrier of the tracked texture, see `TexturesPool::ensure_layout`.
+    /// It's recorded only if it's emitted.
+    pub(crate) fn ensure_image_state(
+        &mut self,
+        command_buffer: CommandBuffer,
+        textures_pool: &TexturesPool,
+        (texture_reference, image_name): (TextureReference, &'static str),
+        new_state: ImageState,
+    ) {
+        if let Some(old_state) =
+            textures_pool.ensure_layout(command_buffer, texture_reference, new_state)
+        {
+            self.record_image_barrier(image_name, old_state, new_state);
+        }
+    }
+
+    /// Emits the queued buffer barriers, they're recorded as a single one of `resource_name`.
+    pub(crate) fn flush_buffer_barriers(
+        &mut self,
+        command_buffer: CommandBuffer,
+        buffers_pool: &BuffersPool,
+        resource_name: &'static str,
+    ) {
+        if let Some((old_state, new_state)) = buffers_pool.flush_buffer_barriers(command_buffer) {
+            self.record_barrier(
+                resource_name,
+                None,
+                None,
+                old_state.stage_mask,
+                new_state.stage_mask,
+            );
+        }
+    }
+
+    /// Transitions an untracked color image without mips, e.g. the swapchain one.
+    pub(crate) fn transition_image(
+        &mut self,
+        command_buffer: CommandBuffer,
+        (image, image_name): (Image, &'static str),
+        old_state: ImageState,
+        new_state: ImageState,
+    ) {
+        utils::transition_image(
+            command_buffer,
+            image,
+            old_state.layout,
+            new_state.layout,
+            old_state.stage_mask,
+            new_state.stage_mask,
+            old_state.access_mask,
+            new_state.access_mask,
+            ImageAspectFlags::Color,
+            1,
+        );
+        self.record_image_barrier(image_name, old_state, new_state);
+    }
+
+    /// Release goes to the first command buffer and acquire to the second one, the image keeps
+    /// the layout of the acquire.
+    pub(crate) fn transfer_image_ownership(
+        &mut self,
+        (release_command_buffer, acquire_command_buffer): (CommandBuffer, CommandBuffer),
+        (allocated_image, image_name): (&AllocatedImage, &'static str),
+        (release_state, acquire_state): (ImageState, ImageState),
+        (src_queue_family_index, dst_queue_family_index): (u32, u32),
+    ) {
+        let mip_levels_count = allocated_image.texture_metadata.mip_levels_count;
+        for (command_buffer, src_state, dst_state) in [
+            (release_command_buffer, release_state, Default::default()),
+            (acquire_command_buffer, Default::default(), acquire_state),
+        ] {
+            utils::transfer_image_ownership(
+                command_buffer,
+                allocated_image.image,
+                acquire_state.layout,
+                src_state.stage_mask,
+                dst_state.stage_mask,
+                src_state.access_mask,
+                dst_state.access_mask,
+                src_queue_family_index,
+                dst_queue_family_index,
+                allocated_image.image_aspect_flags,
+                mip_levels_count,
+            );
+        }
+        self.record_ownership_transfer(
+            image_name,
+            Some(acquire_state.layout),
+            release_state.stage_mask,
+            acquire_state.stage_mask,
+            src_queue_family_index,
+            dst_queue_family_index,
+        );
+    }
+
+    fn record_image_barrier(
+        &mut self,
+        resource_name: &'static str,
+        old_state: ImageState,
+        new_state: ImageState,
+    ) {
+        self.record_barrier(
+            resource_name,
+            Some(old_state.layout),
+            Some(new_state.layout),
+            old_state.stage_mask,
+            new_state.stage_mask,
+        );
+    }
+}
+
+#[cfg(feature = "capture")]
+impl FrameGraphCapture {
+    fn record_barrier(
+        &mut self,
+        resource_name: &'static str,
+        old_layout: Option<ImageLayout>,
+        new_layout: Option<ImageLayout>,
+        src_stage_mask: PipelineStageFlags2,
+        dst_stage_mask: PipelineStageFlags2,
+    ) {
+        if !self.is_recording() {
+            return;
+        }
+
+        self.barriers.push(FrameGraphBarrier {
+            resource_name,
+            old_layout,
+            new_layout,
+            src_stage_mask,
+            dst_stage_mask,
+            queue_family_indices: None,
+            pass_index: self.passes.len(),
+        });
+    }
+
+    /// Release and acquire are recorded as a single barrier, from the stage of the release
+    /// to the stage of the acquire.
+    fn record_ownership_transfer(
+        &mut self,
+        resource_name: &'static str,
+        image_layout: Option<ImageLayout>,
+        src_stage_mask: PipelineStageFlags2,
+        dst_stage_mask: PipelineStageFlags2,
+        src_queue_family_index: u32,
+        dst_queue_family_index: u32,
+    ) {
+        if !self.is_recording() {
+            return;
+        }
+
+        self.barriers.push(FrameGraphBarrier {
+            resource_name,
+            old_layout: image_layout,
+            new_layout: image_layout,
+            src_stage_mask,
+            dst_stage_mask,
+            queue_family_indices: Some((src_queue_family_index, dst_queue_family_index)),
+            pass_index: self.passes.len(),
+        });
+    }
+
+    pub(crate) fn end_frame(&mut self) {
+        let Some(path) = self.recording_path.take() else {
+            return;
+        };
+
+        match std::fs::write(&path, self.to_dot()) {
+            Ok(()) => println!("Frame graph is dumped to {}", path.display()),
+            Err(error) => println!(
+                "Failed to dump frame graph to {}: {}",
+                path.display(),
+                error
+            ),
+        }
+    }
+
+    fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        _ = writeln!(dot, "digraph frame_graph {{");
+        _ = writeln!(dot, "    rankdir=LR;");
+        _ = writeln!(dot, "    node [fontname=\"monospace\"];");
+
+        for (pass_index, pass) in self.passes.iter().enumerate() {
+            _ = writeln!(
+                dot,
+                "    pass_{} [shape=box, style=filled, fillcolor=lightblue, label=\"{}. {}\"];",
+                pass_index, pass_index, pass.name
+            );
+        }
+
+        // NOTE: Edge goes from the last writer of a resource to the pass that accesses it next.
+        let mut last_writers: Vec<(&'static str, String)> = Vec::new();
+        let get_last_writer = |last_writers: &Vec<(&'static str, String)>, resource_name| {
+            last_writers
+                .iter()
+                .rev()
+                .find(|(name, _)| *name == resource_name)
+                .map(|(_, node)| node.clone())
+        };
+
+        let mut barriers = self.barriers.iter().enumerate().peekable();
+        // NOTE: One extra iteration for barriers recorded after the last pass.
+        for pass_index in 0..=self.passes.len() {
+            while let Some((barrier_index, barrier)) =
+                barriers.next_if(|(_, barrier)| barrier.pass_index <= pass_index)
+            {
+                let barrier_node = format!("barrier_{}", barrier_index);
+                let (fill_color, queue_families) = match barrier.queue_family_indices {
+                    Some((src_queue_family_index, dst_queue_family_index)) => (
+                        "salmon",
+                        format!(
+                            "\\nqueue family {} -> {}",
+                            src_queue_family_index, dst_queue_family_index
+                        ),
+                    ),
+                    None => ("khaki", String::new()),
+                };
+                _ = writeln!(
+                    dot,
+                    "    {} [shape=diamond, style=filled, fillcolor={}, label=\"{}\\n{}\\n{:?} -> {:?}{}\"];",
+                    barrier_node,
+                    fill_color,
+                    barrier.resource_name,
+                    Self::format_layouts(barrier.old_layout, barrier.new_layout),
+                    barrier.src_stage_mask,
+                    barrier.dst_stage_mask,
+                    queue_families
+                );
+
+                if let Some(last_writer) = get_last_writer(&last_writers, barrier.resource_name) {
+                    _ = writeln!(
+                        dot,
+                        "    {} -> {} [color=orange, label=\"{}\"];",
+                        last_writer, barrier_node, barrier.resource_name
+                    );
+                }
+                last_writers.push((barrier.resource_name, barrier_node));
+            }
+
+            let Some(pass) = self.passes.get(pass_index) else {
+                continue;
+            };
+
+            let pass_node = format!("pass_{}", pass_index);
+            for access in pass.reads.iter().chain(pass.writes.iter()) {
+                let Some(last_writer) = get_last_writer(&last_writers, access.resource_name) else {
+                    continue;
+                };
+                if last_writer == pass_node {
+                    continue;
+                }
+
+                _ = writeln!(
+                    dot,
+                    "    {} -> {} [label=\"{}\"];",
+                    last_writer,
+                    pass_node,
+                    Self::format_access(access)
+                );
+            }
+
+            for access in &pass.writes {
+                last_writers.push((access.resource_name, pass_node.clone()));
+            }
+        }
+
+        _ = writeln!(dot, "}}");
+
+        dot
+    }
+
+    fn format_access(access: &FrameGraphAccess) -> String {
+        match access.image_layout {
+            Some(image_layout) => format!("{} ({:?})", access.resource_name, image_layout),
+            None => access.resource_name.to_string(),
+        }
+    }
+
+    fn format_layouts(old_layout: Option<ImageLayout>, new_layout: Option<ImageLayout>) -> String {
+        match (old_layout, new_layout) {
+            (Some(old_layout), Some(new_layout)) => format!("{:?} -> {:?}", old_layout, new_layout),
+            _ => "buffer".to_string(),
+        }
+    }
+}
+
+#[cfg(not(feature = "capture"))]
+impl FrameGraphCapture {
+    #[inline(always)]
+    pub fn request_dump(&mut self, _path: impl Into<PathBuf>) {}
+
+    #[inline(always)]
+    pub fn get_dump_key(&self) -> Option<KeyCode> {
+        None
+    }
+
+    #[inline(always)]
+    pub fn set_dump_key(&mut self, _dump_key: Option<KeyCode>) {}
+
+    #[inline(always)]
+    pub fn is_recording(&self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    pub(crate) fn begin_frame(&mut self) {}
+
+    #[inline(always)]
+    pub(crate) fn record_pass(&mut self, _pass_info: FrameGraphPassInfo) {}
+
+    #[inline(always)]
+    fn record_barrier(
+        &mut self,
+        _resource_name: &'static str,
+        _old_layout: Option<ImageLayout>,
+        _new_layout: Option<ImageLayout>,
+        _src_stage_mask: PipelineStageFlags2,
+        _dst_stage_mask: PipelineStageFlags2,
+    ) {
+    }
+
+    #[inline(always)]
+    fn record_ownership_transfer(
+        &mut self,
+        _resource_name: &'static str,
+        _image_layout: Option<ImageLayout>,
+        _src_stage_mask: PipelineStageFlags2,
+        _dst_stage_mask: PipelineStageFlags2,
+        _src_queue_family_index: u32,
+        _dst_queue_family_index: u32,
+    ) {
+    }
+
+    #[inline(always)]
+    pub(crate) fn end_frame(&mut self) {}
+}
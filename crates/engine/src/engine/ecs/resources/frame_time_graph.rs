@@ -0,0 +1,241 @@
+#[cfg(feature = "profiler")]
+use std::collections::VecDeque;
+
+use bevy_ecs::resource::Resource;
+use math::Vec2;
+#[cfg(feature = "profiler")]
+use math::Vec4;
+use winit::keyboard::KeyCode;
+
+#[cfg(feature = "profiler")]
+use crate::engine::resources::DebugOverlay;
+
+/// Durations in milliseconds.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct FrameTimeSample {
+    /// Time between the frames, it's what the player sees.
+    pub frame_time: f32,
+    pub cpu_time: f32,
+    pub gpu_time: f32,
+}
+
+/// Frame times of the last frames drawn as CPU and GPU bars through `DebugOverlay`.
+/// Horizontal lines mark the target frame time and the 1% low, hitches stand out above them.
+/// Without the `profiler` feature it's a no-op, samples aren't kept.
+#[cfg(feature = "profiler")]
+#[derive(Resource)]
+pub struct FrameTimeGraph {
+    is_visible: bool,
+    toggle_key: Option<KeyCode>,
+    samples: VecDeque<FrameTimeSample>,
+    /// Frame time at the middle of the graph, in milliseconds.
+    pub target_frame_time: f32,
+    /// Top left corner, in pixels of the render extent.
+    pub position: Vec2,
+    pub bar_width: f32,
+    pub height: f32,
+}
+
+#[cfg(feature = "profiler")]
+impl Default for FrameTimeGraph {
+    fn default() -> Self {
+        Self {
+            is_visible: false,
+            toggle_key: Some(KeyCode::F3),
+            samples: VecDeque::with_capacity(Self::MAX_SAMPLES_COUNT),
+            target_frame_time: 1000.0 / 60.0,
+            position: Vec2::new(16.0, 16.0),
+            bar_width: 2.0,
+            height: 160.0,
+        }
+    }
+}
+
+impl FrameTimeGraph {
+    pub const MAX_SAMPLES_COUNT: usize = 240;
+}
+
+#[cfg(feature = "profiler")]
+impl FrameTimeGraph {
+    const BACKGROUND_COLOR: Vec4 = Vec4::new(0.0, 0.0, 0.0, 0.6);
+    const CPU_COLOR: Vec4 = Vec4::new(0.25, 0.75, 1.0, 0.9);
+    const GPU_COLOR: Vec4 = Vec4::new(1.0, 0.6, 0.2, 0.9);
+    const TARGET_COLOR: Vec4 = Vec4::new(0.3, 1.0, 0.3, 0.8);
+    const ONE_PERCENT_LOW_COLOR: Vec4 = Vec4::new(1.0, 0.25, 0.25, 0.8);
+    const LINE_THICKNESS: f32 = 1.0;
+
+    pub fn with_toggle_key(mut self, toggle_key: Option<KeyCode>) -> Self {
+        self.toggle_key = toggle_key;
+
+        self
+    }
+
+    #[inline(always)]
+    pub fn get_toggle_key(&self) -> Option<KeyCode> {
+        self.toggle_key
+    }
+
+    #[inline(always)]
+    pub fn is_visible(&self) -> bool {
+        self.is_visible
+    }
+
+    #[inline(always)]
+    pub fn toggle(&mut self) {
+        self.is_visible = !self.is_visible;
+    }
+
+    /// Oldest sample is dropped once the graph is full.
+    pub fn push_sample(&mut self, sample: FrameTimeSample) {
+        if self.samples.len() >= Self::MAX_SAMPLES_COUNT {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back(sample);
+    }
+
+    /// Oldest first.
+    pub fn get_samples(&self) -> impl Iterator<Item = &FrameTimeSample> {
+        self.samples.iter()
+    }
+
+    pub fn get_average_frame_time(&self) -> f32 {
+        if self.samples.is_empty() {
+            return Default::default();
+        }
+
+        self.samples
+            .iter()
+            .map(|sample| sample.frame_time)
+            .sum::<f32>()
+            / self.samples.len() as f32
+    }
+
+    /// Average of the slowest 1% of the frames, at least one of them.
+    pub fn get_one_percent_low_frame_time(&self) -> f32 {
+        let mut frame_times: Vec<_> = self
+            .samples
+            .iter()
+            .map(|sample| sample.frame_time)
+            .collect();
+        if frame_times.is_empty() {
+            return Default::default();
+        }
+        frame_times.sort_unstable_by(|a, b| b.total_cmp(a));
+
+        let slowest_frames_count = frame_times.len().div_ceil(100);
+        frame_times[..slowest_frames_count].iter().sum::<f32>() / slowest_frames_count as f32
+    }
+
+    /// Bars are scaled so the target frame time is at the middle, the slower frames are clipped.
+    pub(crate) fn draw(&self, debug_overlay: &mut DebugOverlay) {
+        let max_frame_time = (self.target_frame_time * 2.0).max(f32::EPSILON);
+        let bar_width = self.bar_width.max(1.0);
+        let pair_width = bar_width * 2.0;
+        let width = pair_width * Self::MAX_SAMPLES_COUNT as f32;
+        let get_bar_height = |frame_time: f32| (frame_time / max_frame_time).min(1.0) * self.height;
+
+        debug_overlay.draw_rect(
+            self.position,
+            Vec2::new(width, self.height),
+            Self::BACKGROUND_COLOR,
+        );
+
+        let bottom = self.position.y + self.height;
+        // NOTE: Newest sample is at the right edge, so the graph scrolls to the left.
+        let first_sample_x = self.position.x + width - pair_width * self.samples.len() as f32;
+        for (sample_index, sample) in self.samples.iter().enumerate() {
+            let x = first_sample_x + pair_width * sample_index as f32;
+
+            let cpu_height = get_bar_height(sample.cpu_time);
+            debug_overlay.draw_rect(
+                Vec2::new(x, bottom - cpu_height),
+                Vec2::new(bar_width, cpu_height),
+                Self::CPU_COLOR,
+            );
+            let gpu_height = get_bar_height(sample.gpu_time);
+            debug_overlay.draw_rect(
+                Vec2::new(x + bar_width, bottom - gpu_height),
+                Vec2::new(bar_width, gpu_height),
+                Self::GPU_COLOR,
+            );
+        }
+
+        for (frame_time, color) in [
+            (self.target_frame_time, Self::TARGET_COLOR),
+            (
+                self.get_one_percent_low_frame_time(),
+                Self::ONE_PERCENT_LOW_COLOR,
+            ),
+        ] {
+            debug_overlay.draw_rect(
+                Vec2::new(
+                    self.position.x,
+                    bottom - get_bar_height(frame_time) - Self::LINE_THICKNESS * 0.5,
+                ),
+                Vec2::new(width, Self::LINE_THICKNESS),
+                color,
+            );
+        }
+    }
+}
+
+#[cfg(not(feature = "profiler"))]
+#[derive(Resource)]
+pub struct FrameTimeGraph {
+    pub target_frame_time: f32,
+    pub position: Vec2,
+    pub bar_width: f32,
+    pub height: f32,
+}
+
+#[cfg(not(feature = "profiler"))]
+impl Default for FrameTimeGraph {
+    fn default() -> Self {
+        Self {
+            target_frame_time: 1000.0 / 60.0,
+            position: Vec2::new(16.0, 16.0),
+            bar_width: 2.0,
+            height: 160.0,
+        }
+    }
+}
+
+#[cfg(not(feature = "profiler"))]
+impl FrameTimeGraph {
+    #[inline(always)]
+    pub fn with_toggle_key(self, _toggle_key: Option<KeyCode>) -> Self {
+        self
+    }
+
+    #[inline(always)]
+    pub fn get_toggle_key(&self) -> Option<KeyCode> {
+        None
+    }
+
+    #[inline(always)]
+    pub fn is_visible(&self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    pub fn toggle(&mut self) {}
+
+    #[inline(always)]
+    pub fn push_sample(&mut self, _sample: FrameTimeSample) {}
+
+    #[inline(always)]
+    pub fn get_samples(&self) -> impl Iterator<Item = &FrameTimeSample> {
+        std::iter::empty()
+    }
+
+    #[inline(always)]
+    pub fn get_average_frame_time(&self) -> f32 {
+        Default::default()
+    }
+
+    #[inline(always)]
+    pub fn get_one_percent_low_frame_time(&self) -> f32 {
+        Default::default()
+    }
+}
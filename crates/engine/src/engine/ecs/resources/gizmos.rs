@@ -0,0 +1,130 @@
+use bevy_ecs::resource::Resource;
+use math::{Vec3, Vec4};
+
+#[cfg(feature = "debug-tools")]
+#[derive(Clone, Copy)]
+pub(crate) struct GizmoLine {
+    pub(crate) start: Vec3,
+    pub(crate) end: Vec3,
+    pub(crate) color: Vec4,
+}
+
+/// World space lines drawn through `DebugOverlay` for a single frame, so they're drawn every frame.
+/// Lines are projected by the first camera and aren't hidden by the scene.
+/// Without the `debug-tools` feature it's a no-op.
+#[cfg(feature = "debug-tools")]
+#[derive(Default, Resource)]
+pub struct Gizmos {
+    /// Frusta of the cameras and the lights with their directions and ranges are drawn from their
+    /// components, see `CVars::GIZMOS`.
+    pub is_scene_gizmos_enabled: bool,
+    lines: Vec<GizmoLine>,
+}
+
+#[cfg(feature = "debug-tools")]
+impl Gizmos {
+    pub fn draw_line(&mut self, start: Vec3, end: Vec3, color: Vec4) {
+        self.lines.push(GizmoLine { start, end, color });
+    }
+
+    /// Arrow from `start` to `end`, the head is a fifth of its length.
+    pub fn draw_arrow(&mut self, start: Vec3, end: Vec3, color: Vec4) {
+        self.draw_line(start, end, color);
+
+        let direction = end - start;
+        let Some(axis) = direction.try_normalize() else {
+            return;
+        };
+        let normal = axis.any_orthonormal_vector();
+        let binormal = axis.cross(normal);
+        let head_base = end - direction * 0.2;
+        let head_radius = direction.length() * 0.08;
+        for offset in [normal, -normal, binormal, -binormal] {
+            self.draw_line(end, head_base + offset * head_radius, color);
+        }
+    }
+
+    /// Circle in the plane facing `normal`.
+    pub fn draw_circle(&mut self, center: Vec3, normal: Vec3, radius: f32, color: Vec4) {
+        const SEGMENTS_COUNT: usize = 24;
+
+        let Some(normal) = normal.try_normalize() else {
+            return;
+        };
+        let tangent = normal.any_orthonormal_vector();
+        let bitangent = normal.cross(tangent);
+        let get_point = |segment_index: usize| {
+            let angle = segment_index as f32 / SEGMENTS_COUNT as f32 * std::f32::consts::TAU;
+
+            center + (tangent * angle.cos() + bitangent * angle.sin()) * radius
+        };
+
+        for segment_index in 0..SEGMENTS_COUNT {
+            self.draw_line(
+                get_point(segment_index),
+                get_point(segment_index + 1),
+                color,
+            );
+        }
+    }
+
+    /// Three circles around the axes.
+    pub fn draw_sphere(&mut self, center: Vec3, radius: f32, color: Vec4) {
+        for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+            self.draw_circle(center, axis, radius, color);
+        }
+    }
+
+    /// Edges of the box between `corners`, the first four are the near rectangle and the rest are
+    /// the far one in the same winding.
+    pub fn draw_box(&mut self, corners: [Vec3; 8], color: Vec4) {
+        for corner_index in 0..4 {
+            let next_corner_index = (corner_index + 1) % 4;
+            self.draw_line(corners[corner_index], corners[next_corner_index], color);
+            self.draw_line(
+                corners[corner_index + 4],
+                corners[next_corner_index + 4],
+                color,
+            );
+            self.draw_line(corners[corner_index], corners[corner_index + 4], color);
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    pub(crate) fn take_lines(&mut self) -> Vec<GizmoLine> {
+        std::mem::take(&mut self.lines)
+    }
+}
+
+#[cfg(not(feature = "debug-tools"))]
+#[derive(Default, Resource)]
+pub struct Gizmos {
+    pub is_scene_gizmos_enabled: bool,
+}
+
+#[cfg(not(feature = "debug-tools"))]
+impl Gizmos {
+    #[inline(always)]
+    pub fn draw_line(&mut self, _start: Vec3, _end: Vec3, _color: Vec4) {}
+
+    #[inline(always)]
+    pub fn draw_arrow(&mut self, _start: Vec3, _end: Vec3, _color: Vec4) {}
+
+    #[inline(always)]
+    pub fn draw_circle(&mut self, _center: Vec3, _normal: Vec3, _radius: f32, _color: Vec4) {}
+
+    #[inline(always)]
+    pub fn draw_sphere(&mut self, _center: Vec3, _radius: f32, _color: Vec4) {}
+
+    #[inline(always)]
+    pub fn draw_box(&mut self, _corners: [Vec3; 8], _color: Vec4) {}
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        true
+    }
+}
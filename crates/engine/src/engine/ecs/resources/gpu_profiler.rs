@@ -0,0 +1,134 @@
+use bevy_ecs::resource::Resource;
+use vulkanite::vk::{
+    PipelineStageFlags2, QueryPoolCreateInfo, QueryResultFlags, QueryType,
+    rs::{CommandBuffer, Device, QueryPool},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GpuTimestamp {
+    FrameBegin,
+    BackgroundEnd,
+    MeshesEnd,
+    FrameEnd,
+}
+
+impl GpuTimestamp {
+    const COUNT: u32 = 4;
+}
+
+/// Durations in milliseconds.
+#[derive(Default, Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct GpuPassTimes {
+    pub background_time: f32,
+    pub meshes_time: f32,
+    pub blit_time: f32,
+    pub frame_time: f32,
+}
+
+/// Timestamps of every frame in flight, they're read back once the frame slot is reused.
+#[derive(Resource)]
+pub struct GpuProfiler {
+    query_pool: QueryPool,
+    timestamp_period: f32,
+    written_frames: Vec<bool>,
+    current_frame_index: usize,
+    last_pass_times: GpuPassTimes,
+}
+
+impl GpuProfiler {
+    pub(crate) fn new(device: &Device, frame_overlap: usize, timestamp_period: f32) -> Self {
+        let query_pool_create_info = QueryPoolCreateInfo::default()
+            .query_type(QueryType::Timestamp)
+            .query_count(GpuTimestamp::COUNT * frame_overlap as u32);
+        let query_pool = device.create_query_pool(&query_pool_create_info).unwrap();
+
+        Self {
+            query_pool,
+            timestamp_period,
+            written_frames: vec![false; frame_overlap],
+            current_frame_index: Default::default(),
+            last_pass_times: Default::default(),
+        }
+    }
+
+    pub fn get_last_pass_times(&self) -> GpuPassTimes {
+        self.last_pass_times
+    }
+
+    /// Must be called after the fence of the frame is waited and its command buffer began.
+    pub(crate) fn begin_frame(
+        &mut self,
+        device: &Device,
+        command_buffer: CommandBuffer,
+        frame_number: usize,
+    ) {
+        self.current_frame_index = frame_number % self.written_frames.len();
+        let first_query = self.get_first_query();
+
+        if self.written_frames[self.current_frame_index] {
+            let mut timestamps = [0u64; GpuTimestamp::COUNT as usize];
+            let is_available = device
+                .get_query_pool_results(
+                    self.query_pool,
+                    first_query,
+                    GpuTimestamp::COUNT,
+                    timestamps.as_mut_slice(),
+                    std::mem::size_of::<u64>() as _,
+                    QueryResultFlags::Result64,
+                )
+                .is_ok();
+
+            if is_available {
+                let to_milliseconds = |from: GpuTimestamp, to: GpuTimestamp| {
+                    let ticks = timestamps[to as usize].saturating_sub(timestamps[from as usize]);
+
+                    ticks as f32 * self.timestamp_period / 1_000_000.0
+                };
+
+                self.last_pass_times = GpuPassTimes {
+                    background_time: to_milliseconds(
+                        GpuTimestamp::FrameBegin,
+                        GpuTimestamp::BackgroundEnd,
+                    ),
+                    meshes_time: to_milliseconds(
+                        GpuTimestamp::BackgroundEnd,
+                        GpuTimestamp::MeshesEnd,
+                    ),
+                    blit_time: to_milliseconds(GpuTimestamp::MeshesEnd, GpuTimestamp::FrameEnd),
+                    frame_time: to_milliseconds(GpuTimestamp::FrameBegin, GpuTimestamp::FrameEnd),
+                };
+            }
+        }
+
+        command_buffer.reset_query_pool(self.query_pool, first_query, GpuTimestamp::COUNT);
+        self.write_timestamp(
+            command_buffer,
+            GpuTimestamp::FrameBegin,
+            PipelineStageFlags2::TopOfPipe,
+        );
+        self.written_frames[self.current_frame_index] = true;
+    }
+
+    pub(crate) fn write_timestamp(
+        &self,
+        command_buffer: CommandBuffer,
+        timestamp: GpuTimestamp,
+        stage: PipelineStageFlags2,
+    ) {
+        command_buffer.write_timestamp2(
+            stage,
+            self.query_pool,
+            self.get_first_query() + timestamp as u32,
+        );
+    }
+
+    pub(crate) unsafe fn destroy(&self, device: &Device) {
+        device.destroy_query_pool(Some(self.query_pool));
+    }
+
+    #[inline(always)]
+    fn get_first_query(&self) -> u32 {
+        self.current_frame_index as u32 * GpuTimestamp::COUNT
+    }
+}
@@ -0,0 +1,25 @@
+use bevy_ecs::resource::Resource;
+
+/// Preprocessing of imported meshes before they are optimized, it applies to models loaded afterwards.
+#[derive(Resource, Clone, Copy)]
+pub struct ImportSettings {
+    /// Vertices closer than the epsilons are merged, exported models often split them only by the precision.
+    pub is_welding_enabled: bool,
+    pub weld_position_epsilon: f32,
+    pub weld_normal_epsilon: f32,
+    pub weld_uv_epsilon: f32,
+    /// Triangles with repeated vertices or without an area are dropped.
+    pub is_degenerate_removal_enabled: bool,
+}
+
+impl Default for ImportSettings {
+    fn default() -> Self {
+        Self {
+            is_welding_enabled: false,
+            weld_position_epsilon: 0.0001,
+            weld_normal_epsilon: 0.001,
+            weld_uv_epsilon: 0.0001,
+            is_degenerate_removal_enabled: false,
+        }
+    }
+}
@@ -64,6 +64,12 @@ impl Input {
         self.mouse_axis += mouse_delta;
     }
 
+    #[inline(always)]
+    pub(crate) fn release_all(&mut self) {
+        self.just_released.extend(self.pressed.drain());
+        self.just_pressed.clear();
+    }
+
     #[inline(always)]
     pub(crate) fn reset(&mut self) {
         self.just_pressed.clear();
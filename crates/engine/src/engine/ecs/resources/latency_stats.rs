@@ -0,0 +1,82 @@
+use std::time::{Duration, Instant};
+
+use bevy_ecs::resource::Resource;
+
+use crate::engine::resources::LatencyMode;
+
+/// Timings of the frame from the input sampling to the present, all of them are in seconds.
+/// Driver doesn't report when the image reaches the display, so the present is predicted.
+#[derive(Resource, Default, Debug)]
+pub struct LatencyStats {
+    /// Work of the last frame on the CPU, without the waits.
+    pub cpu_time: f32,
+    /// Blocked on the fence of the frame and the acquire of the swapchain image.
+    pub wait_time: f32,
+    /// Slept before the input of the next frame is sampled, only in the low latency mode.
+    pub pacing_time: f32,
+    /// Smoothed time between presents.
+    pub present_interval: f32,
+    frame_start: Option<Instant>,
+    last_present: Option<Instant>,
+}
+
+impl LatencyStats {
+    const SMOOTHING_FACTOR: f32 = 0.1;
+    /// Wait that is kept, so a slower frame of the GPU doesn't miss the vertical blank.
+    const PACING_MARGIN: f32 = 0.001;
+    const MAX_PACING_TIME: f32 = 0.05;
+
+    /// Present of the next frame, if the interval stays the same.
+    pub fn get_predicted_present_time(&self) -> Option<Instant> {
+        self.last_present
+            .map(|last_present| last_present + Duration::from_secs_f32(self.present_interval))
+    }
+
+    /// From the input sampling to the present, the image is expected an interval after the submission.
+    pub fn get_predicted_latency(&self) -> f32 {
+        self.cpu_time + self.wait_time + self.present_interval
+    }
+
+    #[inline(always)]
+    pub(crate) fn begin_frame(&mut self) {
+        self.frame_start = Some(Instant::now());
+        self.wait_time = Default::default();
+    }
+
+    #[inline(always)]
+    pub(crate) fn add_wait_time(&mut self, wait_start: Instant) {
+        self.wait_time += wait_start.elapsed().as_secs_f32();
+    }
+
+    pub(crate) fn record_present(&mut self) {
+        let now = Instant::now();
+        if let Some(last_present) = self.last_present {
+            let present_interval = now.duration_since(last_present).as_secs_f32();
+            self.present_interval = if self.present_interval == Default::default() {
+                present_interval
+            } else {
+                self.present_interval
+                    + (present_interval - self.present_interval) * Self::SMOOTHING_FACTOR
+            };
+        }
+
+        self.last_present = Some(now);
+    }
+
+    /// Returns the sleep before the next frame. Pacing grows while the frame still waits for the GPU
+    /// or the display, so the input is sampled later without losing the throughput.
+    pub(crate) fn end_frame(&mut self, latency_mode: LatencyMode) -> Duration {
+        let Some(frame_start) = self.frame_start.take() else {
+            return Duration::ZERO;
+        };
+        self.cpu_time = (frame_start.elapsed().as_secs_f32() - self.wait_time).max(0.0);
+
+        self.pacing_time = match latency_mode {
+            LatencyMode::Throughput => Default::default(),
+            LatencyMode::LowLatency => (self.pacing_time + self.wait_time - Self::PACING_MARGIN)
+                .clamp(0.0, Self::MAX_PACING_TIME),
+        };
+
+        Duration::from_secs_f32(self.pacing_time)
+    }
+}
@@ -0,0 +1,356 @@
+use bevy_ecs::resource::Resource;
+use math::Vec2;
+#[cfg(feature = "debug-tools")]
+use math::Vec4;
+use winit::keyboard::KeyCode;
+
+#[cfg(feature = "debug-tools")]
+use crate::engine::resources::DebugOverlay;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum MemoryPool {
+    MeshBuffers,
+    Textures,
+    RenderTargets,
+    Materials,
+    Staging,
+    /// Images and buffers of `TransientResourcesPool`.
+    Transient,
+    OtherBuffers,
+}
+
+impl MemoryPool {
+    pub const COUNT: usize = 7;
+    pub const ALL: [Self; Self::COUNT] = [
+        Self::MeshBuffers,
+        Self::Textures,
+        Self::RenderTargets,
+        Self::Materials,
+        Self::Staging,
+        Self::Transient,
+        Self::OtherBuffers,
+    ];
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Self::MeshBuffers => "mesh buffers",
+            Self::Textures => "textures",
+            Self::RenderTargets => "render targets",
+            Self::Materials => "materials",
+            Self::Staging => "staging",
+            Self::Transient => "transient",
+            Self::OtherBuffers => "other buffers",
+        }
+    }
+
+    #[cfg(feature = "debug-tools")]
+    fn get_color(self) -> Vec4 {
+        match self {
+            Self::MeshBuffers => Vec4::new(0.25, 0.75, 1.0, 0.9),
+            Self::Textures => Vec4::new(1.0, 0.6, 0.2, 0.9),
+            Self::RenderTargets => Vec4::new(1.0, 0.3, 0.6, 0.9),
+            Self::Materials => Vec4::new(0.3, 1.0, 0.3, 0.9),
+            Self::Staging => Vec4::new(1.0, 1.0, 0.3, 0.9),
+            Self::Transient => Vec4::new(0.7, 0.4, 1.0, 0.9),
+            Self::OtherBuffers => Vec4::new(0.6, 0.6, 0.6, 0.9),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MemoryAllocation {
+    pub name: String,
+    pub pool: MemoryPool,
+    pub size: u64,
+    pub is_device_local: bool,
+    /// Samples of a texture counted by `TextureUsage`, it's `None` while it isn't tracked.
+    pub samples_count: Option<u64>,
+}
+
+/// Sizes are in bytes of the allocations, so the alignment is included.
+#[derive(Default, Clone, Debug)]
+#[non_exhaustive]
+pub struct MemoryReport {
+    pub device_local_bytes_count: u64,
+    /// Memory mapped by the host counts here, even when it's device local as well.
+    pub host_visible_bytes_count: u64,
+    pub pools_bytes_counts: [u64; MemoryPool::COUNT],
+    /// Biggest first.
+    pub largest_allocations: Vec<MemoryAllocation>,
+    /// Totals of VMA, the blocks include their free space.
+    pub allocations_count: u32,
+    pub allocation_bytes_count: u64,
+    pub block_bytes_count: u64,
+    /// Textures with no samples through the frames counted by `TextureUsage`.
+    pub unsampled_textures_count: u32,
+    pub unsampled_texture_bytes_count: u64,
+    pub texture_usage_frames_count: u32,
+}
+
+impl MemoryReport {
+    #[inline(always)]
+    pub fn get_pool_bytes_count(&self, pool: MemoryPool) -> u64 {
+        self.pools_bytes_counts[pool as usize]
+    }
+
+    pub fn get_lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            std::format!(
+                "Device local: {}, host visible: {}",
+                format_bytes_count(self.device_local_bytes_count),
+                format_bytes_count(self.host_visible_bytes_count)
+            ),
+            std::format!(
+                "VMA: {} allocations, {} in {} of blocks",
+                self.allocations_count,
+                format_bytes_count(self.allocation_bytes_count),
+                format_bytes_count(self.block_bytes_count)
+            ),
+        ];
+        lines.extend(MemoryPool::ALL.iter().map(|&pool| {
+            std::format!(
+                "  {}: {}",
+                pool.get_name(),
+                format_bytes_count(self.get_pool_bytes_count(pool))
+            )
+        }));
+        if self.texture_usage_frames_count > Default::default() {
+            lines.push(std::format!(
+                "Unsampled textures in {} frames: {}, {}",
+                self.texture_usage_frames_count,
+                self.unsampled_textures_count,
+                format_bytes_count(self.unsampled_texture_bytes_count)
+            ));
+        }
+        lines.push("Largest allocations:".to_string());
+        lines.extend(self.largest_allocations.iter().map(|allocation| {
+            let samples = allocation
+                .samples_count
+                .map(|samples_count| std::format!(", {} samples", samples_count))
+                .unwrap_or_default();
+
+            std::format!(
+                "  {} ({}, {}{}): {}",
+                allocation.name,
+                allocation.pool.get_name(),
+                if allocation.is_device_local {
+                    "device local"
+                } else {
+                    "host visible"
+                },
+                samples,
+                format_bytes_count(allocation.size)
+            )
+        }));
+
+        lines
+    }
+}
+
+fn format_bytes_count(bytes_count: u64) -> String {
+    const MEBIBYTE: f64 = 1024.0 * 1024.0;
+
+    if bytes_count as f64 >= MEBIBYTE {
+        std::format!("{:.1} MiB", bytes_count as f64 / MEBIBYTE)
+    } else {
+        std::format!("{:.1} KiB", bytes_count as f64 / 1024.0)
+    }
+}
+
+/// Memory usage of the pools drawn as stacked bars through `DebugOverlay`: the upper bar splits
+/// device local and host visible memory, the lower one splits it by the pools.
+/// Names of the allocations are logged to the console, the overlay doesn't draw text.
+/// Without the `debug-tools` feature it's a no-op, the report stays empty.
+#[cfg(feature = "debug-tools")]
+#[derive(Resource)]
+pub struct MemoryOverlay {
+    is_visible: bool,
+    toggle_key: Option<KeyCode>,
+    report: MemoryReport,
+    is_report_requested: bool,
+    frames_since_update_count: u32,
+    /// Report is gathered once per this count of frames while the overlay is visible.
+    pub update_interval: u32,
+    pub largest_allocations_count: usize,
+    /// Top left corner, in pixels of the render extent.
+    pub position: Vec2,
+    pub width: f32,
+    pub bar_height: f32,
+}
+
+#[cfg(feature = "debug-tools")]
+impl Default for MemoryOverlay {
+    fn default() -> Self {
+        Self {
+            is_visible: false,
+            toggle_key: Some(KeyCode::F4),
+            report: Default::default(),
+            is_report_requested: false,
+            frames_since_update_count: Default::default(),
+            update_interval: 30,
+            largest_allocations_count: 8,
+            position: Vec2::new(16.0, 192.0),
+            width: 480.0,
+            bar_height: 16.0,
+        }
+    }
+}
+
+#[cfg(feature = "debug-tools")]
+impl MemoryOverlay {
+    const BACKGROUND_COLOR: Vec4 = Vec4::new(0.0, 0.0, 0.0, 0.6);
+    const DEVICE_LOCAL_COLOR: Vec4 = Vec4::new(0.4, 0.4, 1.0, 0.9);
+    const HOST_VISIBLE_COLOR: Vec4 = Vec4::new(1.0, 0.4, 0.4, 0.9);
+    const PADDING: f32 = 4.0;
+
+    pub fn with_toggle_key(mut self, toggle_key: Option<KeyCode>) -> Self {
+        self.toggle_key = toggle_key;
+
+        self
+    }
+
+    #[inline(always)]
+    pub fn get_toggle_key(&self) -> Option<KeyCode> {
+        self.toggle_key
+    }
+
+    #[inline(always)]
+    pub fn is_visible(&self) -> bool {
+        self.is_visible
+    }
+
+    pub fn toggle(&mut self) {
+        self.is_visible = !self.is_visible;
+        self.frames_since_update_count = self.update_interval;
+    }
+
+    /// Report is gathered and logged to the console at the next frame.
+    #[inline(always)]
+    pub fn request_report(&mut self) {
+        self.is_report_requested = true;
+    }
+
+    /// Last gathered one, it's empty until the overlay is shown or the report is requested.
+    #[inline(always)]
+    pub fn get_report(&self) -> &MemoryReport {
+        &self.report
+    }
+
+    /// Returns `true`, if the report has to be gathered at this frame.
+    pub(crate) fn begin_update(&mut self) -> bool {
+        self.frames_since_update_count = self.frames_since_update_count.saturating_add(1);
+
+        let is_update_due =
+            self.is_visible && self.frames_since_update_count >= self.update_interval;
+        if !is_update_due && !self.is_report_requested {
+            return false;
+        }
+        self.frames_since_update_count = Default::default();
+
+        true
+    }
+
+    /// Returns `true`, if the report was requested.
+    pub(crate) fn set_report(&mut self, report: MemoryReport) -> bool {
+        self.report = report;
+
+        std::mem::take(&mut self.is_report_requested)
+    }
+
+    /// Bars are scaled by the total of the device local and host visible memory.
+    pub(crate) fn draw(&self, debug_overlay: &mut DebugOverlay) {
+        let report = &self.report;
+        let total_bytes_count =
+            (report.device_local_bytes_count + report.host_visible_bytes_count).max(1) as f32;
+        let bars_width = self.width - Self::PADDING * 2.0;
+
+        debug_overlay.draw_rect(
+            self.position,
+            Vec2::new(self.width, self.bar_height * 2.0 + Self::PADDING * 3.0),
+            Self::BACKGROUND_COLOR,
+        );
+
+        let mut draw_stacked_bar = |y: f32, segments: &mut dyn Iterator<Item = (u64, Vec4)>| {
+            let mut x = self.position.x + Self::PADDING;
+            for (bytes_count, color) in segments {
+                let segment_width = bytes_count as f32 / total_bytes_count * bars_width;
+                debug_overlay.draw_rect(
+                    Vec2::new(x, y),
+                    Vec2::new(segment_width, self.bar_height),
+                    color,
+                );
+                x += segment_width;
+            }
+        };
+
+        draw_stacked_bar(
+            self.position.y + Self::PADDING,
+            &mut [
+                (report.device_local_bytes_count, Self::DEVICE_LOCAL_COLOR),
+                (report.host_visible_bytes_count, Self::HOST_VISIBLE_COLOR),
+            ]
+            .into_iter(),
+        );
+        draw_stacked_bar(
+            self.position.y + self.bar_height + Self::PADDING * 2.0,
+            &mut MemoryPool::ALL
+                .iter()
+                .map(|&pool| (report.get_pool_bytes_count(pool), pool.get_color())),
+        );
+    }
+}
+
+#[cfg(not(feature = "debug-tools"))]
+#[derive(Resource)]
+pub struct MemoryOverlay {
+    report: MemoryReport,
+    pub update_interval: u32,
+    pub largest_allocations_count: usize,
+    pub position: Vec2,
+    pub width: f32,
+    pub bar_height: f32,
+}
+
+#[cfg(not(feature = "debug-tools"))]
+impl Default for MemoryOverlay {
+    fn default() -> Self {
+        Self {
+            report: Default::default(),
+            update_interval: 30,
+            largest_allocations_count: 8,
+            position: Vec2::new(16.0, 192.0),
+            width: 480.0,
+            bar_height: 16.0,
+        }
+    }
+}
+
+#[cfg(not(feature = "debug-tools"))]
+impl MemoryOverlay {
+    #[inline(always)]
+    pub fn with_toggle_key(self, _toggle_key: Option<KeyCode>) -> Self {
+        self
+    }
+
+    #[inline(always)]
+    pub fn get_toggle_key(&self) -> Option<KeyCode> {
+        None
+    }
+
+    #[inline(always)]
+    pub fn is_visible(&self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    pub fn toggle(&mut self) {}
+
+    #[inline(always)]
+    pub fn request_report(&mut self) {}
+
+    #[inline(always)]
+    pub fn get_report(&self) -> &MemoryReport {
+        &self.report
+    }
+}
@@ -1,13 +1,91 @@
+pub mod asset_hot_reload;
+pub mod clipboard;
+pub mod console;
+pub mod cubemap_capture;
+pub mod culling_settings;
+pub mod cvars;
+pub mod debug_overlay;
+pub mod defragmentation_settings;
+pub mod deletion_queue;
+pub mod depth_picking;
+pub mod device_capabilities;
 pub mod device_properties;
+pub mod engine_config;
+pub mod engine_state;
+pub mod entity_inspector;
+pub mod environment_settings;
+pub mod frame_arena;
+pub mod frame_capture;
 pub mod frame_context;
+pub mod frame_errors;
+pub mod frame_graph_capture;
+pub mod frame_time_graph;
+pub mod gizmos;
+pub mod gpu_profiler;
+pub mod import_settings;
 pub mod input;
+pub mod latency_stats;
+pub mod memory_overlay;
+pub mod outline_settings;
+pub mod post_process_settings;
 pub mod render_context;
 pub mod render_resources;
+pub mod render_scale_settings;
+pub mod render_statistics;
+pub mod replay;
+pub mod scene_dump;
+pub mod script_host;
+pub mod snapshot;
+pub mod task_pool;
+pub mod text_input;
+pub mod texture_usage;
+pub mod time_of_day;
+pub mod type_registry;
 pub mod vulkan_context_resource;
+pub mod window_settings;
 
+pub use asset_hot_reload::*;
+pub use clipboard::*;
+pub use console::*;
+pub use cubemap_capture::*;
+pub use culling_settings::*;
+pub use cvars::*;
+pub use debug_overlay::*;
+pub use defragmentation_settings::*;
+pub use deletion_queue::*;
+pub use depth_picking::*;
+pub use device_capabilities::*;
 pub use device_properties::*;
+pub use engine_config::*;
+pub use engine_state::*;
+pub use entity_inspector::*;
+pub use environment_settings::*;
+pub use frame_arena::*;
+pub use frame_capture::*;
 pub use frame_context::*;
+pub use frame_errors::*;
+pub use frame_graph_capture::*;
+pub use frame_time_graph::*;
+pub use gizmos::*;
+pub use gpu_profiler::*;
+pub use import_settings::*;
 pub use input::*;
+pub use latency_stats::*;
+pub use memory_overlay::*;
+pub use outline_settings::*;
+pub use post_process_settings::*;
 pub use render_context::*;
 pub use render_resources::*;
+pub use render_scale_settings::*;
+pub use render_statistics::*;
+pub use replay::*;
+pub use scene_dump::*;
+pub use script_host::*;
+pub use snapshot::*;
+pub use task_pool::*;
+pub use text_input::*;
+pub use texture_usage::*;
+pub use time_of_day::*;
+pub use type_registry::*;
 pub use vulkan_context_resource::*;
+pub use window_settings::*;
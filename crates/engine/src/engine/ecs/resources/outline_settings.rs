@@ -0,0 +1,35 @@
+use bevy_ecs::resource::Resource;
+use math::Vec3;
+
+#[derive(Clone, Copy, Debug)]
+pub struct OutlineStyle {
+    pub color: Vec3,
+    /// In pixels of the render extent, it's clamped to `MAX_THICKNESS`.
+    pub thickness: u32,
+}
+
+impl OutlineStyle {
+    /// Outline pass searches this many pixels around every pixel.
+    pub const MAX_THICKNESS: u32 = 8;
+
+    pub const fn new(color: Vec3, thickness: u32) -> Self {
+        Self { color, thickness }
+    }
+}
+
+/// Outlines are drawn around visible parts of `Selected` and `Highlighted` meshes,
+/// the pass is skipped while there are none.
+#[derive(Resource)]
+pub struct OutlineSettings {
+    pub selected: OutlineStyle,
+    pub highlighted: OutlineStyle,
+}
+
+impl Default for OutlineSettings {
+    fn default() -> Self {
+        Self {
+            selected: OutlineStyle::new(Vec3::new(1.0, 0.55, 0.1), 2),
+            highlighted: OutlineStyle::new(Vec3::new(0.3, 0.7, 1.0), 1),
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use std::path::PathBuf;
+
+use bevy_ecs::resource::Resource;
+use math::Vec3;
+
+use crate::engine::resources::textures_pool::TextureReference;
+
+#[derive(Clone, Copy, Debug)]
+pub struct AutoExposure {
+    /// Luminance range in EV the histogram covers, the rest is clamped.
+    pub min_log_luminance: f32,
+    pub max_log_luminance: f32,
+    /// How fast the exposure follows the scene luminance, higher is faster.
+    pub adaptation_speed: f32,
+}
+
+impl Default for AutoExposure {
+    fn default() -> Self {
+        Self {
+            min_log_luminance: -8.0,
+            max_log_luminance: 4.0,
+            adaptation_speed: 1.5,
+        }
+    }
+}
+
+/// Applied after tonemapping in a single pass, zero strength disables an effect.
+#[derive(Clone, Copy, Debug)]
+pub struct PostEffects {
+    pub vignette_strength: f32,
+    /// Distance from the center, relative to the half diagonal, where darkening starts.
+    pub vignette_radius: f32,
+    pub vignette_smoothness: f32,
+    /// Offset of the red and blue channels in pixels at the corners of the image.
+    pub chromatic_aberration_offset: f32,
+    pub grain_strength: f32,
+    /// Size of a grain cell in pixels.
+    pub grain_size: f32,
+}
+
+impl Default for PostEffects {
+    fn default() -> Self {
+        Self {
+            vignette_strength: 0.0,
+            vignette_radius: 0.5,
+            vignette_smoothness: 0.5,
+            chromatic_aberration_offset: 0.0,
+            grain_strength: 0.0,
+            grain_size: 1.0,
+        }
+    }
+}
+
+impl PostEffects {
+    pub fn is_enabled(&self) -> bool {
+        self.vignette_strength > 0.0
+            || self.chromatic_aberration_offset > 0.0
+            || self.grain_strength > 0.0
+    }
+}
+
+/// Reflections traced against the depth buffer before tonemapping, the environment is used on a miss.
+#[derive(Clone, Copy, Debug)]
+pub struct ScreenSpaceReflections {
+    /// Ray steps per pixel, quality and cost grow with it.
+    pub steps_count: u32,
+    /// Depth of a surface behind its front face, which the ray still hits.
+    pub thickness: f32,
+    pub max_distance: f32,
+    pub intensity: f32,
+}
+
+impl Default for ScreenSpaceReflections {
+    fn default() -> Self {
+        Self {
+            steps_count: 64,
+            thickness: 0.5,
+            max_distance: 50.0,
+            intensity: 1.0,
+        }
+    }
+}
+
+/// Sprite of the lens flare, it's placed on the line from the sun through the center of the viewport.
+#[derive(Clone, Copy, Debug)]
+pub struct LensFlareSprite {
+    /// Without a texture the sprite is a soft disk.
+    pub texture_reference: Option<TextureReference>,
+    /// Loaded by `LoadLensFlareTexturesEvent` into `texture_reference`, small textures share atlas pages.
+    pub texture_path: Option<PathBuf>,
+    /// `0.0` is at the sun, `1.0` at the center and `2.0` is mirrored to the other side.
+    pub position: f32,
+    /// Radius relative to the viewport height.
+    pub size: f32,
+    pub color: Vec3,
+}
+
+impl LensFlareSprite {
+    pub fn new(position: f32, size: f32, color: Vec3) -> Self {
+        Self {
+            texture_reference: Default::default(),
+            texture_path: Default::default(),
+            position,
+            size,
+            color,
+        }
+    }
+
+    pub fn with_texture(mut self, texture_reference: TextureReference) -> Self {
+        self.texture_reference = Some(texture_reference);
+
+        self
+    }
+
+    pub fn with_texture_path(mut self, texture_path: impl Into<PathBuf>) -> Self {
+        self.texture_path = Some(texture_path.into());
+
+        self
+    }
+}
+
+/// Sprites composited over the image after tonemapping, while the sun is visible to the first camera.
+#[derive(Clone, Debug)]
+pub struct LensFlare {
+    pub intensity: f32,
+    /// How fast the flare fades, once the sun is hidden or revealed, higher is faster.
+    pub fade_speed: f32,
+    /// Sprites above `LensFlare::MAX_SPRITES_COUNT` aren't drawn.
+    pub sprites: Vec<LensFlareSprite>,
+}
+
+impl Default for LensFlare {
+    fn default() -> Self {
+        Self {
+            intensity: 1.0,
+            fade_speed: 8.0,
+            sprites: vec![
+                LensFlareSprite::new(0.0, 0.15, Vec3::new(1.0, 0.95, 0.8)),
+                LensFlareSprite::new(0.6, 0.04, Vec3::new(0.4, 0.6, 1.0)),
+                LensFlareSprite::new(1.3, 0.07, Vec3::new(0.5, 1.0, 0.6)),
+                LensFlareSprite::new(1.8, 0.12, Vec3::new(1.0, 0.5, 0.3)),
+            ],
+        }
+    }
+}
+
+impl LensFlare {
+    pub const MAX_SPRITES_COUNT: usize = 8;
+}
+
+/// Radial blur of the sky towards the sun before tonemapping, anything in the depth buffer blocks it.
+#[derive(Clone, Copy, Debug)]
+pub struct LightShafts {
+    /// Samples along the ray to the sun per pixel, quality and cost grow with it.
+    pub samples_count: u32,
+    /// Part of the way to the sun the samples cover.
+    pub density: f32,
+    /// Falloff of every next sample.
+    pub decay: f32,
+    pub intensity: f32,
+}
+
+impl Default for LightShafts {
+    fn default() -> Self {
+        Self {
+            samples_count: 64,
+            density: 0.9,
+            decay: 0.96,
+            intensity: 0.5,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct PostProcessSettings {
+    /// Linear multiplier applied before tonemapping, with auto exposure it's the compensation.
+    pub exposure: f32,
+    pub auto_exposure: Option<AutoExposure>,
+    pub effects: PostEffects,
+    pub screen_space_reflections: Option<ScreenSpaceReflections>,
+    pub lens_flare: Option<LensFlare>,
+    pub light_shafts: Option<LightShafts>,
+}
+
+impl Default for PostProcessSettings {
+    fn default() -> Self {
+        Self {
+            exposure: 1.0,
+            auto_exposure: Default::default(),
+            effects: Default::default(),
+            screen_space_reflections: Default::default(),
+            lens_flare: Default::default(),
+            light_shafts: Default::default(),
+        }
+    }
+}
@@ -1,17 +1,45 @@
-use bevy_ecs::resource::Resource;
+use bevy_ecs::{entity::Entity, resource::Resource};
+use math::Vec2;
 use vulkanite::vk::{
     Extent2D,
     rs::{CommandBuffer, CommandPool, Fence, Image, ImageView, Semaphore},
 };
 
-use crate::engine::resources::textures_pool::TextureReference;
+use crate::engine::{
+    general::renderer::DepthPyramid,
+    resources::{buffers_pool::BufferReference, textures_pool::TextureReference},
+};
 
 pub struct FrameData {
     pub command_group: CommandGroup,
+    /// Signaled by the acquire, it's waited by the submission of the same frame.
     pub swapchain_semaphore: Semaphore,
-    pub render_semaphore: Semaphore,
+    /// Set by the acquire and cleared by the submission, a pending semaphore can't be signaled again.
+    pub is_swapchain_semaphore_pending: bool,
     pub draw_texture_reference: TextureReference,
     pub depth_texture_reference: TextureReference,
+    /// Light clusters of every active camera, written by the light culling pass.
+    pub light_clusters_buffer_reference: BufferReference,
+    /// Scattered foliage instances of every active camera, written by the foliage scattering pass.
+    pub foliage_instances_buffer_reference: BufferReference,
+    /// Indirect draw arguments of the scattered instances, one per active camera.
+    pub foliage_draw_arguments_buffer_reference: BufferReference,
+    /// Flag per visibility query, written by the visibility queries pass and read after the fence.
+    pub visibility_query_results_buffer_reference: BufferReference,
+    /// Entities of the submitted visibility queries, in the order of their results.
+    pub visibility_query_entities: Vec<Entity>,
+    /// Picks of the depth by their screen positions, written in place by the depth picks pass.
+    pub depth_picks_buffer_reference: BufferReference,
+    /// Screen positions of the submitted depth picks, in the order of their results.
+    pub depth_pick_positions: Vec<Vec2>,
+    /// Samples of the textures by their indices, written by the fragment shader of the meshes.
+    pub texture_usage_counters_buffer_reference: BufferReference,
+    /// Whether the submission of the frame counted samples of the textures.
+    pub is_texture_usage_counted: bool,
+    /// Present while `CullingSettings::is_depth_pyramid_enabled`, it's as large as the depth image.
+    pub depth_pyramid: Option<DepthPyramid>,
+    /// Present only with the compute queue, post-processing of the frame is recorded into it.
+    pub async_compute_command_group: Option<AsyncComputeCommandGroup>,
 }
 
 #[derive(Clone, Copy)]
@@ -21,24 +49,70 @@ pub struct CommandGroup {
     pub fence: Fence,
 }
 
+/// Frame is split into three submissions: the scene on the graphics queue, post-processing on the
+/// compute queue and the blit to the swapchain back on the graphics queue.
+#[derive(Clone, Copy)]
+pub struct AsyncComputeCommandGroup {
+    /// Pool of the compute queue family.
+    pub command_pool: CommandPool,
+    pub command_buffer: CommandBuffer,
+    /// Allocated from the graphics pool of the frame, it waits for the post-processing.
+    pub present_command_buffer: CommandBuffer,
+}
+
 #[derive(Clone, Copy)]
 pub struct UploadContext {
     pub command_group: CommandGroup,
 }
 
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SwapchainStatus {
+    #[default]
+    Valid,
+    /// Swapchain doesn't match the surface anymore, it's recreated before the next frame.
+    OutOfDate,
+    /// Surface was destroyed by the platform, it's recreated with `Engine::recreate_lost_surface`.
+    SurfaceLost,
+}
+
 #[derive(Resource)]
 pub struct RendererContext {
     pub images: Vec<Image>,
     pub image_views: Vec<ImageView>,
+    /// One per swapchain image, the presentation may still wait for it after the fence of the frame.
+    pub render_semaphores: Vec<Semaphore>,
+    /// Frames in flight, it doesn't depend on the swapchain images count.
     pub frame_overlap: usize,
     pub frames_data: Vec<FrameData>,
     pub upload_context: UploadContext,
     pub frame_number: usize,
     pub draw_extent: Extent2D,
+    /// Part of the draw image that is rendered this frame, it's the draw extent with the render scale.
+    pub render_extent: Extent2D,
+    /// Set by acquire and present, handled by the engine between frames.
+    pub swapchain_status: SwapchainStatus,
+    /// Orders submissions of the async compute, every frame signals two values of it.
+    pub async_compute_semaphore: Option<Semaphore>,
 }
 
 impl RendererContext {
     pub fn get_current_frame_data(&self) -> &FrameData {
         &self.frames_data[self.frame_number % self.frame_overlap]
     }
+
+    pub fn get_current_frame_data_mut(&mut self) -> &mut FrameData {
+        &mut self.frames_data[self.frame_number % self.frame_overlap]
+    }
+
+    /// Value signaled by the graphics queue, once the scene of the current frame is rendered.
+    #[inline(always)]
+    pub fn get_scene_timeline_value(&self) -> u64 {
+        self.frame_number as u64 * 2 + 1
+    }
+
+    /// Value signaled by the compute queue, once the current frame is post-processed.
+    #[inline(always)]
+    pub fn get_post_process_timeline_value(&self) -> u64 {
+        self.frame_number as u64 * 2 + 2
+    }
 }
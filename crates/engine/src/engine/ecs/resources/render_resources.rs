@@ -1,22 +1,32 @@
 pub mod audio;
+pub mod buffer_state_tracker;
 pub mod buffers_pool;
+pub mod image_state_tracker;
+pub mod instance_slot_allocator;
 pub mod materials_pool;
 pub mod mesh_buffers_pool;
+pub mod mesh_registry;
 pub mod model_loader;
 pub mod physics;
 pub mod samplers_pool;
+pub mod texture_atlas;
 pub mod textures_pool;
+pub mod transient_resources_pool;
 
 use bevy_ecs::resource::Resource;
-use bytemuck::{NoUninit, Pod, Zeroable};
-use math::{Vec3, Vec4};
+use bytemuck::{Pod, Zeroable};
+use math::{Aabb, Vec2, Vec3, Vec4};
 use padding_struct::padding_struct;
 use slotmap::new_key_type;
 use vulkanite::vk::{rs::*, *};
 
+use crate::engine::general::renderer::GpuBackend;
 use crate::engine::resources::{
-    buffers_pool::BufferReference, render_resources::model_loader::ModelLoader,
-    samplers_pool::SamplerReference, textures_pool::TextureReference,
+    LensFlare,
+    buffers_pool::{BufferReference, BuffersPool},
+    render_resources::instance_slot_allocator::InstanceSlotAllocator,
+    samplers_pool::SamplerReference,
+    textures_pool::TextureReference,
 };
 
 #[repr(C)]
@@ -27,6 +37,12 @@ pub struct Meshlet {
     pub triangle_offset: u32,
     pub vertex_count: u32,
     pub triangle_count: u32,
+    /// Bounding sphere and the cone of triangle normals in the object space of the mesh.
+    pub center: [f32; 3],
+    pub radius: f32,
+    pub cone_axis: [f32; 3],
+    /// Cosine of the cone half angle, meshlets with `1.0` face every direction and are never culled.
+    pub cone_cutoff: f32,
 }
 
 #[repr(C)]
@@ -39,14 +55,97 @@ pub struct Vertex {
     pub color: [f32; 3],
 }
 
+#[repr(u32)]
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VertexFormat {
+    #[default]
+    Full,
+    /// `CompressedVertex`, decoded in the mesh shader.
+    Compressed,
+}
+
+/// Quantized `Vertex`, positions are snorm16 in the bounds of the mesh (see `MeshObject::position_offset`),
+/// normals are octahedral unorm16, uvs are halves and colors are unorm8.
+#[repr(C)]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct CompressedVertex {
+    /// `x | y << 16` and `z`.
+    pub position: [u32; 2],
+    pub normal: u32,
+    pub uv: u32,
+    pub color: u32,
+}
+
+impl CompressedVertex {
+    pub fn new(vertex: &Vertex, position_offset: Vec3, position_scale: Vec3) -> Self {
+        let position = (Vec3::from_array(vertex.position) - position_offset) / position_scale;
+        let [x, y, z] = position
+            .to_array()
+            .map(|value| meshopt::quantize_snorm(value, 16) as u16 as u32);
+        let normal = Self::oct_encode(Vec3::from_array(vertex.normal));
+        let [normal_x, normal_y] = normal
+            .to_array()
+            .map(|value| meshopt::quantize_unorm(value, 16) as u32);
+        let [u, v] = vertex.uv.map(|value| meshopt::quantize_half(value) as u32);
+        let [r, g, b] = vertex
+            .color
+            .map(|value| meshopt::quantize_unorm(value, 8) as u32);
+
+        Self {
+            position: [x | y << 16, z],
+            normal: normal_x | normal_y << 16,
+            uv: u | v << 16,
+            color: r | g << 8 | b << 16,
+        }
+    }
+
+    /// Offset and scale that map the bounds of the vertices into `-1.0..=1.0`.
+    pub fn get_quantization(vertices: &[Vertex]) -> (Vec3, Vec3) {
+        let Some(bounds) = Aabb::from_points(
+            vertices
+                .iter()
+                .map(|vertex| Vec3::from_array(vertex.position)),
+        ) else {
+            return (Vec3::ZERO, Vec3::ONE);
+        };
+
+        // NOTE: Flat meshes have no extent on one of the axes.
+        (
+            bounds.get_center(),
+            bounds.get_half_extents().max(Vec3::splat(f32::EPSILON)),
+        )
+    }
+
+    /// Same mapping as `oct_encode` of the mesh shader, into `0.0..=1.0`.
+    fn oct_encode(direction: Vec3) -> Vec2 {
+        let direction = direction.try_normalize().unwrap_or(Vec3::Y);
+        let n = direction / (direction.x.abs() + direction.y.abs() + direction.z.abs());
+        let mut encoded = Vec2::new(n.x, n.z);
+        if n.y < 0.0 {
+            let signs = Vec2::select(encoded.cmpge(Vec2::ZERO), Vec2::ONE, Vec2::NEG_ONE);
+            encoded = (Vec2::ONE - Vec2::new(encoded.y, encoded.x).abs()) * signs;
+        }
+
+        encoded * 0.5 + 0.5
+    }
+}
+
 #[repr(C)]
 #[padding_struct]
 #[derive(Default, Clone, Copy, Pod, Zeroable)]
 pub struct MeshObject {
-    pub device_address_vertex_buffer: DeviceAddress,
-    pub device_address_vertex_indices_buffer: DeviceAddress,
-    pub device_address_meshlets_buffer: DeviceAddress,
-    pub device_address_local_indices_buffer: DeviceAddress,
+    /// Indices of the buffers in the buffer addresses table, so moved buffers don't invalidate it.
+    pub vertex_buffer_index: u32,
+    pub vertex_indices_buffer_index: u32,
+    pub meshlets_buffer_index: u32,
+    pub local_indices_buffer_index: u32,
+    /// Bounding sphere in the object space, instances outside of the camera frustum are skipped by it.
+    pub center: [f32; 3],
+    pub radius: f32,
+    /// Compressed positions are decoded as `position * position_scale + position_offset`.
+    pub position_offset: [f32; 3],
+    pub vertex_format: u32,
+    pub position_scale: [f32; 3],
 }
 
 #[repr(C)]
@@ -56,18 +155,103 @@ pub struct InstanceObject {
     pub model_matrix: [f32; 16],
     pub device_address_mesh_object: DeviceAddress,
     pub device_address_material_data: DeviceAddress,
+    /// Null for meshes without a baked impostor.
+    pub device_address_impostor: DeviceAddress,
     pub meshlet_count: u32,
     pub material_type: u8,
+    /// Value written into the outline mask, see `OutlineType`.
+    pub outline_type: u8,
+}
+
+/// Instances farther than `distance` are drawn as a billboard of the closest baked frame,
+/// the center and radius bound the mesh in its object space.
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct ImpostorData {
+    pub device_address_material_data: DeviceAddress,
+    pub center: [f32; 3],
+    pub radius: f32,
+    pub frames_per_side: u32,
+    pub distance: f32,
 }
 
 #[repr(C)]
 #[padding_struct]
 #[derive(Clone, Copy, Default, Pod, Zeroable)]
 pub struct GraphicsPushConstant {
-    pub device_address_scene_data: DeviceAddress,
+    pub device_address_push_constant_overflow: DeviceAddress,
     pub device_address_instance_object: DeviceAddress,
-    pub draw_image_index: u32,
     pub current_material_type: u32,
+    pub draw_image_index: u32,
+    pub device_address_scene_data: DeviceAddress,
+}
+
+// NOTE: Vulkan guarantees at least 128 bytes of push constants, stay within it.
+const _: () = assert!(
+    std::mem::size_of::<GraphicsPushConstant>() <= GraphicsPushConstant::GUARANTEED_MAX_SIZE
+);
+
+impl GraphicsPushConstant {
+    /// Minimum of `maxPushConstantsSize` that every device supports.
+    pub const GUARANTEED_MAX_SIZE: usize = 128;
+    /// Fields pushed per draw, they must always stay in push constants.
+    pub const RESIDENT_SIZE: usize =
+        std::mem::offset_of!(Self, current_material_type) + std::mem::size_of::<u32>();
+
+    pub fn get_overflow(&self) -> PushConstantOverflow {
+        PushConstantOverflow {
+            device_address_scene_data: self.device_address_scene_data,
+            draw_image_index: self.draw_image_index,
+            ..Default::default()
+        }
+    }
+}
+
+/// Tail of [`GraphicsPushConstant`] that is read from a uniform buffer,
+/// when the device limit can't fit the whole push constant block.
+#[repr(C)]
+#[padding_struct]
+#[derive(Clone, Copy, Default, Pod, Zeroable)]
+pub struct PushConstantOverflow {
+    pub device_address_scene_data: DeviceAddress,
+    pub draw_image_index: u32,
+}
+
+#[derive(Clone, Copy)]
+pub struct PushConstantLayout {
+    pushed_size: u32,
+}
+
+impl PushConstantLayout {
+    pub fn new(max_push_constants_size: u32) -> Self {
+        let resident_size = GraphicsPushConstant::RESIDENT_SIZE as u32;
+        if max_push_constants_size < resident_size {
+            panic!(
+                "Device supports only {} bytes of push constants, but {} bytes are required!",
+                max_push_constants_size, resident_size
+            );
+        }
+
+        let push_constant_size = std::mem::size_of::<GraphicsPushConstant>() as u32;
+        let pushed_size = if push_constant_size > max_push_constants_size {
+            resident_size
+        } else {
+            push_constant_size
+        };
+
+        Self { pushed_size }
+    }
+
+    #[inline(always)]
+    pub fn get_pushed_size(&self) -> u32 {
+        self.pushed_size
+    }
+
+    #[inline(always)]
+    pub fn is_overflowing(&self) -> bool {
+        self.pushed_size < std::mem::size_of::<GraphicsPushConstant>() as u32
+    }
 }
 
 #[derive(Default, Clone, Copy)]
@@ -107,22 +291,423 @@ pub struct SceneData {
     pub camera_position: Vec3,
     pub light_properties: LightProperties,
     pub directional_light: DirectionalLight,
+    pub environment_data: EnvironmentData,
+    pub post_process_data: PostProcessData,
+    pub fog_data: FogData,
+    pub depth_of_field_data: DepthOfFieldData,
+    pub screen_space_reflections_data: ScreenSpaceReflectionsData,
+    pub light_clusters_data: LightClustersData,
+    pub water_data: WaterData,
+    pub foliage_instances_data: FoliageInstancesData,
+    pub visibility_queries_data: VisibilityQueriesData,
+    pub depth_picks_data: DepthPicksData,
+    pub outlines_data: OutlinesData,
+    pub light_shafts_data: LightShaftsData,
+    pub lens_flare_data: LensFlareData,
+    pub debug_overlay_data: DebugOverlayData,
+    pub texture_usage_data: TextureUsageData,
+    /// Device addresses of buffers by their indices, see `BuffersPool`.
+    pub device_address_buffer_addresses: DeviceAddress,
+    pub is_meshlet_cone_culling_enabled: u32,
+}
+
+/// Parameters of the exposure, tonemapping and post effects passes.
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct PostProcessData {
+    pub device_address_exposure_state: DeviceAddress,
+    pub exposure: f32,
+    pub is_auto_exposure_enabled: u32,
+    pub min_log_luminance: f32,
+    pub log_luminance_range: f32,
+    pub adaptation_factor: f32,
+    pub post_image_index: u32,
+    pub vignette_strength: f32,
+    pub vignette_radius: f32,
+    pub vignette_smoothness: f32,
+    pub chromatic_aberration_offset: f32,
+    pub grain_strength: f32,
+    pub grain_size: f32,
+    /// Animates the grain, it's the frame index.
+    pub grain_seed: u32,
+    pub is_post_effects_enabled: u32,
+}
+
+/// GPU side state of auto exposure, the histogram is cleared by the adaptation pass.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct ExposureState {
+    pub histogram: [u32; ExposureState::HISTOGRAM_BINS_COUNT],
+    /// Zero until the first adaptation, which takes the luminance of the histogram as is.
+    pub average_luminance: f32,
+}
+
+impl ExposureState {
+    pub const HISTOGRAM_BINS_COUNT: usize = 256;
+}
+
+impl Default for ExposureState {
+    fn default() -> Self {
+        Self {
+            histogram: [Default::default(); Self::HISTOGRAM_BINS_COUNT],
+            average_luminance: Default::default(),
+        }
+    }
+}
+
+#[repr(u32)]
+#[derive(Default, Clone, Copy)]
+pub enum EnvironmentKind {
+    #[default]
+    Gradient,
+    Skybox,
+}
+
+/// Parameters of the background compute pass.
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct EnvironmentData {
+    pub top_color: Vec4,
+    pub bottom_color: Vec4,
+    pub inverse_camera_view_matrix: [f32; 16],
+    pub environment_kind: u32,
+    pub skybox_texture_index: u32,
+    pub skybox_sampler_index: u32,
+    pub render_width: u32,
+    pub render_height: u32,
+    pub viewport_x: u32,
+    pub viewport_y: u32,
+    pub viewport_width: u32,
+    pub viewport_height: u32,
+    pub depth_image_index: u32,
+    /// Min depth pyramid of the frame, zero mips means it isn't built.
+    pub depth_pyramid_image_index: u32,
+    pub depth_pyramid_mip_levels_count: u32,
+}
+
+#[repr(u32)]
+#[derive(Default, Clone, Copy)]
+pub enum FogKind {
+    #[default]
+    None,
+    Linear,
+    Exponential,
+    ExponentialSquared,
+}
+
+/// Parameters of the distance fog and atmospheric scattering, applied in the fragment stage.
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct FogData {
+    pub color: Vec4,
+    pub rayleigh_coefficients: Vec3,
+    pub mie_coefficient: f32,
+    pub planet_center: Vec3,
+    pub planet_radius: f32,
+    pub fog_kind: u32,
+    pub start: f32,
+    pub end: f32,
+    pub density: f32,
+    pub atmosphere_radius: f32,
+    pub sun_intensity: f32,
+    pub is_scattering_enabled: u32,
+}
+
+/// Depth of field of the camera, zero max blur radius disables it.
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct DepthOfFieldData {
+    pub focal_distance: f32,
+    /// Blur radius in pixels of a point at infinity.
+    pub infinity_blur_radius: f32,
+    pub max_blur_radius: f32,
+}
+
+/// Screen space reflections of the camera, zero steps count disables them.
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct ScreenSpaceReflectionsData {
+    pub steps_count: u32,
+    pub thickness: f32,
+    pub max_distance: f32,
+    pub intensity: f32,
+}
+
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct PointLightData {
+    pub position: Vec3,
+    pub radius: f32,
+    pub color: Vec3,
+    pub intensity: f32,
+}
+
+/// Froxels of the camera, every one keeps a count and indices of point lights touching it.
+pub const LIGHT_CLUSTERS_GRID: [u32; 3] = [16, 9, 24];
+pub const MAX_LIGHTS_PER_CLUSTER: usize = 63;
+pub const LIGHT_CLUSTER_SIZE: usize = std::mem::size_of::<u32>() * (MAX_LIGHTS_PER_CLUSTER + 1);
+pub const LIGHT_CLUSTERS_COUNT: usize =
+    (LIGHT_CLUSTERS_GRID[0] * LIGHT_CLUSTERS_GRID[1] * LIGHT_CLUSTERS_GRID[2]) as _;
+pub const MAX_POINT_LIGHTS_COUNT: usize = 1024;
+
+/// Clusters are sliced exponentially by the view distance between the clipping planes.
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct LightClustersData {
+    pub device_address_point_lights: DeviceAddress,
+    pub device_address_light_clusters: DeviceAddress,
+    pub camera_forward: Vec3,
+    pub point_lights_count: u32,
+    pub near: f32,
+    pub far: f32,
+}
+
+/// Water surface of the frame, it's a rectangle in the XZ plane. Zero `is_enabled` skips the pass.
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct WaterData {
+    pub color: Vec3,
+    pub height: f32,
+    pub center: Vec2,
+    pub half_size: Vec2,
+    pub scroll_velocity_0: Vec2,
+    pub scroll_velocity_1: Vec2,
+    /// Zero if there is no normal map, procedural waves are used then.
+    pub normal_map_index: u32,
+    pub normal_sampler_index: u32,
+    pub normal_scale: f32,
+    pub normal_strength: f32,
+    pub visibility_distance: f32,
+    pub refraction_distortion: f32,
+    pub reflection_intensity: f32,
+    pub time: f32,
+    pub has_normal_map: u32,
+    pub is_enabled: u32,
+}
+
+pub const MAX_FOLIAGES_COUNT: usize = 16;
+/// Scattered instances of a camera, the rest of the cells are dropped when it's exceeded.
+pub const MAX_FOLIAGE_INSTANCES_COUNT: usize = 65536;
+
+/// Scattered layer of a mesh, every cell of its grid spawns at most one instance.
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct FoliageData {
+    pub model_matrix: [f32; 16],
+    pub device_address_mesh_object: DeviceAddress,
+    pub device_address_material_data: DeviceAddress,
+    pub half_size: Vec2,
+    pub cell_size: f32,
+    pub meshlet_count: u32,
+    pub material_type: u32,
+    pub cells_count_x: u32,
+    pub cells_count_z: u32,
+    /// Zero if there is no density map, every cell is filled then.
+    pub density_map_index: u32,
+    /// Zero if there is no height map, instances are placed at the height of the entity then.
+    pub height_map_index: u32,
+    pub sampler_index: u32,
+    pub height_scale: f32,
+    pub min_scale: f32,
+    pub max_scale: f32,
+    pub bounding_radius: f32,
+    pub fade_start: f32,
+    pub fade_end: f32,
+    pub has_density_map: u32,
+    pub has_height_map: u32,
+}
+
+/// Foliages of the frame and the instances of the camera appended by the scattering pass.
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct FoliageInstancesData {
+    pub device_address_foliages: DeviceAddress,
+    pub device_address_instances: DeviceAddress,
+    /// Indirect draw arguments of the camera, the group count is the instances count.
+    pub device_address_draw_arguments: DeviceAddress,
+    pub foliages_count: u32,
+    pub max_instances_count: u32,
+}
+
+/// Points are tested against the depth of the first camera only.
+pub const MAX_VISIBILITY_QUERIES_COUNT: usize = 4096;
+
+/// Points of visibility queries and a flag per each of them, written by the visibility queries pass.
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct VisibilityQueriesData {
+    pub device_address_points: DeviceAddress,
+    pub device_address_results: DeviceAddress,
+    pub points_count: u32,
+}
+
+/// Screen position is written by the host, the rest by the depth picks pass.
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct DepthPick {
+    pub screen_position: Vec2,
+    pub depth: f32,
+    pub is_hit: u32,
+    pub world_position: Vec3,
+}
+
+/// Picks are read from the depth of the first camera only.
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct DepthPicksData {
+    pub device_address_picks: DeviceAddress,
+    pub picks_count: u32,
+}
+
+/// Mask keeps the nearest outlined fragment of every pixel, the depth is in the upper 24 bits and
+/// the outline type in the lower 8 bits. Null mask address skips writing it.
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct OutlinesData {
+    pub device_address_mask: DeviceAddress,
+    /// Width of the draw image, the mask covers it whole.
+    pub mask_stride: u32,
+    pub selected_thickness: u32,
+    pub selected_color: Vec3,
+    pub highlighted_thickness: u32,
+    pub highlighted_color: Vec3,
 }
 
-pub struct SwappableBuffer<T: NoUninit + Pod + Sized> {
+/// Mip of a depth pyramid built by a dispatch, the source is the depth image for the first one
+/// and the previous mip for the rest.
+#[repr(C)]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct DepthPyramidLevel {
+    pub source_image_index: u32,
+    pub target_image_index: u32,
+    pub source_offset_x: u32,
+    pub source_offset_y: u32,
+    pub source_width: u32,
+    pub source_height: u32,
+    pub target_width: u32,
+    pub target_height: u32,
+    pub is_source_depth: u32,
+    pub reduction: u32,
+}
+
+/// Null counters address disables the counting.
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct TextureUsageData {
+    pub device_address_counters: DeviceAddress,
+    pub counters_count: u32,
+}
+
+/// Sun position is in the viewport, zero `intensity` skips the camera.
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct LightShaftsData {
+    pub sun_uv: Vec2,
+    pub samples_count: u32,
+    pub density: f32,
+    pub decay: f32,
+    pub intensity: f32,
+}
+
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct LensFlareSpriteData {
+    pub color: Vec3,
+    pub position: f32,
+    pub size: f32,
+    /// Zero if there is no texture, the sprite is a soft disk then.
+    pub texture_index: u32,
+    pub has_texture: u32,
+    /// Offset and size in UV space of the texture, it's a rect of the page for atlas textures.
+    pub uv_rect: Vec4,
+}
+
+/// Flare of the first camera, the post effects pass covers the whole image at once.
+/// Sun position is in pixels of the render extent, zero `intensity` hides the flare.
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct LensFlareData {
+    pub sprites: [LensFlareSpriteData; LensFlare::MAX_SPRITES_COUNT],
+    pub sun_position: Vec2,
+    pub center_position: Vec2,
+    pub viewport_height: f32,
+    pub intensity: f32,
+    pub sprites_count: u32,
+    pub sampler_index: u32,
+}
+
+pub const MAX_DEBUG_OVERLAY_RECTS_COUNT: usize = 4096;
+
+/// Position and size are in pixels of the render extent, the origin is the top left corner.
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct DebugOverlayRect {
+    pub position: Vec2,
+    pub size: Vec2,
+    /// Alpha blends the rect over the image.
+    pub color: Vec4,
+}
+
+/// Rects of `DebugOverlay`, the pass is dispatched only over their bounds.
+#[repr(C)]
+#[padding_struct]
+#[derive(Default, Clone, Copy, Pod, Zeroable)]
+pub struct DebugOverlayData {
+    pub device_address_rects: DeviceAddress,
+    pub rects_count: u32,
+    /// Image the frame is presented from, the overlay is drawn last.
+    pub target_image_index: u32,
+    pub bounds_offset: [u32; 2],
+}
+
+/// Per frame in flight GPU buffers of `T`, only the current one is written to.
+pub struct SwappableBuffer<T: Pod> {
     current_buffer_index: usize,
     buffers: Vec<BufferReference>,
+    capacity: usize,
     objects: Vec<T>,
-    objects_to_write: Vec<u8>,
 }
 
-impl<'a, T: bytemuck::Pod> SwappableBuffer<T> {
-    pub fn new(buffers: Vec<BufferReference>) -> Self {
+impl<T: Pod> SwappableBuffer<T> {
+    pub fn new(gpu_backend: &impl GpuBackend, buffers: Vec<BufferReference>) -> Self {
+        let capacity = buffers
+            .iter()
+            .map(|&buffer_reference| {
+                gpu_backend.get_buffer_info(buffer_reference).size as usize
+                    / std::mem::size_of::<T>()
+            })
+            .min()
+            .unwrap_or_default();
+        assert!(
+            capacity > Default::default(),
+            "Swappable buffer can't fit a single object of {}",
+            std::any::type_name::<T>()
+        );
+
         Self {
             current_buffer_index: Default::default(),
             buffers,
-            objects: Default::default(),
-            objects_to_write: Default::default(),
+            capacity,
+            objects: Vec::with_capacity(capacity),
         }
     }
 
@@ -141,40 +726,290 @@ impl<'a, T: bytemuck::Pod> SwappableBuffer<T> {
     }
 
     #[inline(always)]
-    pub fn get_objects_to_write_as_slice(&'a self) -> &'a [u8] {
-        &self.objects_to_write
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
     }
 
     #[inline(always)]
-    pub fn prepare_objects_for_writing(&mut self) {
-        let object_to_write = bytemuck::cast_slice(&self.objects);
-        self.objects_to_write.extend_from_slice(object_to_write);
+    pub fn len(&self) -> usize {
+        self.objects.len()
     }
 
     #[inline(always)]
-    pub fn add_instance_object(&mut self, object_to_write: T) {
-        self.objects.push(object_to_write);
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn get_objects(&self) -> &[T] {
+        &self.objects
+    }
+
+    /// Returns index of the object in the buffer.
+    #[inline(always)]
+    pub fn push(&mut self, object: T) -> usize {
+        assert!(
+            self.objects.len() < self.capacity,
+            "Swappable buffer of {} overflowed its capacity of {} objects",
+            std::any::type_name::<T>(),
+            self.capacity
+        );
+
+        let index = self.objects.len();
+        self.objects.push(object);
+
+        index
+    }
+
+    /// Uploads only the written objects into the current buffer.
+    pub fn flush(&self, gpu_backend: &mut impl GpuBackend) {
+        if self.objects.is_empty() {
+            return;
+        }
+
+        gpu_backend.write_buffer(
+            self.get_current_buffer(),
+            Default::default(),
+            bytemuck::cast_slice(&self.objects),
+        );
     }
 
     #[inline(always)]
     pub fn clear(&mut self) {
         self.objects.clear();
-        self.objects_to_write.clear();
+    }
+}
+
+/// Per frame in flight GPU buffers of `T` that keep their objects between frames,
+/// after a rebuild every buffer gets uploaded once it becomes the current one.
+/// Objects written in place are uploaded alone, so each buffer tracks its own stale indices.
+pub struct PersistentBuffer<T: Pod> {
+    current_buffer_index: usize,
+    buffers: Vec<BufferReference>,
+    capacity: usize,
+    objects: Vec<T>,
+    stale_buffers: Vec<bool>,
+    stale_indices: Vec<Vec<usize>>,
+}
+
+impl<T: Pod> PersistentBuffer<T> {
+    pub fn new(gpu_backend: &impl GpuBackend, buffers: Vec<BufferReference>) -> Self {
+        let capacity = buffers
+            .iter()
+            .map(|&buffer_reference| {
+                gpu_backend.get_buffer_info(buffer_reference).size as usize
+                    / std::mem::size_of::<T>()
+            })
+            .min()
+            .unwrap_or_default();
+        assert!(
+            capacity > Default::default(),
+            "Persistent buffer can't fit a single object of {}",
+            std::any::type_name::<T>()
+        );
+        let buffers_count = buffers.len();
+
+        Self {
+            current_buffer_index: Default::default(),
+            buffers,
+            capacity,
+            objects: Default::default(),
+            stale_buffers: vec![Default::default(); buffers_count],
+            stale_indices: vec![Default::default(); buffers_count],
+        }
+    }
+
+    #[inline(always)]
+    pub fn next_buffer(&mut self) {
+        self.current_buffer_index += 1;
+        if self.current_buffer_index >= self.buffers.len() {
+            self.current_buffer_index = Default::default();
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_current_buffer(&self) -> BufferReference {
+        unsafe { *self.buffers.get_unchecked(self.current_buffer_index) }
+    }
+
+    #[inline(always)]
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn get_objects(&self) -> &[T] {
+        &self.objects
+    }
+
+    pub fn rebuild(&mut self, objects: impl IntoIterator<Item = T>) {
+        self.objects.clear();
+        self.objects.extend(objects);
+        assert!(
+            self.objects.len() <= self.capacity,
+            "Persistent buffer of {} overflowed its capacity of {} objects",
+            std::any::type_name::<T>(),
+            self.capacity
+        );
+
+        self.stale_buffers.fill(true);
+        self.stale_indices.iter_mut().for_each(Vec::clear);
+    }
+
+    /// Returns index of the object in the buffer.
+    pub fn push(&mut self, object: T) -> usize {
+        assert!(
+            self.objects.len() < self.capacity,
+            "Persistent buffer of {} overflowed its capacity of {} objects",
+            std::any::type_name::<T>(),
+            self.capacity
+        );
+
+        let index = self.objects.len();
+        self.objects.push(object);
+        self.mark_stale(index);
+
+        index
+    }
+
+    #[inline(always)]
+    pub fn set(&mut self, index: usize, object: T) {
+        self.objects[index] = object;
+        self.mark_stale(index);
+    }
+
+    /// Drops the objects past `len`, e.g. trailing holes of freed slots.
+    #[inline(always)]
+    pub fn truncate(&mut self, len: usize) {
+        self.objects.truncate(len);
+    }
+
+    /// Moves the last object into the hole, returns its previous index if it was moved.
+    pub fn swap_remove(&mut self, index: usize) -> Option<usize> {
+        let last_index = self.objects.len() - 1;
+        self.objects.swap_remove(index);
+        if index == last_index {
+            return None;
+        }
+        self.mark_stale(index);
+
+        Some(last_index)
+    }
+
+    /// Uploads the stale objects into the current buffer, the whole buffer if it's stale or most of it.
+    pub fn flush(&mut self, gpu_backend: &mut impl GpuBackend) {
+        let current_buffer = self.get_current_buffer();
+        let is_stale = std::mem::take(&mut self.stale_buffers[self.current_buffer_index]);
+        let stale_indices = &mut self.stale_indices[self.current_buffer_index];
+        // NOTE: Removed objects leave indices past the end, the count of the draw skips them anyway.
+        stale_indices.retain(|&index| index < self.objects.len());
+
+        if is_stale || stale_indices.len() > self.objects.len() / 4 {
+            stale_indices.clear();
+            gpu_backend.write_buffer(
+                current_buffer,
+                Default::default(),
+                bytemuck::cast_slice(&self.objects),
+            );
+
+            return;
+        }
+
+        stale_indices.sort_unstable();
+        stale_indices.dedup();
+        // NOTE: Neighbouring objects are uploaded by a single write.
+        for range in stale_indices.chunk_by(|&previous, &next| previous + 1 == next) {
+            let (first_index, last_index) = (range[0], range[range.len() - 1]);
+            gpu_backend.write_buffer(
+                current_buffer,
+                first_index * std::mem::size_of::<T>(),
+                bytemuck::cast_slice(&self.objects[first_index..=last_index]),
+            );
+        }
+        stale_indices.clear();
+    }
+
+    #[inline(always)]
+    fn mark_stale(&mut self, index: usize) {
+        self.stale_indices
+            .iter_mut()
+            .zip(&self.stale_buffers)
+            .filter(|(_, is_stale)| !**is_stale)
+            .for_each(|(stale_indices, _)| stale_indices.push(index));
     }
 }
 
 pub struct ResourcesPool {
-    pub instances_buffer: Option<SwappableBuffer<InstanceObject>>,
+    /// Indices are stable, see `instance_slot_allocator`.
+    pub instances_buffer: Option<PersistentBuffer<InstanceObject>>,
+    pub instance_slot_allocator: InstanceSlotAllocator,
+    pub static_instances_buffer: Option<PersistentBuffer<InstanceObject>>,
     pub scene_data_buffer: Option<SwappableBuffer<SceneData>>,
+    pub push_constant_overflow_buffer: Option<SwappableBuffer<PushConstantOverflow>>,
+    pub point_lights_buffer: Option<SwappableBuffer<PointLightData>>,
+    pub foliages_buffer: Option<SwappableBuffer<FoliageData>>,
+    /// World space points of visibility queries, `w` is always one.
+    pub visibility_query_points_buffer: Option<SwappableBuffer<Vec4>>,
+    #[cfg(feature = "debug-tools")]
+    pub debug_overlay_rects_buffer: Option<SwappableBuffer<DebugOverlayRect>>,
 }
 
 impl ResourcesPool {
     pub fn new() -> Self {
         Self {
             instances_buffer: Default::default(),
+            instance_slot_allocator: Default::default(),
+            static_instances_buffer: Default::default(),
             scene_data_buffer: Default::default(),
+            push_constant_overflow_buffer: Default::default(),
+            point_lights_buffer: Default::default(),
+            foliages_buffer: Default::default(),
+            visibility_query_points_buffer: Default::default(),
+            #[cfg(feature = "debug-tools")]
+            debug_overlay_rects_buffer: Default::default(),
         }
     }
+
+    /// Offset in [`GraphicsPushConstant`] and the address to push, so shaders read scene data of the camera.
+    pub fn get_camera_push_constant(
+        &self,
+        buffers_pool: &BuffersPool,
+        camera_index: usize,
+    ) -> (u32, DeviceAddress) {
+        if let Some(push_constant_overflow_buffer) = self.push_constant_overflow_buffer.as_ref() {
+            let device_address = buffers_pool
+                .get_buffer_info(push_constant_overflow_buffer.get_current_buffer())
+                .device_address
+                + (camera_index * std::mem::size_of::<PushConstantOverflow>()) as DeviceAddress;
+
+            return (
+                std::mem::offset_of!(GraphicsPushConstant, device_address_push_constant_overflow)
+                    as _,
+                device_address,
+            );
+        }
+
+        let scene_data_buffer = unsafe { self.scene_data_buffer.as_ref().unwrap_unchecked() };
+        let device_address = buffers_pool
+            .get_buffer_info(scene_data_buffer.get_current_buffer())
+            .device_address
+            + (camera_index * std::mem::size_of::<SceneData>()) as DeviceAddress;
+
+        (
+            std::mem::offset_of!(GraphicsPushConstant, device_address_scene_data) as _,
+            device_address,
+        )
+    }
 }
 
 #[derive(Resource)]
@@ -185,11 +1020,165 @@ pub struct RendererResources {
     // TODO: Move to mesh buffers pool
     pub mesh_objects_buffer_reference: BufferReference,
     pub materials_data_buffer_reference: BufferReference,
+    pub exposure_state_buffer_reference: BufferReference,
     pub gradient_compute_shader_object: ShaderObject,
+    pub luminance_histogram_compute_shader_object: ShaderObject,
+    pub exposure_adaptation_compute_shader_object: ShaderObject,
+    pub tonemap_compute_shader_object: ShaderObject,
+    pub post_effects_compute_shader_object: ShaderObject,
+    pub depth_of_field_compute_shader_object: ShaderObject,
+    pub screen_space_reflections_compute_shader_object: ShaderObject,
+    pub light_culling_compute_shader_object: ShaderObject,
+    pub water_compute_shader_object: ShaderObject,
+    pub foliage_scatter_compute_shader_object: ShaderObject,
+    pub visibility_queries_compute_shader_object: ShaderObject,
+    pub light_shafts_compute_shader_object: ShaderObject,
+    #[cfg(feature = "debug-tools")]
+    pub debug_overlay_compute_shader_object: ShaderObject,
+    pub depth_pyramid_compute_shader_object: ShaderObject,
+    pub depth_picks_compute_shader_object: ShaderObject,
+    pub outlines_compute_shader_object: ShaderObject,
     pub task_shader_object: ShaderObject,
     pub mesh_shader_object: ShaderObject,
     pub fragment_shader_object: ShaderObject,
-    pub model_loader: ModelLoader,
+    /// Every shader object created by `prepare_shaders_system`, they're destroyed from it.
+    pub shader_objects: Vec<ShaderObject>,
     pub resources_pool: ResourcesPool,
-    pub is_printed_scene_hierarchy: bool,
+    pub push_constant_layout: PushConstantLayout,
+}
+
+#[cfg(test)]
+mod tests {
+    use vulkanite::vk::BufferUsageFlags;
+
+    use super::{PersistentBuffer, SwappableBuffer};
+    use crate::engine::{
+        general::renderer::{GpuBackend, MockGpuBackend},
+        resources::buffers_pool::{BufferReference, BufferVisibility},
+    };
+
+    fn create_buffers(
+        gpu_backend: &mut MockGpuBackend,
+        buffers_count: usize,
+        capacity: usize,
+    ) -> Vec<BufferReference> {
+        (0..buffers_count)
+            .map(|_| {
+                gpu_backend.create_buffer(
+                    std::mem::size_of::<u32>() * capacity,
+                    BufferUsageFlags::StorageBuffer,
+                    BufferVisibility::HostVisible,
+                    None,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn swappable_buffer_flushes_into_current_buffer() {
+        let mut gpu_backend = MockGpuBackend::new();
+        let buffers = create_buffers(&mut gpu_backend, 2, 4);
+        let mut swappable_buffer = SwappableBuffer::<u32>::new(&gpu_backend, buffers.clone());
+        assert_eq!(swappable_buffer.get_capacity(), 4);
+
+        swappable_buffer.push(1);
+        swappable_buffer.push(2);
+        swappable_buffer.flush(&mut gpu_backend);
+        swappable_buffer.next_buffer();
+        swappable_buffer.push(3);
+        swappable_buffer.flush(&mut gpu_backend);
+
+        assert_eq!(
+            gpu_backend.read_objects::<u32>(buffers[0], 4),
+            vec![1, 2, 0, 0]
+        );
+        assert_eq!(
+            gpu_backend.read_objects::<u32>(buffers[1], 4),
+            vec![3, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn swappable_buffer_panics_on_overflow() {
+        let mut gpu_backend = MockGpuBackend::new();
+        let buffers = create_buffers(&mut gpu_backend, 1, 1);
+        let mut swappable_buffer = SwappableBuffer::<u32>::new(&gpu_backend, buffers);
+
+        swappable_buffer.push(1);
+        swappable_buffer.push(2);
+    }
+
+    #[test]
+    fn persistent_buffer_uploads_rebuild_into_every_buffer() {
+        let mut gpu_backend = MockGpuBackend::new();
+        let buffers = create_buffers(&mut gpu_backend, 2, 8);
+        let mut persistent_buffer = PersistentBuffer::<u32>::new(&gpu_backend, buffers.clone());
+
+        persistent_buffer.rebuild([1, 2, 3]);
+        persistent_buffer.flush(&mut gpu_backend);
+        persistent_buffer.next_buffer();
+        persistent_buffer.flush(&mut gpu_backend);
+
+        for buffer_reference in buffers {
+            assert_eq!(
+                gpu_backend.read_objects::<u32>(buffer_reference, 3),
+                vec![1, 2, 3]
+            );
+        }
+    }
+
+    #[test]
+    fn persistent_buffer_uploads_only_stale_objects() {
+        let mut gpu_backend = MockGpuBackend::new();
+        let buffers = create_buffers(&mut gpu_backend, 2, 16);
+        let mut persistent_buffer = PersistentBuffer::<u32>::new(&gpu_backend, buffers.clone());
+        persistent_buffer.rebuild(0..16);
+        persistent_buffer.flush(&mut gpu_backend);
+        persistent_buffer.next_buffer();
+        persistent_buffer.flush(&mut gpu_backend);
+
+        // NOTE: Marks untouched objects, a whole upload would overwrite them.
+        gpu_backend.write_objects(buffers[0], &[u32::MAX; 16]);
+        persistent_buffer.next_buffer();
+        persistent_buffer.set(3, 30);
+        persistent_buffer.set(4, 40);
+        persistent_buffer.flush(&mut gpu_backend);
+
+        let objects = gpu_backend.read_objects::<u32>(buffers[0], 16);
+        assert_eq!(&objects[3..5], &[30, 40]);
+        assert!(
+            objects
+                .iter()
+                .enumerate()
+                .filter(|&(index, _)| index != 3 && index != 4)
+                .all(|(_, &object)| object == u32::MAX)
+        );
+
+        // NOTE: Other buffer was in flight, it gets the same objects once it's the current one.
+        persistent_buffer.next_buffer();
+        persistent_buffer.flush(&mut gpu_backend);
+        assert_eq!(
+            gpu_backend.read_objects::<u32>(buffers[1], 16),
+            vec![0, 1, 2, 30, 40, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]
+        );
+    }
+
+    #[test]
+    fn persistent_buffer_skips_indices_removed_before_flush() {
+        let mut gpu_backend = MockGpuBackend::new();
+        let buffers = create_buffers(&mut gpu_backend, 1, 16);
+        let mut persistent_buffer = PersistentBuffer::<u32>::new(&gpu_backend, buffers.clone());
+        persistent_buffer.rebuild(0..16);
+        persistent_buffer.flush(&mut gpu_backend);
+
+        persistent_buffer.set(15, 150);
+        assert_eq!(persistent_buffer.swap_remove(15), None);
+        assert_eq!(persistent_buffer.swap_remove(2), Some(14));
+        persistent_buffer.flush(&mut gpu_backend);
+
+        let objects = gpu_backend.read_objects::<u32>(buffers[0], 14);
+        assert_eq!(objects[2], 14);
+        assert_eq!(persistent_buffer.len(), 14);
+    }
 }
@@ -1,16 +1,19 @@
 use std::{
     hash::{Hash, Hasher},
     path::Path,
+    time::Duration,
 };
 
 use ahash::AHasher;
 use bevy_ecs::resource::Resource;
 use kira::{
-    AudioManager, AudioManagerSettings, DefaultBackend,
+    AudioManager, AudioManagerSettings, Decibels, DefaultBackend, Tween,
     sound::{
-        PlaybackState,
+        FromFileError, PlaybackState,
         static_sound::{StaticSoundData, StaticSoundHandle},
+        streaming::{StreamingSoundData, StreamingSoundHandle},
     },
+    track::{TrackBuilder, TrackHandle},
 };
 use shared::AudioKey;
 use slotmap::SlotMap;
@@ -40,23 +43,49 @@ pub struct AudioHandleReference {
     pub key: AudioKey,
 }
 
+/// Volumes of the buses in `0.0..=1.0`, music and sound effects are mixed into the master.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct AudioVolumes {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+}
+
+impl Default for AudioVolumes {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 1.0,
+            sfx: 1.0,
+        }
+    }
+}
+
 #[derive(Resource)]
 pub struct Audio {
     audio_manager: AudioManager,
+    music_track: TrackHandle,
+    sfx_track: TrackHandle,
     audios: SlotMap<AudioKey, AudioContainer>,
     active_audio_handlers: SlotMap<AudioKey, AudioHandle>,
+    music: Option<StreamingSoundHandle<FromFileError>>,
     hasher: ahash::AHasher,
 }
 
 impl Audio {
     pub(crate) fn new() -> Self {
-        let audio_manager =
+        let mut audio_manager =
             AudioManager::<DefaultBackend>::new(AudioManagerSettings::default()).unwrap();
+        let music_track = audio_manager.add_sub_track(TrackBuilder::new()).unwrap();
+        let sfx_track = audio_manager.add_sub_track(TrackBuilder::new()).unwrap();
 
         Self {
             audio_manager,
+            music_track,
+            sfx_track,
             audios: SlotMap::with_capacity_and_key(u8::MAX as _),
             active_audio_handlers: SlotMap::with_capacity_and_key(u8::MAX as _),
+            music: Default::default(),
             hasher: AHasher::default(),
         }
     }
@@ -102,7 +131,7 @@ impl Audio {
     ) -> AudioHandleReference {
         let audio_container = unsafe { self.audios.get(audio_reference.key).unwrap_unchecked() };
         let mut static_sound_handle = self
-            .audio_manager
+            .sfx_track
             .play(audio_container.static_sound_data.clone())
             .unwrap();
 
@@ -120,4 +149,78 @@ impl Audio {
             key: audio_handle_reference,
         }
     }
+
+    /// Streams the track from the file, it's decoded on the audio thread ahead of the playback,
+    /// so long tracks aren't kept in memory. Current music fades out while the new one fades in.
+    pub fn play_music(&mut self, path: &Path, is_looped: bool, crossfade_duration: Duration) {
+        let mut streaming_sound_data = match StreamingSoundData::from_file(path) {
+            Ok(streaming_sound_data) => streaming_sound_data.volume(Decibels::SILENCE),
+            Err(error) => {
+                println!("Failed to stream {}: {}", path.display(), error);
+
+                return;
+            }
+        };
+        if is_looped {
+            streaming_sound_data = streaming_sound_data.loop_region(..);
+        }
+
+        self.stop_music(crossfade_duration);
+
+        let mut music = self.music_track.play(streaming_sound_data).unwrap();
+        music.set_volume(Decibels::IDENTITY, Self::get_tween(crossfade_duration));
+        self.music = Some(music);
+    }
+
+    pub fn pause_music(&mut self, fade_duration: Duration) {
+        if let Some(music) = self.music.as_mut() {
+            music.pause(Self::get_tween(fade_duration));
+        }
+    }
+
+    pub fn resume_music(&mut self, fade_duration: Duration) {
+        if let Some(music) = self.music.as_mut() {
+            music.resume(Self::get_tween(fade_duration));
+        }
+    }
+
+    /// Music keeps playing on the audio thread until the fade is over.
+    pub fn stop_music(&mut self, fade_duration: Duration) {
+        if let Some(mut music) = self.music.take() {
+            music.stop(Self::get_tween(fade_duration));
+        }
+    }
+
+    pub fn is_music_playing(&self) -> bool {
+        self.music
+            .as_ref()
+            .is_some_and(|music| music.state() == PlaybackState::Playing)
+    }
+
+    pub(crate) fn apply_volumes(&mut self, audio_volumes: &AudioVolumes) {
+        let tween = Tween::default();
+
+        self.audio_manager
+            .main_track()
+            .set_volume(Self::get_decibels(audio_volumes.master), tween);
+        self.music_track
+            .set_volume(Self::get_decibels(audio_volumes.music), tween);
+        self.sfx_track
+            .set_volume(Self::get_decibels(audio_volumes.sfx), tween);
+    }
+
+    fn get_decibels(volume: f32) -> Decibels {
+        if volume <= 0.0 {
+            return Decibels::SILENCE;
+        }
+
+        Decibels((20.0 * volume.log10()).max(Decibels::SILENCE.0))
+    }
+
+    fn get_tween(duration: Duration) -> Tween {
+        Tween {
+            duration,
+            ..Default::default()
+        }
+    }
 }
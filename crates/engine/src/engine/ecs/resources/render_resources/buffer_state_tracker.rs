@@ -0,0 +1,179 @@
+use ahash::{AHashMap, AHashSet};
+use shared::BufferKey;
+use vulkanite::vk::{
+    AccessFlags2, BufferMemoryBarrier2, DependencyInfo, PipelineStageFlags2, QUEUE_FAMILY_IGNORED,
+    WHOLE_SIZE,
+    rs::{Buffer, CommandBuffer},
+};
+
+/// Last stage and access that touched a buffer.
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub struct BufferState {
+    pub stage_mask: PipelineStageFlags2,
+    pub access_mask: AccessFlags2,
+}
+
+impl BufferState {
+    pub fn new(stage_mask: PipelineStageFlags2, access_mask: AccessFlags2) -> Self {
+        Self {
+            stage_mask,
+            access_mask,
+        }
+    }
+
+    pub fn host_write() -> Self {
+        Self::new(PipelineStageFlags2::Host, AccessFlags2::HostWrite)
+    }
+
+    pub fn host_read() -> Self {
+        Self::new(PipelineStageFlags2::Host, AccessFlags2::HostRead)
+    }
+
+    pub fn transfer_write() -> Self {
+        Self::new(PipelineStageFlags2::Copy, AccessFlags2::TransferWrite)
+    }
+
+    pub fn shader_read() -> Self {
+        Self::new(
+            PipelineStageFlags2::TaskShaderEXT
+                | PipelineStageFlags2::MeshShaderEXT
+                | PipelineStageFlags2::FragmentShader
+                | PipelineStageFlags2::ComputeShader,
+            AccessFlags2::ShaderStorageRead | AccessFlags2::UniformRead,
+        )
+    }
+
+    pub fn indirect_read() -> Self {
+        Self::new(
+            PipelineStageFlags2::DrawIndirect,
+            AccessFlags2::IndirectCommandRead,
+        )
+    }
+
+    /// Stages and accesses of both states, e.g. to describe several barriers as one.
+    pub fn combine(self, other: Self) -> Self {
+        Self::new(
+            self.stage_mask | other.stage_mask,
+            self.access_mask | other.access_mask,
+        )
+    }
+
+    pub fn is_write(&self) -> bool {
+        let write_access_mask = AccessFlags2::HostWrite
+            | AccessFlags2::TransferWrite
+            | AccessFlags2::ShaderStorageWrite
+            | AccessFlags2::ShaderWrite
+            | AccessFlags2::MemoryWrite;
+
+        !(self.access_mask & write_access_mask).is_empty()
+    }
+}
+
+#[derive(Clone, Copy)]
+struct PendingBufferBarrier {
+    buffer: Buffer,
+    old_state: BufferState,
+    new_state: BufferState,
+}
+
+/// Tracks buffer states between uploads and passes, so barriers are emitted only when needed.
+#[derive(Default)]
+pub struct BufferStateTracker {
+    states: AHashMap<BufferKey, BufferState>,
+    written_buffer_keys: AHashSet<BufferKey>,
+    pending_barriers: Vec<PendingBufferBarrier>,
+}
+
+impl BufferStateTracker {
+    pub fn get_state(&self, buffer_key: BufferKey) -> BufferState {
+        self.states.get(&buffer_key).copied().unwrap_or_default()
+    }
+
+    /// Marks a write that already happened outside of a command buffer (e.g. upload).
+    pub fn record_write(&mut self, buffer_key: BufferKey, buffer_state: BufferState) {
+        self.states.insert(buffer_key, buffer_state);
+        self.written_buffer_keys.insert(buffer_key);
+    }
+
+    /// Queues barriers for every buffer written since the last call.
+    pub fn transition_written_buffers(
+        &mut self,
+        get_buffer: impl Fn(BufferKey) -> Option<Buffer>,
+        new_state: BufferState,
+    ) {
+        let written_buffer_keys = std::mem::take(&mut self.written_buffer_keys);
+        for buffer_key in written_buffer_keys {
+            if let Some(buffer) = get_buffer(buffer_key) {
+                self.transition(buffer_key, buffer, new_state);
+            }
+        }
+    }
+
+    /// Queues a barrier from the last state to the new one, if there is a hazard.
+    pub fn transition(&mut self, buffer_key: BufferKey, buffer: Buffer, new_state: BufferState) {
+        let old_state = self.get_state(buffer_key);
+        self.states.insert(buffer_key, new_state);
+
+        // NOTE: Read after read and never touched buffers don't need synchronization.
+        let is_hazard =
+            old_state.is_write() || (new_state.is_write() && old_state != Default::default());
+        if !is_hazard {
+            return;
+        }
+
+        self.pending_barriers.push(PendingBufferBarrier {
+            buffer,
+            old_state,
+            new_state,
+        });
+    }
+
+    /// Emits all queued barriers with a single `vkCmdPipelineBarrier2`.
+    /// Returns the combined old and new states of the barriers, if any is emitted.
+    pub fn flush_barriers(
+        &mut self,
+        command_buffer: CommandBuffer,
+    ) -> Option<(BufferState, BufferState)> {
+        if self.pending_barriers.is_empty() {
+            return None;
+        }
+
+        let buffer_memory_barriers: Vec<_> = self
+            .pending_barriers
+            .iter()
+            .map(|pending_barrier| {
+                BufferMemoryBarrier2::default()
+                    .src_stage_mask(pending_barrier.old_state.stage_mask)
+                    .src_access_mask(pending_barrier.old_state.access_mask)
+                    .dst_stage_mask(pending_barrier.new_state.stage_mask)
+                    .dst_access_mask(pending_barrier.new_state.access_mask)
+                    .src_queue_family_index(QUEUE_FAMILY_IGNORED)
+                    .dst_queue_family_index(QUEUE_FAMILY_IGNORED)
+                    .buffer(&pending_barrier.buffer)
+                    .offset(Default::default())
+                    .size(WHOLE_SIZE)
+            })
+            .collect();
+
+        let dependency_info =
+            DependencyInfo::default().buffer_memory_barriers(&buffer_memory_barriers);
+        command_buffer.pipeline_barrier2(&dependency_info);
+
+        let combined_states = self.pending_barriers.drain(..).fold(
+            (BufferState::default(), BufferState::default()),
+            |(old_state, new_state), pending_barrier| {
+                (
+                    old_state.combine(pending_barrier.old_state),
+                    new_state.combine(pending_barrier.new_state),
+                )
+            },
+        );
+
+        Some(combined_states)
+    }
+
+    pub fn forget(&mut self, buffer_key: BufferKey) {
+        self.states.remove(&buffer_key);
+        self.written_buffer_keys.remove(&buffer_key);
+    }
+}
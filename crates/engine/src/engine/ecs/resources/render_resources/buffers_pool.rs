@@ -1,11 +1,18 @@
 use std::{
     ffi::{CString, c_void},
     str::FromStr as _,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
 };
 
+use ahash::AHashMap;
 use bevy_ecs::resource::Resource;
+use bytemuck::Pod;
 use shared::BufferKey;
-use slotmap::SlotMap;
+use slotmap::{Key, SlotMap};
 use vma::{
     Alloc as _, Allocation, AllocationCreateFlags, AllocationCreateInfo, Allocator, MemoryUsage,
 };
@@ -19,7 +26,13 @@ use vulkanite::{
     },
 };
 
-use crate::engine::ecs::CommandGroup;
+use crate::engine::{
+    ecs::{
+        CommandGroup,
+        buffer_state_tracker::{BufferState, BufferStateTracker},
+    },
+    events::{BufferCreatedEvent, BufferDestroyedEvent, BufferMovedEvent, GpuResourceEvent},
+};
 
 pub struct MapppedAllocationHandler {
     allocator: Allocator,
@@ -62,12 +75,14 @@ pub struct AllocatedBuffer {
     pub buffer: Buffer,
     pub allocation: Allocation,
     pub buffer_info: BufferInfo,
+    pub usage: BufferUsageFlags,
+    /// Debug name, it's shown by the memory report as well.
+    pub name: Option<String>,
 }
 
 #[derive(Default, Clone, Copy)]
 pub struct BufferReference {
     key: BufferKey,
-    buffer_info: BufferInfo,
 }
 
 #[derive(Default, Clone, Copy)]
@@ -92,13 +107,41 @@ impl BufferInfo {
 }
 
 impl BufferReference {
+    pub(crate) fn new(key: BufferKey) -> Self {
+        Self { key }
+    }
+
+    #[inline(always)]
+    pub(crate) fn get_key(&self) -> BufferKey {
+        self.key
+    }
+
+    /// Slot of the device address in the buffer addresses table.
+    pub fn get_index(&self) -> u32 {
+        self.key.data().get_key() - 1
+    }
+
     pub fn get_buffer<'a>(&'a self, buffers_pool: &'a BuffersPool) -> Option<&'a AllocatedBuffer> {
         buffers_pool.get_buffer(*self)
     }
+}
 
-    #[inline(always)]
-    pub fn get_buffer_info(&self) -> BufferInfo {
-        self.buffer_info
+#[derive(Default, Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct UploadStatistics {
+    pub uploaded_bytes_count: u64,
+    pub upload_duration: Duration,
+}
+
+impl UploadStatistics {
+    /// Bytes per second.
+    pub fn get_throughput(&self) -> f64 {
+        let seconds = self.upload_duration.as_secs_f64();
+        if seconds <= f64::EPSILON {
+            return Default::default();
+        }
+
+        self.uploaded_bytes_count as f64 / seconds
     }
 }
 
@@ -108,26 +151,65 @@ pub struct BuffersPool {
     allocator: Allocator,
     slots: SlotMap<BufferKey, AllocatedBuffer>,
     staging_buffer_reference: BufferReference,
+    buffer_addresses_reference: BufferReference,
     upload_command_group: CommandGroup,
     transfer_queue: Queue,
+    /// Buffers are shared concurrently by several families, so the compute queue reads them
+    /// without ownership transfers.
+    queue_family_indices: Vec<u32>,
+    uploaded_bytes_count: AtomicU64,
+    upload_nanoseconds: AtomicU64,
+    buffer_state_tracker: Mutex<BufferStateTracker>,
+    /// Lookups of freed buffers, they're counted in debug builds only.
+    stale_accesses_count: AtomicU64,
+    /// Debug names of the freed buffers, so stale lookups tell what was freed.
+    freed_buffer_names: Mutex<AHashMap<BufferKey, Option<String>>>,
+    is_stale_access_panicking: bool,
+    /// Lifecycle changes since the last `notify_gpu_resource_events_system`.
+    gpu_resource_events: Vec<GpuResourceEvent>,
 }
 
 impl BuffersPool {
+    pub const MAX_BUFFERS_COUNT: usize = 65_536;
+    /// Names of older frees are forgotten, their stale lookups are still counted.
+    const MAX_FREED_BUFFER_NAMES_COUNT: usize = 4_096;
+
     pub fn new(
         device: Device,
         allocator: Allocator,
         upload_command_group: CommandGroup,
         transfer_queue: Queue,
+        queue_family_indices: Vec<u32>,
     ) -> Self {
         let mut memory_bucket = Self {
             device,
             allocator,
             slots: SlotMap::with_capacity_and_key(2_048),
             staging_buffer_reference: Default::default(),
+            buffer_addresses_reference: Default::default(),
             upload_command_group,
             transfer_queue,
+            queue_family_indices,
+            uploaded_bytes_count: Default::default(),
+            upload_nanoseconds: Default::default(),
+            buffer_state_tracker: Default::default(),
+            stale_accesses_count: Default::default(),
+            freed_buffer_names: Default::default(),
+            is_stale_access_panicking: false,
+            gpu_resource_events: Default::default(),
         };
 
+        // NOTE: Shaders reach buffers by their indices, so buffers can be moved by rewriting a single address.
+        let buffer_addresses_reference = memory_bucket.create_buffer(
+            std::mem::size_of::<DeviceAddress>() * Self::MAX_BUFFERS_COUNT,
+            BufferUsageFlags::empty(),
+            BufferVisibility::HostVisible,
+            None,
+            Some("Buffer Addresses Buffer".to_string()),
+        );
+        memory_bucket.buffer_addresses_reference = buffer_addresses_reference;
+        memory_bucket.write_buffer_address(buffer_addresses_reference);
+
         // Pre-allocate 64 MB for transfers.
         let staging_buffer_reference = memory_bucket.create_buffer(
             1024 * 1024 * 64,
@@ -159,12 +241,13 @@ impl BuffersPool {
             buffer_kind_usage = BufferUsageFlags::empty();
         }
 
-        let buffer_create_info = BufferCreateInfo {
-            size: allocation_size as _,
-            usage: usage | buffer_kind_usage | BufferUsageFlags::ShaderDeviceAddress,
-            sharing_mode: vulkanite::vk::SharingMode::Exclusive,
-            ..Default::default()
-        };
+        // NOTE: Defragmentation copies content of device buffers, when their memory is moved.
+        if buffer_visibility == BufferVisibility::DeviceOnly {
+            buffer_kind_usage |= BufferUsageFlags::TransferSrc;
+        }
+
+        let usage = usage | buffer_kind_usage | BufferUsageFlags::ShaderDeviceAddress;
+        let buffer_create_info = self.get_buffer_create_info(allocation_size as _, usage);
 
         if buffer_visibility == BufferVisibility::Unspecified {
             panic!("Trying to create a buffer with unspecified visibility!");
@@ -220,12 +303,12 @@ impl BuffersPool {
         let buffer = Buffer::from_inner(buffer);
         let device_address = unsafe { self.get_device_address(buffer) };
 
-        if let Some(name) = name {
-            let name = CString::from_str(name.as_str()).unwrap();
+        if let Some(name) = name.as_ref() {
+            let object_name = CString::from_str(name.as_str()).unwrap();
             let debug_utils_object_name = DebugUtilsObjectNameInfoEXT {
                 object_type: ObjectType::Buffer,
                 object_handle: buffer.as_raw().get(),
-                p_object_name: name.as_ptr() as *const _,
+                p_object_name: object_name.as_ptr() as *const _,
                 ..Default::default()
             };
 
@@ -239,25 +322,145 @@ impl BuffersPool {
             buffer,
             allocation,
             buffer_info,
+            usage,
+            name: name.clone(),
         };
 
-        self.insert_buffer(allocated_buffer)
+        let buffer_reference = self.insert_buffer(allocated_buffer);
+        self.gpu_resource_events
+            .push(GpuResourceEvent::BufferCreated(BufferCreatedEvent {
+                buffer_reference,
+                name,
+                size: buffer_info.size,
+            }));
+
+        buffer_reference
     }
 
     #[inline(always)]
     fn insert_buffer(&mut self, allocated_buffer: AllocatedBuffer) -> BufferReference {
-        let buffer_info = allocated_buffer.buffer_info;
         let buffer_key = self.slots.insert(allocated_buffer);
 
-        BufferReference {
-            key: buffer_key,
-            buffer_info,
+        let buffer_reference = BufferReference::new(buffer_key);
+        self.write_buffer_address(buffer_reference);
+
+        buffer_reference
+    }
+
+    /// Writes the current device address of the buffer into its slot of the buffer addresses table.
+    // NOTE: Slots are reused only after the deletion queue, so frames in flight don't read the written one.
+    fn write_buffer_address(&self, buffer_reference: BufferReference) {
+        let Some(buffer_addresses) = self.get_buffer(self.buffer_addresses_reference) else {
+            return;
+        };
+
+        let buffer_index = buffer_reference.get_index() as usize;
+        if buffer_index >= Self::MAX_BUFFERS_COUNT {
+            panic!(
+                "Buffer index {} exceeds the buffer addresses table of {} buffers!",
+                buffer_index,
+                Self::MAX_BUFFERS_COUNT
+            );
+        }
+
+        let device_address = self
+            .get_buffer(buffer_reference)
+            .unwrap()
+            .buffer_info
+            .device_address;
+        unsafe {
+            let ptr_mapped_memory = self
+                .allocator
+                .map_memory(buffer_addresses.allocation)
+                .unwrap() as *mut DeviceAddress;
+
+            ptr_mapped_memory.add(buffer_index).write(device_address);
+
+            self.allocator.unmap_memory(buffer_addresses.allocation);
         }
     }
 
+    pub fn get_buffer_addresses_reference(&self) -> BufferReference {
+        self.buffer_addresses_reference
+    }
+
     #[inline(always)]
     pub fn get_buffer(&self, buffer_reference: BufferReference) -> Option<&AllocatedBuffer> {
-        self.slots.get(buffer_reference.key)
+        let allocated_buffer = self.slots.get(buffer_reference.key);
+        if cfg!(debug_assertions) && allocated_buffer.is_none() && !buffer_reference.key.is_null() {
+            self.report_stale_access(buffer_reference);
+        }
+
+        allocated_buffer
+    }
+
+    /// Info of the current buffer, it changes when defragmentation moves the buffer.
+    /// Freed buffers give the default info, so their device address is null.
+    #[inline(always)]
+    pub fn get_buffer_info(&self, buffer_reference: BufferReference) -> BufferInfo {
+        self.get_buffer(buffer_reference)
+            .map(|allocated_buffer| allocated_buffer.buffer_info)
+            .unwrap_or_default()
+    }
+
+    /// Key of the reference was freed, or its slot was reused by a buffer of a newer generation.
+    #[cold]
+    fn report_stale_access(&self, buffer_reference: BufferReference) {
+        self.stale_accesses_count.fetch_add(1, Ordering::Relaxed);
+
+        let freed_buffer_names = self.freed_buffer_names.lock().unwrap();
+        let name = freed_buffer_names
+            .get(&buffer_reference.key)
+            .and_then(|name| name.as_deref())
+            .unwrap_or("Unnamed Buffer");
+        let message = std::format!(
+            "Stale buffer reference {:?} of \"{}\", the buffer was freed",
+            buffer_reference.key,
+            name
+        );
+        if self.is_stale_access_panicking {
+            panic!("{}", message);
+        }
+        println!("{}", message);
+    }
+
+    /// Lookups of freed buffers since the start, it's always zero in release builds.
+    #[inline(always)]
+    pub fn get_stale_accesses_count(&self) -> u64 {
+        self.stale_accesses_count.load(Ordering::Relaxed)
+    }
+
+    /// Stale lookups panic instead of being logged, e.g. for tests that catch a use after free.
+    pub fn set_stale_access_panicking(&mut self, is_stale_access_panicking: bool) {
+        self.is_stale_access_panicking = is_stale_access_panicking;
+    }
+
+    pub fn get_buffers(&self) -> impl Iterator<Item = (BufferReference, &AllocatedBuffer)> {
+        self.slots.iter().map(|(buffer_key, allocated_buffer)| {
+            let buffer_reference = BufferReference::new(buffer_key);
+
+            (buffer_reference, allocated_buffer)
+        })
+    }
+
+    fn get_buffer_create_info(
+        &self,
+        size: DeviceSize,
+        usage: BufferUsageFlags,
+    ) -> BufferCreateInfo<'_> {
+        let buffer_create_info = BufferCreateInfo {
+            size,
+            usage,
+            sharing_mode: vulkanite::vk::SharingMode::Exclusive,
+            ..Default::default()
+        };
+        if self.queue_family_indices.len() > 1 {
+            return buffer_create_info
+                .sharing_mode(vulkanite::vk::SharingMode::Concurrent)
+                .queue_family_indices(&self.queue_family_indices);
+        }
+
+        buffer_create_info
     }
 
     unsafe fn get_device_address(&self, buffer: Buffer) -> DeviceAddress {
@@ -266,41 +469,76 @@ impl BuffersPool {
         self.device.get_buffer_address(&buffer_device_address)
     }
 
-    pub unsafe fn transfer_data_to_buffer(
+    /// Values are written from the start of the buffer, through the staging buffer if it's device only.
+    pub fn transfer_slice<T: Pod>(&self, buffer_reference: BufferReference, src: &[T]) {
+        let src = bytemuck::cast_slice::<T, u8>(src);
+        self.validate_transfer(buffer_reference, src.len(), src.len());
+
+        unsafe {
+            self.transfer_data_to_buffer_raw(buffer_reference, src.as_ptr() as _, src.len());
+        }
+    }
+
+    #[inline(always)]
+    pub fn transfer_value<T: Pod>(&self, buffer_reference: BufferReference, value: &T) {
+        self.transfer_slice(buffer_reference, std::slice::from_ref(value));
+    }
+
+    /// Offsets and sizes of the regions are in bytes, sources are in `src` and destinations in the buffer.
+    pub fn transfer_regions<T: Pod>(
         &self,
         buffer_reference: BufferReference,
-        src: &[u8],
-        size: usize,
+        src: &[T],
+        regions_to_copy: &[BufferCopy],
     ) {
-        let allocated_buffer = buffer_reference.get_buffer(self).unwrap();
-
-        let buffer_visibility = allocated_buffer.buffer_info.buffer_visibility;
-        let target_buffer = match buffer_visibility {
-            BufferVisibility::HostVisible => allocated_buffer,
-            BufferVisibility::DeviceOnly => self.get_buffer(self.staging_buffer_reference).unwrap(),
-            BufferVisibility::Unspecified => unreachable!(),
-        };
+        let src = bytemuck::cast_slice::<T, u8>(src);
+        let mut dst_end = 0;
+        let mut staged_size = 0;
+        for buffer_copy in regions_to_copy {
+            assert!(
+                (buffer_copy.src_offset + buffer_copy.size) as usize <= src.len(),
+                "Region {}..{} is out of the source of {} bytes",
+                buffer_copy.src_offset,
+                buffer_copy.src_offset + buffer_copy.size,
+                src.len()
+            );
+            dst_end = dst_end.max((buffer_copy.dst_offset + buffer_copy.size) as usize);
+            staged_size += buffer_copy.size as usize;
+        }
+        self.validate_transfer(buffer_reference, dst_end, staged_size);
 
         unsafe {
-            let p_mapped_memory = self.allocator.map_memory(target_buffer.allocation).unwrap();
-
-            std::ptr::copy_nonoverlapping(src.as_ptr(), p_mapped_memory as _, size);
-
-            self.allocator.unmap_memory(target_buffer.allocation);
+            self.transfer_data_to_buffer_with_offset(
+                buffer_reference,
+                src.as_ptr() as _,
+                regions_to_copy,
+            );
         }
+    }
 
-        if buffer_visibility == BufferVisibility::DeviceOnly {
-            let regions_to_copy = [BufferCopy {
-                size: size as _,
-                ..Default::default()
-            }];
-            unsafe {
-                self.copy_buffer_to_buffer(
-                    target_buffer.buffer,
-                    allocated_buffer.buffer,
-                    &regions_to_copy,
-                )
-            }
+    /// Writes out of the buffer or of the staging buffer would corrupt the memory, they are bugs of the caller.
+    fn validate_transfer(
+        &self,
+        buffer_reference: BufferReference,
+        dst_end: usize,
+        staged_size: usize,
+    ) {
+        let buffer_info = self.get_buffer_info(buffer_reference);
+        assert!(
+            dst_end as DeviceSize <= buffer_info.size,
+            "Transfer of {} bytes is out of the buffer of {} bytes",
+            dst_end,
+            buffer_info.size
+        );
+
+        if buffer_info.buffer_visibility == BufferVisibility::DeviceOnly {
+            let staging_buffer_size = self.get_buffer_info(self.staging_buffer_reference).size;
+            assert!(
+                staged_size as DeviceSize <= staging_buffer_size,
+                "Transfer of {} bytes is out of the staging buffer of {} bytes",
+                staged_size,
+                staging_buffer_size
+            );
         }
     }
 
@@ -308,12 +546,13 @@ impl BuffersPool {
         self.staging_buffer_reference
     }
 
-    pub unsafe fn transfer_data_to_buffer_raw(
-        &mut self,
+    unsafe fn transfer_data_to_buffer_raw(
+        &self,
         buffer_reference: BufferReference,
         src: *const c_void,
         size: usize,
     ) {
+        let upload_started_at = Instant::now();
         let allocated_buffer = buffer_reference.get_buffer(self).unwrap();
 
         let buffer_visibility = allocated_buffer.buffer_info.buffer_visibility;
@@ -344,14 +583,18 @@ impl BuffersPool {
                 )
             }
         }
+
+        self.record_buffer_write(buffer_reference, buffer_visibility);
+        self.record_upload(size, upload_started_at);
     }
 
-    pub unsafe fn transfer_data_to_buffer_with_offset(
+    unsafe fn transfer_data_to_buffer_with_offset(
         &self,
         buffer_reference: BufferReference,
         src: *const c_void,
         regions_to_copy: &[BufferCopy],
     ) {
+        let upload_started_at = Instant::now();
         let allocated_buffer = buffer_reference.get_buffer(self).unwrap();
 
         let buffer_visibility = allocated_buffer.buffer_info.buffer_visibility;
@@ -405,6 +648,84 @@ impl BuffersPool {
                 )
             }
         }
+
+        let size = regions_to_copy
+            .iter()
+            .map(|buffer_copy| buffer_copy.size as usize)
+            .sum();
+        self.record_buffer_write(buffer_reference, buffer_visibility);
+        self.record_upload(size, upload_started_at);
+    }
+
+    pub fn get_upload_statistics(&self) -> UploadStatistics {
+        UploadStatistics {
+            uploaded_bytes_count: self.uploaded_bytes_count.load(Ordering::Relaxed),
+            upload_duration: Duration::from_nanos(self.upload_nanoseconds.load(Ordering::Relaxed)),
+        }
+    }
+
+    /// Queues barriers for buffers written since the last call, so shaders see the uploads.
+    pub fn prepare_written_buffers(&self, new_state: BufferState) {
+        let mut buffer_state_tracker = self.buffer_state_tracker.lock().unwrap();
+        buffer_state_tracker.transition_written_buffers(
+            |buffer_key| {
+                self.slots
+                    .get(buffer_key)
+                    .map(|allocated_buffer| allocated_buffer.buffer)
+            },
+            new_state,
+        );
+    }
+
+    /// Queues a barrier, if the last access to the buffer conflicts with the new one.
+    pub fn require_buffer_state(&self, buffer_reference: BufferReference, new_state: BufferState) {
+        let Some(allocated_buffer) = self.slots.get(buffer_reference.key) else {
+            return;
+        };
+
+        self.buffer_state_tracker.lock().unwrap().transition(
+            buffer_reference.key,
+            allocated_buffer.buffer,
+            new_state,
+        );
+    }
+
+    /// Returns the combined old and new states of the emitted barriers, if any is emitted.
+    pub fn flush_buffer_barriers(
+        &self,
+        command_buffer: CommandBuffer,
+    ) -> Option<(BufferState, BufferState)> {
+        self.buffer_state_tracker
+            .lock()
+            .unwrap()
+            .flush_barriers(command_buffer);
+    }
+
+    #[inline(always)]
+    fn record_buffer_write(
+        &self,
+        buffer_reference: BufferReference,
+        buffer_visibility: BufferVisibility,
+    ) {
+        let buffer_state = match buffer_visibility {
+            BufferVisibility::DeviceOnly => BufferState::transfer_write(),
+            _ => BufferState::host_write(),
+        };
+
+        self.buffer_state_tracker
+            .lock()
+            .unwrap()
+            .record_write(buffer_reference.key, buffer_state);
+    }
+
+    #[inline(always)]
+    fn record_upload(&self, size: usize, upload_started_at: Instant) {
+        self.uploaded_bytes_count
+            .fetch_add(size as _, Ordering::Relaxed);
+        self.upload_nanoseconds.fetch_add(
+            upload_started_at.elapsed().as_nanos() as _,
+            Ordering::Relaxed,
+        );
     }
 
     unsafe fn copy_buffer_to_buffer(
@@ -412,6 +733,18 @@ impl BuffersPool {
         src_buffer: Buffer,
         dst_buffer: Buffer,
         regions_to_copy: &[BufferCopy],
+    ) {
+        unsafe {
+            self.submit_upload_commands(|command_buffer| {
+                command_buffer.copy_buffer(src_buffer, dst_buffer, regions_to_copy);
+            });
+        }
+    }
+
+    /// Records commands into the upload command buffer and waits until the transfer queue executes them.
+    pub(crate) unsafe fn submit_upload_commands(
+        &self,
+        record_commands: impl FnOnce(CommandBuffer),
     ) {
         let command_buffer = self.upload_command_group.command_buffer;
 
@@ -422,11 +755,7 @@ impl BuffersPool {
 
         command_buffer.begin(&command_buffer_begin_info).unwrap();
 
-        self.upload_command_group.command_buffer.copy_buffer(
-            src_buffer,
-            dst_buffer,
-            regions_to_copy,
-        );
+        record_commands(command_buffer);
 
         command_buffer.end().unwrap();
 
@@ -451,6 +780,59 @@ impl BuffersPool {
             .unwrap();
     }
 
+    /// Creates a buffer like the given one on the memory of `dst_allocation` and records the copy of the content to it.
+    pub(crate) unsafe fn create_moved_buffer(
+        &self,
+        buffer_reference: BufferReference,
+        dst_allocation: Allocation,
+        command_buffer: CommandBuffer,
+    ) -> Buffer {
+        let allocated_buffer = self.get_buffer(buffer_reference).unwrap();
+        let size = allocated_buffer.buffer_info.size;
+
+        let buffer_create_info = self.get_buffer_create_info(size, allocated_buffer.usage);
+        let buffer = self.device.create_buffer(&buffer_create_info).unwrap();
+        unsafe {
+            self.allocator
+                .bind_buffer_memory(dst_allocation, *buffer)
+                .unwrap();
+        }
+
+        let regions_to_copy = [BufferCopy {
+            size,
+            ..Default::default()
+        }];
+        command_buffer.copy_buffer(allocated_buffer.buffer, buffer, &regions_to_copy);
+
+        buffer
+    }
+
+    /// Destroys the old buffer, once its allocation is moved, the reference keeps its key.
+    pub(crate) unsafe fn replace_buffer(
+        &mut self,
+        buffer_reference: BufferReference,
+        buffer: Buffer,
+    ) {
+        let device_address = unsafe { self.get_device_address(buffer) };
+        let allocated_buffer = self.slots.get_mut(buffer_reference.key).unwrap();
+
+        self.device.destroy_buffer(Some(allocated_buffer.buffer));
+        let previous_device_address = allocated_buffer.buffer_info.device_address;
+        allocated_buffer.buffer = buffer;
+        allocated_buffer.buffer_info.device_address = device_address;
+        let name = allocated_buffer.name.clone();
+
+        self.record_buffer_write(buffer_reference, BufferVisibility::DeviceOnly);
+
+        self.write_buffer_address(buffer_reference);
+        self.gpu_resource_events
+            .push(GpuResourceEvent::BufferMoved(BufferMovedEvent {
+                buffer_reference,
+                name,
+                previous_device_address,
+            }));
+    }
+
     #[inline(always)]
     pub fn map_allocation(&self, buffer_reference: BufferReference) -> MapppedAllocationHandler {
         let allocated_buffer = self.get_buffer(buffer_reference).unwrap();
@@ -464,6 +846,43 @@ impl BuffersPool {
         MapppedAllocationHandler::new(self.allocator, allocated_buffer.allocation, ptr)
     }
 
+    /// Buffer must not be in use by the GPU anymore, see `DeletionQueue`.
+    pub unsafe fn free_buffer(&mut self, buffer_reference: BufferReference) {
+        self.buffer_state_tracker
+            .get_mut()
+            .unwrap()
+            .forget(buffer_reference.key);
+
+        if let Some(allocated_buffer) = self.slots.remove(buffer_reference.key) {
+            if cfg!(debug_assertions) {
+                let freed_buffer_names = self.freed_buffer_names.get_mut().unwrap();
+                if freed_buffer_names.len() >= Self::MAX_FREED_BUFFER_NAMES_COUNT {
+                    freed_buffer_names.clear();
+                }
+                freed_buffer_names.insert(buffer_reference.key, allocated_buffer.name.clone());
+            }
+
+            let mut allocation = allocated_buffer.allocation;
+
+            unsafe {
+                self.allocator
+                    .destroy_buffer(*allocated_buffer.buffer, &mut allocation);
+            }
+
+            self.gpu_resource_events
+                .push(GpuResourceEvent::BufferDestroyed(BufferDestroyedEvent {
+                    buffer_reference,
+                    name: allocated_buffer.name,
+                    size: allocated_buffer.buffer_info.size,
+                }));
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn take_gpu_resource_events(&mut self) -> Vec<GpuResourceEvent> {
+        std::mem::take(&mut self.gpu_resource_events)
+    }
+
     pub unsafe fn free_allocations(&mut self) {
         self.slots.drain().for_each(|(_, allocated_buffer)| unsafe {
             let mut allocation = allocated_buffer.allocation;
@@ -0,0 +1,165 @@
+use ahash::AHashMap;
+use shared::TextureKey;
+use vulkanite::vk::{
+    AccessFlags2, DependencyInfo, ImageLayout, ImageMemoryBarrier2, ImageSubresourceRange,
+    PipelineStageFlags2, QUEUE_FAMILY_IGNORED,
+    rs::{CommandBuffer, Image},
+};
+
+/// Last layout, stage and access that touched an image.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ImageState {
+    pub layout: ImageLayout,
+    pub stage_mask: PipelineStageFlags2,
+    pub access_mask: AccessFlags2,
+}
+
+impl Default for ImageState {
+    fn default() -> Self {
+        Self::new(
+            ImageLayout::Undefined,
+            PipelineStageFlags2::None,
+            AccessFlags2::None,
+        )
+    }
+}
+
+impl ImageState {
+    pub fn new(
+        layout: ImageLayout,
+        stage_mask: PipelineStageFlags2,
+        access_mask: AccessFlags2,
+    ) -> Self {
+        Self {
+            layout,
+            stage_mask,
+            access_mask,
+        }
+    }
+
+    pub fn compute_write() -> Self {
+        Self::new(
+            ImageLayout::General,
+            PipelineStageFlags2::ComputeShader,
+            AccessFlags2::ShaderStorageWrite,
+        )
+    }
+
+    pub fn compute_read_write() -> Self {
+        Self::new(
+            ImageLayout::General,
+            PipelineStageFlags2::ComputeShader,
+            AccessFlags2::ShaderStorageRead | AccessFlags2::ShaderStorageWrite,
+        )
+    }
+
+    /// Uploaded textures stay in the general layout.
+    pub fn sampled_read() -> Self {
+        Self::new(
+            ImageLayout::General,
+            PipelineStageFlags2::FragmentShader,
+            AccessFlags2::ShaderSampledRead,
+        )
+    }
+
+    pub fn transfer_read() -> Self {
+        Self::new(
+            ImageLayout::General,
+            PipelineStageFlags2::Copy,
+            AccessFlags2::TransferRead,
+        )
+    }
+
+    pub fn transfer_write() -> Self {
+        Self::new(
+            ImageLayout::General,
+            PipelineStageFlags2::Copy,
+            AccessFlags2::TransferWrite,
+        )
+    }
+
+    pub fn is_write(&self) -> bool {
+        let write_access_mask = AccessFlags2::TransferWrite
+            | AccessFlags2::ShaderStorageWrite
+            | AccessFlags2::ShaderWrite
+            | AccessFlags2::ColorAttachmentWrite
+            | AccessFlags2::DepthStencilAttachmentWrite
+            | AccessFlags2::HostWrite
+            | AccessFlags2::MemoryWrite;
+
+        !(self.access_mask & write_access_mask).is_empty()
+    }
+}
+
+/// Tracks image states between passes, so callers don't have to know the previous layout.
+/// Storage and sampled textures live in different slot maps, so their keys may collide.
+#[derive(Default)]
+pub struct ImageStateTracker {
+    states: AHashMap<(TextureKey, bool), ImageState>,
+}
+
+impl ImageStateTracker {
+    /// Never touched images are in the undefined layout.
+    pub fn get_state(&self, texture_key: TextureKey, is_read_only: bool) -> ImageState {
+        self.states
+            .get(&(texture_key, is_read_only))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Marks a transition that already happened outside of the tracker (e.g. upload).
+    pub fn record_state(&mut self, texture_key: TextureKey, is_read_only: bool, state: ImageState) {
+        self.states.insert((texture_key, is_read_only), state);
+    }
+
+    /// Next transition starts from the undefined layout, the content isn't preserved by it.
+    pub fn discard(&mut self, texture_key: TextureKey, is_read_only: bool) {
+        if let Some(state) = self.states.get_mut(&(texture_key, is_read_only)) {
+            state.layout = ImageLayout::Undefined;
+        }
+    }
+
+    /// Emits a barrier from the last state to the new one, if there is a hazard or a layout change.
+    /// Returns the last state when the barrier is emitted.
+    pub fn transition(
+        &mut self,
+        command_buffer: CommandBuffer,
+        texture_key: TextureKey,
+        is_read_only: bool,
+        image: Image,
+        subresource_range: ImageSubresourceRange,
+        new_state: ImageState,
+    ) -> Option<ImageState> {
+        let old_state = self.get_state(texture_key, is_read_only);
+        self.states.insert((texture_key, is_read_only), new_state);
+
+        // NOTE: Read after read in the same layout doesn't need synchronization.
+        let is_hazard = old_state.layout != new_state.layout
+            || old_state.is_write()
+            || (new_state.is_write() && old_state != Default::default());
+        if !is_hazard {
+            return None;
+        }
+
+        let image_memory_barriers = [ImageMemoryBarrier2::default()
+            .src_stage_mask(old_state.stage_mask)
+            .src_access_mask(old_state.access_mask)
+            .dst_stage_mask(new_state.stage_mask)
+            .dst_access_mask(new_state.access_mask)
+            .old_layout(old_state.layout)
+            .new_layout(new_state.layout)
+            .src_queue_family_index(QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(QUEUE_FAMILY_IGNORED)
+            .image(&image)
+            .subresource_range(subresource_range)];
+        let dependency_info =
+            DependencyInfo::default().image_memory_barriers(&image_memory_barriers);
+        command_buffer.pipeline_barrier2(&dependency_info);
+
+        Some(old_state)
+    }
+
+    pub fn forget(&mut self, texture_key: TextureKey, is_read_only: bool) {
+        self.states.remove(&(texture_key, is_read_only));
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::BTreeSet;
+
+use ahash::AHashMap;
+use bevy_ecs::entity::Entity;
+
+/// Stable indices of renderable entities in the instance buffer, an entity keeps its index until it's freed,
+/// so per-instance data on the GPU can be kept between frames. Freed indices are reused by new entities,
+/// the lowest one first, so the slots stay packed at the front.
+#[derive(Default)]
+pub struct InstanceSlotAllocator {
+    indices: AHashMap<Entity, usize>,
+    free_indices: BTreeSet<usize>,
+    slots_count: usize,
+}
+
+impl InstanceSlotAllocator {
+    #[inline(always)]
+    pub fn get_index(&self, entity: Entity) -> Option<usize> {
+        self.indices.get(&entity).copied()
+    }
+
+    /// Returns the index of the entity, the same one while it's allocated.
+    pub fn allocate(&mut self, entity: Entity) -> usize {
+        if let Some(&index) = self.indices.get(&entity) {
+            return index;
+        }
+
+        let index = self.free_indices.pop_first().unwrap_or_else(|| {
+            self.slots_count += 1;
+
+            self.slots_count - 1
+        });
+        self.indices.insert(entity, index);
+
+        index
+    }
+
+    /// Returns the freed index, it stays a hole until another entity takes it.
+    /// Trailing holes are trimmed, so the count of slots shrinks with the last entities.
+    pub fn free(&mut self, entity: Entity) -> Option<usize> {
+        let index = self.indices.remove(&entity)?;
+        self.free_indices.insert(index);
+
+        while self
+            .free_indices
+            .last()
+            .is_some_and(|&last_index| last_index + 1 == self.slots_count)
+        {
+            self.free_indices.pop_last();
+            self.slots_count -= 1;
+        }
+
+        Some(index)
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.indices.clear();
+        self.free_indices.clear();
+        self.slots_count = Default::default();
+    }
+
+    /// Holes included, it's the count of instances to draw.
+    #[inline(always)]
+    pub fn get_slots_count(&self) -> usize {
+        self.slots_count
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+}
@@ -4,7 +4,10 @@ use shared::MaterialKey;
 use slotmap::SlotMap;
 use vulkanite::vk::DeviceAddress;
 
-use crate::engine::ecs::components::material::{MaterialState, MaterialType};
+use crate::engine::ecs::{
+    components::material::{MaterialProperties, MaterialState, MaterialType},
+    textures_pool::TextureReference,
+};
 
 #[derive(Clone, Copy)]
 pub struct OffsetElement {
@@ -55,6 +58,9 @@ impl MaterialReference {
 pub struct MaterialInstance {
     material_state: MaterialState,
     offset_element: Option<OffsetElement>,
+    texture_references: Vec<TextureReference>,
+    references_count: usize,
+    data: Vec<u8>,
 }
 
 impl MaterialInstance {
@@ -62,9 +68,20 @@ impl MaterialInstance {
         Self {
             material_state,
             offset_element,
+            texture_references: Default::default(),
+            references_count: Default::default(),
+            data: Default::default(),
         }
     }
 
+    pub fn get_texture_references(&self) -> &[TextureReference] {
+        &self.texture_references
+    }
+
+    pub fn get_data(&self) -> &[u8] {
+        &self.data
+    }
+
     pub fn get_material_state(&self) -> MaterialState {
         self.material_state
     }
@@ -91,6 +108,8 @@ pub struct MaterialInfo {
 #[derive(Resource)]
 pub struct MaterialsPool {
     slots: SlotMap<MaterialKey, MaterialInstance>,
+    materials_lookup: AHashMap<(MaterialType, Vec<u8>), MaterialReference>,
+    deduplicated_materials_count: usize,
     materials_to_write: AHashMap<MaterialReference, Vec<u8>>,
     base_device_address_material_data: DeviceAddress,
     variable_offsets: VariableOffsets,
@@ -103,18 +122,32 @@ impl MaterialsPool {
     ) -> Self {
         Self {
             slots: SlotMap::with_capacity_and_key(pre_allocated_count),
+            materials_lookup: AHashMap::with_capacity(pre_allocated_count),
+            deduplicated_materials_count: Default::default(),
             materials_to_write: AHashMap::with_capacity(1024),
             variable_offsets: VariableOffsets::new(pre_allocated_count),
             base_device_address_material_data,
         }
     }
 
+    /// Returns the already written material, if data and state are the same, and whether it's a new one.
+    /// New material owns `texture_references`, they're given back once it's released.
     pub fn write_material(
         &mut self,
         data: &[u8],
         material_state: MaterialState,
-    ) -> MaterialReference {
-        let material_instance = MaterialInstance::new(material_state, None);
+        texture_references: &[TextureReference],
+    ) -> (MaterialReference, bool) {
+        let material_content = (material_state.material_type, data.to_vec());
+        if let Some(&material_reference) = self.materials_lookup.get(&material_content) {
+            self.deduplicated_materials_count += 1;
+
+            return (material_reference, false);
+        }
+
+        let mut material_instance = MaterialInstance::new(material_state, None);
+        material_instance.texture_references = texture_references.to_vec();
+        material_instance.data = data.to_vec();
         let material_key = self.slots.insert(material_instance);
         let material_reference = MaterialReference::new(material_key);
 
@@ -130,8 +163,45 @@ impl MaterialsPool {
         }
         self.materials_to_write
             .insert(material_reference, data.to_vec());
+        self.materials_lookup
+            .insert(material_content, material_reference);
 
-        material_reference
+        (material_reference, true)
+    }
+
+    pub fn retain_material(&mut self, material_reference: MaterialReference) {
+        if let Some(material_instance) = self.slots.get_mut(material_reference.key) {
+            material_instance.references_count += 1;
+        }
+    }
+
+    /// Removes the material after its last reference and returns textures it owned.
+    // NOTE: Space of the material data isn't reused, offsets only grow.
+    pub fn release_material(
+        &mut self,
+        material_reference: MaterialReference,
+    ) -> Option<Vec<TextureReference>> {
+        let material_instance = self.slots.get_mut(material_reference.key)?;
+
+        material_instance.references_count = material_instance.references_count.saturating_sub(1);
+        if material_instance.references_count > Default::default() {
+            return None;
+        }
+
+        let material_instance = self.slots.remove(material_reference.key)?;
+        self.materials_lookup
+            .retain(|_, &mut looked_up_reference| looked_up_reference != material_reference);
+        self.materials_to_write.remove(&material_reference);
+
+        Some(material_instance.texture_references)
+    }
+
+    pub fn get_materials_count(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn get_deduplicated_materials_count(&self) -> usize {
+        self.deduplicated_materials_count
     }
 
     pub fn reset_materails_to_write(&mut self) {
@@ -149,6 +219,39 @@ impl MaterialsPool {
         self.slots.get(material_reference.key)
     }
 
+    /// Properties are the head of every material data.
+    pub fn get_material_properties(
+        &self,
+        material_reference: MaterialReference,
+    ) -> Option<MaterialProperties> {
+        let material_instance = self.slots.get(material_reference.key)?;
+        let data = material_instance
+            .data
+            .get(..std::mem::size_of::<MaterialProperties>())?;
+
+        Some(bytemuck::pod_read_unaligned(data))
+    }
+
+    /// Rewrites properties of the material, every mesh sharing it is affected.
+    /// Returns the whole material data to upload at the material offset.
+    pub fn set_material_properties(
+        &mut self,
+        material_reference: MaterialReference,
+        material_properties: MaterialProperties,
+    ) -> Option<&[u8]> {
+        // NOTE: Edited material no longer matches the data it was deduplicated by.
+        self.materials_lookup
+            .retain(|_, &mut looked_up_reference| looked_up_reference != material_reference);
+
+        let material_instance = self.slots.get_mut(material_reference.key)?;
+        material_instance
+            .data
+            .get_mut(..std::mem::size_of::<MaterialProperties>())?
+            .copy_from_slice(bytemuck::bytes_of(&material_properties));
+
+        Some(&material_instance.data)
+    }
+
     pub fn get_material_info(&self, material_reference: MaterialReference) -> MaterialInfo {
         let material_instance = self.slots.get(material_reference.key).unwrap();
 
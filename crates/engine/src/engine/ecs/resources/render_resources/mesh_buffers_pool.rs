@@ -1,9 +1,39 @@
 use bevy_ecs::resource::Resource;
+use math::Vec3;
 use shared::MeshBufferKey;
 use slotmap::{Key, SlotMap};
 use vulkanite::vk::DeviceAddress;
 
-use crate::engine::ecs::{buffers_pool::BufferReference, components::mesh::MeshData};
+use crate::engine::{
+    ecs::{buffers_pool::BufferReference, components::mesh::MeshData},
+    resources::{Vertex, VertexFormat, mesh_registry::MeshRegistry},
+};
+
+/// Efficiency of the optimized mesh, as analyzed by meshopt at the import.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct MeshStatistics {
+    /// Average cache miss ratio, transformed vertices per triangle, `0.5` is close to the best.
+    pub acmr: f32,
+    /// Average transformed vertex ratio, transformed vertices per vertex, `1.0` is the best.
+    pub atvr: f32,
+    /// Shaded pixels per covered pixel from several directions, `1.0` is the best.
+    pub overdraw: f32,
+    /// Fetched bytes per byte of the vertices, `1.0` is the best.
+    pub overfetch: f32,
+}
+
+impl MeshStatistics {
+    const MAX_ACMR: f32 = 1.5;
+    const MAX_OVERDRAW: f32 = 3.0;
+    const MAX_OVERFETCH: f32 = 2.0;
+
+    /// Far from the expected values even after the optimization, the content should be fixed.
+    pub fn is_pathological(&self) -> bool {
+        self.acmr > Self::MAX_ACMR
+            || self.overdraw > Self::MAX_OVERDRAW
+            || self.overfetch > Self::MAX_OVERFETCH
+    }
+}
 
 pub struct MeshBuffer {
     pub mesh_object_device_address: DeviceAddress,
@@ -12,10 +42,31 @@ pub struct MeshBuffer {
     pub meshlets_buffer_reference: BufferReference,
     pub local_indices_buffer_reference: BufferReference,
     pub meshlets_count: usize,
+    /// Bounding sphere in the object space of the mesh.
+    pub bounding_center: Vec3,
+    pub bounding_radius: f32,
+    pub vertex_format: VertexFormat,
+    /// Quantization of compressed positions, identity for full vertices.
+    pub position_offset: Vec3,
+    pub position_scale: Vec3,
+    /// Full vertices are kept on the CPU regardless of the format on the GPU.
     pub mesh_data: MeshData,
+    pub mesh_statistics: MeshStatistics,
+    pub references_count: usize,
+}
+
+impl MeshBuffer {
+    pub fn get_buffer_references(&self) -> [BufferReference; 4] {
+        [
+            self.vertex_buffer_reference,
+            self.vertex_indices_buffer_reference,
+            self.meshlets_buffer_reference,
+            self.local_indices_buffer_reference,
+        ]
+    }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct MeshBufferReference {
     key: MeshBufferKey,
 }
@@ -29,21 +80,51 @@ impl MeshBufferReference {
 #[derive(Resource)]
 pub struct MeshBuffersPool {
     slots: SlotMap<MeshBufferKey, MeshBuffer>,
+    mesh_registry: MeshRegistry,
 }
 
 impl MeshBuffersPool {
     pub fn new(pre_allocated_count: usize) -> Self {
         Self {
             slots: SlotMap::with_capacity_and_key(pre_allocated_count),
+            mesh_registry: Default::default(),
         }
     }
 
     pub fn insert_mesh_buffer(&mut self, mesh_buffer: MeshBuffer) -> MeshBufferReference {
+        let content_hash = MeshRegistry::get_content_hash(
+            &mesh_buffer.mesh_data.vertices,
+            &mesh_buffer.mesh_data.indices,
+            mesh_buffer.vertex_format,
+        );
         let mesh_buffer_key = self.slots.insert(mesh_buffer);
-
-        MeshBufferReference {
+        let mesh_buffer_reference = MeshBufferReference {
             key: mesh_buffer_key,
-        }
+        };
+        self.mesh_registry
+            .insert(content_hash, mesh_buffer_reference);
+
+        mesh_buffer_reference
+    }
+
+    /// Returns the inserted mesh buffer with the same geometry, so it's reused instead of uploaded again.
+    pub fn find_mesh_buffer(
+        &self,
+        vertices: &[Vertex],
+        indices: &[u32],
+        vertex_format: VertexFormat,
+    ) -> Option<MeshBufferReference> {
+        let content_hash = MeshRegistry::get_content_hash(vertices, indices, vertex_format);
+        let mesh_buffer_reference = self.mesh_registry.get(content_hash)?;
+        let mesh_buffer = self.slots.get(mesh_buffer_reference.key)?;
+
+        // NOTE: Hash collisions are possible, so the content is compared too.
+        let is_same_content = mesh_buffer.vertex_format == vertex_format
+            && mesh_buffer.mesh_data.indices == indices
+            && bytemuck::cast_slice::<_, u8>(&mesh_buffer.mesh_data.vertices)
+                == bytemuck::cast_slice::<_, u8>(vertices);
+
+        is_same_content.then_some(mesh_buffer_reference)
     }
 
     pub fn get_mesh_buffer(
@@ -59,4 +140,41 @@ impl MeshBuffersPool {
     ) -> Option<&mut MeshBuffer> {
         self.slots.get_mut(mesh_buffer_reference.key)
     }
+
+    pub fn get_mesh_buffers(&self) -> impl Iterator<Item = (MeshBufferReference, &MeshBuffer)> {
+        self.slots.iter().map(|(mesh_buffer_key, mesh_buffer)| {
+            let mesh_buffer_reference = MeshBufferReference {
+                key: mesh_buffer_key,
+            };
+
+            (mesh_buffer_reference, mesh_buffer)
+        })
+    }
+
+    pub fn retain_mesh_buffer(&mut self, mesh_buffer_reference: MeshBufferReference) {
+        if let Some(mesh_buffer) = self.slots.get_mut(mesh_buffer_reference.key) {
+            mesh_buffer.references_count += 1;
+        }
+    }
+
+    /// Returns `true` if it was the last reference and the mesh buffer can be removed.
+    pub fn release_mesh_buffer(&mut self, mesh_buffer_reference: MeshBufferReference) -> bool {
+        match self.slots.get_mut(mesh_buffer_reference.key) {
+            Some(mesh_buffer) => {
+                mesh_buffer.references_count = mesh_buffer.references_count.saturating_sub(1);
+
+                mesh_buffer.references_count == Default::default()
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove_mesh_buffer(
+        &mut self,
+        mesh_buffer_reference: MeshBufferReference,
+    ) -> Option<MeshBuffer> {
+        self.mesh_registry.remove(mesh_buffer_reference);
+
+        self.slots.remove(mesh_buffer_reference.key)
+    }
 }
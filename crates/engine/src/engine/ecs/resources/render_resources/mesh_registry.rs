@@ -0,0 +1,51 @@
+use std::hash::{Hash, Hasher};
+
+use ahash::{AHashMap, AHasher};
+
+use crate::engine::resources::{Vertex, VertexFormat, mesh_buffers_pool::MeshBufferReference};
+
+/// Mesh buffers keyed by the content hash of their geometry, so identical meshes of different loads
+/// share the same buffers on the GPU.
+#[derive(Default)]
+pub struct MeshRegistry {
+    references: AHashMap<u64, MeshBufferReference>,
+    hashes: AHashMap<MeshBufferReference, u64>,
+}
+
+impl MeshRegistry {
+    pub fn get_content_hash(
+        vertices: &[Vertex],
+        indices: &[u32],
+        vertex_format: VertexFormat,
+    ) -> u64 {
+        let mut hasher = AHasher::default();
+        bytemuck::cast_slice::<_, u8>(vertices).hash(&mut hasher);
+        indices.hash(&mut hasher);
+        (vertex_format as u32).hash(&mut hasher);
+
+        hasher.finish()
+    }
+
+    #[inline(always)]
+    pub fn get(&self, content_hash: u64) -> Option<MeshBufferReference> {
+        self.references.get(&content_hash).copied()
+    }
+
+    /// First registered mesh buffer of the content is kept.
+    pub fn insert(&mut self, content_hash: u64, mesh_buffer_reference: MeshBufferReference) {
+        self.references
+            .entry(content_hash)
+            .or_insert(mesh_buffer_reference);
+        self.hashes.insert(mesh_buffer_reference, content_hash);
+    }
+
+    pub fn remove(&mut self, mesh_buffer_reference: MeshBufferReference) {
+        let Some(content_hash) = self.hashes.remove(&mesh_buffer_reference) else {
+            return;
+        };
+
+        if self.references.get(&content_hash) == Some(&mesh_buffer_reference) {
+            self.references.remove(&content_hash);
+        }
+    }
+}
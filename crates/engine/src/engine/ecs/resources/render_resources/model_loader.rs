@@ -1,5 +1,18 @@
+pub mod draco_mesh;
+pub mod gltf_document;
+
+use std::{
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
 use asset_importer::{Importer, Scene, postprocess::PostProcessSteps};
 
+use crate::engine::ecs::model_loader::gltf_document::GltfDocument;
+
+/// Cooker and runtime loads run at the same time, each decoded copy needs its own name.
+static DECODED_MODELS_COUNT: AtomicUsize = AtomicUsize::new(0);
+
 pub struct ModelLoader {
     importer: Importer,
 }
@@ -12,10 +25,42 @@ impl ModelLoader {
     }
 
     pub fn load_model(&self, path: &str) -> Scene {
-        self.importer
-            .read_file(path)
+        self.try_load_model(path).unwrap()
+    }
+
+    /// Meshopt and Draco compressed glTF is decoded to a temporary `.glb` first,
+    /// Assimp doesn't know them.
+    pub fn try_load_model(&self, path: &str) -> Result<Scene, String> {
+        let model_path = Path::new(path);
+        let decoded_model_path = match GltfDocument::try_read(model_path)? {
+            Some(gltf_document) if gltf_document.is_compressed() => {
+                let decoded_model_path = std::env::temp_dir().join(std::format!(
+                    "{}-{}-{}.glb",
+                    model_path.file_stem().unwrap().to_string_lossy(),
+                    std::process::id(),
+                    DECODED_MODELS_COUNT.fetch_add(1, Ordering::Relaxed)
+                ));
+                gltf_document.write_decoded(&decoded_model_path)?;
+
+                Some(decoded_model_path)
+            }
+            _ => None,
+        };
+
+        let import_path = decoded_model_path
+            .as_deref()
+            .map_or(path, |decoded_model_path| {
+                decoded_model_path.to_str().unwrap()
+            });
+        let scene = self
+            .importer
+            .read_file(import_path)
             .with_post_process(PostProcessSteps::MAX_QUALITY | PostProcessSteps::FLIP_UVS)
-            .import()
-            .unwrap()
+            .import();
+        if let Some(decoded_model_path) = decoded_model_path {
+            _ = std::fs::remove_file(decoded_model_path);
+        }
+
+        scene.map_err(|error| std::format!("{:?}", error))
     }
 }
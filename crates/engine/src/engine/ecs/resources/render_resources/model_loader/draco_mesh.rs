@@ -0,0 +1,115 @@
+#[cfg(feature = "draco")]
+mod ffi {
+    use std::ffi::c_void;
+
+    #[repr(C)]
+    pub struct Mesh {
+        _private: [u8; 0],
+    }
+
+    unsafe extern "C" {
+        pub fn draco_decode_mesh(data: *const u8, size: usize) -> *mut Mesh;
+        pub fn draco_free_mesh(mesh: *mut Mesh);
+        pub fn draco_get_points_count(mesh: *const Mesh) -> u32;
+        pub fn draco_get_faces_count(mesh: *const Mesh) -> u32;
+        pub fn draco_read_indices(mesh: *const Mesh, indices: *mut u32);
+        pub fn draco_read_attribute(
+            mesh: *const Mesh,
+            unique_id: u32,
+            component_type: u32,
+            components_count: u8,
+            values: *mut c_void,
+        ) -> bool;
+    }
+}
+
+/// Mesh decoded from a `KHR_draco_mesh_compression` buffer view.
+#[cfg(feature = "draco")]
+pub struct DracoMesh {
+    mesh: *mut ffi::Mesh,
+}
+
+#[cfg(feature = "draco")]
+impl DracoMesh {
+    pub fn decode(data: &[u8]) -> Result<Self, String> {
+        let mesh = unsafe { ffi::draco_decode_mesh(data.as_ptr(), data.len()) };
+        if mesh.is_null() {
+            return Err("Draco decoding failed".to_string());
+        }
+
+        Ok(Self { mesh })
+    }
+
+    pub fn get_points_count(&self) -> usize {
+        unsafe { ffi::draco_get_points_count(self.mesh) as usize }
+    }
+
+    pub fn get_indices(&self) -> Vec<u32> {
+        let faces_count = unsafe { ffi::draco_get_faces_count(self.mesh) } as usize;
+        let mut indices = vec![0; faces_count * 3];
+        unsafe { ffi::draco_read_indices(self.mesh, indices.as_mut_ptr()) };
+
+        indices
+    }
+
+    /// Values of the attribute with the unique ID, converted to the glTF component type.
+    pub fn get_attribute(
+        &self,
+        unique_id: u32,
+        component_type: u32,
+        components_count: usize,
+        component_size: usize,
+    ) -> Result<Vec<u8>, String> {
+        let mut values = vec![0u8; self.get_points_count() * components_count * component_size];
+        let is_read = unsafe {
+            ffi::draco_read_attribute(
+                self.mesh,
+                unique_id,
+                component_type,
+                components_count as u8,
+                values.as_mut_ptr().cast(),
+            )
+        };
+        if !is_read {
+            return Err(std::format!("Draco attribute {unique_id} can't be read"));
+        }
+
+        Ok(values)
+    }
+}
+
+#[cfg(feature = "draco")]
+impl Drop for DracoMesh {
+    fn drop(&mut self) {
+        unsafe { ffi::draco_free_mesh(self.mesh) };
+    }
+}
+
+/// Builds without the `draco` feature report Draco meshes instead of decoding them.
+#[cfg(not(feature = "draco"))]
+pub struct DracoMesh;
+
+#[cfg(not(feature = "draco"))]
+impl DracoMesh {
+    pub fn decode(_data: &[u8]) -> Result<Self, String> {
+        Err("Draco meshes need the engine built with the `draco` feature".to_string())
+    }
+
+    pub fn get_points_count(&self) -> usize {
+        Default::default()
+    }
+
+    pub fn get_indices(&self) -> Vec<u32> {
+        Vec::new()
+    }
+
+    pub fn get_attribute(
+        &self,
+        _unique_id: u32,
+        _component_type: u32,
+        _components_count: usize,
+        _component_size: usize,
+    ) -> Result<Vec<u8>, String> {
+        Ok(Vec::new())
+    }
+}
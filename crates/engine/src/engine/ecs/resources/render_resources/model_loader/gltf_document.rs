@@ -0,0 +1,808 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value, json};
+
+use crate::engine::ecs::model_loader::draco_mesh::DracoMesh;
+
+const GLB_MAGIC: u32 = 0x4654_6C67;
+const GLB_VERSION: u32 = 2;
+const GLB_HEADER_SIZE: usize = 12;
+const GLB_CHUNK_HEADER_SIZE: usize = 8;
+const GLB_JSON_CHUNK: u32 = 0x4E4F_534A;
+const GLB_BIN_CHUNK: u32 = 0x004E_4942;
+
+/// Both names are the same format, the KHR one is the ratified version of the EXT one.
+const MESHOPT_EXTENSIONS: [&str; 2] = ["EXT_meshopt_compression", "KHR_meshopt_compression"];
+pub const DRACO_EXTENSION: &str = "KHR_draco_mesh_compression";
+
+const BYTE: u64 = 5120;
+const UNSIGNED_BYTE: u64 = 5121;
+const SHORT: u64 = 5122;
+const UNSIGNED_SHORT: u64 = 5123;
+const UNSIGNED_INT: u64 = 5125;
+const FLOAT: u64 = 5126;
+
+/// JSON of a `.gltf` or `.glb` and its buffers, it's read only to decode what Assimp can't.
+pub struct GltfDocument {
+    json: Value,
+    bin_chunk: Option<Vec<u8>>,
+    directory: PathBuf,
+}
+
+impl GltfDocument {
+    /// `None` for models that aren't glTF.
+    pub fn try_read(model_path: &Path) -> Result<Option<Self>, String> {
+        let is_gltf = model_path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| matches!(extension.to_lowercase().as_str(), "glb" | "gltf"));
+        if !is_gltf {
+            return Ok(None);
+        }
+
+        let bytes = std::fs::read(model_path).map_err(|error| error.to_string())?;
+        let (json_bytes, bin_chunk) = if read_u32(&bytes, 0) == Some(GLB_MAGIC) {
+            read_glb_chunks(&bytes)?
+        } else {
+            (bytes.as_slice(), None)
+        };
+        let json = serde_json::from_slice(json_bytes).map_err(|error| error.to_string())?;
+
+        Ok(Some(Self {
+            json,
+            bin_chunk: bin_chunk.map(<[u8]>::to_vec),
+            directory: model_path.parent().unwrap_or(Path::new("")).to_path_buf(),
+        }))
+    }
+
+    pub fn uses_extension(&self, extension_name: &str) -> bool {
+        self.json["extensionsUsed"]
+            .as_array()
+            .is_some_and(|extensions| extensions.iter().any(|name| name == extension_name))
+    }
+
+    /// Meshopt or Draco, Assimp reads neither of them.
+    pub fn is_compressed(&self) -> bool {
+        MESHOPT_EXTENSIONS
+            .iter()
+            .chain([&DRACO_EXTENSION])
+            .any(|&extension_name| self.uses_extension(extension_name))
+    }
+
+    /// Writes a `.glb` with the buffer views and Draco primitives decoded and the external files
+    /// embedded, so the copy is read the same from any directory.
+    pub fn write_decoded(mut self, decoded_model_path: &Path) -> Result<(), String> {
+        let buffers = self.read_buffers()?;
+        let mut bin_chunk = Vec::new();
+
+        let buffer_views = self.json["bufferViews"]
+            .as_array_mut()
+            .map(std::mem::take)
+            .unwrap_or_default();
+        let mut decoded_buffer_views = Vec::with_capacity(buffer_views.len());
+        for (buffer_view_index, buffer_view) in buffer_views.into_iter().enumerate() {
+            let Value::Object(mut buffer_view) = buffer_view else {
+                return Err(std::format!(
+                    "Buffer view {buffer_view_index} isn't an object"
+                ));
+            };
+            let meshopt_compression = buffer_view
+                .get_mut("extensions")
+                .and_then(Value::as_object_mut)
+                .and_then(|extensions| {
+                    MESHOPT_EXTENSIONS
+                        .iter()
+                        .find_map(|&extension_name| extensions.remove(extension_name))
+                });
+            let data = match meshopt_compression {
+                Some(Value::Object(meshopt_compression)) => {
+                    let source = get_buffer_slice(&buffers, &meshopt_compression)?;
+
+                    decode_meshopt_buffer_view(source, &meshopt_compression)
+                        .map_err(|error| std::format!("Buffer view {buffer_view_index}: {error}"))?
+                }
+                Some(_) => {
+                    return Err(std::format!(
+                        "Buffer view {buffer_view_index} has invalid meshopt compression"
+                    ));
+                }
+                None => get_buffer_slice(&buffers, &buffer_view)?.to_vec(),
+            };
+            if buffer_view
+                .get("extensions")
+                .and_then(Value::as_object)
+                .is_some_and(Map::is_empty)
+            {
+                buffer_view.remove("extensions");
+            }
+
+            push_buffer_view(&mut bin_chunk, &mut buffer_view, &data);
+            decoded_buffer_views.push(Value::Object(buffer_view));
+        }
+        self.decode_draco_primitives(&mut bin_chunk, &mut decoded_buffer_views)?;
+
+        // NOTE: Assimp resolves textures relatively to the model, the decoded copy lives elsewhere.
+        if let Some(images) = self.json.get_mut("images").and_then(Value::as_array_mut) {
+            for image in images {
+                let Some(uri) = image["uri"]
+                    .as_str()
+                    .filter(|uri| !uri.starts_with("data:"))
+                else {
+                    continue;
+                };
+                let image_path = self.directory.join(decode_uri(uri));
+                let image_data = std::fs::read(&image_path)
+                    .map_err(|error| std::format!("{}: {error}", image_path.display()))?;
+                let mime_type = match image_path
+                    .extension()
+                    .and_then(|extension| extension.to_str())
+                    .map(str::to_lowercase)
+                    .as_deref()
+                {
+                    Some("jpg" | "jpeg") => "image/jpeg",
+                    Some("ktx2") => "image/ktx2",
+                    Some("webp") => "image/webp",
+                    _ => "image/png",
+                };
+
+                let mut buffer_view = Map::new();
+                push_buffer_view(&mut bin_chunk, &mut buffer_view, &image_data);
+                image["bufferView"] = json!(decoded_buffer_views.len());
+                image["mimeType"] = json!(mime_type);
+                image.as_object_mut().unwrap().remove("uri");
+                decoded_buffer_views.push(Value::Object(buffer_view));
+            }
+        }
+
+        self.json["bufferViews"] = Value::Array(decoded_buffer_views);
+        self.json["buffers"] = json!([{ "byteLength": bin_chunk.len() }]);
+        for extensions_key in ["extensionsUsed", "extensionsRequired"] {
+            if let Some(extensions) = self
+                .json
+                .get_mut(extensions_key)
+                .and_then(Value::as_array_mut)
+            {
+                extensions.retain(|name| {
+                    name.as_str().is_none_or(|name| {
+                        !MESHOPT_EXTENSIONS.contains(&name) && name != DRACO_EXTENSION
+                    })
+                });
+            }
+        }
+
+        let json_chunk = serde_json::to_vec(&self.json).map_err(|error| error.to_string())?;
+        std::fs::write(decoded_model_path, get_glb(json_chunk, bin_chunk))
+            .map_err(|error| std::format!("{}: {error}", decoded_model_path.display()))
+    }
+
+    /// Draco primitives get plain accessors, each one with its own buffer view.
+    fn decode_draco_primitives(
+        &mut self,
+        bin_chunk: &mut Vec<u8>,
+        buffer_views: &mut Vec<Value>,
+    ) -> Result<(), String> {
+        if !self.uses_extension(DRACO_EXTENSION) {
+            return Ok(());
+        }
+
+        let mut accessors = self
+            .json
+            .get_mut("accessors")
+            .and_then(Value::as_array_mut)
+            .map(std::mem::take)
+            .unwrap_or_default();
+        let primitives = self
+            .json
+            .get_mut("meshes")
+            .and_then(Value::as_array_mut)
+            .into_iter()
+            .flatten()
+            .filter_map(|mesh| mesh.get_mut("primitives").and_then(Value::as_array_mut))
+            .flatten();
+        for primitive in primitives {
+            let Some(Value::Object(draco_compression)) = primitive
+                .get_mut("extensions")
+                .and_then(Value::as_object_mut)
+                .and_then(|extensions| extensions.remove(DRACO_EXTENSION))
+            else {
+                continue;
+            };
+            if primitive["extensions"]
+                .as_object()
+                .is_some_and(Map::is_empty)
+            {
+                primitive.as_object_mut().unwrap().remove("extensions");
+            }
+
+            let draco_mesh = draco_compression
+                .get("bufferView")
+                .and_then(Value::as_u64)
+                .and_then(|buffer_view_index| buffer_views.get(buffer_view_index as usize))
+                .and_then(Value::as_object)
+                .ok_or_else(|| "Draco compression has no buffer view".to_string())
+                .and_then(|buffer_view| {
+                    get_buffer_slice(std::slice::from_ref(bin_chunk), buffer_view)
+                })
+                .and_then(DracoMesh::decode)?;
+
+            let draco_attributes = draco_compression
+                .get("attributes")
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten();
+            for (attribute_name, unique_id) in draco_attributes {
+                let accessor =
+                    get_accessor(&mut accessors, &primitive["attributes"][attribute_name])?;
+                let component_type = get_component_type(accessor)?;
+                let components_count = match accessor.get("type").and_then(Value::as_str) {
+                    Some("SCALAR") => 1,
+                    Some("VEC2") => 2,
+                    Some("VEC3") => 3,
+                    Some("VEC4") => 4,
+                    _ => {
+                        return Err(std::format!(
+                            "Draco attribute {attribute_name} has invalid type"
+                        ));
+                    }
+                };
+                let unique_id = unique_id
+                    .as_u64()
+                    .ok_or_else(|| std::format!("Draco attribute {attribute_name} has no ID"))?;
+
+                let data = draco_mesh.get_attribute(
+                    unique_id as u32,
+                    component_type as u32,
+                    components_count,
+                    get_component_size(component_type)?,
+                )?;
+                push_accessor_data(
+                    bin_chunk,
+                    buffer_views,
+                    accessor,
+                    &data,
+                    draco_mesh.get_points_count(),
+                );
+            }
+
+            if let Some(indices) = primitive.get("indices") {
+                let accessor = get_accessor(&mut accessors, indices)?;
+                let indices = draco_mesh.get_indices();
+                let data = match get_component_type(accessor)? {
+                    UNSIGNED_BYTE => indices.iter().map(|&index| index as u8).collect(),
+                    UNSIGNED_SHORT => indices
+                        .iter()
+                        .flat_map(|&index| (index as u16).to_le_bytes())
+                        .collect(),
+                    _ => bytemuck::cast_slice(&indices).to_vec(),
+                };
+                push_accessor_data(bin_chunk, buffer_views, accessor, &data, indices.len());
+            }
+        }
+
+        self.json["accessors"] = Value::Array(accessors);
+
+        Ok(())
+    }
+
+    fn read_buffers(&mut self) -> Result<Vec<Vec<u8>>, String> {
+        let Some(buffers) = self.json["buffers"].as_array() else {
+            return Ok(Vec::new());
+        };
+
+        buffers
+            .iter()
+            .enumerate()
+            .map(|(buffer_index, buffer)| match buffer["uri"].as_str() {
+                Some(uri) => match uri.strip_prefix("data:") {
+                    Some(data_uri) => data_uri
+                        .split_once(";base64,")
+                        .and_then(|(_, data)| decode_base64(data))
+                        .ok_or_else(|| std::format!("Buffer {buffer_index} has invalid data URI")),
+                    None => {
+                        let buffer_path = self.directory.join(decode_uri(uri));
+
+                        std::fs::read(&buffer_path)
+                            .map_err(|error| std::format!("{}: {error}", buffer_path.display()))
+                    }
+                },
+                // NOTE: Only the first buffer can be the BIN chunk, the others without URI
+                // are meshopt fallbacks, nothing reads them.
+                None if buffer_index == 0 && self.bin_chunk.is_some() => {
+                    Ok(self.bin_chunk.take().unwrap())
+                }
+                None => Ok(Vec::new()),
+            })
+            .collect()
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    bytes
+        .get(offset..offset + size_of::<u32>())
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_glb_chunks(bytes: &[u8]) -> Result<(&[u8], Option<&[u8]>), String> {
+    if read_u32(bytes, 4) != Some(GLB_VERSION) {
+        return Err("Only GLB of version 2 is supported".to_string());
+    }
+
+    let mut json_chunk = None;
+    let mut bin_chunk = None;
+    let mut chunk_offset = GLB_HEADER_SIZE;
+    while let (Some(chunk_size), Some(chunk_type)) = (
+        read_u32(bytes, chunk_offset),
+        read_u32(bytes, chunk_offset + size_of::<u32>()),
+    ) {
+        let data_offset = chunk_offset + GLB_CHUNK_HEADER_SIZE;
+        let chunk = bytes
+            .get(data_offset..data_offset + chunk_size as usize)
+            .ok_or_else(|| "GLB chunk is out of the file".to_string())?;
+        match chunk_type {
+            GLB_JSON_CHUNK => json_chunk = json_chunk.or(Some(chunk)),
+            GLB_BIN_CHUNK => bin_chunk = bin_chunk.or(Some(chunk)),
+            _ => (),
+        }
+
+        chunk_offset = data_offset + chunk_size as usize;
+    }
+
+    json_chunk
+        .map(|json_chunk| (json_chunk, bin_chunk))
+        .ok_or_else(|| "GLB has no JSON chunk".to_string())
+}
+
+fn get_glb(mut json_chunk: Vec<u8>, mut bin_chunk: Vec<u8>) -> Vec<u8> {
+    json_chunk.resize(json_chunk.len().next_multiple_of(4), b' ');
+    bin_chunk.resize(bin_chunk.len().next_multiple_of(4), Default::default());
+
+    let glb_size = GLB_HEADER_SIZE + 2 * GLB_CHUNK_HEADER_SIZE + json_chunk.len() + bin_chunk.len();
+    let mut glb = Vec::with_capacity(glb_size);
+    for value in [GLB_MAGIC, GLB_VERSION, glb_size as u32] {
+        glb.extend_from_slice(&value.to_le_bytes());
+    }
+    for (chunk_type, chunk) in [(GLB_JSON_CHUNK, json_chunk), (GLB_BIN_CHUNK, bin_chunk)] {
+        glb.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+        glb.extend_from_slice(&chunk_type.to_le_bytes());
+        glb.extend_from_slice(&chunk);
+    }
+
+    glb
+}
+
+/// Slice of `buffer`, `byteOffset` and `byteLength`, both buffer views and their meshopt
+/// extensions address data this way.
+fn get_buffer_slice<'a>(
+    buffers: &'a [Vec<u8>],
+    buffer_range: &Map<String, Value>,
+) -> Result<&'a [u8], String> {
+    let buffer_index = buffer_range.get("buffer").and_then(Value::as_u64);
+    let byte_offset = buffer_range
+        .get("byteOffset")
+        .and_then(Value::as_u64)
+        .unwrap_or_default() as usize;
+    let byte_length = buffer_range
+        .get("byteLength")
+        .and_then(Value::as_u64)
+        .unwrap_or_default() as usize;
+
+    buffer_index
+        .and_then(|buffer_index| buffers.get(buffer_index as usize))
+        .and_then(|buffer| buffer.get(byte_offset..byte_offset + byte_length))
+        .ok_or_else(|| "Buffer view is out of its buffer".to_string())
+}
+
+/// Appends the data to the BIN chunk and points the buffer view at it.
+fn push_buffer_view(bin_chunk: &mut Vec<u8>, buffer_view: &mut Map<String, Value>, data: &[u8]) {
+    // NOTE: Four bytes is the biggest component, accessors stay aligned.
+    bin_chunk.resize(bin_chunk.len().next_multiple_of(4), Default::default());
+
+    buffer_view.insert("buffer".to_string(), json!(0));
+    buffer_view.insert("byteOffset".to_string(), json!(bin_chunk.len()));
+    buffer_view.insert("byteLength".to_string(), json!(data.len()));
+    bin_chunk.extend_from_slice(data);
+}
+
+fn decode_meshopt_buffer_view(
+    source: &[u8],
+    meshopt_compression: &Map<String, Value>,
+) -> Result<Vec<u8>, String> {
+    let get_usize = |key: &str| {
+        meshopt_compression
+            .get(key)
+            .and_then(Value::as_u64)
+            .map(|value| value as usize)
+            .ok_or_else(|| std::format!("Meshopt compression has no {key}"))
+    };
+    let count = get_usize("count")?;
+    let byte_stride = get_usize("byteStride")?;
+    let mode = meshopt_compression
+        .get("mode")
+        .and_then(Value::as_str)
+        .unwrap_or("ATTRIBUTES");
+    let filter = meshopt_compression
+        .get("filter")
+        .and_then(Value::as_str)
+        .unwrap_or("NONE");
+
+    let mut decoded = vec![0u8; count * byte_stride];
+    let destination = decoded.as_mut_ptr().cast();
+    let result = unsafe {
+        match mode {
+            "ATTRIBUTES" => meshopt::ffi::meshopt_decodeVertexBuffer(
+                destination,
+                count,
+                byte_stride,
+                source.as_ptr(),
+                source.len(),
+            ),
+            "TRIANGLES" => meshopt::ffi::meshopt_decodeIndexBuffer(
+                destination,
+                count,
+                byte_stride,
+                source.as_ptr(),
+                source.len(),
+            ),
+            "INDICES" => meshopt::ffi::meshopt_decodeIndexSequence(
+                destination,
+                count,
+                byte_stride,
+                source.as_ptr(),
+                source.len(),
+            ),
+            _ => return Err(std::format!("Unknown meshopt mode {mode}")),
+        }
+    };
+    if result != Default::default() {
+        return Err(std::format!("Meshopt decoding failed with {result}"));
+    }
+
+    unsafe {
+        match filter {
+            "NONE" => (),
+            "OCTAHEDRAL" => meshopt::ffi::meshopt_decodeFilterOct(destination, count, byte_stride),
+            "QUATERNION" => meshopt::ffi::meshopt_decodeFilterQuat(destination, count, byte_stride),
+            "EXPONENTIAL" => meshopt::ffi::meshopt_decodeFilterExp(destination, count, byte_stride),
+            _ => return Err(std::format!("Unknown meshopt filter {filter}")),
+        }
+    }
+
+    Ok(decoded)
+}
+
+fn get_accessor<'a>(
+    accessors: &'a mut [Value],
+    accessor_index: &Value,
+) -> Result<&'a mut Map<String, Value>, String> {
+    accessor_index
+        .as_u64()
+        .and_then(|accessor_index| accessors.get_mut(accessor_index as usize))
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| std::format!("Accessor {accessor_index} doesn't exist"))
+}
+
+fn get_component_type(accessor: &Map<String, Value>) -> Result<u64, String> {
+    accessor
+        .get("componentType")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| "Accessor has no component type".to_string())
+}
+
+fn get_component_size(component_type: u64) -> Result<usize, String> {
+    match component_type {
+        BYTE | UNSIGNED_BYTE => Ok(1),
+        SHORT | UNSIGNED_SHORT => Ok(2),
+        UNSIGNED_INT | FLOAT => Ok(4),
+        _ => Err(std::format!("Unknown component type {component_type}")),
+    }
+}
+
+/// Points the accessor at the data in a new buffer view, `count` follows the decoded mesh.
+fn push_accessor_data(
+    bin_chunk: &mut Vec<u8>,
+    buffer_views: &mut Vec<Value>,
+    accessor: &mut Map<String, Value>,
+    data: &[u8],
+    count: usize,
+) {
+    let mut buffer_view = Map::new();
+    push_buffer_view(bin_chunk, &mut buffer_view, data);
+    accessor.insert("bufferView".to_string(), json!(buffer_views.len()));
+    accessor.insert("count".to_string(), json!(count));
+    accessor.remove("byteOffset");
+    buffer_views.push(Value::Object(buffer_view));
+}
+
+/// Relative URIs are percent encoded, spaces in file names are the usual case.
+fn decode_uri(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut byte_index = 0;
+    while byte_index < bytes.len() {
+        let escaped_byte = (bytes[byte_index] == b'%')
+            .then(|| uri.get(byte_index + 1..byte_index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped_byte {
+            Some(escaped_byte) => {
+                decoded.push(escaped_byte);
+                byte_index += 3;
+            }
+            None => {
+                decoded.push(bytes[byte_index]);
+                byte_index += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn decode_base64(data: &str) -> Option<Vec<u8>> {
+    let get_sextet = |byte| match byte {
+        b'A'..=b'Z' => Some(byte - b'A'),
+        b'a'..=b'z' => Some(byte - b'a' + 26),
+        b'0'..=b'9' => Some(byte - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    };
+
+    let data = data.trim_end_matches('=').as_bytes();
+    let mut decoded = Vec::with_capacity(data.len() * 3 / 4);
+    let mut bits = 0u32;
+    let mut bits_count = 0;
+    for &byte in data {
+        bits = (bits << 6) | get_sextet(byte)? as u32;
+        bits_count += 6;
+        if bits_count >= 8 {
+            bits_count -= 8;
+            decoded.push((bits >> bits_count) as u8);
+        }
+    }
+
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use serde_json::{Map, Value, json};
+
+    use super::{
+        GLB_BIN_CHUNK, GLB_HEADER_SIZE, GltfDocument, decode_base64, decode_meshopt_buffer_view,
+        decode_uri, get_glb, read_glb_chunks,
+    };
+
+    const BASE64_ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    fn encode_base64(data: &[u8]) -> String {
+        data.chunks(3)
+            .flat_map(|chunk| {
+                let bits = chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0u32, |bits, (byte_index, &byte)| {
+                        bits | ((byte as u32) << (16 - 8 * byte_index))
+                    });
+
+                (0..=chunk.len()).map(move |sextet_index| {
+                    BASE64_ALPHABET[(bits >> (18 - 6 * sextet_index)) as usize & 63] as char
+                })
+            })
+            .collect()
+    }
+
+    fn encode_meshopt_vertices(vertices: &[[f32; 3]]) -> Vec<u8> {
+        let vertex_size = size_of::<[f32; 3]>();
+        let encoded_bound =
+            unsafe { meshopt::ffi::meshopt_encodeVertexBufferBound(vertices.len(), vertex_size) };
+        let mut encoded = vec![0u8; encoded_bound];
+        let encoded_size = unsafe {
+            meshopt::ffi::meshopt_encodeVertexBuffer(
+                encoded.as_mut_ptr(),
+                encoded.len(),
+                vertices.as_ptr().cast(),
+                vertices.len(),
+                vertex_size,
+            )
+        };
+        encoded.truncate(encoded_size);
+
+        encoded
+    }
+
+    fn get_meshopt_compression(mode: &str, count: usize, byte_stride: usize) -> Map<String, Value> {
+        json!({ "mode": mode, "count": count, "byteStride": byte_stride })
+            .as_object()
+            .unwrap()
+            .clone()
+    }
+
+    fn get_temp_path(file_name: &str) -> PathBuf {
+        std::env::temp_dir().join(std::format!("{}-{file_name}", std::process::id()))
+    }
+
+    #[test]
+    fn glb_chunks_round_trip() {
+        let glb = get_glb(br#"{"asset":{}}"#.to_vec(), vec![1, 2, 3]);
+
+        let (json_chunk, bin_chunk) = read_glb_chunks(&glb).unwrap();
+
+        // NOTE: Chunks are padded to four bytes, JSON with spaces and BIN with zeros.
+        assert_eq!(json_chunk, br#"{"asset":{}}"#);
+        assert_eq!(bin_chunk, Some([1, 2, 3, 0].as_slice()));
+    }
+
+    #[test]
+    fn glb_chunks_reject_invalid_files() {
+        let glb = get_glb(b"{}".to_vec(), vec![1, 2, 3, 4]);
+
+        let mut wrong_version = glb.clone();
+        wrong_version[4] = 1;
+        assert!(read_glb_chunks(&wrong_version).is_err());
+
+        let truncated = &glb[..glb.len() - 2];
+        assert!(read_glb_chunks(truncated).is_err());
+
+        let mut without_json = glb[..GLB_HEADER_SIZE].to_vec();
+        without_json.extend_from_slice(&4u32.to_le_bytes());
+        without_json.extend_from_slice(&GLB_BIN_CHUNK.to_le_bytes());
+        without_json.extend_from_slice(&[0; 4]);
+        assert!(read_glb_chunks(&without_json).is_err());
+    }
+
+    #[test]
+    fn base64_decodes_with_and_without_padding() {
+        assert_eq!(decode_base64("SGVsbG8="), Some(b"Hello".to_vec()));
+        assert_eq!(decode_base64("SGVsbG8"), Some(b"Hello".to_vec()));
+        assert_eq!(decode_base64("AAEC/w=="), Some(vec![0, 1, 2, 255]));
+        assert_eq!(decode_base64(""), Some(Vec::new()));
+        assert_eq!(decode_base64("SGV*bG8="), None);
+
+        let data = (0..=255).collect::<Vec<u8>>();
+        assert_eq!(decode_base64(&encode_base64(&data)), Some(data));
+    }
+
+    #[test]
+    fn uri_percent_escapes_are_decoded() {
+        assert_eq!(decode_uri("My%20Model.bin"), "My Model.bin");
+        assert_eq!(decode_uri("textures/%C3%A9t%C3%A9.png"), "textures/été.png");
+        // NOTE: Broken escapes are kept as they are.
+        assert_eq!(decode_uri("100%.bin"), "100%.bin");
+        assert_eq!(decode_uri("a%zzb%2"), "a%zzb%2");
+    }
+
+    #[test]
+    fn data_uri_buffers_are_read() {
+        let model_path = get_temp_path("data_uri_buffers_are_read.gltf");
+        let gltf = json!({
+            "asset": { "version": "2.0" },
+            "buffers": [
+                { "byteLength": 4, "uri": "data:application/octet-stream;base64,AAEC/w==" },
+                { "byteLength": 1, "uri": "data:application/octet-stream;base64,*" },
+            ],
+        });
+        std::fs::write(&model_path, gltf.to_string()).unwrap();
+
+        let mut gltf_document = GltfDocument::try_read(&model_path).unwrap().unwrap();
+        _ = std::fs::remove_file(&model_path);
+        assert!(gltf_document.read_buffers().is_err());
+
+        gltf_document.json["buffers"].as_array_mut().unwrap().pop();
+        assert_eq!(gltf_document.read_buffers().unwrap(), [vec![0, 1, 2, 255]]);
+    }
+
+    #[test]
+    fn meshopt_buffer_views_are_decoded() {
+        let vertices = [[0.0, 1.0, 2.0], [-3.5, 4.25, 0.5], [8.0, -1.0, 16.0]];
+        let encoded_vertices = encode_meshopt_vertices(&vertices);
+        let decoded_vertices = decode_meshopt_buffer_view(
+            &encoded_vertices,
+            &get_meshopt_compression("ATTRIBUTES", vertices.len(), size_of::<[f32; 3]>()),
+        )
+        .unwrap();
+        assert_eq!(
+            decoded_vertices,
+            bytemuck::cast_slice::<f32, u8>(vertices.as_flattened())
+        );
+
+        let indices = [0u32, 1, 2, 2, 1, 0];
+        let encoded_bound =
+            unsafe { meshopt::ffi::meshopt_encodeIndexBufferBound(indices.len(), 3) };
+        let mut encoded_indices = vec![0u8; encoded_bound];
+        let encoded_size = unsafe {
+            meshopt::ffi::meshopt_encodeIndexBuffer(
+                encoded_indices.as_mut_ptr(),
+                encoded_indices.len(),
+                indices.as_ptr(),
+                indices.len(),
+            )
+        };
+        encoded_indices.truncate(encoded_size);
+        let decoded_indices = decode_meshopt_buffer_view(
+            &encoded_indices,
+            &get_meshopt_compression("TRIANGLES", indices.len(), size_of::<u32>()),
+        )
+        .unwrap();
+        assert_eq!(decoded_indices, bytemuck::cast_slice::<u32, u8>(&indices));
+
+        assert!(
+            decode_meshopt_buffer_view(
+                &encoded_vertices,
+                &get_meshopt_compression("POINTS", vertices.len(), size_of::<[f32; 3]>()),
+            )
+            .is_err()
+        );
+        assert!(
+            decode_meshopt_buffer_view(
+                &encoded_vertices[..encoded_vertices.len() / 2],
+                &get_meshopt_compression("ATTRIBUTES", vertices.len(), size_of::<[f32; 3]>()),
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn decoded_copy_has_plain_buffer_views() {
+        let vertices = [[1.0f32, 2.0, 3.0], [4.0, 5.0, 6.0]];
+        let encoded_vertices = encode_meshopt_vertices(&vertices);
+        let model_path = get_temp_path("decoded_copy_has_plain_buffer_views.gltf");
+        let decoded_model_path = get_temp_path("decoded_copy_has_plain_buffer_views.glb");
+        let gltf = json!({
+            "asset": { "version": "2.0" },
+            "extensionsUsed": ["EXT_meshopt_compression"],
+            "extensionsRequired": ["EXT_meshopt_compression"],
+            "buffers": [
+                {
+                    "byteLength": encoded_vertices.len(),
+                    "uri": std::format!(
+                        "data:application/octet-stream;base64,{}",
+                        encode_base64(&encoded_vertices)
+                    ),
+                },
+                { "byteLength": size_of_val(&vertices) },
+            ],
+            "bufferViews": [{
+                "buffer": 1,
+                "byteLength": size_of_val(&vertices),
+                "byteStride": size_of::<[f32; 3]>(),
+                "extensions": {
+                    "EXT_meshopt_compression": {
+                        "buffer": 0,
+                        "byteLength": encoded_vertices.len(),
+                        "byteStride": size_of::<[f32; 3]>(),
+                        "count": vertices.len(),
+                        "mode": "ATTRIBUTES",
+                    },
+                },
+            }],
+        });
+        std::fs::write(&model_path, gltf.to_string()).unwrap();
+
+        let gltf_document = GltfDocument::try_read(&model_path).unwrap().unwrap();
+        _ = std::fs::remove_file(&model_path);
+        assert!(gltf_document.is_compressed());
+        gltf_document.write_decoded(&decoded_model_path).unwrap();
+
+        let mut decoded_document = GltfDocument::try_read(&decoded_model_path)
+            .unwrap()
+            .unwrap();
+        _ = std::fs::remove_file(&decoded_model_path);
+        assert!(!decoded_document.is_compressed());
+        assert_eq!(decoded_document.json["extensionsRequired"], json!([]));
+
+        let buffer_view = decoded_document.json["bufferViews"][0]
+            .as_object()
+            .unwrap()
+            .clone();
+        assert!(!buffer_view.contains_key("extensions"));
+        let buffers = decoded_document.read_buffers().unwrap();
+        assert_eq!(
+            super::get_buffer_slice(&buffers, &buffer_view).unwrap(),
+            bytemuck::cast_slice::<f32, u8>(vertices.as_flattened())
+        );
+    }
+}
@@ -5,9 +5,10 @@ use rapier3d::{
     prelude::{
         CCDSolver, ColliderBuilder, ColliderSet, DefaultBroadPhase, ImpulseJointSet,
         IntegrationParameters, IslandManager, MultibodyJointSet, NarrowPhase, PhysicsPipeline,
-        RigidBodyBuilder, RigidBodyHandle, RigidBodySet,
+        QueryFilter, Ray, RigidBodyBuilder, RigidBodyHandle, RigidBodySet,
     },
 };
+use serde::{Deserialize, Serialize};
 
 use crate::engine::{LocalTransform, Physics, ecs::components::mesh::MeshData};
 
@@ -42,6 +43,15 @@ impl RigidBody {
     }
 }
 
+/// Pose and velocities of the rigid body in the world space.
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize)]
+pub struct RigidBodyState {
+    pub position: [f32; 3],
+    pub rotation: [f32; 4],
+    pub linear_velocity: [f32; 3],
+    pub angular_velocity: [f32; 3],
+}
+
 #[derive(Resource)]
 pub struct PhysicsManager {
     gravity: rapier3d::math::Vec3,
@@ -108,6 +118,74 @@ impl PhysicsManager {
         );
     }
 
+    pub fn get_rigid_body_state(&self, rigid_body: RigidBody) -> RigidBodyState {
+        let rigid_body = &self.rigid_body_set[rigid_body.rigid_body_handle];
+
+        RigidBodyState {
+            position: rigid_body.translation().to_array(),
+            rotation: rigid_body.rotation().to_array(),
+            linear_velocity: rigid_body.linvel().to_array(),
+            angular_velocity: rigid_body.angvel().to_array(),
+        }
+    }
+
+    pub fn set_rigid_body_state(
+        &mut self,
+        rigid_body: RigidBody,
+        rigid_body_state: RigidBodyState,
+    ) {
+        let Some(rigid_body) = self.rigid_body_set.get_mut(rigid_body.rigid_body_handle) else {
+            return;
+        };
+
+        rigid_body.set_translation(
+            rapier3d::math::Vec3::from_array(rigid_body_state.position),
+            true,
+        );
+        rigid_body.set_rotation(Quat::from_array(rigid_body_state.rotation), true);
+        rigid_body.set_linvel(
+            rapier3d::math::Vec3::from_array(rigid_body_state.linear_velocity),
+            true,
+        );
+        rigid_body.set_angvel(
+            rapier3d::math::Vec3::from_array(rigid_body_state.angular_velocity),
+            true,
+        );
+    }
+
+    /// Distance along the direction to the first collider, the excluded rigid body is skipped.
+    pub fn cast_ray(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        max_distance: f32,
+        excluded_rigid_body: Option<RigidBody>,
+    ) -> Option<f32> {
+        let direction = direction.normalize_or_zero();
+        if direction == Vec3::ZERO {
+            return None;
+        }
+
+        let mut query_filter = QueryFilter::default();
+        if let Some(excluded_rigid_body) = excluded_rigid_body {
+            query_filter = query_filter.exclude_rigid_body(excluded_rigid_body.rigid_body_handle);
+        }
+        let query_pipeline = self.broad_phase.as_query_pipeline(
+            self.narrow_phase.query_dispatcher(),
+            &self.rigid_body_set,
+            &self.collider_set,
+            query_filter,
+        );
+        let ray = Ray::new(
+            rapier3d::math::Vec3::from_array(origin.to_array()),
+            rapier3d::math::Vec3::from_array(direction.to_array()),
+        );
+
+        query_pipeline
+            .cast_ray(&ray, max_distance, true)
+            .map(|(_, distance)| distance)
+    }
+
     // TODO: Later accept Option RigidBody as parameter, for unified and easy to use API.
     pub fn create_convex_mesh_collider(
         &mut self,
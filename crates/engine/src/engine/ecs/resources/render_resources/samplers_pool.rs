@@ -17,17 +17,71 @@ impl SamplerReference {
     }
 }
 
+#[derive(Clone, Copy, PartialEq)]
+pub struct SamplerPreset {
+    pub mag_filter: Filter,
+    pub min_filter: Filter,
+    pub mipmap_mode: SamplerMipmapMode,
+    pub address_mode_u: SamplerAddressMode,
+    pub address_mode_v: SamplerAddressMode,
+    pub mip_map_enabled: bool,
+    pub anisotropy_enabled: bool,
+}
+
+impl SamplerPreset {
+    pub const DEFAULT: SamplerPreset = SamplerPreset {
+        mag_filter: Filter::Linear,
+        min_filter: Filter::Linear,
+        mipmap_mode: SamplerMipmapMode::Linear,
+        address_mode_u: SamplerAddressMode::Repeat,
+        address_mode_v: SamplerAddressMode::Repeat,
+        mip_map_enabled: true,
+        anisotropy_enabled: true,
+    };
+
+    pub fn new(filter: Filter, wrap: SamplerAddressMode, mip_map_enabled: bool) -> Self {
+        let mipmap_mode = match filter {
+            Filter::Nearest => SamplerMipmapMode::Nearest,
+            Filter::Linear => SamplerMipmapMode::Linear,
+            _ => panic!("Unsupported filter mode: {:?}", filter),
+        };
+
+        Self {
+            mag_filter: filter,
+            min_filter: filter,
+            mipmap_mode,
+            address_mode_u: wrap,
+            address_mode_v: wrap,
+            mip_map_enabled,
+            anisotropy_enabled: mip_map_enabled && filter == Filter::Linear,
+        }
+    }
+}
+
+impl Default for SamplerPreset {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
 #[derive(Resource)]
 pub struct SamplersPool {
     device: Device,
+    max_sampler_anisotropy: f32,
     slots: SlotMap<SamplerKey, Sampler>,
+    presets: Vec<(SamplerPreset, SamplerReference)>,
 }
 
 impl SamplersPool {
-    pub fn new(device: Device) -> Self {
+    // NOTE: Must be in sync with the count of sampler descriptors in the descriptor set.
+    pub const MAX_SAMPLERS_COUNT: usize = 16;
+
+    pub fn new(device: Device, max_sampler_anisotropy: f32) -> Self {
         Self {
             device,
-            slots: SlotMap::with_capacity_and_key(16),
+            max_sampler_anisotropy,
+            slots: SlotMap::with_capacity_and_key(Self::MAX_SAMPLERS_COUNT),
+            presets: Vec::with_capacity(Self::MAX_SAMPLERS_COUNT),
         }
     }
 
@@ -37,42 +91,75 @@ impl SamplersPool {
         wrap: SamplerAddressMode,
         mip_map_enabled: bool,
     ) -> SamplerReference {
-        let mipmap_mode = if mip_map_enabled {
-            match filter {
-                Filter::Nearest => SamplerMipmapMode::Nearest,
-                Filter::Linear => SamplerMipmapMode::Linear,
-                _ => panic!("Unsupported filter mode: {:?}", filter),
-            }
-        } else {
-            SamplerMipmapMode::Nearest
-        };
+        let (sampler_reference, _) =
+            self.get_or_create_sampler(SamplerPreset::new(filter, wrap, mip_map_enabled));
+
+        sampler_reference
+    }
+
+    /// Returns the sampler, that matches the preset, and flag if it was just created,
+    /// in such case the caller is responsible for writing its descriptor.
+    pub fn get_or_create_sampler(&mut self, preset: SamplerPreset) -> (SamplerReference, bool) {
+        if let Some((_, sampler_reference)) = self
+            .presets
+            .iter()
+            .find(|(existing_preset, _)| *existing_preset == preset)
+        {
+            return (*sampler_reference, false);
+        }
+
+        if self.presets.len() >= Self::MAX_SAMPLERS_COUNT {
+            println!(
+                "Samplers pool is full ({} presets), falling back to the first preset.",
+                Self::MAX_SAMPLERS_COUNT
+            );
 
-        let compare_op = if mip_map_enabled {
+            return (self.presets[0].1, false);
+        }
+
+        let compare_op = if preset.mip_map_enabled {
             CompareOp::Always
         } else {
             CompareOp::Never
         };
 
-        let max_lod = if mip_map_enabled {
+        let max_lod = if preset.mip_map_enabled {
             LOD_CLAMP_NONE
         } else {
             Default::default()
         };
 
+        let mipmap_mode = if preset.mip_map_enabled {
+            preset.mipmap_mode
+        } else {
+            SamplerMipmapMode::Nearest
+        };
+
+        let anisotropy_enabled = preset.anisotropy_enabled && self.max_sampler_anisotropy > 1.0;
+
         let sampler_create_info = SamplerCreateInfo {
-            mag_filter: filter,
-            min_filter: filter,
+            mag_filter: preset.mag_filter,
+            min_filter: preset.min_filter,
             mipmap_mode,
-            address_mode_u: wrap,
-            address_mode_v: wrap,
-            address_mode_w: wrap,
+            address_mode_u: preset.address_mode_u,
+            address_mode_v: preset.address_mode_v,
+            address_mode_w: preset.address_mode_u,
+            anisotropy_enable: anisotropy_enabled.into(),
+            max_anisotropy: if anisotropy_enabled {
+                self.max_sampler_anisotropy
+            } else {
+                1.0
+            },
             compare_op,
             max_lod,
             ..Default::default()
         };
         let sampler = self.device.create_sampler(&sampler_create_info).unwrap();
 
-        self.insert_sampler(sampler)
+        let sampler_reference = self.insert_sampler(sampler);
+        self.presets.push((preset, sampler_reference));
+
+        (sampler_reference, true)
     }
 
     fn insert_sampler(&mut self, sampler: Sampler) -> SamplerReference {
@@ -86,6 +173,7 @@ impl SamplersPool {
     }
 
     pub fn destroy_samplers(&mut self) {
+        self.presets.clear();
         self.slots.drain().for_each(|(_, sampler)| unsafe {
             self.device.destroy_sampler(Some(sampler));
         });
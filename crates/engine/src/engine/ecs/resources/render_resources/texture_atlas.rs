@@ -0,0 +1,197 @@
+use image::RgbaImage;
+use math::{Vec2, Vec4};
+
+/// Part of a texture in UV space, the whole texture by default.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UvRect {
+    pub offset: Vec2,
+    pub size: Vec2,
+}
+
+impl Default for UvRect {
+    fn default() -> Self {
+        Self {
+            offset: Vec2::ZERO,
+            size: Vec2::ONE,
+        }
+    }
+}
+
+impl UvRect {
+    /// Maps UV of the sub texture to UV of the whole one.
+    #[inline(always)]
+    pub fn transform(&self, uv: Vec2) -> Vec2 {
+        self.offset + uv * self.size
+    }
+
+    /// Offset in `xy` and size in `zw`, as shaders take it.
+    #[inline(always)]
+    pub fn to_vec4(&self) -> Vec4 {
+        Vec4::new(self.offset.x, self.offset.y, self.size.x, self.size.y)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TextureAtlasEntry {
+    pub page_index: usize,
+    pub uv_rect: UvRect,
+}
+
+/// Packed pages and places of the images by the order of their addition.
+pub struct TextureAtlas {
+    pub pages: Vec<RgbaImage>,
+    pub entries: Vec<TextureAtlasEntry>,
+}
+
+/// Packs small images into shared pages, so they take a single bindless slot and allocation per page.
+/// Images are placed on shelves from the tallest one, edges are extruded into the padding,
+/// so the linear filtering doesn't bleed the neighbours in.
+pub struct TextureAtlasBuilder {
+    page_size: u32,
+    padding: u32,
+    /// Bigger images are worth their own textures.
+    max_image_size: u32,
+    images: Vec<RgbaImage>,
+}
+
+impl Default for TextureAtlasBuilder {
+    fn default() -> Self {
+        Self {
+            page_size: 1024,
+            padding: 2,
+            max_image_size: 256,
+            images: Default::default(),
+        }
+    }
+}
+
+impl TextureAtlasBuilder {
+    pub fn with_page_size(mut self, page_size: u32) -> Self {
+        self.page_size = page_size;
+
+        self
+    }
+
+    pub fn with_padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+
+        self
+    }
+
+    pub fn with_max_image_size(mut self, max_image_size: u32) -> Self {
+        self.max_image_size = max_image_size;
+
+        self
+    }
+
+    /// Whether the image is small enough to share a page and fits into it with its padding.
+    pub fn can_pack(&self, width: u32, height: u32) -> bool {
+        let size = width.max(height);
+
+        size <= self.max_image_size && size + self.padding * 2 <= self.page_size
+    }
+
+    /// Returns the index of the entry, images that don't fit into a page are scaled down.
+    pub fn add_image(&mut self, image: RgbaImage) -> usize {
+        let image = if image.width() == 0 || image.height() == 0 {
+            RgbaImage::new(1, 1)
+        } else {
+            image
+        };
+        let max_size = self.page_size.saturating_sub(self.padding * 2).max(1);
+        let image = if image.width().max(image.height()) > max_size {
+            let scale = max_size as f32 / image.width().max(image.height()) as f32;
+            image::imageops::resize(
+                &image,
+                ((image.width() as f32 * scale) as u32).max(1),
+                ((image.height() as f32 * scale) as u32).max(1),
+                image::imageops::FilterType::Triangle,
+            )
+        } else {
+            image
+        };
+        self.images.push(image);
+
+        self.images.len() - 1
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    pub fn build(self) -> TextureAtlas {
+        let mut image_indices: Vec<_> = (0..self.images.len()).collect();
+        image_indices.sort_unstable_by_key(|&image_index| {
+            std::cmp::Reverse(self.images[image_index].height())
+        });
+
+        let page_size = self.page_size as f32;
+        let mut pages: Vec<RgbaImage> = Vec::new();
+        let mut entries = vec![
+            TextureAtlasEntry {
+                page_index: Default::default(),
+                uv_rect: Default::default(),
+            };
+            self.images.len()
+        ];
+        // NOTE: Shelves are filled left to right, a new shelf starts below the tallest image of the current one.
+        let mut shelf_x = self.page_size;
+        let mut shelf_y = 0;
+        let mut shelf_height = 0;
+        for image_index in image_indices {
+            let image = &self.images[image_index];
+            let padded_width = image.width() + self.padding * 2;
+            let padded_height = image.height() + self.padding * 2;
+
+            if shelf_x + padded_width > self.page_size {
+                shelf_x = 0;
+                shelf_y += shelf_height;
+                shelf_height = 0;
+            }
+            if pages.is_empty() || shelf_y + padded_height > self.page_size {
+                pages.push(RgbaImage::new(self.page_size, self.page_size));
+                shelf_x = 0;
+                shelf_y = 0;
+                shelf_height = 0;
+            }
+
+            let page_index = pages.len() - 1;
+            Self::blit_extruded(
+                &mut pages[page_index],
+                image,
+                shelf_x,
+                shelf_y,
+                self.padding,
+            );
+
+            entries[image_index] = TextureAtlasEntry {
+                page_index,
+                uv_rect: UvRect {
+                    offset: Vec2::new(
+                        (shelf_x + self.padding) as f32,
+                        (shelf_y + self.padding) as f32,
+                    ) / page_size,
+                    size: Vec2::new(image.width() as f32, image.height() as f32) / page_size,
+                },
+            };
+
+            shelf_x += padded_width;
+            shelf_height = shelf_height.max(padded_height);
+        }
+
+        TextureAtlas { pages, entries }
+    }
+
+    fn blit_extruded(page: &mut RgbaImage, image: &RgbaImage, x: u32, y: u32, padding: u32) {
+        let padded_width = image.width() + padding * 2;
+        let padded_height = image.height() + padding * 2;
+        for dst_y in 0..padded_height {
+            let src_y = dst_y.saturating_sub(padding).min(image.height() - 1);
+            for dst_x in 0..padded_width {
+                let src_x = dst_x.saturating_sub(padding).min(image.width() - 1);
+                page.put_pixel(x + dst_x, y + dst_y, *image.get_pixel(src_x, src_y));
+            }
+        }
+    }
+}
@@ -1,3 +1,6 @@
+use std::sync::Mutex;
+
+use ahash::AHashMap;
 use bevy_ecs::resource::Resource;
 use bytemuck::{Pod, Zeroable};
 use fast_image_resize::{PixelType, images::Image};
@@ -6,10 +9,21 @@ use shared::TextureKey;
 use slotmap::{Key, SlotMap};
 use vma::{Alloc, Allocation, AllocationCreateInfo, Allocator, MemoryUsage};
 use vulkanite::vk::{
-    ComponentMapping, ComponentSwizzle, Extent3D, Format, ImageAspectFlags, ImageCreateInfo,
-    ImageLayout, ImageSubresourceRange, ImageTiling, ImageType, ImageUsageFlags,
-    ImageViewCreateInfo, ImageViewType, MemoryPropertyFlags, SampleCountFlags, SharingMode,
-    rs::Device,
+    AccessFlags2, ComponentMapping, ComponentSwizzle, CopyMemoryToImageInfo, Extent3D, Format,
+    FormatFeatureFlags2, FormatProperties3, HostImageLayoutTransitionInfo, ImageAspectFlags,
+    ImageCopy, ImageCreateInfo, ImageLayout, ImageSubresourceLayers, ImageSubresourceRange,
+    ImageTiling, ImageType, ImageUsageFlags, ImageViewCreateInfo, ImageViewType,
+    MemoryPropertyFlags, MemoryToImageCopy, PipelineStageFlags2, SampleCountFlags, SharingMode,
+    rs::{CommandBuffer, Device, PhysicalDevice},
+};
+
+use crate::engine::{
+    ecs::{
+        image_state_tracker::{ImageState, ImageStateTracker},
+        texture_atlas::UvRect,
+    },
+    events::{GpuResourceEvent, TextureCreatedEvent, TextureDestroyedEvent},
+    utils::transition_image,
 };
 
 #[repr(C)]
@@ -29,6 +43,21 @@ pub struct AllocatedImage {
     pub format: Format,
     pub subresource_range: ImageSubresourceRange,
     pub texture_metadata: TextureMetadata,
+    pub usage_flags: ImageUsageFlags,
+    /// Only textures, that are just sampled after the upload, are moved by the defragmentation.
+    pub is_movable: bool,
+    /// Content is written from the host with the host image copy, no staging buffer is involved.
+    pub is_host_copyable: bool,
+    /// Storage views of the single mips, see `TexturesPool::create_mip_views`.
+    pub mip_views: Vec<MipView>,
+}
+
+/// View of a single mip, it's written by compute passes that build the mip chain themselves.
+#[derive(Clone, Copy)]
+pub struct MipView {
+    pub image_view: vulkanite::vk::rs::ImageView,
+    /// Index of the storage image descriptor, it's past the indices of the storage textures.
+    pub index: u32,
 }
 
 #[derive(Default, Clone, Copy)]
@@ -36,29 +65,74 @@ pub struct TextureReference {
     pub key: TextureKey,
     pub texture_metadata: TextureMetadata,
     read_only: bool,
+    uv_rect: UvRect,
 }
 
 impl TextureReference {
     pub fn get_index(&self) -> u32 {
         self.key.data().get_key() - 1
     }
+
+    /// Part of an atlas page, the reference counting and the metadata stay the ones of the page.
+    pub fn with_uv_rect(mut self, uv_rect: UvRect) -> Self {
+        self.uv_rect = uv_rect;
+
+        self
+    }
+
+    #[inline(always)]
+    pub fn get_uv_rect(&self) -> UvRect {
+        self.uv_rect
+    }
+
+    /// Sampled and storage textures live in different slot maps, so only both identify it.
+    #[inline(always)]
+    pub(crate) fn is_read_only(&self) -> bool {
+        self.read_only
+    }
 }
 
 #[derive(Resource)]
 pub struct TexturesPool {
     device: Device,
+    physical_device: PhysicalDevice,
     allocator: Allocator,
+    is_host_image_copy_supported: bool,
     storage_slots: SlotMap<TextureKey, AllocatedImage>,
     sampled_slots: SlotMap<TextureKey, AllocatedImage>,
+    references_counts: AHashMap<TextureKey, usize>,
+    /// Lifecycle changes since the last `notify_gpu_resource_events_system`.
+    gpu_resource_events: Vec<GpuResourceEvent>,
+    free_mip_view_indices: Vec<u32>,
+    next_mip_view_index: u32,
+    image_state_tracker: Mutex<ImageStateTracker>,
 }
 
 impl TexturesPool {
-    pub fn new(device: Device, allocator: Allocator) -> Self {
+    /// Bigger textures are still uploaded through the staging buffer, the host copy of them stalls the caller.
+    const MAX_HOST_COPY_TEXELS_COUNT: u32 = 1024 * 1024;
+    /// Storage image descriptors from it to the end of the binding are the ones of the mip views.
+    pub const FIRST_MIP_VIEW_INDEX: u32 = 1024;
+    pub const MAX_STORAGE_IMAGES_COUNT: u32 = 2048;
+
+    pub fn new(
+        device: Device,
+        physical_device: PhysicalDevice,
+        allocator: Allocator,
+        is_host_image_copy_supported: bool,
+    ) -> Self {
         Self {
             device,
+            physical_device,
             allocator,
+            is_host_image_copy_supported,
             storage_slots: SlotMap::with_capacity_and_key(128),
             sampled_slots: SlotMap::with_capacity_and_key(10_000),
+            references_counts: AHashMap::with_capacity(10_000),
+            gpu_resource_events: Default::default(),
+            free_mip_view_indices: Default::default(),
+            next_mip_view_index: Self::FIRST_MIP_VIEW_INDEX,
+            image_state_tracker: Default::default(),
         }
     }
 
@@ -72,6 +146,23 @@ impl TexturesPool {
         mip_map_enabled: bool,
     ) -> (TextureReference, Option<Ktx2Texture>) {
         let read_only = usage_flags.contains(ImageUsageFlags::Sampled);
+        let is_movable = usage_flags == ImageUsageFlags::Sampled | ImageUsageFlags::TransferDst;
+        let usage_flags = if is_movable {
+            usage_flags | ImageUsageFlags::TransferSrc
+        } else {
+            usage_flags
+        };
+        // NOTE: Mip maps and compressed formats are uploaded only by the transfer queue.
+        let is_host_copyable = is_movable
+            && !mip_map_enabled
+            && !Self::is_compressed_image_format(format)
+            && extent.width * extent.height <= Self::MAX_HOST_COPY_TEXELS_COUNT
+            && self.is_host_image_copy_format(format);
+        let usage_flags = if is_host_copyable {
+            usage_flags | ImageUsageFlags::HostTransfer
+        } else {
+            usage_flags
+        };
 
         let mut aspect_flags = ImageAspectFlags::Color;
         if format == Format::D32Sfloat {
@@ -79,10 +170,7 @@ impl TexturesPool {
         }
 
         let mip_levels_count = if mip_map_enabled {
-            f32::max(extent.width as _, extent.height as _)
-                .log2()
-                .floor() as u32
-                + 1
+            Self::get_mip_levels_count(extent.width, extent.height)
         } else {
             1
         };
@@ -99,96 +187,7 @@ impl TexturesPool {
             && !is_cached
             && let Some(data) = data
         {
-            let target_ktx_format = match format {
-                Format::Bc3SrgbBlock | Format::Bc1RgbSrgbBlock => ktx2_rw::VkFormat::R8G8B8A8Srgb,
-                _ => panic!("Unsupported KTX format: {:?}!", format),
-            };
-
-            let mut texture = Ktx2Texture::create(
-                texture_metadata.width,
-                texture_metadata.height,
-                1,
-                1,
-                1,
-                mip_levels_count,
-                target_ktx_format,
-            )
-            .unwrap();
-
-            let src_image = match format {
-                Format::Bc3SrgbBlock => Image::from_slice_u8(
-                    texture_metadata.width,
-                    texture_metadata.height,
-                    data,
-                    PixelType::U8x4,
-                )
-                .unwrap(),
-                Format::Bc1RgbSrgbBlock => Image::from_slice_u8(
-                    texture_metadata.width,
-                    texture_metadata.height,
-                    data,
-                    PixelType::U8x4,
-                )
-                .unwrap(),
-                _ => panic!("Unsupported Image format: {:?}!", format),
-            };
-
-            // TODO: We can effectively pre-allocate required total size of texture_data
-            let mut texture_data = Vec::new();
-            for mip_level_index in 0..mip_levels_count {
-                let current_width = (texture_metadata.width >> mip_level_index).max(1);
-                let current_height = (texture_metadata.height >> mip_level_index).max(1);
-
-                let mut resizer = fast_image_resize::Resizer::new();
-                unsafe {
-                    resizer.set_cpu_extensions(fast_image_resize::CpuExtensions::Avx2);
-                }
-
-                let mut dst_image = fast_image_resize::images::Image::new(
-                    current_width,
-                    current_height,
-                    src_image.pixel_type(),
-                );
-
-                resizer.resize(&src_image, &mut dst_image, None).unwrap();
-
-                let image_bytes = dst_image.buffer();
-
-                texture
-                    .set_image_data(mip_level_index, 0, 0, image_bytes)
-                    .unwrap();
-            }
-
-            texture
-                .compress_basis(
-                    &BasisCompressionParams::builder()
-                        .thread_count((num_cpus::get() - 1) as _)
-                        .build(),
-                )
-                .unwrap();
-
-            let transcode_format = match format {
-                Format::Bc1RgbSrgbBlock => ktx2_rw::TranscodeFormat::Bc1Rgb,
-                Format::Bc3SrgbBlock => ktx2_rw::TranscodeFormat::Bc3Rgba,
-                Format::Bc7SrgbBlock => ktx2_rw::TranscodeFormat::Bc7Rgba,
-                _ => panic!("Unsupported transcode format!"),
-            };
-
-            texture.transcode_basis(transcode_format).unwrap();
-
-            for mip_level_index in 0..mip_levels_count {
-                let texture_data_ref = texture.get_image_data(mip_level_index, 0, 0).unwrap();
-                texture_data.extend_from_slice(texture_data_ref);
-            }
-
-            texture
-                .set_metadata(
-                    stringify!(TextureMetadata),
-                    bytemuck::bytes_of(&texture_metadata),
-                )
-                .unwrap();
-
-            ktx_texture = Some(texture);
+            ktx_texture = Some(Self::encode_ktx_texture(data, texture_metadata, format));
         }
 
         let allocation_info = AllocationCreateInfo {
@@ -231,9 +230,139 @@ impl TexturesPool {
                 height: extent.height,
                 mip_levels_count,
             },
+            usage_flags,
+            is_movable,
+            is_host_copyable,
+            mip_views: Default::default(),
+        };
+
+        let size = self.allocator.get_allocation_info(allocation).size;
+        let texture_reference = self.insert_image(allocated_image, read_only);
+        let name = Self::get_texture_name(
+            texture_reference,
+            self.get_image(texture_reference).unwrap(),
+        );
+        self.gpu_resource_events
+            .push(GpuResourceEvent::TextureCreated(TextureCreatedEvent {
+                texture_reference,
+                name,
+                format,
+                extent,
+                size,
+            }));
+
+        (texture_reference, ktx_texture)
+    }
+
+    /// Textures have no debug names, so it's made of the index, extent and format.
+    pub(crate) fn get_texture_name(
+        texture_reference: TextureReference,
+        allocated_image: &AllocatedImage,
+    ) -> String {
+        std::format!(
+            "Texture {} {}x{} {:?}",
+            texture_reference.get_index(),
+            allocated_image.extent.width,
+            allocated_image.extent.height,
+            allocated_image.format
+        )
+    }
+
+    pub(crate) fn get_mip_levels_count(width: u32, height: u32) -> u32 {
+        f32::max(width as _, height as _).log2().floor() as u32 + 1
+    }
+
+    /// Basis compresses RGBA8 data with its mip chain and transcodes it into `format`,
+    /// it doesn't touch the device, so the cook step shares it with the runtime.
+    pub(crate) fn encode_ktx_texture(
+        data: &mut [u8],
+        texture_metadata: TextureMetadata,
+        format: Format,
+    ) -> Ktx2Texture {
+        let mip_levels_count = texture_metadata.mip_levels_count;
+        let target_ktx_format = match format {
+            Format::Bc3SrgbBlock | Format::Bc1RgbSrgbBlock => ktx2_rw::VkFormat::R8G8B8A8Srgb,
+            _ => panic!("Unsupported KTX format: {:?}!", format),
+        };
+
+        let mut texture = Ktx2Texture::create(
+            texture_metadata.width,
+            texture_metadata.height,
+            1,
+            1,
+            1,
+            mip_levels_count,
+            target_ktx_format,
+        )
+        .unwrap();
+
+        let src_image = match format {
+            Format::Bc3SrgbBlock => Image::from_slice_u8(
+                texture_metadata.width,
+                texture_metadata.height,
+                data,
+                PixelType::U8x4,
+            )
+            .unwrap(),
+            Format::Bc1RgbSrgbBlock => Image::from_slice_u8(
+                texture_metadata.width,
+                texture_metadata.height,
+                data,
+                PixelType::U8x4,
+            )
+            .unwrap(),
+            _ => panic!("Unsupported Image format: {:?}!", format),
+        };
+
+        for mip_level_index in 0..mip_levels_count {
+            let current_width = (texture_metadata.width >> mip_level_index).max(1);
+            let current_height = (texture_metadata.height >> mip_level_index).max(1);
+
+            let mut resizer = fast_image_resize::Resizer::new();
+            unsafe {
+                resizer.set_cpu_extensions(fast_image_resize::CpuExtensions::Avx2);
+            }
+
+            let mut dst_image = fast_image_resize::images::Image::new(
+                current_width,
+                current_height,
+                src_image.pixel_type(),
+            );
+
+            resizer.resize(&src_image, &mut dst_image, None).unwrap();
+
+            let image_bytes = dst_image.buffer();
+
+            texture
+                .set_image_data(mip_level_index, 0, 0, image_bytes)
+                .unwrap();
+        }
+
+        texture
+            .compress_basis(
+                &BasisCompressionParams::builder()
+                    .thread_count((num_cpus::get() - 1) as _)
+                    .build(),
+            )
+            .unwrap();
+
+        let transcode_format = match format {
+            Format::Bc1RgbSrgbBlock => ktx2_rw::TranscodeFormat::Bc1Rgb,
+            Format::Bc3SrgbBlock => ktx2_rw::TranscodeFormat::Bc3Rgba,
+            Format::Bc7SrgbBlock => ktx2_rw::TranscodeFormat::Bc7Rgba,
+            _ => panic!("Unsupported transcode format!"),
         };
 
-        (self.insert_image(allocated_image, read_only), ktx_texture)
+        texture.transcode_basis(transcode_format).unwrap();
+
+        texture
+            .set_metadata(
+                stringify!(TextureMetadata),
+                bytemuck::bytes_of(&texture_metadata),
+            )
+            .unwrap();
+
+        texture
     }
 
     fn insert_image(
@@ -259,7 +388,111 @@ impl TexturesPool {
             key: texture_key,
             texture_metadata,
             read_only,
+            uv_rect: Default::default(),
+        }
+    }
+
+    fn is_host_image_copy_format(&self, format: Format) -> bool {
+        if !self.is_host_image_copy_supported {
+            return false;
+        }
+
+        let (_, format_properties): (_, FormatProperties3) =
+            self.physical_device.get_format_properties2(format);
+
+        format_properties
+            .optimal_tiling_features
+            .contains(FormatFeatureFlags2::HostImageTransfer)
+    }
+
+    /// Writes the content of the texture from the host, the texture is ready to be sampled once it returns.
+    /// Returns `false` if the texture isn't host copyable, then it has to be uploaded by the transfer queue.
+    pub fn copy_memory_to_texture(&self, texture_reference: TextureReference, data: &[u8]) -> bool {
+        let allocated_image = self.get_image(texture_reference).unwrap();
+        if !allocated_image.is_host_copyable {
+            return false;
         }
+
+        // NOTE: Sampled textures stay in the general layout after the upload.
+        let transition_infos = [HostImageLayoutTransitionInfo::default()
+            .image(&allocated_image.image)
+            .old_layout(ImageLayout::Undefined)
+            .new_layout(ImageLayout::General)
+            .subresource_range(allocated_image.subresource_range)];
+        self.device
+            .transition_image_layout(&transition_infos)
+            .unwrap();
+
+        let memory_to_image_copies = [MemoryToImageCopy::default()
+            .host_pointer(data.as_ptr() as *const _)
+            .image_subresource(ImageSubresourceLayers {
+                aspect_mask: allocated_image.image_aspect_flags,
+                mip_level: Default::default(),
+                base_array_layer: Default::default(),
+                layer_count: 1,
+            })
+            .image_extent(allocated_image.extent)];
+        let copy_memory_to_image_info = CopyMemoryToImageInfo::default()
+            .dst_image(&allocated_image.image)
+            .dst_image_layout(ImageLayout::General)
+            .regions(&memory_to_image_copies);
+        self.device
+            .copy_memory_to_image(&copy_memory_to_image_info)
+            .unwrap();
+        self.record_image_state(
+            texture_reference,
+            ImageState::new(
+                ImageLayout::General,
+                PipelineStageFlags2::None,
+                AccessFlags2::None,
+            ),
+        );
+
+        true
+    }
+
+    /// Emits the barrier from the last state of the texture to the new one, if it's needed.
+    /// Returns the last state when the barrier is emitted.
+    pub fn ensure_layout(
+        &self,
+        command_buffer: CommandBuffer,
+        texture_reference: TextureReference,
+        new_state: ImageState,
+    ) -> Option<ImageState> {
+        let allocated_image = self.get_image(texture_reference)?;
+
+        self.image_state_tracker.lock().unwrap().transition(
+            command_buffer,
+            texture_reference.key,
+            texture_reference.read_only,
+            allocated_image.image,
+            allocated_image.subresource_range,
+            new_state,
+        )
+    }
+
+    /// Content of the texture is discarded by its next `ensure_layout`, e.g. it's fully rewritten.
+    pub fn discard_content(&self, texture_reference: TextureReference) {
+        self.image_state_tracker
+            .lock()
+            .unwrap()
+            .discard(texture_reference.key, texture_reference.read_only);
+    }
+
+    /// Marks a transition that was recorded without `ensure_layout` (e.g. upload).
+    pub fn record_image_state(&self, texture_reference: TextureReference, state: ImageState) {
+        self.image_state_tracker.lock().unwrap().record_state(
+            texture_reference.key,
+            texture_reference.read_only,
+            state,
+        );
+    }
+
+    pub fn get_image_state(&self, texture_reference: TextureReference) -> ImageState {
+        self.image_state_tracker
+            .lock()
+            .unwrap()
+            .get_state(texture_reference.key, texture_reference.read_only)
     }
 
     fn is_compressed_image_format(format: Format) -> bool {
@@ -335,10 +568,313 @@ impl TexturesPool {
         image_view_create_info
     }
 
+    /// Render targets first, then sampled textures.
+    pub fn get_textures(&self) -> impl Iterator<Item = (TextureReference, &AllocatedImage)> {
+        let storage_textures = self.storage_slots.iter().map(|slot| (slot, false));
+        let sampled_textures = self.sampled_slots.iter().map(|slot| (slot, true));
+
+        storage_textures.chain(sampled_textures).map(
+            |((texture_key, allocated_image), read_only)| {
+                let texture_reference = TextureReference {
+                    key: texture_key,
+                    texture_metadata: allocated_image.texture_metadata,
+                    read_only,
+                    uv_rect: Default::default(),
+                };
+
+                (texture_reference, allocated_image)
+            },
+        )
+    }
+
+    pub fn get_movable_textures(
+        &self,
+    ) -> impl Iterator<Item = (TextureReference, &AllocatedImage)> {
+        self.sampled_slots
+            .iter()
+            .filter(|(_, allocated_image)| allocated_image.is_movable)
+            .map(|(texture_key, allocated_image)| {
+                let texture_reference = TextureReference {
+                    key: texture_key,
+                    texture_metadata: allocated_image.texture_metadata,
+                    read_only: true,
+                    uv_rect: Default::default(),
+                };
+
+                (texture_reference, allocated_image)
+            })
+    }
+
+    /// Creates an image like the given one on the memory of `dst_allocation` and records the copy of the content to it.
+    // NOTE: Sampled textures stay in the general layout after the upload.
+    pub(crate) fn create_moved_image(
+        &self,
+        texture_reference: TextureReference,
+        dst_allocation: Allocation,
+        command_buffer: CommandBuffer,
+    ) -> vulkanite::vk::rs::Image {
+        let allocated_image = self.get_image(texture_reference).unwrap();
+        let mip_levels_count = allocated_image.texture_metadata.mip_levels_count;
+        let aspect_mask = allocated_image.subresource_range.aspect_mask;
+
+        let image_create_info = Self::get_image_info(
+            allocated_image.format,
+            allocated_image.usage_flags,
+            allocated_image.extent,
+            ImageLayout::Undefined,
+            mip_levels_count,
+        );
+        let image = self.device.create_image(&image_create_info).unwrap();
+        unsafe {
+            self.allocator
+                .bind_image_memory(dst_allocation, *image)
+                .unwrap();
+        }
+
+        transition_image(
+            command_buffer,
+            image,
+            ImageLayout::Undefined,
+            ImageLayout::General,
+            PipelineStageFlags2::None,
+            PipelineStageFlags2::Copy,
+            AccessFlags2::None,
+            AccessFlags2::TransferWrite,
+            aspect_mask,
+            mip_levels_count,
+        );
+
+        let image_copies = (0..mip_levels_count)
+            .map(|mip_level_index| {
+                let image_subresource = ImageSubresourceLayers {
+                    aspect_mask,
+                    mip_level: mip_level_index,
+                    base_array_layer: Default::default(),
+                    layer_count: 1,
+                };
+
+                ImageCopy {
+                    src_subresource: image_subresource,
+                    dst_subresource: image_subresource,
+                    extent: Extent3D {
+                        width: (allocated_image.extent.width >> mip_level_index).max(1),
+                        height: (allocated_image.extent.height >> mip_level_index).max(1),
+                        depth: 1,
+                    },
+                    ..Default::default()
+                }
+            })
+            .collect::<Vec<_>>();
+        command_buffer.copy_image(
+            allocated_image.image,
+            ImageLayout::General,
+            image,
+            ImageLayout::General,
+            &image_copies,
+        );
+
+        transition_image(
+            command_buffer,
+            image,
+            ImageLayout::General,
+            ImageLayout::General,
+            PipelineStageFlags2::Copy,
+            PipelineStageFlags2::FragmentShader,
+            AccessFlags2::TransferWrite,
+            AccessFlags2::ShaderSampledRead,
+            aspect_mask,
+            mip_levels_count,
+        );
+
+        image
+    }
+
+    /// Destroys the old image, once its allocation is moved, and returns the view of the new one.
+    pub(crate) fn replace_image(
+        &mut self,
+        texture_reference: TextureReference,
+        image: vulkanite::vk::rs::Image,
+    ) -> vulkanite::vk::rs::ImageView {
+        let allocated_image = self.sampled_slots.get_mut(texture_reference.key).unwrap();
+
+        let image_view_create_info = Self::get_image_view_info(
+            allocated_image.format,
+            &image,
+            allocated_image.image_aspect_flags,
+            allocated_image.texture_metadata.mip_levels_count,
+        );
+        let image_view = self
+            .device
+            .create_image_view(&image_view_create_info)
+            .unwrap();
+
+        self.device
+            .destroy_image_view(Some(allocated_image.image_view));
+        self.device.destroy_image(Some(allocated_image.image));
+        allocated_image.image = image;
+        allocated_image.image_view = image_view;
+        self.image_state_tracker.get_mut().unwrap().record_state(
+            texture_reference.key,
+            texture_reference.read_only,
+            ImageState::sampled_read(),
+        );
+
+        image_view
+    }
+
+    /// Creates a view per mip of a storage texture once, the caller writes their descriptors.
+    /// Views are destroyed with the texture.
+    pub fn create_mip_views(&mut self, texture_reference: TextureReference) -> Vec<MipView> {
+        let allocated_image = self.get_image(texture_reference).unwrap();
+        if !allocated_image.mip_views.is_empty() {
+            return allocated_image.mip_views.clone();
+        }
+        if !allocated_image
+            .usage_flags
+            .contains(ImageUsageFlags::Storage)
+        {
+            panic!("Trying to create mip views of a texture without the storage usage!");
+        }
+
+        let format = allocated_image.format;
+        let image = allocated_image.image;
+        let image_aspect_flags = allocated_image.image_aspect_flags;
+        let mip_levels_count = allocated_image.texture_metadata.mip_levels_count;
+
+        let mip_views: Vec<_> = (0..mip_levels_count)
+            .map(|mip_level| {
+                let mut image_view_create_info =
+                    Self::get_image_view_info(format, &image, image_aspect_flags, 1);
+                image_view_create_info.subresource_range.base_mip_level = mip_level;
+                let image_view = self
+                    .device
+                    .create_image_view(&image_view_create_info)
+                    .unwrap();
+
+                MipView {
+                    image_view,
+                    index: self.allocate_mip_view_index(),
+                }
+            })
+            .collect();
+
+        let allocated_image = if texture_reference.read_only {
+            self.sampled_slots.get_mut(texture_reference.key)
+        } else {
+            self.storage_slots.get_mut(texture_reference.key)
+        };
+        allocated_image.unwrap().mip_views = mip_views.clone();
+
+        mip_views
+    }
+
+    #[inline(always)]
+    pub fn get_mip_views(&self, texture_reference: TextureReference) -> &[MipView] {
+        self.get_image(texture_reference)
+            .map(|allocated_image| allocated_image.mip_views.as_slice())
+            .unwrap_or_default()
+    }
+
+    fn allocate_mip_view_index(&mut self) -> u32 {
+        if let Some(mip_view_index) = self.free_mip_view_indices.pop() {
+            return mip_view_index;
+        }
+
+        let mip_view_index = self.next_mip_view_index;
+        if mip_view_index >= Self::MAX_STORAGE_IMAGES_COUNT {
+            panic!(
+                "Mip views exceed {} storage image descriptors!",
+                Self::MAX_STORAGE_IMAGES_COUNT - Self::FIRST_MIP_VIEW_INDEX
+            );
+        }
+        self.next_mip_view_index += 1;
+
+        mip_view_index
+    }
+
+    // NOTE: Indices are reused right away, the texture is already out of the frames in flight.
+    fn destroy_mip_views(&mut self, mip_views: Vec<MipView>) {
+        for mip_view in mip_views {
+            self.device.destroy_image_view(Some(mip_view.image_view));
+            self.free_mip_view_indices.push(mip_view.index);
+        }
+    }
+
+    /// Only sampled textures are reference counted, e.g. by materials.
+    pub fn retain_texture(&mut self, texture_reference: TextureReference) {
+        if texture_reference.read_only {
+            *self
+                .references_counts
+                .entry(texture_reference.key)
+                .or_default() += 1;
+        }
+    }
+
+    /// Returns `true` if it was the last reference and the texture can be freed.
+    pub fn release_texture(&mut self, texture_reference: TextureReference) -> bool {
+        let Some(references_count) = self.references_counts.get_mut(&texture_reference.key) else {
+            return false;
+        };
+
+        *references_count = references_count.saturating_sub(1);
+        if *references_count > Default::default() {
+            return false;
+        }
+        self.references_counts.remove(&texture_reference.key);
+
+        true
+    }
+
+    /// Texture must not be in use by the GPU anymore, see `DeletionQueue`.
+    pub fn free_texture(&mut self, texture_reference: TextureReference) {
+        let allocated_image = if texture_reference.read_only {
+            self.sampled_slots.remove(texture_reference.key)
+        } else {
+            self.storage_slots.remove(texture_reference.key)
+        };
+        self.image_state_tracker
+            .get_mut()
+            .unwrap()
+            .forget(texture_reference.key, texture_reference.read_only);
+
+        if let Some(mut allocated_image) = allocated_image {
+            self.destroy_mip_views(std::mem::take(&mut allocated_image.mip_views));
+            let name = Self::get_texture_name(texture_reference, &allocated_image);
+            let size = self
+                .allocator
+                .get_allocation_info(allocated_image.allocation)
+                .size;
+            unsafe {
+                self.device
+                    .destroy_image_view(Some(allocated_image.image_view));
+                self.allocator
+                    .destroy_image(*allocated_image.image, &mut allocated_image.allocation);
+            }
+
+            self.gpu_resource_events
+                .push(GpuResourceEvent::TextureDestroyed(TextureDestroyedEvent {
+                    texture_reference,
+                    name,
+                    size,
+                }));
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn take_gpu_resource_events(&mut self) -> Vec<GpuResourceEvent> {
+        std::mem::take(&mut self.gpu_resource_events)
+    }
+
     pub fn free_allocations(&mut self) {
+        self.references_counts.clear();
+        *self.image_state_tracker.get_mut().unwrap() = Default::default();
         self.sampled_slots
-            .iter_mut()
-            .for_each(|(_, allocated_image)| unsafe {
+            .drain()
+            .for_each(|(_, mut allocated_image)| unsafe {
+                allocated_image
+                    .mip_views
+                    .iter()
+                    .for_each(|mip_view| self.device.destroy_image_view(Some(mip_view.image_view)));
                 self.device
                     .destroy_image_view(Some(allocated_image.image_view));
                 self.allocator
@@ -346,8 +882,12 @@ impl TexturesPool {
             });
 
         self.storage_slots
-            .iter_mut()
-            .for_each(|(_, allocated_image)| unsafe {
+            .drain()
+            .for_each(|(_, mut allocated_image)| unsafe {
+                allocated_image
+                    .mip_views
+                    .iter()
+                    .for_each(|mip_view| self.device.destroy_image_view(Some(mip_view.image_view)));
                 self.device
                     .destroy_image_view(Some(allocated_image.image_view));
                 self.allocator
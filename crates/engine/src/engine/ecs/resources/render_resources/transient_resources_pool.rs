@@ -0,0 +1,282 @@
+use bevy_ecs::resource::Resource;
+use shared::BufferKey;
+use vulkanite::vk::{BufferUsageFlags, Extent3D, Format, ImageUsageFlags};
+
+use crate::engine::{
+    general::renderer::{
+        DescriptorKind, DescriptorSampledImage, DescriptorSetHandle, DescriptorStorageImage,
+    },
+    resources::{
+        DeferredResource,
+        buffers_pool::{BufferReference, BufferVisibility, BuffersPool},
+        textures_pool::{TextureReference, TexturesPool},
+    },
+};
+
+/// Images of the same descriptor are interchangeable, since their content isn't kept.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TransientImageDescriptor {
+    pub format: Format,
+    pub width: u32,
+    pub height: u32,
+    pub usage_flags: ImageUsageFlags,
+}
+
+impl TransientImageDescriptor {
+    pub fn new(format: Format, width: u32, height: u32, usage_flags: ImageUsageFlags) -> Self {
+        Self {
+            format,
+            width,
+            height,
+            usage_flags,
+        }
+    }
+}
+
+/// Buffers are device only, a larger one of the same usage is compatible.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TransientBufferDescriptor {
+    pub size: usize,
+    pub usage: BufferUsageFlags,
+}
+
+impl TransientBufferDescriptor {
+    pub fn new(size: usize, usage: BufferUsageFlags) -> Self {
+        Self { size, usage }
+    }
+}
+
+struct PooledResource<D, R> {
+    name: &'static str,
+    descriptor: D,
+    reference: R,
+    is_acquired: bool,
+    last_frame_index: usize,
+}
+
+/// Images and buffers that passes request by their descriptors, instead of owning them per frame.
+/// Acquisitions last until the next frame, the same resource is handed out again only once every
+/// frame in flight that used it is finished, or in the same frame after it's released.
+/// Resources that aren't requested for `MAX_UNUSED_FRAMES` are freed, e.g. after a resize.
+#[derive(Default, Resource)]
+pub struct TransientResourcesPool {
+    frame_index: usize,
+    frame_overlap: usize,
+    images: Vec<PooledResource<TransientImageDescriptor, TextureReference>>,
+    buffers: Vec<PooledResource<TransientBufferDescriptor, BufferReference>>,
+}
+
+impl TransientResourcesPool {
+    pub const MAX_UNUSED_FRAMES: usize = 8;
+
+    /// New images get the descriptors of their usage, so they're bound once.
+    pub fn acquire_image(
+        &mut self,
+        textures_pool: &mut TexturesPool,
+        buffers_pool: &mut BuffersPool,
+        descriptor_set_handle: &mut DescriptorSetHandle,
+        name: &'static str,
+        descriptor: TransientImageDescriptor,
+    ) -> TextureReference {
+        let (frame_index, frame_overlap) = (self.frame_index, self.frame_overlap);
+        if let Some(pooled_image) = self.images.iter_mut().find(|pooled_image| {
+            pooled_image.descriptor == descriptor
+                && Self::is_available(pooled_image, frame_index, frame_overlap)
+        }) {
+            pooled_image.name = name;
+            pooled_image.is_acquired = true;
+            pooled_image.last_frame_index = frame_index;
+            // NOTE: Next pass starts from the undefined layout, the previous content is garbage.
+            textures_pool.discard_content(pooled_image.reference);
+
+            return pooled_image.reference;
+        }
+
+        let (texture_reference, _) = textures_pool.create_texture(
+            None,
+            false,
+            descriptor.format,
+            Extent3D {
+                width: descriptor.width,
+                height: descriptor.height,
+                depth: 1,
+            },
+            descriptor.usage_flags,
+            false,
+        );
+        let image_view = textures_pool
+            .get_image(texture_reference)
+            .unwrap()
+            .image_view;
+        if descriptor.usage_flags.contains(ImageUsageFlags::Storage) {
+            let descriptor_storage_image = DescriptorKind::StorageImage(DescriptorStorageImage {
+                image_view,
+                index: texture_reference.get_index(),
+            });
+            descriptor_set_handle.update_binding(buffers_pool, descriptor_storage_image);
+        }
+        if descriptor.usage_flags.contains(ImageUsageFlags::Sampled) {
+            let descriptor_sampled_image = DescriptorKind::SampledImage(DescriptorSampledImage {
+                image_view,
+                index: texture_reference.get_index(),
+            });
+            descriptor_set_handle.update_binding(buffers_pool, descriptor_sampled_image);
+        }
+
+        self.images.push(PooledResource {
+            name,
+            descriptor,
+            reference: texture_reference,
+            is_acquired: true,
+            last_frame_index: frame_index,
+        });
+
+        texture_reference
+    }
+
+    /// Smallest compatible buffer is reused, its size may be larger than the requested one.
+    pub fn acquire_buffer(
+        &mut self,
+        buffers_pool: &mut BuffersPool,
+        name: &'static str,
+        descriptor: TransientBufferDescriptor,
+    ) -> BufferReference {
+        let (frame_index, frame_overlap) = (self.frame_index, self.frame_overlap);
+        if let Some(pooled_buffer) = self
+            .buffers
+            .iter_mut()
+            .filter(|pooled_buffer| {
+                pooled_buffer.descriptor.usage == descriptor.usage
+                    && pooled_buffer.descriptor.size >= descriptor.size
+                    && Self::is_available(pooled_buffer, frame_index, frame_overlap)
+            })
+            .min_by_key(|pooled_buffer| pooled_buffer.descriptor.size)
+        {
+            pooled_buffer.name = name;
+            pooled_buffer.is_acquired = true;
+            pooled_buffer.last_frame_index = frame_index;
+
+            return pooled_buffer.reference;
+        }
+
+        let buffer_reference = buffers_pool.create_buffer(
+            descriptor.size,
+            descriptor.usage,
+            BufferVisibility::DeviceOnly,
+            None,
+            Some(std::format!("Transient {} {}", name, self.buffers.len())),
+        );
+
+        self.buffers.push(PooledResource {
+            name,
+            descriptor,
+            reference: buffer_reference,
+            is_acquired: true,
+            last_frame_index: frame_index,
+        });
+
+        buffer_reference
+    }
+
+    /// Image can be acquired by a later pass of the same queue, barriers are left to its states.
+    #[allow(dead_code)]
+    pub fn release_image(&mut self, texture_reference: TextureReference) {
+        if let Some(pooled_image) = self
+            .images
+            .iter_mut()
+            .find(|pooled_image| Self::is_same_texture(pooled_image.reference, texture_reference))
+        {
+            pooled_image.is_acquired = false;
+        }
+    }
+
+    /// Buffer can be acquired by a later pass of the same queue, barriers are left to the caller.
+    #[allow(dead_code)]
+    pub fn release_buffer(&mut self, buffer_reference: BufferReference) {
+        if let Some(pooled_buffer) = self
+            .buffers
+            .iter_mut()
+            .find(|pooled_buffer| pooled_buffer.reference.get_key() == buffer_reference.get_key())
+        {
+            pooled_buffer.is_acquired = false;
+        }
+    }
+
+    /// Name of the last pass that acquired it.
+    pub fn get_texture_name(&self, texture_reference: TextureReference) -> Option<&'static str> {
+        self.images
+            .iter()
+            .find(|pooled_image| Self::is_same_texture(pooled_image.reference, texture_reference))
+            .map(|pooled_image| pooled_image.name)
+    }
+
+    pub(crate) fn get_buffer_keys(&self) -> impl Iterator<Item = BufferKey> {
+        self.buffers
+            .iter()
+            .map(|pooled_buffer| pooled_buffer.reference.get_key())
+    }
+
+    /// Must be called once per frame, after the fence of the current frame is waited.
+    /// Returns resources that weren't requested for a while, they're unused by the GPU already.
+    pub(crate) fn begin_frame(&mut self, frame_overlap: usize) -> Vec<DeferredResource> {
+        self.frame_index += 1;
+        self.frame_overlap = frame_overlap;
+
+        let unused_frames_count = frame_overlap.max(Self::MAX_UNUSED_FRAMES);
+        let frame_index = self.frame_index;
+        let is_unused =
+            |last_frame_index: usize| last_frame_index + unused_frames_count <= frame_index;
+
+        let mut unused_resources = Vec::new();
+        self.images.retain_mut(|pooled_image| {
+            pooled_image.is_acquired = false;
+            if is_unused(pooled_image.last_frame_index) {
+                unused_resources.push(DeferredResource::Texture(pooled_image.reference));
+
+                return false;
+            }
+
+            true
+        });
+        self.buffers.retain_mut(|pooled_buffer| {
+            pooled_buffer.is_acquired = false;
+            if is_unused(pooled_buffer.last_frame_index) {
+                unused_resources.push(DeferredResource::Buffer(pooled_buffer.reference));
+
+                return false;
+            }
+
+            true
+        });
+
+        unused_resources
+    }
+
+    /// Device must be idle, e.g. before the draw textures are recreated.
+    pub(crate) fn take_resources(&mut self) -> Vec<DeferredResource> {
+        self.images
+            .drain(..)
+            .map(|pooled_image| DeferredResource::Texture(pooled_image.reference))
+            .chain(
+                self.buffers
+                    .drain(..)
+                    .map(|pooled_buffer| DeferredResource::Buffer(pooled_buffer.reference)),
+            )
+            .collect()
+    }
+
+    /// Resource of the previous frames is still read by them, until the frames in flight finish.
+    fn is_available<D, R>(
+        pooled_resource: &PooledResource<D, R>,
+        frame_index: usize,
+        frame_overlap: usize,
+    ) -> bool {
+        !pooled_resource.is_acquired
+            && (pooled_resource.last_frame_index == frame_index
+                || pooled_resource.last_frame_index + frame_overlap <= frame_index)
+    }
+
+    fn is_same_texture(a: TextureReference, b: TextureReference) -> bool {
+        a.key == b.key && a.is_read_only() == b.is_read_only()
+    }
+}
@@ -0,0 +1,147 @@
+use bevy_ecs::resource::Resource;
+use vulkanite::vk::Extent2D;
+
+#[derive(Clone, Copy, Debug)]
+pub struct DynamicResolution {
+    /// GPU frame time to keep, in milliseconds.
+    pub target_frame_time: f32,
+    pub min_render_scale: f32,
+    pub max_render_scale: f32,
+}
+
+impl Default for DynamicResolution {
+    fn default() -> Self {
+        Self {
+            target_frame_time: 1000.0 / 60.0,
+            min_render_scale: RenderScaleSettings::MIN_RENDER_SCALE,
+            max_render_scale: 1.0,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct RenderScaleSettings {
+    render_scale: f32,
+    dynamic_resolution: Option<DynamicResolution>,
+    current_render_scale: f32,
+    is_dirty: bool,
+}
+
+impl Default for RenderScaleSettings {
+    fn default() -> Self {
+        Self {
+            render_scale: 1.0,
+            dynamic_resolution: Default::default(),
+            current_render_scale: 1.0,
+            is_dirty: false,
+        }
+    }
+}
+
+impl RenderScaleSettings {
+    pub const MIN_RENDER_SCALE: f32 = 0.5;
+    pub const MAX_RENDER_SCALE: f32 = 2.0;
+    // NOTE: Smaller changes aren't worth the resolution jump.
+    const DYNAMIC_RESOLUTION_STEP: f32 = 0.05;
+
+    pub fn get_render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    pub fn get_dynamic_resolution(&self) -> Option<DynamicResolution> {
+        self.dynamic_resolution
+    }
+
+    /// Scale the last frame was rendered with, it differs from the render scale with dynamic resolution.
+    pub fn get_current_render_scale(&self) -> f32 {
+        self.current_render_scale
+    }
+
+    pub fn set_render_scale(&mut self, render_scale: f32) {
+        let render_scale = render_scale.clamp(Self::MIN_RENDER_SCALE, Self::MAX_RENDER_SCALE);
+        if self.render_scale == render_scale {
+            return;
+        }
+
+        let max_render_scale = self.get_max_render_scale();
+        self.render_scale = render_scale;
+        if self.dynamic_resolution.is_none() {
+            self.current_render_scale = render_scale;
+        }
+
+        self.is_dirty |= max_render_scale != self.get_max_render_scale();
+    }
+
+    /// `None` disables dynamic resolution and goes back to the fixed render scale.
+    pub fn set_dynamic_resolution(&mut self, dynamic_resolution: Option<DynamicResolution>) {
+        let max_render_scale = self.get_max_render_scale();
+
+        self.dynamic_resolution = dynamic_resolution.map(|mut dynamic_resolution| {
+            dynamic_resolution.min_render_scale = dynamic_resolution
+                .min_render_scale
+                .clamp(Self::MIN_RENDER_SCALE, Self::MAX_RENDER_SCALE);
+            dynamic_resolution.max_render_scale = dynamic_resolution
+                .max_render_scale
+                .clamp(dynamic_resolution.min_render_scale, Self::MAX_RENDER_SCALE);
+
+            dynamic_resolution
+        });
+        self.current_render_scale = self.clamp_render_scale(self.current_render_scale);
+        if self.dynamic_resolution.is_none() {
+            self.current_render_scale = self.render_scale;
+        }
+
+        self.is_dirty |= max_render_scale != self.get_max_render_scale();
+    }
+
+    /// Draw images are allocated with this scale, so dynamic resolution never reallocates them.
+    pub(crate) fn get_max_render_scale(&self) -> f32 {
+        match self.dynamic_resolution {
+            Some(dynamic_resolution) => dynamic_resolution.max_render_scale,
+            None => self.render_scale,
+        }
+    }
+
+    /// Moves the current render scale towards the one that fits the target GPU frame time.
+    pub(crate) fn update_dynamic_resolution(&mut self, gpu_frame_time: f32) {
+        let Some(dynamic_resolution) = self.dynamic_resolution else {
+            return;
+        };
+        if gpu_frame_time <= f32::EPSILON {
+            return;
+        }
+
+        // NOTE: GPU cost grows with pixels count, that is, with the square of the scale.
+        let frame_time_ratio = dynamic_resolution.target_frame_time / gpu_frame_time;
+        let desired_render_scale = self.current_render_scale * frame_time_ratio.sqrt();
+        let render_scale = self.clamp_render_scale(
+            self.current_render_scale + (desired_render_scale - self.current_render_scale) * 0.25,
+        );
+
+        if (render_scale - self.current_render_scale).abs() >= Self::DYNAMIC_RESOLUTION_STEP {
+            self.current_render_scale = render_scale;
+        }
+    }
+
+    pub(crate) fn get_scaled_extent(extent: Extent2D, render_scale: f32) -> Extent2D {
+        Extent2D {
+            width: ((extent.width as f32 * render_scale) as u32).max(1),
+            height: ((extent.height as f32 * render_scale) as u32).max(1),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn take_is_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.is_dirty)
+    }
+
+    fn clamp_render_scale(&self, render_scale: f32) -> f32 {
+        match self.dynamic_resolution {
+            Some(dynamic_resolution) => render_scale.clamp(
+                dynamic_resolution.min_render_scale,
+                dynamic_resolution.max_render_scale,
+            ),
+            None => self.render_scale,
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use bevy_ecs::resource::Resource;
+
+#[derive(Default, Resource)]
+#[non_exhaustive]
+pub struct RenderStatistics {
+    pub instances_count: usize,
+    pub static_instances_count: usize,
+    pub materials_count: usize,
+    pub deduplicated_materials_count: usize,
+    /// Totals of the defragmentation since the start.
+    pub moved_allocations_count: usize,
+    pub moved_bytes_count: u64,
+    /// Lookups of freed buffers since the start, they're detected in debug builds only.
+    pub stale_buffer_accesses_count: u64,
+}
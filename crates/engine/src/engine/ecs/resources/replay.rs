@@ -0,0 +1,145 @@
+use std::path::{Path, PathBuf};
+
+use bevy_ecs::resource::Resource;
+use serde::{Deserialize, Serialize};
+use winit::keyboard::KeyCode;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum ReplayInputEvent {
+    KeyPressed(KeyCode),
+    KeyReleased(KeyCode),
+    MouseMoved { x: f32, y: f32 },
+}
+
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    pub delta_time: f32,
+    pub input_events: Vec<ReplayInputEvent>,
+}
+
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct ReplayRecording {
+    pub frames: Vec<ReplayFrame>,
+}
+
+impl ReplayRecording {
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let content = toml::ser::to_string(self).map_err(std::io::Error::other)?;
+
+        std::fs::write(path, content)
+    }
+
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+
+        toml::de::from_str(content.as_str()).map_err(std::io::Error::other)
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReplayMode {
+    #[default]
+    Disabled,
+    Recording,
+    Playback,
+}
+
+/// Records input events and frame deltas, so a session can be played back frame-exactly.
+#[derive(Default, Resource)]
+pub struct Replay {
+    mode: ReplayMode,
+    path: Option<PathBuf>,
+    recording: ReplayRecording,
+    current_frame: ReplayFrame,
+    playback_frame_index: usize,
+    fixed_delta_time: Option<f32>,
+}
+
+impl Replay {
+    pub fn get_mode(&self) -> ReplayMode {
+        self.mode
+    }
+
+    pub fn is_playing_back(&self) -> bool {
+        self.mode == ReplayMode::Playback
+    }
+
+    /// In playback every frame advances by it instead of the recorded delta.
+    pub fn set_fixed_delta_time(&mut self, fixed_delta_time: Option<f32>) {
+        self.fixed_delta_time = fixed_delta_time;
+    }
+
+    /// Recording is saved into `path` once it's stopped.
+    pub fn start_recording(&mut self, path: impl Into<PathBuf>) {
+        self.mode = ReplayMode::Recording;
+        self.path = Some(path.into());
+        self.recording = Default::default();
+        self.current_frame = Default::default();
+    }
+
+    pub fn stop_recording(&mut self) -> ReplayRecording {
+        if self.mode != ReplayMode::Recording {
+            return Default::default();
+        }
+        self.mode = ReplayMode::Disabled;
+
+        let recording = std::mem::take(&mut self.recording);
+        if let Some(path) = self.path.take()
+            && let Err(error) = recording.save(&path)
+        {
+            println!("Failed to save replay to {}: {}", path.display(), error);
+        }
+
+        recording
+    }
+
+    pub fn start_playback(&mut self, path: impl Into<PathBuf>) -> std::io::Result<()> {
+        let path = path.into();
+        let recording = ReplayRecording::load(&path)?;
+
+        self.play(recording);
+        self.path = Some(path);
+
+        Ok(())
+    }
+
+    pub fn play(&mut self, recording: ReplayRecording) {
+        self.mode = ReplayMode::Playback;
+        self.path = None;
+        self.recording = recording;
+        self.playback_frame_index = Default::default();
+    }
+
+    #[inline(always)]
+    pub(crate) fn record_input_event(&mut self, input_event: ReplayInputEvent) {
+        if self.mode == ReplayMode::Recording {
+            self.current_frame.input_events.push(input_event);
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn end_recorded_frame(&mut self, delta_time: f32) {
+        let mut frame = std::mem::take(&mut self.current_frame);
+        frame.delta_time = delta_time;
+
+        self.recording.frames.push(frame);
+    }
+
+    /// Returns `None` once the recording is over, playback is stopped then.
+    pub(crate) fn next_played_back_frame(&mut self) -> Option<ReplayFrame> {
+        let Some(frame) = self.recording.frames.get(self.playback_frame_index) else {
+            println!("Replay playback is finished.");
+            self.mode = ReplayMode::Disabled;
+
+            return None;
+        };
+        self.playback_frame_index += 1;
+
+        let mut frame = frame.clone();
+        if let Some(fixed_delta_time) = self.fixed_delta_time {
+            frame.delta_time = fixed_delta_time;
+        }
+
+        Some(frame)
+    }
+}
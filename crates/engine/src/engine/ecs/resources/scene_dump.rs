@@ -0,0 +1,165 @@
+use std::fmt::Write;
+
+use bevy_ecs::entity::Entity;
+
+use crate::engine::components::local_transform::LocalTransform;
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum SceneDumpFormat {
+    /// Indented hierarchy, one entity per line.
+    #[default]
+    Tree,
+    Json,
+}
+
+impl SceneDumpFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "tree" => Some(Self::Tree),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Entity of the dumped hierarchy, resources are referred by their indices in the pools.
+#[derive(Clone, Debug)]
+pub struct SceneDumpNode {
+    pub entity: Entity,
+    pub name: String,
+    pub transform: Option<LocalTransform>,
+    pub mesh_buffer_index: Option<u32>,
+    pub material_index: Option<u32>,
+    /// Components of `TypeRegistry` with their inline TOML.
+    pub components: Vec<(String, Option<String>)>,
+    pub children: Vec<SceneDumpNode>,
+}
+
+pub(crate) fn format_scene_dump(nodes: &[SceneDumpNode], format: SceneDumpFormat) -> String {
+    let mut output = String::new();
+    match format {
+        SceneDumpFormat::Tree => nodes
+            .iter()
+            .for_each(|node| write_tree_node(&mut output, node, Default::default())),
+        SceneDumpFormat::Json => write_json_nodes(&mut output, nodes),
+    }
+
+    output
+}
+
+fn write_tree_node(output: &mut String, node: &SceneDumpNode, depth: usize) {
+    _ = write!(
+        output,
+        "{:indent$}{} [{}]",
+        "",
+        node.name,
+        node.entity,
+        indent = depth * 2
+    );
+    if let Some(transform) = node.transform {
+        _ = write!(
+            output,
+            " | Position: {} | Rotation: {} | Scale: {}",
+            transform.local_position, transform.local_rotation, transform.local_scale
+        );
+    }
+    if let Some(mesh_buffer_index) = node.mesh_buffer_index {
+        _ = write!(output, " | Mesh: {}", mesh_buffer_index);
+    }
+    if let Some(material_index) = node.material_index {
+        _ = write!(output, " | Material: {}", material_index);
+    }
+    for (name, text) in node.components.iter() {
+        _ = write!(output, " | {}: {}", name, text.as_deref().unwrap_or("?"));
+    }
+    _ = writeln!(output);
+
+    node.children
+        .iter()
+        .for_each(|child_node| write_tree_node(output, child_node, depth + 1));
+}
+
+fn write_json_nodes(output: &mut String, nodes: &[SceneDumpNode]) {
+    output.push('[');
+    for (node_index, node) in nodes.iter().enumerate() {
+        if node_index > Default::default() {
+            output.push(',');
+        }
+
+        _ = write!(
+            output,
+            "{{\"entity\":\"{}\",\"name\":\"{}\"",
+            node.entity,
+            escape_json(&node.name)
+        );
+        if let Some(transform) = node.transform {
+            let position = transform.local_position;
+            let rotation = transform.local_rotation;
+            let scale = transform.local_scale;
+            _ = write!(
+                output,
+                ",\"transform\":{{\"position\":[{},{},{}],\"rotation\":[{},{},{},{}],\"scale\":[{},{},{}]}}",
+                position.x,
+                position.y,
+                position.z,
+                rotation.x,
+                rotation.y,
+                rotation.z,
+                rotation.w,
+                scale.x,
+                scale.y,
+                scale.z
+            );
+        }
+        if let Some(mesh_buffer_index) = node.mesh_buffer_index {
+            _ = write!(output, ",\"mesh\":{}", mesh_buffer_index);
+        }
+        if let Some(material_index) = node.material_index {
+            _ = write!(output, ",\"material\":{}", material_index);
+        }
+        if !node.components.is_empty() {
+            output.push_str(",\"components\":{");
+            for (component_index, (name, text)) in node.components.iter().enumerate() {
+                if component_index > Default::default() {
+                    output.push(',');
+                }
+                match text {
+                    Some(text) => {
+                        _ = write!(
+                            output,
+                            "\"{}\":\"{}\"",
+                            escape_json(name),
+                            escape_json(text)
+                        )
+                    }
+                    None => _ = write!(output, "\"{}\":null", escape_json(name)),
+                }
+            }
+            output.push('}');
+        }
+        output.push_str(",\"children\":");
+        write_json_nodes(output, &node.children);
+        output.push('}');
+    }
+    output.push(']');
+}
+
+fn escape_json(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for character in value.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            character if character.is_control() => {
+                _ = write!(escaped, "\\u{:04x}", character as u32);
+            }
+            character => escaped.push(character),
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,583 @@
+use std::{
+    cell::RefCell,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use bevy_ecs::{entity::Entity, name::Name, resource::Resource, world::World};
+use math::{Quat, Vec3};
+use mlua::{FromLua, Function, IntoLua, Lua, Table, UserData, UserDataRef, Value, Variadic};
+
+use crate::engine::{
+    components::{
+        local_transform::LocalTransform,
+        script_vars::{ScriptValue, ScriptVars},
+    },
+    resources::Console,
+};
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ScriptError {
+    Io(std::io::Error),
+    Lua(mlua::Error),
+}
+
+impl Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{}", error),
+            Self::Lua(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl From<std::io::Error> for ScriptError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<mlua::Error> for ScriptError {
+    fn from(error: mlua::Error) -> Self {
+        Self::Lua(error)
+    }
+}
+
+/// Entity as scripts see it, it can't be made from a number, so scripts reach only existing entities.
+#[derive(Clone, Copy)]
+struct ScriptEntity(Entity);
+
+impl UserData for ScriptEntity {}
+
+impl IntoLua for ScriptValue {
+    fn into_lua(self, lua: &Lua) -> mlua::Result<Value> {
+        Ok(match self {
+            Self::Bool(value) => Value::Boolean(value),
+            Self::Number(value) => Value::Number(value),
+            Self::String(value) => Value::String(lua.create_string(value)?),
+        })
+    }
+}
+
+impl FromLua for ScriptValue {
+    fn from_lua(value: Value, _: &Lua) -> mlua::Result<Self> {
+        match value {
+            Value::Boolean(value) => Ok(Self::Bool(value)),
+            Value::Integer(value) => Ok(Self::Number(value as _)),
+            Value::Number(value) => Ok(Self::Number(value)),
+            Value::String(value) => Ok(Self::String(value.to_str()?.to_string())),
+            _ => Err(mlua::Error::runtime(std::format!(
+                "Script vars can't hold {}",
+                value.type_name()
+            ))),
+        }
+    }
+}
+
+struct Script {
+    path: PathBuf,
+    environment: Table,
+    /// Sequences of callbacks by the event names.
+    subscriptions: Table,
+    is_failed: bool,
+}
+
+impl Script {
+    fn update(&self, delta_time: f32, events: &[ScriptEvent]) -> mlua::Result<()> {
+        for event in events {
+            if let Some(callbacks) = self
+                .subscriptions
+                .get::<Option<Table>>(event.name.as_str())?
+            {
+                for callback in callbacks.sequence_values::<Function>() {
+                    callback?.call::<()>(Variadic::from_iter(event.arguments.iter().cloned()))?;
+                }
+            }
+        }
+
+        if let Some(on_update) = self.environment.get::<Option<Function>>("on_update")? {
+            on_update.call::<()>(delta_time)?;
+        }
+
+        Ok(())
+    }
+}
+
+struct ScriptEvent {
+    name: String,
+    arguments: Vec<ScriptValue>,
+}
+
+/// Lua scripts for gameplay iteration without recompiling the game plugin.
+/// Every script has its own environment with these globals:
+/// - `on_update(delta_time)`, defined by the script, it's called every world update;
+/// - `subscribe(event_name, callback)`, the callback gets the arguments of the event;
+/// - `world`, the whitelisted access to the entities, it's available only in the callbacks:
+///   `find(name)`, `get_position`/`set_position`, `get_rotation`/`set_rotation`, `get_scale`/`set_scale`,
+///   `get_var`/`set_var` of `ScriptVars` and `log(text)` into the console.
+///
+/// A script that raises an error is stopped until it's loaded again.
+#[derive(Resource)]
+pub struct ScriptHost {
+    lua: Lua,
+    scripts: Vec<Script>,
+    events: Vec<ScriptEvent>,
+}
+
+impl Default for ScriptHost {
+    fn default() -> Self {
+        Self {
+            lua: Lua::new(),
+            scripts: Default::default(),
+            events: Default::default(),
+        }
+    }
+}
+
+impl ScriptHost {
+    /// Arguments are the command name and its arguments.
+    pub const CONSOLE_COMMAND_EVENT: &str = "console_command";
+    /// Arguments are the cvar name and its new value.
+    pub const CVAR_CHANGED_EVENT: &str = "cvar_changed";
+
+    /// Runs the script, the loaded one of the same path is replaced only if it succeeds.
+    pub fn load_script(&mut self, path: impl AsRef<Path>) -> Result<(), ScriptError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)?;
+
+        let environment: Table = self
+            .lua
+            .load("return setmetatable({}, { __index = _G })")
+            .eval()?;
+        let subscriptions = self.lua.create_table()?;
+        let callbacks = subscriptions.clone();
+        environment.set(
+            "subscribe",
+            self.lua
+                .create_function(move |lua, (name, callback): (String, Function)| {
+                    let event_callbacks = match callbacks.get::<Option<Table>>(name.as_str())? {
+                        Some(event_callbacks) => event_callbacks,
+                        None => {
+                            let event_callbacks = lua.create_table()?;
+                            callbacks.set(name, event_callbacks.clone())?;
+
+                            event_callbacks
+                        }
+                    };
+
+                    event_callbacks.push(callback)
+                })?,
+        )?;
+
+        self.lua
+            .load(source)
+            .set_name(path.display().to_string())
+            .set_environment(environment.clone())
+            .exec()?;
+
+        let script = Script {
+            path: path.to_path_buf(),
+            environment,
+            subscriptions,
+            is_failed: false,
+        };
+        match self
+            .scripts
+            .iter_mut()
+            .find(|loaded_script| loaded_script.path == path)
+        {
+            Some(loaded_script) => *loaded_script = script,
+            None => self.scripts.push(script),
+        }
+
+        Ok(())
+    }
+
+    /// Returns `false` if the script isn't loaded.
+    pub fn unload_script(&mut self, path: impl AsRef<Path>) -> bool {
+        let path = path.as_ref();
+        let scripts_count = self.scripts.len();
+        self.scripts.retain(|script| script.path != path);
+
+        self.scripts.len() != scripts_count
+    }
+
+    /// Loads the scripts from their files again, returns the ones that failed, they keep running as they were.
+    pub fn reload_scripts(&mut self) -> Vec<(PathBuf, ScriptError)> {
+        let paths: Vec<_> = self.get_script_paths().map(Path::to_path_buf).collect();
+
+        paths
+            .into_iter()
+            .filter_map(|path| self.load_script(&path).err().map(|error| (path, error)))
+            .collect()
+    }
+
+    pub fn get_script_paths(&self) -> impl Iterator<Item = &Path> {
+        self.scripts.iter().map(|script| script.path.as_path())
+    }
+
+    /// Callbacks subscribed to the name get the arguments at the next world update.
+    pub fn send_event(&mut self, name: impl Into<String>, arguments: Vec<ScriptValue>) {
+        if self.scripts.is_empty() {
+            return;
+        }
+
+        self.events.push(ScriptEvent {
+            name: name.into(),
+            arguments,
+        });
+    }
+
+    /// Returns errors of the scripts that were stopped.
+    pub(crate) fn update(&mut self, world: &mut World, delta_time: f32) -> Vec<String> {
+        let events = std::mem::take(&mut self.events);
+        let mut errors = Vec::new();
+        if self.scripts.is_empty() {
+            return errors;
+        }
+
+        let lua = &self.lua;
+        let scripts = &mut self.scripts;
+        let world_cell = &RefCell::new(world);
+        // NOTE: Scoped functions borrow the world only during the call, they're invalidated after it.
+        let result =
+            lua.scope(|scope| {
+                let world_table = lua.create_table()?;
+                world_table.set(
+                    "find",
+                    scope.create_function(move |_, name: String| {
+                        let mut world = world_cell.borrow_mut();
+                        let world: &mut World = &mut world;
+                        let entity = world
+                            .query::<(Entity, &Name)>()
+                            .iter(world)
+                            .find(|(_, entity_name)| entity_name.as_str() == name)
+                            .map(|(entity, _)| ScriptEntity(entity));
+
+                        Ok(entity)
+                    })?,
+                )?;
+                world_table.set(
+                    "get_position",
+                    scope.create_function(move |_, entity: UserDataRef<ScriptEntity>| {
+                        let position = Self::get_local_transform(&world_cell.borrow(), entity.0)?
+                            .local_position;
+
+                        Ok((position.x, position.y, position.z))
+                    })?,
+                )?;
+                world_table.set(
+                    "set_position",
+                    scope.create_function(
+                        move |_, (entity, x, y, z): (UserDataRef<ScriptEntity>, f32, f32, f32)| {
+                            Self::set_local_transform(
+                                &mut world_cell.borrow_mut(),
+                                entity.0,
+                                |local_transform| {
+                                    local_transform.local_position = Vec3::new(x, y, z);
+                                },
+                            )
+                        },
+                    )?,
+                )?;
+                world_table.set(
+                    "get_rotation",
+                    scope.create_function(move |_, entity: UserDataRef<ScriptEntity>| {
+                        let rotation = Self::get_local_transform(&world_cell.borrow(), entity.0)?
+                            .local_rotation;
+
+                        Ok((rotation.x, rotation.y, rotation.z, rotation.w))
+                    })?,
+                )?;
+                world_table.set(
+                    "set_rotation",
+                    scope.create_function(
+                        move |_,
+                              (entity, x, y, z, w): (
+                            UserDataRef<ScriptEntity>,
+                            f32,
+                            f32,
+                            f32,
+                            f32,
+                        )| {
+                            Self::set_local_transform(
+                                &mut world_cell.borrow_mut(),
+                                entity.0,
+                                |local_transform| {
+                                    local_transform.local_rotation =
+                                        Quat::from_xyzw(x, y, z, w).normalize();
+                                },
+                            )
+                        },
+                    )?,
+                )?;
+                world_table.set(
+                    "get_scale",
+                    scope.create_function(move |_, entity: UserDataRef<ScriptEntity>| {
+                        let scale =
+                            Self::get_local_transform(&world_cell.borrow(), entity.0)?.local_scale;
+
+                        Ok((scale.x, scale.y, scale.z))
+                    })?,
+                )?;
+                world_table.set(
+                    "set_scale",
+                    scope.create_function(
+                        move |_, (entity, x, y, z): (UserDataRef<ScriptEntity>, f32, f32, f32)| {
+                            Self::set_local_transform(
+                                &mut world_cell.borrow_mut(),
+                                entity.0,
+                                |local_transform| {
+                                    local_transform.local_scale = Vec3::new(x, y, z);
+                                },
+                            )
+                        },
+                    )?,
+                )?;
+                world_table.set(
+                    "get_var",
+                    scope.create_function(
+                        move |_, (entity, name): (UserDataRef<ScriptEntity>, String)| {
+                            let value = world_cell
+                                .borrow()
+                                .get::<ScriptVars>(entity.0)
+                                .and_then(|script_vars| script_vars.get(&name).cloned());
+
+                            Ok(value)
+                        },
+                    )?,
+                )?;
+                world_table.set(
+                    "set_var",
+                    scope.create_function(
+                        move |_,
+                              (entity, name, value): (
+                            UserDataRef<ScriptEntity>,
+                            String,
+                            Option<ScriptValue>,
+                        )| {
+                            let mut world = world_cell.borrow_mut();
+                            let mut entity_world_mut = world
+                                .get_entity_mut(entity.0)
+                                .map_err(mlua::Error::external)?;
+                            if !entity_world_mut.contains::<ScriptVars>() {
+                                entity_world_mut.insert(ScriptVars::default());
+                            }
+                            let mut script_vars = entity_world_mut.get_mut::<ScriptVars>().unwrap();
+                            match value {
+                                Some(value) => script_vars.set(name, value),
+                                None => {
+                                    script_vars.remove(&name);
+                                }
+                            }
+
+                            Ok(())
+                        },
+                    )?,
+                )?;
+                world_table.set(
+                    "log",
+                    scope.create_function(move |_, text: String| {
+                        world_cell.borrow_mut().resource_mut::<Console>().log(text);
+
+                        Ok(())
+                    })?,
+                )?;
+                lua.globals().set("world", world_table)?;
+
+                for script in scripts.iter_mut().filter(|script| !script.is_failed) {
+                    if let Err(error) = script.update(delta_time, &events) {
+                        script.is_failed = true;
+                        errors.push(std::format!(
+                            "Script {} is stopped: {}",
+                            script.path.display(),
+                            error
+                        ));
+                    }
+                }
+
+                Ok(())
+            });
+        if let Err(error) = result {
+            errors.push(std::format!("Failed to run scripts: {}", error));
+        }
+
+        errors
+    }
+
+    fn get_local_transform(world: &World, entity: Entity) -> mlua::Result<LocalTransform> {
+        world.get::<LocalTransform>(entity).copied().ok_or_else(|| {
+            mlua::Error::runtime(std::format!("Entity {} has no LocalTransform", entity))
+        })
+    }
+
+    fn set_local_transform(
+        world: &mut World,
+        entity: Entity,
+        set: impl FnOnce(&mut LocalTransform),
+    ) -> mlua::Result<()> {
+        let mut local_transform = world.get_mut::<LocalTransform>(entity).ok_or_else(|| {
+            mlua::Error::runtime(std::format!("Entity {} has no LocalTransform", entity))
+        })?;
+        set(&mut local_transform);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_temp_path(file_name: &str) -> PathBuf {
+        std::env::temp_dir().join(std::format!("{}-{file_name}", std::process::id()))
+    }
+
+    fn load_script(
+        script_host: &mut ScriptHost,
+        file_name: &str,
+        source: &str,
+    ) -> Result<(), ScriptError> {
+        let script_path = get_temp_path(file_name);
+        std::fs::write(&script_path, source).unwrap();
+
+        let result = script_host.load_script(&script_path);
+        std::fs::remove_file(&script_path).unwrap();
+
+        result
+    }
+
+    fn spawn_player(world: &mut World) -> Entity {
+        world
+            .spawn((
+                Name::new("player"),
+                LocalTransform {
+                    local_position: Vec3::new(1.0, 2.0, 3.0),
+                    ..LocalTransform::IDENTITY
+                },
+            ))
+            .id()
+    }
+
+    #[test]
+    fn local_transform_is_read_and_written_through_world() {
+        let mut world = World::new();
+        let player_entity = spawn_player(&mut world);
+        let mut script_host = ScriptHost::default();
+        load_script(
+            &mut script_host,
+            "local_transform.lua",
+            r#"
+            function on_update(delta_time)
+                local player = world.find("player")
+                local x, y, z = world.get_position(player)
+                world.set_position(player, x + delta_time, y * 2, z)
+                world.set_scale(player, 2, 2, 2)
+            end
+            "#,
+        )
+        .unwrap();
+
+        let errors = script_host.update(&mut world, 1.0);
+
+        assert!(errors.is_empty(), "{:?}", errors);
+        let local_transform = world.get::<LocalTransform>(player_entity).unwrap();
+        assert_eq!(local_transform.local_position, Vec3::new(2.0, 4.0, 3.0));
+        assert_eq!(local_transform.local_scale, Vec3::splat(2.0));
+        assert_eq!(local_transform.local_rotation, Quat::IDENTITY);
+    }
+
+    #[test]
+    fn components_outside_of_whitelist_are_rejected() {
+        let mut world = World::new();
+        let player_entity = spawn_player(&mut world);
+        world.entity_mut(player_entity).remove::<LocalTransform>();
+        let mut script_host = ScriptHost::default();
+        load_script(
+            &mut script_host,
+            "whitelist.lua",
+            r#"
+            function on_update(delta_time)
+                world.get_component(world.find("player"), "Camera")
+            end
+            "#,
+        )
+        .unwrap();
+        load_script(
+            &mut script_host,
+            "missing_local_transform.lua",
+            r#"
+            function on_update(delta_time)
+                world.set_position(world.find("player"), 0, 0, 0)
+            end
+            "#,
+        )
+        .unwrap();
+
+        let errors = script_host.update(&mut world, 1.0);
+
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors[0].contains("get_component"), "{}", errors[0]);
+        assert!(errors[1].contains("has no LocalTransform"), "{}", errors[1]);
+        assert!(!world.entity(player_entity).contains::<LocalTransform>());
+        // NOTE: Stopped scripts don't run until they're loaded again.
+        assert!(script_host.update(&mut world, 1.0).is_empty());
+    }
+
+    #[test]
+    fn script_that_fails_to_load_is_reported() {
+        let mut script_host = ScriptHost::default();
+
+        let result = load_script(&mut script_host, "syntax_error.lua", "function on_update(");
+        assert!(matches!(result, Err(ScriptError::Lua(_))));
+        let result = load_script(&mut script_host, "runtime_error.lua", "error('broken')");
+        assert!(matches!(result, Err(ScriptError::Lua(_))));
+        let result = script_host.load_script(get_temp_path("missing.lua"));
+        assert!(matches!(result, Err(ScriptError::Io(_))));
+
+        assert_eq!(script_host.get_script_paths().count(), 0);
+    }
+
+    #[test]
+    fn sent_event_reaches_subscribed_callback() {
+        let mut world = World::new();
+        let player_entity = spawn_player(&mut world);
+        let mut script_host = ScriptHost::default();
+        load_script(
+            &mut script_host,
+            "events.lua",
+            r#"
+            subscribe("damage", function(amount, source)
+                local player = world.find("player")
+                world.set_var(player, "health", 100 - amount)
+                world.set_var(player, "source", source)
+            end)
+            "#,
+        )
+        .unwrap();
+
+        script_host.send_event(
+            "damage",
+            vec![
+                ScriptValue::Number(25.0),
+                ScriptValue::String("trap".to_string()),
+            ],
+        );
+        script_host.send_event("heal", vec![ScriptValue::Number(50.0)]);
+        let errors = script_host.update(&mut world, 1.0);
+
+        assert!(errors.is_empty(), "{:?}", errors);
+        let script_vars = world.get::<ScriptVars>(player_entity).unwrap();
+        assert_eq!(script_vars.get("health"), Some(&ScriptValue::Number(75.0)));
+        assert_eq!(
+            script_vars.get("source"),
+            Some(&ScriptValue::String("trap".to_string()))
+        );
+
+        // NOTE: Events are delivered once.
+        world.entity_mut(player_entity).remove::<ScriptVars>();
+        assert!(script_host.update(&mut world, 1.0).is_empty());
+        assert!(!world.entity(player_entity).contains::<ScriptVars>());
+    }
+}
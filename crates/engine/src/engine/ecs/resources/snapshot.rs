@@ -0,0 +1,299 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use ahash::{AHashMap, AHashSet};
+use bevy_ecs::{
+    entity::Entity,
+    hierarchy::{ChildOf, Children},
+    name::Name,
+    resource::Resource,
+    world::World,
+};
+use math::{Quat, Vec3};
+use serde::{Deserialize, Serialize};
+
+use crate::engine::{
+    components::{
+        loaded_model::LoadedModel, local_transform::LocalTransform, persistent::Persistent,
+    },
+    ecs::physics::{PhysicsManager, RigidBody, RigidBodyState},
+    events::LoadModelEvent,
+    resources::TypeRegistry,
+};
+
+pub enum SnapshotRequest {
+    Save(PathBuf),
+    Load(PathBuf),
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SnapshotError {
+    Io(std::io::Error),
+    Encoding(bincode::Error),
+    UnsupportedVersion(u32),
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{}", error),
+            Self::Encoding(error) => write!(f, "Invalid snapshot: {}", error),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "Unsupported snapshot version: {}", version)
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for SnapshotError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<bincode::Error> for SnapshotError {
+    fn from(error: bincode::Error) -> Self {
+        Self::Encoding(error)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotTransform {
+    position: [f32; 3],
+    rotation: [f32; 4],
+    scale: [f32; 3],
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotEntity {
+    id: u64,
+    parent_id: Option<u64>,
+    name: Option<String>,
+    transform: Option<SnapshotTransform>,
+    rigid_body_state: Option<RigidBodyState>,
+    model_path: Option<PathBuf>,
+    /// Registered components by their names.
+    components: Vec<(String, Vec<u8>)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotData {
+    version: u32,
+    entities: Vec<SnapshotEntity>,
+}
+
+/// Save games of the dynamic state, unlike scene files only `Persistent` entities are saved:
+/// transforms, rigid bodies, loaded models and components of `TypeRegistry`.
+/// Entities are written in the order of their ids, so the same state gives the same bytes.
+#[derive(Default, Resource)]
+pub struct Snapshots {
+    requests: Vec<SnapshotRequest>,
+}
+
+impl Snapshots {
+    const VERSION: u32 = 1;
+
+    /// Saved at the next world update.
+    pub fn request_save(&mut self, path: impl AsRef<Path>) {
+        self.requests
+            .push(SnapshotRequest::Save(path.as_ref().to_path_buf()));
+    }
+
+    /// Restored at the next world update.
+    pub fn request_load(&mut self, path: impl AsRef<Path>) {
+        self.requests
+            .push(SnapshotRequest::Load(path.as_ref().to_path_buf()));
+    }
+
+    pub(crate) fn take_requests(&mut self) -> Vec<SnapshotRequest> {
+        std::mem::take(&mut self.requests)
+    }
+
+    pub fn save(&self, world: &World) -> Result<Vec<u8>, SnapshotError> {
+        let mut persistent_entities: Vec<_> = world
+            .try_query::<(Entity, &Persistent)>()
+            .map(|mut persistent_query| {
+                persistent_query
+                    .iter(world)
+                    .map(|(entity, persistent)| (persistent.id, entity))
+                    .collect()
+            })
+            .unwrap_or_default();
+        persistent_entities.sort_unstable_by_key(|(id, _)| *id);
+        let ids: AHashMap<Entity, u64> = persistent_entities
+            .iter()
+            .map(|&(id, entity)| (entity, id))
+            .collect();
+
+        let type_registry = world.resource::<TypeRegistry>();
+        let physics_manager = world.get_resource::<PhysicsManager>();
+        let mut entities = Vec::with_capacity(persistent_entities.len());
+        for (id, entity) in persistent_entities {
+            let entity_ref = world.entity(entity);
+
+            let mut components = Vec::new();
+            for (name, registration) in type_registry.iter() {
+                if let Some(bytes) = registration.serialize(&entity_ref) {
+                    components.push((name.to_string(), bytes?));
+                }
+            }
+
+            entities.push(SnapshotEntity {
+                id,
+                parent_id: entity_ref
+                    .get::<ChildOf>()
+                    .and_then(|child_of| ids.get(&child_of.parent()).copied()),
+                name: entity_ref
+                    .get::<Name>()
+                    .map(|name| name.as_str().to_string()),
+                transform: entity_ref.get::<LocalTransform>().map(|local_transform| {
+                    SnapshotTransform {
+                        position: local_transform.local_position.to_array(),
+                        rotation: local_transform.local_rotation.to_array(),
+                        scale: local_transform.local_scale.to_array(),
+                    }
+                }),
+                rigid_body_state: entity_ref.get::<RigidBody>().zip(physics_manager).map(
+                    |(&rigid_body, physics_manager)| {
+                        physics_manager.get_rigid_body_state(rigid_body)
+                    },
+                ),
+                model_path: entity_ref
+                    .get::<LoadedModel>()
+                    .map(|loaded_model| loaded_model.path.clone()),
+                components,
+            });
+        }
+
+        let snapshot_data = SnapshotData {
+            version: Self::VERSION,
+            entities,
+        };
+
+        Ok(bincode::serialize(&snapshot_data)?)
+    }
+
+    /// Matches `Persistent` entities by their ids, missing ones are spawned and the ones
+    /// that aren't in the snapshot are despawned. Models are loaded again if their paths differ.
+    pub fn load(&self, world: &mut World, bytes: &[u8]) -> Result<(), SnapshotError> {
+        let snapshot_data: SnapshotData = bincode::deserialize(bytes)?;
+        if snapshot_data.version != Self::VERSION {
+            return Err(SnapshotError::UnsupportedVersion(snapshot_data.version));
+        }
+
+        world.resource_scope::<TypeRegistry, _>(|world, type_registry| {
+            Self::apply_snapshot_data(world, &type_registry, snapshot_data)
+        })
+    }
+
+    fn apply_snapshot_data(
+        world: &mut World,
+        type_registry: &TypeRegistry,
+        snapshot_data: SnapshotData,
+    ) -> Result<(), SnapshotError> {
+        let existing_entities: AHashMap<u64, Entity> = world
+            .query::<(Entity, &Persistent)>()
+            .iter(world)
+            .map(|(entity, persistent)| (persistent.id, entity))
+            .collect();
+        let saved_ids: AHashSet<u64> = snapshot_data
+            .entities
+            .iter()
+            .map(|snapshot_entity| snapshot_entity.id)
+            .collect();
+
+        // NOTE: Children are despawned with their parents, so some of them could be gone already.
+        for (id, &entity) in existing_entities.iter() {
+            if !saved_ids.contains(id)
+                && let Ok(entity_world_mut) = world.get_entity_mut(entity)
+            {
+                entity_world_mut.despawn();
+            }
+        }
+
+        let entities: AHashMap<u64, Entity> = snapshot_data
+            .entities
+            .iter()
+            .map(|snapshot_entity| {
+                let entity = existing_entities
+                    .get(&snapshot_entity.id)
+                    .copied()
+                    .filter(|&entity| world.get_entity(entity).is_ok())
+                    .unwrap_or_else(|| world.spawn(Persistent::new(snapshot_entity.id)).id());
+
+                (snapshot_entity.id, entity)
+            })
+            .collect();
+
+        let mut rigid_body_states = Vec::new();
+        let mut model_paths = Vec::new();
+        for snapshot_entity in snapshot_data.entities {
+            let entity = entities[&snapshot_entity.id];
+            let mut entity_world_mut = world.entity_mut(entity);
+
+            if let Some(name) = snapshot_entity.name {
+                entity_world_mut.insert(Name::new(name));
+            }
+            if let Some(transform) = snapshot_entity.transform {
+                entity_world_mut.insert(LocalTransform::new(
+                    Vec3::from_array(transform.position),
+                    Quat::from_array(transform.rotation),
+                    Vec3::from_array(transform.scale),
+                ));
+            }
+            if let Some(&parent) = snapshot_entity
+                .parent_id
+                .and_then(|parent_id| entities.get(&parent_id))
+            {
+                entity_world_mut.insert(ChildOf(parent));
+            }
+
+            for (name, bytes) in snapshot_entity.components {
+                match type_registry.get(&name) {
+                    Some(registration) => {
+                        registration.deserialize(&mut entity_world_mut, &bytes)?
+                    }
+                    None => println!("Skipping unregistered snapshot component: {}", name),
+                }
+            }
+
+            if let Some((&rigid_body, rigid_body_state)) = entity_world_mut
+                .get::<RigidBody>()
+                .zip(snapshot_entity.rigid_body_state)
+            {
+                rigid_body_states.push((rigid_body, rigid_body_state));
+            }
+
+            let loaded_model_path = entity_world_mut
+                .get::<LoadedModel>()
+                .map(|loaded_model| loaded_model.path.clone());
+            if loaded_model_path != snapshot_entity.model_path {
+                if loaded_model_path.is_some() {
+                    entity_world_mut.despawn_related::<Children>();
+                    entity_world_mut.remove::<LoadedModel>();
+                }
+                if let Some(model_path) = snapshot_entity.model_path {
+                    model_paths.push((entity, model_path));
+                }
+            }
+        }
+
+        if let Some(mut physics_manager) = world.get_resource_mut::<PhysicsManager>() {
+            for (rigid_body, rigid_body_state) in rigid_body_states {
+                physics_manager.set_rigid_body_state(rigid_body, rigid_body_state);
+            }
+        }
+
+        for (entity, model_path) in model_paths {
+            world.trigger(LoadModelEvent {
+                path: model_path,
+                parent_entity: Some(entity),
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use bevy_ecs::resource::Resource;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+pub type TaskScope<'scope> = rayon::Scope<'scope>;
+
+pub struct Task<T> {
+    receiver: Receiver<T>,
+    result: Option<T>,
+}
+
+impl<T> Task<T> {
+    pub fn is_finished(&mut self) -> bool {
+        if self.result.is_none() {
+            match self.receiver.try_recv() {
+                Ok(result) => self.result = Some(result),
+                Err(TryRecvError::Empty) => return false,
+                Err(TryRecvError::Disconnected) => panic!("Task panicked before finishing."),
+            }
+        }
+
+        true
+    }
+
+    pub fn try_take(&mut self) -> Option<T> {
+        if self.is_finished() {
+            return self.result.take();
+        }
+
+        None
+    }
+
+    /// Blocks the calling thread until the task is finished.
+    pub fn wait(mut self) -> T {
+        if let Some(result) = self.result.take() {
+            return result;
+        }
+
+        self.receiver
+            .recv()
+            .expect("Task panicked before finishing.")
+    }
+}
+
+/// Background workers for the work that doesn't fit into a frame (IO, compression, meshlets building).
+#[derive(Resource)]
+pub struct TaskPool {
+    thread_pool: ThreadPool,
+}
+
+impl TaskPool {
+    pub(crate) fn new() -> Self {
+        // NOTE: Main thread is busy with the frame, so leave one core for it.
+        let threads_count = num_cpus::get().saturating_sub(1).max(1);
+
+        let thread_pool = ThreadPoolBuilder::new()
+            .num_threads(threads_count)
+            .thread_name(|thread_index| std::format!("task_pool_{thread_index}"))
+            .build()
+            .unwrap();
+
+        Self { thread_pool }
+    }
+
+    pub fn get_threads_count(&self) -> usize {
+        self.thread_pool.current_num_threads()
+    }
+
+    pub fn spawn<T, F>(&self, task: F) -> Task<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        self.thread_pool.spawn(move || {
+            _ = sender.send(task());
+        });
+
+        Task {
+            receiver,
+            result: None,
+        }
+    }
+
+    /// Blocks until every task spawned inside the scope is finished, so tasks can borrow local data.
+    pub fn scope<'scope, R, F>(&self, scope: F) -> R
+    where
+        R: Send,
+        F: FnOnce(&TaskScope<'scope>) -> R + Send,
+    {
+        self.thread_pool.scope(scope)
+    }
+}
@@ -0,0 +1,95 @@
+use bevy_ecs::resource::Resource;
+use winit::keyboard::KeyCode;
+
+/// Text typed during the frame, for the console and text fields of the game UI.
+/// Committed text comes from key presses and IME, composition in progress stays in the preedit.
+#[derive(Default, Resource)]
+pub struct TextInput {
+    is_active: bool,
+    is_ime_enabled: bool,
+    text: String,
+    preedit: String,
+    preedit_cursor: Option<(usize, usize)>,
+    pressed_keys: Vec<KeyCode>,
+    is_control_pressed: bool,
+    is_super_pressed: bool,
+}
+
+impl TextInput {
+    #[inline(always)]
+    pub fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    /// Text field of the game has focus, IME is enabled on the window while it's active or the console is open.
+    pub fn set_active(&mut self, is_active: bool) {
+        self.is_active = is_active;
+    }
+
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+
+    /// Composition that isn't committed yet, the cursor is a byte range in it.
+    pub fn get_preedit(&self) -> (&str, Option<(usize, usize)>) {
+        (&self.preedit, self.preedit_cursor)
+    }
+
+    /// Presses of the frame with their repeats, for editing keys like `Backspace` or arrows.
+    pub fn get_pressed_keys(&self) -> &[KeyCode] {
+        &self.pressed_keys
+    }
+
+    /// `Ctrl` or, on macOS, `Cmd`.
+    pub fn is_shortcut_modifier_pressed(&self) -> bool {
+        if cfg!(target_os = "macos") {
+            self.is_super_pressed
+        } else {
+            self.is_control_pressed
+        }
+    }
+
+    /// Whether the state differs from the one the window has.
+    pub(crate) fn set_ime_enabled(&mut self, is_ime_enabled: bool) -> bool {
+        if self.is_ime_enabled == is_ime_enabled {
+            return false;
+        }
+        self.is_ime_enabled = is_ime_enabled;
+        if !is_ime_enabled {
+            self.clear_preedit();
+        }
+
+        true
+    }
+
+    pub(crate) fn push_text(&mut self, text: &str) {
+        self.text
+            .extend(text.chars().filter(|character| !character.is_control()));
+    }
+
+    pub(crate) fn set_preedit(&mut self, preedit: String, preedit_cursor: Option<(usize, usize)>) {
+        self.preedit = preedit;
+        self.preedit_cursor = preedit_cursor;
+    }
+
+    pub(crate) fn clear_preedit(&mut self) {
+        self.preedit.clear();
+        self.preedit_cursor = None;
+    }
+
+    /// Modifiers are tracked here, since `Input` doesn't get keys while the console is open.
+    pub(crate) fn process_key(&mut self, key_code: KeyCode, is_pressed: bool) {
+        match key_code {
+            KeyCode::ControlLeft | KeyCode::ControlRight => self.is_control_pressed = is_pressed,
+            KeyCode::SuperLeft | KeyCode::SuperRight => self.is_super_pressed = is_pressed,
+            _ if is_pressed => self.pressed_keys.push(key_code),
+            _ => (),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn reset(&mut self) {
+        self.text.clear();
+        self.pressed_keys.clear();
+    }
+}
@@ -0,0 +1,98 @@
+use bevy_ecs::resource::Resource;
+
+use crate::engine::resources::TextureReference;
+
+/// Samples of the albedo textures by their indices, counted by the fragment shader of the meshes.
+/// Only every `sample_interval` frame is counted, the atomics slow the fragment shader down.
+/// Textures that are never sampled are reported by `MemoryReport`, they could be evicted or downsized.
+#[derive(Resource)]
+pub struct TextureUsage {
+    is_enabled: bool,
+    /// Count of frames between the counted ones.
+    pub sample_interval: u32,
+    frames_since_sample_count: u32,
+    sampled_frames_count: u32,
+    samples_counts: Vec<u64>,
+}
+
+impl Default for TextureUsage {
+    fn default() -> Self {
+        Self {
+            is_enabled: false,
+            sample_interval: 30,
+            frames_since_sample_count: Default::default(),
+            sampled_frames_count: Default::default(),
+            samples_counts: Default::default(),
+        }
+    }
+}
+
+impl TextureUsage {
+    pub fn with_enabled(mut self, is_enabled: bool) -> Self {
+        self.set_enabled(is_enabled);
+
+        self
+    }
+
+    #[inline(always)]
+    pub fn is_enabled(&self) -> bool {
+        self.is_enabled
+    }
+
+    /// Counts are kept, so tracking could be paused.
+    pub fn set_enabled(&mut self, is_enabled: bool) {
+        self.is_enabled = is_enabled;
+        self.frames_since_sample_count = self.sample_interval;
+    }
+
+    pub fn reset(&mut self) {
+        self.sampled_frames_count = Default::default();
+        self.samples_counts.clear();
+    }
+
+    /// Count of the frames, which samples are accumulated.
+    #[inline(always)]
+    pub fn get_sampled_frames_count(&self) -> u32 {
+        self.sampled_frames_count
+    }
+
+    /// Fragments that sampled the texture in the counted frames.
+    pub fn get_samples_count(&self, texture_reference: TextureReference) -> u64 {
+        self.samples_counts
+            .get(texture_reference.get_index() as usize)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Nothing is known to be unsampled, until a frame is counted.
+    pub fn is_unsampled(&self, texture_reference: TextureReference) -> bool {
+        self.sampled_frames_count > Default::default()
+            && self.get_samples_count(texture_reference) == Default::default()
+    }
+
+    /// Returns `true`, if the current frame has to be counted.
+    pub(crate) fn begin_frame(&mut self) -> bool {
+        if !self.is_enabled {
+            return false;
+        }
+
+        self.frames_since_sample_count = self.frames_since_sample_count.saturating_add(1);
+        if self.frames_since_sample_count < self.sample_interval {
+            return false;
+        }
+        self.frames_since_sample_count = Default::default();
+
+        true
+    }
+
+    pub(crate) fn accumulate(&mut self, counters: &[u32]) {
+        if self.samples_counts.len() < counters.len() {
+            self.samples_counts
+                .resize(counters.len(), Default::default());
+        }
+        for (samples_count, &counter) in self.samples_counts.iter_mut().zip(counters) {
+            *samples_count += counter as u64;
+        }
+        self.sampled_frames_count += 1;
+    }
+}
@@ -0,0 +1,167 @@
+use bevy_ecs::resource::Resource;
+use math::{Vec3, Vec4};
+
+use crate::engine::resources::{EnvironmentMode, EnvironmentSettings};
+
+/// Colors of the sky gradient and the light at a moment of the day.
+#[derive(Clone, Copy)]
+pub struct SkyColors {
+    pub top_color: Vec4,
+    pub bottom_color: Vec4,
+    pub sun_color: Vec3,
+    pub ambient_color: Vec4,
+}
+
+/// Day cycle of the sun, it drives the direction and color of the directional light, the ambient
+/// and the sky gradient of `EnvironmentSettings`. Atmospheric scattering follows the sun by itself.
+/// Sun moves in the local frame, where Y is up, X points east and Z points north.
+#[derive(Resource)]
+pub struct TimeOfDay {
+    pub is_enabled: bool,
+    /// Hours in the range from 0 to 24, 12 is the noon.
+    hours: f32,
+    /// Hours of the day per second, it's paused with zero.
+    pub speed: f32,
+    /// Degrees, it's positive in the northern hemisphere.
+    pub latitude: f32,
+    /// Degrees of the sun from the celestial equator, 0 by the equinoxes and 23.44 by the summer solstice.
+    pub declination: f32,
+    pub day_colors: SkyColors,
+    /// Colors, while the sun is at the horizon.
+    pub sunset_colors: SkyColors,
+    pub night_colors: SkyColors,
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        Self {
+            is_enabled: false,
+            hours: 12.0,
+            speed: 0.1,
+            latitude: 45.0,
+            declination: Default::default(),
+            day_colors: SkyColors {
+                top_color: Vec4::new(0.25, 0.45, 0.85, 1.0),
+                bottom_color: Vec4::new(0.75, 0.8, 0.9, 1.0),
+                sun_color: Vec3::new(0.72, 0.72, 0.93),
+                ambient_color: Vec4::new(0.1, 0.1, 0.1, 1.0),
+            },
+            sunset_colors: SkyColors {
+                top_color: Vec4::new(0.2, 0.25, 0.5, 1.0),
+                bottom_color: Vec4::new(0.95, 0.55, 0.3, 1.0),
+                sun_color: Vec3::new(1.0, 0.5, 0.25),
+                ambient_color: Vec4::new(0.08, 0.06, 0.06, 1.0),
+            },
+            night_colors: SkyColors {
+                top_color: Vec4::new(0.01, 0.01, 0.04, 1.0),
+                bottom_color: Vec4::new(0.04, 0.05, 0.1, 1.0),
+                sun_color: Vec3::ZERO,
+                ambient_color: Vec4::new(0.02, 0.02, 0.05, 1.0),
+            },
+        }
+    }
+}
+
+impl TimeOfDay {
+    const HOURS_PER_DAY: f32 = 24.0;
+    /// Sine of the sun elevation, where the sunset colors turn into the day ones.
+    const SUNSET_ELEVATION: f32 = 0.25;
+    /// Sine of the sun elevation under the horizon, where the night begins.
+    const NIGHT_ELEVATION: f32 = -0.1;
+
+    pub fn with_hours(mut self, hours: f32) -> Self {
+        self.set_hours(hours);
+
+        self
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+
+        self
+    }
+
+    pub fn with_latitude(mut self, latitude: f32) -> Self {
+        self.latitude = latitude;
+
+        self
+    }
+
+    #[inline(always)]
+    pub fn get_hours(&self) -> f32 {
+        self.hours
+    }
+
+    /// Wraps around the day.
+    pub fn set_hours(&mut self, hours: f32) {
+        self.hours = hours.rem_euclid(Self::HOURS_PER_DAY);
+    }
+
+    pub(crate) fn advance(&mut self, delta_time: f32) {
+        self.set_hours(self.hours + self.speed * delta_time);
+    }
+
+    /// Direction to the sun, it's under the horizon at night.
+    pub fn get_sun_direction(&self) -> Vec3 {
+        let latitude = self.latitude.to_radians();
+        let declination = self.declination.to_radians();
+        let hour_angle =
+            (self.hours - Self::HOURS_PER_DAY * 0.5) / Self::HOURS_PER_DAY * std::f32::consts::TAU;
+
+        let east = -declination.cos() * hour_angle.sin();
+        let north = latitude.cos() * declination.sin()
+            - latitude.sin() * declination.cos() * hour_angle.cos();
+        let up = latitude.sin() * declination.sin()
+            + latitude.cos() * declination.cos() * hour_angle.cos();
+
+        Vec3::new(east, up, north).normalize_or_zero()
+    }
+
+    /// Night, sunset and day colors are blended by the elevation of the sun.
+    pub fn get_sky_colors(&self) -> SkyColors {
+        let elevation = self.get_sun_direction().y;
+        if elevation >= Self::SUNSET_ELEVATION {
+            return self.day_colors;
+        }
+
+        if elevation >= Default::default() {
+            Self::blend_sky_colors(
+                &self.sunset_colors,
+                &self.day_colors,
+                elevation / Self::SUNSET_ELEVATION,
+            )
+        } else {
+            Self::blend_sky_colors(
+                &self.sunset_colors,
+                &self.night_colors,
+                (elevation / Self::NIGHT_ELEVATION).min(1.0),
+            )
+        }
+    }
+
+    pub(crate) fn apply(&self, environment_settings: &mut EnvironmentSettings) {
+        let sky_colors = self.get_sky_colors();
+
+        environment_settings.sun_direction = self.get_sun_direction();
+        environment_settings.sun_color = sky_colors.sun_color;
+        environment_settings.ambient_color = sky_colors.ambient_color;
+        // NOTE: Solid color and skybox are kept, only the gradient is the sky of the cycle.
+        if let EnvironmentMode::Gradient {
+            top_color,
+            bottom_color,
+        } = &mut environment_settings.mode
+        {
+            *top_color = sky_colors.top_color;
+            *bottom_color = sky_colors.bottom_color;
+        }
+    }
+
+    fn blend_sky_colors(from: &SkyColors, to: &SkyColors, factor: f32) -> SkyColors {
+        SkyColors {
+            top_color: from.top_color.lerp(to.top_color, factor),
+            bottom_color: from.bottom_color.lerp(to.bottom_color, factor),
+            sun_color: from.sun_color.lerp(to.sun_color, factor),
+            ambient_color: from.ambient_color.lerp(to.ambient_color, factor),
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+
+use bevy_ecs::{
+    component::Component,
+    resource::Resource,
+    world::{EntityRef, EntityWorldMut},
+};
+use serde::{Serialize, de::DeserializeOwned};
+
+/// Registers components by their type names, e.g. `register_components!(type_registry, Health, Inventory)`.
+#[macro_export]
+macro_rules! register_components {
+    ($type_registry:expr, $($component:ty),+ $(,)?) => {
+        $($type_registry.register::<$component>(stringify!($component));)+
+    };
+}
+
+/// Functions of a registered component, so tools can work with it only by its name.
+pub struct ComponentRegistration {
+    contains: fn(&EntityRef) -> bool,
+    serialize: fn(&EntityRef) -> Option<bincode::Result<Vec<u8>>>,
+    deserialize: fn(&mut EntityWorldMut, &[u8]) -> bincode::Result<()>,
+    to_text: fn(&EntityRef) -> Option<String>,
+    from_text: fn(&mut EntityWorldMut, &str) -> Result<(), String>,
+    insert_default: fn(&mut EntityWorldMut),
+    remove: fn(&mut EntityWorldMut),
+}
+
+impl ComponentRegistration {
+    #[inline(always)]
+    pub fn contains(&self, entity_ref: &EntityRef) -> bool {
+        (self.contains)(entity_ref)
+    }
+
+    /// Binary form for save games, `None` if the entity doesn't have the component.
+    #[inline(always)]
+    pub fn serialize(&self, entity_ref: &EntityRef) -> Option<bincode::Result<Vec<u8>>> {
+        (self.serialize)(entity_ref)
+    }
+
+    /// Inserts the component, it replaces the current one.
+    #[inline(always)]
+    pub fn deserialize(
+        &self,
+        entity_world_mut: &mut EntityWorldMut,
+        bytes: &[u8],
+    ) -> bincode::Result<()> {
+        (self.deserialize)(entity_world_mut, bytes)
+    }
+
+    /// Inline TOML for scene files and the inspector, `None` if the entity doesn't have the component
+    /// or it has no TOML representation.
+    #[inline(always)]
+    pub fn to_text(&self, entity_ref: &EntityRef) -> Option<String> {
+        (self.to_text)(entity_ref)
+    }
+
+    /// Parses the inline TOML and inserts the component, it replaces the current one.
+    #[inline(always)]
+    pub fn from_text(
+        &self,
+        entity_world_mut: &mut EntityWorldMut,
+        text: &str,
+    ) -> Result<(), String> {
+        (self.from_text)(entity_world_mut, text)
+    }
+
+    #[inline(always)]
+    pub fn insert_default(&self, entity_world_mut: &mut EntityWorldMut) {
+        (self.insert_default)(entity_world_mut)
+    }
+
+    #[inline(always)]
+    pub fn remove(&self, entity_world_mut: &mut EntityWorldMut) {
+        (self.remove)(entity_world_mut)
+    }
+}
+
+/// Components that can be enumerated and constructed by their names: scene dumps, snapshots
+/// and the inspector. Names are written into snapshots, so they must stay the same between versions.
+#[derive(Default, Resource)]
+pub struct TypeRegistry {
+    registrations: BTreeMap<String, ComponentRegistration>,
+}
+
+impl TypeRegistry {
+    pub fn register<T: Component + Default + Serialize + DeserializeOwned>(&mut self, name: &str) {
+        self.registrations.insert(
+            name.to_string(),
+            ComponentRegistration {
+                contains: |entity_ref| entity_ref.contains::<T>(),
+                serialize: |entity_ref| entity_ref.get::<T>().map(bincode::serialize),
+                deserialize: |entity_world_mut, bytes| {
+                    entity_world_mut.insert(bincode::deserialize::<T>(bytes)?);
+
+                    Ok(())
+                },
+                to_text: |entity_ref| {
+                    entity_ref
+                        .get::<T>()
+                        .and_then(|component| toml::Value::try_from(component).ok())
+                        .map(|value| value.to_string())
+                },
+                from_text: |entity_world_mut, text| {
+                    let component = text
+                        .parse::<toml::Value>()
+                        .and_then(|value| value.try_into::<T>())
+                        .map_err(|error| error.to_string())?;
+                    entity_world_mut.insert(component);
+
+                    Ok(())
+                },
+                insert_default: |entity_world_mut| {
+                    entity_world_mut.insert(T::default());
+                },
+                remove: |entity_world_mut| {
+                    entity_world_mut.remove::<T>();
+                },
+            },
+        );
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ComponentRegistration> {
+        self.registrations.get(name)
+    }
+
+    /// Sorted by the names.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ComponentRegistration)> {
+        self.registrations
+            .iter()
+            .map(|(name, registration)| (name.as_str(), registration))
+    }
+
+    /// Registered components of the entity with their texts.
+    pub fn get_components_text(&self, entity_ref: &EntityRef) -> Vec<(String, Option<String>)> {
+        self.iter()
+            .filter(|(_, registration)| registration.contains(entity_ref))
+            .map(|(name, registration)| (name.to_string(), registration.to_text(entity_ref)))
+            .collect()
+    }
+}
@@ -19,14 +19,18 @@ use crate::engine::{
 pub struct VulkanContextResource {
     pub instance: Instance,
     pub debug_utils_messenger: Option<DebugUtilsMessengerEXT>,
-    pub surface: SurfaceKHR,
+    /// `None` for the headless engine, as well as the swapchain.
+    pub surface: Option<SurfaceKHR>,
     pub device: Device,
     pub physical_device: PhysicalDevice,
     pub allocator: Allocator,
     pub graphics_queue: Queue,
     pub transfer_queue: Queue,
     pub queue_family_index: usize,
-    pub swapchain: SwapchainKHR,
+    /// Queue of a family without graphics, post-processing is submitted to it when it's present.
+    pub compute_queue: Option<Queue>,
+    pub compute_queue_family_index: Option<usize>,
+    pub swapchain: Option<SwapchainKHR>,
     pub surface_format: SurfaceFormatKHR,
 }
 
@@ -35,9 +39,8 @@ impl VulkanContextResource {
         &self,
         allocated_image: &AllocatedImage,
         buffers_pool: &mut BuffersPool,
-        data_to_copy: *const std::ffi::c_void,
+        data_to_copy: &[u8],
         upload_context: &UploadContext,
-        size: Option<usize>,
     ) {
         let texture_metadata = allocated_image.texture_metadata;
         let command_buffer = upload_context.command_group.command_buffer;
@@ -49,20 +52,8 @@ impl VulkanContextResource {
 
         command_buffer.begin(&command_buffer_begin_info).unwrap();
 
-        let size = match size {
-            Some(size) => size,
-            None => (texture_metadata.width * texture_metadata.height * 8) as usize,
-        };
-
-        let staging_buffer_reference =
-            unsafe { &*(&buffers_pool.get_staging_buffer_reference() as *const _) };
-        unsafe {
-            buffers_pool.transfer_data_to_buffer_raw(
-                *staging_buffer_reference,
-                data_to_copy,
-                size as _,
-            );
-        }
+        let staging_buffer_reference = buffers_pool.get_staging_buffer_reference();
+        buffers_pool.transfer_slice(staging_buffer_reference, data_to_copy);
 
         transition_image(
             command_buffer,
@@ -122,7 +113,7 @@ impl VulkanContextResource {
             .command_buffer
             .copy_buffer_to_image(
                 buffers_pool
-                    .get_buffer(*staging_buffer_reference)
+                    .get_buffer(staging_buffer_reference)
                     .unwrap()
                     .buffer,
                 allocated_image.image,
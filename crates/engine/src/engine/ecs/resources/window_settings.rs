@@ -0,0 +1,119 @@
+use bevy_ecs::resource::Resource;
+use winit::keyboard::KeyCode;
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum WindowMode {
+    #[default]
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LatencyMode {
+    /// Frames are queued ahead, the GPU is never starved.
+    #[default]
+    Throughput,
+    /// Present mode that replaces queued frames and the frame start is delayed,
+    /// so the input is sampled as close to the present as possible.
+    LowLatency,
+}
+
+#[derive(Resource)]
+pub struct WindowSettings {
+    window_mode: WindowMode,
+    resolution: (u32, u32),
+    vsync: bool,
+    latency_mode: LatencyMode,
+    fullscreen_toggle_key: Option<KeyCode>,
+    is_dirty: bool,
+}
+
+impl WindowSettings {
+    pub(crate) fn new(resolution: (u32, u32), vsync: bool, latency_mode: LatencyMode) -> Self {
+        Self {
+            window_mode: Default::default(),
+            resolution,
+            vsync,
+            latency_mode,
+            fullscreen_toggle_key: Some(KeyCode::F11),
+            is_dirty: false,
+        }
+    }
+
+    pub fn get_window_mode(&self) -> WindowMode {
+        self.window_mode
+    }
+
+    pub fn get_resolution(&self) -> (u32, u32) {
+        self.resolution
+    }
+
+    pub fn is_vsync_enabled(&self) -> bool {
+        self.vsync
+    }
+
+    pub fn get_latency_mode(&self) -> LatencyMode {
+        self.latency_mode
+    }
+
+    pub fn get_fullscreen_toggle_key(&self) -> Option<KeyCode> {
+        self.fullscreen_toggle_key
+    }
+
+    pub fn set_window_mode(&mut self, window_mode: WindowMode) {
+        if self.window_mode != window_mode {
+            self.window_mode = window_mode;
+            self.is_dirty = true;
+        }
+    }
+
+    /// In windowed mode it's the size of the window, in fullscreen mode it's the video mode to pick.
+    pub fn set_resolution(&mut self, width: u32, height: u32) {
+        if self.resolution != (width, height) {
+            self.resolution = (width, height);
+            self.is_dirty = true;
+        }
+    }
+
+    pub fn set_vsync(&mut self, vsync: bool) {
+        if self.vsync != vsync {
+            self.vsync = vsync;
+            self.is_dirty = true;
+        }
+    }
+
+    pub fn set_latency_mode(&mut self, latency_mode: LatencyMode) {
+        if self.latency_mode != latency_mode {
+            self.latency_mode = latency_mode;
+            self.is_dirty = true;
+        }
+    }
+
+    /// Key is handled by the engine and never reaches `Input`, `None` disables the toggle.
+    pub fn set_fullscreen_toggle_key(&mut self, fullscreen_toggle_key: Option<KeyCode>) {
+        self.fullscreen_toggle_key = fullscreen_toggle_key;
+    }
+
+    pub fn toggle_fullscreen(&mut self) {
+        let window_mode = match self.window_mode {
+            WindowMode::Windowed => WindowMode::Borderless,
+            WindowMode::Borderless | WindowMode::Fullscreen => WindowMode::Windowed,
+        };
+
+        self.set_window_mode(window_mode);
+    }
+
+    #[inline(always)]
+    pub(crate) fn take_is_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.is_dirty)
+    }
+
+    #[inline(always)]
+    pub(crate) fn sync_resolution(&mut self, width: u32, height: u32) {
+        if self.window_mode == WindowMode::Windowed {
+            self.resolution = (width, height);
+        }
+    }
+}
@@ -58,6 +58,12 @@ impl<'w, 's> Physics<'w, 's> {
         }
     }
 
+    /// Distance along the direction to the first collider.
+    pub fn cast_ray(&self, origin: Vec3, direction: Vec3, max_distance: f32) -> Option<f32> {
+        self.physics_manager
+            .cast_ray(origin, direction, max_distance, None)
+    }
+
     pub fn create_rigid_body(&mut self, target_entity: Entity, world_position: Vec3) -> RigidBody {
         let rigid_body = self
             .physics_manager
@@ -1,2 +1,10 @@
+pub mod on_bake_impostor;
+pub mod on_console_command;
+pub mod on_cvar_changed;
+pub mod on_load_foliage_maps;
+pub mod on_load_lens_flare_textures;
 pub mod on_load_model;
+pub mod on_load_terrain;
+pub mod on_load_water_normal_map;
+pub mod on_script_events;
 pub mod on_spawn_model;
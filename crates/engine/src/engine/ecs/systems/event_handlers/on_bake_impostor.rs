@@ -0,0 +1,351 @@
+use ahash::AHashMap;
+use bevy_ecs::{
+    hierarchy::Children,
+    observer::On,
+    system::{Commands, Local, Query, Res, ResMut},
+};
+use image::{DynamicImage, ImageBuffer, Rgba};
+use math::{Vec2, Vec3};
+use vulkanite::vk::Format;
+
+use crate::engine::{
+    components::{
+        impostor::{Impostor, MeshImpostor},
+        material::{MaterialData, MaterialImpostor},
+        mesh::{Mesh, MeshData},
+    },
+    ecs::{
+        ImpostorData,
+        buffers_pool::BuffersPool,
+        materials_pool::{MaterialReference, MaterialsPool},
+        mesh_buffers_pool::MeshBuffersPool,
+        on_load_model::{
+            create_and_copy_to_buffer, upload_texture_from_image, write_materials_data,
+        },
+        textures_pool::{TextureReference, TexturesPool},
+    },
+    events::BakeImpostorEvent,
+    general::renderer::DescriptorSetHandle,
+    resources::{RendererContext, RendererResources, TaskPool, VulkanContextResource},
+};
+
+/// Mesh buffer index, material, frames per side, frame resolution and bits of the distance.
+type ImpostorKey = (u32, MaterialReference, u32, u32, u32);
+
+/// Texel of the frame, UVs of the mesh with the coverage and depth towards the viewer.
+#[derive(Clone, Copy)]
+struct FrameTexel {
+    uv: Vec2,
+    depth: f32,
+    is_covered: bool,
+}
+
+pub fn on_bake_impostor_system(
+    bake_impostor_event: On<BakeImpostorEvent>,
+    mut commands: Commands,
+    impostors_query: Query<&Impostor>,
+    children_query: Query<&Children>,
+    meshes_query: Query<&Mesh>,
+    vulkan_context: Res<VulkanContextResource>,
+    renderer_context: Res<RendererContext>,
+    renderer_resources: Res<RendererResources>,
+    mut descriptor_set_handle: ResMut<DescriptorSetHandle>,
+    mut materials_pool: ResMut<MaterialsPool>,
+    mut buffers_pool: ResMut<BuffersPool>,
+    mut textures_pool: ResMut<TexturesPool>,
+    mut mesh_buffers_pool: ResMut<MeshBuffersPool>,
+    task_pool: Res<TaskPool>,
+    mut baked_impostors: Local<AHashMap<ImpostorKey, MeshImpostor>>,
+) {
+    let impostor_entity = bake_impostor_event.impostor_entity;
+    let Ok(&impostor) = impostors_query.get(impostor_entity) else {
+        return;
+    };
+
+    materials_pool.reset_materails_to_write();
+    for mesh_entity in
+        std::iter::once(impostor_entity).chain(children_query.iter_descendants(impostor_entity))
+    {
+        let Ok(&mesh) = meshes_query.get(mesh_entity) else {
+            continue;
+        };
+
+        let impostor_key = (
+            mesh.mesh_buffer_reference.get_index(),
+            mesh.material_reference,
+            impostor.frames_per_side,
+            impostor.frame_resolution,
+            impostor.distance.to_bits(),
+        );
+        if let Some(&mesh_impostor) = baked_impostors.get(&impostor_key) {
+            commands.entity(mesh_entity).insert(mesh_impostor);
+
+            continue;
+        }
+
+        let Some(mesh_buffer) = mesh_buffers_pool.get_mesh_buffer(mesh.mesh_buffer_reference)
+        else {
+            continue;
+        };
+        let center = mesh_buffer.bounding_center;
+        let radius = mesh_buffer.bounding_radius;
+        let atlas = bake_atlas(
+            &task_pool,
+            &mesh_buffer.mesh_data,
+            center,
+            radius,
+            &impostor,
+        );
+
+        let atlas_texture_reference = upload_texture_from_image(
+            &vulkan_context,
+            &renderer_context,
+            &mut descriptor_set_handle,
+            &mut buffers_pool,
+            &mut textures_pool,
+            &atlas,
+            Format::R16G16B16A16Unorm,
+        );
+        let Some(material_reference) = write_impostor_material(
+            &mut materials_pool,
+            &mut textures_pool,
+            mesh.material_reference,
+            atlas_texture_reference,
+            &impostor,
+        ) else {
+            continue;
+        };
+
+        // NOTE: Baked impostors are shared until the exit, so they keep the source mesh and material alive
+        // and their keys are never taken by other meshes.
+        mesh_buffers_pool.retain_mesh_buffer(mesh.mesh_buffer_reference);
+        materials_pool.retain_material(mesh.material_reference);
+        materials_pool.retain_material(material_reference);
+
+        let impostor_data = ImpostorData {
+            device_address_material_data: materials_pool
+                .get_material_info(material_reference)
+                .device_adddress_material_data,
+            center: center.to_array(),
+            radius,
+            frames_per_side: impostor.frames_per_side,
+            distance: impostor.distance,
+        };
+        let impostor_buffer_reference = create_and_copy_to_buffer(
+            buffers_pool.as_mut(),
+            std::slice::from_ref(&impostor_data),
+            std::format!("impostor_{}", mesh.mesh_buffer_reference.get_index()),
+        );
+
+        let mesh_impostor = MeshImpostor {
+            device_address_impostor: buffers_pool
+                .get_buffer_info(impostor_buffer_reference)
+                .device_address,
+        };
+        baked_impostors.insert(impostor_key, mesh_impostor);
+
+        commands.entity(mesh_entity).insert(mesh_impostor);
+    }
+
+    write_materials_data(
+        buffers_pool.as_mut(),
+        &materials_pool,
+        renderer_resources.materials_data_buffer_reference,
+    );
+}
+
+/// Frames are captured by an orthographic projection over the bounding sphere, they're laid out
+/// by the octahedral mapping of their directions, the same way the mesh shader picks them.
+fn bake_atlas(
+    task_pool: &TaskPool,
+    mesh_data: &MeshData,
+    center: Vec3,
+    radius: f32,
+    impostor: &Impostor,
+) -> DynamicImage {
+    let frames_per_side = impostor.frames_per_side;
+    let frame_resolution = impostor.frame_resolution;
+
+    let mut frames = Vec::with_capacity((frames_per_side * frames_per_side) as usize);
+    frames.resize_with((frames_per_side * frames_per_side) as usize, Vec::new);
+
+    task_pool.scope(|scope| {
+        for (frame_index, frame) in frames.iter_mut().enumerate() {
+            scope.spawn(move |_| {
+                let frame_coord = Vec2::new(
+                    (frame_index as u32 % frames_per_side) as f32,
+                    (frame_index as u32 / frames_per_side) as f32,
+                );
+                let direction = oct_decode((frame_coord + 0.5) / frames_per_side as f32);
+
+                *frame = rasterize_frame(mesh_data, center, radius, direction, frame_resolution);
+            });
+        }
+    });
+
+    let atlas_size = frames_per_side * frame_resolution;
+    let mut atlas = ImageBuffer::<Rgba<u16>, Vec<u16>>::new(atlas_size, atlas_size);
+    for (frame_index, frame) in frames.into_iter().enumerate() {
+        let frame_x = (frame_index as u32 % frames_per_side) * frame_resolution;
+        let frame_y = (frame_index as u32 / frames_per_side) * frame_resolution;
+
+        for (texel_index, frame_texel) in frame.into_iter().enumerate() {
+            if !frame_texel.is_covered {
+                continue;
+            }
+
+            let x = frame_x + texel_index as u32 % frame_resolution;
+            let y = frame_y + texel_index as u32 / frame_resolution;
+            atlas.put_pixel(
+                x,
+                y,
+                Rgba([
+                    (frame_texel.uv.x * u16::MAX as f32) as u16,
+                    (frame_texel.uv.y * u16::MAX as f32) as u16,
+                    Default::default(),
+                    u16::MAX,
+                ]),
+            );
+        }
+    }
+
+    DynamicImage::ImageRgba16(atlas)
+}
+
+fn rasterize_frame(
+    mesh_data: &MeshData,
+    center: Vec3,
+    radius: f32,
+    direction: Vec3,
+    frame_resolution: u32,
+) -> Vec<FrameTexel> {
+    let up_hint = if direction.y.abs() > 0.999 {
+        Vec3::Z
+    } else {
+        Vec3::Y
+    };
+    let right = up_hint.cross(direction).normalize();
+    let up = direction.cross(right);
+
+    let resolution = frame_resolution as f32;
+    let project = |index: u32| {
+        let vertex = mesh_data.vertices[index as usize];
+        let local_position = Vec3::from_array(vertex.position) - center;
+        let texel_position = Vec2::new(
+            (local_position.dot(right) / radius * 0.5 + 0.5) * resolution,
+            (0.5 - local_position.dot(up) / radius * 0.5) * resolution,
+        );
+
+        (
+            texel_position,
+            local_position.dot(direction),
+            Vec2::from_array(vertex.uv),
+        )
+    };
+
+    let empty_texel = FrameTexel {
+        uv: Vec2::ZERO,
+        depth: f32::MIN,
+        is_covered: false,
+    };
+    let mut frame = vec![empty_texel; (frame_resolution * frame_resolution) as usize];
+    for triangle in mesh_data.indices.chunks_exact(3) {
+        let (p0, depth0, uv0) = project(triangle[0]);
+        let (p1, depth1, uv1) = project(triangle[1]);
+        let (p2, depth2, uv2) = project(triangle[2]);
+
+        let area = (p1 - p0).perp_dot(p2 - p0);
+        if area.abs() <= f32::EPSILON {
+            continue;
+        }
+
+        let texels_min = p0.min(p1).min(p2).floor().max(Vec2::ZERO);
+        let texels_max = p0.max(p1).max(p2).ceil().min(Vec2::splat(resolution));
+        for y in texels_min.y as u32..texels_max.y as u32 {
+            for x in texels_min.x as u32..texels_max.x as u32 {
+                // NOTE: Both windings are rasterized, the depth test keeps the side facing the viewer.
+                let texel_center = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let w0 = (p2 - p1).perp_dot(texel_center - p1) / area;
+                let w1 = (p0 - p2).perp_dot(texel_center - p2) / area;
+                let w2 = 1.0 - w0 - w1;
+                if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                    continue;
+                }
+
+                let depth = depth0 * w0 + depth1 * w1 + depth2 * w2;
+                let frame_texel = &mut frame[(y * frame_resolution + x) as usize];
+                if depth <= frame_texel.depth {
+                    continue;
+                }
+
+                let uv = uv0 * w0 + uv1 * w1 + uv2 * w2;
+                *frame_texel = FrameTexel {
+                    uv: Vec2::new(wrap_uv(uv.x), wrap_uv(uv.y)),
+                    depth,
+                    is_covered: true,
+                };
+            }
+        }
+    }
+
+    frame
+}
+
+/// UVs outside of `0.0..=1.0` are wrapped, the atlas can't store them.
+#[inline(always)]
+fn wrap_uv(uv: f32) -> f32 {
+    if (0.0..=1.0).contains(&uv) {
+        uv
+    } else {
+        uv.rem_euclid(1.0)
+    }
+}
+
+/// Inverse of `oct_encode` from the mesh shader, the lower hemisphere is folded into the corners.
+fn oct_decode(encoded: Vec2) -> Vec3 {
+    let f = encoded * 2.0 - 1.0;
+    let mut direction = Vec3::new(f.x, 1.0 - f.x.abs() - f.y.abs(), f.y);
+    let t = (-direction.y).max(0.0);
+    direction.x += if direction.x >= 0.0 { -t } else { t };
+    direction.z += if direction.z >= 0.0 { -t } else { t };
+
+    direction.normalize()
+}
+
+/// Copy of the source material, that reads its UVs from the atlas, it owns the atlas and shares the source textures.
+fn write_impostor_material(
+    materials_pool: &mut MaterialsPool,
+    textures_pool: &mut TexturesPool,
+    source_material_reference: MaterialReference,
+    atlas_texture_reference: TextureReference,
+    impostor: &Impostor,
+) -> Option<MaterialReference> {
+    let source_material_instance =
+        materials_pool.get_material_instance(source_material_reference)?;
+
+    let mut material_data =
+        bytemuck::pod_read_unaligned::<MaterialData>(source_material_instance.get_data());
+    material_data.material_impostor = MaterialImpostor {
+        atlas_texture_index: atlas_texture_reference.get_index(),
+        frames_per_side: impostor.frames_per_side,
+        frame_resolution: impostor.frame_resolution,
+    };
+
+    let material_state = source_material_instance.get_material_state();
+    let mut material_texture_references =
+        source_material_instance.get_texture_references().to_vec();
+    material_texture_references.push(atlas_texture_reference);
+
+    let (material_reference, is_material_created) = materials_pool.write_material(
+        bytemuck::bytes_of(&material_data),
+        material_state,
+        &material_texture_references,
+    );
+    if is_material_created {
+        material_texture_references
+            .into_iter()
+            .for_each(|texture_reference| textures_pool.retain_texture(texture_reference));
+    }
+
+    Some(material_reference)
+}
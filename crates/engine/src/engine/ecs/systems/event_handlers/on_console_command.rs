@@ -0,0 +1,242 @@
+use bevy_ecs::{
+    observer::On,
+    system::{Commands, ResMut},
+    world::World,
+};
+#[cfg(feature = "capture")]
+use math::Vec3;
+
+#[cfg(feature = "profiler")]
+use crate::engine::resources::FrameTimeGraph;
+#[cfg(feature = "capture")]
+use crate::engine::resources::{CubemapCapture, FrameGraphCapture};
+#[cfg(feature = "debug-tools")]
+use crate::engine::resources::{EntityInspector, MemoryOverlay};
+use crate::engine::{
+    ecs::general::dump_scene::dump_scene,
+    events::ConsoleCommandEvent,
+    resources::{
+        CVars, Console, EngineConfig, SceneDumpFormat, ScriptHost, Snapshots, TextureUsage,
+    },
+};
+
+/// Built-in commands, the rest are handled by their own observers.
+pub fn on_console_command_system(
+    console_command_event: On<ConsoleCommandEvent>,
+    mut console: ResMut<Console>,
+    #[cfg(feature = "capture")] mut frame_graph_capture: ResMut<FrameGraphCapture>,
+    #[cfg(feature = "debug-tools")] mut entity_inspector: ResMut<EntityInspector>,
+    #[cfg(feature = "profiler")] mut frame_time_graph: ResMut<FrameTimeGraph>,
+    #[cfg(feature = "debug-tools")] mut memory_overlay: ResMut<MemoryOverlay>,
+    mut texture_usage: ResMut<TextureUsage>,
+    #[cfg(feature = "capture")] mut cubemap_capture: ResMut<CubemapCapture>,
+    mut cvars: ResMut<CVars>,
+    mut snapshots: ResMut<Snapshots>,
+    mut script_host: ResMut<ScriptHost>,
+    mut commands: Commands,
+) {
+    match console_command_event.name.as_str() {
+        "help" => {
+            let help_lines: Vec<_> = console
+                .get_commands()
+                .map(|(name, command_info)| {
+                    std::format!(
+                        "{} {} - {}",
+                        name,
+                        command_info.usage,
+                        command_info.description
+                    )
+                })
+                .collect();
+            for help_line in help_lines {
+                console.log(help_line);
+            }
+        }
+        "clear" => console.clear_output(),
+        #[cfg(feature = "capture")]
+        "dump_frame_graph" => {
+            let path = console_command_event
+                .arguments
+                .first()
+                .map(String::as_str)
+                .unwrap_or(FrameGraphCapture::DEFAULT_PATH);
+            frame_graph_capture.request_dump(path);
+        }
+        "dump_scene" => {
+            let mut arguments = console_command_event.arguments.iter();
+            let scene_dump_format = match arguments.next() {
+                Some(format_name) => {
+                    let Some(scene_dump_format) = SceneDumpFormat::from_name(format_name) else {
+                        console.log("Usage: dump_scene [tree|json] [path]");
+                        return;
+                    };
+
+                    scene_dump_format
+                }
+                None => Default::default(),
+            };
+            let path = arguments.next().cloned();
+
+            // NOTE: Components of `TypeRegistry` are read through the world.
+            commands.queue(move |world: &mut World| {
+                let scene_dump = dump_scene(world, scene_dump_format);
+
+                let mut console = world.resource_mut::<Console>();
+                match path {
+                    Some(path) => match std::fs::write(&path, scene_dump) {
+                        Ok(()) => console.log(std::format!("Scene is dumped to {}", path)),
+                        Err(error) => {
+                            console.log(std::format!("Failed to dump scene to {}: {}", path, error))
+                        }
+                    },
+                    None => scene_dump.lines().for_each(|line| console.log(line)),
+                }
+            });
+        }
+        #[cfg(feature = "debug-tools")]
+        "inspector" => {
+            entity_inspector.toggle();
+            console.log(std::format!(
+                "Entity inspector is {}",
+                if entity_inspector.is_open() {
+                    "open"
+                } else {
+                    "closed"
+                }
+            ));
+        }
+        #[cfg(feature = "profiler")]
+        "frame_graph" => {
+            frame_time_graph.toggle();
+
+            let average_frame_time = frame_time_graph.get_average_frame_time();
+            let one_percent_low_frame_time = frame_time_graph.get_one_percent_low_frame_time();
+            console.log(std::format!(
+                "Frame time: {:.2} ms average, {:.2} ms 1% low ({:.0} FPS)",
+                average_frame_time,
+                one_percent_low_frame_time,
+                1000.0 / one_percent_low_frame_time.max(f32::EPSILON)
+            ));
+        }
+        #[cfg(feature = "capture")]
+        "capture_cubemap" => {
+            let arguments = &console_command_event.arguments;
+            let coordinates: Vec<_> = arguments
+                .iter()
+                .take(3)
+                .map_while(|argument| argument.parse::<f32>().ok())
+                .collect();
+            let face_size = match arguments.get(4).map(|argument| argument.parse::<u32>()) {
+                Some(Ok(face_size)) => Some(face_size),
+                Some(Err(_)) => None,
+                None => Some(CubemapCapture::DEFAULT_FACE_SIZE),
+            };
+            let (&[x, y, z], Some(face_size)) = (coordinates.as_slice(), face_size) else {
+                console.log("Usage: capture_cubemap <x> <y> <z> [path] [face_size]");
+                return;
+            };
+            let path = arguments
+                .get(3)
+                .map(String::as_str)
+                .unwrap_or(CubemapCapture::DEFAULT_PATH);
+
+            cubemap_capture.request_capture(Vec3::new(x, y, z), path, face_size);
+        }
+        #[cfg(feature = "debug-tools")]
+        "memory" => {
+            memory_overlay.toggle();
+            memory_overlay.request_report();
+        }
+        "texture_usage" => {
+            match console_command_event.arguments.first().map(String::as_str) {
+                Some("on") => texture_usage.set_enabled(true),
+                Some("off") => texture_usage.set_enabled(false),
+                Some("reset") => texture_usage.reset(),
+                None => {
+                    let is_enabled = !texture_usage.is_enabled();
+                    texture_usage.set_enabled(is_enabled);
+                }
+                Some(_) => console.log("Usage: texture_usage [on|off|reset]"),
+            }
+            console.log(std::format!(
+                "Texture usage is {}, {} frames are counted",
+                if texture_usage.is_enabled() {
+                    "on"
+                } else {
+                    "off"
+                },
+                texture_usage.get_sampled_frames_count()
+            ));
+        }
+        "cvars" => {
+            let prefix = console_command_event
+                .arguments
+                .first()
+                .map(String::as_str)
+                .unwrap_or_default();
+            let cvar_lines: Vec<_> = cvars
+                .iter()
+                .filter(|(name, _)| name.starts_with(prefix))
+                .map(|(name, cvar)| std::format!("{} = {}", name, cvar.value))
+                .collect();
+            for cvar_line in cvar_lines {
+                console.log(cvar_line);
+            }
+        }
+        "reset" => {
+            let Some(name) = console_command_event.arguments.first() else {
+                console.log("Usage: reset <cvar>");
+                return;
+            };
+            if let Err(error) = cvars.reset(name) {
+                console.log(error.to_string());
+            }
+        }
+        "snapshot_save" => match console_command_event.arguments.first() {
+            Some(path) => snapshots.request_save(path),
+            None => console.log("Usage: snapshot_save <path>"),
+        },
+        "snapshot_load" => match console_command_event.arguments.first() {
+            Some(path) => snapshots.request_load(path),
+            None => console.log("Usage: snapshot_load <path>"),
+        },
+        "script_load" => match console_command_event.arguments.first() {
+            Some(path) => match script_host.load_script(path) {
+                Ok(()) => console.log(std::format!("Script {} is loaded", path)),
+                Err(error) => {
+                    console.log(std::format!("Failed to load script {}: {}", path, error))
+                }
+            },
+            None => console.log("Usage: script_load <path>"),
+        },
+        "script_unload" => match console_command_event.arguments.first() {
+            Some(path) => {
+                if !script_host.unload_script(path) {
+                    console.log(std::format!("Script {} isn't loaded", path));
+                }
+            }
+            None => console.log("Usage: script_unload <path>"),
+        },
+        "script_reload" => {
+            for (path, error) in script_host.reload_scripts() {
+                console.log(std::format!(
+                    "Failed to reload script {}: {}",
+                    path.display(),
+                    error
+                ));
+            }
+        }
+        "cvars_save" => {
+            let path = EngineConfig::get_path();
+            match cvars.save(&path) {
+                Ok(()) => console.log(std::format!("Cvars are saved to {}", path.display())),
+                Err(error) => console.log(std::format!(
+                    "Failed to save cvars to {}: {}",
+                    path.display(),
+                    error
+                )),
+            }
+        }
+        _ => (),
+    }
+}
@@ -0,0 +1,60 @@
+use bevy_ecs::{observer::On, system::ResMut};
+
+use crate::engine::{
+    events::CVarChangedEvent,
+    resources::{
+        AutoExposure, CVarValue, CVars, CullingSettings, DefragmentationSettings,
+        DynamicResolution, Gizmos, LatencyMode, PostProcessSettings, RenderScaleSettings,
+        WindowSettings,
+    },
+};
+
+/// Applies engine cvars to the settings resources they mirror.
+pub fn on_cvar_changed_system(
+    cvar_changed_event: On<CVarChangedEvent>,
+    mut render_scale_settings: ResMut<RenderScaleSettings>,
+    mut post_process_settings: ResMut<PostProcessSettings>,
+    mut window_settings: ResMut<WindowSettings>,
+    mut culling_settings: ResMut<CullingSettings>,
+    mut defragmentation_settings: ResMut<DefragmentationSettings>,
+    mut gizmos: ResMut<Gizmos>,
+) {
+    match (cvar_changed_event.name.as_str(), &cvar_changed_event.value) {
+        (CVars::RENDER_SCALE, &CVarValue::Float(render_scale)) => {
+            render_scale_settings.set_render_scale(render_scale);
+        }
+        (CVars::DYNAMIC_RESOLUTION, &CVarValue::Bool(is_enabled)) => {
+            render_scale_settings
+                .set_dynamic_resolution(is_enabled.then(DynamicResolution::default));
+        }
+        (CVars::EXPOSURE, &CVarValue::Float(exposure)) => {
+            post_process_settings.exposure = exposure;
+        }
+        (CVars::AUTO_EXPOSURE, &CVarValue::Bool(is_enabled)) => {
+            post_process_settings.auto_exposure = is_enabled.then(AutoExposure::default);
+        }
+        (CVars::VSYNC, &CVarValue::Bool(vsync)) => {
+            window_settings.set_vsync(vsync);
+        }
+        (CVars::LOW_LATENCY, &CVarValue::Bool(is_enabled)) => {
+            window_settings.set_latency_mode(if is_enabled {
+                LatencyMode::LowLatency
+            } else {
+                LatencyMode::Throughput
+            });
+        }
+        (CVars::MESHLET_CONE_CULLING, &CVarValue::Bool(is_enabled)) => {
+            culling_settings.is_meshlet_cone_culling_enabled = is_enabled;
+        }
+        (CVars::DEPTH_PYRAMID, &CVarValue::Bool(is_enabled)) => {
+            culling_settings.is_depth_pyramid_enabled = is_enabled;
+        }
+        (CVars::DEFRAGMENTATION, &CVarValue::Bool(is_enabled)) => {
+            defragmentation_settings.is_enabled = is_enabled;
+        }
+        (CVars::GIZMOS, &CVarValue::Bool(is_enabled)) => {
+            gizmos.is_scene_gizmos_enabled = is_enabled;
+        }
+        _ => (),
+    }
+}
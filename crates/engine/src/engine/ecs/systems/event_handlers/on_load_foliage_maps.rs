@@ -0,0 +1,69 @@
+use bevy_ecs::{
+    observer::On,
+    system::{Query, Res, ResMut},
+};
+use vulkanite::vk::Format;
+
+use crate::engine::{
+    components::foliage::FoliageScatter,
+    ecs::{
+        buffers_pool::BuffersPool, on_load_model::upload_texture_from_path,
+        textures_pool::TexturesPool,
+    },
+    events::LoadFoliageMapsEvent,
+    general::renderer::DescriptorSetHandle,
+    resources::{RendererContext, VulkanContextResource},
+};
+
+pub fn on_load_foliage_maps_system(
+    load_foliage_maps_event: On<LoadFoliageMapsEvent>,
+    mut foliage_scatters_query: Query<&mut FoliageScatter>,
+    vulkan_context: Res<VulkanContextResource>,
+    renderer_context: Res<RendererContext>,
+    mut descriptor_set_handle: ResMut<DescriptorSetHandle>,
+    mut buffers_pool: ResMut<BuffersPool>,
+    mut textures_pool: ResMut<TexturesPool>,
+) {
+    let Ok(mut foliage_scatter) =
+        foliage_scatters_query.get_mut(load_foliage_maps_event.foliage_entity)
+    else {
+        return;
+    };
+
+    // NOTE: Maps hold data instead of colors, so they aren't converted from sRGB.
+    if foliage_scatter
+        .get_density_map_texture_reference()
+        .is_none()
+        && let Some(density_map_path) = foliage_scatter.density_map_path.clone()
+    {
+        let texture_reference = upload_texture_from_path(
+            &vulkan_context,
+            &renderer_context,
+            &mut descriptor_set_handle,
+            &mut buffers_pool,
+            &mut textures_pool,
+            &density_map_path,
+            Format::R8G8B8A8Unorm,
+        );
+        textures_pool.retain_texture(texture_reference);
+
+        foliage_scatter.set_density_map_texture_reference(texture_reference);
+    }
+
+    if foliage_scatter.get_height_map_texture_reference().is_none()
+        && let Some(height_map_path) = foliage_scatter.height_map_path.clone()
+    {
+        let texture_reference = upload_texture_from_path(
+            &vulkan_context,
+            &renderer_context,
+            &mut descriptor_set_handle,
+            &mut buffers_pool,
+            &mut textures_pool,
+            &height_map_path,
+            Format::R8G8B8A8Unorm,
+        );
+        textures_pool.retain_texture(texture_reference);
+
+        foliage_scatter.set_height_map_texture_reference(texture_reference);
+    }
+}
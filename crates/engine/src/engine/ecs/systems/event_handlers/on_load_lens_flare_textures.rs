@@ -0,0 +1,94 @@
+use bevy_ecs::{
+    observer::On,
+    system::{Res, ResMut},
+};
+use image::DynamicImage;
+use vulkanite::vk::Format;
+
+use crate::engine::{
+    ecs::{
+        buffers_pool::BuffersPool,
+        on_load_model::{upload_texture_atlas, upload_texture_from_image},
+        texture_atlas::TextureAtlasBuilder,
+        textures_pool::TexturesPool,
+    },
+    events::LoadLensFlareTexturesEvent,
+    general::renderer::DescriptorSetHandle,
+    resources::{PostProcessSettings, RendererContext, VulkanContextResource},
+};
+
+/// Small sprites are packed into shared atlas pages, the big ones get their own textures.
+pub fn on_load_lens_flare_textures_system(
+    _: On<LoadLensFlareTexturesEvent>,
+    mut post_process_settings: ResMut<PostProcessSettings>,
+    vulkan_context: Res<VulkanContextResource>,
+    renderer_context: Res<RendererContext>,
+    mut descriptor_set_handle: ResMut<DescriptorSetHandle>,
+    mut buffers_pool: ResMut<BuffersPool>,
+    mut textures_pool: ResMut<TexturesPool>,
+) {
+    let Some(lens_flare) = post_process_settings.lens_flare.as_mut() else {
+        return;
+    };
+
+    let mut texture_atlas_builder = TextureAtlasBuilder::default();
+    let mut packed_sprite_indices = Vec::new();
+    for (sprite_index, sprite) in lens_flare.sprites.iter_mut().enumerate() {
+        if sprite.texture_reference.is_some() {
+            continue;
+        }
+        let Some(texture_path) = sprite.texture_path.as_ref() else {
+            continue;
+        };
+
+        let image = match image::open(texture_path) {
+            Ok(image) => image.to_rgba8(),
+            Err(error) => {
+                println!(
+                    "Failed to load lens flare texture {}: {}",
+                    texture_path.display(),
+                    error
+                );
+                continue;
+            }
+        };
+
+        if texture_atlas_builder.can_pack(image.width(), image.height()) {
+            texture_atlas_builder.add_image(image);
+            packed_sprite_indices.push(sprite_index);
+        } else {
+            let texture_reference = upload_texture_from_image(
+                &vulkan_context,
+                &renderer_context,
+                &mut descriptor_set_handle,
+                &mut buffers_pool,
+                &mut textures_pool,
+                &DynamicImage::ImageRgba8(image),
+                Format::R8G8B8A8Srgb,
+            );
+            textures_pool.retain_texture(texture_reference);
+            sprite.texture_reference = Some(texture_reference);
+        }
+    }
+
+    if texture_atlas_builder.is_empty() {
+        return;
+    }
+
+    let texture_references = upload_texture_atlas(
+        &vulkan_context,
+        &renderer_context,
+        &mut descriptor_set_handle,
+        &mut buffers_pool,
+        &mut textures_pool,
+        texture_atlas_builder.build(),
+        Format::R8G8B8A8Srgb,
+    );
+    // NOTE: Pages are reference counted by their sprites, so a page lives while any of them uses it.
+    for (sprite_index, texture_reference) in
+        packed_sprite_indices.into_iter().zip(texture_references)
+    {
+        textures_pool.retain_texture(texture_reference);
+        lens_flare.sprites[sprite_index].texture_reference = Some(texture_reference);
+    }
+}
@@ -1,102 +1,80 @@
-use asset_importer::{Matrix4x4, node::Node};
-use image::{EncodableLayout, ImageReader};
-use ktx2_rw::Ktx2Texture;
+use bytemuck::Pod;
+use image::{DynamicImage, ImageReader};
 use nameof::name_of;
-use std::{collections::HashMap, ffi::c_void, io::Cursor, str::FromStr};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 use vulkanite::vk::{
-    BufferCopy, BufferUsageFlags, DeviceAddress, Extent3D, Format, ImageUsageFlags,
+    BufferCopy, BufferUsageFlags, Extent3D, Filter, Format, ImageUsageFlags, SamplerAddressMode,
+    SamplerMipmapMode,
 };
 
 use bevy_ecs::{
     observer::On,
     system::{Commands, Res, ResMut},
 };
-use math::{Mat4, Vec2, Vec3, Vec4};
+use math::{Vec3, Vec4};
 use meshopt::{
-    VertexDataAdapter, build_meshlets, optimize_vertex_cache_in_place, optimize_vertex_fetch,
+    VertexDataAdapter, analyze_overdraw, analyze_vertex_cache, analyze_vertex_fetch,
+    build_meshlets, compute_meshlet_bounds, optimize_vertex_cache_in_place, optimize_vertex_fetch,
     optimize_vertex_fetch_remap, remap_index_buffer, remap_vertex_buffer, typed_to_bytes,
 };
 
 use crate::engine::{
     components::{
+        loaded_model::LoadedModel,
         local_transform::LocalTransform,
         material::{
             MaterialData, MaterialProperties, MaterialState, MaterialTextures, MaterialType,
         },
     },
+    cook::{
+        AssetCooker,
+        cooked_model::{CookedMaterial, CookedModel},
+        get_asset_key, get_cook_settings_hash, get_cooked_model_path, get_cooked_texture_path,
+        read_cooked_texture,
+    },
     ecs::{
         buffers_pool::BuffersPool,
         components::mesh::MeshData,
+        image_state_tracker::ImageState,
         materials_pool::{MaterialReference, MaterialsPool},
-        mesh_buffers_pool::{MeshBuffer, MeshBufferReference, MeshBuffersPool},
+        mesh_buffers_pool::{MeshBuffer, MeshBufferReference, MeshBuffersPool, MeshStatistics},
+        samplers_pool::{SamplerPreset, SamplersPool},
+        texture_atlas::TextureAtlas,
         textures_pool::TexturesPool,
     },
     events::{LoadModelEvent, SpawnEvent, SpawnEventRecord},
-    general::renderer::{DescriptorKind, DescriptorSampledImage, DescriptorSetHandle},
+    general::renderer::{
+        DescriptorKind, DescriptorSampledImage, DescriptorSampler, DescriptorSetHandle, GpuBackend,
+    },
     resources::{
-        MeshObject, Meshlet, RendererContext, RendererResources, Vertex, VulkanContextResource,
+        CompressedVertex, CullingSettings, EngineConfig, FrameAllocator, FrameArena,
+        ImportSettings, MeshObject, Meshlet, RendererContext, RendererResources, Vertex,
+        VertexFormat, VulkanContextResource,
         buffers_pool::{BufferReference, BufferVisibility},
-        textures_pool::{TextureMetadata, TextureReference},
+        textures_pool::TextureReference,
     },
 };
 
-struct NodeData {
-    pub name: String,
-    pub index: usize,
-    pub parent_index: Option<usize>,
-    pub matrix: Mat4,
-    pub mesh_indices: Vec<usize>,
+pub(crate) struct MeshGeometry {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+    pub meshlets: Vec<Meshlet>,
+    pub vertex_indices: Vec<u32>,
+    pub triangles: Vec<u8>,
+    pub mesh_statistics: MeshStatistics,
 }
 
-impl NodeData {
-    pub fn new(
-        name: String,
-        index: usize,
-        parent_index: Option<usize>,
-        transformation: Matrix4x4,
-        mesh_indices: Vec<usize>,
-    ) -> Self {
-        let matrix = Self::get_matrix(transformation);
-
-        Self {
-            name,
-            index,
-            parent_index,
-            matrix,
-            mesh_indices,
-        }
-    }
-
-    pub fn get_matrix(transformation: Matrix4x4) -> Mat4 {
-        math::Mat4 {
-            x_axis: Vec4::new(
-                transformation.x_axis.x,
-                transformation.x_axis.y,
-                transformation.x_axis.z,
-                transformation.x_axis.w,
-            ),
-            y_axis: Vec4::new(
-                transformation.y_axis.x,
-                transformation.y_axis.y,
-                transformation.y_axis.z,
-                transformation.y_axis.w,
-            ),
-            z_axis: Vec4::new(
-                transformation.z_axis.x,
-                transformation.z_axis.y,
-                transformation.z_axis.z,
-                transformation.z_axis.w,
-            ),
-            w_axis: Vec4::new(
-                transformation.w_axis.x,
-                transformation.w_axis.y,
-                transformation.w_axis.z,
-                transformation.w_axis.w,
-            ),
-        }
-    }
+/// Meshlets of the mesh, cooked ones are read in place from the mapped file.
+pub(crate) struct MeshletsData<'a> {
+    pub meshlets: &'a [Meshlet],
+    pub vertex_indices: &'a [u32],
+    pub triangles: &'a [u8],
 }
 
+/// Models are loaded only from their cooked `.model` files, so loads skip Assimp and meshopt.
 pub fn on_load_model_system(
     load_model_event: On<LoadModelEvent>,
     mut commands: Commands,
@@ -107,363 +85,497 @@ pub fn on_load_model_system(
     mut descriptor_set_handle: ResMut<DescriptorSetHandle>,
     mut buffers_pool: ResMut<BuffersPool>,
     mut textures_pool: ResMut<TexturesPool>,
+    mut samplers_pool: ResMut<SamplersPool>,
     mut mesh_buffers_pool: ResMut<MeshBuffersPool>,
+    culling_settings: Res<CullingSettings>,
+    import_settings: Res<ImportSettings>,
+    engine_config: Res<EngineConfig>,
+    mut frame_arena: ResMut<FrameArena>,
 ) {
-    let model_loader = &renderer_resources.model_loader;
-
-    let mut nodes = Vec::new();
-
-    let scene = model_loader.load_model(load_model_event.path.as_os_str().to_str().unwrap());
-
-    let root_node_index = Default::default();
-    let root_node = scene.root_node().unwrap();
-
-    nodes.push(NodeData::new(
-        root_node.name(),
-        root_node_index,
-        None,
-        root_node.transformation(),
-        get_mesh_indices(&root_node, root_node.num_meshes()),
-    ));
-
-    let mut stack: Vec<(Node, usize)> = Vec::new();
-    stack.push((root_node, root_node_index));
-
-    loop {
-        while let Some((parent_node, parent_index_in_array)) = stack.pop() {
-            for child_index in (0..parent_node.num_children()).rev() {
-                let child_node = parent_node.child(child_index).unwrap();
-
-                let child_index_in_array = nodes.len();
-                stack.push((child_node.clone(), child_index_in_array));
-
-                nodes.push(NodeData::new(
-                    child_node.name(),
-                    child_index_in_array,
-                    Some(parent_index_in_array),
-                    child_node.transformation(),
-                    get_mesh_indices(&child_node, child_node.num_meshes()),
-                ));
-            }
-        }
-
-        if stack.len() == Default::default() {
-            break;
-        }
+    let model_path = load_model_event.path.as_path();
+    let asset_key = get_asset_key(&engine_config.get_asset_root(), model_path);
+    let Some(cooked_model) = open_cooked_model(
+        model_path,
+        &asset_key,
+        &engine_config,
+        *import_settings,
+        culling_settings.meshlet_cone_weight,
+    ) else {
+        return;
+    };
+    let intermediate_root = engine_config.get_intermediate_root();
+
+    if let Some(parent_entity) = load_model_event.parent_entity {
+        commands.entity(parent_entity).insert(LoadedModel {
+            path: load_model_event.path.clone(),
+        });
     }
 
     let mut spawn_event = SpawnEvent::default();
     spawn_event.parent_entity = load_model_event.parent_entity;
     let mut spawn_event_record = SpawnEventRecord::default();
 
-    nodes.iter().for_each(|node_data| {
-        let local_matrix = node_data.matrix;
-
-        let (local_scale, rotation, position) = local_matrix.to_scale_rotation_translation();
+    let cooked_nodes = cooked_model.get_nodes();
+    cooked_nodes.iter().for_each(|cooked_node| {
+        let (local_scale, rotation, position) =
+            cooked_node.get_matrix().to_scale_rotation_translation();
         let transform = LocalTransform {
             local_position: position,
             local_rotation: rotation,
             local_scale,
         };
-        spawn_event_record.name = node_data.name.clone();
-        spawn_event_record.parent_index = node_data.parent_index;
+        spawn_event_record.name = cooked_model.get_str(cooked_node.name).to_string();
+        spawn_event_record.parent_index = cooked_node.get_parent_index();
         spawn_event_record.transform = transform;
 
         spawn_event.spawn_records.push(spawn_event_record.clone());
     });
 
-    let mut mesh_buffers_to_upload = Vec::with_capacity(scene.num_meshes());
-    let mut uploaded_mesh_buffers = HashMap::with_capacity(scene.num_meshes());
-    let mut uploaded_textures = HashMap::with_capacity(uploaded_mesh_buffers.capacity());
-    let mut uploaded_materials = HashMap::with_capacity(scene.num_materials());
+    let cooked_meshes = cooked_model.get_meshes();
+    let cooked_materials = cooked_model.get_materials();
+    let frame_allocator = frame_arena.get_allocator();
+    let mut mesh_buffers_to_upload = frame_allocator.vec_with_capacity(cooked_meshes.len());
+    let mut uploaded_mesh_buffers = HashMap::with_capacity(cooked_meshes.len());
+    let mut uploaded_textures = HashMap::with_capacity(cooked_materials.len());
+    let mut uploaded_materials = HashMap::with_capacity(cooked_materials.len());
 
     materials_pool.reset_materails_to_write();
-    for node_data in nodes.into_iter() {
-        if node_data.mesh_indices.len() > Default::default() {
-            let mut mesh_name: String;
-            let mut mesh_buffer_reference: MeshBufferReference;
-            let mut texture_reference: TextureReference;
-            for &mesh_index in node_data.mesh_indices.iter() {
-                texture_reference = renderer_resources.fallback_texture_reference;
-                let mesh = scene.mesh(mesh_index).unwrap();
-
-                let material_index = mesh.material_index();
-                let material_reference: MaterialReference;
-                if let std::collections::hash_map::Entry::Vacant(e) =
-                    uploaded_materials.entry(material_index)
-                {
-                    let material = scene.material(material_index).unwrap();
+    for (node_index, cooked_node) in cooked_nodes.iter().enumerate() {
+        for &mesh_index in cooked_model.get_slice::<u32>(cooked_node.mesh_indices) {
+            let Some(cooked_mesh) = cooked_meshes.get(mesh_index as usize) else {
+                continue;
+            };
+            let material_index = cooked_mesh.material_index;
+            let Some(&cooked_material) = cooked_materials.get(material_index as usize) else {
+                continue;
+            };
 
-                    let alpha_mode = std::str::from_utf8(
-                        material
-                            .get_property_raw_ref(c"$mat.gltf.alphaMode", None, 0)
-                            .unwrap(),
-                    )
-                    .unwrap();
-                    let mut material_type = MaterialType::Opaque;
-                    if alpha_mode.contains("BLEND") {
-                        material_type = MaterialType::Transparent;
-                    }
+            let material_reference: MaterialReference;
+            if let std::collections::hash_map::Entry::Vacant(e) =
+                uploaded_materials.entry(material_index)
+            {
+                let material_type = if cooked_material.is_transparent != Default::default() {
+                    MaterialType::Transparent
+                } else {
+                    MaterialType::Opaque
+                };
 
+                let mut texture_reference = renderer_resources.fallback_texture_reference;
+                let texture_name = cooked_model.get_str(cooked_material.base_color_texture);
+                let has_texture = !texture_name.is_empty();
+                if has_texture {
                     try_upload_texture(
                         &vulkan_context,
                         &renderer_context_resource,
                         &mut textures_pool,
                         &mut buffers_pool,
                         &mut descriptor_set_handle,
-                        &scene,
                         &mut uploaded_textures,
-                        material.clone(),
+                        get_cooked_texture_path(&intermediate_root, &asset_key, texture_name),
+                        model_path,
                         &mut texture_reference,
-                        load_model_event.path.file_stem().unwrap().to_str().unwrap(),
                     );
-
-                    let base_color_raw = material.base_color().unwrap();
-                    let base_color = Vec4::new(
-                        base_color_raw.x,
-                        base_color_raw.y,
-                        base_color_raw.z,
-                        base_color_raw.w,
-                    );
-
-                    let metallic_value = material.metallic_factor().unwrap_or(0.0);
-                    let roughness_value = material.roughness_factor().unwrap_or(0.0);
-                    let albedo_texture_index = texture_reference.get_index();
-                    let metallic_texture_index =
-                        renderer_resources.fallback_texture_reference.get_index();
-                    let roughness_texture_index =
-                        renderer_resources.fallback_texture_reference.get_index();
-
-                    let material_data = MaterialData {
-                        material_properties: MaterialProperties::new(
-                            base_color,
-                            metallic_value,
-                            roughness_value,
-                        ),
-                        material_textures: MaterialTextures::new(
-                            albedo_texture_index,
-                            metallic_texture_index,
-                            roughness_texture_index,
-                        ),
-                        sampler_index: Default::default(),
-                    };
-
-                    material_reference = materials_pool.write_material(
-                        bytemuck::bytes_of(&material_data),
-                        MaterialState { material_type },
-                    );
-                    e.insert(material_reference);
-                } else {
-                    material_reference = *uploaded_materials.get(&material_index).unwrap();
                 }
 
-                if let std::collections::hash_map::Entry::Vacant(e) =
-                    uploaded_mesh_buffers.entry(mesh_index)
+                let base_color = Vec4::from_array(cooked_material.base_color);
+                let metallic_value = cooked_material.metallic;
+                let roughness_value = cooked_material.roughness;
+                let albedo_texture_index = texture_reference.get_index();
+                let metallic_texture_index =
+                    renderer_resources.fallback_texture_reference.get_index();
+                let roughness_texture_index =
+                    renderer_resources.fallback_texture_reference.get_index();
+
+                let default_sampler_index =
+                    renderer_resources.default_sampler_reference.get_index();
+                let albedo_sampler_index = if has_texture {
+                    get_or_create_texture_sampler(
+                        &mut samplers_pool,
+                        &mut buffers_pool,
+                        &mut descriptor_set_handle,
+                        get_sampler_preset(&cooked_material),
+                    )
+                } else {
+                    default_sampler_index
+                };
+
+                let material_data = MaterialData {
+                    material_properties: MaterialProperties::new(
+                        base_color,
+                        metallic_value,
+                        roughness_value,
+                    )
+                    .with_emissive_color(Vec3::from_array(cooked_material.emissive_color))
+                    .with_clearcoat(
+                        cooked_material.clearcoat,
+                        cooked_material.clearcoat_roughness,
+                    )
+                    .with_transmission(cooked_material.transmission),
+                    material_textures: MaterialTextures::new(
+                        albedo_texture_index,
+                        metallic_texture_index,
+                        roughness_texture_index,
+                    )
+                    .with_sampler_indices(
+                        albedo_sampler_index,
+                        default_sampler_index,
+                        default_sampler_index,
+                    ),
+                    material_splat: Default::default(),
+                    material_impostor: Default::default(),
+                };
+
+                let material_texture_references = if texture_reference.get_index()
+                    != renderer_resources.fallback_texture_reference.get_index()
                 {
-                    let mesh = scene.mesh(mesh_index).unwrap();
-                    mesh_name = mesh.name();
-
-                    let mut indices = Vec::with_capacity(mesh.faces().len() * 3);
+                    vec![texture_reference]
+                } else {
+                    Vec::new()
+                };
+
+                let is_material_created;
+                (material_reference, is_material_created) = materials_pool.write_material(
+                    bytemuck::bytes_of(&material_data),
+                    MaterialState { material_type },
+                    &material_texture_references,
+                );
+                if is_material_created {
+                    material_texture_references
+                        .into_iter()
+                        .for_each(|texture_reference| {
+                            textures_pool.retain_texture(texture_reference)
+                        });
+                }
+                e.insert(material_reference);
+            } else {
+                material_reference = *uploaded_materials.get(&material_index).unwrap();
+            }
 
-                    for face in mesh.faces() {
-                        for index in face.indices() {
-                            indices.push(*index);
+            let mesh_name = cooked_model.get_str(cooked_mesh.name).to_string();
+            let mesh_buffer_reference = match uploaded_mesh_buffers.entry(mesh_index) {
+                std::collections::hash_map::Entry::Occupied(e) => *e.get(),
+                std::collections::hash_map::Entry::Vacant(e) => {
+                    let vertices = cooked_model.get_slice::<Vertex>(cooked_mesh.vertices);
+                    let indices = cooked_model.get_slice::<u32>(cooked_mesh.indices);
+                    let vertex_format = engine_config.get_vertex_format();
+                    // NOTE: Same mesh of another load reuses its buffers, e.g. clones of a prefab.
+                    let mesh_buffer_reference = match mesh_buffers_pool.find_mesh_buffer(
+                        vertices,
+                        indices,
+                        vertex_format,
+                    ) {
+                        Some(mesh_buffer_reference) => mesh_buffer_reference,
+                        None => {
+                            let mesh_buffer = create_mesh_buffer_from_parts(
+                                &mut buffers_pool,
+                                MeshData {
+                                    vertices: vertices.to_vec(),
+                                    indices: indices.to_vec(),
+                                },
+                                MeshletsData {
+                                    meshlets: cooked_model.get_slice(cooked_mesh.meshlets),
+                                    vertex_indices: cooked_model
+                                        .get_slice(cooked_mesh.vertex_indices),
+                                    triangles: cooked_model.get_slice(cooked_mesh.triangles),
+                                },
+                                (
+                                    Vec3::from_array(cooked_mesh.aabb_min),
+                                    Vec3::from_array(cooked_mesh.aabb_max),
+                                ),
+                                cooked_mesh.get_mesh_statistics(),
+                                &mesh_name,
+                                vertex_format,
+                            );
+                            let mesh_buffer_reference =
+                                mesh_buffers_pool.insert_mesh_buffer(mesh_buffer);
+                            mesh_buffers_to_upload.push(mesh_buffer_reference);
+
+                            mesh_buffer_reference
                         }
-                    }
-
-                    let positions: Vec<Vec3> = mesh
-                        .vertices_iter()
-                        .map(|v| Vec3::new(v.x, v.y, v.z))
-                        .collect();
-                    let colors: Vec<Vec3> = mesh
-                        .vertex_colors(Default::default())
-                        .map(|colors| {
-                            colors
-                                .iter()
-                                .map(|color| Vec3::new(color.x, color.y, color.z))
-                                .collect()
-                        })
-                        .unwrap_or_else(|| vec![Vec3::ZERO; positions.len()]);
-                    let normals: Vec<Vec3> = mesh
-                        .normals()
-                        .map(|ns| ns.iter().map(|n| Vec3::new(n.x, n.y, n.z)).collect())
-                        .unwrap_or_else(|| vec![Vec3::ZERO; positions.len()]);
-
-                    let uvs: Vec<Vec2> = if mesh.has_texture_coords(0) {
-                        mesh.texture_coords_iter(0)
-                            .map(|uv| Vec2::new(uv.x, uv.y))
-                            .collect()
-                    } else {
-                        vec![Vec2::ZERO; positions.len()]
                     };
 
-                    let mut vertices = Vec::with_capacity(positions.len());
-                    for i in 0..positions.len() {
-                        vertices.push(Vertex {
-                            position: positions[i].to_array(),
-                            normal: normals[i].to_array(),
-                            uv: uvs[i].to_array(),
-                            color: colors[i].to_array(),
-                            ..Default::default()
-                        });
-                    }
-
-                    let remap = optimize_vertex_fetch_remap(&indices, vertices.len());
-                    indices = remap_index_buffer(Some(&indices), vertices.len(), &remap);
-                    vertices = remap_vertex_buffer(&vertices, vertices.len(), &remap);
+                    *e.insert(mesh_buffer_reference)
+                }
+            };
 
-                    let position_offset = std::mem::offset_of!(Vertex, position);
-                    let vertex_stride = std::mem::size_of::<Vertex>();
-                    let vertex_data = typed_to_bytes(&vertices);
+            spawn_event_record.name = mesh_name;
+            spawn_event_record.parent_index = Some(node_index);
+            spawn_event_record.material_reference = Some(material_reference);
+            spawn_event_record.mesh_buffer_reference = Some(mesh_buffer_reference);
+            spawn_event_record.transform = LocalTransform::IDENTITY;
 
-                    let vertex_data_adapter =
-                        VertexDataAdapter::new(vertex_data, vertex_stride, position_offset)
-                            .unwrap();
+            spawn_event.spawn_records.push(spawn_event_record.clone());
+        }
+    }
 
-                    optimize_vertex_cache_in_place(&mut indices, vertices.len());
-                    let vertices = optimize_vertex_fetch(&mut indices, &vertices);
+    write_mesh_objects(
+        buffers_pool.as_mut(),
+        &mut mesh_buffers_pool,
+        renderer_resources.mesh_objects_buffer_reference,
+        frame_allocator,
+        &mesh_buffers_to_upload,
+    );
+    write_materials_data(
+        buffers_pool.as_mut(),
+        &materials_pool,
+        renderer_resources.materials_data_buffer_reference,
+    );
 
-                    let (meshlets, vertex_indices, triangles) =
-                        generate_meshlets(&indices, &vertex_data_adapter);
+    commands.trigger(spawn_event);
+}
 
-                    let vertex_buffer_reference = create_and_copy_to_buffer(
-                        &mut buffers_pool,
-                        vertices.as_ptr() as *const _,
-                        vertices.len() * std::mem::size_of::<Vertex>(),
-                        std::format!("{}_{}", mesh_name, name_of!(vertices)),
-                    );
-                    let vertex_indices_buffer_reference = create_and_copy_to_buffer(
-                        &mut buffers_pool,
-                        vertex_indices.as_ptr() as _,
-                        vertex_indices.len() * std::mem::size_of::<u32>(),
-                        std::format!("{}_{}", mesh_name, name_of!(vertex_indices)),
-                    );
-                    let meshlets_buffer_reference = create_and_copy_to_buffer(
-                        &mut buffers_pool,
-                        meshlets.as_ptr() as _,
-                        meshlets.len() * std::mem::size_of::<Meshlet>(),
-                        std::format!("{}_{}", mesh_name, name_of!(meshlets)),
-                    );
+/// Model that isn't cooked is cooked on demand, if `EngineConfig::import_fallback` allows it.
+fn open_cooked_model(
+    model_path: &Path,
+    asset_key: &str,
+    engine_config: &EngineConfig,
+    import_settings: ImportSettings,
+    cone_weight: f32,
+) -> Option<CookedModel> {
+    let settings_hash = get_cook_settings_hash(&import_settings, cone_weight);
+    let cooked_model_path =
+        get_cooked_model_path(&engine_config.get_intermediate_root(), asset_key);
+    if let Some(cooked_model) =
+        CookedModel::open(&cooked_model_path, model_path, asset_key, settings_hash)
+    {
+        return Some(cooked_model);
+    }
 
-                    let local_indices_buffer_reference = create_and_copy_to_buffer(
-                        &mut buffers_pool,
-                        triangles.as_ptr() as _,
-                        triangles.len() * std::mem::size_of::<u8>(),
-                        std::format!("{}_{}", mesh_name, name_of!(triangles)),
-                    );
+    if !engine_config.import_fallback {
+        println!(
+            "Model {} isn't cooked, run asset_cook.",
+            model_path.display()
+        );
 
-                    let mesh_data = MeshData { vertices, indices };
+        return None;
+    }
 
-                    let mesh_buffer = MeshBuffer {
-                        mesh_object_device_address: Default::default(),
-                        vertex_buffer_reference,
-                        vertex_indices_buffer_reference,
-                        meshlets_buffer_reference,
-                        local_indices_buffer_reference,
-                        meshlets_count: meshlets.len(),
-                        mesh_data,
-                    };
+    println!(
+        "Model {} isn't cooked, cooking it on demand.",
+        model_path.display()
+    );
+    let asset_cooker = AssetCooker::new(engine_config)
+        .with_import_settings(import_settings)
+        .with_meshlet_cone_weight(cone_weight);
+    if let Err(error) = asset_cooker.cook_model(model_path, &mut Default::default()) {
+        println!("Failed to cook {}: {}", model_path.display(), error);
 
-                    mesh_buffer_reference = mesh_buffers_pool.insert_mesh_buffer(mesh_buffer);
-                    mesh_buffers_to_upload.push(mesh_buffer_reference);
+        return None;
+    }
 
-                    e.insert((mesh, mesh_buffer_reference));
-                } else {
-                    let already_uploaded_mesh = uploaded_mesh_buffers.get(&mesh_index).unwrap();
-                    mesh_name = already_uploaded_mesh.0.name();
-                    mesh_buffer_reference = already_uploaded_mesh.1;
-                }
+    CookedModel::open(&cooked_model_path, model_path, asset_key, settings_hash)
+}
 
-                spawn_event_record.name = mesh_name;
-                spawn_event_record.parent_index = Some(node_data.index);
-                spawn_event_record.material_reference = Some(material_reference);
-                spawn_event_record.mesh_buffer_reference = Some(mesh_buffer_reference);
-                spawn_event_record.transform = LocalTransform::IDENTITY;
+pub(crate) fn create_mesh_buffer(
+    buffers_pool: &mut BuffersPool,
+    mesh_geometry: MeshGeometry,
+    mesh_name: &str,
+    vertex_format: VertexFormat,
+) -> MeshBuffer {
+    let MeshGeometry {
+        vertices,
+        indices,
+        meshlets,
+        vertex_indices,
+        triangles,
+        mesh_statistics,
+    } = mesh_geometry;
+    let aabb = get_aabb(&vertices);
+
+    create_mesh_buffer_from_parts(
+        buffers_pool,
+        MeshData { vertices, indices },
+        MeshletsData {
+            meshlets: &meshlets,
+            vertex_indices: &vertex_indices,
+            triangles: &triangles,
+        },
+        aabb,
+        mesh_statistics,
+        mesh_name,
+        vertex_format,
+    )
+}
 
-                spawn_event.spawn_records.push(spawn_event_record.clone());
-            }
+/// `aabb` is the minimum and the maximum of the vertex positions.
+pub(crate) fn create_mesh_buffer_from_parts(
+    buffers_pool: &mut BuffersPool,
+    mesh_data: MeshData,
+    meshlets_data: MeshletsData,
+    aabb: (Vec3, Vec3),
+    mesh_statistics: MeshStatistics,
+    mesh_name: &str,
+    vertex_format: VertexFormat,
+) -> MeshBuffer {
+    let vertices = &mesh_data.vertices;
+    let MeshletsData {
+        meshlets,
+        vertex_indices,
+        triangles,
+    } = meshlets_data;
+
+    let (vertex_buffer_reference, position_offset, position_scale) = match vertex_format {
+        VertexFormat::Full => (
+            create_and_copy_to_buffer(
+                buffers_pool,
+                vertices,
+                std::format!("{}_{}", mesh_name, name_of!(vertices)),
+            ),
+            Vec3::ZERO,
+            Vec3::ONE,
+        ),
+        VertexFormat::Compressed => {
+            let (position_offset, position_scale) = CompressedVertex::get_quantization(vertices);
+            let compressed_vertices = vertices
+                .iter()
+                .map(|vertex| CompressedVertex::new(vertex, position_offset, position_scale))
+                .collect::<Vec<_>>();
+
+            (
+                create_and_copy_to_buffer(
+                    buffers_pool,
+                    &compressed_vertices,
+                    std::format!("{}_{}", mesh_name, name_of!(compressed_vertices)),
+                ),
+                position_offset,
+                position_scale,
+            )
         }
+    };
+    let vertex_indices_buffer_reference = create_and_copy_to_buffer(
+        buffers_pool,
+        vertex_indices,
+        std::format!("{}_{}", mesh_name, name_of!(vertex_indices)),
+    );
+    let meshlets_buffer_reference = create_and_copy_to_buffer(
+        buffers_pool,
+        meshlets,
+        std::format!("{}_{}", mesh_name, name_of!(meshlets)),
+    );
+
+    let local_indices_buffer_reference = create_and_copy_to_buffer(
+        buffers_pool,
+        triangles,
+        std::format!("{}_{}", mesh_name, name_of!(triangles)),
+    );
+
+    let (bounding_center, bounding_radius) = get_bounding_sphere(vertices, aabb);
+
+    MeshBuffer {
+        mesh_object_device_address: Default::default(),
+        vertex_buffer_reference,
+        vertex_indices_buffer_reference,
+        meshlets_buffer_reference,
+        local_indices_buffer_reference,
+        meshlets_count: meshlets.len(),
+        bounding_center,
+        bounding_radius,
+        vertex_format,
+        position_offset,
+        position_scale,
+        mesh_data,
+        mesh_statistics,
+        references_count: Default::default(),
     }
+}
+
+pub(crate) fn get_aabb(vertices: &[Vertex]) -> (Vec3, Vec3) {
+    vertices
+        .iter()
+        .fold((Vec3::MAX, Vec3::MIN), |(aabb_min, aabb_max), vertex| {
+            let position = Vec3::from_array(vertex.position);
+
+            (aabb_min.min(position), aabb_max.max(position))
+        })
+}
 
-    let mesh_objects_to_write = mesh_buffers_to_upload
+/// Center of the bounds and the distance to the farthest vertex from it.
+fn get_bounding_sphere(vertices: &[Vertex], (aabb_min, aabb_max): (Vec3, Vec3)) -> (Vec3, f32) {
+    let center = (aabb_min + aabb_max) * 0.5;
+    let radius = vertices
         .iter()
-        .map(|mesh_buffer_reference| {
+        .map(|vertex| Vec3::from_array(vertex.position).distance(center))
+        .fold(f32::EPSILON, f32::max);
+
+    (center, radius)
+}
+
+/// Writes mesh objects of the newly inserted mesh buffers into their slots of the mesh objects buffer.
+pub(crate) fn write_mesh_objects(
+    gpu_backend: &mut impl GpuBackend,
+    mesh_buffers_pool: &mut MeshBuffersPool,
+    mesh_objects_buffer_reference: BufferReference,
+    frame_allocator: FrameAllocator,
+    mesh_buffers_to_upload: &[MeshBufferReference],
+) {
+    let mesh_objects_to_write =
+        frame_allocator.collect(mesh_buffers_to_upload.iter().map(|&mesh_buffer_reference| {
             let mesh_buffer_ref = unsafe {
                 mesh_buffers_pool
-                    .get_mesh_buffer(*mesh_buffer_reference)
+                    .get_mesh_buffer(mesh_buffer_reference)
                     .unwrap_unchecked()
             };
 
-            let device_address_vertex_buffer: DeviceAddress = mesh_buffer_ref
-                .vertex_buffer_reference
-                .get_buffer_info()
-                .device_address;
-            let device_address_vertex_indices_buffer: DeviceAddress = mesh_buffer_ref
-                .vertex_indices_buffer_reference
-                .get_buffer_info()
-                .device_address;
-            let device_address_meshlets_buffer: DeviceAddress = mesh_buffer_ref
-                .meshlets_buffer_reference
-                .get_buffer_info()
-                .device_address;
-            let device_address_local_indices_buffer: DeviceAddress = mesh_buffer_ref
-                .local_indices_buffer_reference
-                .get_buffer_info()
-                .device_address;
-
             MeshObject {
-                device_address_vertex_buffer,
-                device_address_vertex_indices_buffer,
-                device_address_meshlets_buffer,
-                device_address_local_indices_buffer,
+                vertex_buffer_index: mesh_buffer_ref.vertex_buffer_reference.get_index(),
+                vertex_indices_buffer_index: mesh_buffer_ref
+                    .vertex_indices_buffer_reference
+                    .get_index(),
+                meshlets_buffer_index: mesh_buffer_ref.meshlets_buffer_reference.get_index(),
+                local_indices_buffer_index: mesh_buffer_ref
+                    .local_indices_buffer_reference
+                    .get_index(),
+                center: mesh_buffer_ref.bounding_center.to_array(),
+                radius: mesh_buffer_ref.bounding_radius,
+                position_offset: mesh_buffer_ref.position_offset.to_array(),
+                vertex_format: mesh_buffer_ref.vertex_format as _,
+                position_scale: mesh_buffer_ref.position_scale.to_array(),
                 ..Default::default()
             }
-        })
-        .collect::<Vec<_>>();
+        }));
 
     let mesh_object_size = std::mem::size_of::<MeshObject>();
-    let mesh_objects_device_address = renderer_resources
-        .mesh_objects_buffer_reference
-        .get_buffer_info()
+    let mesh_objects_device_address = gpu_backend
+        .get_buffer_info(mesh_objects_buffer_reference)
         .device_address;
 
-    let mesh_objects_to_copy_regions = mesh_buffers_to_upload
-        .into_iter()
-        .enumerate()
-        .map(|(src_mesh_buffer_index, mesh_buffer_reference)| {
-            let src_offset = src_mesh_buffer_index as u32 * mesh_object_size as u32;
-            let dst_offset = mesh_buffer_reference.get_index() * mesh_object_size as u32;
-
-            let mesh_buffer = unsafe {
-                mesh_buffers_pool
-                    .get_mesh_buffer_mut(mesh_buffer_reference)
-                    .unwrap_unchecked()
-            };
-
-            mesh_buffer.mesh_object_device_address =
-                mesh_objects_device_address + dst_offset as u64;
-
-            BufferCopy {
-                src_offset: src_offset as _,
-                dst_offset: dst_offset as _,
-                size: mesh_object_size as _,
-            }
-        })
-        .collect::<Vec<BufferCopy>>();
+    let mesh_objects_to_copy_regions =
+        frame_allocator.collect(mesh_buffers_to_upload.iter().copied().enumerate().map(
+            |(src_mesh_buffer_index, mesh_buffer_reference)| {
+                let src_offset = src_mesh_buffer_index as u32 * mesh_object_size as u32;
+                let dst_offset = mesh_buffer_reference.get_index() * mesh_object_size as u32;
+
+                let mesh_buffer = unsafe {
+                    mesh_buffers_pool
+                        .get_mesh_buffer_mut(mesh_buffer_reference)
+                        .unwrap_unchecked()
+                };
+
+                mesh_buffer.mesh_object_device_address =
+                    mesh_objects_device_address + dst_offset as u64;
+
+                BufferCopy {
+                    src_offset: src_offset as _,
+                    dst_offset: dst_offset as _,
+                    size: mesh_object_size as _,
+                }
+            },
+        ));
 
-    unsafe {
-        buffers_pool.transfer_data_to_buffer_with_offset(
-            renderer_resources.mesh_objects_buffer_reference,
-            mesh_objects_to_write.as_ptr() as *const _,
-            &mesh_objects_to_copy_regions,
-        );
-    }
+    gpu_backend.write_buffer_regions(
+        mesh_objects_buffer_reference,
+        bytemuck::cast_slice(&mesh_objects_to_write[..]),
+        &mesh_objects_to_copy_regions,
+    );
+}
 
-    let materials_data_buffer_reference = renderer_resources.materials_data_buffer_reference;
+pub(crate) fn write_materials_data(
+    gpu_backend: &mut impl GpuBackend,
+    materials_pool: &MaterialsPool,
+    materials_data_buffer_reference: BufferReference,
+) {
     let materials_data_to_write_slice = materials_pool.get_materials_data_to_write();
     for (&material_reference, data_to_write) in materials_data_to_write_slice {
-        let ptr_materials_data_to_write = data_to_write.as_slice().as_ptr();
-
         let material_instance = materials_pool
             .get_material_instance(material_reference)
             .unwrap();
@@ -474,221 +586,459 @@ pub fn on_load_model_system(
             ..Default::default()
         }];
 
-        unsafe {
-            buffers_pool.transfer_data_to_buffer_with_offset(
-                materials_data_buffer_reference,
-                ptr_materials_data_to_write as *const _,
-                &regions,
-            );
-        }
+        gpu_backend.write_buffer_regions(materials_data_buffer_reference, data_to_write, &regions);
     }
-
-    commands.trigger(spawn_event);
 }
 
-pub fn create_and_copy_to_buffer(
-    buffers_pool: &mut BuffersPool,
-    src: *const c_void,
-    size: usize,
+pub fn create_and_copy_to_buffer<T: Pod>(
+    gpu_backend: &mut impl GpuBackend,
+    src: &[T],
     name: String,
 ) -> BufferReference {
-    let buffer_reference = buffers_pool.create_buffer(
-        size,
+    let buffer_reference = gpu_backend.create_buffer(
+        std::mem::size_of_val(src),
         BufferUsageFlags::TransferDst,
         BufferVisibility::DeviceOnly,
-        None,
         Some(name),
     );
 
-    unsafe {
-        buffers_pool.transfer_data_to_buffer_raw(buffer_reference, src, size);
-    }
+    gpu_backend.write_objects(buffer_reference, src);
 
     buffer_reference
 }
 
+/// Uploads an uncompressed texture without mips, it's for textures outside of model files.
+pub(crate) fn upload_texture_from_path(
+    vulkan_context: &VulkanContextResource,
+    renderer_context: &RendererContext,
+    descriptor_set_handle: &mut DescriptorSetHandle,
+    buffers_pool: &mut BuffersPool,
+    textures_pool: &mut TexturesPool,
+    path: &Path,
+    format: Format,
+) -> TextureReference {
+    let image = ImageReader::open(path)
+        .unwrap()
+        .with_guessed_format()
+        .unwrap()
+        .decode()
+        .unwrap()
+        .to_rgba8();
+
+    upload_texture_from_image(
+        vulkan_context,
+        renderer_context,
+        descriptor_set_handle,
+        buffers_pool,
+        textures_pool,
+        &DynamicImage::ImageRgba8(image),
+        format,
+    )
+}
+
+/// Uploads pixels of the image as they are, so the format must match the layout of its pixels.
+pub(crate) fn upload_texture_from_image(
+    vulkan_context: &VulkanContextResource,
+    renderer_context: &RendererContext,
+    descriptor_set_handle: &mut DescriptorSetHandle,
+    buffers_pool: &mut BuffersPool,
+    textures_pool: &mut TexturesPool,
+    image: &DynamicImage,
+    format: Format,
+) -> TextureReference {
+    let extent = Extent3D {
+        width: image.width(),
+        height: image.height(),
+        depth: 1,
+    };
+    let (texture_reference, _) = textures_pool.create_texture(
+        None,
+        false,
+        format,
+        extent,
+        ImageUsageFlags::Sampled | ImageUsageFlags::TransferDst,
+        false,
+    );
+
+    let image_bytes = image.as_bytes();
+    if !textures_pool.copy_memory_to_texture(texture_reference, image_bytes) {
+        vulkan_context.transfer_data_to_image(
+            textures_pool.get_image(texture_reference).unwrap(),
+            buffers_pool,
+            image_bytes,
+            &renderer_context.upload_context,
+        );
+        textures_pool.record_image_state(texture_reference, ImageState::sampled_read());
+    }
+
+    let descriptor_texture = DescriptorKind::SampledImage(DescriptorSampledImage {
+        image_view: textures_pool
+            .get_image(texture_reference)
+            .unwrap()
+            .image_view,
+        index: texture_reference.get_index(),
+    });
+    descriptor_set_handle.update_binding(buffers_pool, descriptor_texture);
+
+    texture_reference
+}
+
+/// Uploads the pages of the atlas, references of the entries are the rects of their pages.
+pub(crate) fn upload_texture_atlas(
+    vulkan_context: &VulkanContextResource,
+    renderer_context: &RendererContext,
+    descriptor_set_handle: &mut DescriptorSetHandle,
+    buffers_pool: &mut BuffersPool,
+    textures_pool: &mut TexturesPool,
+    texture_atlas: TextureAtlas,
+    format: Format,
+) -> Vec<TextureReference> {
+    let page_texture_references: Vec<_> = texture_atlas
+        .pages
+        .into_iter()
+        .map(|page| {
+            upload_texture_from_image(
+                vulkan_context,
+                renderer_context,
+                descriptor_set_handle,
+                buffers_pool,
+                textures_pool,
+                &DynamicImage::ImageRgba8(page),
+                format,
+            )
+        })
+        .collect();
+
+    texture_atlas
+        .entries
+        .iter()
+        .map(|entry| page_texture_references[entry.page_index].with_uv_rect(entry.uv_rect))
+        .collect()
+}
+
 fn try_upload_texture(
     vulkan_context: &VulkanContextResource,
     renderer_context: &RendererContext,
     textures_pool: &mut TexturesPool,
     buffers_pool: &mut BuffersPool,
     descriptor_set_handle: &mut DescriptorSetHandle,
-    scene: &asset_importer::Scene,
-    uploaded_textures: &mut HashMap<usize, TextureReference>,
-    material: asset_importer::Material,
+    uploaded_textures: &mut HashMap<PathBuf, TextureReference>,
+    cooked_texture_path: PathBuf,
+    model_path: &Path,
     texture_reference_to_use: &mut TextureReference,
-    model_name: &str,
 ) {
-    if material.texture_count(asset_importer::TextureType::BaseColor) > Default::default() {
-        let texture_info = material
-            .texture(asset_importer::TextureType::BaseColor, Default::default())
-            .unwrap();
-        let texture_index = texture_info.path[1..].parse::<usize>().unwrap();
-
-        if let std::collections::hash_map::Entry::Vacant(e) = uploaded_textures.entry(texture_index)
-        {
-            let texture = scene.texture(texture_index).unwrap();
-            let texture_name = texture
-                .filename()
-                .unwrap_or(std::format!("{model_name}_texture_{texture_index}"));
+    if let Some(&texture_reference) = uploaded_textures.get(&cooked_texture_path) {
+        *texture_reference_to_use = texture_reference;
 
-            let (texture_reference, texture_data) = try_to_load_cached_texture(
-                textures_pool,
-                model_name,
-                texture.clone(),
-                &texture_name,
-            );
-            *texture_reference_to_use = texture_reference;
-
-            vulkan_context.transfer_data_to_image(
-                textures_pool.get_image(texture_reference).unwrap(),
-                buffers_pool,
-                texture_data.as_ptr() as *const _,
-                &renderer_context.upload_context,
-                Some(texture_data.len()),
-            );
-
-            let descriptor_texture = DescriptorKind::SampledImage(DescriptorSampledImage {
-                image_view: textures_pool
-                    .get_image(texture_reference)
-                    .unwrap()
-                    .image_view,
-                index: texture_reference.get_index(),
-            });
-            descriptor_set_handle.update_binding(buffers_pool, descriptor_texture);
-
-            let texture_metadata = texture_reference.texture_metadata;
-            println!(
-                "Name: {} | Index: {} | Extent: {}x{}x{}",
-                texture_name,
-                texture_reference.get_index(),
-                texture_metadata.width,
-                texture_metadata.height,
-                1,
-            );
-
-            e.insert(texture_reference);
-        } else {
-            *texture_reference_to_use = *uploaded_textures.get(&texture_index).unwrap();
-        }
+        return;
     }
-}
 
-fn try_to_load_cached_texture(
-    textures_pool: &mut TexturesPool,
-    model_name: &str,
-    texture: asset_importer::Texture,
-    texture_name: &str,
-) -> (TextureReference, Vec<u8>) {
-    let mut path = std::path::PathBuf::from("intermediate/textures/");
-    path.push(model_name);
-    std::fs::create_dir_all(&path).unwrap();
-
-    path.push(String::from_str(texture_name).unwrap());
-    let does_exist = std::fs::exists(&path).unwrap();
-
-    let texture_reference: TextureReference;
-    let mut texture_data: Vec<u8> = Vec::new();
-
-    if does_exist {
-        let texture = Ktx2Texture::from_file(&path).unwrap();
-        let texture_metadata_raw: Vec<u8> =
-            texture.get_metadata(stringify!(TextureMetadata)).unwrap();
-        let texture_metadata = *bytemuck::from_bytes::<TextureMetadata>(&texture_metadata_raw);
-
-        for mip_level_index in 0..texture_metadata.mip_levels_count {
-            texture_data.extend_from_slice(texture.get_image_data(mip_level_index, 0, 0).unwrap());
-        }
+    let Some((texture_reference, texture_data)) =
+        try_to_load_cooked_texture(textures_pool, &cooked_texture_path, model_path)
+    else {
+        return;
+    };
+    *texture_reference_to_use = texture_reference;
+
+    vulkan_context.transfer_data_to_image(
+        textures_pool.get_image(texture_reference).unwrap(),
+        buffers_pool,
+        &texture_data,
+        &renderer_context.upload_context,
+    );
+    textures_pool.record_image_state(texture_reference, ImageState::sampled_read());
 
-        let extent = Extent3D {
-            width: texture_metadata.width,
-            height: texture_metadata.height,
-            depth: 1,
-        };
+    let descriptor_texture = DescriptorKind::SampledImage(DescriptorSampledImage {
+        image_view: textures_pool
+            .get_image(texture_reference)
+            .unwrap()
+            .image_view,
+        index: texture_reference.get_index(),
+    });
+    descriptor_set_handle.update_binding(buffers_pool, descriptor_texture);
+
+    let texture_metadata = texture_reference.texture_metadata;
+    println!(
+        "Name: {} | Index: {} | Extent: {}x{}x{}",
+        cooked_texture_path.display(),
+        texture_reference.get_index(),
+        texture_metadata.width,
+        texture_metadata.height,
+        1,
+    );
 
-        let (created_texture_reference, _) = textures_pool.create_texture(
-            Some(&mut texture_data),
-            true,
-            Format::Bc1RgbSrgbBlock,
-            extent,
-            ImageUsageFlags::Sampled | ImageUsageFlags::TransferDst,
-            true,
-        );
+    uploaded_textures.insert(cooked_texture_path, texture_reference);
+}
+
+fn get_or_create_texture_sampler(
+    samplers_pool: &mut SamplersPool,
+    buffers_pool: &mut BuffersPool,
+    descriptor_set_handle: &mut DescriptorSetHandle,
+    sampler_preset: SamplerPreset,
+) -> u32 {
+    let (sampler_reference, is_created) = samplers_pool.get_or_create_sampler(sampler_preset);
+
+    if is_created {
+        let sampler_descriptor = DescriptorKind::Sampler(DescriptorSampler {
+            sampler: *samplers_pool.get_sampler(sampler_reference).unwrap(),
+            index: sampler_reference.get_index(),
+        });
+        descriptor_set_handle.update_binding(buffers_pool, sampler_descriptor);
+    }
 
-        texture_reference = created_texture_reference;
-    } else {
-        let mut data = texture.data_bytes_ref().unwrap();
+    sampler_reference.get_index()
+}
 
-        let cursor = Cursor::new(&mut data);
+// NOTE: Values are taken from glTF spec, Assimp passes them as is.
+const GLTF_FILTER_NEAREST: i32 = 9728;
+const GLTF_FILTER_NEAREST_MIPMAP_NEAREST: i32 = 9984;
+const GLTF_FILTER_LINEAR_MIPMAP_NEAREST: i32 = 9985;
+const GLTF_FILTER_NEAREST_MIPMAP_LINEAR: i32 = 9986;
 
-        let image = ImageReader::new(cursor)
-            .with_guessed_format()
-            .unwrap()
-            .decode()
-            .unwrap();
+fn get_sampler_preset(cooked_material: &CookedMaterial) -> SamplerPreset {
+    let mut sampler_preset = SamplerPreset::DEFAULT;
 
-        let extent = Extent3D {
-            width: image.width(),
-            height: image.height(),
-            depth: 1,
-        };
-        let rgba_image = image.to_rgba8();
-        let mut image_bytes = rgba_image.as_bytes().to_vec();
-
-        let (created_texture_reference, ktx_texture) = textures_pool.create_texture(
-            Some(&mut image_bytes),
-            false,
-            Format::Bc1RgbSrgbBlock,
-            extent,
-            ImageUsageFlags::Sampled | ImageUsageFlags::TransferDst,
-            true,
-        );
-        texture_reference = created_texture_reference;
+    if let Some(mag_filter) = CookedMaterial::get_value(cooked_material.mag_filter)
+        && mag_filter == GLTF_FILTER_NEAREST
+    {
+        sampler_preset.mag_filter = Filter::Nearest;
+    }
 
-        let ktx_texture = ktx_texture.unwrap();
-        for mip_level_index in 0..created_texture_reference.texture_metadata.mip_levels_count {
-            texture_data
-                .extend_from_slice(ktx_texture.get_image_data(mip_level_index, 0, 0).unwrap());
+    if let Some(min_filter) = CookedMaterial::get_value(cooked_material.min_filter) {
+        match min_filter {
+            GLTF_FILTER_NEAREST => {
+                sampler_preset.min_filter = Filter::Nearest;
+                sampler_preset.mip_map_enabled = false;
+            }
+            GLTF_FILTER_NEAREST_MIPMAP_NEAREST => {
+                sampler_preset.min_filter = Filter::Nearest;
+                sampler_preset.mipmap_mode = SamplerMipmapMode::Nearest;
+            }
+            GLTF_FILTER_LINEAR_MIPMAP_NEAREST => {
+                sampler_preset.mipmap_mode = SamplerMipmapMode::Nearest;
+            }
+            GLTF_FILTER_NEAREST_MIPMAP_LINEAR => {
+                sampler_preset.min_filter = Filter::Nearest;
+            }
+            _ => (),
         }
+    }
 
-        ktx_texture.write_to_file(path).unwrap();
+    if let Some(wrap_u) = CookedMaterial::get_value(cooked_material.wrap_u) {
+        sampler_preset.address_mode_u = get_sampler_address_mode(wrap_u);
+    }
+    if let Some(wrap_v) = CookedMaterial::get_value(cooked_material.wrap_v) {
+        sampler_preset.address_mode_v = get_sampler_address_mode(wrap_v);
     }
 
-    (texture_reference, texture_data)
+    sampler_preset.anisotropy_enabled = sampler_preset.mip_map_enabled
+        && sampler_preset.min_filter == Filter::Linear
+        && sampler_preset.mag_filter == Filter::Linear;
+
+    sampler_preset
 }
 
-fn get_mesh_indices(node: &Node, num_meshes: usize) -> Vec<usize> {
-    let mut mesh_indices = Vec::with_capacity(num_meshes);
-    if num_meshes > Default::default() {
-        for mesh_index in node.mesh_indices() {
-            mesh_indices.push(mesh_index);
-        }
+// NOTE: Values of aiTextureMapMode.
+fn get_sampler_address_mode(texture_map_mode: i32) -> SamplerAddressMode {
+    match texture_map_mode {
+        1 => SamplerAddressMode::ClampToEdge,
+        2 => SamplerAddressMode::MirroredRepeat,
+        3 => SamplerAddressMode::ClampToBorder,
+        _ => SamplerAddressMode::Repeat,
     }
+}
+
+fn try_to_load_cooked_texture(
+    textures_pool: &mut TexturesPool,
+    cooked_texture_path: &Path,
+    model_path: &Path,
+) -> Option<(TextureReference, Vec<u8>)> {
+    let Some((texture_metadata, mut texture_data)) =
+        read_cooked_texture(cooked_texture_path, model_path)
+    else {
+        println!(
+            "Texture {} isn't cooked, run asset_cook.",
+            cooked_texture_path.display()
+        );
+
+        return None;
+    };
+
+    let extent = Extent3D {
+        width: texture_metadata.width,
+        height: texture_metadata.height,
+        depth: 1,
+    };
+
+    let (texture_reference, _) = textures_pool.create_texture(
+        Some(&mut texture_data),
+        true,
+        Format::Bc1RgbSrgbBlock,
+        extent,
+        ImageUsageFlags::Sampled | ImageUsageFlags::TransferDst,
+        true,
+    );
 
-    mesh_indices
+    Some((texture_reference, texture_data))
+}
+
+pub(crate) fn build_mesh_geometry(
+    mut vertices: Vec<Vertex>,
+    mut indices: Vec<u32>,
+    cone_weight: f32,
+) -> MeshGeometry {
+    let remap = optimize_vertex_fetch_remap(&indices, vertices.len());
+    indices = remap_index_buffer(Some(&indices), vertices.len(), &remap);
+    vertices = remap_vertex_buffer(&vertices, vertices.len(), &remap);
+
+    optimize_vertex_cache_in_place(&mut indices, vertices.len());
+    let optimized_vertices = optimize_vertex_fetch(&mut indices, &vertices);
+
+    // NOTE: Meshlet bounds are read from the positions, so they must be the reordered vertices.
+    let position_offset = std::mem::offset_of!(Vertex, position);
+    let vertex_stride = std::mem::size_of::<Vertex>();
+    let vertex_data = typed_to_bytes(&optimized_vertices);
+
+    let vertex_data_adapter =
+        VertexDataAdapter::new(vertex_data, vertex_stride, position_offset).unwrap();
+
+    let (meshlets, vertex_indices, triangles) =
+        generate_meshlets(&indices, &vertex_data_adapter, cone_weight);
+    let mesh_statistics = analyze_mesh(&indices, &vertex_data_adapter);
+
+    MeshGeometry {
+        vertices: optimized_vertices,
+        indices,
+        meshlets,
+        vertex_indices,
+        triangles,
+        mesh_statistics,
+    }
+}
+
+fn analyze_mesh(indices: &[u32], vertices: &VertexDataAdapter) -> MeshStatistics {
+    const CACHE_SIZE: u32 = 16;
+
+    let vertex_cache_statistics =
+        analyze_vertex_cache(indices, vertices.vertex_count, CACHE_SIZE, 0, 0);
+    let overdraw_statistics = analyze_overdraw(indices, vertices);
+    let vertex_fetch_statistics =
+        analyze_vertex_fetch(indices, vertices.vertex_count, vertices.vertex_stride);
+
+    MeshStatistics {
+        acmr: vertex_cache_statistics.acmr,
+        atvr: vertex_cache_statistics.atvr,
+        overdraw: overdraw_statistics.overdraw,
+        overfetch: vertex_fetch_statistics.overfetch,
+    }
 }
 
 fn generate_meshlets(
     indices: &[u32],
     vertices: &VertexDataAdapter,
+    cone_weight: f32,
 ) -> (Vec<Meshlet>, Vec<u32>, Vec<u8>) {
     let max_vertices = 64;
     let max_triangles = 64;
-    let cone_weight = 0.0;
 
     let raw_meshlets = build_meshlets(indices, vertices, max_vertices, max_triangles, cone_weight);
 
     let mut meshlets = Vec::new();
 
-    for raw_meshlet in raw_meshlets.meshlets.iter() {
+    for (raw_meshlet, meshlet) in raw_meshlets.meshlets.iter().zip(raw_meshlets.iter()) {
+        let bounds = compute_meshlet_bounds(meshlet, vertices);
+
         meshlets.push(Meshlet {
             vertex_offset: raw_meshlet.vertex_offset as _,
             triangle_offset: raw_meshlet.triangle_offset as _,
             vertex_count: raw_meshlet.vertex_count as _,
             triangle_count: raw_meshlet.triangle_count as _,
-            ..Default::default()
+            center: bounds.center,
+            radius: bounds.radius,
+            cone_axis: bounds.cone_axis,
+            cone_cutoff: bounds.cone_cutoff,
         });
     }
 
     (meshlets, raw_meshlets.vertices, raw_meshlets.triangles)
 }
+
+#[cfg(test)]
+mod tests {
+    use vulkanite::vk::BufferUsageFlags;
+
+    use super::{create_and_copy_to_buffer, write_materials_data};
+    use crate::engine::{
+        components::material::{MaterialState, MaterialType},
+        ecs::materials_pool::MaterialsPool,
+        general::renderer::{GpuBackend, MockGpuBackend},
+        resources::buffers_pool::BufferVisibility,
+    };
+
+    #[test]
+    fn materials_are_written_at_their_offsets() {
+        let mut gpu_backend = MockGpuBackend::new();
+        let materials_data_buffer_reference = gpu_backend.create_buffer(
+            16,
+            BufferUsageFlags::StorageBuffer,
+            BufferVisibility::DeviceOnly,
+            None,
+        );
+        let mut materials_pool = MaterialsPool::new(
+            gpu_backend
+                .get_buffer_info(materials_data_buffer_reference)
+                .device_address,
+            4,
+        );
+        let material_state = MaterialState {
+            material_type: MaterialType::Opaque,
+        };
+        let (first_material_reference, _) =
+            materials_pool.write_material(&[1, 2, 3, 4], material_state, &[]);
+        let (second_material_reference, _) =
+            materials_pool.write_material(&[5, 6], material_state, &[]);
+        // NOTE: Same data is deduplicated, nothing new is written.
+        let (deduplicated_material_reference, is_new) =
+            materials_pool.write_material(&[5, 6], material_state, &[]);
+        assert!(!is_new);
+        assert_eq!(deduplicated_material_reference, second_material_reference);
+
+        write_materials_data(
+            &mut gpu_backend,
+            &materials_pool,
+            materials_data_buffer_reference,
+        );
+
+        let materials_data = gpu_backend
+            .get_buffer_data(materials_data_buffer_reference)
+            .unwrap();
+        let first_offset = materials_pool
+            .get_material_instance(first_material_reference)
+            .unwrap()
+            .get_offset();
+        let second_offset = materials_pool
+            .get_material_instance(second_material_reference)
+            .unwrap()
+            .get_offset();
+        assert_eq!(
+            &materials_data[first_offset..first_offset + 4],
+            &[1, 2, 3, 4]
+        );
+        assert_eq!(&materials_data[second_offset..second_offset + 2], &[5, 6]);
+        assert_eq!(&materials_data[6..], &[0; 10]);
+    }
+
+    #[test]
+    fn created_buffer_holds_the_source() {
+        let mut gpu_backend = MockGpuBackend::new();
+
+        let buffer_reference =
+            create_and_copy_to_buffer(&mut gpu_backend, &[1u32, 2, 3], "Test".to_string());
+
+        assert_eq!(gpu_backend.get_buffer_info(buffer_reference).size, 12);
+        assert_eq!(
+            gpu_backend.read_objects::<u32>(buffer_reference, 3),
+            vec![1, 2, 3]
+        );
+    }
+}
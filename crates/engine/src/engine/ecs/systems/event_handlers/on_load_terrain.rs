@@ -0,0 +1,463 @@
+use std::path::Path;
+
+use bevy_ecs::{
+    hierarchy::ChildOf,
+    name::Name,
+    observer::On,
+    system::{Commands, Query, Res, ResMut},
+};
+use image::ImageReader;
+use math::{Vec2, Vec3, Vec4};
+use vulkanite::vk::Format;
+
+use crate::engine::{
+    components::{
+        local_transform::{GlobalTransform, LocalTransform},
+        material::{
+            MaterialData, MaterialProperties, MaterialSplat, MaterialState, MaterialTextures,
+            MaterialType,
+        },
+        terrain::{Terrain, TerrainChunk},
+    },
+    ecs::{
+        buffers_pool::BuffersPool,
+        materials_pool::{MaterialReference, MaterialsPool},
+        mesh_buffers_pool::MeshBuffersPool,
+        on_load_model::{
+            MeshGeometry, build_mesh_geometry, create_mesh_buffer, upload_texture_from_path,
+            write_materials_data, write_mesh_objects,
+        },
+        textures_pool::{TextureReference, TexturesPool},
+    },
+    events::LoadTerrainEvent,
+    general::renderer::DescriptorSetHandle,
+    resources::{
+        CullingSettings, FrameArena, RendererContext, RendererResources, TaskPool, Vertex,
+        VertexFormat, VulkanContextResource,
+    },
+};
+
+struct Heightmap {
+    width: usize,
+    height: usize,
+    heights: Vec<f32>,
+}
+
+impl Heightmap {
+    fn load(path: &Path) -> Self {
+        let image = ImageReader::open(path)
+            .unwrap()
+            .with_guessed_format()
+            .unwrap()
+            .decode()
+            .unwrap()
+            .to_luma32f();
+
+        Self {
+            width: image.width() as _,
+            height: image.height() as _,
+            heights: image.into_raw(),
+        }
+    }
+
+    /// Bilinear sample in `0.0..=1.0`, coordinates outside of the heightmap are clamped.
+    fn sample(&self, uv: Vec2) -> f32 {
+        let texel = uv.clamp(Vec2::ZERO, Vec2::ONE)
+            * Vec2::new((self.width - 1) as f32, (self.height - 1) as f32);
+        let x0 = texel.x.floor() as usize;
+        let y0 = texel.y.floor() as usize;
+        let x1 = (x0 + 1).min(self.width - 1);
+        let y1 = (y0 + 1).min(self.height - 1);
+        let fraction = texel - Vec2::new(x0 as f32, y0 as f32);
+
+        let get_height = |x: usize, y: usize| self.heights[y * self.width + x];
+        let top = get_height(x0, y0) + (get_height(x1, y0) - get_height(x0, y0)) * fraction.x;
+        let bottom = get_height(x0, y1) + (get_height(x1, y1) - get_height(x0, y1)) * fraction.x;
+
+        top + (bottom - top) * fraction.y
+    }
+}
+
+struct ChunkLodGeometry {
+    mesh_geometry: MeshGeometry,
+    bounds_min: Vec3,
+    bounds_max: Vec3,
+}
+
+pub fn on_load_terrain_system(
+    load_terrain_event: On<LoadTerrainEvent>,
+    mut commands: Commands,
+    terrains_query: Query<&Terrain>,
+    vulkan_context: Res<VulkanContextResource>,
+    renderer_context: Res<RendererContext>,
+    renderer_resources: Res<RendererResources>,
+    mut descriptor_set_handle: ResMut<DescriptorSetHandle>,
+    mut materials_pool: ResMut<MaterialsPool>,
+    mut buffers_pool: ResMut<BuffersPool>,
+    mut textures_pool: ResMut<TexturesPool>,
+    mut mesh_buffers_pool: ResMut<MeshBuffersPool>,
+    task_pool: Res<TaskPool>,
+    culling_settings: Res<CullingSettings>,
+    mut frame_arena: ResMut<FrameArena>,
+) {
+    let terrain_entity = load_terrain_event.terrain_entity;
+    let Ok(terrain) = terrains_query.get(terrain_entity) else {
+        return;
+    };
+
+    let heightmap = Heightmap::load(&terrain.heightmap_path);
+
+    let chunks_count = (terrain.chunks_per_side * terrain.chunks_per_side) as usize;
+    let lods_count = terrain.get_lods_count();
+    let mut chunks_lod_geometry = Vec::with_capacity(chunks_count * lods_count);
+    chunks_lod_geometry.resize_with(chunks_count * lods_count, || None);
+
+    let cone_weight = culling_settings.meshlet_cone_weight;
+    task_pool.scope(|scope| {
+        for (chunk_lod_index, chunk_lod_geometry) in chunks_lod_geometry.iter_mut().enumerate() {
+            let heightmap = &heightmap;
+            scope.spawn(move |_| {
+                let chunk_index = chunk_lod_index / lods_count;
+                let lod = chunk_lod_index % lods_count;
+                *chunk_lod_geometry = Some(build_chunk_lod_geometry(
+                    terrain,
+                    heightmap,
+                    chunk_index,
+                    lod,
+                    cone_weight,
+                ));
+            });
+        }
+    });
+
+    let (material_splat, material_texture_references) = upload_splat_textures(
+        terrain,
+        &vulkan_context,
+        &renderer_context,
+        &mut descriptor_set_handle,
+        &mut buffers_pool,
+        &mut textures_pool,
+    );
+    materials_pool.reset_materails_to_write();
+    let material_reference = write_terrain_material(
+        &renderer_resources,
+        &mut materials_pool,
+        &mut textures_pool,
+        material_splat,
+        material_texture_references,
+    );
+
+    let terrain_name = terrain
+        .heightmap_path
+        .file_stem()
+        .and_then(|file_stem| file_stem.to_str())
+        .unwrap_or("terrain")
+        .to_owned();
+
+    let frame_allocator = frame_arena.get_allocator();
+    let mut mesh_buffers_to_upload = frame_allocator.vec_with_capacity(chunks_lod_geometry.len());
+    let mut chunks_lod_geometry = chunks_lod_geometry.into_iter().flatten();
+    for chunk_index in 0..chunks_count {
+        let chunk_name = std::format!("{terrain_name}_chunk_{chunk_index}");
+
+        let mut lod_mesh_buffer_references = Vec::with_capacity(lods_count);
+        let mut bounds_min = Vec3::MAX;
+        let mut bounds_max = Vec3::MIN;
+        for lod in 0..lods_count {
+            let chunk_lod_geometry = unsafe { chunks_lod_geometry.next().unwrap_unchecked() };
+            bounds_min = bounds_min.min(chunk_lod_geometry.bounds_min);
+            bounds_max = bounds_max.max(chunk_lod_geometry.bounds_max);
+
+            // NOTE: Quantized in the bounds of every chunk, shared edges wouldn't match exactly.
+            let mesh_buffer = create_mesh_buffer(
+                &mut buffers_pool,
+                chunk_lod_geometry.mesh_geometry,
+                &std::format!("{chunk_name}_lod_{lod}"),
+                VertexFormat::Full,
+            );
+            let mesh_buffer_reference = mesh_buffers_pool.insert_mesh_buffer(mesh_buffer);
+            mesh_buffers_to_upload.push(mesh_buffer_reference);
+            lod_mesh_buffer_references.push(mesh_buffer_reference);
+        }
+
+        let chunk_transform = LocalTransform {
+            local_position: get_chunk_center(terrain, chunk_index),
+            ..LocalTransform::IDENTITY
+        };
+        let terrain_chunk = TerrainChunk {
+            lod_mesh_buffer_references,
+            material_reference,
+            lod_distances: terrain.lod_distances.clone(),
+            bounds_min,
+            bounds_max,
+            current_lod: None,
+        };
+
+        // NOTE: `Mesh` is inserted by `update_terrain_chunks_system` once the chunk is visible.
+        commands.spawn((
+            Name::new(std::format!("Terrain Chunk ID: {chunk_name}")),
+            GlobalTransform(chunk_transform.local_to_world_matrix()),
+            chunk_transform,
+            terrain_chunk,
+            ChildOf(terrain_entity),
+        ));
+    }
+
+    write_mesh_objects(
+        buffers_pool.as_mut(),
+        &mut mesh_buffers_pool,
+        renderer_resources.mesh_objects_buffer_reference,
+        frame_allocator,
+        &mesh_buffers_to_upload,
+    );
+    write_materials_data(
+        buffers_pool.as_mut(),
+        &materials_pool,
+        renderer_resources.materials_data_buffer_reference,
+    );
+}
+
+fn get_chunk_center(terrain: &Terrain, chunk_index: usize) -> Vec3 {
+    let chunks_per_side = terrain.chunks_per_side as usize;
+    let chunk_size = terrain.get_chunk_size();
+    let half_size = terrain.size * 0.5;
+
+    Vec3::new(
+        -half_size + ((chunk_index % chunks_per_side) as f32 + 0.5) * chunk_size,
+        0.0,
+        -half_size + ((chunk_index / chunks_per_side) as f32 + 0.5) * chunk_size,
+    )
+}
+
+/// Grid of the chunk in its local space, followed by the skirt vertices under its border.
+fn build_chunk_lod_geometry(
+    terrain: &Terrain,
+    heightmap: &Heightmap,
+    chunk_index: usize,
+    lod: usize,
+    cone_weight: f32,
+) -> ChunkLodGeometry {
+    let resolution = (terrain.chunk_resolution >> lod).max(1) as usize;
+    let chunk_size = terrain.get_chunk_size();
+    let half_chunk_size = chunk_size * 0.5;
+    let half_size = terrain.size * 0.5;
+    let chunk_center = get_chunk_center(terrain, chunk_index);
+    let step = chunk_size / resolution as f32;
+    // NOTE: Normals are taken with the step of the most detailed level, so they don't pop between levels.
+    let normal_step = chunk_size / terrain.chunk_resolution as f32;
+
+    let get_uv = |local_position: Vec2| {
+        (Vec2::new(chunk_center.x, chunk_center.z) + local_position + half_size) / terrain.size
+    };
+    let get_height = |uv: Vec2| heightmap.sample(uv) * terrain.height_scale;
+
+    let row_vertices_count = resolution + 1;
+    let grid_vertices_count = row_vertices_count * row_vertices_count;
+    let mut vertices = Vec::with_capacity(grid_vertices_count + resolution * 4 + 4);
+    for z in 0..row_vertices_count {
+        for x in 0..row_vertices_count {
+            let local_position = Vec2::new(
+                -half_chunk_size + x as f32 * step,
+                -half_chunk_size + z as f32 * step,
+            );
+            let uv = get_uv(local_position);
+
+            let normal_uv_step = normal_step / terrain.size;
+            let height_dx = get_height(uv + Vec2::new(normal_uv_step, 0.0))
+                - get_height(uv - Vec2::new(normal_uv_step, 0.0));
+            let height_dz = get_height(uv + Vec2::new(0.0, normal_uv_step))
+                - get_height(uv - Vec2::new(0.0, normal_uv_step));
+            let normal = Vec3::new(-height_dx, 2.0 * normal_step, -height_dz).normalize();
+
+            vertices.push(Vertex {
+                position: [local_position.x, get_height(uv), local_position.y],
+                normal: normal.to_array(),
+                uv: uv.to_array(),
+                color: Vec3::ONE.to_array(),
+                ..Default::default()
+            });
+        }
+    }
+
+    let mut indices = Vec::with_capacity(resolution * resolution * 6 + resolution * 24);
+    for z in 0..resolution {
+        for x in 0..resolution {
+            let top_left = (z * row_vertices_count + x) as u32;
+            let top_right = top_left + 1;
+            let bottom_left = top_left + row_vertices_count as u32;
+            let bottom_right = bottom_left + 1;
+
+            indices.extend_from_slice(&[
+                top_left,
+                bottom_left,
+                top_right,
+                top_right,
+                bottom_left,
+                bottom_right,
+            ]);
+        }
+    }
+
+    // NOTE: Every border is walked along +X or +Z, the winding is flipped where that faces inwards.
+    let borders = [
+        ((0..row_vertices_count).collect::<Vec<_>>(), true),
+        (
+            (0..row_vertices_count)
+                .map(|x| resolution * row_vertices_count + x)
+                .collect(),
+            false,
+        ),
+        (
+            (0..row_vertices_count)
+                .map(|z| z * row_vertices_count)
+                .collect(),
+            false,
+        ),
+        (
+            (0..row_vertices_count)
+                .map(|z| z * row_vertices_count + resolution)
+                .collect(),
+            true,
+        ),
+    ];
+    for (border_vertex_indices, is_winding_flipped) in borders {
+        let skirt_vertices_offset = vertices.len() as u32;
+        for &border_vertex_index in border_vertex_indices.iter() {
+            let mut skirt_vertex = vertices[border_vertex_index];
+            skirt_vertex.position[1] -= terrain.skirt_depth;
+            vertices.push(skirt_vertex);
+        }
+
+        for edge_index in 0..resolution {
+            let top_start = border_vertex_indices[edge_index] as u32;
+            let top_end = border_vertex_indices[edge_index + 1] as u32;
+            let bottom_start = skirt_vertices_offset + edge_index as u32;
+            let bottom_end = bottom_start + 1;
+
+            if is_winding_flipped {
+                indices.extend_from_slice(&[
+                    top_start,
+                    top_end,
+                    bottom_start,
+                    top_end,
+                    bottom_end,
+                    bottom_start,
+                ]);
+            } else {
+                indices.extend_from_slice(&[
+                    top_start,
+                    bottom_start,
+                    top_end,
+                    top_end,
+                    bottom_start,
+                    bottom_end,
+                ]);
+            }
+        }
+    }
+
+    let (bounds_min, bounds_max) = vertices.iter().fold(
+        (Vec3::MAX, Vec3::MIN),
+        |(bounds_min, bounds_max), vertex| {
+            let position = Vec3::from_array(vertex.position);
+            (bounds_min.min(position), bounds_max.max(position))
+        },
+    );
+
+    ChunkLodGeometry {
+        mesh_geometry: build_mesh_geometry(vertices, indices, cone_weight),
+        bounds_min,
+        bounds_max,
+    }
+}
+
+/// Layer textures and the splat map are uploaded only if both are set, otherwise the terrain is plain white.
+fn upload_splat_textures(
+    terrain: &Terrain,
+    vulkan_context: &VulkanContextResource,
+    renderer_context: &RendererContext,
+    descriptor_set_handle: &mut DescriptorSetHandle,
+    buffers_pool: &mut BuffersPool,
+    textures_pool: &mut TexturesPool,
+) -> (MaterialSplat, Vec<TextureReference>) {
+    let mut texture_references = Vec::new();
+    let Some(splat_map_path) = terrain.splat_map_path.as_ref() else {
+        return (Default::default(), texture_references);
+    };
+    if terrain.layer_texture_paths.is_empty() {
+        return (Default::default(), texture_references);
+    }
+
+    let mut upload_texture = |path: &Path, format: Format| {
+        let texture_reference = upload_texture_from_path(
+            vulkan_context,
+            renderer_context,
+            descriptor_set_handle,
+            buffers_pool,
+            textures_pool,
+            path,
+            format,
+        );
+        texture_references.push(texture_reference);
+
+        texture_reference.get_index()
+    };
+
+    // NOTE: Weights are linear, unlike the colors of the layers.
+    let splat_texture_index = upload_texture(splat_map_path, Format::R8G8B8A8Unorm);
+    let layer_texture_indices = terrain
+        .layer_texture_paths
+        .iter()
+        .map(|layer_texture_path| upload_texture(layer_texture_path, Format::R8G8B8A8Srgb))
+        .collect::<Vec<_>>();
+
+    let material_splat = MaterialSplat::new(
+        splat_texture_index,
+        &layer_texture_indices,
+        terrain.layer_tiling,
+    );
+
+    (material_splat, texture_references)
+}
+
+fn write_terrain_material(
+    renderer_resources: &RendererResources,
+    materials_pool: &mut MaterialsPool,
+    textures_pool: &mut TexturesPool,
+    material_splat: MaterialSplat,
+    material_texture_references: Vec<TextureReference>,
+) -> MaterialReference {
+    let fallback_texture_index = renderer_resources.fallback_texture_reference.get_index();
+    let default_sampler_index = renderer_resources.default_sampler_reference.get_index();
+
+    let material_data = MaterialData {
+        material_properties: MaterialProperties::new(Vec4::ONE, 0.0, 1.0),
+        material_textures: MaterialTextures::new(
+            fallback_texture_index,
+            fallback_texture_index,
+            fallback_texture_index,
+        )
+        .with_sampler_indices(
+            default_sampler_index,
+            default_sampler_index,
+            default_sampler_index,
+        ),
+        material_splat,
+        material_impostor: Default::default(),
+    };
+
+    let (material_reference, is_material_created) = materials_pool.write_material(
+        bytemuck::bytes_of(&material_data),
+        MaterialState {
+            material_type: MaterialType::Opaque,
+        },
+        &material_texture_references,
+    );
+    if is_material_created {
+        material_texture_references
+            .into_iter()
+            .for_each(|texture_reference| textures_pool.retain_texture(texture_reference));
+    }
+
+    material_reference
+}
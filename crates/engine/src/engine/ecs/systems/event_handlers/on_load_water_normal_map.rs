@@ -0,0 +1,49 @@
+use bevy_ecs::{
+    observer::On,
+    system::{Query, Res, ResMut},
+};
+use vulkanite::vk::Format;
+
+use crate::engine::{
+    components::water::WaterSettings,
+    ecs::{
+        buffers_pool::BuffersPool, on_load_model::upload_texture_from_path,
+        textures_pool::TexturesPool,
+    },
+    events::LoadWaterNormalMapEvent,
+    general::renderer::DescriptorSetHandle,
+    resources::{RendererContext, VulkanContextResource},
+};
+
+pub fn on_load_water_normal_map_system(
+    load_water_normal_map_event: On<LoadWaterNormalMapEvent>,
+    mut water_settings_query: Query<&mut WaterSettings>,
+    vulkan_context: Res<VulkanContextResource>,
+    renderer_context: Res<RendererContext>,
+    mut descriptor_set_handle: ResMut<DescriptorSetHandle>,
+    mut buffers_pool: ResMut<BuffersPool>,
+    mut textures_pool: ResMut<TexturesPool>,
+) {
+    let Ok(mut water_settings) =
+        water_settings_query.get_mut(load_water_normal_map_event.water_entity)
+    else {
+        return;
+    };
+    let Some(normal_map_path) = water_settings.normal_map_path.clone() else {
+        return;
+    };
+
+    // NOTE: Normals are stored in `0.0..=1.0`, so they aren't converted from sRGB.
+    let texture_reference = upload_texture_from_path(
+        &vulkan_context,
+        &renderer_context,
+        &mut descriptor_set_handle,
+        &mut buffers_pool,
+        &mut textures_pool,
+        &normal_map_path,
+        Format::R8G8B8A8Unorm,
+    );
+    textures_pool.retain_texture(texture_reference);
+
+    water_settings.set_normal_map_texture_reference(texture_reference);
+}
@@ -0,0 +1,36 @@
+use bevy_ecs::{observer::On, system::ResMut};
+
+use crate::engine::{
+    components::script_vars::ScriptValue,
+    events::{CVarChangedEvent, ConsoleCommandEvent},
+    resources::{CVarValue, ScriptHost},
+};
+
+/// Forwards console commands to the scripts subscribed to `ScriptHost::CONSOLE_COMMAND_EVENT`.
+pub fn on_console_command_script_system(
+    console_command_event: On<ConsoleCommandEvent>,
+    mut script_host: ResMut<ScriptHost>,
+) {
+    let arguments = std::iter::once(&console_command_event.name)
+        .chain(console_command_event.arguments.iter())
+        .map(|argument| ScriptValue::String(argument.clone()))
+        .collect();
+    script_host.send_event(ScriptHost::CONSOLE_COMMAND_EVENT, arguments);
+}
+
+/// Forwards cvar changes to the scripts subscribed to `ScriptHost::CVAR_CHANGED_EVENT`.
+pub fn on_cvar_changed_script_system(
+    cvar_changed_event: On<CVarChangedEvent>,
+    mut script_host: ResMut<ScriptHost>,
+) {
+    let value = match &cvar_changed_event.value {
+        &CVarValue::Bool(value) => ScriptValue::Bool(value),
+        &CVarValue::Int(value) => ScriptValue::Number(value as _),
+        &CVarValue::Float(value) => ScriptValue::Number(value as _),
+        CVarValue::String(value) => ScriptValue::String(value.clone()),
+    };
+    script_host.send_event(
+        ScriptHost::CVAR_CHANGED_EVENT,
+        vec![ScriptValue::String(cvar_changed_event.name.clone()), value],
+    );
+}
@@ -0,0 +1,87 @@
+use ahash::AHashSet;
+use bevy_ecs::system::{Query, Res, ResMut};
+use vulkanite::vk::BufferCopy;
+
+use crate::engine::{
+    components::{material_animation::AnimateMaterial, mesh::Mesh, time::Time},
+    general::renderer::GpuBackend,
+    resources::{RendererResources, buffers_pool::BuffersPool, materials_pool::MaterialsPool},
+};
+
+pub fn animate_materials_system(
+    time: Res<Time>,
+    mut materials_pool: ResMut<MaterialsPool>,
+    renderer_resources: Res<RendererResources>,
+    mut buffers_pool: ResMut<BuffersPool>,
+    animated_meshes_query: Query<(&Mesh, &AnimateMaterial)>,
+) {
+    if animated_meshes_query.is_empty() {
+        return;
+    }
+
+    let elapsed_time = time.get_elapsed_time();
+    let mut animated_material_references = AHashSet::new();
+    let mut properties_data = Vec::new();
+    let mut regions = Vec::new();
+    for (mesh, animate_material) in animated_meshes_query.iter() {
+        // NOTE: Shared material is animated once, by the first mesh that refers to it.
+        if !animated_material_references.insert(mesh.material_reference) {
+            continue;
+        }
+
+        let Some(mut material_properties) =
+            materials_pool.get_material_properties(mesh.material_reference)
+        else {
+            continue;
+        };
+        if let Some(base_color) = animate_material
+            .base_color
+            .as_ref()
+            .and_then(|base_color| base_color.evaluate(elapsed_time))
+        {
+            material_properties.base_color = base_color.to_array();
+        }
+        if let Some(emissive_color) = animate_material
+            .emissive_color
+            .as_ref()
+            .and_then(|emissive_color| emissive_color.evaluate(elapsed_time))
+        {
+            material_properties.emissive_color = emissive_color.to_array();
+        }
+        if let Some(roughness) = animate_material
+            .roughness
+            .as_ref()
+            .and_then(|roughness| roughness.evaluate(elapsed_time))
+        {
+            material_properties.roughness_value = roughness;
+        }
+
+        let Some(material_offset) = materials_pool
+            .get_material_instance(mesh.material_reference)
+            .map(|material_instance| material_instance.get_offset())
+        else {
+            continue;
+        };
+        if materials_pool
+            .set_material_properties(mesh.material_reference, material_properties)
+            .is_none()
+        {
+            continue;
+        }
+
+        // NOTE: Properties are the head of the material data, the rest of it doesn't change.
+        let material_properties_data = bytemuck::bytes_of(&material_properties);
+        regions.push(BufferCopy {
+            src_offset: properties_data.len() as _,
+            dst_offset: material_offset as _,
+            size: material_properties_data.len() as _,
+        });
+        properties_data.extend_from_slice(material_properties_data);
+    }
+
+    buffers_pool.write_buffer_regions(
+        renderer_resources.materials_data_buffer_reference,
+        &properties_data,
+        &regions,
+    );
+}
@@ -0,0 +1,11 @@
+use bevy_ecs::system::{Res, ResMut};
+
+use crate::engine::{Audio, AudioVolumes};
+
+pub fn apply_audio_volumes_system(mut audio: ResMut<Audio>, audio_volumes: Res<AudioVolumes>) {
+    if !audio_volumes.is_changed() {
+        return;
+    }
+
+    audio.apply_volumes(&audio_volumes);
+}
@@ -0,0 +1,98 @@
+use bevy_ecs::{
+    entity::Entity,
+    hierarchy::{ChildOf, Children},
+    name::Name,
+    query::{With, Without},
+    relationship::RelationshipTarget,
+    system::Query,
+    world::World,
+};
+
+use crate::engine::{
+    components::{local_transform::LocalTransform, mesh::Mesh},
+    resources::{SceneDumpFormat, SceneDumpNode, TypeRegistry, format_scene_dump},
+};
+
+pub(crate) type SceneDumpRootQuery<'w, 's> = Query<'w, 's, Entity, (With<Name>, Without<ChildOf>)>;
+pub(crate) type SceneDumpNodesQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Option<&'static Name>,
+        Option<&'static LocalTransform>,
+        Option<&'static Mesh>,
+        Option<&'static Children>,
+    ),
+>;
+
+pub fn collect_scene_dump_nodes_system(
+    root_query: SceneDumpRootQuery,
+    nodes_query: SceneDumpNodesQuery,
+) -> Vec<SceneDumpNode> {
+    root_query
+        .iter()
+        .map(|root_entity| build_scene_dump_node(root_entity, &nodes_query))
+        .collect()
+}
+
+/// Components of `TypeRegistry` are read through the world, so it's not a system itself.
+pub(crate) fn dump_scene(world: &mut World, scene_dump_format: SceneDumpFormat) -> String {
+    let mut nodes = world
+        .run_system_cached(collect_scene_dump_nodes_system)
+        .unwrap();
+
+    let world: &World = world;
+    let type_registry = world.resource::<TypeRegistry>();
+    nodes
+        .iter_mut()
+        .for_each(|node| add_registered_components(world, type_registry, node));
+
+    format_scene_dump(&nodes, scene_dump_format)
+}
+
+fn add_registered_components(
+    world: &World,
+    type_registry: &TypeRegistry,
+    node: &mut SceneDumpNode,
+) {
+    if let Ok(entity_ref) = world.get_entity(node.entity) {
+        node.components = type_registry.get_components_text(&entity_ref);
+    }
+
+    node.children
+        .iter_mut()
+        .for_each(|child_node| add_registered_components(world, type_registry, child_node));
+}
+
+fn build_scene_dump_node(entity: Entity, nodes_query: &SceneDumpNodesQuery) -> SceneDumpNode {
+    let Ok((name, transform, mesh, children)) = nodes_query.get(entity) else {
+        return SceneDumpNode {
+            entity,
+            name: entity.to_string(),
+            transform: Default::default(),
+            mesh_buffer_index: Default::default(),
+            material_index: Default::default(),
+            components: Default::default(),
+            children: Default::default(),
+        };
+    };
+
+    SceneDumpNode {
+        entity,
+        name: name
+            .map(|name| name.as_str().to_string())
+            .unwrap_or_else(|| entity.to_string()),
+        transform: transform.copied(),
+        mesh_buffer_index: mesh.map(|mesh| mesh.mesh_buffer_reference.get_index()),
+        material_index: mesh.map(|mesh| mesh.material_reference.get_index()),
+        components: Default::default(),
+        children: children
+            .map(|children| {
+                children
+                    .iter()
+                    .map(|child_entity| build_scene_dump_node(child_entity, nodes_query))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
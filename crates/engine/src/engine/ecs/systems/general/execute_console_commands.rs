@@ -0,0 +1,47 @@
+use bevy_ecs::system::{Commands, ResMut};
+
+use crate::engine::{
+    events::ConsoleCommandEvent,
+    resources::{CVars, Console},
+};
+
+pub fn execute_console_commands_system(
+    mut console: ResMut<Console>,
+    mut cvars: ResMut<CVars>,
+    mut commands: Commands,
+) {
+    for line in console.take_submitted_lines() {
+        console.log(std::format!("> {}", line));
+
+        let mut arguments = Console::parse_line(&line);
+        if arguments.is_empty() {
+            continue;
+        }
+
+        let name = arguments.remove(Default::default());
+        // NOTE: `<cvar>` prints the value, `<cvar> <value>` sets it.
+        if let Some(cvar) = cvars.get(&name) {
+            match arguments.first() {
+                Some(text) => {
+                    if let Err(error) = cvars.set_from_str(&name, text) {
+                        console.log(error.to_string());
+                    }
+                }
+                None => console.log(std::format!(
+                    "{} = {} (default {}) - {}",
+                    name,
+                    cvar.value,
+                    cvar.default_value,
+                    cvar.description
+                )),
+            }
+            continue;
+        }
+        if !console.is_command_registered(&name) {
+            console.log(std::format!("Unknown command: {}", name));
+            continue;
+        }
+
+        commands.trigger(ConsoleCommandEvent { name, arguments });
+    }
+}
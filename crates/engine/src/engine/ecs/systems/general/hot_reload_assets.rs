@@ -0,0 +1,70 @@
+use std::collections::HashSet;
+
+use bevy_ecs::{
+    entity::Entity,
+    hierarchy::Children,
+    system::{Commands, Query, Res, ResMut},
+};
+
+use crate::engine::{
+    components::{loaded_model::LoadedModel, time::Time},
+    cook::{AssetCooker, CookReport},
+    events::LoadModelEvent,
+    resources::{AssetHotReload, CullingSettings, EngineConfig, ImportSettings},
+};
+
+pub fn hot_reload_assets_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    engine_config: Res<EngineConfig>,
+    import_settings: Res<ImportSettings>,
+    culling_settings: Res<CullingSettings>,
+    mut asset_hot_reload: ResMut<AssetHotReload>,
+    loaded_models_query: Query<(Entity, &LoadedModel)>,
+) {
+    if !asset_hot_reload.is_enabled || !asset_hot_reload.tick(time.get_delta_time()) {
+        return;
+    }
+
+    let loaded_model_paths = loaded_models_query
+        .iter()
+        .map(|(_, loaded_model)| loaded_model.path.as_path())
+        .collect::<HashSet<_>>();
+    asset_hot_reload.retain_models(|model_path| loaded_model_paths.contains(model_path));
+    let changed_model_paths = asset_hot_reload.poll_changed_models();
+    loaded_model_paths
+        .iter()
+        .for_each(|model_path| asset_hot_reload.watch_model(model_path));
+
+    if changed_model_paths.is_empty() {
+        return;
+    }
+
+    // NOTE: Cook is forced, since buffers and textures of a `.gltf` aren't compared with the artifact.
+    let asset_cooker = AssetCooker::new(&engine_config)
+        .with_import_settings(*import_settings)
+        .with_meshlet_cone_weight(culling_settings.meshlet_cone_weight)
+        .with_forced(true);
+    for model_path in changed_model_paths {
+        println!("Reloading {}", model_path.display());
+        // NOTE: Failed cook keeps the spawned model, so a half saved file doesn't empty the scene.
+        if let Err(error) = asset_cooker.cook_model(&model_path, &mut CookReport::default()) {
+            println!("Failed to cook {}: {}", model_path.display(), error);
+
+            continue;
+        }
+
+        // NOTE: Parent keeps its transform, only the spawned hierarchy under it is replaced.
+        // Removed meshes release their buffers, materials and textures through `DeletionQueue`.
+        loaded_models_query
+            .iter()
+            .filter(|(_, loaded_model)| loaded_model.path == model_path)
+            .for_each(|(model_entity, _)| {
+                commands.entity(model_entity).despawn_related::<Children>();
+                commands.trigger(LoadModelEvent {
+                    path: model_path.clone(),
+                    parent_entity: Some(model_entity),
+                });
+            });
+    }
+}
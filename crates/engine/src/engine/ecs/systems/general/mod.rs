@@ -1,4 +1,22 @@
+pub mod animate_materials;
+pub mod apply_audio_volumes;
 pub mod check_audio_state;
+pub mod dump_scene;
+pub mod execute_console_commands;
+pub mod hot_reload_assets;
+pub mod notify_cvar_changes;
 pub mod physics_tick;
+pub mod process_snapshot_requests;
 pub mod propogate_disabled_to_new_children;
+pub mod run_scripts;
+pub mod stream_world_cells;
+pub mod update_camera_rigs;
+pub mod update_camera_shakes;
+#[cfg(feature = "debug-tools")]
+pub mod update_entity_inspector;
+pub mod update_impostors;
+pub mod update_replay;
+pub mod update_sun_occlusion;
+pub mod update_terrain_chunks;
 pub mod update_time;
+pub mod update_time_of_day;
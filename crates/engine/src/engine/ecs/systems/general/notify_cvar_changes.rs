@@ -0,0 +1,14 @@
+use bevy_ecs::system::{Commands, ResMut};
+
+use crate::engine::{events::CVarChangedEvent, resources::CVars};
+
+pub fn notify_cvar_changes_system(mut cvars: ResMut<CVars>, mut commands: Commands) {
+    for name in cvars.take_changed_names() {
+        let Some(cvar) = cvars.get(&name) else {
+            continue;
+        };
+
+        let value = cvar.value.clone();
+        commands.trigger(CVarChangedEvent { name, value });
+    }
+}
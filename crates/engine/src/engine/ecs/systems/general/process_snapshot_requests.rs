@@ -0,0 +1,44 @@
+use bevy_ecs::world::World;
+
+use crate::engine::resources::{Console, SnapshotRequest, Snapshots};
+
+/// Exclusive, entities are spawned and despawned right away while a snapshot is restored.
+pub fn process_snapshot_requests_system(world: &mut World) {
+    let requests = world.resource_mut::<Snapshots>().take_requests();
+    if requests.is_empty() {
+        return;
+    }
+
+    world.resource_scope::<Snapshots, _>(|world, snapshots| {
+        for request in requests {
+            let message = match request {
+                SnapshotRequest::Save(path) => {
+                    match snapshots
+                        .save(world)
+                        .and_then(|bytes| Ok(std::fs::write(&path, bytes)?))
+                    {
+                        Ok(()) => std::format!("Snapshot is saved to {}", path.display()),
+                        Err(error) => {
+                            std::format!("Failed to save snapshot to {}: {}", path.display(), error)
+                        }
+                    }
+                }
+                SnapshotRequest::Load(path) => {
+                    match std::fs::read(&path)
+                        .map_err(Into::into)
+                        .and_then(|bytes| snapshots.load(world, &bytes))
+                    {
+                        Ok(()) => std::format!("Snapshot is loaded from {}", path.display()),
+                        Err(error) => std::format!(
+                            "Failed to load snapshot from {}: {}",
+                            path.display(),
+                            error
+                        ),
+                    }
+                }
+            };
+
+            world.resource_mut::<Console>().log(message);
+        }
+    });
+}
@@ -0,0 +1,19 @@
+use bevy_ecs::world::World;
+
+use crate::engine::{
+    components::time::Time,
+    resources::{Console, ScriptHost},
+};
+
+/// Exclusive, scripts reach the world through the whitelisted functions of `ScriptHost`.
+pub fn run_scripts_system(world: &mut World) {
+    let delta_time = world.resource::<Time>().get_delta_time();
+    let errors = world.resource_scope::<ScriptHost, _>(|world, mut script_host| {
+        script_host.update(world, delta_time)
+    });
+
+    let mut console = world.resource_mut::<Console>();
+    for error in errors {
+        console.log(error);
+    }
+}
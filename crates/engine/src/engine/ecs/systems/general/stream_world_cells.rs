@@ -0,0 +1,53 @@
+use bevy_ecs::{
+    entity::Entity,
+    hierarchy::Children,
+    query::With,
+    system::{Commands, Query},
+};
+use math::Vec3;
+
+use crate::engine::{
+    components::{
+        camera::Camera, loaded_model::LoadedModel, local_transform::GlobalTransform,
+        world_cell::WorldCell,
+    },
+    events::LoadModelEvent,
+};
+
+pub fn stream_world_cells_system(
+    mut commands: Commands,
+    cameras_query: Query<&GlobalTransform, With<Camera>>,
+    mut world_cells_query: Query<(Entity, &GlobalTransform, &mut WorldCell)>,
+) {
+    // NOTE: Without a camera there is nothing to stream around, loaded cells are kept.
+    if cameras_query.is_empty() {
+        return;
+    }
+
+    for (world_cell_entity, world_cell_transform, mut world_cell) in world_cells_query.iter_mut() {
+        let world_cell_position = world_cell_transform.transform_point(Vec3::ZERO);
+        let camera_distance = cameras_query
+            .iter()
+            .map(|camera_transform| {
+                camera_transform
+                    .transform_point(Vec3::ZERO)
+                    .distance(world_cell_position)
+            })
+            .fold(f32::MAX, f32::min);
+
+        if !world_cell.is_loaded() && camera_distance <= world_cell.load_radius {
+            world_cell.set_loaded(true);
+            commands.trigger(LoadModelEvent {
+                path: world_cell.model_path.clone(),
+                parent_entity: Some(world_cell_entity),
+            });
+        } else if world_cell.is_loaded() && camera_distance > world_cell.unload_radius {
+            world_cell.set_loaded(false);
+            // NOTE: Removed meshes release their buffers, materials and textures through `DeletionQueue`.
+            commands
+                .entity(world_cell_entity)
+                .despawn_related::<Children>()
+                .remove::<LoadedModel>();
+        }
+    }
+}
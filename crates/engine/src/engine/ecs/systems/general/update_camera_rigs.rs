@@ -0,0 +1,78 @@
+use bevy_ecs::{
+    query::With,
+    system::{Query, Res},
+};
+use math::{EulerRot, Quat, Vec3};
+
+use crate::engine::{
+    components::{
+        camera::Camera,
+        camera_rig::{CameraRig, CameraRigMode},
+        local_transform::{GlobalTransform, LocalTransform},
+        time::Time,
+    },
+    ecs::physics::{PhysicsManager, RigidBody},
+};
+
+/// Runs after the game logic and the transform propagation, so the camera follows the target
+/// at the pose it's rendered with.
+pub fn update_camera_rigs_system(
+    time: Res<Time>,
+    physics_manager: Res<PhysicsManager>,
+    mut camera_rigs_query: Query<(&mut CameraRig, &mut LocalTransform), With<Camera>>,
+    targets_query: Query<(&GlobalTransform, Option<&RigidBody>)>,
+) {
+    for (mut camera_rig, mut transform) in camera_rigs_query.iter_mut() {
+        let Some((target_transform, target_rigid_body)) = camera_rig
+            .target
+            .and_then(|target| targets_query.get(target).ok())
+        else {
+            continue;
+        };
+
+        let (_, target_rotation, target_position) =
+            target_transform.0.to_scale_rotation_translation();
+        let pivot = target_position + camera_rig.look_at_offset;
+        let desired_position = match camera_rig.mode {
+            CameraRigMode::Follow { offset } => {
+                let target_forward = target_rotation * Vec3::NEG_Z;
+                let yaw = (-target_forward.x).atan2(-target_forward.z);
+
+                target_position + Quat::from_rotation_y(yaw) * offset
+            }
+            CameraRigMode::Orbit(orbit_rig) => {
+                let rotation = Quat::from_euler(
+                    EulerRot::YXZ,
+                    orbit_rig.yaw.to_radians(),
+                    orbit_rig.pitch.to_radians(),
+                    Default::default(),
+                );
+
+                pivot + rotation * Vec3::Z * orbit_rig.distance
+            }
+        };
+
+        let mut position = camera_rig.smooth_position(
+            transform.get_local_position(),
+            desired_position,
+            time.get_delta_time(),
+        );
+        // NOTE: Ray goes from the pivot, so the camera is pulled to the target, not through walls.
+        if let Some(collision_radius) = camera_rig.collision_radius {
+            let pivot_offset = position - pivot;
+            let distance = pivot_offset.length();
+            if let Some(hit_distance) = physics_manager.cast_ray(
+                pivot,
+                pivot_offset,
+                distance + collision_radius,
+                target_rigid_body.copied(),
+            ) {
+                position = pivot
+                    + pivot_offset.normalize_or_zero() * (hit_distance - collision_radius).max(0.0);
+            }
+        }
+
+        transform.set_local_position(position);
+        transform.look_at(pivot, Vec3::Y);
+    }
+}
@@ -0,0 +1,12 @@
+use bevy_ecs::system::{Query, Res};
+
+use crate::engine::components::{camera_shake::CameraShake, time::Time};
+
+pub fn update_camera_shakes_system(
+    time: Res<Time>,
+    mut camera_shakes_query: Query<&mut CameraShake>,
+) {
+    for mut camera_shake in camera_shakes_query.iter_mut() {
+        camera_shake.update(time.get_delta_time());
+    }
+}
@@ -0,0 +1,193 @@
+use bevy_ecs::{
+    entity::Entity,
+    hierarchy::{ChildOf, Children},
+    name::Name,
+    query::{With, Without},
+    relationship::RelationshipTarget,
+    system::{Query, Res, ResMut},
+    world::World,
+};
+
+use crate::engine::{
+    components::{camera::Camera, local_transform::LocalTransform, mesh::Mesh},
+    general::renderer::GpuBackend,
+    resources::{
+        EntityInspector, InspectorComponent, InspectorComponentEdit, InspectorEdit, InspectorNode,
+        InspectorSelection, RendererResources, TypeRegistry, buffers_pool::BuffersPool,
+        materials_pool::MaterialsPool,
+    },
+};
+
+pub fn update_entity_inspector_system(
+    mut entity_inspector: ResMut<EntityInspector>,
+    mut materials_pool: ResMut<MaterialsPool>,
+    renderer_resources: Res<RendererResources>,
+    mut buffers_pool: ResMut<BuffersPool>,
+    root_query: Query<Entity, (With<Name>, Without<ChildOf>)>,
+    nodes_query: Query<(Option<&Name>, Option<&Children>)>,
+    mut selected_query: Query<(
+        Option<&mut LocalTransform>,
+        Option<&mut Camera>,
+        Option<&Mesh>,
+    )>,
+) {
+    if !entity_inspector.is_open() {
+        return;
+    }
+
+    let hierarchy = root_query
+        .iter()
+        .map(|root_entity| build_inspector_node(root_entity, &nodes_query))
+        .collect();
+    entity_inspector.set_hierarchy(hierarchy);
+
+    let pending_edits = entity_inspector.take_pending_edits();
+    let Some(selected_entity) = entity_inspector.get_selected_entity() else {
+        return;
+    };
+    let Ok((mut local_transform, mut camera, mesh)) = selected_query.get_mut(selected_entity)
+    else {
+        // NOTE: Selected entity was despawned.
+        entity_inspector.set_selection(None);
+        return;
+    };
+
+    for pending_edit in pending_edits {
+        match pending_edit {
+            InspectorEdit::Transform(transform) => {
+                if let Some(local_transform) = local_transform.as_mut() {
+                    **local_transform = transform;
+                }
+            }
+            InspectorEdit::Camera {
+                fov,
+                clipping_planes,
+            } => {
+                if let Some(camera) = camera.as_mut() {
+                    camera.fov = fov;
+                    camera.clipping_planes = clipping_planes;
+                }
+            }
+            InspectorEdit::MaterialProperties(material_properties) => {
+                let Some(mesh) = mesh else {
+                    continue;
+                };
+                let Some(material_offset) = materials_pool
+                    .get_material_instance(mesh.material_reference)
+                    .map(|material_instance| material_instance.get_offset())
+                else {
+                    continue;
+                };
+                let Some(material_data) = materials_pool
+                    .set_material_properties(mesh.material_reference, material_properties)
+                else {
+                    continue;
+                };
+
+                buffers_pool.write_buffer(
+                    renderer_resources.materials_data_buffer_reference,
+                    material_offset,
+                    material_data,
+                );
+            }
+        }
+    }
+
+    let selection = InspectorSelection {
+        entity: selected_entity,
+        transform: local_transform.as_deref().copied(),
+        camera: camera
+            .as_deref()
+            .map(|camera| (camera.fov, camera.clipping_planes)),
+        material_properties: mesh
+            .and_then(|mesh| materials_pool.get_material_properties(mesh.material_reference)),
+    };
+    entity_inspector.set_selection(Some(selection));
+}
+
+/// Exclusive, components of `TypeRegistry` are inserted and removed by their names.
+pub fn update_inspector_components_system(world: &mut World) {
+    let mut entity_inspector = world.resource_mut::<EntityInspector>();
+    if !entity_inspector.is_open() {
+        return;
+    }
+    let pending_component_edits = entity_inspector.take_pending_component_edits();
+    let Some(selected_entity) = entity_inspector.get_selected_entity() else {
+        return;
+    };
+
+    world.resource_scope::<TypeRegistry, _>(|world, type_registry| {
+        let Ok(mut entity_world_mut) = world.get_entity_mut(selected_entity) else {
+            return;
+        };
+        for pending_component_edit in pending_component_edits {
+            let (name, result) = match &pending_component_edit {
+                InspectorComponentEdit::InsertDefault(name) => (
+                    name,
+                    type_registry.get(name).map(|registration| {
+                        registration.insert_default(&mut entity_world_mut);
+
+                        Ok(())
+                    }),
+                ),
+                InspectorComponentEdit::Set { name, text } => (
+                    name,
+                    type_registry
+                        .get(name)
+                        .map(|registration| registration.from_text(&mut entity_world_mut, text)),
+                ),
+                InspectorComponentEdit::Remove(name) => (
+                    name,
+                    type_registry.get(name).map(|registration| {
+                        registration.remove(&mut entity_world_mut);
+
+                        Ok(())
+                    }),
+                ),
+            };
+
+            match result {
+                Some(Ok(())) => (),
+                Some(Err(error)) => println!("Invalid value of {}: {}", name, error),
+                None => println!("Unregistered component: {}", name),
+            }
+        }
+
+        let selected_components = type_registry
+            .get_components_text(&world.entity(selected_entity))
+            .into_iter()
+            .map(|(name, text)| InspectorComponent { name, text })
+            .collect();
+        world
+            .resource_mut::<EntityInspector>()
+            .set_selected_components(selected_components);
+    });
+}
+
+fn build_inspector_node(
+    entity: Entity,
+    nodes_query: &Query<(Option<&Name>, Option<&Children>)>,
+) -> InspectorNode {
+    let Ok((name, children)) = nodes_query.get(entity) else {
+        return InspectorNode {
+            entity,
+            name: entity.to_string(),
+            children: Default::default(),
+        };
+    };
+
+    InspectorNode {
+        entity,
+        name: name
+            .map(|name| name.as_str().to_string())
+            .unwrap_or_else(|| entity.to_string()),
+        children: children
+            .map(|children| {
+                children
+                    .iter()
+                    .map(|child_entity| build_inspector_node(child_entity, nodes_query))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
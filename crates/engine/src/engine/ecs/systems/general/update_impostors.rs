@@ -0,0 +1,33 @@
+use bevy_ecs::{
+    entity::Entity,
+    hierarchy::Children,
+    query::With,
+    system::{Commands, Query},
+};
+
+use crate::engine::{
+    components::{impostor::Impostor, mesh::Mesh},
+    events::BakeImpostorEvent,
+};
+
+pub fn update_impostors_system(
+    mut commands: Commands,
+    mut impostors_query: Query<(Entity, &mut Impostor)>,
+    children_query: Query<&Children>,
+    meshes_query: Query<(), With<Mesh>>,
+) {
+    for (impostor_entity, mut impostor) in impostors_query.iter_mut() {
+        if impostor.is_baked() {
+            continue;
+        }
+
+        // NOTE: Models are spawned after their load, so the bake waits for the first mesh in the hierarchy.
+        let has_meshes = std::iter::once(impostor_entity)
+            .chain(children_query.iter_descendants(impostor_entity))
+            .any(|entity| meshes_query.contains(entity));
+        if has_meshes {
+            impostor.set_baked(true);
+            commands.trigger(BakeImpostorEvent { impostor_entity });
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use bevy_ecs::system::ResMut;
+
+use crate::engine::{
+    components::time::Time,
+    resources::{Input, Replay, ReplayInputEvent, ReplayMode},
+};
+
+pub fn update_replay_system(
+    mut replay: ResMut<Replay>,
+    mut input: ResMut<Input>,
+    mut time: ResMut<Time>,
+) {
+    match replay.get_mode() {
+        ReplayMode::Disabled => (),
+        ReplayMode::Recording => {
+            replay.end_recorded_frame(time.get_delta_time());
+        }
+        ReplayMode::Playback => {
+            if let Some(frame) = replay.next_played_back_frame() {
+                for input_event in frame.input_events {
+                    match input_event {
+                        ReplayInputEvent::KeyPressed(key_code) => input.press(key_code),
+                        ReplayInputEvent::KeyReleased(key_code) => input.release(key_code),
+                        ReplayInputEvent::MouseMoved { x, y } => input.set_mouse_delta((x, y)),
+                    }
+                }
+
+                time.set_delta_time(frame.delta_time);
+            } else {
+                input.release_all();
+            }
+        }
+    }
+}
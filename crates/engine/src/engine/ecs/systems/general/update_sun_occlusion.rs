@@ -0,0 +1,64 @@
+use bevy_ecs::{
+    entity::Entity,
+    name::Name,
+    query::Without,
+    system::{Commands, Query, Res},
+};
+
+use crate::engine::{
+    components::{
+        camera::Camera, local_transform::LocalTransform, sun_occlusion::SunOcclusion, time::Time,
+        visibility_query::VisibilityQuery,
+    },
+    resources::{EnvironmentSettings, PostProcessSettings},
+};
+
+pub fn update_sun_occlusion_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    environment_settings: Res<EnvironmentSettings>,
+    post_process_settings: Res<PostProcessSettings>,
+    cameras_query: Query<(&Camera, &LocalTransform), Without<SunOcclusion>>,
+    mut sun_occlusions_query: Query<(
+        Entity,
+        &mut SunOcclusion,
+        &mut LocalTransform,
+        &VisibilityQuery,
+    )>,
+) {
+    let Some(lens_flare) = post_process_settings.lens_flare.as_ref() else {
+        for (sun_occlusion_entity, ..) in sun_occlusions_query.iter() {
+            commands.entity(sun_occlusion_entity).despawn();
+        }
+
+        return;
+    };
+
+    let Some((_, mut sun_occlusion, mut local_transform, visibility_query)) =
+        sun_occlusions_query.iter_mut().next()
+    else {
+        commands.spawn((SunOcclusion::default(), Name::new("Sun Occlusion")));
+
+        return;
+    };
+    let Some((camera, camera_transform)) =
+        Camera::sort_active(cameras_query.iter()).into_iter().next()
+    else {
+        return;
+    };
+
+    // NOTE: Sun is placed right before the far plane, so only the sky is behind it.
+    local_transform.local_position = camera_transform.local_position
+        + environment_settings.sun_direction.normalize_or_zero()
+            * camera.clipping_planes.far
+            * 0.99;
+
+    let target_visibility = if visibility_query.is_visible().unwrap_or_default() {
+        1.0
+    } else {
+        0.0
+    };
+    let fade_factor = (time.get_delta_time() * lens_flare.fade_speed).clamp(0.0, 1.0);
+    let visibility = sun_occlusion.get_visibility();
+    sun_occlusion.set_visibility(visibility + (target_visibility - visibility) * fade_factor);
+}
@@ -0,0 +1,172 @@
+use bevy_ecs::{
+    entity::Entity,
+    system::{Commands, Query, Res},
+};
+use math::{Mat4, Vec3, Vec4};
+
+use crate::engine::{
+    components::{
+        camera::Camera,
+        local_transform::GlobalTransform,
+        mesh::Mesh,
+        terrain::{Terrain, TerrainChunk},
+    },
+    events::LoadTerrainEvent,
+    resources::RendererContext,
+};
+
+/// Planes of the camera frustum, points inside satisfy `dot(plane.xyz, point) + plane.w >= 0`.
+struct Frustum {
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    fn from_view_projection(view_projection: Mat4) -> Self {
+        let row_x = view_projection.row(0);
+        let row_y = view_projection.row(1);
+        let row_z = view_projection.row(2);
+        let row_w = view_projection.row(3);
+
+        // NOTE: Depth is in `0.0..=1.0`, so the depth planes are taken apart from the side ones.
+        Self {
+            planes: [
+                row_w + row_x,
+                row_w - row_x,
+                row_w + row_y,
+                row_w - row_y,
+                row_z,
+                row_w - row_z,
+            ],
+        }
+    }
+
+    fn intersects_aabb(&self, bounds_min: Vec3, bounds_max: Vec3) -> bool {
+        self.planes.iter().all(|plane| {
+            let normal = plane.truncate();
+            let farthest_point = Vec3::select(normal.cmpge(Vec3::ZERO), bounds_max, bounds_min);
+
+            normal.dot(farthest_point) + plane.w >= 0.0
+        })
+    }
+}
+
+struct CameraView {
+    position: Vec3,
+    /// Reflected cameras see the mirrored scene and cull nothing.
+    frustum: Option<Frustum>,
+}
+
+pub fn update_terrain_chunks_system(
+    mut commands: Commands,
+    renderer_context: Res<RendererContext>,
+    cameras_query: Query<(&Camera, &GlobalTransform)>,
+    mut terrains_query: Query<(Entity, &mut Terrain)>,
+    mut terrain_chunks_query: Query<(Entity, &GlobalTransform, &mut TerrainChunk)>,
+) {
+    for (terrain_entity, mut terrain) in terrains_query.iter_mut() {
+        if !terrain.is_loaded() {
+            terrain.set_loaded(true);
+            commands.trigger(LoadTerrainEvent { terrain_entity });
+        }
+    }
+
+    // NOTE: Without a camera chunks keep their last level of detail.
+    if cameras_query.is_empty() {
+        return;
+    }
+
+    let render_extent = renderer_context.render_extent;
+    let camera_views = Camera::sort_active(cameras_query.iter())
+        .into_iter()
+        .map(|(camera, camera_transform)| {
+            let frustum = camera.reflection_plane.is_none().then(|| {
+                let aspect_ratio = (render_extent.width as f32 * camera.viewport.width)
+                    / (render_extent.height as f32 * camera.viewport.height).max(f32::EPSILON);
+                let projection = camera.get_projection(aspect_ratio);
+
+                Frustum::from_view_projection(projection * camera_transform.0.inverse())
+            });
+
+            CameraView {
+                position: camera_transform.transform_point(Vec3::ZERO),
+                frustum,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    for (terrain_chunk_entity, terrain_chunk_transform, mut terrain_chunk) in
+        terrain_chunks_query.iter_mut()
+    {
+        let (bounds_min, bounds_max) = get_world_bounds(
+            terrain_chunk_transform,
+            terrain_chunk.bounds_min,
+            terrain_chunk.bounds_max,
+        );
+
+        let is_visible = camera_views.iter().any(|camera_view| {
+            camera_view
+                .frustum
+                .as_ref()
+                .is_none_or(|frustum| frustum.intersects_aabb(bounds_min, bounds_max))
+        });
+        if !is_visible {
+            // NOTE: Mesh buffers stay retained by the chunk, so the hidden chunk comes back without uploads.
+            if terrain_chunk.current_lod.take().is_some() {
+                commands.entity(terrain_chunk_entity).remove::<Mesh>();
+            }
+
+            continue;
+        }
+
+        let camera_distance = camera_views
+            .iter()
+            .map(|camera_view| {
+                camera_view
+                    .position
+                    .clamp(bounds_min, bounds_max)
+                    .distance(camera_view.position)
+            })
+            .fold(f32::MAX, f32::min);
+
+        let lod = terrain_chunk.get_lod(camera_distance);
+        if terrain_chunk.current_lod != Some(lod) {
+            terrain_chunk.current_lod = Some(lod);
+            commands.entity(terrain_chunk_entity).insert(Mesh {
+                mesh_buffer_reference: terrain_chunk.lod_mesh_buffer_references[lod],
+                material_reference: terrain_chunk.material_reference,
+            });
+        }
+    }
+}
+
+fn get_world_bounds(
+    global_transform: &GlobalTransform,
+    local_bounds_min: Vec3,
+    local_bounds_max: Vec3,
+) -> (Vec3, Vec3) {
+    (0..8).fold(
+        (Vec3::MAX, Vec3::MIN),
+        |(bounds_min, bounds_max), corner_index| {
+            let corner = Vec3::new(
+                if corner_index & 1 == 0 {
+                    local_bounds_min.x
+                } else {
+                    local_bounds_max.x
+                },
+                if corner_index & 2 == 0 {
+                    local_bounds_min.y
+                } else {
+                    local_bounds_max.y
+                },
+                if corner_index & 4 == 0 {
+                    local_bounds_min.z
+                } else {
+                    local_bounds_max.z
+                },
+            );
+            let world_corner = global_transform.transform_point(corner);
+
+            (bounds_min.min(world_corner), bounds_max.max(world_corner))
+        },
+    )
+}
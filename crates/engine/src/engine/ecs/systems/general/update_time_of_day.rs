@@ -0,0 +1,19 @@
+use bevy_ecs::system::{Res, ResMut};
+
+use crate::engine::{
+    components::time::Time,
+    resources::{EnvironmentSettings, TimeOfDay},
+};
+
+pub fn update_time_of_day_system(
+    time: Res<Time>,
+    mut time_of_day: ResMut<TimeOfDay>,
+    mut environment_settings: ResMut<EnvironmentSettings>,
+) {
+    if !time_of_day.is_enabled {
+        return;
+    }
+
+    time_of_day.advance(time.get_delta_time());
+    time_of_day.apply(&mut environment_settings);
+}
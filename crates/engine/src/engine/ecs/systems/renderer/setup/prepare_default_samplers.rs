@@ -2,13 +2,13 @@ use crate::engine::{
     ecs::{RendererResources, buffers_pool::BuffersPool, samplers_pool::SamplersPool},
     general::renderer::{DescriptorKind, DescriptorSampler, DescriptorSetHandle},
 };
-use bevy_ecs::system::{Res, ResMut};
+use bevy_ecs::system::ResMut;
 use vulkanite::vk::*;
 
 pub fn prepare_default_samplers_system(
     mut renderer_resources: ResMut<RendererResources>,
     mut descriptor_set_handle: ResMut<DescriptorSetHandle>,
-    buffers_pool: Res<BuffersPool>,
+    mut buffers_pool: ResMut<BuffersPool>,
     mut samplers_pool: ResMut<SamplersPool>,
 ) {
     let default_sampler_reference =
@@ -23,5 +23,5 @@ pub fn prepare_default_samplers_system(
         index: renderer_resources.default_sampler_reference.get_index(),
     });
 
-    descriptor_set_handle.update_binding(&buffers_pool, sampler_descriptor);
+    descriptor_set_handle.update_binding(&mut buffers_pool, sampler_descriptor);
 }
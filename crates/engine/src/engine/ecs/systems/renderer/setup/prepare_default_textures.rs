@@ -5,8 +5,8 @@ use vulkanite::vk::*;
 
 use crate::engine::{
     ecs::{
-        RendererContext, RendererResources, VulkanContextResource, buffers_pool::BuffersPool,
-        textures_pool::TexturesPool,
+        RenderScaleSettings, RendererContext, RendererResources, VulkanContextResource,
+        buffers_pool::BuffersPool, image_state_tracker::ImageState, textures_pool::TexturesPool,
     },
     general::renderer::{
         DescriptorKind, DescriptorSampledImage, DescriptorSetHandle, DescriptorStorageImage,
@@ -20,6 +20,7 @@ pub fn prepare_default_textures_system(
     mut descriptor_set_handle: ResMut<DescriptorSetHandle>,
     mut textures_pool: ResMut<TexturesPool>,
     mut buffers_pool: ResMut<BuffersPool>,
+    render_scale_settings: Res<RenderScaleSettings>,
 ) {
     let magenta = &pack_unorm_4x8(Vec4::new(1.0, 0.0, 1.0, 1.0));
     let black = &pack_unorm_4x8(Vec4::new(0.0, 0.0, 0.0, 0.0));
@@ -56,17 +57,23 @@ pub fn prepare_default_textures_system(
             .image_view,
         index: checkerboard_texture_reference.get_index(),
     });
-    descriptor_set_handle.update_binding(&buffers_pool, descriptor_checkerboard_image);
-
-    vulkan_ctx_resource.transfer_data_to_image(
+    descriptor_set_handle.update_binding(&mut buffers_pool, descriptor_checkerboard_image);
+
+    if !textures_pool.copy_memory_to_texture(
+        checkerboard_texture_reference,
+        bytemuck::cast_slice(&pixels),
+    ) {
+        vulkan_ctx_resource.transfer_data_to_image(
+            textures_pool
+                .get_image(checkerboard_texture_reference)
+                .unwrap(),
+            &mut buffers_pool,
+            bytemuck::cast_slice(&pixels),
+            &renderer_context.upload_context,
+        );
         textures_pool
-            .get_image(checkerboard_texture_reference)
-            .unwrap(),
-        &mut buffers_pool,
-        pixels.as_ptr() as *const _,
-        &renderer_context.upload_context,
-        None,
-    );
+            .record_image_state(checkerboard_texture_reference, ImageState::sampled_read());
+    }
 
     let white_image_extent = Extent3D {
         width: 1,
@@ -84,13 +91,18 @@ pub fn prepare_default_textures_system(
     renderer_resources.fallback_texture_reference = white_texture_reference;
 
     let white_image_pixels = [pack_unorm_4x8(Vec4::new(1.0, 1.0, 1.0, 1.0))];
-    vulkan_ctx_resource.transfer_data_to_image(
-        textures_pool.get_image(white_texture_reference).unwrap(),
-        &mut buffers_pool,
-        white_image_pixels.as_ptr() as *const _,
-        &renderer_context.upload_context,
-        None,
-    );
+    if !textures_pool.copy_memory_to_texture(
+        white_texture_reference,
+        bytemuck::cast_slice(&white_image_pixels),
+    ) {
+        vulkan_ctx_resource.transfer_data_to_image(
+            textures_pool.get_image(white_texture_reference).unwrap(),
+            &mut buffers_pool,
+            bytemuck::cast_slice(&white_image_pixels),
+            &renderer_context.upload_context,
+        );
+        textures_pool.record_image_state(white_texture_reference, ImageState::sampled_read());
+    }
 
     let descriptor_white_image = DescriptorKind::SampledImage(DescriptorSampledImage {
         image_view: textures_pool
@@ -99,9 +111,29 @@ pub fn prepare_default_textures_system(
             .image_view,
         index: white_texture_reference.get_index(),
     });
-    descriptor_set_handle.update_binding(&buffers_pool, descriptor_white_image);
+    descriptor_set_handle.update_binding(&mut buffers_pool, descriptor_white_image);
 
-    let draw_extent = renderer_context.draw_extent;
+    create_draw_textures(
+        &mut renderer_context,
+        &render_scale_settings,
+        &mut descriptor_set_handle,
+        &mut textures_pool,
+        &mut buffers_pool,
+    );
+}
+
+/// Draw and depth images are allocated with the max render scale, frames render into a part of them.
+pub(crate) fn create_draw_textures(
+    renderer_context: &mut RendererContext,
+    render_scale_settings: &RenderScaleSettings,
+    descriptor_set_handle: &mut DescriptorSetHandle,
+    textures_pool: &mut TexturesPool,
+    buffers_pool: &mut BuffersPool,
+) {
+    let draw_extent = RenderScaleSettings::get_scaled_extent(
+        renderer_context.draw_extent,
+        render_scale_settings.get_max_render_scale(),
+    );
     renderer_context
         .frames_data
         .iter_mut()
@@ -118,6 +150,7 @@ pub fn prepare_default_textures_system(
                 Format::R16G16B16A16Sfloat,
                 draw_image_extent,
                 ImageUsageFlags::TransferSrc
+                    | ImageUsageFlags::TransferDst
                     | ImageUsageFlags::Storage
                     | ImageUsageFlags::ColorAttachment,
                 false,
@@ -128,9 +161,18 @@ pub fn prepare_default_textures_system(
                 false,
                 Format::D32Sfloat,
                 draw_image_extent,
-                ImageUsageFlags::DepthStencilAttachment,
+                ImageUsageFlags::DepthStencilAttachment | ImageUsageFlags::Sampled,
                 false,
             );
+            // NOTE: Depth is read by the depth of field pass.
+            let descriptor_depth_image = DescriptorKind::SampledImage(DescriptorSampledImage {
+                image_view: textures_pool
+                    .get_image(depth_texture_reference)
+                    .unwrap()
+                    .image_view,
+                index: depth_texture_reference.get_index(),
+            });
+            descriptor_set_handle.update_binding(buffers_pool, descriptor_depth_image);
 
             let descriptor_draw_image = DescriptorKind::StorageImage(DescriptorStorageImage {
                 image_view: textures_pool
@@ -139,7 +181,7 @@ pub fn prepare_default_textures_system(
                     .image_view,
                 index: draw_texture_reference.get_index(),
             });
-            descriptor_set_handle.update_binding(&buffers_pool, descriptor_draw_image);
+            descriptor_set_handle.update_binding(buffers_pool, descriptor_draw_image);
 
             frame_data.draw_texture_reference = draw_texture_reference;
             frame_data.depth_texture_reference = depth_texture_reference;
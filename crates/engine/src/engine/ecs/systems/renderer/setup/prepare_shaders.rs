@@ -1,10 +1,17 @@
 use bevy_ecs::system::{Commands, Res, ResMut};
+use math::Vec4;
 use vulkanite::vk::{rs::*, *};
 
+#[cfg(feature = "debug-tools")]
+use crate::engine::ecs::{DebugOverlayRect, MAX_DEBUG_OVERLAY_RECTS_COUNT};
 use crate::engine::{
+    Camera,
     ecs::{
-        InstanceObject, MeshObject, RendererContext, RendererResources, SceneData, ShaderObject,
-        SwappableBuffer, VulkanContextResource,
+        DepthPick, DepthPicking, ExposureState, FoliageData, InstanceObject, LIGHT_CLUSTER_SIZE,
+        LIGHT_CLUSTERS_COUNT, MAX_FOLIAGE_INSTANCES_COUNT, MAX_FOLIAGES_COUNT,
+        MAX_POINT_LIGHTS_COUNT, MAX_VISIBILITY_QUERIES_COUNT, MeshObject, PersistentBuffer,
+        PointLightData, PushConstantOverflow, RendererContext, RendererResources, SceneData,
+        ShaderObject, SwappableBuffer, VulkanContextResource,
         buffers_pool::{BufferVisibility, BuffersPool},
         materials_pool::MaterialsPool,
     },
@@ -15,7 +22,7 @@ use crate::engine::{
 pub fn prepare_shaders_system(
     mut commands: Commands,
     vulkan_ctx_resource: Res<VulkanContextResource>,
-    render_context: ResMut<RendererContext>,
+    mut render_context: ResMut<RendererContext>,
     mut renderer_resources: ResMut<RendererResources>,
     descriptor_set_handle: Res<DescriptorSetHandle>,
     mut buffers_pool: ResMut<BuffersPool>,
@@ -25,10 +32,10 @@ pub fn prepare_shaders_system(
     let descriptor_set_layouts = [descriptor_set_handle.get_descriptor_set_layout()];
     let push_constant_ranges = descriptor_set_handle.push_contant_ranges.as_slice();
 
-    let mesh_shader_path = r"intermediate\shaders\mesh.slang.spv";
+    let mesh_shader_name = "mesh.slang.spv";
     let shaders_info = [
         ShaderInfo {
-            path: r"intermediate\shaders\gradient.slang.spv",
+            name: "gradient.slang.spv",
             flags: ShaderCreateFlagsEXT::empty(),
             stage: ShaderStageFlags::Compute,
             next_stage: ShaderStageFlags::empty(),
@@ -36,7 +43,7 @@ pub fn prepare_shaders_system(
             push_constant_ranges: Some(push_constant_ranges),
         },
         ShaderInfo {
-            path: mesh_shader_path,
+            name: mesh_shader_name,
             flags: ShaderCreateFlagsEXT::LinkStage,
             stage: ShaderStageFlags::TaskEXT,
             next_stage: ShaderStageFlags::MeshEXT,
@@ -44,7 +51,7 @@ pub fn prepare_shaders_system(
             push_constant_ranges: Some(push_constant_ranges),
         },
         ShaderInfo {
-            path: mesh_shader_path,
+            name: mesh_shader_name,
             flags: ShaderCreateFlagsEXT::LinkStage,
             stage: ShaderStageFlags::MeshEXT,
             next_stage: ShaderStageFlags::Fragment,
@@ -52,13 +59,135 @@ pub fn prepare_shaders_system(
             push_constant_ranges: Some(push_constant_ranges),
         },
         ShaderInfo {
-            path: mesh_shader_path,
+            name: mesh_shader_name,
             flags: ShaderCreateFlagsEXT::LinkStage,
             stage: ShaderStageFlags::Fragment,
             next_stage: ShaderStageFlags::empty(),
             descriptor_layouts: &descriptor_set_layouts,
             push_constant_ranges: Some(push_constant_ranges),
         },
+        ShaderInfo {
+            name: "luminance_histogram.slang.spv",
+            flags: ShaderCreateFlagsEXT::empty(),
+            stage: ShaderStageFlags::Compute,
+            next_stage: ShaderStageFlags::empty(),
+            descriptor_layouts: &descriptor_set_layouts,
+            push_constant_ranges: Some(push_constant_ranges),
+        },
+        ShaderInfo {
+            name: "exposure_adaptation.slang.spv",
+            flags: ShaderCreateFlagsEXT::empty(),
+            stage: ShaderStageFlags::Compute,
+            next_stage: ShaderStageFlags::empty(),
+            descriptor_layouts: &descriptor_set_layouts,
+            push_constant_ranges: Some(push_constant_ranges),
+        },
+        ShaderInfo {
+            name: "tonemap.slang.spv",
+            flags: ShaderCreateFlagsEXT::empty(),
+            stage: ShaderStageFlags::Compute,
+            next_stage: ShaderStageFlags::empty(),
+            descriptor_layouts: &descriptor_set_layouts,
+            push_constant_ranges: Some(push_constant_ranges),
+        },
+        ShaderInfo {
+            name: "post_effects.slang.spv",
+            flags: ShaderCreateFlagsEXT::empty(),
+            stage: ShaderStageFlags::Compute,
+            next_stage: ShaderStageFlags::empty(),
+            descriptor_layouts: &descriptor_set_layouts,
+            push_constant_ranges: Some(push_constant_ranges),
+        },
+        ShaderInfo {
+            name: "depth_of_field.slang.spv",
+            flags: ShaderCreateFlagsEXT::empty(),
+            stage: ShaderStageFlags::Compute,
+            next_stage: ShaderStageFlags::empty(),
+            descriptor_layouts: &descriptor_set_layouts,
+            push_constant_ranges: Some(push_constant_ranges),
+        },
+        ShaderInfo {
+            name: "screen_space_reflections.slang.spv",
+            flags: ShaderCreateFlagsEXT::empty(),
+            stage: ShaderStageFlags::Compute,
+            next_stage: ShaderStageFlags::empty(),
+            descriptor_layouts: &descriptor_set_layouts,
+            push_constant_ranges: Some(push_constant_ranges),
+        },
+        ShaderInfo {
+            name: "light_culling.slang.spv",
+            flags: ShaderCreateFlagsEXT::empty(),
+            stage: ShaderStageFlags::Compute,
+            next_stage: ShaderStageFlags::empty(),
+            descriptor_layouts: &descriptor_set_layouts,
+            push_constant_ranges: Some(push_constant_ranges),
+        },
+        ShaderInfo {
+            name: "water.slang.spv",
+            flags: ShaderCreateFlagsEXT::empty(),
+            stage: ShaderStageFlags::Compute,
+            next_stage: ShaderStageFlags::empty(),
+            descriptor_layouts: &descriptor_set_layouts,
+            push_constant_ranges: Some(push_constant_ranges),
+        },
+        ShaderInfo {
+            name: "foliage_scatter.slang.spv",
+            flags: ShaderCreateFlagsEXT::empty(),
+            stage: ShaderStageFlags::Compute,
+            next_stage: ShaderStageFlags::empty(),
+            descriptor_layouts: &descriptor_set_layouts,
+            push_constant_ranges: Some(push_constant_ranges),
+        },
+        ShaderInfo {
+            name: "visibility_queries.slang.spv",
+            flags: ShaderCreateFlagsEXT::empty(),
+            stage: ShaderStageFlags::Compute,
+            next_stage: ShaderStageFlags::empty(),
+            descriptor_layouts: &descriptor_set_layouts,
+            push_constant_ranges: Some(push_constant_ranges),
+        },
+        ShaderInfo {
+            name: "light_shafts.slang.spv",
+            flags: ShaderCreateFlagsEXT::empty(),
+            stage: ShaderStageFlags::Compute,
+            next_stage: ShaderStageFlags::empty(),
+            descriptor_layouts: &descriptor_set_layouts,
+            push_constant_ranges: Some(push_constant_ranges),
+        },
+        ShaderInfo {
+            name: "depth_pyramid.slang.spv",
+            flags: ShaderCreateFlagsEXT::empty(),
+            stage: ShaderStageFlags::Compute,
+            next_stage: ShaderStageFlags::empty(),
+            descriptor_layouts: &descriptor_set_layouts,
+            push_constant_ranges: Some(push_constant_ranges),
+        },
+        ShaderInfo {
+            name: "depth_picks.slang.spv",
+            flags: ShaderCreateFlagsEXT::empty(),
+            stage: ShaderStageFlags::Compute,
+            next_stage: ShaderStageFlags::empty(),
+            descriptor_layouts: &descriptor_set_layouts,
+            push_constant_ranges: Some(push_constant_ranges),
+        },
+        ShaderInfo {
+            name: "outlines.slang.spv",
+            flags: ShaderCreateFlagsEXT::empty(),
+            stage: ShaderStageFlags::Compute,
+            next_stage: ShaderStageFlags::empty(),
+            descriptor_layouts: &descriptor_set_layouts,
+            push_constant_ranges: Some(push_constant_ranges),
+        },
+        // NOTE: Optional shaders go last, so the indices of the others don't depend on features.
+        #[cfg(feature = "debug-tools")]
+        ShaderInfo {
+            name: "debug_overlay.slang.spv",
+            flags: ShaderCreateFlagsEXT::empty(),
+            stage: ShaderStageFlags::Compute,
+            next_stage: ShaderStageFlags::empty(),
+            descriptor_layouts: &descriptor_set_layouts,
+            push_constant_ranges: Some(push_constant_ranges),
+        },
     ];
 
     let created_shaders = create_shaders(device, &shaders_info);
@@ -67,6 +196,25 @@ pub fn prepare_shaders_system(
     renderer_resources.task_shader_object = created_shaders[1];
     renderer_resources.mesh_shader_object = created_shaders[2];
     renderer_resources.fragment_shader_object = created_shaders[3];
+    renderer_resources.luminance_histogram_compute_shader_object = created_shaders[4];
+    renderer_resources.exposure_adaptation_compute_shader_object = created_shaders[5];
+    renderer_resources.tonemap_compute_shader_object = created_shaders[6];
+    renderer_resources.post_effects_compute_shader_object = created_shaders[7];
+    renderer_resources.depth_of_field_compute_shader_object = created_shaders[8];
+    renderer_resources.screen_space_reflections_compute_shader_object = created_shaders[9];
+    renderer_resources.light_culling_compute_shader_object = created_shaders[10];
+    renderer_resources.water_compute_shader_object = created_shaders[11];
+    renderer_resources.foliage_scatter_compute_shader_object = created_shaders[12];
+    renderer_resources.visibility_queries_compute_shader_object = created_shaders[13];
+    renderer_resources.light_shafts_compute_shader_object = created_shaders[14];
+    renderer_resources.depth_pyramid_compute_shader_object = created_shaders[15];
+    renderer_resources.depth_picks_compute_shader_object = created_shaders[16];
+    renderer_resources.outlines_compute_shader_object = created_shaders[17];
+    #[cfg(feature = "debug-tools")]
+    {
+        renderer_resources.debug_overlay_compute_shader_object = created_shaders[18];
+    }
+    renderer_resources.shader_objects.extend(created_shaders);
 
     // TODO: Move to the other place.
     let materials_data_buffer_reference = buffers_pool.create_buffer(
@@ -92,10 +240,26 @@ pub fn prepare_shaders_system(
         instance_objects_buffers.push(instance_objects_buffer_reference);
     }
 
+    let mut static_instance_objects_buffers = Vec::with_capacity(render_context.frame_overlap);
+    for static_instances_objects_buffer_index in 0..static_instance_objects_buffers.capacity() {
+        let static_instance_objects_buffer_reference = buffers_pool.create_buffer(
+            std::mem::size_of::<InstanceObject>() * 262_144,
+            BufferUsageFlags::ShaderDeviceAddress | BufferUsageFlags::TransferDst,
+            BufferVisibility::HostVisible,
+            Some(MemoryPropertyFlags::LazilyAllocated),
+            Some(std::format!(
+                "Static Instances Objects Buffer {}",
+                static_instances_objects_buffer_index
+            )),
+        );
+
+        static_instance_objects_buffers.push(static_instance_objects_buffer_reference);
+    }
+
     let mut scene_data_buffers = Vec::with_capacity(render_context.frame_overlap);
     for scene_data_buffer_index in 0..scene_data_buffers.capacity() {
         let scene_data_buffer_reference = buffers_pool.create_buffer(
-            std::mem::size_of::<SceneData>(),
+            std::mem::size_of::<SceneData>() * Camera::MAX_ACTIVE_CAMERAS_COUNT,
             BufferUsageFlags::ShaderDeviceAddress | BufferUsageFlags::TransferDst,
             BufferVisibility::HostVisible,
             None,
@@ -108,6 +272,156 @@ pub fn prepare_shaders_system(
         scene_data_buffers.push(scene_data_buffer_reference);
     }
 
+    let mut point_lights_buffers = Vec::with_capacity(render_context.frame_overlap);
+    for point_lights_buffer_index in 0..point_lights_buffers.capacity() {
+        let point_lights_buffer_reference = buffers_pool.create_buffer(
+            std::mem::size_of::<PointLightData>() * MAX_POINT_LIGHTS_COUNT,
+            BufferUsageFlags::ShaderDeviceAddress | BufferUsageFlags::TransferDst,
+            BufferVisibility::HostVisible,
+            None,
+            Some(std::format!(
+                "Point Lights Buffer {}",
+                point_lights_buffer_index
+            )),
+        );
+
+        point_lights_buffers.push(point_lights_buffer_reference);
+    }
+
+    // NOTE: Written and read only by shaders, so every frame in flight owns one.
+    for (frame_index, frame_data) in render_context.frames_data.iter_mut().enumerate() {
+        frame_data.light_clusters_buffer_reference = buffers_pool.create_buffer(
+            LIGHT_CLUSTER_SIZE * LIGHT_CLUSTERS_COUNT * Camera::MAX_ACTIVE_CAMERAS_COUNT,
+            BufferUsageFlags::StorageBuffer | BufferUsageFlags::ShaderDeviceAddress,
+            BufferVisibility::DeviceOnly,
+            None,
+            Some(std::format!("Light Clusters Buffer {}", frame_index)),
+        );
+
+        frame_data.foliage_instances_buffer_reference = buffers_pool.create_buffer(
+            std::mem::size_of::<InstanceObject>()
+                * MAX_FOLIAGE_INSTANCES_COUNT
+                * Camera::MAX_ACTIVE_CAMERAS_COUNT,
+            BufferUsageFlags::StorageBuffer | BufferUsageFlags::ShaderDeviceAddress,
+            BufferVisibility::DeviceOnly,
+            None,
+            Some(std::format!("Foliage Instances Buffer {}", frame_index)),
+        );
+        frame_data.foliage_draw_arguments_buffer_reference = buffers_pool.create_buffer(
+            std::mem::size_of::<DrawMeshTasksIndirectCommandEXT>()
+                * Camera::MAX_ACTIVE_CAMERAS_COUNT,
+            BufferUsageFlags::StorageBuffer
+                | BufferUsageFlags::IndirectBuffer
+                | BufferUsageFlags::ShaderDeviceAddress
+                | BufferUsageFlags::TransferDst,
+            BufferVisibility::DeviceOnly,
+            None,
+            Some(std::format!(
+                "Foliage Draw Arguments Buffer {}",
+                frame_index
+            )),
+        );
+        // NOTE: Results are read on the host, once the fence of the frame is waited.
+        frame_data.visibility_query_results_buffer_reference = buffers_pool.create_buffer(
+            std::mem::size_of::<u32>() * MAX_VISIBILITY_QUERIES_COUNT,
+            BufferUsageFlags::StorageBuffer | BufferUsageFlags::ShaderDeviceAddress,
+            BufferVisibility::HostVisible,
+            None,
+            Some(std::format!(
+                "Visibility Query Results Buffer {}",
+                frame_index
+            )),
+        );
+        // NOTE: Picks are written on the host and their results are read, once the fence is waited.
+        frame_data.depth_picks_buffer_reference = buffers_pool.create_buffer(
+            std::mem::size_of::<DepthPick>() * DepthPicking::MAX_PICKS_COUNT,
+            BufferUsageFlags::StorageBuffer | BufferUsageFlags::ShaderDeviceAddress,
+            BufferVisibility::HostVisible,
+            None,
+            Some(std::format!("Depth Picks Buffer {}", frame_index)),
+        );
+        // NOTE: Counters are zeroed on the host before the frame counts into them.
+        frame_data.texture_usage_counters_buffer_reference = buffers_pool.create_buffer(
+            std::mem::size_of::<u32>() * DescriptorSetHandle::MAX_SAMPLED_IMAGES_COUNT as usize,
+            BufferUsageFlags::StorageBuffer | BufferUsageFlags::ShaderDeviceAddress,
+            BufferVisibility::HostVisible,
+            None,
+            Some(std::format!(
+                "Texture Usage Counters Buffer {}",
+                frame_index
+            )),
+        );
+    }
+
+    let mut foliages_buffers = Vec::with_capacity(render_context.frame_overlap);
+    for foliages_buffer_index in 0..foliages_buffers.capacity() {
+        let foliages_buffer_reference = buffers_pool.create_buffer(
+            std::mem::size_of::<FoliageData>() * MAX_FOLIAGES_COUNT,
+            BufferUsageFlags::ShaderDeviceAddress | BufferUsageFlags::TransferDst,
+            BufferVisibility::HostVisible,
+            None,
+            Some(std::format!("Foliages Buffer {}", foliages_buffer_index)),
+        );
+
+        foliages_buffers.push(foliages_buffer_reference);
+    }
+
+    let mut visibility_query_points_buffers = Vec::with_capacity(render_context.frame_overlap);
+    for visibility_query_points_buffer_index in 0..visibility_query_points_buffers.capacity() {
+        let visibility_query_points_buffer_reference = buffers_pool.create_buffer(
+            std::mem::size_of::<Vec4>() * MAX_VISIBILITY_QUERIES_COUNT,
+            BufferUsageFlags::ShaderDeviceAddress | BufferUsageFlags::TransferDst,
+            BufferVisibility::HostVisible,
+            None,
+            Some(std::format!(
+                "Visibility Query Points Buffer {}",
+                visibility_query_points_buffer_index
+            )),
+        );
+
+        visibility_query_points_buffers.push(visibility_query_points_buffer_reference);
+    }
+
+    if renderer_resources.push_constant_layout.is_overflowing() {
+        let mut push_constant_overflow_buffers = Vec::with_capacity(render_context.frame_overlap);
+        for push_constant_overflow_buffer_index in 0..push_constant_overflow_buffers.capacity() {
+            let push_constant_overflow_buffer_reference = buffers_pool.create_buffer(
+                std::mem::size_of::<PushConstantOverflow>() * Camera::MAX_ACTIVE_CAMERAS_COUNT,
+                BufferUsageFlags::UniformBuffer
+                    | BufferUsageFlags::ShaderDeviceAddress
+                    | BufferUsageFlags::TransferDst,
+                BufferVisibility::HostVisible,
+                None,
+                Some(std::format!(
+                    "Push Constant Overflow Buffer {}",
+                    push_constant_overflow_buffer_index
+                )),
+            );
+
+            push_constant_overflow_buffers.push(push_constant_overflow_buffer_reference);
+        }
+
+        renderer_resources
+            .resources_pool
+            .push_constant_overflow_buffer = Some(SwappableBuffer::new(
+            buffers_pool.as_ref(),
+            push_constant_overflow_buffers,
+        ));
+    }
+
+    let exposure_state_buffer_reference = buffers_pool.create_buffer(
+        std::mem::size_of::<ExposureState>(),
+        BufferUsageFlags::StorageBuffer
+            | BufferUsageFlags::ShaderDeviceAddress
+            | BufferUsageFlags::TransferDst,
+        BufferVisibility::DeviceOnly,
+        None,
+        Some("Exposure State Buffer".to_string()),
+    );
+    let exposure_state = ExposureState::default();
+    buffers_pool.transfer_value(exposure_state_buffer_reference, &exposure_state);
+    renderer_resources.exposure_state_buffer_reference = exposure_state_buffer_reference;
+
     let mesh_objects_buffer_reference = buffers_pool.create_buffer(
         std::mem::size_of::<MeshObject>() * 8192,
         BufferUsageFlags::ShaderDeviceAddress | BufferUsageFlags::TransferDst,
@@ -116,15 +430,60 @@ pub fn prepare_shaders_system(
         Some("Mesh Objects Buffer".to_string()),
     );
 
-    renderer_resources.resources_pool.instances_buffer =
-        Some(SwappableBuffer::new(instance_objects_buffers));
-    renderer_resources.resources_pool.scene_data_buffer =
-        Some(SwappableBuffer::new(scene_data_buffers));
+    renderer_resources.resources_pool.instances_buffer = Some(PersistentBuffer::new(
+        buffers_pool.as_ref(),
+        instance_objects_buffers,
+    ));
+    renderer_resources.resources_pool.static_instances_buffer = Some(PersistentBuffer::new(
+        buffers_pool.as_ref(),
+        static_instance_objects_buffers,
+    ));
+    renderer_resources.resources_pool.scene_data_buffer = Some(SwappableBuffer::new(
+        buffers_pool.as_ref(),
+        scene_data_buffers,
+    ));
+    renderer_resources.resources_pool.point_lights_buffer = Some(SwappableBuffer::new(
+        buffers_pool.as_ref(),
+        point_lights_buffers,
+    ));
+    renderer_resources.resources_pool.foliages_buffer = Some(SwappableBuffer::new(
+        buffers_pool.as_ref(),
+        foliages_buffers,
+    ));
+    renderer_resources
+        .resources_pool
+        .visibility_query_points_buffer = Some(SwappableBuffer::new(
+        buffers_pool.as_ref(),
+        visibility_query_points_buffers,
+    ));
+    #[cfg(feature = "debug-tools")]
+    {
+        let mut debug_overlay_rects_buffers = Vec::with_capacity(render_context.frame_overlap);
+        for debug_overlay_rects_buffer_index in 0..debug_overlay_rects_buffers.capacity() {
+            let debug_overlay_rects_buffer_reference = buffers_pool.create_buffer(
+                std::mem::size_of::<DebugOverlayRect>() * MAX_DEBUG_OVERLAY_RECTS_COUNT,
+                BufferUsageFlags::ShaderDeviceAddress | BufferUsageFlags::TransferDst,
+                BufferVisibility::HostVisible,
+                None,
+                Some(std::format!(
+                    "Debug Overlay Rects Buffer {}",
+                    debug_overlay_rects_buffer_index
+                )),
+            );
+
+            debug_overlay_rects_buffers.push(debug_overlay_rects_buffer_reference);
+        }
+
+        renderer_resources.resources_pool.debug_overlay_rects_buffer = Some(SwappableBuffer::new(
+            buffers_pool.as_ref(),
+            debug_overlay_rects_buffers,
+        ));
+    }
 
     renderer_resources.materials_data_buffer_reference = materials_data_buffer_reference;
     let materials_pool = MaterialsPool::new(
-        materials_data_buffer_reference
-            .get_buffer_info()
+        buffers_pool
+            .get_buffer_info(materials_data_buffer_reference)
             .device_address,
         2048,
     );
@@ -136,7 +495,7 @@ pub fn prepare_shaders_system(
 fn create_shaders(device: Device, shader_infos: &[ShaderInfo]) -> Vec<ShaderObject> {
     let shader_codes: Vec<Vec<u8>> = shader_infos
         .iter()
-        .map(|shader_info| load_shader(shader_info.path))
+        .map(|shader_info| load_shader(shader_info.name))
         .collect();
 
     let shader_create_infos: Vec<_> = shader_infos
@@ -0,0 +1,67 @@
+use bevy_ecs::{
+    query::{Or, With},
+    system::{Query, Res, ResMut},
+};
+use vulkanite::vk::{BufferUsageFlags, Format, ImageUsageFlags};
+
+use crate::engine::{
+    components::{
+        mesh::Mesh,
+        outline::{Highlighted, Selected},
+    },
+    general::renderer::DescriptorSetHandle,
+    resources::{
+        FrameContext, RendererContext,
+        buffers_pool::BuffersPool,
+        textures_pool::TexturesPool,
+        transient_resources_pool::{
+            TransientBufferDescriptor, TransientImageDescriptor, TransientResourcesPool,
+        },
+    },
+};
+
+/// Requests the images and buffers the passes of the frame write, they're as large as the draw
+/// image. Scene data is written next, so their indices and addresses are known by then.
+pub fn acquire_transient_resources_system(
+    renderer_context: Res<RendererContext>,
+    mut frame_context: ResMut<FrameContext>,
+    mut transient_resources_pool: ResMut<TransientResourcesPool>,
+    mut textures_pool: ResMut<TexturesPool>,
+    mut buffers_pool: ResMut<BuffersPool>,
+    mut descriptor_set_handle: ResMut<DescriptorSetHandle>,
+    outlined_meshes_query: Query<(), (With<Mesh>, Or<(With<Selected>, With<Highlighted>)>)>,
+) {
+    let draw_texture_metadata = renderer_context
+        .get_current_frame_data()
+        .draw_texture_reference
+        .texture_metadata;
+
+    frame_context.post_texture_reference = transient_resources_pool.acquire_image(
+        &mut textures_pool,
+        &mut buffers_pool,
+        &mut descriptor_set_handle,
+        "Post Image",
+        TransientImageDescriptor::new(
+            Format::R16G16B16A16Sfloat,
+            draw_texture_metadata.width,
+            draw_texture_metadata.height,
+            ImageUsageFlags::TransferSrc | ImageUsageFlags::Storage,
+        ),
+    );
+
+    // NOTE: Zeroed before the meshes that write it, only while anything is outlined.
+    frame_context.is_outlined = !outlined_meshes_query.is_empty();
+    if frame_context.is_outlined {
+        frame_context.outline_mask_buffer_reference = transient_resources_pool.acquire_buffer(
+            &mut buffers_pool,
+            "Outline Mask Buffer",
+            TransientBufferDescriptor::new(
+                std::mem::size_of::<u32>()
+                    * (draw_texture_metadata.width * draw_texture_metadata.height) as usize,
+                BufferUsageFlags::StorageBuffer
+                    | BufferUsageFlags::ShaderDeviceAddress
+                    | BufferUsageFlags::TransferDst,
+            ),
+        );
+    }
+}
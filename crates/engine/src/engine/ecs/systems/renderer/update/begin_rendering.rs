@@ -8,103 +8,174 @@ use vulkanite::{
 };
 
 use crate::engine::{
-    ecs::textures_pool::TexturesPool,
+    ecs::{
+        LIGHT_CLUSTERS_GRID,
+        buffer_state_tracker::BufferState,
+        buffers_pool::{BufferReference, BuffersPool},
+        image_state_tracker::ImageState,
+        textures_pool::TexturesPool,
+    },
     general::renderer::DescriptorSetHandle,
-    resources::{FrameContext, GraphicsPushConstant, RendererContext, RendererResources},
-    utils::{self, transition_image},
+    resources::{
+        CameraFrameData, FrameContext, FrameData, FrameError, FrameErrors, FrameGraphAccess,
+        FrameGraphCapture, FrameGraphPassInfo, GpuProfiler, GpuTimestamp, GraphicsPushConstant,
+        RendererContext, RendererResources, SceneData, VulkanContextResource,
+    },
+    utils,
 };
 
 pub fn begin_rendering_system(
+    vulkan_context: Res<VulkanContextResource>,
     render_context: Res<RendererContext>,
-    renderer_resources: Res<RendererResources>,
+    mut renderer_resources: ResMut<RendererResources>,
+    mut buffers_pool: ResMut<BuffersPool>,
     descriptor_set_handle: Res<DescriptorSetHandle>,
     textures_pool: ResMut<TexturesPool>,
     mut frame_context: ResMut<FrameContext>,
+    mut gpu_profiler: ResMut<GpuProfiler>,
+    mut frame_graph_capture: ResMut<FrameGraphCapture>,
+    mut frame_errors: ResMut<FrameErrors>,
 ) {
     let frame_data = render_context.get_current_frame_data();
 
     let command_buffer = frame_data.command_group.command_buffer;
     frame_context.command_buffer = Some(command_buffer);
+    frame_context.async_compute_command_group = frame_data.async_compute_command_group;
     frame_context.draw_texture_reference = frame_data.draw_texture_reference;
     frame_context.depth_texture_reference = frame_data.depth_texture_reference;
 
+    // NOTE: Missing resources abandon the frame before anything is recorded.
+    let Some(draw_image) = textures_pool.get_image(frame_context.draw_texture_reference) else {
+        frame_errors.record(FrameError::missing_resource("get the draw image"));
+
+        return;
+    };
+    let Some(depth_image) = textures_pool.get_image(frame_context.depth_texture_reference) else {
+        frame_errors.record(FrameError::missing_resource("get the depth image"));
+
+        return;
+    };
+    let resources_pool = &renderer_resources.resources_pool;
+    let Some(instance_objects_buffer) = resources_pool.instances_buffer.as_ref() else {
+        frame_errors.record(FrameError::missing_resource("get the instances buffer"));
+
+        return;
+    };
+    let device_address_instance_objects_buffer = buffers_pool
+        .get_buffer_info(instance_objects_buffer.get_current_buffer())
+        .device_address;
+    let Some(scene_data_buffer) = resources_pool.scene_data_buffer.as_ref() else {
+        frame_errors.record(FrameError::missing_resource("get the scene data buffer"));
+
+        return;
+    };
+    let device_address_scene_data_buffer = buffers_pool
+        .get_buffer_info(scene_data_buffer.get_current_buffer())
+        .device_address;
+    let Some(point_lights_buffer) = resources_pool.point_lights_buffer.as_ref() else {
+        frame_errors.record(FrameError::missing_resource("get the point lights buffer"));
+
+        return;
+    };
+    let is_light_culled = !point_lights_buffer.is_empty();
+    let Some(foliages_buffer) = resources_pool.foliages_buffer.as_ref() else {
+        frame_errors.record(FrameError::missing_resource("get the foliages buffer"));
+
+        return;
+    };
+    let foliages_count = foliages_buffer.len();
+
     let command_buffer_begin_info =
         utils::create_command_buffer_begin_info(CommandBufferUsageFlags::OneTimeSubmit);
 
-    command_buffer.begin(&command_buffer_begin_info).unwrap();
+    if let Err(status) = command_buffer.begin(&command_buffer_begin_info) {
+        frame_errors.record(FrameError::new("begin the command buffer", status));
 
-    let draw_image = textures_pool
-        .get_image(frame_context.draw_texture_reference)
-        .unwrap();
-    let depth_image = textures_pool
-        .get_image(frame_context.depth_texture_reference)
-        .unwrap();
+        return;
+    }
+    gpu_profiler.begin_frame(
+        &vulkan_context.device,
+        command_buffer,
+        render_context.frame_number,
+    );
+    frame_graph_capture.begin_frame();
 
-    transition_image(
+    // NOTE: Both are fully rewritten every frame, so their previous content is discarded.
+    textures_pool.discard_content(frame_context.draw_texture_reference);
+    textures_pool.discard_content(frame_context.depth_texture_reference);
+    frame_graph_capture.ensure_image_state(
         command_buffer,
-        draw_image.image,
-        ImageLayout::Undefined,
-        ImageLayout::General,
-        PipelineStageFlags2::Blit,
-        PipelineStageFlags2::ComputeShader,
-        AccessFlags2::TransferRead,
-        AccessFlags2::ShaderStorageWrite,
-        draw_image.image_aspect_flags,
-        frame_context
-            .draw_texture_reference
-            .texture_metadata
-            .mip_levels_count,
+        &textures_pool,
+        (frame_context.draw_texture_reference, "Draw Image"),
+        ImageState::compute_write(),
     );
-    transition_image(
+    frame_graph_capture.ensure_image_state(
         command_buffer,
-        depth_image.image,
-        ImageLayout::Undefined,
-        ImageLayout::General,
-        PipelineStageFlags2::LateFragmentTests,
-        PipelineStageFlags2::EarlyFragmentTests,
-        AccessFlags2::DepthStencilAttachmentWrite,
-        AccessFlags2::DepthStencilAttachmentWrite,
-        depth_image.image_aspect_flags,
-        frame_context
-            .depth_texture_reference
-            .texture_metadata
-            .mip_levels_count,
+        &textures_pool,
+        (frame_context.depth_texture_reference, "Depth Image"),
+        ImageState::new(
+            ImageLayout::General,
+            PipelineStageFlags2::EarlyFragmentTests | PipelineStageFlags2::LateFragmentTests,
+            AccessFlags2::DepthStencilAttachmentRead | AccessFlags2::DepthStencilAttachmentWrite,
+        ),
     );
 
-    let draw_image_extent3d = draw_image.extent;
-    let draw_image_extent2d = Extent2D {
-        width: draw_image_extent3d.width,
-        height: draw_image_extent3d.height,
-    };
+    // NOTE: Draw image is allocated with the max render scale, only its part is rendered.
+    let render_extent = render_context.render_extent;
 
-    let instance_objects_buffer_reference = renderer_resources
-        .resources_pool
-        .instances_buffer
-        .as_ref()
-        .unwrap()
-        .get_current_buffer();
-    let device_address_instance_objects_buffer = instance_objects_buffer_reference
-        .get_buffer_info()
-        .device_address;
-
-    let scene_data_buffer_reference = renderer_resources
-        .resources_pool
-        .scene_data_buffer
-        .as_ref()
-        .unwrap()
-        .get_current_buffer();
-    let device_address_scene_data_buffer =
-        scene_data_buffer_reference.get_buffer_info().device_address;
-
-    let mesh_push_constant = GraphicsPushConstant {
+    let mut mesh_push_constant = GraphicsPushConstant {
         device_address_scene_data: device_address_scene_data_buffer,
         device_address_instance_object: device_address_instance_objects_buffer,
         draw_image_index: frame_context.draw_texture_reference.get_index(),
         ..Default::default()
     };
 
+    if let Some(push_constant_overflow_buffer) = renderer_resources
+        .resources_pool
+        .push_constant_overflow_buffer
+        .as_mut()
+    {
+        push_constant_overflow_buffer.clear();
+        for camera_index in 0..frame_context.cameras.len() {
+            let mut push_constant_overflow = mesh_push_constant.get_overflow();
+            push_constant_overflow.device_address_scene_data +=
+                (camera_index * std::mem::size_of::<SceneData>()) as DeviceAddress;
+
+            push_constant_overflow_buffer.push(push_constant_overflow);
+        }
+        push_constant_overflow_buffer.flush(buffers_pool.as_mut());
+
+        mesh_push_constant.device_address_push_constant_overflow = buffers_pool
+            .get_buffer_info(push_constant_overflow_buffer.get_current_buffer())
+            .device_address;
+    }
+
+    frame_context.push_constant = mesh_push_constant;
+
+    if frame_context.is_outlined {
+        clear_outline_mask(
+            &buffers_pool,
+            command_buffer,
+            &mut frame_graph_capture,
+            frame_context.outline_mask_buffer_reference,
+        );
+    }
+
+    // NOTE: Uploads of this frame must be visible for task, mesh, fragment and compute stages.
+    buffers_pool.prepare_written_buffers(BufferState::shader_read());
+    if frame_data.is_texture_usage_counted {
+        buffers_pool.require_buffer_state(
+            frame_data.texture_usage_counters_buffer_reference,
+            BufferState::new(
+                PipelineStageFlags2::FragmentShader,
+                AccessFlags2::ShaderStorageRead | AccessFlags2::ShaderStorageWrite,
+            ),
+        );
+    }
+    frame_graph_capture.flush_buffer_barriers(command_buffer, &buffers_pool, "Uploaded Buffers");
+
     let pipeline_layout = descriptor_set_handle.get_pipeline_layout();
-    let descriptor_buffer_info = descriptor_set_handle.get_buffer_info();
+    let descriptor_buffer_info = descriptor_set_handle.get_buffer_info(&buffers_pool);
 
     command_buffer.push_constants(
         pipeline_layout,
@@ -113,40 +184,86 @@ pub fn begin_rendering_system(
             | ShaderStageFlags::Compute
             | ShaderStageFlags::TaskEXT,
         Default::default(),
-        size_of::<GraphicsPushConstant>() as u32,
+        renderer_resources.push_constant_layout.get_pushed_size(),
         &mesh_push_constant as *const _ as _,
     );
 
-    draw_gradient(
-        renderer_resources.as_ref(),
+    if is_light_culled {
+        cull_lights(
+            renderer_resources.as_ref(),
+            &buffers_pool,
+            command_buffer,
+            &mut frame_graph_capture,
+            frame_data.light_clusters_buffer_reference,
+            frame_context.cameras.len(),
+            pipeline_layout,
+            descriptor_buffer_info.device_address,
+        );
+    }
+
+    if frame_context.is_foliage_scattered() {
+        scatter_foliages(
+            renderer_resources.as_ref(),
+            &buffers_pool,
+            command_buffer,
+            &mut frame_graph_capture,
+            frame_data,
+            &frame_context,
+            foliages_count,
+            pipeline_layout,
+            descriptor_buffer_info.device_address,
+        );
+    }
+
+    let is_background_drawn = frame_context.background_clear_color.is_none()
+        && frame_context
+            .cameras
+            .iter()
+            .any(|camera_frame_data| camera_frame_data.is_background_drawn);
+    if is_background_drawn {
+        draw_gradient(
+            renderer_resources.as_ref(),
+            &buffers_pool,
+            command_buffer,
+            &mut frame_graph_capture,
+            &frame_context.cameras,
+            pipeline_layout,
+            descriptor_buffer_info.device_address,
+        );
+    }
+    gpu_profiler.write_timestamp(
         command_buffer,
-        draw_image_extent2d,
-        pipeline_layout,
-        descriptor_buffer_info.device_address,
+        GpuTimestamp::BackgroundEnd,
+        PipelineStageFlags2::ComputeShader,
     );
 
-    transition_image(
+    frame_graph_capture.ensure_image_state(
         command_buffer,
-        draw_image.image,
-        ImageLayout::General,
-        ImageLayout::General,
-        PipelineStageFlags2::ComputeShader,
-        PipelineStageFlags2::ColorAttachmentOutput,
-        AccessFlags2::ShaderStorageWrite,
-        AccessFlags2::ColorAttachmentRead,
-        draw_image.image_aspect_flags,
-        frame_context
-            .draw_texture_reference
-            .texture_metadata
-            .mip_levels_count,
+        &textures_pool,
+        (frame_context.draw_texture_reference, "Draw Image"),
+        ImageState::new(
+            ImageLayout::General,
+            PipelineStageFlags2::ColorAttachmentOutput,
+            AccessFlags2::ColorAttachmentRead | AccessFlags2::ColorAttachmentWrite,
+        ),
     );
 
+    // NOTE: Solid color doesn't need the compute pass, the attachment is just cleared.
+    let (color_load_op, clear_color) = match frame_context.background_clear_color {
+        Some(color) => (AttachmentLoadOp::Clear, color.to_array()),
+        None => (AttachmentLoadOp::Load, Default::default()),
+    };
     let color_attachment_infos = [RenderingAttachmentInfo {
         image_view: Some(draw_image.image_view.borrow()),
         image_layout: ImageLayout::General,
         resolve_mode: ResolveModeFlags::None,
-        load_op: AttachmentLoadOp::Load,
+        load_op: color_load_op,
         store_op: AttachmentStoreOp::Store,
+        clear_value: ClearValue {
+            color: ClearColorValue {
+                float32: clear_color,
+            },
+        },
         ..Default::default()
     }];
     let depth_attachment_info = &RenderingAttachmentInfo {
@@ -163,7 +280,7 @@ pub fn begin_rendering_system(
 
     let rendering_info = RenderingInfo {
         render_area: Rect2D {
-            extent: draw_image_extent2d,
+            extent: render_extent,
             ..Default::default()
         },
         layer_count: 1,
@@ -173,26 +290,10 @@ pub fn begin_rendering_system(
         ..Default::default()
     };
 
-    command_buffer.begin_rendering(&rendering_info);
-
-    let viewports = Viewport {
-        width: draw_image_extent2d.width as _,
-        height: -(draw_image_extent2d.height as f32),
-        min_depth: 0.0,
-        max_depth: 1.0,
-        y: draw_image_extent2d.height as f32,
-        ..Default::default()
-    };
-    let scissors = Rect2D {
-        extent: draw_image_extent2d,
-        ..Default::default()
-    };
-
-    command_buffer.set_viewport_with_count(&viewports);
-    command_buffer.set_scissor_with_count(&scissors);
+    begin_meshes_rendering(command_buffer, &mut frame_graph_capture, &rendering_info);
 
+    // NOTE: Viewport, scissor and front face are set per camera, when meshes are rendered.
     command_buffer.set_cull_mode(CullModeFlags::Back);
-    command_buffer.set_front_face(FrontFace::CounterClockwise);
     command_buffer.set_primitive_topology(PrimitiveTopology::TriangleList);
     command_buffer.set_polygon_mode_ext(PolygonMode::Fill);
     command_buffer.set_primitive_restart_enable(false);
@@ -263,10 +364,303 @@ pub fn begin_rendering_system(
     command_buffer.bind_shaders_ext(shader_stages.as_slice(), shaders.as_slice());
 }
 
+/// Meshes are drawn by `render_meshes_system` until `end_rendering_system`, they're recorded
+/// as a single pass.
+fn begin_meshes_rendering(
+    command_buffer: CommandBuffer,
+    frame_graph_capture: &mut FrameGraphCapture,
+    rendering_info: &RenderingInfo,
+) {
+    command_buffer.begin_rendering(rendering_info);
+    frame_graph_capture.record_pass(FrameGraphPassInfo::new(
+        "Meshes",
+        &[
+            FrameGraphAccess::buffer("Instances Objects Buffer"),
+            FrameGraphAccess::buffer("Static Instances Objects Buffer"),
+            FrameGraphAccess::buffer("Scene Data Buffer"),
+            FrameGraphAccess::buffer("Mesh Objects Buffer"),
+            FrameGraphAccess::buffer("Materials Data Buffer"),
+        ],
+        &[
+            FrameGraphAccess::image("Draw Image", ImageLayout::General),
+            FrameGraphAccess::image("Depth Image", ImageLayout::General),
+        ],
+    ));
+}
+
+/// Zeroes the mask, afterwards it's written by the fragment stage of the outlined meshes.
+fn clear_outline_mask(
+    buffers_pool: &BuffersPool,
+    command_buffer: CommandBuffer,
+    frame_graph_capture: &mut FrameGraphCapture,
+    outline_mask_buffer_reference: BufferReference,
+) {
+    // NOTE: Mask of the previous use might be still read by the outlines pass.
+    buffers_pool.require_buffer_state(
+        outline_mask_buffer_reference,
+        BufferState::new(PipelineStageFlags2::Clear, AccessFlags2::TransferWrite),
+    );
+    frame_graph_capture.flush_buffer_barriers(command_buffer, buffers_pool, "Outline Mask Buffer");
+
+    let outline_mask_buffer = unsafe {
+        buffers_pool
+            .get_buffer(outline_mask_buffer_reference)
+            .unwrap_unchecked()
+    };
+    command_buffer.fill_buffer(
+        outline_mask_buffer.buffer,
+        Default::default(),
+        WHOLE_SIZE,
+        Default::default(),
+    );
+    frame_graph_capture.record_pass(FrameGraphPassInfo::new(
+        "Outline Mask Clear",
+        &[],
+        &[FrameGraphAccess::buffer("Outline Mask Buffer")],
+    ));
+
+    buffers_pool.require_buffer_state(
+        outline_mask_buffer_reference,
+        BufferState::new(
+            PipelineStageFlags2::FragmentShader,
+            AccessFlags2::ShaderStorageRead | AccessFlags2::ShaderStorageWrite,
+        ),
+    );
+}
+
+/// Bins point lights into clusters of every camera, they're read by the fragment stage.
+#[allow(clippy::too_many_arguments)]
+fn cull_lights(
+    renderer_resources: &RendererResources,
+    buffers_pool: &BuffersPool,
+    command_buffer: CommandBuffer,
+    frame_graph_capture: &mut FrameGraphCapture,
+    light_clusters_buffer_reference: BufferReference,
+    cameras_count: usize,
+    pipeline_layout: PipelineLayout,
+    descriptor_buffer_device_address: DeviceAddress,
+) {
+    // NOTE: Buffer was last read by fragments, when its frame in flight was rendered before.
+    buffers_pool.require_buffer_state(
+        light_clusters_buffer_reference,
+        BufferState::new(
+            PipelineStageFlags2::ComputeShader,
+            AccessFlags2::ShaderStorageWrite,
+        ),
+    );
+    frame_graph_capture.flush_buffer_barriers(
+        command_buffer,
+        buffers_pool,
+        "Light Clusters Buffer",
+    );
+
+    let light_culling_compute_shader_object =
+        renderer_resources.light_culling_compute_shader_object;
+
+    let stages = [light_culling_compute_shader_object.stage];
+    let shaders = [light_culling_compute_shader_object.shader.unwrap()];
+
+    command_buffer.bind_shaders_ext(stages.as_slice(), shaders.as_slice());
+
+    let descriptor_binding_info = DescriptorBufferBindingInfoEXT::default()
+        .usage(BufferUsageFlags::ResourceDescriptorBufferEXT)
+        .address(descriptor_buffer_device_address);
+
+    let descriptor_binding_infos = [descriptor_binding_info];
+    command_buffer.bind_descriptor_buffers_ext(&descriptor_binding_infos);
+
+    let buffer_indices = [0];
+    let offsets = [0];
+    command_buffer.set_descriptor_buffer_offsets_ext(
+        PipelineBindPoint::Compute,
+        pipeline_layout,
+        Default::default(),
+        &buffer_indices,
+        &offsets,
+    );
+
+    let [_, _, slices_count] = LIGHT_CLUSTERS_GRID;
+    for camera_index in 0..cameras_count {
+        let (push_constant_offset, device_address) = renderer_resources
+            .resources_pool
+            .get_camera_push_constant(buffers_pool, camera_index);
+        command_buffer.push_constants(
+            pipeline_layout,
+            ShaderStageFlags::MeshEXT
+                | ShaderStageFlags::Fragment
+                | ShaderStageFlags::Compute
+                | ShaderStageFlags::TaskEXT,
+            push_constant_offset,
+            std::mem::size_of::<DeviceAddress>() as _,
+            &device_address as *const _ as _,
+        );
+
+        // NOTE: A group covers every tile of 4 slices.
+        command_buffer.dispatch(1, 1, slices_count.div_ceil(4));
+    }
+    frame_graph_capture.record_pass(FrameGraphPassInfo::new(
+        "Light Culling",
+        &[
+            FrameGraphAccess::buffer("Scene Data Buffer"),
+            FrameGraphAccess::buffer("Point Lights Buffer"),
+        ],
+        &[FrameGraphAccess::buffer("Light Clusters Buffer")],
+    ));
+
+    buffers_pool.require_buffer_state(
+        light_clusters_buffer_reference,
+        BufferState::new(
+            PipelineStageFlags2::FragmentShader,
+            AccessFlags2::ShaderStorageRead,
+        ),
+    );
+    frame_graph_capture.flush_buffer_barriers(
+        command_buffer,
+        buffers_pool,
+        "Light Clusters Buffer",
+    );
+}
+
+/// Appends visible foliage instances of every camera and counts them into its indirect draw arguments.
+#[allow(clippy::too_many_arguments)]
+fn scatter_foliages(
+    renderer_resources: &RendererResources,
+    buffers_pool: &BuffersPool,
+    command_buffer: CommandBuffer,
+    frame_graph_capture: &mut FrameGraphCapture,
+    frame_data: &FrameData,
+    frame_context: &FrameContext,
+    foliages_count: usize,
+    pipeline_layout: PipelineLayout,
+    descriptor_buffer_device_address: DeviceAddress,
+) {
+    let foliage_instances_buffer_reference = frame_data.foliage_instances_buffer_reference;
+    let foliage_draw_arguments_buffer_reference =
+        frame_data.foliage_draw_arguments_buffer_reference;
+
+    // NOTE: Arguments were last read by the indirect draw, when its frame in flight was rendered before.
+    buffers_pool.require_buffer_state(
+        foliage_draw_arguments_buffer_reference,
+        BufferState::new(PipelineStageFlags2::Clear, AccessFlags2::TransferWrite),
+    );
+    frame_graph_capture.flush_buffer_barriers(
+        command_buffer,
+        buffers_pool,
+        "Foliage Draw Arguments Buffer",
+    );
+
+    let foliage_draw_arguments_buffer = unsafe {
+        buffers_pool
+            .get_buffer(foliage_draw_arguments_buffer_reference)
+            .unwrap_unchecked()
+    };
+    // NOTE: Counts are zeroed, the pass sets the rest of the group counts with the first instance.
+    command_buffer.fill_buffer(
+        foliage_draw_arguments_buffer.buffer,
+        Default::default(),
+        WHOLE_SIZE,
+        Default::default(),
+    );
+
+    buffers_pool.require_buffer_state(
+        foliage_draw_arguments_buffer_reference,
+        BufferState::new(
+            PipelineStageFlags2::ComputeShader,
+            AccessFlags2::ShaderStorageRead | AccessFlags2::ShaderStorageWrite,
+        ),
+    );
+    buffers_pool.require_buffer_state(
+        foliage_instances_buffer_reference,
+        BufferState::new(
+            PipelineStageFlags2::ComputeShader,
+            AccessFlags2::ShaderStorageWrite,
+        ),
+    );
+    frame_graph_capture.flush_buffer_barriers(command_buffer, buffers_pool, "Foliage Buffers");
+
+    let foliage_scatter_compute_shader_object =
+        renderer_resources.foliage_scatter_compute_shader_object;
+
+    let stages = [foliage_scatter_compute_shader_object.stage];
+    let shaders = [foliage_scatter_compute_shader_object.shader.unwrap()];
+
+    command_buffer.bind_shaders_ext(stages.as_slice(), shaders.as_slice());
+
+    let descriptor_binding_info = DescriptorBufferBindingInfoEXT::default()
+        .usage(BufferUsageFlags::ResourceDescriptorBufferEXT)
+        .address(descriptor_buffer_device_address);
+
+    let descriptor_binding_infos = [descriptor_binding_info];
+    command_buffer.bind_descriptor_buffers_ext(&descriptor_binding_infos);
+
+    let buffer_indices = [0];
+    let offsets = [0];
+    command_buffer.set_descriptor_buffer_offsets_ext(
+        PipelineBindPoint::Compute,
+        pipeline_layout,
+        Default::default(),
+        &buffer_indices,
+        &offsets,
+    );
+
+    let [cells_count_x, cells_count_z] = frame_context.foliage_cells_count;
+    for camera_index in 0..frame_context.cameras.len() {
+        let (push_constant_offset, device_address) = renderer_resources
+            .resources_pool
+            .get_camera_push_constant(buffers_pool, camera_index);
+        command_buffer.push_constants(
+            pipeline_layout,
+            ShaderStageFlags::MeshEXT
+                | ShaderStageFlags::Fragment
+                | ShaderStageFlags::Compute
+                | ShaderStageFlags::TaskEXT,
+            push_constant_offset,
+            std::mem::size_of::<DeviceAddress>() as _,
+            &device_address as *const _ as _,
+        );
+
+        // NOTE: A thread per cell of the largest grid, a layer of groups per foliage.
+        command_buffer.dispatch(
+            cells_count_x.div_ceil(8),
+            cells_count_z.div_ceil(8),
+            foliages_count as _,
+        );
+    }
+    frame_graph_capture.record_pass(FrameGraphPassInfo::new(
+        "Foliage Scattering",
+        &[
+            FrameGraphAccess::buffer("Scene Data Buffer"),
+            FrameGraphAccess::buffer("Foliages Buffer"),
+        ],
+        &[
+            FrameGraphAccess::buffer("Foliage Instances Buffer"),
+            FrameGraphAccess::buffer("Foliage Draw Arguments Buffer"),
+        ],
+    ));
+
+    buffers_pool.require_buffer_state(
+        foliage_draw_arguments_buffer_reference,
+        BufferState::new(
+            PipelineStageFlags2::DrawIndirect,
+            AccessFlags2::IndirectCommandRead,
+        ),
+    );
+    buffers_pool.require_buffer_state(
+        foliage_instances_buffer_reference,
+        BufferState::new(
+            PipelineStageFlags2::TaskShaderEXT | PipelineStageFlags2::MeshShaderEXT,
+            AccessFlags2::ShaderStorageRead,
+        ),
+    );
+    frame_graph_capture.flush_buffer_barriers(command_buffer, buffers_pool, "Foliage Buffers");
+}
+
 fn draw_gradient(
     renderer_resources: &RendererResources,
+    buffers_pool: &BuffersPool,
     command_buffer: CommandBuffer,
-    draw_extent: Extent2D,
+    frame_graph_capture: &mut FrameGraphCapture,
+    cameras: &[CameraFrameData],
     pipeline_layout: PipelineLayout,
     descriptor_buffer_device_address: DeviceAddress,
 ) {
@@ -294,9 +688,35 @@ fn draw_gradient(
         &offsets,
     );
 
-    command_buffer.dispatch(
-        f32::ceil(draw_extent.width as f32 / 16.0) as _,
-        f32::ceil(draw_extent.height as f32 / 16.0) as _,
-        1,
-    );
+    for (camera_index, camera_frame_data) in cameras
+        .iter()
+        .enumerate()
+        .filter(|(_, camera_frame_data)| camera_frame_data.is_background_drawn)
+    {
+        let camera_viewport = camera_frame_data.viewport;
+        let (push_constant_offset, device_address) = renderer_resources
+            .resources_pool
+            .get_camera_push_constant(buffers_pool, camera_index);
+        command_buffer.push_constants(
+            pipeline_layout,
+            ShaderStageFlags::MeshEXT
+                | ShaderStageFlags::Fragment
+                | ShaderStageFlags::Compute
+                | ShaderStageFlags::TaskEXT,
+            push_constant_offset,
+            std::mem::size_of::<DeviceAddress>() as _,
+            &device_address as *const _ as _,
+        );
+
+        command_buffer.dispatch(
+            f32::ceil(camera_viewport.extent.width as f32 / 16.0) as _,
+            f32::ceil(camera_viewport.extent.height as f32 / 16.0) as _,
+            1,
+        );
+    }
+    frame_graph_capture.record_pass(FrameGraphPassInfo::new(
+        "Background",
+        &[FrameGraphAccess::buffer("Scene Data Buffer")],
+        &[FrameGraphAccess::image("Draw Image", ImageLayout::General)],
+    ));
 }
@@ -1,46 +1,431 @@
-use bevy_ecs::system::{Query, Res, ResMut};
+use bevy_ecs::{
+    change_detection::DetectChanges,
+    entity::Entity,
+    entity_disabling::Disabled,
+    lifecycle::RemovedComponents,
+    query::{Added, Changed, Has, Or, With, Without},
+    system::{Local, Query, Res, ResMut, SystemParam},
+    world::Ref,
+};
+use math::{Vec2, Vec3};
 
 use crate::engine::{
-    components::{local_transform::GlobalTransform, mesh::Mesh},
-    ecs::{InstanceObject, materials_pool::MaterialsPool, mesh_buffers_pool::MeshBuffersPool},
-    resources::RendererResources,
+    components::{
+        billboard::Billboard,
+        camera::Camera,
+        foliage::FoliageScatter,
+        impostor::MeshImpostor,
+        local_transform::{GlobalTransform, LocalTransform},
+        mesh::{Mesh, StaticInstance},
+        outline::{Highlighted, OutlineType, Selected},
+    },
+    ecs::{
+        FoliageData, InstanceObject, materials_pool::MaterialsPool,
+        mesh_buffers_pool::MeshBuffersPool,
+    },
+    resources::{
+        FrameArena, FrameContext, PersistentBuffer, RenderStatistics, RendererResources,
+        render_resources::instance_slot_allocator::InstanceSlotAllocator,
+    },
 };
 
-// TODO: Take into account if GlobalTransform really changed or not and update if necessary.
+/// Slots of static instances in the buffer, so only changed entities rewrite their objects.
+#[derive(Default)]
+pub struct StaticInstanceSlots {
+    instance_slot_allocator: InstanceSlotAllocator,
+}
+
+impl StaticInstanceSlots {
+    /// Once more than a quarter of instances changed, the whole buffer is rebuilt with new slots.
+    const REBUILD_RATIO: usize = 4;
+}
+
+/// Components removed since the last frame, despawned entities are reported as well.
+#[derive(SystemParam)]
+pub struct RemovedInstanceComponents<'w, 's> {
+    static_instances: RemovedComponents<'w, 's, StaticInstance>,
+    meshes: RemovedComponents<'w, 's, Mesh>,
+    mesh_impostors: RemovedComponents<'w, 's, MeshImpostor>,
+    billboards: RemovedComponents<'w, 's, Billboard>,
+    selected: RemovedComponents<'w, 's, Selected>,
+    highlighted: RemovedComponents<'w, 's, Highlighted>,
+    disabled: RemovedComponents<'w, 's, Disabled>,
+}
+
 pub fn collect_instance_objects_system(
     materials_pool: Res<MaterialsPool>,
     mut renderer_resources: ResMut<RendererResources>,
-    mesh_query: Query<(&GlobalTransform, &Mesh)>,
+    mesh_query: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &Mesh,
+            Option<&MeshImpostor>,
+            Option<&Billboard>,
+            (Has<Selected>, Has<Highlighted>),
+        ),
+        Without<StaticInstance>,
+    >,
+    static_mesh_query: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &Mesh,
+            Option<&MeshImpostor>,
+            Option<&Billboard>,
+            (Has<Selected>, Has<Highlighted>),
+        ),
+        With<StaticInstance>,
+    >,
+    changed_mesh_query: Query<
+        Entity,
+        (
+            With<Mesh>,
+            Or<(
+                Added<StaticInstance>,
+                Changed<GlobalTransform>,
+                Changed<Mesh>,
+                Changed<MeshImpostor>,
+                Changed<Billboard>,
+                Changed<Selected>,
+                Changed<Highlighted>,
+            )>,
+        ),
+    >,
+    billboards_query: Query<Entity, (With<Mesh>, With<Billboard>)>,
+    disabled_meshes_query: Query<Entity, (With<Mesh>, With<Disabled>, Added<Disabled>)>,
+    camera_query: Query<(&Camera, Ref<LocalTransform>)>,
+    mut removed_instance_components: RemovedInstanceComponents,
+    foliage_scatters_query: Query<(&GlobalTransform, &FoliageScatter)>,
     mesh_buffers: ResMut<MeshBuffersPool>,
+    mut render_statistics: ResMut<RenderStatistics>,
+    mut frame_context: ResMut<FrameContext>,
+    mut frame_arena: ResMut<FrameArena>,
+    mut static_instance_slots: Local<StaticInstanceSlots>,
 ) {
-    let instance_objects_buffer = unsafe {
+    // NOTE: Billboards face the first camera, the same one that gets the first scene data entry.
+    let camera_transform = Camera::sort_active(camera_query.iter())
+        .into_iter()
+        .next()
+        .map(|(_, camera_transform)| camera_transform);
+    let camera_position = camera_transform
+        .as_ref()
+        .map(|camera_transform| camera_transform.get_local_position());
+    let is_camera_moved =
+        camera_transform.is_some_and(|camera_transform| camera_transform.is_changed());
+
+    let frame_allocator = frame_arena.get_allocator();
+    let removed_static_instances =
+        frame_allocator.collect(removed_instance_components.static_instances.read());
+    let removed_entities = frame_allocator.collect(
+        removed_static_instances
+            .iter()
+            .copied()
+            .chain(removed_instance_components.meshes.read())
+            .chain(disabled_meshes_query.iter()),
+    );
+    // NOTE: Entities that lost a component still have the rest of them, so they're rewritten.
+    let changed_entities = frame_allocator.collect(
+        changed_mesh_query
+            .iter()
+            .chain(
+                is_camera_moved
+                    .then(|| billboards_query.iter())
+                    .into_iter()
+                    .flatten(),
+            )
+            .chain(removed_instance_components.mesh_impostors.read())
+            .chain(removed_instance_components.billboards.read())
+            .chain(removed_instance_components.selected.read())
+            .chain(removed_instance_components.highlighted.read())
+            .chain(removed_instance_components.disabled.read())
+            .chain(removed_static_instances),
+    );
+
+    let static_instance_objects_buffer = unsafe {
         renderer_resources
             .resources_pool
-            .instances_buffer
+            .static_instances_buffer
             .as_mut()
             .unwrap_unchecked()
     };
-    // TODO: TEMP SOLUTION, in the future will be remade into slot based collecting of instance objects.
-    instance_objects_buffer.clear();
 
-    for (global_transform, mesh) in mesh_query.iter() {
-        let material_info = materials_pool.get_material_info(mesh.material_reference);
+    let static_instance_slot_allocator = &mut static_instance_slots.instance_slot_allocator;
+    for &entity in removed_entities.iter() {
+        if !static_mesh_query.contains(entity) {
+            free_instance_slot(
+                static_instance_slot_allocator,
+                static_instance_objects_buffer,
+                entity,
+            );
+        }
+    }
+    static_instance_objects_buffer.truncate(static_instance_slot_allocator.get_slots_count());
+
+    let changed_static_entities = frame_allocator.collect(
+        changed_entities
+            .iter()
+            .copied()
+            .filter(|&entity| static_mesh_query.contains(entity)),
+    );
+
+    if changed_static_entities.len() * StaticInstanceSlots::REBUILD_RATIO
+        > static_instance_slot_allocator.len()
+    {
+        // NOTE: Every buffer gets the full upload, since all the slots are reassigned without holes.
+        static_instance_slot_allocator.clear();
+        static_instance_objects_buffer.rebuild(static_mesh_query.iter().map(
+            |(
+                entity,
+                global_transform,
+                mesh,
+                mesh_impostor,
+                billboard,
+                (is_selected, is_highlighted),
+            )| {
+                static_instance_slot_allocator.allocate(entity);
+
+                create_instance_object(
+                    &get_billboard_transform(global_transform, billboard, camera_position),
+                    mesh,
+                    mesh_impostor,
+                    OutlineType::new(is_selected, is_highlighted),
+                    &materials_pool,
+                    &mesh_buffers,
+                )
+            },
+        ));
+    } else {
+        for entity in changed_static_entities {
+            let Ok((
+                entity,
+                global_transform,
+                mesh,
+                mesh_impostor,
+                billboard,
+                (is_selected, is_highlighted),
+            )) = static_mesh_query.get(entity)
+            else {
+                continue;
+            };
 
-        let mesh_buffer = unsafe {
-            mesh_buffers
-                .get_mesh_buffer(mesh.mesh_buffer_reference)
-                .unwrap_unchecked()
+            let instance_object = create_instance_object(
+                &get_billboard_transform(global_transform, billboard, camera_position),
+                mesh,
+                mesh_impostor,
+                OutlineType::new(is_selected, is_highlighted),
+                &materials_pool,
+                &mesh_buffers,
+            );
+            write_instance_slot(
+                static_instance_slot_allocator,
+                static_instance_objects_buffer,
+                entity,
+                instance_object,
+            );
+        }
+    }
+    render_statistics.static_instances_count = static_instance_slot_allocator.len();
+
+    let resources_pool = &mut renderer_resources.resources_pool;
+    let instance_objects_buffer =
+        unsafe { resources_pool.instances_buffer.as_mut().unwrap_unchecked() };
+    let instance_slot_allocator = &mut resources_pool.instance_slot_allocator;
+
+    // NOTE: Holes are empty objects, the task shader skips instances without a mesh.
+    // Entities that became static leave their slots as well.
+    for &entity in removed_entities.iter().chain(changed_entities.iter()) {
+        if mesh_query.contains(entity) {
+            continue;
+        }
+
+        free_instance_slot(instance_slot_allocator, instance_objects_buffer, entity);
+    }
+    instance_objects_buffer.truncate(instance_slot_allocator.get_slots_count());
+
+    for &entity in changed_entities.iter() {
+        let Ok((
+            entity,
+            global_transform,
+            mesh,
+            mesh_impostor,
+            billboard,
+            (is_selected, is_highlighted),
+        )) = mesh_query.get(entity)
+        else {
+            continue;
         };
 
-        instance_objects_buffer.add_instance_object(InstanceObject {
-            model_matrix: global_transform.0.to_cols_array(),
-            device_address_mesh_object: mesh_buffer.mesh_object_device_address,
-            device_address_material_data: material_info.device_adddress_material_data,
-            meshlet_count: mesh_buffer.meshlets_count as _,
-            material_type: material_info.material_type as _,
-            ..Default::default()
-        });
+        let instance_object = create_instance_object(
+            &get_billboard_transform(global_transform, billboard, camera_position),
+            mesh,
+            mesh_impostor,
+            OutlineType::new(is_selected, is_highlighted),
+            &materials_pool,
+            &mesh_buffers,
+        );
+        write_instance_slot(
+            instance_slot_allocator,
+            instance_objects_buffer,
+            entity,
+            instance_object,
+        );
     }
 
-    instance_objects_buffer.prepare_objects_for_writing();
+    render_statistics.instances_count = instance_slot_allocator.len();
+
+    let default_sampler_index = renderer_resources.default_sampler_reference.get_index();
+    let foliages_buffer = unsafe {
+        renderer_resources
+            .resources_pool
+            .foliages_buffer
+            .as_mut()
+            .unwrap_unchecked()
+    };
+    foliages_buffer.clear();
+    frame_context.foliage_cells_count = Default::default();
+    for (global_transform, foliage_scatter) in foliage_scatters_query
+        .iter()
+        .take(foliages_buffer.get_capacity())
+    {
+        let foliage_data = create_foliage_data(
+            global_transform,
+            foliage_scatter,
+            default_sampler_index,
+            &materials_pool,
+            &mesh_buffers,
+        );
+        frame_context.foliage_cells_count[0] =
+            frame_context.foliage_cells_count[0].max(foliage_data.cells_count_x);
+        frame_context.foliage_cells_count[1] =
+            frame_context.foliage_cells_count[1].max(foliage_data.cells_count_z);
+
+        foliages_buffer.push(foliage_data);
+    }
+
+    render_statistics.materials_count = materials_pool.get_materials_count();
+    render_statistics.deduplicated_materials_count =
+        materials_pool.get_deduplicated_materials_count();
+}
+
+/// Hole is left empty, trailing ones are truncated by the caller once every entity is freed.
+fn free_instance_slot(
+    instance_slot_allocator: &mut InstanceSlotAllocator,
+    buffer: &mut PersistentBuffer<InstanceObject>,
+    entity: Entity,
+) {
+    if let Some(index) = instance_slot_allocator.free(entity) {
+        buffer.set(index, Default::default());
+    }
+}
+
+/// Slot past the end of the buffer is a new one, since holes are taken first.
+fn write_instance_slot(
+    instance_slot_allocator: &mut InstanceSlotAllocator,
+    buffer: &mut PersistentBuffer<InstanceObject>,
+    entity: Entity,
+    instance_object: InstanceObject,
+) {
+    let index = instance_slot_allocator.allocate(entity);
+    if index == buffer.len() {
+        buffer.push(instance_object);
+    } else {
+        buffer.set(index, instance_object);
+    }
+}
+
+fn get_billboard_transform(
+    global_transform: &GlobalTransform,
+    billboard: Option<&Billboard>,
+    camera_position: Option<Vec3>,
+) -> GlobalTransform {
+    match (billboard, camera_position) {
+        (Some(billboard), Some(camera_position)) => {
+            GlobalTransform(billboard.get_model_matrix(global_transform.0, camera_position))
+        }
+        _ => *global_transform,
+    }
+}
+
+fn create_instance_object(
+    global_transform: &GlobalTransform,
+    mesh: &Mesh,
+    mesh_impostor: Option<&MeshImpostor>,
+    outline_type: OutlineType,
+    materials_pool: &MaterialsPool,
+    mesh_buffers: &MeshBuffersPool,
+) -> InstanceObject {
+    let material_info = materials_pool.get_material_info(mesh.material_reference);
+
+    let mesh_buffer = unsafe {
+        mesh_buffers
+            .get_mesh_buffer(mesh.mesh_buffer_reference)
+            .unwrap_unchecked()
+    };
+
+    InstanceObject {
+        model_matrix: global_transform.0.to_cols_array(),
+        device_address_mesh_object: mesh_buffer.mesh_object_device_address,
+        device_address_material_data: material_info.device_adddress_material_data,
+        device_address_impostor: mesh_impostor
+            .map(|mesh_impostor| mesh_impostor.device_address_impostor)
+            .unwrap_or_default(),
+        meshlet_count: mesh_buffer.meshlets_count as _,
+        material_type: material_info.material_type as _,
+        outline_type: outline_type as _,
+        ..Default::default()
+    }
+}
+
+fn create_foliage_data(
+    global_transform: &GlobalTransform,
+    foliage_scatter: &FoliageScatter,
+    sampler_index: u32,
+    materials_pool: &MaterialsPool,
+    mesh_buffers: &MeshBuffersPool,
+) -> FoliageData {
+    let instance_object = create_instance_object(
+        global_transform,
+        &foliage_scatter.mesh,
+        None,
+        Default::default(),
+        materials_pool,
+        mesh_buffers,
+    );
+
+    // NOTE: Cells are squares, so the density is kept along both axes.
+    let cell_size = 1.0 / foliage_scatter.density.max(f32::EPSILON).sqrt();
+    let size = foliage_scatter.size.max(Vec2::ZERO);
+    let density_map_texture_reference = foliage_scatter.get_density_map_texture_reference();
+    let height_map_texture_reference = foliage_scatter.get_height_map_texture_reference();
+
+    FoliageData {
+        model_matrix: instance_object.model_matrix,
+        device_address_mesh_object: instance_object.device_address_mesh_object,
+        device_address_material_data: instance_object.device_address_material_data,
+        half_size: size * 0.5,
+        cell_size,
+        meshlet_count: instance_object.meshlet_count,
+        material_type: instance_object.material_type as _,
+        cells_count_x: (size.x / cell_size).ceil() as _,
+        cells_count_z: (size.y / cell_size).ceil() as _,
+        density_map_index: density_map_texture_reference
+            .map(|texture_reference| texture_reference.get_index())
+            .unwrap_or_default(),
+        height_map_index: height_map_texture_reference
+            .map(|texture_reference| texture_reference.get_index())
+            .unwrap_or_default(),
+        sampler_index,
+        height_scale: foliage_scatter.height_scale,
+        min_scale: foliage_scatter.scale_range.x,
+        max_scale: foliage_scatter.scale_range.y,
+        bounding_radius: foliage_scatter.bounding_radius,
+        fade_start: foliage_scatter.fade_start,
+        fade_end: foliage_scatter
+            .fade_end
+            .max(foliage_scatter.fade_start + f32::EPSILON),
+        has_density_map: density_map_texture_reference.is_some() as _,
+        has_height_map: height_map_texture_reference.is_some() as _,
+        ..Default::default()
+    }
 }
@@ -0,0 +1,235 @@
+use ahash::AHashMap;
+use bevy_ecs::system::{Local, Res, ResMut};
+use vma::{Allocation, ffi};
+use vulkanite::vk::rs::{Buffer, Image};
+
+use crate::engine::{
+    general::renderer::{DescriptorKind, DescriptorSampledImage, DescriptorSetHandle},
+    resources::{
+        DefragmentationSettings, DeletionQueue, FrameError, FrameErrors, RenderStatistics,
+        VulkanContextResource,
+        buffers_pool::BuffersPool,
+        mesh_buffers_pool::{MeshBufferReference, MeshBuffersPool},
+        textures_pool::{TextureReference, TexturesPool},
+    },
+};
+
+#[derive(Clone, Copy)]
+enum MoveTarget {
+    /// Index of the buffer in the mesh buffer references.
+    MeshBuffer(MeshBufferReference, usize),
+    Texture(TextureReference),
+}
+
+/// Resource copied to the new place, the old one is replaced by it after the pass.
+enum MovedResource {
+    MeshBuffer(MeshBufferReference, usize, Buffer),
+    Texture(TextureReference, Image),
+}
+
+#[derive(Default)]
+pub struct DefragmentationState {
+    uploaded_bytes_count: u64,
+    is_memory_compacted: bool,
+}
+
+/// Moves allocations of mesh buffers and sampled textures closer together within the per frame budget.
+// NOTE: Everything else keeps its place, device addresses of it are still baked into records.
+pub fn defragment_memory_system(
+    vulkan_context: Res<VulkanContextResource>,
+    defragmentation_settings: Res<DefragmentationSettings>,
+    deletion_queue: Res<DeletionQueue>,
+    mut defragmentation_state: Local<DefragmentationState>,
+    mut buffers_pool: ResMut<BuffersPool>,
+    mut textures_pool: ResMut<TexturesPool>,
+    mesh_buffers_pool: Res<MeshBuffersPool>,
+    mut descriptor_set_handle: ResMut<DescriptorSetHandle>,
+    mut render_statistics: ResMut<RenderStatistics>,
+    mut frame_errors: ResMut<FrameErrors>,
+) {
+    render_statistics.stale_buffer_accesses_count = buffers_pool.get_stale_accesses_count();
+
+    // NOTE: Memory is idle, when nothing was uploaded since the last frame and nothing waits for the release.
+    let uploaded_bytes_count = buffers_pool.get_upload_statistics().uploaded_bytes_count;
+    let is_memory_idle = uploaded_bytes_count == defragmentation_state.uploaded_bytes_count
+        && deletion_queue.is_empty();
+    defragmentation_state.uploaded_bytes_count = uploaded_bytes_count;
+    if !is_memory_idle {
+        defragmentation_state.is_memory_compacted = false;
+
+        return;
+    }
+
+    if !defragmentation_settings.is_enabled || defragmentation_state.is_memory_compacted {
+        return;
+    }
+
+    let mut move_targets = AHashMap::with_capacity(1024);
+    for (mesh_buffer_reference, mesh_buffer) in mesh_buffers_pool.get_mesh_buffers() {
+        for (buffer_index, buffer_reference) in
+            mesh_buffer.get_buffer_references().into_iter().enumerate()
+        {
+            let allocation = buffer_reference
+                .get_buffer(&buffers_pool)
+                .unwrap()
+                .allocation;
+            move_targets.insert(
+                get_raw_allocation(allocation),
+                MoveTarget::MeshBuffer(mesh_buffer_reference, buffer_index),
+            );
+        }
+    }
+    for (texture_reference, allocated_image) in textures_pool.get_movable_textures() {
+        move_targets.insert(
+            get_raw_allocation(allocated_image.allocation),
+            MoveTarget::Texture(texture_reference),
+        );
+    }
+
+    let defragmentation_info = ffi::VmaDefragmentationInfo {
+        flags: ffi::VmaDefragmentationFlagBits::VMA_DEFRAGMENTATION_FLAG_ALGORITHM_FAST_BIT as _,
+        pool: std::ptr::null_mut(),
+        maxBytesPerPass: defragmentation_settings.max_bytes_per_frame,
+        maxAllocationsPerPass: defragmentation_settings.max_allocations_per_frame,
+        pfnBreakCallback: None,
+        pBreakCallbackUserData: std::ptr::null_mut(),
+    };
+    let defragmentation_context = match unsafe {
+        vulkan_context
+            .allocator
+            .begin_defragmentation(&defragmentation_info)
+    } {
+        Ok(defragmentation_context) => defragmentation_context,
+        Err(status) => {
+            frame_errors.record(FrameError::new("begin the defragmentation", status));
+
+            return;
+        }
+    };
+
+    let mut moved_resources = Vec::new();
+    let mut moved_bytes_count = 0;
+    let mut wait_error = None;
+    defragmentation_context.begin_pass(|defragmentation_moves| {
+        let mut targets_to_move = Vec::with_capacity(defragmentation_moves.len());
+        for defragmentation_move in defragmentation_moves.iter_mut() {
+            match move_targets.get(&defragmentation_move.srcAllocation) {
+                Some(&move_target) => {
+                    targets_to_move.push((move_target, defragmentation_move.dstTmpAllocation));
+                }
+                None => {
+                    defragmentation_move.operation =
+                        ffi::VmaDefragmentationMoveOperation::VMA_DEFRAGMENTATION_MOVE_OPERATION_IGNORE;
+                }
+            }
+        }
+
+        if targets_to_move.is_empty() {
+            return;
+        }
+
+        // NOTE: Frames in flight still read the old memory, VMA releases it once the pass is ended.
+        // Nothing is moved, if they can't be waited.
+        if let Err(status) = vulkan_context.device.wait_idle() {
+            for defragmentation_move in defragmentation_moves.iter_mut() {
+                defragmentation_move.operation =
+                    ffi::VmaDefragmentationMoveOperation::VMA_DEFRAGMENTATION_MOVE_OPERATION_IGNORE;
+            }
+            wait_error = Some(FrameError::new("wait for the device idle", status));
+
+            return;
+        }
+
+        unsafe {
+            buffers_pool.submit_upload_commands(|command_buffer| {
+                for (move_target, dst_allocation) in targets_to_move {
+                    let dst_allocation = from_raw_allocation(dst_allocation);
+
+                    match move_target {
+                        MoveTarget::MeshBuffer(mesh_buffer_reference, buffer_index) => {
+                            let buffer_reference = mesh_buffers_pool
+                                .get_mesh_buffer(mesh_buffer_reference)
+                                .unwrap()
+                                .get_buffer_references()[buffer_index];
+                            moved_bytes_count +=
+                                buffers_pool.get_buffer_info(buffer_reference).size;
+
+                            let buffer = buffers_pool.create_moved_buffer(
+                                buffer_reference,
+                                dst_allocation,
+                                command_buffer,
+                            );
+                            moved_resources.push(MovedResource::MeshBuffer(
+                                mesh_buffer_reference,
+                                buffer_index,
+                                buffer,
+                            ));
+                        }
+                        MoveTarget::Texture(texture_reference) => {
+                            let image = textures_pool.create_moved_image(
+                                texture_reference,
+                                dst_allocation,
+                                command_buffer,
+                            );
+                            let allocation_info = vulkan_context
+                                .allocator
+                                .get_allocation_info(dst_allocation);
+                            moved_bytes_count += allocation_info.size;
+
+                            moved_resources.push(MovedResource::Texture(texture_reference, image));
+                        }
+                    }
+                }
+            });
+        }
+    });
+    defragmentation_context.end();
+
+    if let Some(wait_error) = wait_error {
+        frame_errors.record(wait_error);
+
+        return;
+    }
+
+    // NOTE: Nothing is moved again, until memory is changed by uploads or releases.
+    if moved_resources.is_empty() {
+        defragmentation_state.is_memory_compacted = true;
+
+        return;
+    }
+
+    render_statistics.moved_allocations_count += moved_resources.len();
+    render_statistics.moved_bytes_count += moved_bytes_count;
+    for moved_resource in moved_resources {
+        match moved_resource {
+            MovedResource::MeshBuffer(mesh_buffer_reference, buffer_index, buffer) => {
+                let buffer_reference = mesh_buffers_pool
+                    .get_mesh_buffer(mesh_buffer_reference)
+                    .unwrap()
+                    .get_buffer_references()[buffer_index];
+                // NOTE: Mesh objects refer to buffers by indices, only the buffer addresses table is rewritten.
+                unsafe { buffers_pool.replace_buffer(buffer_reference, buffer) };
+            }
+            MovedResource::Texture(texture_reference, image) => {
+                let image_view = textures_pool.replace_image(texture_reference, image);
+
+                let descriptor_texture = DescriptorKind::SampledImage(DescriptorSampledImage {
+                    image_view,
+                    index: texture_reference.get_index(),
+                });
+                descriptor_set_handle.update_binding(&mut buffers_pool, descriptor_texture);
+            }
+        }
+    }
+}
+
+// NOTE: `Allocation` is a plain wrapper of the VMA handle, moves refer to allocations by the raw handles.
+#[inline(always)]
+fn get_raw_allocation(allocation: Allocation) -> ffi::VmaAllocation {
+    unsafe { std::mem::transmute::<Allocation, ffi::VmaAllocation>(allocation) }
+}
+
+#[inline(always)]
+fn from_raw_allocation(allocation: ffi::VmaAllocation) -> Allocation {
+    unsafe { std::mem::transmute::<ffi::VmaAllocation, Allocation>(allocation) }
+}
@@ -1,82 +1,1119 @@
 use bevy_ecs::system::{Res, ResMut};
 
+#[cfg(feature = "capture")]
 use crate::engine::{
-    ecs::textures_pool::TexturesPool,
-    resources::{FrameContext, RendererContext},
-    utils::{copy_image_to_image, transition_image},
+    ecs::buffers_pool::BufferReference,
+    resources::{CubemapCapture, FrameCapture},
+};
+use crate::engine::{
+    ecs::{
+        buffer_state_tracker::BufferState,
+        buffers_pool::BuffersPool,
+        image_state_tracker::ImageState,
+        textures_pool::{AllocatedImage, TextureReference, TexturesPool},
+    },
+    general::renderer::{ComputePass, DescriptorSetHandle},
+    resources::{
+        AsyncComputeCommandGroup, FrameContext, FrameError, FrameErrors, FrameGraphAccess,
+        FrameGraphCapture, FrameGraphPassInfo, GpuProfiler, GpuTimestamp, LensFlare,
+        PostProcessSettings, RendererContext, RendererResources, ShaderObject,
+        VulkanContextResource,
+    },
+    utils::{copy_image_regions, copy_image_to_image, create_command_buffer_begin_info},
+};
+use vulkanite::vk::{
+    rs::{CommandBuffer, Image, PipelineLayout},
+    *,
 };
-use vulkanite::vk::*;
 
 pub fn end_rendering_system(
+    vulkan_context: Res<VulkanContextResource>,
     renderer_context: Res<RendererContext>,
     textures_pool: ResMut<TexturesPool>,
     frame_context: Res<FrameContext>,
+    gpu_profiler: Res<GpuProfiler>,
+    renderer_resources: Res<RendererResources>,
+    descriptor_set_handle: Res<DescriptorSetHandle>,
+    buffers_pool: Res<BuffersPool>,
+    post_process_settings: Res<PostProcessSettings>,
+    mut frame_graph_capture: ResMut<FrameGraphCapture>,
+    #[cfg(feature = "capture")] mut cubemap_capture: ResMut<CubemapCapture>,
+    #[cfg(feature = "capture")] mut frame_capture: ResMut<FrameCapture>,
+    mut frame_errors: ResMut<FrameErrors>,
 ) {
-    let command_buffer = frame_context.command_buffer.unwrap();
+    let Some(command_buffer) = frame_context.command_buffer else {
+        frame_errors.record(FrameError::missing_resource("get the command buffer"));
 
-    let swapchain_image = renderer_context.images[frame_context.swapchain_image_index as usize];
+        return;
+    };
 
-    let draw_image = textures_pool
-        .get_image(frame_context.draw_texture_reference)
-        .unwrap();
+    // NOTE: Headless engine has no swapchain images, the frame ends in the present image.
+    let swapchain_image = renderer_context
+        .images
+        .get(frame_context.swapchain_image_index as usize)
+        .copied();
+
+    let Some(draw_image) = textures_pool.get_image(frame_context.draw_texture_reference) else {
+        frame_errors.record(FrameError::missing_resource("get the draw image"));
 
-    let draw_image_extent3d = draw_image.extent;
-    let draw_image_extent2d = Extent2D {
-        width: draw_image_extent3d.width,
-        height: draw_image_extent3d.height,
+        return;
     };
 
     command_buffer.end_rendering();
+    gpu_profiler.write_timestamp(
+        command_buffer,
+        GpuTimestamp::MeshesEnd,
+        PipelineStageFlags2::AllGraphics,
+    );
+
+    let frame_data = renderer_context.get_current_frame_data();
+    if frame_data.is_texture_usage_counted {
+        buffers_pool.require_buffer_state(
+            frame_data.texture_usage_counters_buffer_reference,
+            BufferState::host_read(),
+        );
+        frame_graph_capture.flush_buffer_barriers(
+            command_buffer,
+            &buffers_pool,
+            "Texture Usage Counters Buffer",
+        );
+    }
 
-    transition_image(
+    frame_graph_capture.ensure_image_state(
         command_buffer,
-        draw_image.image,
-        ImageLayout::General,
+        &textures_pool,
+        (frame_context.draw_texture_reference, "Draw Image"),
+        ImageState::compute_read_write(),
+    );
+
+    let pipeline_layout = descriptor_set_handle.get_pipeline_layout();
+    let descriptor_buffer_device_address = descriptor_set_handle
+        .get_buffer_info(&buffers_pool)
+        .device_address;
+    let render_extent = renderer_context.render_extent;
+    let screen_group_counts = [
+        render_extent.width.div_ceil(16),
+        render_extent.height.div_ceil(16),
+        1,
+    ];
+
+    let mut viewports_passes = Vec::new();
+    if post_process_settings.screen_space_reflections.is_some() {
+        viewports_passes.push(ViewportsPass {
+            name: "Screen Space Reflections",
+            compute_shader_object: renderer_resources
+                .screen_space_reflections_compute_shader_object,
+            camera_indices: (0..frame_context.cameras.len()).collect(),
+        });
+    }
+    // NOTE: Water goes after reflections, they'd be traced against the depth beneath it.
+    if frame_context.is_water_enabled {
+        viewports_passes.push(ViewportsPass {
+            name: "Water",
+            compute_shader_object: renderer_resources.water_compute_shader_object,
+            camera_indices: (0..frame_context.cameras.len()).collect(),
+        });
+    }
+    let light_shafts_camera_indices: Vec<_> = frame_context
+        .cameras
+        .iter()
+        .enumerate()
+        .filter(|(_, camera_frame_data)| camera_frame_data.is_light_shafts_enabled)
+        .map(|(camera_index, _)| camera_index)
+        .collect();
+    if !light_shafts_camera_indices.is_empty() {
+        viewports_passes.push(ViewportsPass {
+            name: "Light Shafts",
+            compute_shader_object: renderer_resources.light_shafts_compute_shader_object,
+            camera_indices: light_shafts_camera_indices,
+        });
+    }
+    // NOTE: Depth of field goes last, so reflections are blurred with the surfaces.
+    let depth_of_field_camera_indices: Vec<_> = frame_context
+        .cameras
+        .iter()
+        .enumerate()
+        .filter(|(_, camera_frame_data)| camera_frame_data.is_depth_of_field_enabled)
+        .map(|(camera_index, _)| camera_index)
+        .collect();
+    if !depth_of_field_camera_indices.is_empty() {
+        viewports_passes.push(ViewportsPass {
+            name: "Depth Of Field",
+            compute_shader_object: renderer_resources.depth_of_field_compute_shader_object,
+            camera_indices: depth_of_field_camera_indices,
+        });
+    }
+    // NOTE: Outlines go after depth of field, so they stay sharp.
+    if frame_context.is_outlined {
+        buffers_pool.require_buffer_state(
+            frame_context.outline_mask_buffer_reference,
+            BufferState::new(
+                PipelineStageFlags2::ComputeShader,
+                AccessFlags2::ShaderStorageRead,
+            ),
+        );
+        frame_graph_capture.flush_buffer_barriers(
+            command_buffer,
+            &buffers_pool,
+            "Outline Mask Buffer",
+        );
+        viewports_passes.push(ViewportsPass {
+            name: "Outlines",
+            compute_shader_object: renderer_resources.outlines_compute_shader_object,
+            camera_indices: (0..frame_context.cameras.len()).collect(),
+        });
+    }
+
+    let visibility_queries_count = renderer_resources
+        .resources_pool
+        .visibility_query_points_buffer
+        .as_ref()
+        .map(|visibility_query_points_buffer| visibility_query_points_buffer.len())
+        .unwrap_or_default();
+    let is_visibility_queried =
+        visibility_queries_count > Default::default() && !frame_context.cameras.is_empty();
+    let depth_picks_count = frame_data.depth_pick_positions.len();
+    let is_depth_picked =
+        depth_picks_count > Default::default() && !frame_context.cameras.is_empty();
+
+    let depth_pyramid = frame_data.depth_pyramid.as_ref();
+    if !viewports_passes.is_empty()
+        || is_visibility_queried
+        || is_depth_picked
+        || depth_pyramid.is_some()
+    {
+        frame_graph_capture.ensure_image_state(
+            command_buffer,
+            &textures_pool,
+            (frame_context.depth_texture_reference, "Depth Image"),
+            ImageState::new(
+                ImageLayout::General,
+                PipelineStageFlags2::ComputeShader,
+                AccessFlags2::ShaderSampledRead,
+            ),
+        );
+    }
+    // NOTE: Pyramid is built first, so the passes after the scene can read it.
+    if let Some(depth_pyramid) = depth_pyramid {
+        depth_pyramid.record(
+            command_buffer,
+            &textures_pool,
+            &buffers_pool,
+            &mut frame_graph_capture,
+            renderer_resources.depth_pyramid_compute_shader_object,
+            pipeline_layout,
+            descriptor_buffer_device_address,
+            frame_context.depth_texture_reference,
+            Rect2D {
+                offset: Default::default(),
+                extent: render_extent,
+            },
+        );
+    }
+    for viewports_pass in viewports_passes {
+        if let Err(error) = draw_viewports_pass(
+            command_buffer,
+            &textures_pool,
+            &buffers_pool,
+            &frame_context,
+            &renderer_resources,
+            &descriptor_set_handle,
+            &mut frame_graph_capture,
+            viewports_pass,
+        ) {
+            frame_errors.record(error);
+
+            return;
+        }
+    }
+    if is_visibility_queried {
+        draw_visibility_queries_pass(
+            command_buffer,
+            &renderer_context,
+            &renderer_resources,
+            &descriptor_set_handle,
+            &buffers_pool,
+            &mut frame_graph_capture,
+            visibility_queries_count,
+        );
+    }
+    if is_depth_picked {
+        draw_depth_picks_pass(
+            command_buffer,
+            &renderer_context,
+            &renderer_resources,
+            &descriptor_set_handle,
+            &buffers_pool,
+            &mut frame_graph_capture,
+            depth_picks_count,
+        );
+    }
+
+    // NOTE: Face is copied before tonemapping, so the cubemap keeps the HDR color. It's skipped,
+    // if the render extent was shrunk under the face, then the face is rendered again.
+    #[cfg(feature = "capture")]
+    if let Some((readback_buffer_reference, face_size)) = cubemap_capture.get_face_readback()
+        && frame_context
+            .cameras
+            .first()
+            .is_some_and(|camera_frame_data| {
+                camera_frame_data.viewport.extent.width == face_size
+                    && camera_frame_data.viewport.extent.height == face_size
+            })
+    {
+        if let Err(error) = copy_draw_image(
+            command_buffer,
+            &textures_pool,
+            frame_context.draw_texture_reference,
+            &buffers_pool,
+            &mut frame_graph_capture,
+            readback_buffer_reference,
+            Extent2D {
+                width: face_size,
+                height: face_size,
+            },
+            ("Cubemap Capture", "Cubemap Capture Buffer"),
+        ) {
+            frame_errors.record(error);
+
+            return;
+        }
+        cubemap_capture.mark_face_copied();
+    }
+    // NOTE: Frame is copied before post-processing as well, so the exposure doesn't change it.
+    #[cfg(feature = "capture")]
+    if let Some((readback_buffer_reference, width, height)) = frame_capture.get_frame_readback() {
+        if let Err(error) = copy_draw_image(
+            command_buffer,
+            &textures_pool,
+            frame_context.draw_texture_reference,
+            &buffers_pool,
+            &mut frame_graph_capture,
+            readback_buffer_reference,
+            Extent2D { width, height },
+            ("Frame Capture", "Frame Capture Buffer"),
+        ) {
+            frame_errors.record(error);
+
+            return;
+        }
+        frame_capture.mark_frame_copied();
+    }
+
+    // NOTE: Without a camera the draw image is just cleared, post-processing would read
+    // the scene data of a camera that isn't written.
+    let is_camera_rendered = !frame_context.cameras.is_empty();
+    let lens_flare_sprite_textures = if is_camera_rendered && frame_context.is_lens_flare_visible {
+        get_lens_flare_sprite_textures(&post_process_settings, &textures_pool)
+    } else {
+        Vec::new()
+    };
+
+    let async_compute = frame_context
+        .async_compute_command_group
+        .zip(vulkan_context.compute_queue_family_index)
+        .map(|(command_group, compute_queue_family_index)| AsyncCompute {
+            command_group,
+            graphics_queue_family_index: vulkan_context.queue_family_index as _,
+            compute_queue_family_index: compute_queue_family_index as _,
+        });
+    // NOTE: Rest of the frame is post-processing, with the compute queue it's recorded separately,
+    // so it can overlap with the scene of the next frame.
+    let command_buffer = match async_compute {
+        Some(async_compute) => match begin_async_compute(
+            command_buffer,
+            async_compute,
+            draw_image,
+            &textures_pool,
+            &lens_flare_sprite_textures,
+            &frame_context,
+            &renderer_resources,
+            pipeline_layout,
+            &mut frame_graph_capture,
+        ) {
+            Ok(compute_command_buffer) => compute_command_buffer,
+            Err(error) => {
+                frame_errors.record(error);
+
+                return;
+            }
+        },
+        None => command_buffer,
+    };
+
+    if is_camera_rendered && post_process_settings.auto_exposure.is_some() {
+        let exposure_state_buffer_reference = renderer_resources.exposure_state_buffer_reference;
+        let compute_read_write_state = BufferState::new(
+            PipelineStageFlags2::ComputeShader,
+            AccessFlags2::ShaderStorageRead | AccessFlags2::ShaderStorageWrite,
+        );
+
+        buffers_pool
+            .require_buffer_state(exposure_state_buffer_reference, compute_read_write_state);
+        frame_graph_capture.flush_buffer_barriers(
+            command_buffer,
+            &buffers_pool,
+            "Exposure State Buffer",
+        );
+        dispatch_compute(
+            command_buffer,
+            &mut frame_graph_capture,
+            FrameGraphPassInfo::new(
+                "Luminance Histogram",
+                &[FrameGraphAccess::image("Draw Image", ImageLayout::General)],
+                &[FrameGraphAccess::buffer("Exposure State Buffer")],
+            ),
+            renderer_resources.luminance_histogram_compute_shader_object,
+            pipeline_layout,
+            descriptor_buffer_device_address,
+            screen_group_counts,
+        );
+
+        buffers_pool
+            .require_buffer_state(exposure_state_buffer_reference, compute_read_write_state);
+        frame_graph_capture.flush_buffer_barriers(
+            command_buffer,
+            &buffers_pool,
+            "Exposure State Buffer",
+        );
+        dispatch_compute(
+            command_buffer,
+            &mut frame_graph_capture,
+            FrameGraphPassInfo::new(
+                "Exposure Adaptation",
+                &[FrameGraphAccess::buffer("Exposure State Buffer")],
+                &[FrameGraphAccess::buffer("Exposure State Buffer")],
+            ),
+            renderer_resources.exposure_adaptation_compute_shader_object,
+            pipeline_layout,
+            descriptor_buffer_device_address,
+            [1, 1, 1],
+        );
+
+        buffers_pool
+            .require_buffer_state(exposure_state_buffer_reference, compute_read_write_state);
+        frame_graph_capture.flush_buffer_barriers(
+            command_buffer,
+            &buffers_pool,
+            "Exposure State Buffer",
+        );
+    }
+
+    if is_camera_rendered {
+        dispatch_compute(
+            command_buffer,
+            &mut frame_graph_capture,
+            FrameGraphPassInfo::new(
+                "Tonemap",
+                &[FrameGraphAccess::buffer("Exposure State Buffer")],
+                &[FrameGraphAccess::image("Draw Image", ImageLayout::General)],
+            ),
+            renderer_resources.tonemap_compute_shader_object,
+            pipeline_layout,
+            descriptor_buffer_device_address,
+            screen_group_counts,
+        );
+    }
+
+    let (present_texture_reference, present_image_name) = if is_camera_rendered
+        && (post_process_settings.effects.is_enabled() || frame_context.is_lens_flare_visible)
+    {
+        frame_graph_capture.ensure_image_state(
+            command_buffer,
+            &textures_pool,
+            (frame_context.draw_texture_reference, "Draw Image"),
+            ImageState::new(
+                ImageLayout::General,
+                PipelineStageFlags2::ComputeShader,
+                AccessFlags2::ShaderStorageRead,
+            ),
+        );
+        // NOTE: Previous content is fully overwritten, so it's discarded. On the compute queue
+        // its last reads are on the graphics queue, they're ordered by the semaphore instead.
+        match async_compute {
+            Some(_) => textures_pool
+                .record_image_state(frame_context.post_texture_reference, Default::default()),
+            None => textures_pool.discard_content(frame_context.post_texture_reference),
+        }
+        frame_graph_capture.ensure_image_state(
+            command_buffer,
+            &textures_pool,
+            (frame_context.post_texture_reference, "Post Image"),
+            ImageState::compute_write(),
+        );
+
+        dispatch_compute(
+            command_buffer,
+            &mut frame_graph_capture,
+            FrameGraphPassInfo::new(
+                "Post Effects",
+                &[FrameGraphAccess::image("Draw Image", ImageLayout::General)],
+                &[FrameGraphAccess::image("Post Image", ImageLayout::General)],
+            ),
+            renderer_resources.post_effects_compute_shader_object,
+            pipeline_layout,
+            descriptor_buffer_device_address,
+            screen_group_counts,
+        );
+
+        (frame_context.post_texture_reference, "Post Image")
+    } else {
+        (frame_context.draw_texture_reference, "Draw Image")
+    };
+    let Some(present_image) = textures_pool.get_image(present_texture_reference) else {
+        frame_errors.record(FrameError::missing_resource("get the present image"));
+
+        return;
+    };
+
+    #[cfg(feature = "debug-tools")]
+    if let Some(debug_overlay_bounds) = frame_context.debug_overlay_bounds {
+        frame_graph_capture.ensure_image_state(
+            command_buffer,
+            &textures_pool,
+            (present_texture_reference, present_image_name),
+            ImageState::compute_read_write(),
+        );
+
+        let present_image_access = [FrameGraphAccess::image(
+            present_image_name,
+            ImageLayout::General,
+        )];
+        dispatch_compute(
+            command_buffer,
+            &mut frame_graph_capture,
+            FrameGraphPassInfo::new(
+                "Debug Overlay",
+                &present_image_access,
+                &present_image_access,
+            ),
+            renderer_resources.debug_overlay_compute_shader_object,
+            pipeline_layout,
+            descriptor_buffer_device_address,
+            [
+                debug_overlay_bounds.extent.width.div_ceil(16),
+                debug_overlay_bounds.extent.height.div_ceil(16),
+                1,
+            ],
+        );
+    }
+
+    let present_image_state = ImageState::new(
         ImageLayout::General,
-        PipelineStageFlags2::ColorAttachmentOutput,
         PipelineStageFlags2::Blit,
-        AccessFlags2::ColorAttachmentWrite,
         AccessFlags2::TransferRead,
-        draw_image.image_aspect_flags,
-        frame_context
-            .draw_texture_reference
-            .texture_metadata
-            .mip_levels_count,
     );
+    // NOTE: Draw image is drawn into by the next frame, so it's returned even if the post image
+    // is presented instead.
+    let returned_textures: Vec<_> =
+        (present_texture_reference.key != frame_context.draw_texture_reference.key)
+            .then_some((
+                frame_context.draw_texture_reference,
+                draw_image,
+                "Draw Image",
+            ))
+            .into_iter()
+            .chain(lens_flare_sprite_textures.iter().map(
+                |&(texture_reference, allocated_image)| {
+                    (texture_reference, allocated_image, "Lens Flare Sprite")
+                },
+            ))
+            .collect();
+    let command_buffer = match async_compute {
+        Some(async_compute) => match end_async_compute(
+            command_buffer,
+            async_compute,
+            (present_image, present_image_name),
+            present_image_state,
+            &textures_pool,
+            &returned_textures,
+            &mut frame_graph_capture,
+        ) {
+            Ok(present_command_buffer) => {
+                // NOTE: Acquire of the ownership transfer is the barrier of the blit.
+                textures_pool.record_image_state(present_texture_reference, present_image_state);
+
+                present_command_buffer
+            }
+            Err(error) => {
+                frame_errors.record(error);
+
+                return;
+            }
+        },
+        None => {
+            frame_graph_capture.ensure_image_state(
+                command_buffer,
+                &textures_pool,
+                (present_texture_reference, present_image_name),
+                present_image_state,
+            );
 
-    transition_image(
+            command_buffer
+        }
+    };
+
+    if let Some(swapchain_image) = swapchain_image {
+        blit_to_swapchain_image(
+            command_buffer,
+            &renderer_context,
+            &mut frame_graph_capture,
+            (present_image, present_image_name),
+            swapchain_image,
+        );
+    }
+
+    gpu_profiler.write_timestamp(
         command_buffer,
-        swapchain_image,
-        ImageLayout::Undefined,
+        GpuTimestamp::FrameEnd,
+        PipelineStageFlags2::AllCommands,
+    );
+
+    frame_graph_capture.end_frame();
+
+    if let Err(status) = command_buffer.end() {
+        frame_errors.record(FrameError::new("end the command buffer", status));
+    }
+}
+
+#[derive(Clone, Copy)]
+struct AsyncCompute {
+    command_group: AsyncComputeCommandGroup,
+    graphics_queue_family_index: u32,
+    compute_queue_family_index: u32,
+}
+
+/// Ends the scene and begins post-processing on the compute queue, the draw image and the lens
+/// flare sprites are handed over to it. Post image isn't, its previous content is discarded anyway.
+/// Buffers are shared by both families, so they're ordered by the semaphore alone.
+#[allow(clippy::too_many_arguments)]
+fn begin_async_compute(
+    command_buffer: CommandBuffer,
+    async_compute: AsyncCompute,
+    draw_image: &AllocatedImage,
+    textures_pool: &TexturesPool,
+    lens_flare_sprite_textures: &[(TextureReference, &AllocatedImage)],
+    frame_context: &FrameContext,
+    renderer_resources: &RendererResources,
+    pipeline_layout: PipelineLayout,
+    frame_graph_capture: &mut FrameGraphCapture,
+) -> Result<CommandBuffer, FrameError> {
+    let queue_family_indices = (
+        async_compute.graphics_queue_family_index,
+        async_compute.compute_queue_family_index,
+    );
+
+    let compute_command_buffer = async_compute.command_group.command_buffer;
+    compute_command_buffer
+        .begin(&create_command_buffer_begin_info(
+            CommandBufferUsageFlags::OneTimeSubmit,
+        ))
+        .map_err(|status| FrameError::new("begin the compute command buffer", status))?;
+
+    frame_graph_capture.transfer_image_ownership(
+        (command_buffer, compute_command_buffer),
+        (draw_image, "Draw Image"),
+        (
+            ImageState::compute_write(),
+            ImageState::compute_read_write(),
+        ),
+        queue_family_indices,
+    );
+    let compute_sampled_read_state = ImageState::new(
+        ImageLayout::General,
+        PipelineStageFlags2::ComputeShader,
+        AccessFlags2::ShaderSampledRead,
+    );
+    for &(texture_reference, allocated_image) in lens_flare_sprite_textures {
+        frame_graph_capture.ensure_image_state(
+            command_buffer,
+            textures_pool,
+            (texture_reference, "Lens Flare Sprite"),
+            ImageState::sampled_read(),
+        );
+        frame_graph_capture.transfer_image_ownership(
+            (command_buffer, compute_command_buffer),
+            (allocated_image, "Lens Flare Sprite"),
+            (ImageState::sampled_read(), compute_sampled_read_state),
+            queue_family_indices,
+        );
+        textures_pool.record_image_state(texture_reference, compute_sampled_read_state);
+    }
+    command_buffer
+        .end()
+        .map_err(|status| FrameError::new("end the scene command buffer", status))?;
+
+    // NOTE: Push constants don't outlive the command buffer, post-processing reads the first camera.
+    compute_command_buffer.push_constants(
+        pipeline_layout,
+        ShaderStageFlags::MeshEXT
+            | ShaderStageFlags::Fragment
+            | ShaderStageFlags::Compute
+            | ShaderStageFlags::TaskEXT,
+        Default::default(),
+        renderer_resources.push_constant_layout.get_pushed_size(),
+        &frame_context.push_constant as *const _ as _,
+    );
+
+    Ok(compute_command_buffer)
+}
+
+/// Ends post-processing and begins the present command buffer, the presented image and the rest
+/// of the images the compute queue acquired are handed back to the graphics queue. Those keep
+/// their states, they're only accessed again by the next frames.
+fn end_async_compute(
+    compute_command_buffer: CommandBuffer,
+    async_compute: AsyncCompute,
+    (present_image, present_image_name): (&AllocatedImage, &'static str),
+    present_image_state: ImageState,
+    textures_pool: &TexturesPool,
+    returned_textures: &[(TextureReference, &AllocatedImage, &'static str)],
+    frame_graph_capture: &mut FrameGraphCapture,
+) -> Result<CommandBuffer, FrameError> {
+    let queue_family_indices = (
+        async_compute.compute_queue_family_index,
+        async_compute.graphics_queue_family_index,
+    );
+
+    let present_command_buffer = async_compute.command_group.present_command_buffer;
+    present_command_buffer
+        .begin(&create_command_buffer_begin_info(
+            CommandBufferUsageFlags::OneTimeSubmit,
+        ))
+        .map_err(|status| FrameError::new("begin the present command buffer", status))?;
+
+    frame_graph_capture.transfer_image_ownership(
+        (compute_command_buffer, present_command_buffer),
+        (present_image, present_image_name),
+        (ImageState::compute_write(), present_image_state),
+        queue_family_indices,
+    );
+    for &(texture_reference, allocated_image, image_name) in returned_textures {
+        let image_state = textures_pool.get_image_state(texture_reference);
+        frame_graph_capture.transfer_image_ownership(
+            (compute_command_buffer, present_command_buffer),
+            (allocated_image, image_name),
+            (image_state, image_state),
+            queue_family_indices,
+        );
+    }
+    compute_command_buffer
+        .end()
+        .map_err(|status| FrameError::new("end the compute command buffer", status))?;
+
+    Ok(present_command_buffer)
+}
+
+/// Textures of the sprites that are drawn, each one once, atlases are shared by several sprites.
+fn get_lens_flare_sprite_textures<'a>(
+    post_process_settings: &PostProcessSettings,
+    textures_pool: &'a TexturesPool,
+) -> Vec<(TextureReference, &'a AllocatedImage)> {
+    let mut lens_flare_sprite_textures: Vec<(TextureReference, &AllocatedImage)> = Vec::new();
+    for texture_reference in post_process_settings
+        .lens_flare
+        .iter()
+        .flat_map(|lens_flare| lens_flare.sprites.iter().take(LensFlare::MAX_SPRITES_COUNT))
+        .filter_map(|sprite| sprite.texture_reference)
+    {
+        if lens_flare_sprite_textures
+            .iter()
+            .any(|(sprite_texture_reference, _)| {
+                sprite_texture_reference.key == texture_reference.key
+            })
+        {
+            continue;
+        }
+        if let Some(allocated_image) = textures_pool.get_image(texture_reference) {
+            lens_flare_sprite_textures.push((texture_reference, allocated_image));
+        }
+    }
+
+    lens_flare_sprite_textures
+}
+
+/// Present image must be readable by the blit already, the swapchain image is left presentable.
+fn blit_to_swapchain_image(
+    command_buffer: CommandBuffer,
+    renderer_context: &RendererContext,
+    frame_graph_capture: &mut FrameGraphCapture,
+    (present_image, present_image_name): (&AllocatedImage, &'static str),
+    swapchain_image: Image,
+) {
+    let swapchain_image_state = ImageState::new(
         ImageLayout::General,
-        PipelineStageFlags2::ColorAttachmentOutput,
         PipelineStageFlags2::Blit,
-        AccessFlags2::None,
         AccessFlags2::TransferWrite,
-        ImageAspectFlags::Color,
-        1,
+    );
+    frame_graph_capture.transition_image(
+        command_buffer,
+        (swapchain_image, "Swapchain Image"),
+        ImageState::new(
+            ImageLayout::Undefined,
+            PipelineStageFlags2::ColorAttachmentOutput,
+            AccessFlags2::None,
+        ),
+        swapchain_image_state,
     );
 
     copy_image_to_image(
         command_buffer,
-        draw_image.image,
+        present_image.image,
         swapchain_image,
-        draw_image_extent2d,
+        renderer_context.render_extent,
         renderer_context.draw_extent,
     );
+    frame_graph_capture.record_pass(FrameGraphPassInfo::new(
+        "Blit",
+        &[FrameGraphAccess::image(
+            present_image_name,
+            ImageLayout::General,
+        )],
+        &[FrameGraphAccess::image(
+            "Swapchain Image",
+            ImageLayout::General,
+        )],
+    ));
 
-    transition_image(
+    frame_graph_capture.transition_image(
         command_buffer,
-        swapchain_image,
+        (swapchain_image, "Swapchain Image"),
+        swapchain_image_state,
+        ImageState::new(
+            ImageLayout::PresentSrcKHR,
+            PipelineStageFlags2::ColorAttachmentOutput,
+            AccessFlags2::None,
+        ),
+    );
+}
+
+/// Compute pass over viewports of some cameras, it reads the draw image and writes the post image.
+struct ViewportsPass {
+    name: &'static str,
+    compute_shader_object: ShaderObject,
+    camera_indices: Vec<usize>,
+}
+
+/// Passes can't write the draw image in place as they read neighbouring texels, so their results
+/// are copied back from the post image and exposure and tonemapping work on them.
+#[allow(clippy::too_many_arguments)]
+fn draw_viewports_pass(
+    command_buffer: CommandBuffer,
+    textures_pool: &TexturesPool,
+    buffers_pool: &BuffersPool,
+    frame_context: &FrameContext,
+    renderer_resources: &RendererResources,
+    descriptor_set_handle: &DescriptorSetHandle,
+    frame_graph_capture: &mut FrameGraphCapture,
+    viewports_pass: ViewportsPass,
+) -> Result<(), FrameError> {
+    let pipeline_layout = descriptor_set_handle.get_pipeline_layout();
+    let descriptor_buffer_device_address = descriptor_set_handle
+        .get_buffer_info(buffers_pool)
+        .device_address;
+    let draw_image = textures_pool
+        .get_image(frame_context.draw_texture_reference)
+        .ok_or(FrameError::missing_resource("get the draw image"))?;
+    let post_image = textures_pool
+        .get_image(frame_context.post_texture_reference)
+        .ok_or(FrameError::missing_resource("get the post image"))?;
+
+    // NOTE: Previous content is fully overwritten, so it's discarded.
+    textures_pool.discard_content(frame_context.post_texture_reference);
+    frame_graph_capture.ensure_image_state(
+        command_buffer,
+        textures_pool,
+        (frame_context.post_texture_reference, "Post Image"),
+        ImageState::compute_write(),
+    );
+
+    let compute_pass = ComputePass::begin(
+        command_buffer,
+        viewports_pass.compute_shader_object,
+        pipeline_layout,
+        descriptor_buffer_device_address,
+    );
+    for &camera_index in &viewports_pass.camera_indices {
+        let camera_viewport = frame_context.cameras[camera_index].viewport;
+        let (push_constant_offset, device_address) = renderer_resources
+            .resources_pool
+            .get_camera_push_constant(buffers_pool, camera_index);
+        command_buffer.push_constants(
+            pipeline_layout,
+            ShaderStageFlags::MeshEXT
+                | ShaderStageFlags::Fragment
+                | ShaderStageFlags::Compute
+                | ShaderStageFlags::TaskEXT,
+            push_constant_offset,
+            std::mem::size_of::<DeviceAddress>() as _,
+            &device_address as *const _ as _,
+        );
+
+        compute_pass.dispatch([
+            camera_viewport.extent.width.div_ceil(16),
+            camera_viewport.extent.height.div_ceil(16),
+            1,
+        ]);
+    }
+    frame_graph_capture.record_pass(FrameGraphPassInfo::new(
+        viewports_pass.name,
+        &[
+            FrameGraphAccess::image("Draw Image", ImageLayout::General),
+            FrameGraphAccess::image("Depth Image", ImageLayout::General),
+        ],
+        &[FrameGraphAccess::image("Post Image", ImageLayout::General)],
+    ));
+
+    frame_graph_capture.ensure_image_state(
+        command_buffer,
+        textures_pool,
+        (frame_context.post_texture_reference, "Post Image"),
+        ImageState::transfer_read(),
+    );
+    frame_graph_capture.ensure_image_state(
+        command_buffer,
+        textures_pool,
+        (frame_context.draw_texture_reference, "Draw Image"),
+        ImageState::transfer_write(),
+    );
+
+    let regions: Vec<_> = viewports_pass
+        .camera_indices
+        .iter()
+        .map(|&camera_index| frame_context.cameras[camera_index].viewport)
+        .collect();
+    copy_image_regions(command_buffer, post_image.image, draw_image.image, &regions);
+    frame_graph_capture.record_pass(FrameGraphPassInfo::new(
+        "Post Image Resolve",
+        &[FrameGraphAccess::image("Post Image", ImageLayout::General)],
+        &[FrameGraphAccess::image("Draw Image", ImageLayout::General)],
+    ));
+
+    frame_graph_capture.ensure_image_state(
+        command_buffer,
+        textures_pool,
+        (frame_context.draw_texture_reference, "Draw Image"),
+        ImageState::compute_read_write(),
+    );
+
+    Ok(())
+}
+
+/// Top left corner of the draw image is copied into the readback buffer and made visible to the host.
+#[cfg(feature = "capture")]
+#[allow(clippy::too_many_arguments)]
+fn copy_draw_image(
+    command_buffer: CommandBuffer,
+    textures_pool: &TexturesPool,
+    draw_texture_reference: TextureReference,
+    buffers_pool: &BuffersPool,
+    frame_graph_capture: &mut FrameGraphCapture,
+    readback_buffer_reference: BufferReference,
+    extent: Extent2D,
+    (pass_name, buffer_name): (&'static str, &'static str),
+) -> Result<(), FrameError> {
+    let draw_image = textures_pool
+        .get_image(draw_texture_reference)
+        .ok_or(FrameError::missing_resource("get the draw image"))?;
+    let readback_buffer = buffers_pool
+        .get_buffer(readback_buffer_reference)
+        .ok_or(FrameError::missing_resource("get the readback buffer"))?
+        .buffer;
+    frame_graph_capture.ensure_image_state(
+        command_buffer,
+        textures_pool,
+        (draw_texture_reference, "Draw Image"),
+        ImageState::transfer_read(),
+    );
+
+    buffers_pool.require_buffer_state(
+        readback_buffer_reference,
+        BufferState::new(PipelineStageFlags2::Copy, AccessFlags2::TransferWrite),
+    );
+    frame_graph_capture.flush_buffer_barriers(command_buffer, buffers_pool, buffer_name);
+    let buffer_image_copy = BufferImageCopy {
+        image_subresource: ImageSubresourceLayers {
+            aspect_mask: ImageAspectFlags::Color,
+            mip_level: Default::default(),
+            base_array_layer: Default::default(),
+            layer_count: 1,
+        },
+        image_extent: Extent3D {
+            width: extent.width,
+            height: extent.height,
+            depth: 1,
+        },
+        ..Default::default()
+    };
+    command_buffer.copy_image_to_buffer(
+        draw_image.image,
         ImageLayout::General,
-        ImageLayout::PresentSrcKHR,
-        PipelineStageFlags2::Blit,
-        PipelineStageFlags2::ColorAttachmentOutput,
-        AccessFlags2::TransferWrite,
-        AccessFlags2::None,
-        ImageAspectFlags::Color,
-        1,
+        readback_buffer,
+        &[buffer_image_copy],
+    );
+    frame_graph_capture.record_pass(FrameGraphPassInfo::new(
+        pass_name,
+        &[FrameGraphAccess::image("Draw Image", ImageLayout::General)],
+        &[FrameGraphAccess::buffer(buffer_name)],
+    ));
+
+    buffers_pool.require_buffer_state(
+        readback_buffer_reference,
+        BufferState::new(PipelineStageFlags2::Host, AccessFlags2::HostRead),
     );
+    frame_graph_capture.flush_buffer_barriers(command_buffer, buffers_pool, buffer_name);
+
+    frame_graph_capture.ensure_image_state(
+        command_buffer,
+        textures_pool,
+        (draw_texture_reference, "Draw Image"),
+        ImageState::compute_read_write(),
+    );
+
+    Ok(())
+}
 
-    command_buffer.end().unwrap();
+fn draw_visibility_queries_pass(
+    command_buffer: CommandBuffer,
+    renderer_context: &RendererContext,
+    renderer_resources: &RendererResources,
+    descriptor_set_handle: &DescriptorSetHandle,
+    buffers_pool: &BuffersPool,
+    frame_graph_capture: &mut FrameGraphCapture,
+    visibility_queries_count: usize,
+) {
+    let pipeline_layout = descriptor_set_handle.get_pipeline_layout();
+    let descriptor_buffer_device_address = descriptor_set_handle
+        .get_buffer_info(buffers_pool)
+        .device_address;
+    let visibility_query_results_buffer_reference = renderer_context
+        .get_current_frame_data()
+        .visibility_query_results_buffer_reference;
+
+    let (push_constant_offset, device_address) = renderer_resources
+        .resources_pool
+        .get_camera_push_constant(buffers_pool, Default::default());
+    command_buffer.push_constants(
+        pipeline_layout,
+        ShaderStageFlags::MeshEXT
+            | ShaderStageFlags::Fragment
+            | ShaderStageFlags::Compute
+            | ShaderStageFlags::TaskEXT,
+        push_constant_offset,
+        std::mem::size_of::<DeviceAddress>() as _,
+        &device_address as *const _ as _,
+    );
+
+    buffers_pool.require_buffer_state(
+        visibility_query_results_buffer_reference,
+        BufferState::new(
+            PipelineStageFlags2::ComputeShader,
+            AccessFlags2::ShaderStorageWrite,
+        ),
+    );
+    frame_graph_capture.flush_buffer_barriers(
+        command_buffer,
+        buffers_pool,
+        "Visibility Query Results Buffer",
+    );
+    dispatch_compute(
+        command_buffer,
+        frame_graph_capture,
+        FrameGraphPassInfo::new(
+            "Visibility Queries",
+            &[FrameGraphAccess::image("Depth Image", ImageLayout::General)],
+            &[FrameGraphAccess::buffer("Visibility Query Results Buffer")],
+        ),
+        renderer_resources.visibility_queries_compute_shader_object,
+        pipeline_layout,
+        descriptor_buffer_device_address,
+        [(visibility_queries_count as u32).div_ceil(64), 1, 1],
+    );
+
+    buffers_pool.require_buffer_state(
+        visibility_query_results_buffer_reference,
+        BufferState::host_read(),
+    );
+    frame_graph_capture.flush_buffer_barriers(
+        command_buffer,
+        buffers_pool,
+        "Visibility Query Results Buffer",
+    );
+}
+
+fn draw_depth_picks_pass(
+    command_buffer: CommandBuffer,
+    renderer_context: &RendererContext,
+    renderer_resources: &RendererResources,
+    descriptor_set_handle: &DescriptorSetHandle,
+    buffers_pool: &BuffersPool,
+    frame_graph_capture: &mut FrameGraphCapture,
+    depth_picks_count: usize,
+) {
+    let pipeline_layout = descriptor_set_handle.get_pipeline_layout();
+    let descriptor_buffer_device_address = descriptor_set_handle
+        .get_buffer_info(buffers_pool)
+        .device_address;
+    let depth_picks_buffer_reference = renderer_context
+        .get_current_frame_data()
+        .depth_picks_buffer_reference;
+
+    let (push_constant_offset, device_address) = renderer_resources
+        .resources_pool
+        .get_camera_push_constant(buffers_pool, Default::default());
+    command_buffer.push_constants(
+        pipeline_layout,
+        ShaderStageFlags::MeshEXT
+            | ShaderStageFlags::Fragment
+            | ShaderStageFlags::Compute
+            | ShaderStageFlags::TaskEXT,
+        push_constant_offset,
+        std::mem::size_of::<DeviceAddress>() as _,
+        &device_address as *const _ as _,
+    );
+
+    buffers_pool.require_buffer_state(
+        depth_picks_buffer_reference,
+        BufferState::new(
+            PipelineStageFlags2::ComputeShader,
+            AccessFlags2::ShaderStorageRead | AccessFlags2::ShaderStorageWrite,
+        ),
+    );
+    frame_graph_capture.flush_buffer_barriers(command_buffer, buffers_pool, "Depth Picks Buffer");
+    dispatch_compute(
+        command_buffer,
+        frame_graph_capture,
+        FrameGraphPassInfo::new(
+            "Depth Picks",
+            &[FrameGraphAccess::image("Depth Image", ImageLayout::General)],
+            &[FrameGraphAccess::buffer("Depth Picks Buffer")],
+        ),
+        renderer_resources.depth_picks_compute_shader_object,
+        pipeline_layout,
+        descriptor_buffer_device_address,
+        [(depth_picks_count as u32).div_ceil(16), 1, 1],
+    );
+
+    buffers_pool.require_buffer_state(depth_picks_buffer_reference, BufferState::host_read());
+    frame_graph_capture.flush_buffer_barriers(command_buffer, buffers_pool, "Depth Picks Buffer");
+}
+
+/// Single dispatch of the shader, it's recorded as the pass.
+fn dispatch_compute(
+    command_buffer: CommandBuffer,
+    frame_graph_capture: &mut FrameGraphCapture,
+    pass_info: FrameGraphPassInfo,
+    compute_shader_object: ShaderObject,
+    pipeline_layout: PipelineLayout,
+    descriptor_buffer_device_address: DeviceAddress,
+    group_counts: [u32; 3],
+) {
+    ComputePass::begin(
+        command_buffer,
+        compute_shader_object,
+        pipeline_layout,
+        descriptor_buffer_device_address,
+    )
+    .dispatch(group_counts);
+    frame_graph_capture.record_pass(pass_info);
 }
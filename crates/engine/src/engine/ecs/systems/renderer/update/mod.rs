@@ -1,7 +1,27 @@
+pub mod acquire_transient_resources;
 pub mod begin_rendering;
 pub mod collect_instance_objects;
+pub mod defragment_memory;
 pub mod end_rendering;
+pub mod notify_gpu_resource_events;
 pub mod prepare_frame;
 pub mod present;
+pub mod release_resources;
 pub mod render_meshes;
+pub mod retire_descriptor_buffers;
+#[cfg(feature = "capture")]
+pub mod update_cubemap_capture;
+pub mod update_depth_picks;
+pub mod update_depth_pyramid;
+#[cfg(feature = "capture")]
+pub mod update_frame_capture;
+#[cfg(feature = "profiler")]
+pub mod update_frame_time_graph;
+#[cfg(feature = "debug-tools")]
+pub mod update_gizmos;
+#[cfg(feature = "debug-tools")]
+pub mod update_memory_overlay;
+pub mod update_render_scale;
 pub mod update_resources;
+pub mod update_texture_usage;
+pub mod update_visibility_queries;
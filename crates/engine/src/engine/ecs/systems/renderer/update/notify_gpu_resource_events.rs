@@ -0,0 +1,31 @@
+use bevy_ecs::system::{Commands, ResMut};
+
+use crate::engine::{
+    events::GpuResourceEvent,
+    general::renderer::DescriptorSetHandle,
+    resources::{buffers_pool::BuffersPool, textures_pool::TexturesPool},
+};
+
+/// Triggers lifecycle changes recorded by the pools, so tools observe them without the pools.
+pub fn notify_gpu_resource_events_system(
+    mut commands: Commands,
+    mut buffers_pool: ResMut<BuffersPool>,
+    mut textures_pool: ResMut<TexturesPool>,
+    mut descriptor_set_handle: ResMut<DescriptorSetHandle>,
+) {
+    let gpu_resource_events = buffers_pool
+        .take_gpu_resource_events()
+        .into_iter()
+        .chain(textures_pool.take_gpu_resource_events())
+        .chain(descriptor_set_handle.take_gpu_resource_events());
+    for gpu_resource_event in gpu_resource_events {
+        match gpu_resource_event {
+            GpuResourceEvent::BufferCreated(event) => commands.trigger(event),
+            GpuResourceEvent::BufferDestroyed(event) => commands.trigger(event),
+            GpuResourceEvent::BufferMoved(event) => commands.trigger(event),
+            GpuResourceEvent::TextureCreated(event) => commands.trigger(event),
+            GpuResourceEvent::TextureDestroyed(event) => commands.trigger(event),
+            GpuResourceEvent::DescriptorIndexAssigned(event) => commands.trigger(event),
+        }
+    }
+}
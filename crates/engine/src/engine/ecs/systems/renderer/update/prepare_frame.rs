@@ -1,33 +1,56 @@
+use std::time::Instant;
+
 use bevy_ecs::system::{Res, ResMut};
 use vulkanite::vk::*;
 
 use crate::engine::resources::{
-    FrameContext, RendererContext, RendererResources, VulkanContextResource,
+    FrameContext, FrameError, FrameErrors, LatencyStats, RendererContext, RendererResources,
+    SwapchainStatus, VulkanContextResource,
 };
 
 pub fn prepare_frame_system(
     vulkan_ctx: Res<VulkanContextResource>,
-    render_ctx: Res<RendererContext>,
+    mut render_ctx: ResMut<RendererContext>,
     mut renderer_resources: ResMut<RendererResources>,
     mut frame_ctx: ResMut<FrameContext>,
+    mut latency_stats: ResMut<LatencyStats>,
+    mut frame_errors: ResMut<FrameErrors>,
 ) {
     let device = &vulkan_ctx.device;
     let frame_data = render_ctx.get_current_frame_data();
     let fences = [frame_data.command_group.fence];
+    let command_buffer = frame_data.command_group.command_buffer;
+    let async_compute_command_group = frame_data.async_compute_command_group;
 
-    device
-        .wait_for_fences(fences.as_slice(), true, u64::MAX)
-        .unwrap();
-    device.reset_fences(fences.as_slice()).unwrap();
-
-    let (_status, swapchain_image_index) = device
-        .acquire_next_image_khr(
-            vulkan_ctx.swapchain,
-            u64::MAX,
-            Some(frame_data.swapchain_semaphore),
-            Default::default(),
-        )
-        .unwrap();
+    let wait_start = Instant::now();
+    if let Err(status) = device.wait_for_fences(fences.as_slice(), true, u64::MAX) {
+        frame_errors.record(FrameError::new("wait for the frame fence", status));
+
+        return;
+    }
+    latency_stats.add_wait_time(wait_start);
+
+    // NOTE: Headless engine renders without the swapchain image, frame ends in the draw texture.
+    let swapchain_image_index = match vulkan_ctx.swapchain {
+        Some(swapchain) => {
+            let swapchain_image_index = acquire_swapchain_image(
+                device,
+                swapchain,
+                &mut render_ctx,
+                &mut latency_stats,
+                &mut frame_errors,
+            );
+            let Some(swapchain_image_index) = swapchain_image_index else {
+                frame_ctx.is_swapchain_image_acquired = false;
+
+                return;
+            };
+
+            swapchain_image_index
+        }
+        None => Default::default(),
+    };
+    frame_ctx.is_swapchain_image_acquired = true;
     frame_ctx.swapchain_image_index = swapchain_image_index;
 
     let instances_objects_buffer = renderer_resources
@@ -37,6 +60,13 @@ pub fn prepare_frame_system(
         .unwrap();
     instances_objects_buffer.next_buffer();
 
+    let static_instances_objects_buffer = renderer_resources
+        .resources_pool
+        .static_instances_buffer
+        .as_mut()
+        .unwrap();
+    static_instances_objects_buffer.next_buffer();
+
     let scene_data_buffer = renderer_resources
         .resources_pool
         .scene_data_buffer
@@ -44,9 +74,140 @@ pub fn prepare_frame_system(
         .unwrap();
     scene_data_buffer.next_buffer();
 
-    frame_data
-        .command_group
-        .command_buffer
-        .reset(CommandBufferResetFlags::ReleaseResources)
+    let point_lights_buffer = renderer_resources
+        .resources_pool
+        .point_lights_buffer
+        .as_mut()
+        .unwrap();
+    point_lights_buffer.next_buffer();
+
+    let foliages_buffer = renderer_resources
+        .resources_pool
+        .foliages_buffer
+        .as_mut()
+        .unwrap();
+    foliages_buffer.next_buffer();
+
+    let visibility_query_points_buffer = renderer_resources
+        .resources_pool
+        .visibility_query_points_buffer
+        .as_mut()
         .unwrap();
+    visibility_query_points_buffer.next_buffer();
+
+    #[cfg(feature = "debug-tools")]
+    renderer_resources
+        .resources_pool
+        .debug_overlay_rects_buffer
+        .as_mut()
+        .unwrap()
+        .next_buffer();
+
+    if let Some(push_constant_overflow_buffer) = renderer_resources
+        .resources_pool
+        .push_constant_overflow_buffer
+        .as_mut()
+    {
+        push_constant_overflow_buffer.next_buffer();
+    }
+
+    // NOTE: Compute submission is waited by the present one, so the fence covers both of them.
+    let command_buffers =
+        std::iter::once(command_buffer).chain(async_compute_command_group.into_iter().flat_map(
+            |async_compute_command_group| {
+                [
+                    async_compute_command_group.command_buffer,
+                    async_compute_command_group.present_command_buffer,
+                ]
+            },
+        ));
+    for command_buffer in command_buffers {
+        if let Err(status) = command_buffer.reset(CommandBufferResetFlags::ReleaseResources) {
+            frame_errors.record(FrameError::new("reset the command buffer", status));
+
+            return;
+        }
+    }
+}
+
+/// Swapchain semaphore is left pending, `None` once the frame can't be rendered.
+fn acquire_swapchain_image(
+    device: &rs::Device,
+    swapchain: rs::SwapchainKHR,
+    render_ctx: &mut RendererContext,
+    latency_stats: &mut LatencyStats,
+    frame_errors: &mut FrameErrors,
+) -> Option<u32> {
+    // NOTE: Acquired image wasn't submitted, so the semaphore still waits for the signal of the acquire.
+    // It can't be signaled again, it's replaced once the presentation engine is done with it.
+    if render_ctx
+        .get_current_frame_data()
+        .is_swapchain_semaphore_pending
+    {
+        let swapchain_semaphore = device
+            .wait_idle()
+            .and_then(|_| device.create_semaphore(&SemaphoreCreateInfo::default()));
+        let swapchain_semaphore = match swapchain_semaphore {
+            Ok(swapchain_semaphore) => swapchain_semaphore,
+            Err(status) => {
+                frame_errors.record(FrameError::new("replace the swapchain semaphore", status));
+
+                return None;
+            }
+        };
+
+        let frame_data = render_ctx.get_current_frame_data_mut();
+        unsafe {
+            device.destroy_semaphore(Some(frame_data.swapchain_semaphore));
+        }
+        frame_data.swapchain_semaphore = swapchain_semaphore;
+        frame_data.is_swapchain_semaphore_pending = false;
+    }
+    let swapchain_semaphore = render_ctx.get_current_frame_data().swapchain_semaphore;
+
+    let wait_start = Instant::now();
+    let acquire_result = device.acquire_next_image_khr(
+        swapchain,
+        u64::MAX,
+        Some(swapchain_semaphore),
+        Default::default(),
+    );
+    latency_stats.add_wait_time(wait_start);
+    let swapchain_image_index = match acquire_result {
+        Ok((status, swapchain_image_index)) => {
+            if status == Status::SuboptimalKHR {
+                render_ctx.swapchain_status = SwapchainStatus::OutOfDate;
+            }
+
+            swapchain_image_index
+        }
+        Err(Status::ErrorOutOfDateKHR) => {
+            render_ctx.swapchain_status = SwapchainStatus::OutOfDate;
+
+            return None;
+        }
+        Err(Status::ErrorSurfaceLostKHR) => {
+            render_ctx.swapchain_status = SwapchainStatus::SurfaceLost;
+
+            return None;
+        }
+        Err(status) => {
+            frame_errors.record(FrameError::new("acquire the swapchain image", status));
+
+            return None;
+        }
+    };
+    render_ctx
+        .get_current_frame_data_mut()
+        .is_swapchain_semaphore_pending = true;
+
+    Some(swapchain_image_index)
+}
+
+/// Rest of the frame is skipped once a system abandons it.
+pub fn is_swapchain_image_acquired(
+    frame_ctx: Res<FrameContext>,
+    frame_errors: Res<FrameErrors>,
+) -> bool {
+    frame_ctx.is_swapchain_image_acquired && !frame_errors.is_frame_abandoned()
 }
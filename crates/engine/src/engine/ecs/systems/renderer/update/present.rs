@@ -1,56 +1,258 @@
-use bevy_ecs::system::{Res, ResMut};
-use vulkanite::vk::*;
+use bevy_ecs::system::{NonSendMarker, Res, ResMut};
+use vulkanite::vk::{
+    rs::{Device, Fence, Queue},
+    *,
+};
 
 use crate::engine::{
-    resources::{FrameContext, RendererContext, VulkanContextResource},
+    resources::{
+        FrameContext, FrameError, FrameErrors, LatencyStats, RendererContext, SwapchainStatus,
+        VulkanContextResource,
+    },
     utils::{command_buffer_submit_info, semaphore_submit_info, submit_info},
 };
 
+/// Runs on the main thread, some platforms accept the present only from the thread of the window.
 pub fn present_system(
+    _main_thread_marker: NonSendMarker,
     vulkan_ctx: Res<VulkanContextResource>,
     mut render_ctx: ResMut<RendererContext>,
     frame_ctx: Res<FrameContext>,
+    mut latency_stats: ResMut<LatencyStats>,
+    mut frame_errors: ResMut<FrameErrors>,
 ) {
-    let _device = &vulkan_ctx.device;
+    let device = &vulkan_ctx.device;
     let frame_data = render_ctx.get_current_frame_data();
     let command_buffer = frame_data.command_group.command_buffer;
+    let fence = frame_data.command_group.fence;
     let swapchain_image_index = frame_ctx.swapchain_image_index;
 
-    let command_buffer_submit_infos = [command_buffer_submit_info(&command_buffer)];
-
-    let wait_semaphore_submit_infos = [semaphore_submit_info(
-        PipelineStageFlags2::ColorAttachmentOutput,
-        &frame_data.swapchain_semaphore,
-    )];
-    let signal_semaphore_submit_infos = [semaphore_submit_info(
-        PipelineStageFlags2::AllGraphics,
-        &frame_data.render_semaphore,
-    )];
-
-    let submit_info = submit_info(
-        &command_buffer_submit_infos,
-        &wait_semaphore_submit_infos,
-        &signal_semaphore_submit_infos,
-    );
-
-    let submit_infos = [submit_info];
-    vulkan_ctx
-        .graphics_queue
-        .submit2(&submit_infos, Some(frame_data.command_group.fence))
-        .unwrap();
-
-    let swapchains = [vulkan_ctx.swapchain];
-    let wait_semaphores = [frame_data.render_semaphore];
+    // NOTE: Headless frame has no swapchain image to wait for and to present.
+    let swapchain_wait_semaphore_submit_info = vulkan_ctx.swapchain.map(|_| {
+        semaphore_submit_info(
+            PipelineStageFlags2::ColorAttachmentOutput,
+            &frame_data.swapchain_semaphore,
+        )
+    });
+    // NOTE: Render semaphore belongs to the image, the presentation of it is done once it's acquired again.
+    let render_semaphore = render_ctx
+        .render_semaphores
+        .get(swapchain_image_index as usize)
+        .copied();
+    let signal_semaphore_submit_info = render_semaphore.as_ref().map(|render_semaphore| {
+        semaphore_submit_info(PipelineStageFlags2::AllGraphics, render_semaphore)
+    });
+
+    // NOTE: Timeline values can be signaled only once, a frame that signaled them can't be retried.
+    let mut is_timeline_signaled = false;
+    let submit_result = match (
+        frame_data.async_compute_command_group,
+        vulkan_ctx.compute_queue,
+        render_ctx.async_compute_semaphore,
+    ) {
+        (Some(async_compute_command_group), Some(compute_queue), Some(async_compute_semaphore)) => {
+            let scene_timeline_value = render_ctx.get_scene_timeline_value();
+            let post_process_timeline_value = render_ctx.get_post_process_timeline_value();
+
+            // NOTE: Scene doesn't touch the swapchain image, only the present submission
+            // waits for it.
+            let scene_command_buffer_submit_infos = [command_buffer_submit_info(&command_buffer)];
+            let scene_signal_semaphore_submit_infos =
+                [
+                    semaphore_submit_info(
+                        PipelineStageFlags2::AllCommands,
+                        &async_compute_semaphore,
+                    )
+                    .value(scene_timeline_value),
+                ];
+            let scene_submit_infos = [submit_info(
+                &scene_command_buffer_submit_infos,
+                &[],
+                &scene_signal_semaphore_submit_infos,
+            )];
+            if let Err(status) = vulkan_ctx
+                .graphics_queue
+                .submit2(&scene_submit_infos, Default::default())
+            {
+                frame_errors.record(FrameError::new("submit the scene", status));
+
+                return;
+            }
+            is_timeline_signaled = true;
+
+            let post_process_command_buffer_submit_infos = [command_buffer_submit_info(
+                &async_compute_command_group.command_buffer,
+            )];
+            let post_process_wait_semaphore_submit_infos = [semaphore_submit_info(
+                PipelineStageFlags2::ComputeShader,
+                &async_compute_semaphore,
+            )
+            .value(scene_timeline_value)];
+            let post_process_signal_semaphore_submit_infos =
+                [
+                    semaphore_submit_info(
+                        PipelineStageFlags2::AllCommands,
+                        &async_compute_semaphore,
+                    )
+                    .value(post_process_timeline_value),
+                ];
+            let post_process_submit_infos = [submit_info(
+                &post_process_command_buffer_submit_infos,
+                &post_process_wait_semaphore_submit_infos,
+                &post_process_signal_semaphore_submit_infos,
+            )];
+            if let Err(status) =
+                compute_queue.submit2(&post_process_submit_infos, Default::default())
+            {
+                let scene_wait_semaphore_submit_infos = [semaphore_submit_info(
+                    PipelineStageFlags2::AllCommands,
+                    &async_compute_semaphore,
+                )
+                .value(scene_timeline_value)];
+                if let Err(error) = signal_frame_fence(
+                    device,
+                    &vulkan_ctx.graphics_queue,
+                    &scene_wait_semaphore_submit_infos,
+                    fence,
+                ) {
+                    frame_errors.record(error);
+                }
+                frame_errors.record(FrameError::new("submit the post-processing", status));
+                render_ctx.frame_number += 1;
+
+                return;
+            }
+
+            let present_command_buffer_submit_infos = [command_buffer_submit_info(
+                &async_compute_command_group.present_command_buffer,
+            )];
+            let present_wait_semaphore_submit_infos: Vec<_> = std::iter::once(
+                semaphore_submit_info(PipelineStageFlags2::Blit, &async_compute_semaphore)
+                    .value(post_process_timeline_value),
+            )
+            .chain(swapchain_wait_semaphore_submit_info)
+            .collect();
+            let present_submit_infos = [submit_info(
+                &present_command_buffer_submit_infos,
+                &present_wait_semaphore_submit_infos,
+                signal_semaphore_submit_info.as_slice(),
+            )];
+            // NOTE: Post-processing is the last work of the frame, the fence waits for it if
+            // the present submission fails.
+            submit_frame(
+                device,
+                &vulkan_ctx.graphics_queue,
+                &present_submit_infos,
+                &present_wait_semaphore_submit_infos[..1],
+                fence,
+            )
+        }
+        _ => {
+            let command_buffer_submit_infos = [command_buffer_submit_info(&command_buffer)];
+
+            let submit_info = submit_info(
+                &command_buffer_submit_infos,
+                swapchain_wait_semaphore_submit_info.as_slice(),
+                signal_semaphore_submit_info.as_slice(),
+            );
+
+            let submit_infos = [submit_info];
+            submit_frame(
+                device,
+                &vulkan_ctx.graphics_queue,
+                &submit_infos,
+                &[],
+                fence,
+            )
+        }
+    };
+    if let Err(error) = submit_result {
+        frame_errors.record(error);
+        if is_timeline_signaled {
+            render_ctx.frame_number += 1;
+        }
+
+        return;
+    }
+
+    let (Some(swapchain), Some(render_semaphore)) = (vulkan_ctx.swapchain, render_semaphore) else {
+        render_ctx.frame_number += 1;
+
+        return;
+    };
+
+    render_ctx
+        .get_current_frame_data_mut()
+        .is_swapchain_semaphore_pending = false;
+
+    let swapchains = [swapchain];
+    let wait_semaphores = [render_semaphore];
     let image_indicies = [swapchain_image_index];
 
     let present_info = PresentInfoKHR::default()
         .swapchain(swapchains.as_slice(), &image_indicies, None::<()>)
         .wait_semaphores(wait_semaphores.as_slice());
 
-    vulkan_ctx
-        .graphics_queue
-        .present_khr(&present_info)
-        .unwrap();
+    // NOTE: Frame is submitted either way, so the frame number still advances.
+    match vulkan_ctx.graphics_queue.present_khr(&present_info) {
+        Ok(Status::SuboptimalKHR) | Err(Status::ErrorOutOfDateKHR) => {
+            render_ctx.swapchain_status = SwapchainStatus::OutOfDate;
+        }
+        Err(Status::ErrorSurfaceLostKHR) => {
+            render_ctx.swapchain_status = SwapchainStatus::SurfaceLost;
+        }
+        Ok(_) => (),
+        Err(status) => {
+            frame_errors.record(FrameError::new("present the swapchain image", status));
+        }
+    }
+    latency_stats.record_present();
 
     render_ctx.frame_number += 1;
 }
+
+/// Fence is reset only once the frame is submitted, otherwise the next wait hangs.
+/// Submitted semaphores are signaled by the work of the frame that already went through.
+fn submit_frame(
+    device: &Device,
+    queue: &Queue,
+    submit_infos: &[SubmitInfo2],
+    submitted_semaphore_submit_infos: &[SemaphoreSubmitInfo],
+    fence: Fence,
+) -> Result<(), FrameError> {
+    let fences = [fence];
+    device
+        .reset_fences(fences.as_slice())
+        .map_err(|status| FrameError::new("reset the frame fence", status))?;
+
+    if let Err(status) = queue.submit2(submit_infos, Some(fence)) {
+        // NOTE: Failed submission leaves the fence reset, one without command buffers signals it
+        // again once the earlier work of the frame is done.
+        let fence_submit_infos = [submit_info(&[], submitted_semaphore_submit_infos, &[])];
+        queue
+            .submit2(&fence_submit_infos, Some(fence))
+            .map_err(|status| FrameError::new("signal the frame fence", status))?;
+
+        return Err(FrameError::new("submit the frame", status));
+    }
+
+    Ok(())
+}
+
+/// For frames that failed halfway, so the fence still covers the submissions that went through.
+fn signal_frame_fence(
+    device: &Device,
+    queue: &Queue,
+    submitted_semaphore_submit_infos: &[SemaphoreSubmitInfo],
+    fence: Fence,
+) -> Result<(), FrameError> {
+    let fences = [fence];
+    device
+        .reset_fences(fences.as_slice())
+        .map_err(|status| FrameError::new("reset the frame fence", status))?;
+
+    let fence_submit_infos = [submit_info(&[], submitted_semaphore_submit_infos, &[])];
+    queue
+        .submit2(&fence_submit_infos, Some(fence))
+        .map_err(|status| FrameError::new("signal the frame fence", status))
+}
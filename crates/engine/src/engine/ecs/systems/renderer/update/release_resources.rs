@@ -0,0 +1,50 @@
+use bevy_ecs::system::{Res, ResMut};
+
+use crate::engine::resources::{
+    DeferredResource, DeletionQueue, RendererContext, buffers_pool::BuffersPool,
+    mesh_buffers_pool::MeshBuffersPool, textures_pool::TexturesPool,
+    transient_resources_pool::TransientResourcesPool,
+};
+
+pub fn release_resources_system(
+    render_context: Res<RendererContext>,
+    mut deletion_queue: ResMut<DeletionQueue>,
+    mut buffers_pool: ResMut<BuffersPool>,
+    mut textures_pool: ResMut<TexturesPool>,
+    mut mesh_buffers_pool: ResMut<MeshBuffersPool>,
+    mut transient_resources_pool: ResMut<TransientResourcesPool>,
+) {
+    let mut released_resources =
+        deletion_queue.take_released_resources(render_context.frame_overlap);
+    released_resources.extend(transient_resources_pool.begin_frame(render_context.frame_overlap));
+
+    for released_resource in released_resources {
+        match released_resource {
+            DeferredResource::Buffer(buffer_reference) => unsafe {
+                buffers_pool.free_buffer(buffer_reference);
+            },
+            DeferredResource::Texture(texture_reference) => {
+                textures_pool.free_texture(texture_reference);
+            }
+            DeferredResource::MeshBuffer(mesh_buffer_reference) => {
+                // NOTE: Mesh buffer could have been referenced again while it was waiting.
+                let is_referenced = mesh_buffers_pool
+                    .get_mesh_buffer(mesh_buffer_reference)
+                    .is_some_and(|mesh_buffer| mesh_buffer.references_count > Default::default());
+                if is_referenced {
+                    continue;
+                }
+
+                if let Some(mesh_buffer) =
+                    mesh_buffers_pool.remove_mesh_buffer(mesh_buffer_reference)
+                {
+                    mesh_buffer.get_buffer_references().into_iter().for_each(
+                        |buffer_reference| unsafe {
+                            buffers_pool.free_buffer(buffer_reference);
+                        },
+                    );
+                }
+            }
+        }
+    }
+}
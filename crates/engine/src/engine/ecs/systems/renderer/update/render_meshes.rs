@@ -1,40 +1,41 @@
-use bevy_ecs::{
-    entity::Entity,
-    hierarchy::ChildOf,
-    name::Name,
-    system::{Query, Res, ResMut},
+use bevy_ecs::system::{Res, ResMut};
+use vulkanite::vk::{
+    Bool32, ColorBlendEquationEXT, DeviceAddress, DeviceSize, DrawMeshTasksIndirectCommandEXT,
+    ShaderStageFlags, Viewport,
 };
-use vulkanite::vk::{Bool32, ColorBlendEquationEXT, ShaderStageFlags};
 
 use crate::engine::{
-    components::{material::MaterialType, mesh::Mesh},
+    components::material::MaterialType,
+    ecs::{InstanceObject, MAX_FOLIAGE_INSTANCES_COUNT, buffers_pool::BuffersPool},
     general::renderer::DescriptorSetHandle,
-    resources::{FrameContext, GraphicsPushConstant, RendererResources},
+    resources::{
+        FrameContext, FrameError, FrameErrors, GraphicsPushConstant, RendererContext,
+        RendererResources,
+    },
 };
 
 pub fn render_meshes_system(
-    graphics_entities: Query<&Mesh>,
-    entities: Query<(Entity, &Name)>,
-    entities_with_parent: Query<&ChildOf>,
-    mut renderer_resources: ResMut<RendererResources>,
+    renderer_resources: Res<RendererResources>,
     descriptor_set_handle: Res<DescriptorSetHandle>,
     frame_context: Res<FrameContext>,
+    render_context: Res<RendererContext>,
+    buffers_pool: Res<BuffersPool>,
+    mut frame_errors: ResMut<FrameErrors>,
 ) {
-    let command_buffer = frame_context.command_buffer.unwrap();
+    let Some(command_buffer) = frame_context.command_buffer else {
+        frame_errors.record(FrameError::missing_resource("get the command buffer"));
 
-    if !renderer_resources.is_printed_scene_hierarchy {
-        println!("=====================================");
+        return;
+    };
+    let resources_pool = &renderer_resources.resources_pool;
+    let (Some(instance_objects_buffer), Some(static_instance_objects_buffer)) = (
+        resources_pool.instances_buffer.as_ref(),
+        resources_pool.static_instances_buffer.as_ref(),
+    ) else {
+        frame_errors.record(FrameError::missing_resource("get the instances buffers"));
 
-        for (entity, name) in entities.iter() {
-            if let Ok(parent) = entities_with_parent.get(entity) {
-                println!("Entity: {} | Name: {} | Parent: {}", entity, name, parent.0);
-            } else {
-                println!("Entity: {} | Name: {}", entity, name);
-            }
-        }
-
-        println!("=====================================");
-    }
+        return;
+    };
 
     let color_blend_equation = [ColorBlendEquationEXT {
         src_color_blend_factor: vulkanite::vk::BlendFactor::One,
@@ -46,33 +47,129 @@ pub fn render_meshes_system(
     }];
     command_buffer.set_color_blend_equation_ext(Default::default(), &color_blend_equation);
 
-    let meshes_len = graphics_entities.iter().len();
-    for material_type in 0..2 {
-        let is_draw_transparent_materials =
-            material_type as u32 == MaterialType::Transparent as u32;
-        let blend_enables = [Bool32::from(is_draw_transparent_materials)];
-
-        command_buffer.set_depth_write_enable(!is_draw_transparent_materials);
+    let instances_batches = [
+        (
+            buffers_pool
+                .get_buffer_info(instance_objects_buffer.get_current_buffer())
+                .device_address,
+            instance_objects_buffer.len(),
+        ),
+        (
+            buffers_pool
+                .get_buffer_info(static_instance_objects_buffer.get_current_buffer())
+                .device_address,
+            static_instance_objects_buffer.len(),
+        ),
+    ];
 
-        command_buffer.set_color_blend_enable_ext(Default::default(), blend_enables.as_slice());
+    // NOTE: Scattered foliages are counted on the GPU, so they're drawn by the indirect arguments of the camera.
+    let frame_data = render_context.get_current_frame_data();
+    let foliage_instances_device_address = buffers_pool
+        .get_buffer_info(frame_data.foliage_instances_buffer_reference)
+        .device_address;
+    let foliage_draw_arguments_buffer = frame_context.is_foliage_scattered().then(|| unsafe {
+        buffers_pool
+            .get_buffer(frame_data.foliage_draw_arguments_buffer_reference)
+            .unwrap_unchecked()
+            .buffer
+    });
 
-        let push_constants = GraphicsPushConstant {
-            current_material_type: material_type as _,
-            ..Default::default()
+    // NOTE: Cameras share the draw image, each one is clipped to its own viewport.
+    for (camera_index, camera_frame_data) in frame_context.cameras.iter().enumerate() {
+        let camera_viewport = &camera_frame_data.viewport;
+        let viewports = Viewport {
+            x: camera_viewport.offset.x as _,
+            y: (camera_viewport.offset.y as u32 + camera_viewport.extent.height) as _,
+            width: camera_viewport.extent.width as _,
+            height: -(camera_viewport.extent.height as f32),
+            min_depth: 0.0,
+            max_depth: 1.0,
         };
+        command_buffer.set_viewport_with_count(&viewports);
+        command_buffer.set_scissor_with_count(camera_viewport);
+        command_buffer.set_front_face(camera_frame_data.front_face);
+
+        let (push_constant_offset, device_address) =
+            resources_pool.get_camera_push_constant(&buffers_pool, camera_index);
         command_buffer.push_constants(
             descriptor_set_handle.get_pipeline_layout(),
             ShaderStageFlags::Fragment
                 | ShaderStageFlags::TaskEXT
                 | ShaderStageFlags::MeshEXT
                 | ShaderStageFlags::Compute,
-            std::mem::offset_of!(GraphicsPushConstant, current_material_type) as _,
-            std::mem::size_of::<u32>() as _,
-            &push_constants.current_material_type as *const _ as _,
+            push_constant_offset,
+            std::mem::size_of::<DeviceAddress>() as _,
+            &device_address as *const _ as _,
         );
 
-        command_buffer.draw_mesh_tasks_ext(meshes_len as _, 1, 1);
-    }
+        for material_type in 0..2 {
+            let is_draw_transparent_materials =
+                material_type as u32 == MaterialType::Transparent as u32;
+            let blend_enables = [Bool32::from(is_draw_transparent_materials)];
+
+            command_buffer.set_depth_write_enable(!is_draw_transparent_materials);
+
+            command_buffer.set_color_blend_enable_ext(Default::default(), blend_enables.as_slice());
 
-    renderer_resources.is_printed_scene_hierarchy = true;
+            let push_constants = GraphicsPushConstant {
+                current_material_type: material_type as _,
+                ..Default::default()
+            };
+            command_buffer.push_constants(
+                descriptor_set_handle.get_pipeline_layout(),
+                ShaderStageFlags::Fragment
+                    | ShaderStageFlags::TaskEXT
+                    | ShaderStageFlags::MeshEXT
+                    | ShaderStageFlags::Compute,
+                std::mem::offset_of!(GraphicsPushConstant, current_material_type) as _,
+                std::mem::size_of::<u32>() as _,
+                &push_constants.current_material_type as *const _ as _,
+            );
+
+            for (device_address_instance_object, instances_count) in instances_batches {
+                if instances_count == Default::default() {
+                    continue;
+                }
+
+                command_buffer.push_constants(
+                    descriptor_set_handle.get_pipeline_layout(),
+                    ShaderStageFlags::Fragment
+                        | ShaderStageFlags::TaskEXT
+                        | ShaderStageFlags::MeshEXT
+                        | ShaderStageFlags::Compute,
+                    std::mem::offset_of!(GraphicsPushConstant, device_address_instance_object) as _,
+                    std::mem::size_of::<DeviceAddress>() as _,
+                    &device_address_instance_object as *const _ as _,
+                );
+
+                command_buffer.draw_mesh_tasks_ext(instances_count as _, 1, 1);
+            }
+
+            if let Some(foliage_draw_arguments_buffer) = foliage_draw_arguments_buffer {
+                let device_address_instance_object = foliage_instances_device_address
+                    + (camera_index
+                        * MAX_FOLIAGE_INSTANCES_COUNT
+                        * std::mem::size_of::<InstanceObject>())
+                        as DeviceAddress;
+                command_buffer.push_constants(
+                    descriptor_set_handle.get_pipeline_layout(),
+                    ShaderStageFlags::Fragment
+                        | ShaderStageFlags::TaskEXT
+                        | ShaderStageFlags::MeshEXT
+                        | ShaderStageFlags::Compute,
+                    std::mem::offset_of!(GraphicsPushConstant, device_address_instance_object) as _,
+                    std::mem::size_of::<DeviceAddress>() as _,
+                    &device_address_instance_object as *const _ as _,
+                );
+
+                let draw_arguments_size = std::mem::size_of::<DrawMeshTasksIndirectCommandEXT>();
+                command_buffer.draw_mesh_tasks_indirect_ext(
+                    foliage_draw_arguments_buffer,
+                    (camera_index * draw_arguments_size) as DeviceSize,
+                    1,
+                    draw_arguments_size as _,
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,34 @@
+use bevy_ecs::system::{Commands, Res, ResMut};
+
+use crate::engine::{
+    events::DescriptorBufferResizedEvent,
+    general::renderer::DescriptorSetHandle,
+    resources::{DeferredResource, DeletionQueue, buffers_pool::BuffersPool},
+};
+
+/// Descriptor buffers replaced by a larger one are released after the frames in flight.
+pub fn retire_descriptor_buffers_system(
+    mut commands: Commands,
+    mut descriptor_set_handle: ResMut<DescriptorSetHandle>,
+    mut deletion_queue: ResMut<DeletionQueue>,
+    buffers_pool: Res<BuffersPool>,
+) {
+    let retired_descriptor_buffer_references =
+        descriptor_set_handle.take_retired_descriptor_buffer_references();
+    if retired_descriptor_buffer_references.is_empty() {
+        return;
+    }
+
+    retired_descriptor_buffer_references
+        .into_iter()
+        .for_each(|buffer_reference| {
+            deletion_queue.push(DeferredResource::Buffer(buffer_reference));
+        });
+
+    commands.trigger(DescriptorBufferResizedEvent {
+        variable_descriptors_capacity: descriptor_set_handle.variable_descriptors_capacity,
+        device_address: descriptor_set_handle
+            .get_buffer_info(&buffers_pool)
+            .device_address,
+    });
+}
@@ -0,0 +1,108 @@
+use bevy_ecs::system::{Res, ResMut};
+use ktx2_rw::Ktx2Texture;
+use vulkanite::vk::BufferUsageFlags;
+
+use crate::engine::resources::{
+    ActiveCubemapCapture, Console, CubemapCapture, FrameError, FrameErrors, RendererContext,
+    VulkanContextResource,
+    buffers_pool::{BufferVisibility, BuffersPool},
+};
+
+/// Reads back the face copied by the last frame and starts the next capture, once the current one is written.
+pub fn update_cubemap_capture_system(
+    mut cubemap_capture: ResMut<CubemapCapture>,
+    mut console: ResMut<Console>,
+    vulkan_context: Res<VulkanContextResource>,
+    renderer_context: Res<RendererContext>,
+    mut buffers_pool: ResMut<BuffersPool>,
+    mut frame_errors: ResMut<FrameErrors>,
+) {
+    if let Some(active_capture) = cubemap_capture.get_active_capture_mut()
+        && active_capture.is_face_copied
+    {
+        // NOTE: Baking is offline, so the whole GPU is waited instead of the fence of the frame.
+        // Face is read back again by the next frame, if it can't be waited.
+        if let Err(status) = vulkan_context.device.wait_idle() {
+            frame_errors.record(FrameError::new("wait for the device idle", status));
+
+            return;
+        }
+
+        let face_size = active_capture.request.face_size as usize;
+        let face_bytes_count = face_size * face_size * CubemapCapture::TEXEL_SIZE;
+        let mapped_allocation_handler =
+            buffers_pool.map_allocation(active_capture.readback_buffer_reference);
+        let face = unsafe {
+            std::slice::from_raw_parts(mapped_allocation_handler.get_ptr(), face_bytes_count)
+        };
+        active_capture.faces.push(face.to_vec());
+        active_capture.face_index += 1;
+        active_capture.is_face_copied = false;
+    }
+
+    if cubemap_capture
+        .get_active_capture_mut()
+        .is_some_and(|active_capture| active_capture.face_index >= CubemapCapture::FACES_COUNT)
+    {
+        let active_capture = cubemap_capture.take_active_capture().unwrap();
+        unsafe {
+            buffers_pool.free_buffer(active_capture.readback_buffer_reference);
+        }
+
+        let path = active_capture.request.path.display();
+        match write_cubemap(&active_capture) {
+            Ok(()) => console.log(std::format!("Cubemap captured to {}", path)),
+            Err(error) => console.log(std::format!("Failed to write cubemap {}: {}", path, error)),
+        }
+    }
+
+    if !cubemap_capture.is_capturing()
+        && let Some(mut request) = cubemap_capture.take_request()
+    {
+        let render_extent = renderer_context.render_extent;
+        request.face_size = request
+            .face_size
+            .min(render_extent.width)
+            .min(render_extent.height);
+        let face_size = request.face_size as usize;
+
+        let readback_buffer_reference = buffers_pool.create_buffer(
+            face_size * face_size * CubemapCapture::TEXEL_SIZE,
+            BufferUsageFlags::TransferDst,
+            BufferVisibility::HostVisible,
+            None,
+            Some("Cubemap Capture Buffer".to_string()),
+        );
+        cubemap_capture.set_active_capture(ActiveCubemapCapture {
+            request,
+            face_index: Default::default(),
+            faces: Vec::with_capacity(CubemapCapture::FACES_COUNT),
+            readback_buffer_reference,
+            is_face_copied: false,
+        });
+    }
+}
+
+fn write_cubemap(active_capture: &ActiveCubemapCapture) -> Result<(), String> {
+    let face_size = active_capture.request.face_size;
+    let mut texture = Ktx2Texture::create(
+        face_size,
+        face_size,
+        1,
+        1,
+        CubemapCapture::FACES_COUNT as _,
+        1,
+        ktx2_rw::VkFormat::R16G16B16A16Sfloat,
+    )
+    .map_err(|error| std::format!("{:?}", error))?;
+
+    for (face_index, face) in active_capture.faces.iter().enumerate() {
+        texture
+            .set_image_data(0, 0, face_index as _, face)
+            .map_err(|error| std::format!("{:?}", error))?;
+    }
+
+    texture
+        .write_to_file(&active_capture.request.path)
+        .map_err(|error| std::format!("{:?}", error))
+}
@@ -0,0 +1,136 @@
+use bevy_ecs::{
+    query::With,
+    system::{Query, ResMut},
+};
+
+use math::Vec2;
+
+use crate::engine::{
+    components::camera::Camera,
+    general::renderer::GpuBackend,
+    resources::{
+        DepthPick, DepthPickResult, DepthPicking, RendererContext,
+        buffers_pool::{BufferReference, BuffersPool},
+    },
+};
+
+/// Reads results of the frame that used the same frame data, its fence is already waited,
+/// and submits the requested picks.
+pub fn update_depth_picks_system(
+    mut render_context: ResMut<RendererContext>,
+    mut depth_picking: ResMut<DepthPicking>,
+    mut buffers_pool: ResMut<BuffersPool>,
+    cameras_query: Query<(), With<Camera>>,
+) {
+    let frame_data = render_context.get_current_frame_data_mut();
+    for depth_pick_result in read_depth_pick_results(
+        buffers_pool.as_ref(),
+        frame_data.depth_picks_buffer_reference,
+        frame_data.depth_pick_positions.len(),
+    ) {
+        depth_picking.push_result(depth_pick_result);
+    }
+    frame_data.depth_pick_positions.clear();
+
+    // NOTE: Without a camera there is no depth to pick, so requests wait for one.
+    if cameras_query.is_empty() || !depth_picking.is_picking() {
+        return;
+    }
+
+    frame_data.depth_pick_positions = depth_picking.take_requested_positions();
+    write_depth_picks(
+        buffers_pool.as_mut(),
+        frame_data.depth_picks_buffer_reference,
+        &frame_data.depth_pick_positions,
+    );
+}
+
+pub(crate) fn read_depth_pick_results(
+    gpu_backend: &impl GpuBackend,
+    depth_picks_buffer_reference: BufferReference,
+    depth_picks_count: usize,
+) -> Vec<DepthPickResult> {
+    if depth_picks_count == Default::default() {
+        return Vec::new();
+    }
+
+    gpu_backend
+        .read_objects::<DepthPick>(depth_picks_buffer_reference, depth_picks_count)
+        .into_iter()
+        .map(|depth_pick| DepthPickResult {
+            screen_position: depth_pick.screen_position,
+            depth: depth_pick.depth,
+            world_position: (depth_pick.is_hit != Default::default())
+                .then_some(depth_pick.world_position),
+        })
+        .collect()
+}
+
+/// Picks are unresolved, the shader fills the rest of them.
+pub(crate) fn write_depth_picks(
+    gpu_backend: &mut impl GpuBackend,
+    depth_picks_buffer_reference: BufferReference,
+    screen_positions: &[Vec2],
+) {
+    let depth_picks: Vec<_> = screen_positions
+        .iter()
+        .map(|&screen_position| DepthPick {
+            screen_position,
+            ..Default::default()
+        })
+        .collect();
+    gpu_backend.write_objects(depth_picks_buffer_reference, &depth_picks);
+}
+
+#[cfg(test)]
+mod tests {
+    use math::{Vec2, Vec3};
+    use vulkanite::vk::BufferUsageFlags;
+
+    use super::{read_depth_pick_results, write_depth_picks};
+    use crate::engine::{
+        general::renderer::{GpuBackend, MockGpuBackend},
+        resources::{DepthPick, buffers_pool::BufferVisibility},
+    };
+
+    #[test]
+    fn resolved_picks_are_read_back_in_requested_order() {
+        let mut gpu_backend = MockGpuBackend::new();
+        let depth_picks_buffer_reference = gpu_backend.create_buffer(
+            std::mem::size_of::<DepthPick>() * 2,
+            BufferUsageFlags::StorageBuffer,
+            BufferVisibility::HostVisible,
+            None,
+        );
+        let screen_positions = [Vec2::new(10.0, 20.0), Vec2::new(30.0, 40.0)];
+        write_depth_picks(
+            &mut gpu_backend,
+            depth_picks_buffer_reference,
+            &screen_positions,
+        );
+
+        // NOTE: Stands for the shader, only the first pick hits the geometry.
+        let mut depth_picks: Vec<DepthPick> =
+            gpu_backend.read_objects(depth_picks_buffer_reference, screen_positions.len());
+        depth_picks[0].depth = 0.5;
+        depth_picks[0].is_hit = 1;
+        depth_picks[0].world_position = Vec3::new(1.0, 2.0, 3.0);
+        gpu_backend.write_objects(depth_picks_buffer_reference, &depth_picks);
+
+        let depth_pick_results = read_depth_pick_results(
+            &gpu_backend,
+            depth_picks_buffer_reference,
+            screen_positions.len(),
+        );
+
+        assert_eq!(depth_pick_results.len(), 2);
+        assert_eq!(depth_pick_results[0].screen_position, screen_positions[0]);
+        assert_eq!(depth_pick_results[0].depth, 0.5);
+        assert_eq!(
+            depth_pick_results[0].world_position,
+            Some(Vec3::new(1.0, 2.0, 3.0))
+        );
+        assert_eq!(depth_pick_results[1].screen_position, screen_positions[1]);
+        assert_eq!(depth_pick_results[1].world_position, None);
+    }
+}
@@ -0,0 +1,47 @@
+use bevy_ecs::system::{Res, ResMut};
+use vulkanite::vk::Extent2D;
+
+use crate::engine::{
+    general::renderer::{DepthPyramid, DepthReduction, DescriptorSetHandle},
+    resources::{
+        CullingSettings, RendererContext, buffers_pool::BuffersPool, textures_pool::TexturesPool,
+    },
+};
+
+/// Creates the pyramid of the current frame data with the extent of its depth image, or frees it
+/// once it's disabled. The fence of the frame that used the same frame data is already waited.
+pub fn update_depth_pyramid_system(
+    mut renderer_context: ResMut<RendererContext>,
+    culling_settings: Res<CullingSettings>,
+    mut textures_pool: ResMut<TexturesPool>,
+    mut buffers_pool: ResMut<BuffersPool>,
+    mut descriptor_set_handle: ResMut<DescriptorSetHandle>,
+) {
+    let frame_data = renderer_context.get_current_frame_data_mut();
+    let depth_texture_metadata = frame_data.depth_texture_reference.texture_metadata;
+    let extent = Extent2D {
+        width: depth_texture_metadata.width,
+        height: depth_texture_metadata.height,
+    };
+
+    // NOTE: Depth image is recreated with the swapchain, the pyramid follows its extent.
+    if let Some(depth_pyramid) = frame_data.depth_pyramid.take_if(|depth_pyramid| {
+        let pyramid_extent = depth_pyramid.get_extent();
+
+        !culling_settings.is_depth_pyramid_enabled
+            || pyramid_extent.width != extent.width
+            || pyramid_extent.height != extent.height
+    }) {
+        depth_pyramid.free(&mut textures_pool, &mut buffers_pool);
+    }
+
+    if culling_settings.is_depth_pyramid_enabled && frame_data.depth_pyramid.is_none() {
+        frame_data.depth_pyramid = Some(DepthPyramid::new(
+            &mut textures_pool,
+            &mut buffers_pool,
+            &mut descriptor_set_handle,
+            extent,
+            DepthReduction::Min,
+        ));
+    }
+}
@@ -0,0 +1,105 @@
+use bevy_ecs::system::{Res, ResMut};
+use image::{Rgba, RgbaImage};
+use vulkanite::vk::BufferUsageFlags;
+
+use crate::engine::resources::{
+    ActiveFrameCapture, FrameCapture, FrameError, FrameErrors, RendererContext,
+    VulkanContextResource,
+    buffers_pool::{BufferVisibility, BuffersPool},
+};
+
+/// Reads back the frame copied by the last frame and starts the requested capture.
+pub fn update_frame_capture_system(
+    mut frame_capture: ResMut<FrameCapture>,
+    vulkan_context: Res<VulkanContextResource>,
+    renderer_context: Res<RendererContext>,
+    mut buffers_pool: ResMut<BuffersPool>,
+    mut frame_errors: ResMut<FrameErrors>,
+) {
+    if frame_capture
+        .get_active_capture_mut()
+        .is_some_and(|active_capture| active_capture.is_frame_copied)
+    {
+        // NOTE: Captures are for tests, so the whole GPU is waited instead of the frame fence.
+        // Frame is read back again by the next frame, if it can't be waited.
+        if let Err(status) = vulkan_context.device.wait_idle() {
+            frame_errors.record(FrameError::new("wait for the device idle", status));
+
+            return;
+        }
+        let active_capture = frame_capture.take_active_capture().unwrap();
+
+        let texels_count = (active_capture.width * active_capture.height) as usize;
+        let mapped_allocation_handler =
+            buffers_pool.map_allocation(active_capture.readback_buffer_reference);
+        let texels = unsafe {
+            std::slice::from_raw_parts(
+                mapped_allocation_handler.get_ptr() as *const [u16; 4],
+                texels_count,
+            )
+        };
+        let captured_frame =
+            RgbaImage::from_fn(active_capture.width, active_capture.height, |x, y| {
+                let texel = texels[(y * active_capture.width + x) as usize];
+
+                Rgba([
+                    encode_srgb(decode_half(texel[0])),
+                    encode_srgb(decode_half(texel[1])),
+                    encode_srgb(decode_half(texel[2])),
+                    u8::MAX,
+                ])
+            });
+        drop(mapped_allocation_handler);
+        unsafe {
+            buffers_pool.free_buffer(active_capture.readback_buffer_reference);
+        }
+
+        frame_capture.set_captured_frame(captured_frame);
+    }
+
+    if frame_capture.get_active_capture_mut().is_none() && frame_capture.tick_request() {
+        let render_extent = renderer_context.render_extent;
+        let readback_buffer_reference = buffers_pool.create_buffer(
+            (render_extent.width * render_extent.height) as usize * FrameCapture::TEXEL_SIZE,
+            BufferUsageFlags::TransferDst,
+            BufferVisibility::HostVisible,
+            None,
+            Some("Frame Capture Buffer".to_string()),
+        );
+        frame_capture.set_active_capture(ActiveFrameCapture {
+            readback_buffer_reference,
+            width: render_extent.width,
+            height: render_extent.height,
+            is_frame_copied: false,
+        });
+    }
+}
+
+fn decode_half(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1F) as i32;
+    let mantissa = (bits & 0x3FF) as f32;
+
+    match exponent {
+        0 => sign * mantissa * 2.0f32.powi(-24),
+        0x1F if mantissa == 0.0 => sign * f32::INFINITY,
+        0x1F => f32::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2.0f32.powi(exponent - 15),
+    }
+}
+
+/// NaNs are black, so a broken shader is caught by the comparison.
+fn encode_srgb(linear: f32) -> u8 {
+    if linear.is_nan() {
+        return Default::default();
+    }
+
+    let linear = linear.clamp(0.0, 1.0);
+    let srgb = if linear <= 0.003_130_8 {
+        linear * 12.92
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    };
+
+    (srgb * u8::MAX as f32).round() as u8
+}
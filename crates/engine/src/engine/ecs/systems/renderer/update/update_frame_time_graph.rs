@@ -0,0 +1,25 @@
+use bevy_ecs::system::{Res, ResMut};
+
+use crate::engine::{
+    Time,
+    resources::{DebugOverlay, FrameTimeGraph, FrameTimeSample, GpuProfiler, LatencyStats},
+};
+
+/// Samples are taken while the graph is hidden as well, so it has the history once it's shown.
+pub fn update_frame_time_graph_system(
+    mut frame_time_graph: ResMut<FrameTimeGraph>,
+    mut debug_overlay: ResMut<DebugOverlay>,
+    time: Res<Time>,
+    latency_stats: Res<LatencyStats>,
+    gpu_profiler: Res<GpuProfiler>,
+) {
+    frame_time_graph.push_sample(FrameTimeSample {
+        frame_time: time.get_delta_time() * 1000.0,
+        cpu_time: latency_stats.cpu_time * 1000.0,
+        gpu_time: gpu_profiler.get_last_pass_times().frame_time,
+    });
+
+    if frame_time_graph.is_visible() {
+        frame_time_graph.draw(&mut debug_overlay);
+    }
+}
@@ -0,0 +1,234 @@
+use bevy_ecs::system::{Query, Res, ResMut};
+use math::{Mat4, Vec2, Vec3, Vec4};
+
+use crate::engine::{
+    components::{
+        camera::Camera,
+        local_transform::{GlobalTransform, LocalTransform},
+        point_light::PointLight,
+    },
+    resources::{DebugOverlay, EnvironmentSettings, Gizmos, RendererContext},
+};
+
+const CAMERA_GIZMO_COLOR: Vec4 = Vec4::new(1.0, 1.0, 1.0, 0.8);
+const SUN_GIZMO_COLOR: Vec4 = Vec4::new(1.0, 0.85, 0.2, 1.0);
+/// Frusta are cut after it past the near plane, so the far plane doesn't cover the whole scene.
+const MAX_FRUSTUM_GIZMO_LENGTH: f32 = 5.0;
+const LIGHT_GIZMO_SIZE: f32 = 0.25;
+/// Sun has no position, so its arrow is in front of the first camera.
+const SUN_GIZMO_DISTANCE: f32 = 4.0;
+
+/// Draws the scene gizmos and projects all the lines by the first camera into `DebugOverlay`.
+/// Frustum of the first camera isn't drawn, it's the one the lines are seen through.
+pub fn update_gizmos_system(
+    mut gizmos: ResMut<Gizmos>,
+    mut debug_overlay: ResMut<DebugOverlay>,
+    renderer_context: Res<RendererContext>,
+    environment_settings: Res<EnvironmentSettings>,
+    cameras_query: Query<(&Camera, &LocalTransform)>,
+    point_lights_query: Query<(&PointLight, &GlobalTransform)>,
+) {
+    let render_size = Vec2::new(
+        renderer_context.render_extent.width as _,
+        renderer_context.render_extent.height as _,
+    );
+    let mut cameras = Camera::sort_active(cameras_query.iter()).into_iter();
+    // NOTE: Without a camera lines can't be projected, so they're dropped as after a frame.
+    let Some((first_camera, first_camera_transform)) = cameras.next() else {
+        gizmos.take_lines();
+
+        return;
+    };
+
+    if gizmos.is_scene_gizmos_enabled {
+        for (camera, camera_transform) in cameras {
+            draw_camera_gizmo(&mut gizmos, camera, camera_transform, render_size);
+        }
+
+        for (point_light, global_transform) in point_lights_query.iter() {
+            let position = global_transform.transform_point(Vec3::ZERO);
+            let color =
+                (point_light.color / point_light.color.max_element().max(f32::EPSILON)).extend(1.0);
+            for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+                gizmos.draw_line(
+                    position - axis * LIGHT_GIZMO_SIZE,
+                    position + axis * LIGHT_GIZMO_SIZE,
+                    color,
+                );
+            }
+            gizmos.draw_sphere(
+                position,
+                point_light.radius,
+                color * Vec4::new(1.0, 1.0, 1.0, 0.5),
+            );
+        }
+
+        // NOTE: Arrow points where the light travels, it's opposite to the sun direction.
+        let sun_direction = environment_settings.sun_direction.normalize_or_zero();
+        let sun_position = first_camera_transform.get_local_position()
+            + first_camera_transform.get_local_rotation() * Vec3::NEG_Z * SUN_GIZMO_DISTANCE;
+        gizmos.draw_arrow(sun_position + sun_direction, sun_position, SUN_GIZMO_COLOR);
+    }
+
+    let lines = gizmos.take_lines();
+    let (viewport_start, viewport_size) = first_camera.get_viewport_bounds(render_size);
+    if lines.is_empty() || viewport_size.min_element() <= 0.0 {
+        return;
+    }
+
+    let view = Mat4::from_rotation_translation(
+        first_camera_transform.get_local_rotation(),
+        first_camera_transform.get_local_position(),
+    )
+    .inverse();
+    let view_projection = first_camera.get_projection(viewport_size.x / viewport_size.y) * view;
+    for line in lines {
+        let Some((start, end)) = project_line(
+            view_projection,
+            line.start,
+            line.end,
+            viewport_start,
+            viewport_size,
+        ) else {
+            continue;
+        };
+
+        draw_screen_line(&mut debug_overlay, start, end, line.color);
+    }
+}
+
+fn draw_camera_gizmo(
+    gizmos: &mut Gizmos,
+    camera: &Camera,
+    camera_transform: &LocalTransform,
+    render_size: Vec2,
+) {
+    let (_, viewport_size) = camera.get_viewport_bounds(render_size);
+    let aspect_ratio = viewport_size.x / viewport_size.y.max(1.0);
+    let near = camera.clipping_planes.near;
+    let far = camera
+        .clipping_planes
+        .far
+        .min(near + MAX_FRUSTUM_GIZMO_LENGTH);
+    let half_height_slope = (camera.fov.to_radians() * 0.5).tan();
+
+    let position = camera_transform.get_local_position();
+    let rotation = camera_transform.get_local_rotation();
+    let mut corners = [Vec3::ZERO; 8];
+    for (plane_index, distance) in [near, far].into_iter().enumerate() {
+        let half_height = half_height_slope * distance;
+        let half_width = half_height * aspect_ratio;
+        for (corner_index, (x, y)) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .into_iter()
+            .enumerate()
+        {
+            corners[plane_index * 4 + corner_index] =
+                position + rotation * Vec3::new(x * half_width, y * half_height, -distance);
+        }
+    }
+
+    gizmos.draw_box(corners, CAMERA_GIZMO_COLOR);
+    // NOTE: Up of the camera is marked, so a rolled camera is told apart.
+    gizmos.draw_line(
+        (corners[2] + corners[3]) * 0.5,
+        (corners[2] + corners[3]) * 0.5 + rotation * Vec3::Y * near.max(0.1),
+        CAMERA_GIZMO_COLOR,
+    );
+}
+
+/// Line in pixels of the render target, clipped by the near plane and the viewport.
+fn project_line(
+    view_projection: Mat4,
+    start: Vec3,
+    end: Vec3,
+    viewport_start: Vec2,
+    viewport_size: Vec2,
+) -> Option<(Vec2, Vec2)> {
+    const MIN_W: f32 = 1e-4;
+
+    let mut clip_start = view_projection * start.extend(1.0);
+    let mut clip_end = view_projection * end.extend(1.0);
+    if clip_start.w < MIN_W && clip_end.w < MIN_W {
+        return None;
+    }
+    if clip_start.w < MIN_W {
+        clip_start = clip_start.lerp(
+            clip_end,
+            (MIN_W - clip_start.w) / (clip_end.w - clip_start.w),
+        );
+    } else if clip_end.w < MIN_W {
+        clip_end = clip_end.lerp(
+            clip_start,
+            (MIN_W - clip_end.w) / (clip_start.w - clip_end.w),
+        );
+    }
+
+    // NOTE: Viewport is flipped, so the top of the image is +Y in NDC.
+    let to_screen = |clip_position: Vec4| {
+        let ndc = Vec2::new(clip_position.x, clip_position.y) / clip_position.w;
+
+        viewport_start + Vec2::new(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5) * viewport_size
+    };
+    let start = to_screen(clip_start);
+    let end = to_screen(clip_end);
+
+    // NOTE: Liang-Barsky clipping, so lines far out of the viewport aren't stepped through.
+    let delta = end - start;
+    let viewport_end = viewport_start + viewport_size - 1.0;
+    let mut t_range = (0.0f32, 1.0f32);
+    for (p, q) in [
+        (-delta.x, start.x - viewport_start.x),
+        (delta.x, viewport_end.x - start.x),
+        (-delta.y, start.y - viewport_start.y),
+        (delta.y, viewport_end.y - start.y),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+
+            continue;
+        }
+
+        let t = q / p;
+        if p < 0.0 {
+            t_range.0 = t_range.0.max(t);
+        } else {
+            t_range.1 = t_range.1.min(t);
+        }
+    }
+    if t_range.0 > t_range.1 {
+        return None;
+    }
+
+    Some((start + delta * t_range.0, start + delta * t_range.1))
+}
+
+/// Pixels of the line are merged into a rect per row or column, whichever is the minor axis.
+fn draw_screen_line(debug_overlay: &mut DebugOverlay, start: Vec2, end: Vec2, color: Vec4) {
+    let delta = end - start;
+    let is_horizontal = delta.x.abs() >= delta.y.abs();
+    let steps_count = delta.abs().max_element().ceil().max(1.0) as usize;
+
+    let mut run: Option<(Vec2, Vec2)> = None;
+    for step in 0..=steps_count {
+        let pixel = (start + delta * (step as f32 / steps_count as f32)).floor();
+        match run.as_mut() {
+            Some((run_min, run_max))
+                if (is_horizontal && run_min.y == pixel.y)
+                    || (!is_horizontal && run_min.x == pixel.x) =>
+            {
+                *run_min = run_min.min(pixel);
+                *run_max = run_max.max(pixel);
+            }
+            _ => {
+                if let Some((run_min, run_max)) = run.replace((pixel, pixel)) {
+                    debug_overlay.draw_rect(run_min, run_max - run_min + 1.0, color);
+                }
+            }
+        }
+    }
+    if let Some((run_min, run_max)) = run {
+        debug_overlay.draw_rect(run_min, run_max - run_min + 1.0, color);
+    }
+}
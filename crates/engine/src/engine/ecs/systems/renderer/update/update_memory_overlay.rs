@@ -0,0 +1,173 @@
+use ahash::AHashMap;
+use bevy_ecs::system::{Res, ResMut};
+use shared::BufferKey;
+use vma::Allocation;
+use vulkanite::vk::{ImageUsageFlags, MemoryPropertyFlags};
+
+use crate::engine::resources::{
+    Console, DebugOverlay, DevicePropertiesResource, MemoryAllocation, MemoryOverlay, MemoryPool,
+    MemoryReport, RendererResources, TextureUsage, VulkanContextResource,
+    buffers_pool::BuffersPool, mesh_buffers_pool::MeshBuffersPool, textures_pool::TexturesPool,
+    transient_resources_pool::TransientResourcesPool,
+};
+
+/// Gathers the report from the allocations of the pools, it's throttled while the overlay is visible.
+pub fn update_memory_overlay_system(
+    mut memory_overlay: ResMut<MemoryOverlay>,
+    mut debug_overlay: ResMut<DebugOverlay>,
+    mut console: ResMut<Console>,
+    vulkan_context: Res<VulkanContextResource>,
+    device_properties: Res<DevicePropertiesResource>,
+    renderer_resources: Res<RendererResources>,
+    buffers_pool: Res<BuffersPool>,
+    textures_pool: Res<TexturesPool>,
+    mesh_buffers_pool: Res<MeshBuffersPool>,
+    texture_usage: Res<TextureUsage>,
+    transient_resources_pool: Res<TransientResourcesPool>,
+) {
+    if memory_overlay.begin_update() {
+        let mut buffer_pools: AHashMap<BufferKey, MemoryPool> = mesh_buffers_pool
+            .get_mesh_buffers()
+            .flat_map(|(_, mesh_buffer)| mesh_buffer.get_buffer_references())
+            .map(|buffer_reference| (buffer_reference.get_key(), MemoryPool::MeshBuffers))
+            .collect();
+        buffer_pools.insert(
+            renderer_resources.materials_data_buffer_reference.get_key(),
+            MemoryPool::Materials,
+        );
+        buffer_pools.insert(
+            buffers_pool.get_staging_buffer_reference().get_key(),
+            MemoryPool::Staging,
+        );
+        buffer_pools.extend(
+            transient_resources_pool
+                .get_buffer_keys()
+                .map(|buffer_key| (buffer_key, MemoryPool::Transient)),
+        );
+
+        let buffers = buffers_pool
+            .get_buffers()
+            .map(|(buffer_reference, allocated_buffer)| {
+                let pool = buffer_pools
+                    .get(&buffer_reference.get_key())
+                    .copied()
+                    .unwrap_or(MemoryPool::OtherBuffers);
+                let name = allocated_buffer
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| std::format!("Buffer {}", buffer_reference.get_index()));
+
+                (name, pool, allocated_buffer.allocation, None)
+            });
+        let textures = textures_pool
+            .get_textures()
+            .map(|(texture_reference, allocated_image)| {
+                let name = TexturesPool::get_texture_name(texture_reference, allocated_image);
+                // NOTE: Transient images are named by the pass that acquired them last.
+                if let Some(transient_name) =
+                    transient_resources_pool.get_texture_name(texture_reference)
+                {
+                    let name = std::format!("{} ({})", transient_name, name);
+
+                    return (
+                        name,
+                        MemoryPool::Transient,
+                        allocated_image.allocation,
+                        None,
+                    );
+                }
+
+                let pool = if allocated_image.usage_flags.intersects(
+                    ImageUsageFlags::ColorAttachment
+                        | ImageUsageFlags::DepthStencilAttachment
+                        | ImageUsageFlags::Storage,
+                ) {
+                    MemoryPool::RenderTargets
+                } else {
+                    MemoryPool::Textures
+                };
+                let samples_count = (pool == MemoryPool::Textures
+                    && texture_usage.get_sampled_frames_count() > Default::default())
+                .then(|| texture_usage.get_samples_count(texture_reference));
+
+                (name, pool, allocated_image.allocation, samples_count)
+            });
+
+        let mut report = gather_memory_report(
+            &vulkan_context,
+            &device_properties,
+            buffers.chain(textures),
+            memory_overlay.largest_allocations_count,
+        );
+        report.texture_usage_frames_count = texture_usage.get_sampled_frames_count();
+        if memory_overlay.set_report(report) {
+            for line in memory_overlay.get_report().get_lines() {
+                console.log(line);
+            }
+        }
+    }
+
+    if memory_overlay.is_visible() {
+        memory_overlay.draw(&mut debug_overlay);
+    }
+}
+
+fn gather_memory_report(
+    vulkan_context: &VulkanContextResource,
+    device_properties: &DevicePropertiesResource,
+    allocations: impl Iterator<Item = (String, MemoryPool, Allocation, Option<u64>)>,
+    largest_allocations_count: usize,
+) -> MemoryReport {
+    let mut report = MemoryReport::default();
+
+    let mut memory_allocations = Vec::new();
+    for (name, pool, allocation, samples_count) in allocations {
+        let allocation_info = vulkan_context.allocator.get_allocation_info(allocation);
+        let is_device_local = !device_properties
+            .memory_type_flags
+            .get(allocation_info.memory_type as usize)
+            .is_some_and(|memory_type_flags| {
+                memory_type_flags.contains(MemoryPropertyFlags::HostVisible)
+            });
+
+        if is_device_local {
+            report.device_local_bytes_count += allocation_info.size;
+        } else {
+            report.host_visible_bytes_count += allocation_info.size;
+        }
+        report.pools_bytes_counts[pool as usize] += allocation_info.size;
+        if samples_count == Some(Default::default()) {
+            report.unsampled_textures_count += 1;
+            report.unsampled_texture_bytes_count += allocation_info.size;
+        }
+
+        memory_allocations.push(MemoryAllocation {
+            name,
+            pool,
+            size: allocation_info.size,
+            is_device_local,
+            samples_count,
+        });
+    }
+
+    // NOTE: Only the largest ones are kept, so the rest isn't sorted.
+    if memory_allocations.len() > largest_allocations_count && largest_allocations_count > 0 {
+        memory_allocations
+            .select_nth_unstable_by(largest_allocations_count - 1, |a, b| b.size.cmp(&a.size));
+    }
+    memory_allocations.truncate(largest_allocations_count);
+    memory_allocations.sort_unstable_by(|a, b| b.size.cmp(&a.size));
+    report.largest_allocations = memory_allocations;
+
+    match vulkan_context.allocator.calculate_statistics() {
+        Ok(total_statistics) => {
+            let statistics = total_statistics.total.statistics;
+            report.allocations_count = statistics.allocationCount;
+            report.allocation_bytes_count = statistics.allocationBytes;
+            report.block_bytes_count = statistics.blockBytes;
+        }
+        Err(error) => println!("Failed to calculate VMA statistics: {:?}", error),
+    }
+
+    report
+}
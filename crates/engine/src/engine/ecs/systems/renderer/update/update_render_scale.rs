@@ -0,0 +1,33 @@
+use bevy_ecs::system::{Res, ResMut};
+use vulkanite::vk::Extent2D;
+
+use crate::engine::{
+    ecs::textures_pool::TexturesPool,
+    resources::{GpuProfiler, RenderScaleSettings, RendererContext},
+};
+
+pub fn update_render_scale_system(
+    mut render_context: ResMut<RendererContext>,
+    mut render_scale_settings: ResMut<RenderScaleSettings>,
+    gpu_profiler: Res<GpuProfiler>,
+    textures_pool: Res<TexturesPool>,
+) {
+    render_scale_settings.update_dynamic_resolution(gpu_profiler.get_last_pass_times().frame_time);
+
+    let draw_image = textures_pool
+        .get_image(
+            render_context
+                .get_current_frame_data()
+                .draw_texture_reference,
+        )
+        .unwrap();
+
+    let render_extent = RenderScaleSettings::get_scaled_extent(
+        render_context.draw_extent,
+        render_scale_settings.get_current_render_scale(),
+    );
+    render_context.render_extent = Extent2D {
+        width: render_extent.width.min(draw_image.extent.width),
+        height: render_extent.height.min(draw_image.extent.height),
+    };
+}
@@ -1,102 +1,664 @@
 use bevy_ecs::system::{Query, Res, ResMut};
-use bytemuck::Pod;
-use math::{Mat4, Vec3, Vec4};
+use math::{Mat4, Quat, Vec2, Vec3, Vec4};
+use vulkanite::vk::{
+    DeviceAddress, DrawMeshTasksIndirectCommandEXT, Extent2D, FrontFace, Offset2D, Rect2D,
+};
 
+#[cfg(feature = "capture")]
+use crate::engine::resources::CubemapCapture;
+#[cfg(feature = "debug-tools")]
+use crate::engine::resources::DebugOverlay;
 use crate::engine::{
-    LocalTransform,
-    components::camera::Camera,
+    LocalTransform, Time,
+    components::{
+        camera::{Camera, CameraViewport},
+        camera_shake::CameraShake,
+        local_transform::GlobalTransform,
+        point_light::PointLight,
+        sun_occlusion::SunOcclusion,
+        water::WaterSettings,
+    },
+    general::renderer::{DepthPyramid, DescriptorSetHandle},
     resources::{
-        DirectionalLight, LightProperties, RendererContext, RendererResources, SceneData,
-        SwappableBuffer, buffers_pool::BuffersPool, frame_context,
+        CullingSettings, DebugOverlayData, DepthOfFieldData, DepthPicksData, DirectionalLight,
+        EnvironmentData, EnvironmentKind, EnvironmentMode, EnvironmentSettings, FogData, FogKind,
+        FogMode, FoliageInstancesData, InstanceObject, LIGHT_CLUSTER_SIZE, LIGHT_CLUSTERS_COUNT,
+        LensFlare, LensFlareData, LensFlareSpriteData, LightClustersData, LightProperties,
+        LightShaftsData, MAX_FOLIAGE_INSTANCES_COUNT, OutlineSettings, OutlineStyle, OutlinesData,
+        PointLightData, PostProcessData, PostProcessSettings, RendererContext, RendererResources,
+        SceneData, ScreenSpaceReflectionsData, TextureUsageData, VisibilityQueriesData, WaterData,
+        buffers_pool::BuffersPool,
+        frame_context::{self, CameraFrameData},
     },
 };
 
 pub fn update_resources_system(
     render_context: Res<RendererContext>,
     mut renderer_resources: ResMut<RendererResources>,
-    buffers: ResMut<BuffersPool>,
+    mut buffers: ResMut<BuffersPool>,
     mut frame_context: ResMut<frame_context::FrameContext>,
-    transform_camera_query: Query<(&Camera, &LocalTransform)>,
+    environment_settings: Res<EnvironmentSettings>,
+    post_process_settings: Res<PostProcessSettings>,
+    culling_settings: Res<CullingSettings>,
+    #[cfg(feature = "debug-tools")] mut debug_overlay: ResMut<DebugOverlay>,
+    #[cfg(feature = "capture")] cubemap_capture: Res<CubemapCapture>,
+    time: Res<Time>,
+    transform_camera_query: Query<(&Camera, &LocalTransform, Option<&CameraShake>)>,
+    point_lights_query: Query<(&PointLight, &GlobalTransform)>,
+    water_settings_query: Query<(&WaterSettings, &GlobalTransform)>,
+    sun_occlusions_query: Query<&SunOcclusion>,
+    outline_settings: Res<OutlineSettings>,
 ) {
     let instances_objects_buffer = unsafe {
         renderer_resources
             .resources_pool
             .instances_buffer
-            .as_ref()
+            .as_mut()
             .unwrap_unchecked()
     };
 
-    update_buffer_data(instances_objects_buffer, &buffers);
+    instances_objects_buffer.flush(buffers.as_mut());
 
-    // TODO: Graceful fallback to black screen, if no cameras on a scene.
-    if let Ok((camera, transform)) = transform_camera_query.single() {
-        let camera_position = transform.get_local_position();
-        let view = Mat4::from_scale_rotation_translation(
-            Vec3::ONE,
-            transform.get_local_rotation(),
-            camera_position,
-        )
-        .inverse();
-
-        let projection = Mat4::perspective_rh(
-            camera.fov.to_radians(),
-            render_context.draw_extent.width as f32 / render_context.draw_extent.height as f32,
-            camera.clipping_planes.far,
-            camera.clipping_planes.near,
-        );
+    let static_instances_objects_buffer = unsafe {
+        renderer_resources
+            .resources_pool
+            .static_instances_buffer
+            .as_mut()
+            .unwrap_unchecked()
+    };
 
-        frame_context.world_matrix = projection * view;
+    static_instances_objects_buffer.flush(buffers.as_mut());
 
-        let scene_data_buffer = unsafe {
+    let foliages_buffer = unsafe {
+        renderer_resources
+            .resources_pool
+            .foliages_buffer
+            .as_ref()
+            .unwrap_unchecked()
+    };
+    foliages_buffer.flush(buffers.as_mut());
+    let foliages_count = foliages_buffer.len() as u32;
+    let device_address_foliages = buffers
+        .get_buffer_info(foliages_buffer.get_current_buffer())
+        .device_address;
+
+    let point_lights_buffer = unsafe {
+        renderer_resources
+            .resources_pool
+            .point_lights_buffer
+            .as_mut()
+            .unwrap_unchecked()
+    };
+    point_lights_buffer.clear();
+    for (point_light, global_transform) in point_lights_query
+        .iter()
+        .take(point_lights_buffer.get_capacity())
+    {
+        point_lights_buffer.push(PointLightData {
+            position: global_transform.transform_point(Vec3::ZERO),
+            radius: point_light.radius,
+            color: point_light.color,
+            intensity: point_light.intensity,
+        });
+    }
+    point_lights_buffer.flush(buffers.as_mut());
+    let point_lights_count = point_lights_buffer.len() as u32;
+    let device_address_point_lights = buffers
+        .get_buffer_info(point_lights_buffer.get_current_buffer())
+        .device_address;
+
+    // NOTE: Frame context is filled later by begin rendering, so textures are taken from the frame.
+    let frame_data = render_context.get_current_frame_data();
+    let device_address_light_clusters = buffers
+        .get_buffer_info(frame_data.light_clusters_buffer_reference)
+        .device_address;
+    let device_address_foliage_instances = buffers
+        .get_buffer_info(frame_data.foliage_instances_buffer_reference)
+        .device_address;
+    let device_address_foliage_draw_arguments = buffers
+        .get_buffer_info(frame_data.foliage_draw_arguments_buffer_reference)
+        .device_address;
+
+    let visibility_query_points_buffer = unsafe {
+        renderer_resources
+            .resources_pool
+            .visibility_query_points_buffer
+            .as_ref()
+            .unwrap_unchecked()
+    };
+    #[cfg(feature = "debug-tools")]
+    let mut debug_overlay_data = {
+        let debug_overlay_rects_buffer = unsafe {
             renderer_resources
                 .resources_pool
-                .scene_data_buffer
+                .debug_overlay_rects_buffer
                 .as_mut()
                 .unwrap_unchecked()
         };
+        debug_overlay_rects_buffer.clear();
+        let render_size = Vec2::new(
+            render_context.render_extent.width as _,
+            render_context.render_extent.height as _,
+        );
+        let mut debug_overlay_bounds_min = render_size;
+        let mut debug_overlay_bounds_max = Vec2::ZERO;
+        for debug_overlay_rect in debug_overlay
+            .take_rects()
+            .into_iter()
+            .take(debug_overlay_rects_buffer.get_capacity())
+        {
+            debug_overlay_bounds_min = debug_overlay_bounds_min.min(debug_overlay_rect.position);
+            debug_overlay_bounds_max =
+                debug_overlay_bounds_max.max(debug_overlay_rect.position + debug_overlay_rect.size);
+            debug_overlay_rects_buffer.push(debug_overlay_rect);
+        }
+        debug_overlay_rects_buffer.flush(buffers.as_mut());
+        let debug_overlay_bounds_min = debug_overlay_bounds_min.max(Vec2::ZERO).floor();
+        let debug_overlay_bounds_max = debug_overlay_bounds_max.min(render_size).ceil();
+        frame_context.debug_overlay_bounds = (!debug_overlay_rects_buffer.is_empty()
+            && debug_overlay_bounds_min
+                .cmplt(debug_overlay_bounds_max)
+                .all())
+        .then(|| Rect2D {
+            offset: Offset2D {
+                x: debug_overlay_bounds_min.x as _,
+                y: debug_overlay_bounds_min.y as _,
+            },
+            extent: Extent2D {
+                width: (debug_overlay_bounds_max.x - debug_overlay_bounds_min.x) as _,
+                height: (debug_overlay_bounds_max.y - debug_overlay_bounds_min.y) as _,
+            },
+        });
+
+        DebugOverlayData {
+            device_address_rects: buffers
+                .get_buffer_info(debug_overlay_rects_buffer.get_current_buffer())
+                .device_address,
+            rects_count: debug_overlay_rects_buffer.len() as _,
+            bounds_offset: [
+                debug_overlay_bounds_min.x as _,
+                debug_overlay_bounds_min.y as _,
+            ],
+            ..Default::default()
+        }
+    };
+    #[cfg(not(feature = "debug-tools"))]
+    let mut debug_overlay_data = DebugOverlayData::default();
+
+    let visibility_queries_data = VisibilityQueriesData {
+        device_address_points: buffers
+            .get_buffer_info(visibility_query_points_buffer.get_current_buffer())
+            .device_address,
+        device_address_results: buffers
+            .get_buffer_info(frame_data.visibility_query_results_buffer_reference)
+            .device_address,
+        points_count: visibility_query_points_buffer.len() as _,
+        ..Default::default()
+    };
+
+    let depth_picks_data = DepthPicksData {
+        device_address_picks: buffers
+            .get_buffer_info(frame_data.depth_picks_buffer_reference)
+            .device_address,
+        picks_count: frame_data.depth_pick_positions.len() as _,
+        ..Default::default()
+    };
+
+    let mut texture_usage_data = TextureUsageData::default();
+    if frame_data.is_texture_usage_counted {
+        texture_usage_data.device_address_counters = buffers
+            .get_buffer_info(frame_data.texture_usage_counters_buffer_reference)
+            .device_address;
+        texture_usage_data.counters_count = DescriptorSetHandle::MAX_SAMPLED_IMAGES_COUNT;
+    }
+
+    let skybox_sampler_index = renderer_resources.default_sampler_reference.get_index();
+
+    let mut water_data = WaterData::default();
+    if let Some((water_settings, global_transform)) = water_settings_query.iter().next() {
+        let water_position = global_transform.transform_point(Vec3::ZERO);
+        let normal_map_texture_reference = water_settings.get_normal_map_texture_reference();
+
+        water_data = WaterData {
+            color: water_settings.color,
+            height: water_position.y,
+            center: Vec2::new(water_position.x, water_position.z),
+            half_size: water_settings.size * 0.5,
+            scroll_velocity_0: water_settings.scroll_velocities[0],
+            scroll_velocity_1: water_settings.scroll_velocities[1],
+            normal_map_index: normal_map_texture_reference
+                .map(|texture_reference| texture_reference.get_index())
+                .unwrap_or_default(),
+            normal_sampler_index: renderer_resources.default_sampler_reference.get_index(),
+            normal_scale: water_settings.normal_scale.max(f32::EPSILON),
+            normal_strength: water_settings.normal_strength,
+            visibility_distance: water_settings.visibility_distance.max(f32::EPSILON),
+            refraction_distortion: water_settings.refraction_distortion,
+            reflection_intensity: water_settings.reflection_intensity,
+            time: time.get_elapsed_time(),
+            has_normal_map: normal_map_texture_reference.is_some() as _,
+            is_enabled: true as _,
+            ..Default::default()
+        };
+    }
+    frame_context.is_water_enabled = water_data.is_enabled != Default::default();
+
+    let mut outlines_data = OutlinesData::default();
+    if frame_context.is_outlined {
+        outlines_data = OutlinesData {
+            device_address_mask: buffers
+                .get_buffer_info(frame_context.outline_mask_buffer_reference)
+                .device_address,
+            mask_stride: frame_data.draw_texture_reference.texture_metadata.width,
+            selected_thickness: outline_settings
+                .selected
+                .thickness
+                .min(OutlineStyle::MAX_THICKNESS),
+            selected_color: outline_settings.selected.color,
+            highlighted_thickness: outline_settings
+                .highlighted
+                .thickness
+                .min(OutlineStyle::MAX_THICKNESS),
+            highlighted_color: outline_settings.highlighted.color,
+            ..Default::default()
+        };
+    }
+    let device_address_exposure_state = buffers
+        .get_buffer_info(renderer_resources.exposure_state_buffer_reference)
+        .device_address;
+    let device_address_buffer_addresses = buffers
+        .get_buffer_info(buffers.get_buffer_addresses_reference())
+        .device_address;
+
+    let scene_data_buffer = unsafe {
+        renderer_resources
+            .resources_pool
+            .scene_data_buffer
+            .as_mut()
+            .unwrap_unchecked()
+    };
+    scene_data_buffer.clear();
+    frame_context.cameras.clear();
+    let mut background_clear_colors = Vec::with_capacity(Camera::MAX_ACTIVE_CAMERAS_COUNT);
+
+    let sun_direction = environment_settings.sun_direction.normalize_or_zero();
+    let sun_visibility = sun_occlusions_query
+        .iter()
+        .next()
+        .map(|sun_occlusion| sun_occlusion.get_visibility())
+        .unwrap_or_default();
+    let mut lens_flare_data = LensFlareData::default();
+
+    let render_extent = render_context.render_extent;
+    // NOTE: While a cubemap is captured, only its face is rendered into the top left corner.
+    #[cfg(feature = "capture")]
+    let face_camera = cubemap_capture.get_face_camera();
+    #[cfg(not(feature = "capture"))]
+    let face_camera: Option<(Camera, LocalTransform, u32)> = None;
+    let cameras = match face_camera.as_ref() {
+        Some((camera, transform, _)) => vec![(camera, (transform, None))],
+        None => Camera::sort_active(
+            transform_camera_query
+                .iter()
+                .map(|(camera, transform, camera_shake)| (camera, (transform, camera_shake))),
+        ),
+    };
+    // NOTE: Every camera gets its own scene data entry, its index is the camera index in the frame.
+    for (camera, (transform, camera_shake)) in cameras {
+        let camera_viewport = match face_camera.as_ref() {
+            Some(&(_, _, face_size)) => Rect2D {
+                offset: Default::default(),
+                extent: Extent2D {
+                    width: face_size.min(render_extent.width),
+                    height: face_size.min(render_extent.height),
+                },
+            },
+            None => get_camera_viewport(camera.viewport, render_extent),
+        };
+        if camera_viewport.extent.width == Default::default()
+            || camera_viewport.extent.height == Default::default()
+        {
+            continue;
+        }
+
+        // NOTE: Shake is in the camera space, it moves only the view, not the transform.
+        let (shake_translation, shake_rotation) = camera_shake
+            .map(|camera_shake| camera_shake.get_offset())
+            .unwrap_or((Vec3::ZERO, Quat::IDENTITY));
+        let camera_rotation = transform.get_local_rotation() * shake_rotation;
+        let mut camera_position =
+            transform.get_local_position() + transform.get_local_rotation() * shake_translation;
+        let mut view =
+            Mat4::from_scale_rotation_translation(Vec3::ONE, camera_rotation, camera_position)
+                .inverse();
+
+        let mut projection = camera.get_projection(
+            camera_viewport.extent.width as f32 / camera_viewport.extent.height as f32,
+        );
+
+        let mut camera_forward = camera_rotation * Vec3::NEG_Z;
+        let mut front_face = FrontFace::CounterClockwise;
+        if let Some(reflection_plane) = camera.reflection_plane {
+            let reflection_matrix = reflection_plane.get_reflection_matrix();
+            view *= reflection_matrix;
+            projection = reflection_plane.get_oblique_projection(projection, view);
+            camera_position = reflection_matrix.transform_point3(camera_position);
+            camera_forward = reflection_matrix.transform_vector3(camera_forward);
+            front_face = FrontFace::Clockwise;
+        }
+
+        let world_matrix = projection * view;
+
+        // NOTE: Sun is at infinity, so only its direction is projected. Behind the camera it's skipped.
+        let sun_clip_position = world_matrix * sun_direction.extend(0.0);
+        let sun_ndc = (sun_clip_position.w > 0.0)
+            .then(|| Vec2::new(sun_clip_position.x, sun_clip_position.y) / sun_clip_position.w);
+
+        let mut light_shafts_data = LightShaftsData::default();
+        if let (Some(light_shafts), Some(sun_ndc)) = (post_process_settings.light_shafts, sun_ndc) {
+            light_shafts_data.sun_uv = Vec2::new(sun_ndc.x * 0.5 + 0.5, 0.5 - sun_ndc.y * 0.5);
+            light_shafts_data.samples_count = light_shafts.samples_count.max(1);
+            light_shafts_data.density = light_shafts.density;
+            light_shafts_data.decay = light_shafts.decay;
+            light_shafts_data.intensity = light_shafts.intensity.max(0.0);
+        }
+        let is_light_shafts_enabled = light_shafts_data.intensity > 0.0;
+
+        // NOTE: Flare follows the first camera, it's the one the sun occlusion is queried for.
+        if scene_data_buffer.is_empty()
+            && let (Some(lens_flare), Some(sun_ndc)) =
+                (post_process_settings.lens_flare.as_ref(), sun_ndc)
+        {
+            lens_flare_data = get_lens_flare_data(
+                lens_flare,
+                sun_ndc,
+                camera_viewport,
+                sun_visibility,
+                skybox_sampler_index,
+            );
+        }
+
+        let mut environment_data = EnvironmentData {
+            inverse_camera_view_matrix: world_matrix.inverse().to_cols_array(),
+            skybox_sampler_index,
+            render_width: render_extent.width,
+            render_height: render_extent.height,
+            viewport_x: camera_viewport.offset.x as _,
+            viewport_y: camera_viewport.offset.y as _,
+            viewport_width: camera_viewport.extent.width,
+            viewport_height: camera_viewport.extent.height,
+            depth_image_index: frame_data.depth_texture_reference.get_index(),
+            depth_pyramid_image_index: frame_data
+                .depth_pyramid
+                .as_ref()
+                .map(DepthPyramid::get_index)
+                .unwrap_or_default(),
+            depth_pyramid_mip_levels_count: frame_data
+                .depth_pyramid
+                .as_ref()
+                .map(DepthPyramid::get_mip_levels_count)
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+        let environment_mode = camera
+            .clear_behavior
+            .get_environment_mode(environment_settings.mode);
+        match environment_mode {
+            // NOTE: Solid color is a flat gradient, unless the attachment is cleared with it.
+            Some(EnvironmentMode::SolidColor(color)) => {
+                environment_data.environment_kind = EnvironmentKind::Gradient as _;
+                environment_data.top_color = color;
+                environment_data.bottom_color = color;
+            }
+            Some(EnvironmentMode::Gradient {
+                top_color,
+                bottom_color,
+            }) => {
+                environment_data.environment_kind = EnvironmentKind::Gradient as _;
+                environment_data.top_color = top_color;
+                environment_data.bottom_color = bottom_color;
+            }
+            Some(EnvironmentMode::Skybox(texture_reference)) => {
+                environment_data.environment_kind = EnvironmentKind::Skybox as _;
+                environment_data.skybox_texture_index = texture_reference.get_index();
+            }
+            None => (),
+        }
+
+        let post_effects = post_process_settings.effects;
+        let mut post_process_data = PostProcessData {
+            device_address_exposure_state,
+            exposure: post_process_settings.exposure,
+            post_image_index: frame_context.post_texture_reference.get_index(),
+            vignette_strength: post_effects.vignette_strength,
+            vignette_radius: post_effects.vignette_radius,
+            vignette_smoothness: post_effects.vignette_smoothness.max(f32::EPSILON),
+            chromatic_aberration_offset: post_effects.chromatic_aberration_offset,
+            grain_strength: post_effects.grain_strength,
+            grain_size: post_effects.grain_size.max(1.0),
+            grain_seed: time.get_frame_index() as _,
+            is_post_effects_enabled: post_effects.is_enabled() as _,
+            ..Default::default()
+        };
+        if let Some(auto_exposure) = post_process_settings.auto_exposure {
+            post_process_data.is_auto_exposure_enabled = true as _;
+            post_process_data.min_log_luminance = auto_exposure.min_log_luminance;
+            post_process_data.log_luminance_range =
+                auto_exposure.max_log_luminance - auto_exposure.min_log_luminance;
+            post_process_data.adaptation_factor =
+                1.0 - f32::exp(-time.get_delta_time() * auto_exposure.adaptation_speed);
+        }
+
+        let mut fog_data = FogData::default();
+        if !camera.is_fog_disabled {
+            if let Some(fog) = environment_settings.fog {
+                fog_data.fog_kind = match fog.mode {
+                    FogMode::Linear => FogKind::Linear,
+                    FogMode::Exponential => FogKind::Exponential,
+                    FogMode::ExponentialSquared => FogKind::ExponentialSquared,
+                } as _;
+                fog_data.color = fog.color;
+                fog_data.start = fog.start;
+                fog_data.end = fog.end.max(fog.start + f32::EPSILON);
+                fog_data.density = fog.density;
+            }
+            if let Some(atmospheric_scattering) = environment_settings.atmospheric_scattering {
+                fog_data.is_scattering_enabled = true as _;
+                fog_data.planet_center = atmospheric_scattering.planet_center;
+                fog_data.planet_radius = atmospheric_scattering.planet_radius;
+                fog_data.atmosphere_radius = atmospheric_scattering
+                    .atmosphere_radius
+                    .max(atmospheric_scattering.planet_radius + f32::EPSILON);
+                fog_data.rayleigh_coefficients = atmospheric_scattering.rayleigh_coefficients;
+                fog_data.mie_coefficient = atmospheric_scattering.mie_coefficient;
+                fog_data.sun_intensity = atmospheric_scattering.sun_intensity;
+            }
+        }
+
+        let mut depth_of_field_data = DepthOfFieldData::default();
+        if let Some(depth_of_field) = camera.depth_of_field {
+            depth_of_field_data.focal_distance = depth_of_field.focal_distance.max(f32::EPSILON);
+            depth_of_field_data.infinity_blur_radius =
+                0.5 * depth_of_field.aperture * camera_viewport.extent.height as f32;
+            depth_of_field_data.max_blur_radius = depth_of_field.max_blur_radius.max(0.0);
+        }
+        let is_depth_of_field_enabled = depth_of_field_data.max_blur_radius > 0.0
+            && depth_of_field_data.infinity_blur_radius > 0.0;
+
+        let mut screen_space_reflections_data = ScreenSpaceReflectionsData::default();
+        if let Some(screen_space_reflections) = post_process_settings.screen_space_reflections {
+            screen_space_reflections_data.steps_count = screen_space_reflections.steps_count;
+            screen_space_reflections_data.thickness = screen_space_reflections.thickness;
+            screen_space_reflections_data.max_distance = screen_space_reflections.max_distance;
+            screen_space_reflections_data.intensity = screen_space_reflections.intensity;
+        }
+
+        let camera_index = scene_data_buffer.len();
+        let light_clusters_data = LightClustersData {
+            device_address_point_lights,
+            device_address_light_clusters: device_address_light_clusters
+                + (camera_index * LIGHT_CLUSTERS_COUNT * LIGHT_CLUSTER_SIZE) as DeviceAddress,
+            camera_forward,
+            point_lights_count,
+            near: camera.clipping_planes.near,
+            far: camera.clipping_planes.far,
+            ..Default::default()
+        };
+
+        let foliage_instances_data = FoliageInstancesData {
+            device_address_foliages,
+            device_address_instances: device_address_foliage_instances
+                + (camera_index
+                    * MAX_FOLIAGE_INSTANCES_COUNT
+                    * std::mem::size_of::<InstanceObject>()) as DeviceAddress,
+            device_address_draw_arguments: device_address_foliage_draw_arguments
+                + (camera_index * std::mem::size_of::<DrawMeshTasksIndirectCommandEXT>())
+                    as DeviceAddress,
+            foliages_count,
+            max_instances_count: MAX_FOLIAGE_INSTANCES_COUNT as _,
+        };
+
+        // NOTE: Overlay is drawn after the post effects, they write the post image if the flare
+        // of the first camera is visible, so the target is known once it's computed.
+        debug_overlay_data.target_image_index =
+            if post_effects.is_enabled() || lens_flare_data.intensity > 0.0 {
+                frame_context.post_texture_reference.get_index()
+            } else {
+                frame_data.draw_texture_reference.get_index()
+            };
 
         let scene_data = SceneData {
-            camera_view_matrix: frame_context.world_matrix.to_cols_array(),
+            camera_view_matrix: world_matrix.to_cols_array(),
             camera_position,
             light_properties: LightProperties {
-                ambient_color: Vec4::new(0.1, 0.1, 0.1, 1.0),
+                ambient_color: environment_settings.ambient_color,
                 ambient_strength: 0.1,
                 specular_strength: 0.7,
                 ..Default::default()
             },
             directional_light: DirectionalLight {
-                light_color: Vec3::new(0.72, 0.72, 0.93),
-                light_position: Vec3::new(0.1, 0.5, 1.0),
+                light_color: environment_settings.sun_color,
+                light_position: sun_direction,
                 ..Default::default()
             },
+            environment_data,
+            post_process_data,
+            fog_data,
+            depth_of_field_data,
+            screen_space_reflections_data,
+            light_clusters_data,
+            water_data,
+            foliage_instances_data,
+            visibility_queries_data,
+            depth_picks_data,
+            outlines_data,
+            light_shafts_data,
+            lens_flare_data,
+            debug_overlay_data,
+            texture_usage_data,
+            device_address_buffer_addresses,
+            is_meshlet_cone_culling_enabled: culling_settings.is_meshlet_cone_culling_enabled as _,
             ..Default::default()
         };
-        scene_data_buffer.clear();
-        scene_data_buffer.add_instance_object(scene_data);
-        scene_data_buffer.prepare_objects_for_writing();
+        scene_data_buffer.push(scene_data);
+        frame_context.cameras.push(CameraFrameData {
+            viewport: camera_viewport,
+            front_face,
+            is_depth_of_field_enabled,
+            is_light_shafts_enabled,
+            is_background_drawn: environment_mode.is_some(),
+        });
+        background_clear_colors.push(match environment_mode {
+            Some(EnvironmentMode::SolidColor(color)) => Some(color),
+            _ => None,
+        });
+    }
+    // NOTE: Without a camera nothing is drawn, so the draw image is cleared to black.
+    frame_context.background_clear_color = match background_clear_colors.first() {
+        Some(&first_clear_color) => first_clear_color.filter(|color| {
+            background_clear_colors
+                .iter()
+                .all(|&clear_color| clear_color == Some(*color))
+        }),
+        None => Some(Vec4::W),
+    };
+    frame_context.is_lens_flare_visible = lens_flare_data.intensity > 0.0;
 
-        let scene_data_buffer = unsafe {
-            renderer_resources
-                .resources_pool
-                .scene_data_buffer
-                .as_ref()
-                .unwrap_unchecked()
-        };
+    scene_data_buffer.flush(buffers.as_mut());
+}
+
+/// Normalized viewport of the camera in pixels of the render extent.
+fn get_camera_viewport(viewport: CameraViewport, render_extent: Extent2D) -> Rect2D {
+    let width = render_extent.width as f32;
+    let height = render_extent.height as f32;
 
-        update_buffer_data(scene_data_buffer, &buffers);
+    let x = (viewport.x.clamp(0.0, 1.0) * width) as u32;
+    let y = (viewport.y.clamp(0.0, 1.0) * height) as u32;
+    let right = ((viewport.x + viewport.width).clamp(0.0, 1.0) * width) as u32;
+    let bottom = ((viewport.y + viewport.height).clamp(0.0, 1.0) * height) as u32;
+
+    Rect2D {
+        offset: Offset2D {
+            x: x as _,
+            y: y as _,
+        },
+        extent: Extent2D {
+            width: right.saturating_sub(x),
+            height: bottom.saturating_sub(y),
+        },
     }
 }
 
-#[inline(always)]
-fn update_buffer_data<T: Pod>(buffer_to_update: &SwappableBuffer<T>, buffers: &BuffersPool) {
-    let data_to_write = buffer_to_update.get_objects_to_write_as_slice();
+fn get_lens_flare_data(
+    lens_flare: &LensFlare,
+    sun_ndc: Vec2,
+    camera_viewport: Rect2D,
+    sun_visibility: f32,
+    sampler_index: u32,
+) -> LensFlareData {
+    let viewport_offset = Vec2::new(
+        camera_viewport.offset.x as f32,
+        camera_viewport.offset.y as f32,
+    );
+    let viewport_size = Vec2::new(
+        camera_viewport.extent.width as f32,
+        camera_viewport.extent.height as f32,
+    );
+    let sun_uv = Vec2::new(sun_ndc.x * 0.5 + 0.5, 0.5 - sun_ndc.y * 0.5);
+    // NOTE: Flare fades out, while the sun leaves the viewport.
+    let edge_fade = ((1.0 - sun_ndc.abs().max_element()) * 4.0).clamp(0.0, 1.0);
 
-    let buffer_to_update_reference = buffer_to_update.get_current_buffer();
-    unsafe {
-        buffers.transfer_data_to_buffer(
-            buffer_to_update_reference,
-            data_to_write,
-            data_to_write.len(),
-        );
+    let mut lens_flare_data = LensFlareData {
+        sun_position: viewport_offset + sun_uv * viewport_size,
+        center_position: viewport_offset + viewport_size * 0.5,
+        viewport_height: viewport_size.y,
+        intensity: lens_flare.intensity.max(0.0) * sun_visibility * edge_fade,
+        sampler_index,
+        ..Default::default()
+    };
+    for (sprite_data, sprite) in lens_flare_data
+        .sprites
+        .iter_mut()
+        .zip(lens_flare.sprites.iter())
+    {
+        *sprite_data = LensFlareSpriteData {
+            color: sprite.color,
+            position: sprite.position,
+            size: sprite.size,
+            texture_index: sprite
+                .texture_reference
+                .map(|texture_reference| texture_reference.get_index())
+                .unwrap_or_default(),
+            has_texture: sprite.texture_reference.is_some() as _,
+            uv_rect: sprite
+                .texture_reference
+                .map(|texture_reference| texture_reference.get_uv_rect())
+                .unwrap_or_default()
+                .to_vec4(),
+            ..Default::default()
+        };
+        lens_flare_data.sprites_count += 1;
     }
+
+    lens_flare_data
 }
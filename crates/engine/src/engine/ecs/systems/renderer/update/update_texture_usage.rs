@@ -0,0 +1,116 @@
+use bevy_ecs::system::ResMut;
+
+use crate::engine::{
+    general::renderer::{DescriptorSetHandle, GpuBackend},
+    resources::{
+        RendererContext, TextureUsage,
+        buffers_pool::{BufferReference, BuffersPool},
+    },
+};
+
+/// Accumulates counters of the frame that used the same frame data, its fence is already waited,
+/// and zeroes them, if the current frame is counted.
+pub fn update_texture_usage_system(
+    mut render_context: ResMut<RendererContext>,
+    mut texture_usage: ResMut<TextureUsage>,
+    mut buffers_pool: ResMut<BuffersPool>,
+) {
+    let frame_data = render_context.get_current_frame_data_mut();
+    let is_texture_usage_counted = texture_usage.begin_frame();
+    if !frame_data.is_texture_usage_counted && !is_texture_usage_counted {
+        return;
+    }
+
+    swap_texture_usage_counters(
+        buffers_pool.as_mut(),
+        frame_data.texture_usage_counters_buffer_reference,
+        &mut texture_usage,
+        frame_data.is_texture_usage_counted,
+        is_texture_usage_counted,
+    );
+    frame_data.is_texture_usage_counted = is_texture_usage_counted;
+}
+
+/// Counters of the previous frame are accumulated, if it was counted,
+/// and zeroed for the current one, if it's counted.
+pub(crate) fn swap_texture_usage_counters(
+    gpu_backend: &mut impl GpuBackend,
+    counters_buffer_reference: BufferReference,
+    texture_usage: &mut TextureUsage,
+    was_counted: bool,
+    is_counted: bool,
+) {
+    let counters_count = DescriptorSetHandle::MAX_SAMPLED_IMAGES_COUNT as usize;
+    if was_counted {
+        let counters: Vec<u32> =
+            gpu_backend.read_objects(counters_buffer_reference, counters_count);
+        texture_usage.accumulate(&counters);
+    }
+    if is_counted {
+        gpu_backend.write_objects(counters_buffer_reference, &vec![0u32; counters_count]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vulkanite::vk::BufferUsageFlags;
+
+    use super::swap_texture_usage_counters;
+    use crate::engine::{
+        general::renderer::{DescriptorSetHandle, GpuBackend, MockGpuBackend},
+        resources::{
+            TextureUsage,
+            buffers_pool::{BufferReference, BufferVisibility},
+        },
+    };
+
+    fn create_counters_buffer(gpu_backend: &mut MockGpuBackend) -> BufferReference {
+        let counters_buffer_reference = gpu_backend.create_buffer(
+            std::mem::size_of::<u32>() * DescriptorSetHandle::MAX_SAMPLED_IMAGES_COUNT as usize,
+            BufferUsageFlags::StorageBuffer,
+            BufferVisibility::HostVisible,
+            None,
+        );
+        gpu_backend.write_objects(counters_buffer_reference, &[3u32, 0, 5]);
+
+        counters_buffer_reference
+    }
+
+    #[test]
+    fn counted_frame_is_accumulated_and_zeroed() {
+        let mut gpu_backend = MockGpuBackend::new();
+        let counters_buffer_reference = create_counters_buffer(&mut gpu_backend);
+        let mut texture_usage = TextureUsage::default();
+
+        swap_texture_usage_counters(
+            &mut gpu_backend,
+            counters_buffer_reference,
+            &mut texture_usage,
+            true,
+            true,
+        );
+
+        assert_eq!(texture_usage.get_sampled_frames_count(), 1);
+        let counters: Vec<u32> = gpu_backend.read_objects(counters_buffer_reference, 3);
+        assert_eq!(counters, vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn uncounted_frame_keeps_counters() {
+        let mut gpu_backend = MockGpuBackend::new();
+        let counters_buffer_reference = create_counters_buffer(&mut gpu_backend);
+        let mut texture_usage = TextureUsage::default();
+
+        swap_texture_usage_counters(
+            &mut gpu_backend,
+            counters_buffer_reference,
+            &mut texture_usage,
+            false,
+            false,
+        );
+
+        assert_eq!(texture_usage.get_sampled_frames_count(), 0);
+        let counters: Vec<u32> = gpu_backend.read_objects(counters_buffer_reference, 3);
+        assert_eq!(counters, vec![3, 0, 5]);
+    }
+}
@@ -0,0 +1,136 @@
+use bevy_ecs::{
+    entity::Entity,
+    query::With,
+    system::{Query, ResMut},
+};
+use math::Vec4;
+
+use crate::engine::{
+    components::{
+        camera::Camera, local_transform::GlobalTransform, visibility_query::VisibilityQuery,
+    },
+    general::renderer::GpuBackend,
+    resources::{
+        RendererContext, RendererResources,
+        buffers_pool::{BufferReference, BuffersPool},
+    },
+};
+
+/// Reads results of the frame that used the same frame data, its fence is already waited,
+/// and submits points of the current queries.
+pub fn update_visibility_queries_system(
+    mut render_context: ResMut<RendererContext>,
+    mut renderer_resources: ResMut<RendererResources>,
+    mut buffers_pool: ResMut<BuffersPool>,
+    mut visibility_queries_query: Query<(Entity, &GlobalTransform, &mut VisibilityQuery)>,
+    cameras_query: Query<(), With<Camera>>,
+) {
+    let frame_data = render_context.get_current_frame_data_mut();
+    // NOTE: Entities could have been despawned or lost the query since the submission.
+    for (entity, is_visible) in read_visibility_query_results(
+        buffers_pool.as_ref(),
+        frame_data.visibility_query_results_buffer_reference,
+        &frame_data.visibility_query_entities,
+    ) {
+        if let Ok((_, _, mut visibility_query)) = visibility_queries_query.get_mut(entity) {
+            visibility_query.set_visible(is_visible);
+        }
+    }
+    frame_data.visibility_query_entities.clear();
+
+    let visibility_query_points_buffer = unsafe {
+        renderer_resources
+            .resources_pool
+            .visibility_query_points_buffer
+            .as_mut()
+            .unwrap_unchecked()
+    };
+    visibility_query_points_buffer.clear();
+    // NOTE: Without a camera there is no depth to test against, so the last results are kept.
+    if cameras_query.is_empty() {
+        return;
+    }
+
+    for (entity, global_transform, visibility_query) in visibility_queries_query
+        .iter()
+        .take(visibility_query_points_buffer.get_capacity())
+    {
+        let point = global_transform.transform_point(visibility_query.offset);
+        visibility_query_points_buffer.push(Vec4::from((point, 1.0)));
+        frame_data.visibility_query_entities.push(entity);
+    }
+
+    visibility_query_points_buffer.flush(buffers_pool.as_mut());
+}
+
+/// Result of every submitted entity is a `u32`, in the order of submission.
+pub(crate) fn read_visibility_query_results(
+    gpu_backend: &impl GpuBackend,
+    results_buffer_reference: BufferReference,
+    entities: &[Entity],
+) -> Vec<(Entity, bool)> {
+    if entities.is_empty() {
+        return Vec::new();
+    }
+
+    let results: Vec<u32> = gpu_backend.read_objects(results_buffer_reference, entities.len());
+
+    entities
+        .iter()
+        .zip(results)
+        .map(|(&entity, result)| (entity, result != Default::default()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::world::World;
+    use vulkanite::vk::BufferUsageFlags;
+
+    use super::read_visibility_query_results;
+    use crate::engine::{
+        general::renderer::{GpuBackend, MockGpuBackend},
+        resources::buffers_pool::BufferVisibility,
+    };
+
+    #[test]
+    fn reads_result_of_every_submitted_entity() {
+        let mut gpu_backend = MockGpuBackend::new();
+        let results_buffer_reference = gpu_backend.create_buffer(
+            std::mem::size_of::<u32>() * 4,
+            BufferUsageFlags::StorageBuffer,
+            BufferVisibility::HostVisible,
+            None,
+        );
+        gpu_backend.write_objects(results_buffer_reference, &[0u32, 7, 1, 1]);
+
+        let mut world = World::new();
+        let entities: [_; 3] = std::array::from_fn(|_| world.spawn_empty().id());
+        let results =
+            read_visibility_query_results(&gpu_backend, results_buffer_reference, &entities);
+
+        assert_eq!(
+            results,
+            vec![
+                (entities[0], false),
+                (entities[1], true),
+                (entities[2], true)
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_nothing_without_submitted_entities() {
+        let mut gpu_backend = MockGpuBackend::new();
+        let results_buffer_reference = gpu_backend.create_buffer(
+            std::mem::size_of::<u32>(),
+            BufferUsageFlags::StorageBuffer,
+            BufferVisibility::HostVisible,
+            None,
+        );
+
+        assert!(
+            read_visibility_query_results(&gpu_backend, results_buffer_reference, &[]).is_empty()
+        );
+    }
+}
@@ -1,10 +1,14 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, str::FromStr};
 
 use bevy_ecs::{entity::Entity, event::Event};
+use vulkanite::vk::{DescriptorType, DeviceAddress, DeviceSize, Extent3D, Format};
 
 use crate::engine::{
     components::local_transform::LocalTransform,
-    ecs::{materials_pool::MaterialReference, mesh_buffers_pool::MeshBufferReference},
+    ecs::{
+        CVarValue, buffers_pool::BufferReference, materials_pool::MaterialReference,
+        mesh_buffers_pool::MeshBufferReference, textures_pool::TextureReference,
+    },
 };
 
 #[derive(Event)]
@@ -13,6 +17,127 @@ pub struct LoadModelEvent {
     pub parent_entity: Option<Entity>,
 }
 
+/// Triggered once for every `Terrain` to build and spawn its chunks.
+#[derive(Event)]
+pub struct LoadTerrainEvent {
+    pub terrain_entity: Entity,
+}
+
+/// Triggered when `FoliageScatter` with a density or height map path is inserted.
+#[derive(Event)]
+pub struct LoadFoliageMapsEvent {
+    pub foliage_entity: Entity,
+}
+
+/// Triggered once the hierarchy of `Impostor` has meshes to bake.
+#[derive(Event)]
+pub struct BakeImpostorEvent {
+    pub impostor_entity: Entity,
+}
+
+/// Triggered when `WaterSettings` with a normal map path is inserted.
+#[derive(Event)]
+pub struct LoadWaterNormalMapEvent {
+    pub water_entity: Entity,
+}
+
+/// Loads textures of the lens flare sprites, that have paths but no textures yet.
+#[derive(Event)]
+pub struct LoadLensFlareTexturesEvent;
+
+/// Triggered for every executed console line with a registered command name.
+#[derive(Clone, Debug, Event)]
+pub struct ConsoleCommandEvent {
+    pub name: String,
+    pub arguments: Vec<String>,
+}
+
+impl ConsoleCommandEvent {
+    pub fn get_argument<T: FromStr>(&self, index: usize) -> Option<T> {
+        self.arguments.get(index)?.parse().ok()
+    }
+}
+
+/// Triggered in the world update for every cvar changed since the last one.
+#[derive(Clone, Debug, Event)]
+pub struct CVarChangedEvent {
+    pub name: String,
+    pub value: CVarValue,
+}
+
+/// Triggered once the descriptor buffer was replaced by a larger one, descriptor indices stay the same.
+#[derive(Clone, Copy, Debug, Event)]
+pub struct DescriptorBufferResizedEvent {
+    pub variable_descriptors_capacity: u32,
+    pub device_address: u64,
+}
+
+/// Triggered in the renderer update for every buffer created since the last one.
+#[derive(Clone, Event)]
+pub struct BufferCreatedEvent {
+    pub buffer_reference: BufferReference,
+    pub name: Option<String>,
+    pub size: DeviceSize,
+}
+
+/// Triggered in the renderer update for every buffer freed since the last one, the reference is
+/// stale already.
+#[derive(Clone, Event)]
+pub struct BufferDestroyedEvent {
+    pub buffer_reference: BufferReference,
+    pub name: Option<String>,
+    pub size: DeviceSize,
+}
+
+/// Triggered once the defragmentation moved the buffer, its index stays the same.
+#[derive(Clone, Event)]
+pub struct BufferMovedEvent {
+    pub buffer_reference: BufferReference,
+    pub name: Option<String>,
+    pub previous_device_address: DeviceAddress,
+}
+
+/// Triggered in the renderer update for every texture created since the last one.
+#[derive(Clone, Event)]
+pub struct TextureCreatedEvent {
+    pub texture_reference: TextureReference,
+    /// Textures have no debug names, it's made of the index, extent and format.
+    pub name: String,
+    pub format: Format,
+    pub extent: Extent3D,
+    /// Bytes of the allocation, it's aligned and includes the mips.
+    pub size: DeviceSize,
+}
+
+/// Triggered in the renderer update for every texture freed since the last one, the reference is
+/// stale already.
+#[derive(Clone, Event)]
+pub struct TextureDestroyedEvent {
+    pub texture_reference: TextureReference,
+    pub name: String,
+    pub size: DeviceSize,
+}
+
+/// Triggered for every descriptor written into the descriptor buffer, shaders reach the resource
+/// by the index.
+#[derive(Clone, Copy, Debug, Event)]
+pub struct DescriptorIndexAssignedEvent {
+    pub descriptor_type: DescriptorType,
+    pub index: u32,
+}
+
+/// Lifecycle changes recorded by the pools, they're triggered as their events by
+/// `notify_gpu_resource_events_system`.
+#[derive(Clone)]
+pub(crate) enum GpuResourceEvent {
+    BufferCreated(BufferCreatedEvent),
+    BufferDestroyed(BufferDestroyedEvent),
+    BufferMoved(BufferMovedEvent),
+    TextureCreated(TextureCreatedEvent),
+    TextureDestroyed(TextureDestroyedEvent),
+    DescriptorIndexAssigned(DescriptorIndexAssignedEvent),
+}
+
 #[derive(Clone, Default)]
 pub struct SpawnEventRecord {
     pub name: String,
@@ -0,0 +1,192 @@
+use vulkanite::vk::{
+    AccessFlags2, BufferUsageFlags, DescriptorBufferBindingInfoEXT, DeviceAddress, DeviceSize,
+    DispatchIndirectCommand, PipelineBindPoint, PipelineStageFlags2, ShaderStageFlags, WHOLE_SIZE,
+    rs::{CommandBuffer, PipelineLayout},
+};
+
+use crate::engine::{
+    ecs::{
+        buffer_state_tracker::BufferState,
+        buffers_pool::{BufferReference, BufferVisibility, BuffersPool},
+    },
+    resources::{GraphicsPushConstant, ShaderObject},
+};
+
+/// Compute shader bound with the descriptor buffer, dispatches are recorded until another shader
+/// or pipeline is bound.
+#[derive(Clone, Copy)]
+pub struct ComputePass {
+    command_buffer: CommandBuffer,
+    pipeline_layout: PipelineLayout,
+}
+
+impl ComputePass {
+    pub fn begin(
+        command_buffer: CommandBuffer,
+        compute_shader_object: ShaderObject,
+        pipeline_layout: PipelineLayout,
+        descriptor_buffer_device_address: DeviceAddress,
+    ) -> Self {
+        let stages = [compute_shader_object.stage];
+        let shaders = [compute_shader_object.shader.unwrap()];
+        command_buffer.bind_shaders_ext(stages.as_slice(), shaders.as_slice());
+
+        let descriptor_binding_info = DescriptorBufferBindingInfoEXT::default()
+            .usage(BufferUsageFlags::ResourceDescriptorBufferEXT)
+            .address(descriptor_buffer_device_address);
+        let descriptor_binding_infos = [descriptor_binding_info];
+        command_buffer.bind_descriptor_buffers_ext(&descriptor_binding_infos);
+
+        let buffer_indices = [0];
+        let offsets = [0];
+        command_buffer.set_descriptor_buffer_offsets_ext(
+            PipelineBindPoint::Compute,
+            pipeline_layout,
+            Default::default(),
+            &buffer_indices,
+            &offsets,
+        );
+
+        Self {
+            command_buffer,
+            pipeline_layout,
+        }
+    }
+
+    /// Pushes the address of the per dispatch data, it's read by the shader as the instance object.
+    pub fn push_device_address(&self, device_address: DeviceAddress) {
+        self.command_buffer.push_constants(
+            self.pipeline_layout,
+            ShaderStageFlags::MeshEXT
+                | ShaderStageFlags::Fragment
+                | ShaderStageFlags::Compute
+                | ShaderStageFlags::TaskEXT,
+            std::mem::offset_of!(GraphicsPushConstant, device_address_instance_object) as _,
+            std::mem::size_of::<DeviceAddress>() as _,
+            &device_address as *const _ as _,
+        );
+    }
+
+    pub fn dispatch(&self, group_counts: [u32; 3]) {
+        let [group_count_x, group_count_y, group_count_z] = group_counts;
+        self.command_buffer
+            .dispatch(group_count_x, group_count_y, group_count_z);
+    }
+}
+
+// NOTE: Built-in passes size their dispatches on the CPU, these are for GPU driven ones.
+#[allow(dead_code)]
+impl ComputePass {
+    /// Group counts are read from the `DispatchIndirectCommand` at `offset` of the buffer,
+    /// e.g. written by a previous pass. Writes of the buffer are waited.
+    pub fn dispatch_indirect(
+        &self,
+        buffers_pool: &BuffersPool,
+        arguments_buffer_reference: BufferReference,
+        offset: DeviceSize,
+    ) {
+        buffers_pool.require_buffer_state(arguments_buffer_reference, BufferState::indirect_read());
+        buffers_pool.flush_buffer_barriers(self.command_buffer);
+
+        let arguments_buffer = unsafe {
+            buffers_pool
+                .get_buffer(arguments_buffer_reference)
+                .unwrap_unchecked()
+        };
+        self.command_buffer
+            .dispatch_indirect(arguments_buffer.buffer, offset);
+    }
+
+    /// Buffer of `arguments_count` commands, it's writable by compute shaders and the transfers.
+    pub fn create_arguments_buffer(
+        buffers_pool: &mut BuffersPool,
+        arguments_count: usize,
+        name: Option<String>,
+    ) -> BufferReference {
+        buffers_pool.create_buffer(
+            std::mem::size_of::<DispatchIndirectCommand>() * arguments_count,
+            BufferUsageFlags::StorageBuffer
+                | BufferUsageFlags::IndirectBuffer
+                | BufferUsageFlags::ShaderDeviceAddress
+                | BufferUsageFlags::TransferDst,
+            BufferVisibility::DeviceOnly,
+            None,
+            name,
+        )
+    }
+
+    /// Zeroes the whole buffer, so shaders can accumulate the group counts atomically.
+    /// Afterwards it's readable and writable by compute shaders.
+    pub fn zero_arguments(
+        command_buffer: CommandBuffer,
+        buffers_pool: &BuffersPool,
+        arguments_buffer_reference: BufferReference,
+    ) {
+        // NOTE: Arguments might be still read by the dispatch of the previous use.
+        buffers_pool.require_buffer_state(
+            arguments_buffer_reference,
+            BufferState::new(PipelineStageFlags2::Clear, AccessFlags2::TransferWrite),
+        );
+        buffers_pool.flush_buffer_barriers(command_buffer);
+
+        let arguments_buffer = unsafe {
+            buffers_pool
+                .get_buffer(arguments_buffer_reference)
+                .unwrap_unchecked()
+        };
+        command_buffer.fill_buffer(
+            arguments_buffer.buffer,
+            Default::default(),
+            WHOLE_SIZE,
+            Default::default(),
+        );
+
+        Self::require_compute_read_write(command_buffer, buffers_pool, arguments_buffer_reference);
+    }
+
+    /// Writes `arguments` from the start of the buffer, e.g. group counts of `[0, 1, 1]` for
+    /// shaders that only increment the X one. Afterwards it's readable and writable by compute
+    /// shaders.
+    pub fn initialize_arguments(
+        command_buffer: CommandBuffer,
+        buffers_pool: &BuffersPool,
+        arguments_buffer_reference: BufferReference,
+        arguments: &[DispatchIndirectCommand],
+    ) {
+        buffers_pool.require_buffer_state(
+            arguments_buffer_reference,
+            BufferState::new(PipelineStageFlags2::Copy, AccessFlags2::TransferWrite),
+        );
+        buffers_pool.flush_buffer_barriers(command_buffer);
+
+        let arguments_buffer = unsafe {
+            buffers_pool
+                .get_buffer(arguments_buffer_reference)
+                .unwrap_unchecked()
+        };
+        // NOTE: Inline updates are limited to 64 KiB, it's thousands of commands.
+        command_buffer.update_buffer(
+            arguments_buffer.buffer,
+            Default::default(),
+            std::mem::size_of_val(arguments) as _,
+            arguments.as_ptr() as _,
+        );
+
+        Self::require_compute_read_write(command_buffer, buffers_pool, arguments_buffer_reference);
+    }
+
+    fn require_compute_read_write(
+        command_buffer: CommandBuffer,
+        buffers_pool: &BuffersPool,
+        arguments_buffer_reference: BufferReference,
+    ) {
+        buffers_pool.require_buffer_state(
+            arguments_buffer_reference,
+            BufferState::new(
+                PipelineStageFlags2::ComputeShader,
+                AccessFlags2::ShaderStorageRead | AccessFlags2::ShaderStorageWrite,
+            ),
+        );
+        buffers_pool.flush_buffer_barriers(command_buffer);
+    }
+}
@@ -0,0 +1,255 @@
+use vulkanite::vk::{
+    AccessFlags2, BufferUsageFlags, DeviceAddress, Extent2D, Extent3D, Format, ImageLayout,
+    ImageUsageFlags, PipelineStageFlags2, Rect2D,
+    rs::{CommandBuffer, PipelineLayout},
+};
+
+use crate::engine::{
+    ecs::{
+        buffers_pool::{BufferReference, BufferVisibility, BuffersPool},
+        image_state_tracker::ImageState,
+        textures_pool::{MipView, TextureReference, TexturesPool},
+    },
+    general::renderer::{
+        ComputePass, DescriptorKind, DescriptorSampledImage, DescriptorSetHandle,
+        DescriptorStorageImage,
+    },
+    resources::{
+        DepthPyramidLevel, FrameGraphAccess, FrameGraphCapture, FrameGraphPassInfo, ShaderObject,
+    },
+};
+
+/// Depth kept by the coarser mips out of the footprint of their texel.
+#[repr(u32)]
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DepthReduction {
+    /// Farthest depth, as the depth is reversed. It's the conservative one for occlusion culling.
+    #[default]
+    Min,
+    /// Nearest depth, e.g. to skip empty space while tracing reflections.
+    #[allow(dead_code)]
+    Max,
+}
+
+/// Mip chain of a depth texture, every texel keeps the min or max depth of its footprint.
+/// Mip 0 has the extent of the source, the rest are halved. Passes sample the whole chain by
+/// `get_index`, the mips are written through their own storage views.
+pub struct DepthPyramid {
+    texture_reference: TextureReference,
+    mip_views: Vec<MipView>,
+    /// Level per mip, the address of the built one is pushed.
+    levels_buffer_reference: BufferReference,
+    reduction: DepthReduction,
+}
+
+impl DepthPyramid {
+    const GROUP_SIZE: u32 = 8;
+
+    pub fn new(
+        textures_pool: &mut TexturesPool,
+        buffers_pool: &mut BuffersPool,
+        descriptor_set_handle: &mut DescriptorSetHandle,
+        extent: Extent2D,
+        reduction: DepthReduction,
+    ) -> Self {
+        let (texture_reference, _) = textures_pool.create_texture(
+            None,
+            false,
+            Format::R32Sfloat,
+            Extent3D {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+            },
+            ImageUsageFlags::Storage | ImageUsageFlags::Sampled,
+            true,
+        );
+        let descriptor_pyramid_image = DescriptorKind::SampledImage(DescriptorSampledImage {
+            image_view: textures_pool
+                .get_image(texture_reference)
+                .unwrap()
+                .image_view,
+            index: texture_reference.get_index(),
+        });
+        descriptor_set_handle.update_binding(buffers_pool, descriptor_pyramid_image);
+
+        let mip_views = textures_pool.create_mip_views(texture_reference);
+        for mip_view in &mip_views {
+            let descriptor_mip_image = DescriptorKind::StorageImage(DescriptorStorageImage {
+                image_view: mip_view.image_view,
+                index: mip_view.index,
+            });
+            descriptor_set_handle.update_binding(buffers_pool, descriptor_mip_image);
+        }
+
+        let levels_buffer_reference = buffers_pool.create_buffer(
+            std::mem::size_of::<DepthPyramidLevel>() * mip_views.len(),
+            BufferUsageFlags::ShaderDeviceAddress,
+            BufferVisibility::HostVisible,
+            None,
+            Some("Depth Pyramid Levels Buffer".to_string()),
+        );
+
+        Self {
+            texture_reference,
+            mip_views,
+            levels_buffer_reference,
+            reduction,
+        }
+    }
+
+    /// Index of the sampled image of the whole chain.
+    #[inline(always)]
+    pub fn get_index(&self) -> u32 {
+        self.texture_reference.get_index()
+    }
+
+    #[inline(always)]
+    pub fn get_mip_levels_count(&self) -> u32 {
+        self.mip_views.len() as _
+    }
+
+    #[inline(always)]
+    pub fn get_extent(&self) -> Extent2D {
+        let texture_metadata = self.texture_reference.texture_metadata;
+
+        Extent2D {
+            width: texture_metadata.width,
+            height: texture_metadata.height,
+        }
+    }
+
+    /// Records the build of the chain from `source_rect` of the depth texture, it must be readable
+    /// by compute shaders already. Texels past the footprint of the source rect are undefined.
+    /// Levels are written from the host, so the pyramid must not be in use by the GPU,
+    /// e.g. it's one per frame in flight. Afterwards the chain is readable by compute shaders.
+    #[allow(clippy::too_many_arguments)]
+    pub fn record(
+        &self,
+        command_buffer: CommandBuffer,
+        textures_pool: &TexturesPool,
+        buffers_pool: &BuffersPool,
+        frame_graph_capture: &mut FrameGraphCapture,
+        compute_shader_object: ShaderObject,
+        pipeline_layout: PipelineLayout,
+        descriptor_buffer_device_address: DeviceAddress,
+        depth_texture_reference: TextureReference,
+        source_rect: Rect2D,
+    ) {
+        let extent = self.get_extent();
+        let mut source_extent = Extent2D {
+            width: source_rect.extent.width.min(extent.width),
+            height: source_rect.extent.height.min(extent.height),
+        };
+        let levels: Vec<_> = self
+            .mip_views
+            .iter()
+            .enumerate()
+            .map(|(mip_level, mip_view)| {
+                let target_extent = if mip_level == 0 {
+                    source_extent
+                } else {
+                    Extent2D {
+                        width: (source_extent.width / 2).max(1),
+                        height: (source_extent.height / 2).max(1),
+                    }
+                };
+                let level = DepthPyramidLevel {
+                    source_image_index: match mip_level {
+                        0 => depth_texture_reference.get_index(),
+                        _ => self.mip_views[mip_level - 1].index,
+                    },
+                    target_image_index: mip_view.index,
+                    source_offset_x: if mip_level == 0 {
+                        source_rect.offset.x as _
+                    } else {
+                        Default::default()
+                    },
+                    source_offset_y: if mip_level == 0 {
+                        source_rect.offset.y as _
+                    } else {
+                        Default::default()
+                    },
+                    source_width: source_extent.width,
+                    source_height: source_extent.height,
+                    target_width: target_extent.width,
+                    target_height: target_extent.height,
+                    is_source_depth: (mip_level == 0) as _,
+                    reduction: self.reduction as _,
+                };
+                source_extent = target_extent;
+
+                level
+            })
+            .collect();
+        buffers_pool.transfer_slice(self.levels_buffer_reference, &levels);
+
+        // NOTE: Every mip is rewritten, so the previous content is discarded.
+        textures_pool.discard_content(self.texture_reference);
+        frame_graph_capture.ensure_image_state(
+            command_buffer,
+            textures_pool,
+            (self.texture_reference, "Depth Pyramid"),
+            ImageState::compute_write(),
+        );
+
+        let compute_pass = ComputePass::begin(
+            command_buffer,
+            compute_shader_object,
+            pipeline_layout,
+            descriptor_buffer_device_address,
+        );
+
+        let levels_device_address = buffers_pool
+            .get_buffer_info(self.levels_buffer_reference)
+            .device_address;
+        for (mip_level, level) in levels.iter().enumerate() {
+            // NOTE: Previous mip is read by this one, the whole chain is waited for simplicity.
+            if mip_level > 0 {
+                frame_graph_capture.ensure_image_state(
+                    command_buffer,
+                    textures_pool,
+                    (self.texture_reference, "Depth Pyramid"),
+                    ImageState::compute_read_write(),
+                );
+            }
+
+            let device_address_level = levels_device_address
+                + (mip_level * std::mem::size_of::<DepthPyramidLevel>()) as DeviceAddress;
+            compute_pass.push_device_address(device_address_level);
+            compute_pass.dispatch([
+                level.target_width.div_ceil(Self::GROUP_SIZE),
+                level.target_height.div_ceil(Self::GROUP_SIZE),
+                1,
+            ]);
+        }
+        frame_graph_capture.record_pass(FrameGraphPassInfo::new(
+            "Depth Pyramid",
+            &[FrameGraphAccess::image("Depth Image", ImageLayout::General)],
+            &[FrameGraphAccess::image(
+                "Depth Pyramid",
+                ImageLayout::General,
+            )],
+        ));
+
+        // NOTE: Passes of the environment data sample it from compute and fragment shaders.
+        frame_graph_capture.ensure_image_state(
+            command_buffer,
+            textures_pool,
+            (self.texture_reference, "Depth Pyramid"),
+            ImageState::new(
+                ImageLayout::General,
+                PipelineStageFlags2::ComputeShader | PipelineStageFlags2::FragmentShader,
+                AccessFlags2::ShaderSampledRead,
+            ),
+        );
+    }
+
+    /// Pyramid must not be in use by the GPU anymore.
+    pub fn free(self, textures_pool: &mut TexturesPool, buffers_pool: &mut BuffersPool) {
+        textures_pool.free_texture(self.texture_reference);
+        unsafe {
+            buffers_pool.free_buffer(self.levels_buffer_reference);
+        }
+    }
+}
@@ -36,6 +36,7 @@ struct DescriptorSetLayoutBindingInfo<'a> {
 #[derive(Default)]
 pub struct DescriptorSetBuilder<'a> {
     bindings_infos: Vec<DescriptorSetLayoutBindingInfo<'a>>,
+    initial_variable_descriptors_count: Option<u32>,
 }
 
 impl<'a> DescriptorSetBuilder<'a> {
@@ -65,6 +66,17 @@ impl<'a> DescriptorSetBuilder<'a> {
         self
     }
 
+    /// Descriptor buffer starts with room for this many descriptors of the variable binding,
+    /// it's grown on demand up to the count of the layout.
+    pub fn with_initial_variable_descriptors_count(
+        mut self,
+        initial_variable_descriptors_count: u32,
+    ) -> Self {
+        self.initial_variable_descriptors_count = Some(initial_variable_descriptors_count);
+
+        self
+    }
+
     pub fn build(
         mut self,
         device: Device,
@@ -92,7 +104,14 @@ impl<'a> DescriptorSetBuilder<'a> {
                     binding_index as _,
                 );
 
-                let binding_info = BindingInfo { binding_offset };
+                let binding = &descriptor_set_layout_binding_info.binding;
+                let binding_info = BindingInfo {
+                    binding_offset,
+                    descriptors_count: binding.descriptor_count,
+                    is_variable: descriptor_set_layout_binding_info
+                        .flags
+                        .contains(DescriptorBindingFlags::VariableDescriptorCount),
+                };
                 bindings_infos.insert(
                     descriptor_set_layout_binding_info.binding.descriptor_type as _,
                     binding_info,
@@ -100,10 +119,54 @@ impl<'a> DescriptorSetBuilder<'a> {
             },
         );
 
-        let descriptor_buffer_size = Self::get_descriptor_buffer_aligned_size(
-            descriptor_set_layout_handle.descriptor_set_layout_size,
-            descriptor_buffer_properties.descriptor_buffer_offset_alignment,
-        );
+        let sampled_image_descriptor_size =
+            descriptor_buffer_properties.sampled_image_descriptor_size;
+        let storage_image_descriptor_size =
+            descriptor_buffer_properties.storage_image_descriptor_size;
+        let sampler_descriptor_size = descriptor_buffer_properties.sampler_descriptor_size;
+
+        let descriptor_buffer_offset_alignment =
+            descriptor_buffer_properties.descriptor_buffer_offset_alignment;
+        // NOTE: Variable binding is the last one, the layout size counts all of its descriptors.
+        let variable_binding = self.bindings_infos.last().and_then(|binding_info| {
+            bindings_infos
+                .get(&(binding_info.binding.descriptor_type as u32))
+                .filter(|binding_info| binding_info.is_variable)
+                .map(|&variable_binding_info| {
+                    let descriptor_size = match binding_info.binding.descriptor_type {
+                        DescriptorType::StorageImage => storage_image_descriptor_size,
+                        DescriptorType::Sampler => sampler_descriptor_size,
+                        _ => sampled_image_descriptor_size,
+                    };
+
+                    (variable_binding_info, descriptor_size)
+                })
+        });
+        let (descriptor_buffer_size, variable_descriptors_capacity) = match variable_binding {
+            Some((variable_binding_info, descriptor_size)) => {
+                let variable_descriptors_capacity = self
+                    .initial_variable_descriptors_count
+                    .unwrap_or(variable_binding_info.descriptors_count)
+                    .min(variable_binding_info.descriptors_count);
+
+                (
+                    DescriptorSetHandle::get_descriptor_buffer_size(
+                        variable_binding_info,
+                        descriptor_size,
+                        variable_descriptors_capacity,
+                        descriptor_buffer_offset_alignment,
+                    ),
+                    variable_descriptors_capacity,
+                )
+            }
+            None => (
+                Self::get_descriptor_buffer_aligned_size(
+                    descriptor_set_layout_handle.descriptor_set_layout_size,
+                    descriptor_buffer_offset_alignment,
+                ),
+                Default::default(),
+            ),
+        };
 
         let descriptor_buffer_reference = buffers_pool.create_buffer(
             descriptor_buffer_size as _,
@@ -120,12 +183,6 @@ impl<'a> DescriptorSetBuilder<'a> {
             .create_pipeline_layout(&pipeline_layout_info)
             .unwrap();
 
-        let sampled_image_descriptor_size =
-            descriptor_buffer_properties.sampled_image_descriptor_size;
-        let storage_image_descriptor_size =
-            descriptor_buffer_properties.storage_image_descriptor_size;
-        let sampler_descriptor_size = descriptor_buffer_properties.sampler_descriptor_size;
-
         let descriptor_sizes = DescriptorsSizes {
             sampled_image_descriptor_size,
             sampler_descriptor_size,
@@ -139,6 +196,9 @@ impl<'a> DescriptorSetBuilder<'a> {
         descriptor_set_handle.pipeline_layout = Some(pipeline_layout);
         descriptor_set_handle.bindings_infos = bindings_infos;
         descriptor_set_handle.descriptors_sizes = descriptor_sizes;
+        descriptor_set_handle.descriptor_buffer_offset_alignment =
+            descriptor_buffer_offset_alignment;
+        descriptor_set_handle.variable_descriptors_capacity = variable_descriptors_capacity;
 
         descriptor_set_handle
     }
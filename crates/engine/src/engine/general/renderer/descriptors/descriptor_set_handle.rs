@@ -9,7 +9,8 @@ use vulkanite::{
 };
 
 use crate::engine::{
-    ecs::buffers_pool::{BufferInfo, BufferReference, BuffersPool},
+    ecs::buffers_pool::{BufferInfo, BufferReference, BufferVisibility, BuffersPool},
+    events::{DescriptorIndexAssignedEvent, GpuResourceEvent},
     general::renderer::DescriptorKind,
 };
 
@@ -29,6 +30,9 @@ pub struct DescriptorsSizes {
 #[derive(Clone, Copy)]
 pub struct BindingInfo {
     pub binding_offset: DeviceSize,
+    /// Count of the layout, it's the upper bound of a variable binding.
+    pub descriptors_count: u32,
+    pub is_variable: bool,
 }
 
 #[derive(Resource)]
@@ -40,9 +44,21 @@ pub struct DescriptorSetHandle {
     pub bindings_infos: HashMap<u32, BindingInfo>,
     pub pipeline_layout: Option<PipelineLayout>,
     pub descriptors_sizes: DescriptorsSizes,
+    pub descriptor_buffer_offset_alignment: DeviceSize,
+    /// Descriptors of the variable binding the descriptor buffer has room for.
+    pub variable_descriptors_capacity: u32,
+    /// Highest written index of the variable binding plus one.
+    pub variable_descriptors_high_water_mark: u32,
+    /// Descriptor buffers replaced by a larger one, frames in flight could still read them.
+    retired_descriptor_buffer_references: Vec<BufferReference>,
+    /// Descriptors written since the last `notify_gpu_resource_events_system`.
+    gpu_resource_events: Vec<GpuResourceEvent>,
 }
 
 impl DescriptorSetHandle {
+    pub const MAX_SAMPLED_IMAGES_COUNT: u32 = 30_000;
+    pub const INITIAL_SAMPLED_IMAGES_COUNT: u32 = 4_096;
+
     pub fn new(device: Device) -> Self {
         Self {
             device,
@@ -52,10 +68,21 @@ impl DescriptorSetHandle {
             bindings_infos: Default::default(),
             pipeline_layout: Default::default(),
             descriptors_sizes: Default::default(),
+            descriptor_buffer_offset_alignment: Default::default(),
+            variable_descriptors_capacity: Default::default(),
+            variable_descriptors_high_water_mark: Default::default(),
+            retired_descriptor_buffer_references: Default::default(),
+            gpu_resource_events: Default::default(),
         }
     }
 
-    pub fn update_binding(&mut self, buffers_pool: &BuffersPool, descriptor_kind: DescriptorKind) {
+    /// Index past the capacity of the variable binding grows the descriptor buffer,
+    /// index past the count of the layout isn't written.
+    pub fn update_binding(
+        &mut self,
+        buffers_pool: &mut BuffersPool,
+        descriptor_kind: DescriptorKind,
+    ) {
         let descriptor_type = descriptor_kind.get_descriptor_type();
 
         let descriptors_sizes = self.descriptors_sizes;
@@ -70,7 +97,7 @@ impl DescriptorSetHandle {
         };
 
         let descriptor_type_raw = descriptor_type as u32;
-        let binding_info = self.bindings_infos.get_mut(&descriptor_type_raw).unwrap();
+        let binding_info = *self.bindings_infos.get(&descriptor_type_raw).unwrap();
 
         // TODO: Temp before migration to fully slot architecture.
         let descriptor_slot_index = match descriptor_kind {
@@ -83,6 +110,28 @@ impl DescriptorSetHandle {
             DescriptorKind::Sampler(descriptor_sampler) => descriptor_sampler.index,
         };
 
+        if descriptor_slot_index >= binding_info.descriptors_count {
+            println!(
+                "{:?} descriptor {} is out of the {} descriptors of the layout, it isn't written.",
+                descriptor_type, descriptor_slot_index, binding_info.descriptors_count
+            );
+
+            return;
+        }
+        if binding_info.is_variable {
+            if descriptor_slot_index >= self.variable_descriptors_capacity {
+                self.grow_descriptor_buffer(
+                    buffers_pool,
+                    binding_info,
+                    descriptor_size,
+                    descriptor_slot_index + 1,
+                );
+            }
+            self.variable_descriptors_high_water_mark = self
+                .variable_descriptors_high_water_mark
+                .max(descriptor_slot_index + 1);
+        }
+
         let base_binding_offset = binding_info.binding_offset;
         let binding_offset =
             base_binding_offset + (descriptor_slot_index as u64 * descriptor_size as u64);
@@ -162,6 +211,88 @@ impl DescriptorSetHandle {
                 }
             }
         };
+
+        self.gpu_resource_events
+            .push(GpuResourceEvent::DescriptorIndexAssigned(
+                DescriptorIndexAssignedEvent {
+                    descriptor_type,
+                    index: descriptor_slot_index,
+                },
+            ));
+    }
+
+    /// Size of the descriptor buffer with the variable binding holding `variable_descriptors_count`.
+    pub(crate) fn get_descriptor_buffer_size(
+        binding_info: BindingInfo,
+        descriptor_size: usize,
+        variable_descriptors_count: u32,
+        alignment: DeviceSize,
+    ) -> DeviceSize {
+        let size = binding_info.binding_offset
+            + variable_descriptors_count as u64 * descriptor_size as u64;
+
+        size.next_multiple_of(alignment.max(1))
+    }
+
+    /// Capacity is doubled and written descriptors are copied, so their indices stay valid.
+    fn grow_descriptor_buffer(
+        &mut self,
+        buffers_pool: &mut BuffersPool,
+        binding_info: BindingInfo,
+        descriptor_size: usize,
+        required_descriptors_count: u32,
+    ) {
+        let variable_descriptors_capacity = (self.variable_descriptors_capacity * 2)
+            .max(required_descriptors_count)
+            .min(binding_info.descriptors_count);
+        let descriptor_buffer_size = Self::get_descriptor_buffer_size(
+            binding_info,
+            descriptor_size,
+            variable_descriptors_capacity,
+            self.descriptor_buffer_offset_alignment,
+        );
+
+        let previous_descriptor_buffer_reference = self.descriptor_buffer_reference;
+        let descriptor_buffer_reference = buffers_pool.create_buffer(
+            descriptor_buffer_size as _,
+            BufferUsageFlags::ShaderDeviceAddress | BufferUsageFlags::ResourceDescriptorBufferEXT,
+            BufferVisibility::HostVisible,
+            None,
+            Some("Descriptor Set".to_string()),
+        );
+
+        let previous_size = buffers_pool
+            .get_buffer_info(previous_descriptor_buffer_reference)
+            .size;
+        let previous_mapped_allocation =
+            buffers_pool.map_allocation(previous_descriptor_buffer_reference);
+        let mapped_allocation = buffers_pool.map_allocation(descriptor_buffer_reference);
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                previous_mapped_allocation.get_ptr(),
+                mapped_allocation.get_ptr(),
+                previous_size as usize,
+            );
+        }
+
+        println!(
+            "Descriptor buffer grew from {} to {} variable descriptors.",
+            self.variable_descriptors_capacity, variable_descriptors_capacity
+        );
+        self.descriptor_buffer_reference = descriptor_buffer_reference;
+        self.variable_descriptors_capacity = variable_descriptors_capacity;
+        self.retired_descriptor_buffer_references
+            .push(previous_descriptor_buffer_reference);
+    }
+
+    #[inline(always)]
+    pub(crate) fn take_retired_descriptor_buffer_references(&mut self) -> Vec<BufferReference> {
+        std::mem::take(&mut self.retired_descriptor_buffer_references)
+    }
+
+    #[inline(always)]
+    pub(crate) fn take_gpu_resource_events(&mut self) -> Vec<GpuResourceEvent> {
+        std::mem::take(&mut self.gpu_resource_events)
     }
 
     #[inline(always)]
@@ -179,8 +310,8 @@ impl DescriptorSetHandle {
     }
 
     #[inline(always)]
-    pub fn get_buffer_info(&self) -> BufferInfo {
-        self.descriptor_buffer_reference.get_buffer_info()
+    pub fn get_buffer_info(&self, buffers_pool: &BuffersPool) -> BufferInfo {
+        buffers_pool.get_buffer_info(self.descriptor_buffer_reference)
     }
 
     pub fn destroy(&self) {
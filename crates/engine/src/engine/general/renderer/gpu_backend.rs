@@ -0,0 +1,236 @@
+use bytemuck::Pod;
+use vulkanite::vk::{BufferCopy, BufferUsageFlags};
+
+use crate::engine::ecs::buffers_pool::{
+    BufferInfo, BufferReference, BufferVisibility, BuffersPool,
+};
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for crate::engine::ecs::buffers_pool::BuffersPool {}
+    #[cfg(test)]
+    impl Sealed for crate::engine::general::renderer::MockGpuBackend {}
+}
+
+/// Buffer operations the ECS side depends on, so its bookkeeping can run without a GPU.
+/// It's sealed, backends are only the ones of the engine.
+pub trait GpuBackend: sealed::Sealed {
+    fn create_buffer(
+        &mut self,
+        allocation_size: usize,
+        usage: BufferUsageFlags,
+        buffer_visibility: BufferVisibility,
+        name: Option<String>,
+    ) -> BufferReference;
+
+    fn write_buffer(&mut self, buffer_reference: BufferReference, offset: usize, data: &[u8]);
+
+    /// Offsets and sizes of the regions are in bytes, sources are in `src` and destinations in the buffer.
+    fn write_buffer_regions(
+        &mut self,
+        buffer_reference: BufferReference,
+        src: &[u8],
+        regions: &[BufferCopy],
+    );
+
+    /// Buffer must be host visible and unused by the GPU, e.g. after the fence of its frame.
+    fn read_buffer(&self, buffer_reference: BufferReference, offset: usize, data: &mut [u8]);
+
+    fn free_buffer(&mut self, buffer_reference: BufferReference);
+
+    /// Freed buffers give the default info, so their device address is null.
+    fn get_buffer_info(&self, buffer_reference: BufferReference) -> BufferInfo;
+
+    fn write_objects<T: Pod>(&mut self, buffer_reference: BufferReference, objects: &[T])
+    where
+        Self: Sized,
+    {
+        self.write_buffer(
+            buffer_reference,
+            Default::default(),
+            bytemuck::cast_slice(objects),
+        );
+    }
+
+    /// Reads the first `count` objects of the buffer.
+    fn read_objects<T: Pod>(&self, buffer_reference: BufferReference, count: usize) -> Vec<T>
+    where
+        Self: Sized,
+    {
+        let mut objects = vec![T::zeroed(); count];
+        self.read_buffer(
+            buffer_reference,
+            Default::default(),
+            bytemuck::cast_slice_mut(&mut objects),
+        );
+
+        objects
+    }
+}
+
+impl GpuBackend for BuffersPool {
+    fn create_buffer(
+        &mut self,
+        allocation_size: usize,
+        usage: BufferUsageFlags,
+        buffer_visibility: BufferVisibility,
+        name: Option<String>,
+    ) -> BufferReference {
+        BuffersPool::create_buffer(self, allocation_size, usage, buffer_visibility, None, name)
+    }
+
+    fn write_buffer(&mut self, buffer_reference: BufferReference, offset: usize, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+
+        let regions = [BufferCopy {
+            dst_offset: offset as _,
+            size: data.len() as _,
+            ..Default::default()
+        }];
+
+        self.transfer_regions(buffer_reference, data, &regions);
+    }
+
+    fn write_buffer_regions(
+        &mut self,
+        buffer_reference: BufferReference,
+        src: &[u8],
+        regions: &[BufferCopy],
+    ) {
+        if regions.is_empty() {
+            return;
+        }
+
+        self.transfer_regions(buffer_reference, src, regions);
+    }
+
+    fn read_buffer(&self, buffer_reference: BufferReference, offset: usize, data: &mut [u8]) {
+        if data.is_empty() {
+            return;
+        }
+
+        let mapped_allocation = self.map_allocation(buffer_reference);
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                mapped_allocation.get_ptr().add(offset),
+                data.as_mut_ptr(),
+                data.len(),
+            );
+        }
+    }
+
+    fn free_buffer(&mut self, buffer_reference: BufferReference) {
+        unsafe {
+            BuffersPool::free_buffer(self, buffer_reference);
+        }
+    }
+
+    fn get_buffer_info(&self, buffer_reference: BufferReference) -> BufferInfo {
+        BuffersPool::get_buffer_info(self, buffer_reference)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vulkanite::vk::{BufferCopy, BufferUsageFlags};
+
+    use super::GpuBackend;
+    use crate::engine::{ecs::buffers_pool::BufferVisibility, general::renderer::MockGpuBackend};
+
+    #[test]
+    fn buffers_get_distinct_non_null_addresses() {
+        let mut gpu_backend = MockGpuBackend::new();
+        let first_buffer_reference = gpu_backend.create_buffer(
+            64,
+            BufferUsageFlags::StorageBuffer,
+            BufferVisibility::DeviceOnly,
+            None,
+        );
+        let second_buffer_reference = gpu_backend.create_buffer(
+            64,
+            BufferUsageFlags::StorageBuffer,
+            BufferVisibility::DeviceOnly,
+            None,
+        );
+
+        let first_device_address = gpu_backend
+            .get_buffer_info(first_buffer_reference)
+            .device_address;
+        let second_device_address = gpu_backend
+            .get_buffer_info(second_buffer_reference)
+            .device_address;
+        assert_ne!(first_device_address, Default::default());
+        assert!(second_device_address >= first_device_address + 64);
+        assert_eq!(gpu_backend.get_buffers_count(), 2);
+    }
+
+    #[test]
+    fn written_regions_are_read_back() {
+        let mut gpu_backend = MockGpuBackend::new();
+        let buffer_reference = gpu_backend.create_buffer(
+            8,
+            BufferUsageFlags::StorageBuffer,
+            BufferVisibility::HostVisible,
+            None,
+        );
+
+        gpu_backend.write_buffer(buffer_reference, 1, &[1, 2]);
+        gpu_backend.write_buffer_regions(
+            buffer_reference,
+            &[7, 8, 9],
+            &[
+                BufferCopy {
+                    src_offset: 1,
+                    dst_offset: 5,
+                    size: 2,
+                },
+                BufferCopy {
+                    src_offset: 0,
+                    dst_offset: 7,
+                    size: 1,
+                },
+            ],
+        );
+
+        let mut data = [0u8; 8];
+        gpu_backend.read_buffer(buffer_reference, 0, &mut data);
+        assert_eq!(data, [0, 1, 2, 0, 0, 8, 9, 7]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn write_out_of_buffer_panics() {
+        let mut gpu_backend = MockGpuBackend::new();
+        let buffer_reference = gpu_backend.create_buffer(
+            4,
+            BufferUsageFlags::StorageBuffer,
+            BufferVisibility::HostVisible,
+            None,
+        );
+
+        gpu_backend.write_buffer(buffer_reference, 2, &[0; 4]);
+    }
+
+    #[test]
+    fn freed_buffer_is_removed() {
+        let mut gpu_backend = MockGpuBackend::new();
+        let buffer_reference = gpu_backend.create_buffer(
+            4,
+            BufferUsageFlags::StorageBuffer,
+            BufferVisibility::HostVisible,
+            None,
+        );
+
+        gpu_backend.free_buffer(buffer_reference);
+
+        assert!(gpu_backend.get_buffer_data(buffer_reference).is_none());
+        assert_eq!(
+            gpu_backend.get_buffer_info(buffer_reference).device_address,
+            Default::default()
+        );
+        assert_eq!(gpu_backend.get_buffers_count(), 0);
+    }
+}
@@ -0,0 +1,111 @@
+use bevy_ecs::resource::Resource;
+use shared::BufferKey;
+use slotmap::SlotMap;
+use vulkanite::vk::{BufferCopy, BufferUsageFlags, DeviceAddress};
+
+use crate::engine::{
+    ecs::buffers_pool::{BufferInfo, BufferReference, BufferVisibility},
+    general::renderer::GpuBackend,
+};
+
+/// Keeps buffers in host memory and hands out fake device addresses.
+#[derive(Default, Resource)]
+pub struct MockGpuBackend {
+    slots: SlotMap<BufferKey, (BufferInfo, Vec<u8>)>,
+    next_device_address: DeviceAddress,
+}
+
+impl MockGpuBackend {
+    // NOTE: Zero address is treated as null by the shaders.
+    const BASE_DEVICE_ADDRESS: DeviceAddress = 0x1000;
+
+    pub fn new() -> Self {
+        Self {
+            slots: Default::default(),
+            next_device_address: Self::BASE_DEVICE_ADDRESS,
+        }
+    }
+
+    pub fn get_buffer_data(&self, buffer_reference: BufferReference) -> Option<&[u8]> {
+        self.slots
+            .get(buffer_reference.get_key())
+            .map(|(_, buffer_data)| buffer_data.as_slice())
+    }
+
+    pub fn get_buffers_count(&self) -> usize {
+        self.slots.len()
+    }
+}
+
+impl GpuBackend for MockGpuBackend {
+    fn create_buffer(
+        &mut self,
+        allocation_size: usize,
+        _usage: BufferUsageFlags,
+        buffer_visibility: BufferVisibility,
+        _name: Option<String>,
+    ) -> BufferReference {
+        if buffer_visibility == BufferVisibility::Unspecified {
+            panic!("Trying to create a buffer with unspecified visibility!");
+        }
+
+        let device_address = self.next_device_address.max(Self::BASE_DEVICE_ADDRESS);
+        self.next_device_address = device_address + allocation_size as DeviceAddress;
+
+        let buffer_info = BufferInfo::new(device_address, allocation_size as _, buffer_visibility);
+        let buffer_key = self
+            .slots
+            .insert((buffer_info, vec![Default::default(); allocation_size]));
+
+        BufferReference::new(buffer_key)
+    }
+
+    fn write_buffer(&mut self, buffer_reference: BufferReference, offset: usize, data: &[u8]) {
+        let (_, buffer_data) = self.slots.get_mut(buffer_reference.get_key()).unwrap();
+        assert!(
+            offset + data.len() <= buffer_data.len(),
+            "Write of {} bytes at {} is out of the buffer of {} bytes",
+            data.len(),
+            offset,
+            buffer_data.len()
+        );
+
+        buffer_data[offset..offset + data.len()].copy_from_slice(data);
+    }
+
+    fn write_buffer_regions(
+        &mut self,
+        buffer_reference: BufferReference,
+        src: &[u8],
+        regions: &[BufferCopy],
+    ) {
+        for region in regions {
+            let src_range = region.src_offset as usize..(region.src_offset + region.size) as usize;
+            self.write_buffer(buffer_reference, region.dst_offset as _, &src[src_range]);
+        }
+    }
+
+    fn read_buffer(&self, buffer_reference: BufferReference, offset: usize, data: &mut [u8]) {
+        let (_, buffer_data) = self.slots.get(buffer_reference.get_key()).unwrap();
+        assert!(
+            offset + data.len() <= buffer_data.len(),
+            "Read of {} bytes at {} is out of the buffer of {} bytes",
+            data.len(),
+            offset,
+            buffer_data.len()
+        );
+
+        data.copy_from_slice(&buffer_data[offset..offset + data.len()]);
+    }
+
+    fn free_buffer(&mut self, buffer_reference: BufferReference) {
+        self.slots.remove(buffer_reference.get_key());
+    }
+
+    fn get_buffer_info(&self, buffer_reference: BufferReference) -> BufferInfo {
+        self.slots
+            .get(buffer_reference.get_key())
+            .map(|(buffer_info, _)| *buffer_info)
+            .unwrap_or_default()
+    }
+}
@@ -1,3 +1,13 @@
+pub mod compute_pass;
+pub mod depth_pyramid;
 pub mod descriptors;
+pub mod gpu_backend;
+#[cfg(test)]
+pub mod mock_gpu_backend;
 
+pub use compute_pass::*;
+pub use depth_pyramid::*;
 pub use descriptors::*;
+pub use gpu_backend::*;
+#[cfg(test)]
+pub use mock_gpu_backend::*;
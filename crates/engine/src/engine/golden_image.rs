@@ -0,0 +1,373 @@
+//! Comparison of captured frames against stored golden images, so renderer refactors can't silently
+//! change the output. Frames are captured through `FrameCapture`.
+
+use std::path::{Path, PathBuf};
+
+use image::{Rgba, RgbaImage};
+
+/// Golden images are written instead of compared, while it's set to `1`.
+pub const UPDATE_GOLDEN_IMAGES_VARIABLE: &str = "UPDATE_GOLDEN_IMAGES";
+
+#[derive(Clone, Copy, Debug)]
+pub struct ImageTolerance {
+    /// Perceptual difference of a pixel in the range from 0 to 1, where it's counted as mismatched.
+    /// It's the YIQ color distance, so the luma matters more than the chroma.
+    pub max_pixel_difference: f32,
+    /// Share of the mismatched pixels in the range from 0 to 1, the images are equal under it.
+    pub max_mismatched_pixels_ratio: f32,
+}
+
+impl Default for ImageTolerance {
+    fn default() -> Self {
+        Self {
+            max_pixel_difference: 0.02,
+            max_mismatched_pixels_ratio: 0.001,
+        }
+    }
+}
+
+pub struct ImageComparison {
+    pub mismatched_pixels_count: u32,
+    pub pixels_count: u32,
+    pub max_pixel_difference: f32,
+    /// Mismatched pixels are red on the faded expected image.
+    pub diff_image: RgbaImage,
+}
+
+impl ImageComparison {
+    #[inline(always)]
+    pub fn get_mismatched_pixels_ratio(&self) -> f32 {
+        self.mismatched_pixels_count as f32 / self.pixels_count.max(1) as f32
+    }
+
+    #[inline(always)]
+    pub fn is_within(&self, tolerance: ImageTolerance) -> bool {
+        self.get_mismatched_pixels_ratio() <= tolerance.max_mismatched_pixels_ratio
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum GoldenImageError {
+    Io(PathBuf, String),
+    /// It's written only while `UPDATE_GOLDEN_IMAGES` is set, so a typo in the path can't pass.
+    Missing(PathBuf),
+    SizeMismatch {
+        expected: (u32, u32),
+        actual: (u32, u32),
+    },
+    /// Actual and diff images are written next to the golden one.
+    Mismatch {
+        mismatched_pixels_ratio: f32,
+        max_pixel_difference: f32,
+        diff_path: PathBuf,
+    },
+}
+
+impl std::fmt::Display for GoldenImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(path, error) => write!(f, "{}: {}", path.display(), error),
+            Self::Missing(path) => write!(
+                f,
+                "golden image {} is missing, set {}=1 to write it",
+                path.display(),
+                UPDATE_GOLDEN_IMAGES_VARIABLE
+            ),
+            Self::SizeMismatch { expected, actual } => write!(
+                f,
+                "expected {}x{} image, got {}x{}",
+                expected.0, expected.1, actual.0, actual.1
+            ),
+            Self::Mismatch {
+                mismatched_pixels_ratio,
+                max_pixel_difference,
+                diff_path,
+            } => write!(
+                f,
+                "{:.3}% of pixels differ by up to {:.3}, see {}",
+                mismatched_pixels_ratio * 100.0,
+                max_pixel_difference,
+                diff_path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GoldenImageError {}
+
+/// Alpha is ignored, captures are opaque.
+pub fn compare_images(
+    actual: &RgbaImage,
+    expected: &RgbaImage,
+    tolerance: ImageTolerance,
+) -> Result<ImageComparison, GoldenImageError> {
+    if actual.dimensions() != expected.dimensions() {
+        return Err(GoldenImageError::SizeMismatch {
+            expected: expected.dimensions(),
+            actual: actual.dimensions(),
+        });
+    }
+
+    const MISMATCH_COLOR: Rgba<u8> = Rgba([255, 0, 0, 255]);
+
+    let mut mismatched_pixels_count = 0;
+    let mut max_pixel_difference: f32 = 0.0;
+    let mut diff_image = RgbaImage::new(expected.width(), expected.height());
+    for ((actual_pixel, expected_pixel), diff_pixel) in actual
+        .pixels()
+        .zip(expected.pixels())
+        .zip(diff_image.pixels_mut())
+    {
+        let pixel_difference = get_pixel_difference(*actual_pixel, *expected_pixel);
+        max_pixel_difference = max_pixel_difference.max(pixel_difference);
+
+        *diff_pixel = if pixel_difference > tolerance.max_pixel_difference {
+            mismatched_pixels_count += 1;
+
+            MISMATCH_COLOR
+        } else {
+            let [y, ..] = get_yiq(*expected_pixel);
+            let faded = (255.0 - (255.0 - y * 255.0) * 0.1) as u8;
+
+            Rgba([faded, faded, faded, 255])
+        };
+    }
+
+    Ok(ImageComparison {
+        mismatched_pixels_count,
+        pixels_count: expected.width() * expected.height(),
+        max_pixel_difference,
+        diff_image,
+    })
+}
+
+/// Golden image is written from the actual one while `UPDATE_GOLDEN_IMAGES` is set, otherwise
+/// a missing one is an error.
+/// On a mismatch `<name>.actual.png` and `<name>.diff.png` are written next to the golden image.
+pub fn assert_golden_image(
+    actual: &RgbaImage,
+    golden_path: impl AsRef<Path>,
+    tolerance: ImageTolerance,
+) -> Result<(), GoldenImageError> {
+    let is_update_requested = std::env::var(UPDATE_GOLDEN_IMAGES_VARIABLE)
+        .is_ok_and(|update_golden_images| update_golden_images == "1");
+
+    check_golden_image(actual, golden_path.as_ref(), tolerance, is_update_requested)
+}
+
+fn check_golden_image(
+    actual: &RgbaImage,
+    golden_path: &Path,
+    tolerance: ImageTolerance,
+    is_update_requested: bool,
+) -> Result<(), GoldenImageError> {
+    if is_update_requested {
+        if let Some(directory) = golden_path.parent() {
+            std::fs::create_dir_all(directory).map_err(|error| {
+                GoldenImageError::Io(directory.to_path_buf(), error.to_string())
+            })?;
+        }
+
+        return save_image(actual, golden_path);
+    }
+    if !golden_path.exists() {
+        return Err(GoldenImageError::Missing(golden_path.to_path_buf()));
+    }
+
+    let expected = image::open(golden_path)
+        .map_err(|error| GoldenImageError::Io(golden_path.to_path_buf(), error.to_string()))?
+        .into_rgba8();
+    let image_comparison = compare_images(actual, &expected, tolerance)?;
+    if image_comparison.is_within(tolerance) {
+        return Ok(());
+    }
+
+    let diff_path = golden_path.with_extension("diff.png");
+    save_image(actual, &golden_path.with_extension("actual.png"))?;
+    save_image(&image_comparison.diff_image, &diff_path)?;
+
+    Err(GoldenImageError::Mismatch {
+        mismatched_pixels_ratio: image_comparison.get_mismatched_pixels_ratio(),
+        max_pixel_difference: image_comparison.max_pixel_difference,
+        diff_path,
+    })
+}
+
+fn save_image(image: &RgbaImage, path: &Path) -> Result<(), GoldenImageError> {
+    image
+        .save(path)
+        .map_err(|error| GoldenImageError::Io(path.to_path_buf(), error.to_string()))
+}
+
+/// YIQ distance normalized by its maximum, as in "Measuring perceived color difference using
+/// YIQ NTSC transmission color space in mobile applications" by Kotsarenko and Ramos.
+fn get_pixel_difference(a: Rgba<u8>, b: Rgba<u8>) -> f32 {
+    const MAX_DELTA: f32 = 35215.0 / (255.0 * 255.0);
+
+    let [ay, ai, aq] = get_yiq(a);
+    let [by, bi, bq] = get_yiq(b);
+    let (dy, di, dq) = (ay - by, ai - bi, aq - bq);
+
+    ((0.5053 * dy * dy + 0.299 * di * di + 0.1957 * dq * dq) / MAX_DELTA).sqrt()
+}
+
+fn get_yiq(pixel: Rgba<u8>) -> [f32; 3] {
+    let [r, g, b, _] = pixel.0.map(|channel| channel as f32 / 255.0);
+
+    [
+        0.298_895_3 * r + 0.586_622_5 * g + 0.114_482_2 * b,
+        0.595_977_99 * r - 0.274_176_3 * g - 0.321_801_69 * b,
+        0.211_470_17 * r - 0.522_617_2 * g + 0.311_147_03 * b,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+    const BLACK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+    fn get_temp_path(file_name: &str) -> PathBuf {
+        std::env::temp_dir().join(std::format!("{}-{file_name}", std::process::id()))
+    }
+
+    #[test]
+    fn pixel_difference_is_normalized() {
+        assert_eq!(get_pixel_difference(WHITE, WHITE), 0.0);
+        // NOTE: Alpha is ignored.
+        assert_eq!(get_pixel_difference(WHITE, Rgba([255, 255, 255, 0])), 0.0);
+
+        let black_white_difference = get_pixel_difference(BLACK, WHITE);
+        assert!(black_white_difference > 0.9 && black_white_difference <= 1.0);
+        assert_eq!(black_white_difference, get_pixel_difference(WHITE, BLACK));
+        // NOTE: Luma is weighted more than the chroma.
+        assert!(
+            get_pixel_difference(BLACK, Rgba([0, 64, 0, 255]))
+                > get_pixel_difference(BLACK, Rgba([0, 0, 64, 255]))
+        );
+    }
+
+    #[test]
+    fn identical_images_match() {
+        let image = RgbaImage::from_pixel(8, 8, Rgba([40, 80, 120, 255]));
+
+        let image_comparison = compare_images(&image, &image, Default::default()).unwrap();
+
+        assert_eq!(image_comparison.mismatched_pixels_count, 0);
+        assert_eq!(image_comparison.pixels_count, 64);
+        assert_eq!(image_comparison.max_pixel_difference, 0.0);
+        assert!(image_comparison.is_within(Default::default()));
+    }
+
+    #[test]
+    fn single_pixel_mismatch_is_checked_against_tolerance() {
+        let expected = RgbaImage::from_pixel(10, 10, BLACK);
+        let mut actual = expected.clone();
+        actual.put_pixel(3, 4, WHITE);
+        let tolerance = ImageTolerance {
+            max_pixel_difference: 0.02,
+            max_mismatched_pixels_ratio: 0.001,
+        };
+
+        let image_comparison = compare_images(&actual, &expected, tolerance).unwrap();
+        assert_eq!(image_comparison.mismatched_pixels_count, 1);
+        assert_eq!(image_comparison.get_mismatched_pixels_ratio(), 0.01);
+        assert!(!image_comparison.is_within(tolerance));
+        assert!(image_comparison.is_within(ImageTolerance {
+            max_mismatched_pixels_ratio: 0.01,
+            ..tolerance
+        }));
+
+        // NOTE: Difference of a single level is under the pixel tolerance.
+        actual.put_pixel(3, 4, Rgba([1, 1, 1, 255]));
+        let image_comparison = compare_images(&actual, &expected, tolerance).unwrap();
+        assert_eq!(image_comparison.mismatched_pixels_count, 0);
+        assert!(image_comparison.max_pixel_difference > 0.0);
+        assert!(image_comparison.is_within(tolerance));
+    }
+
+    #[test]
+    fn size_mismatch_is_an_error() {
+        let result = compare_images(
+            &RgbaImage::new(4, 2),
+            &RgbaImage::new(2, 4),
+            Default::default(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(GoldenImageError::SizeMismatch {
+                expected: (2, 4),
+                actual: (4, 2),
+            })
+        ));
+    }
+
+    #[test]
+    fn diff_image_marks_mismatched_pixels() {
+        let mut expected = RgbaImage::from_pixel(2, 1, WHITE);
+        expected.put_pixel(1, 0, BLACK);
+        let mut actual = expected.clone();
+        actual.put_pixel(0, 0, BLACK);
+
+        let image_comparison = compare_images(&actual, &expected, Default::default()).unwrap();
+
+        // NOTE: Matched pixels are the expected luma faded to white.
+        assert_eq!(
+            *image_comparison.diff_image.get_pixel(0, 0),
+            Rgba([255, 0, 0, 255])
+        );
+        assert_eq!(
+            *image_comparison.diff_image.get_pixel(1, 0),
+            Rgba([229, 229, 229, 255])
+        );
+    }
+
+    #[test]
+    fn missing_golden_image_is_written_only_on_update() {
+        let golden_path = get_temp_path("missing_golden_image.png");
+        _ = std::fs::remove_file(&golden_path);
+        let actual = RgbaImage::from_pixel(4, 4, WHITE);
+
+        let result = check_golden_image(&actual, &golden_path, Default::default(), false);
+        assert!(matches!(result, Err(GoldenImageError::Missing(_))));
+        assert!(!golden_path.exists());
+
+        check_golden_image(&actual, &golden_path, Default::default(), true).unwrap();
+        check_golden_image(&actual, &golden_path, Default::default(), false).unwrap();
+
+        std::fs::remove_file(golden_path).unwrap();
+    }
+
+    #[test]
+    fn mismatch_writes_actual_and_diff_images() {
+        let golden_path = get_temp_path("mismatched_golden_image.png");
+        let expected = RgbaImage::from_pixel(4, 4, WHITE);
+        check_golden_image(&expected, &golden_path, Default::default(), true).unwrap();
+
+        let result = check_golden_image(
+            &RgbaImage::from_pixel(4, 4, BLACK),
+            &golden_path,
+            Default::default(),
+            false,
+        );
+
+        let Err(GoldenImageError::Mismatch {
+            mismatched_pixels_ratio,
+            diff_path,
+            ..
+        }) = result
+        else {
+            panic!("Frame has to mismatch");
+        };
+        assert_eq!(mismatched_pixels_ratio, 1.0);
+        let actual_path = golden_path.with_extension("actual.png");
+        assert!(actual_path.exists() && diff_path.exists());
+
+        for path in [golden_path, actual_path, diff_path] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
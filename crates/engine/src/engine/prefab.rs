@@ -0,0 +1,386 @@
+use bevy_ecs::{
+    bundle::Bundle,
+    entity::{Entity, EntityCloner},
+    entity_disabling::Disabled,
+    hierarchy::{ChildOf, Children},
+    system::{Command, Commands},
+    world::World,
+};
+use math::{Quat, Vec3};
+
+use crate::engine::components::local_transform::LocalTransform;
+
+/// Disabled hierarchy, that serves as a template for clones, e.g. root of a loaded model.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Prefab {
+    root: Entity,
+}
+
+impl Prefab {
+    /// Disables the hierarchy of the root, so the template itself is never rendered or simulated.
+    pub fn register(commands: &mut Commands, root: Entity) -> Self {
+        commands.entity(root).insert(Disabled);
+
+        Self { root }
+    }
+
+    pub fn get_root(&self) -> Entity {
+        self.root
+    }
+}
+
+/// Fields that are `None` are taken from the prefab root.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct TransformOverride {
+    pub position: Option<Vec3>,
+    pub rotation: Option<Quat>,
+    pub scale: Option<Vec3>,
+}
+
+impl TransformOverride {
+    pub fn new(position: Vec3, rotation: Quat, scale: Vec3) -> Self {
+        Self {
+            position: Some(position),
+            rotation: Some(rotation),
+            scale: Some(scale),
+        }
+    }
+
+    pub fn apply(&self, local_transform: &mut LocalTransform) {
+        if let Some(position) = self.position {
+            local_transform.local_position = position;
+        }
+        if let Some(rotation) = self.rotation {
+            local_transform.local_rotation = rotation;
+        }
+        if let Some(scale) = self.scale {
+            local_transform.local_scale = scale;
+        }
+    }
+}
+
+/// Root entity is reserved right away, the hierarchy is cloned into it when commands are applied.
+pub fn spawn_prefab(
+    commands: &mut Commands,
+    prefab: Prefab,
+    transform_override: TransformOverride,
+) -> Entity {
+    let target = commands.spawn_empty().id();
+
+    commands.queue(SpawnPrefabCommand {
+        source: prefab.root,
+        target,
+        transform_override,
+    });
+
+    target
+}
+
+struct SpawnPrefabCommand {
+    source: Entity,
+    target: Entity,
+    transform_override: TransformOverride,
+}
+
+impl Command for SpawnPrefabCommand {
+    fn apply(self, world: &mut World) {
+        let mut entity_cloner_builder = EntityCloner::build_opt_out(world);
+        entity_cloner_builder.linked_cloning(true);
+        let mut entity_cloner = entity_cloner_builder.finish();
+
+        entity_cloner.clone_entity(world, self.source, self.target);
+
+        let mut entity = world.entity_mut(self.target);
+        if let Some(mut local_transform) = entity.get_mut::<LocalTransform>() {
+            self.transform_override.apply(&mut local_transform);
+        }
+
+        entity.remove_recursive::<Children, Disabled>();
+    }
+}
+
+pub fn spawn_prefab_batch(
+    commands: &mut Commands,
+    prefab: Prefab,
+    transform_overrides: &[TransformOverride],
+) {
+    spawn_prefab_batch_with(
+        commands,
+        prefab,
+        transform_overrides
+            .iter()
+            .map(|&transform_override| (transform_override, ()))
+            .collect(),
+    );
+}
+
+/// Same as `spawn_prefab` for every instance, but every node of the hierarchy is spawned for all
+/// the instances at once and parented in bulk. Bundles are inserted into the roots.
+pub fn spawn_prefab_batch_with<B: Bundle>(
+    commands: &mut Commands,
+    prefab: Prefab,
+    instances: Vec<(TransformOverride, B)>,
+) {
+    commands.queue(SpawnPrefabBatchCommand {
+        source: prefab.root,
+        instances,
+    });
+}
+
+struct SpawnPrefabBatchCommand<B: Bundle> {
+    source: Entity,
+    instances: Vec<(TransformOverride, B)>,
+}
+
+/// Node of the prefab hierarchy, parents go before their children.
+struct PrefabNode {
+    source: Entity,
+    parent_index: Option<usize>,
+}
+
+impl<B: Bundle> SpawnPrefabBatchCommand<B> {
+    fn get_prefab_nodes(&self, world: &World) -> Vec<PrefabNode> {
+        let mut prefab_nodes = vec![PrefabNode {
+            source: self.source,
+            parent_index: None,
+        }];
+
+        let mut node_index = Default::default();
+        while node_index < prefab_nodes.len() {
+            if let Some(children) = world.get::<Children>(prefab_nodes[node_index].source) {
+                prefab_nodes.extend(children.iter().map(|child| PrefabNode {
+                    source: child,
+                    parent_index: Some(node_index),
+                }));
+            }
+
+            node_index += 1;
+        }
+
+        prefab_nodes
+    }
+}
+
+impl<B: Bundle> Command for SpawnPrefabBatchCommand<B> {
+    fn apply(self, world: &mut World) {
+        let prefab_nodes = self.get_prefab_nodes(world);
+
+        // NOTE: Hierarchy is wired by hand, so the cloner copies only the node's own components.
+        let mut entity_cloner_builder = EntityCloner::build_opt_out(world);
+        entity_cloner_builder.deny::<(ChildOf, Children, Disabled)>();
+        let mut entity_cloner = entity_cloner_builder.finish();
+
+        let mut nodes_entities: Vec<Vec<Entity>> = Vec::with_capacity(prefab_nodes.len());
+        for prefab_node in prefab_nodes {
+            let node_entities = world
+                .spawn_batch(std::iter::repeat_n((), self.instances.len()))
+                .collect::<Vec<_>>();
+            for &node_entity in &node_entities {
+                entity_cloner.clone_entity(world, prefab_node.source, node_entity);
+            }
+
+            if let Some(parent_index) = prefab_node.parent_index {
+                world.insert_batch(
+                    node_entities
+                        .iter()
+                        .zip(&nodes_entities[parent_index])
+                        .map(|(&node_entity, &parent)| (node_entity, ChildOf(parent))),
+                );
+            }
+
+            nodes_entities.push(node_entities);
+        }
+
+        for (&root, (transform_override, bundle)) in nodes_entities[0].iter().zip(self.instances) {
+            let mut entity = world.entity_mut(root);
+            if let Some(mut local_transform) = entity.get_mut::<LocalTransform>() {
+                transform_override.apply(&mut local_transform);
+            }
+            entity.insert(bundle);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::{
+        component::Component,
+        entity::Entity,
+        entity_disabling::Disabled,
+        hierarchy::{ChildOf, Children},
+        name::Name,
+        world::World,
+    };
+    use math::{Quat, Vec3};
+
+    use super::{Prefab, TransformOverride, spawn_prefab_batch, spawn_prefab_batch_with};
+    use crate::engine::components::local_transform::LocalTransform;
+
+    #[derive(Component, Clone, Copy, PartialEq, Debug)]
+    struct Health(u32);
+
+    #[derive(Component, Clone, Copy, PartialEq, Debug)]
+    struct InstanceIndex(usize);
+
+    fn spawn_template(world: &mut World) -> Prefab {
+        let root = world
+            .spawn((Name::new("Root"), LocalTransform::IDENTITY, Health(7)))
+            .id();
+        world.spawn((
+            Name::new("Child"),
+            LocalTransform::IDENTITY,
+            Health(3),
+            ChildOf(root),
+        ));
+
+        let prefab = Prefab::register(&mut world.commands(), root);
+        world.flush();
+
+        prefab
+    }
+
+    #[test]
+    fn batch_clones_every_component_of_the_hierarchy() {
+        let mut world = World::new();
+        let prefab = spawn_template(&mut world);
+
+        let positions = [Vec3::new(1.0, 2.0, 3.0), Vec3::new(-4.0, 5.0, -6.0)];
+        spawn_prefab_batch_with(
+            &mut world.commands(),
+            prefab,
+            positions
+                .iter()
+                .enumerate()
+                .map(|(instance_index, &position)| {
+                    (
+                        TransformOverride {
+                            position: Some(position),
+                            ..Default::default()
+                        },
+                        InstanceIndex(instance_index),
+                    )
+                })
+                .collect(),
+        );
+        world.flush();
+
+        let mut roots: Vec<Entity> = world
+            .query::<(Entity, &InstanceIndex)>()
+            .iter(&world)
+            .map(|(entity, _)| entity)
+            .collect();
+        roots.sort_by_key(|&root| world.get::<InstanceIndex>(root).unwrap().0);
+        assert_eq!(roots.len(), positions.len());
+
+        for (instance_index, (&root, position)) in roots.iter().zip(positions).enumerate() {
+            let root_ref = world.entity(root);
+            assert_ne!(root, prefab.get_root());
+            assert!(!root_ref.contains::<Disabled>());
+            assert_eq!(root_ref.get::<Health>(), Some(&Health(7)));
+            assert_eq!(
+                root_ref.get::<InstanceIndex>(),
+                Some(&InstanceIndex(instance_index))
+            );
+            assert_eq!(
+                root_ref.get::<LocalTransform>().unwrap().local_position,
+                position
+            );
+
+            let children = root_ref.get::<Children>().unwrap();
+            assert_eq!(children.len(), 1);
+            let child_ref = world.entity(children[0]);
+            assert!(!child_ref.contains::<Disabled>());
+            assert_eq!(child_ref.get::<Health>(), Some(&Health(3)));
+            assert_eq!(child_ref.get::<Name>().unwrap().as_str(), "Child");
+        }
+
+        // NOTE: Template keeps its hierarchy and stays disabled.
+        let template_ref = world.entity(prefab.get_root());
+        assert!(template_ref.contains::<Disabled>());
+        assert_eq!(template_ref.get::<Children>().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn batch_keeps_the_hierarchy_shape_of_every_instance() {
+        let mut world = World::new();
+        let root = world
+            .spawn((Name::new("Root"), LocalTransform::IDENTITY))
+            .id();
+        let arm = world.spawn((Name::new("Arm"), ChildOf(root))).id();
+        world.spawn((Name::new("Hand"), Health(1), ChildOf(arm)));
+        world.spawn((Name::new("Leg"), ChildOf(root)));
+        let prefab = Prefab::register(&mut world.commands(), root);
+        world.flush();
+
+        let transform_overrides = [
+            TransformOverride::new(Vec3::X, Quat::from_rotation_y(1.0), Vec3::splat(2.0)),
+            TransformOverride {
+                scale: Some(Vec3::splat(0.5)),
+                ..Default::default()
+            },
+            TransformOverride {
+                position: Some(Vec3::new(0.0, -7.0, 3.0)),
+                ..Default::default()
+            },
+        ];
+        spawn_prefab_batch(&mut world.commands(), prefab, &transform_overrides);
+        world.flush();
+
+        let mut roots: Vec<Entity> = world
+            .query::<(Entity, &Name)>()
+            .iter(&world)
+            .filter(|&(entity, name)| entity != root && name.as_str() == "Root")
+            .map(|(entity, _)| entity)
+            .collect();
+        roots.sort();
+        assert_eq!(roots.len(), transform_overrides.len());
+        // NOTE: Enabled template nodes and four nodes of every instance.
+        assert_eq!(
+            world.query::<&Name>().iter(&world).count(),
+            3 + 4 * transform_overrides.len()
+        );
+
+        for (&root, transform_override) in roots.iter().zip(transform_overrides) {
+            let root_ref = world.entity(root);
+            assert!(!root_ref.contains::<Disabled>());
+            assert!(!root_ref.contains::<ChildOf>());
+
+            let mut expected_transform = LocalTransform::IDENTITY;
+            transform_override.apply(&mut expected_transform);
+            let local_transform = root_ref.get::<LocalTransform>().unwrap();
+            assert_eq!(
+                local_transform.local_position,
+                expected_transform.local_position
+            );
+            assert_eq!(
+                local_transform.local_rotation,
+                expected_transform.local_rotation
+            );
+            assert_eq!(local_transform.local_scale, expected_transform.local_scale);
+
+            let children = root_ref.get::<Children>().unwrap();
+            let child_names: Vec<&str> = children
+                .iter()
+                .map(|child| world.get::<Name>(child).unwrap().as_str())
+                .collect();
+            assert_eq!(child_names, ["Arm", "Leg"]);
+
+            for child in children.iter() {
+                assert_eq!(world.get::<ChildOf>(child).unwrap().0, root);
+                assert!(!world.entity(child).contains::<Disabled>());
+            }
+
+            let arm_children = world.get::<Children>(children[0]).unwrap();
+            assert_eq!(arm_children.len(), 1);
+            let hand_ref = world.entity(arm_children[0]);
+            assert_eq!(hand_ref.get::<Name>().unwrap().as_str(), "Hand");
+            assert_eq!(hand_ref.get::<Health>(), Some(&Health(1)));
+            assert!(world.get::<Children>(children[1]).is_none());
+        }
+
+        // NOTE: Template keeps only its own children.
+        let template_children = world.get::<Children>(root).unwrap();
+        assert_eq!(template_children.len(), 2);
+        assert!(world.entity(root).contains::<Disabled>());
+    }
+}
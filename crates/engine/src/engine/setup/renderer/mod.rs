@@ -1 +1,2 @@
 pub mod prepare;
+pub mod recreate_swapchain;
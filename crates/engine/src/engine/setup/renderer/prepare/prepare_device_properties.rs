@@ -16,8 +16,22 @@ impl Engine {
             PhysicalDeviceDescriptorBufferPropertiesEXT,
         ) = vulkan_context_resource.physical_device.get_properties2();
 
+        let properties = vulkan_context_resource.physical_device.get_properties();
+        let memory_properties = vulkan_context_resource
+            .physical_device
+            .get_memory_properties();
+        let memory_type_flags = memory_properties.memory_types
+            [..memory_properties.memory_type_count as usize]
+            .iter()
+            .map(|memory_type| memory_type.property_flags)
+            .collect();
+
         DevicePropertiesResource {
             descriptor_buffer_properties,
+            max_sampler_anisotropy: properties.limits.max_sampler_anisotropy,
+            max_push_constants_size: properties.limits.max_push_constants_size,
+            timestamp_period: properties.limits.timestamp_period,
+            memory_type_flags,
         }
     }
 }
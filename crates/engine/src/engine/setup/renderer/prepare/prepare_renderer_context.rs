@@ -1,44 +1,45 @@
 use bevy_ecs::world::World;
 use vulkanite::vk::{rs::*, *};
-use winit::window::Window;
+use winit::dpi::PhysicalSize;
 
 use crate::engine::{
     Engine,
-    resources::{CommandGroup, FrameData, RendererContext, UploadContext, VulkanContextResource},
+    resources::{
+        AsyncComputeCommandGroup, CommandGroup, FrameData, RendererContext, UploadContext,
+        VulkanContextResource,
+    },
 };
 
+/// Frames in flight if the config doesn't set them, it's clamped to the swapchain images count.
+const DEFAULT_FRAME_OVERLAP: usize = 2;
+
 impl Engine {
-    pub(crate) fn create_renderer_context(window: &dyn Window, world: &World) -> RendererContext {
+    pub(crate) fn create_renderer_context(
+        surface_size: PhysicalSize<u32>,
+        world: &World,
+        frame_overlap: Option<usize>,
+    ) -> RendererContext {
         let vulkan_context_resource = world.get_resource_ref::<VulkanContextResource>().unwrap();
-        let device = vulkan_context_resource.device;
-        let swapchain = &vulkan_context_resource.swapchain;
 
-        let images: Vec<Image> = device.get_swapchain_images_khr(*swapchain).unwrap();
-        let image_views: Vec<ImageView> = images
-            .iter()
-            .map(|img| {
-                device
-                    .create_image_view(
-                        &ImageViewCreateInfo::default()
-                            .image(img)
-                            .view_type(ImageViewType::Type2D)
-                            .format(vulkan_context_resource.surface_format.format)
-                            .subresource_range(ImageSubresourceRange {
-                                aspect_mask: ImageAspectFlags::Color,
-                                base_mip_level: 0,
-                                level_count: 1,
-                                base_array_layer: 0,
-                                layer_count: 1,
-                            }),
-                    )
-                    .unwrap()
-            })
-            .collect();
-        let frame_overlap = image_views.len();
+        let (images, image_views) = Self::create_swapchain_image_views(&vulkan_context_resource);
+        // NOTE: Driver may return more images than requested, they don't add frames in flight.
+        let frame_overlap = frame_overlap.unwrap_or(DEFAULT_FRAME_OVERLAP);
+        let frame_overlap = match vulkan_context_resource.swapchain {
+            Some(_) => frame_overlap.min(image_views.len()).max(1),
+            None => frame_overlap.max(1),
+        };
 
         let command_pool_info = CommandPoolCreateInfo::default()
             .flags(CommandPoolCreateFlags::ResetCommandBuffer)
             .queue_family_index(vulkan_context_resource.queue_family_index as _);
+        let compute_command_pool_info =
+            vulkan_context_resource
+                .compute_queue_family_index
+                .map(|compute_queue_family_index| {
+                    CommandPoolCreateInfo::default()
+                        .flags(CommandPoolCreateFlags::ResetCommandBuffer)
+                        .queue_family_index(compute_queue_family_index as _)
+                });
 
         let device = &vulkan_context_resource.device;
         let frames_data = (0..frame_overlap)
@@ -48,19 +49,42 @@ impl Engine {
                 let command_buffer_allocate_info = CommandBufferAllocateInfo::default()
                     .command_pool(&command_pool)
                     .level(vulkanite::vk::CommandBufferLevel::Primary)
-                    .command_buffer_count(1);
+                    .command_buffer_count(2);
 
+                // NOTE: Second one submits the blit to the swapchain, it's used only with the async compute.
                 let command_buffers: Vec<CommandBuffer> = device
                     .allocate_command_buffers(&command_buffer_allocate_info)
                     .unwrap();
                 let command_buffer = command_buffers[0];
 
+                let async_compute_command_group =
+                    compute_command_pool_info.map(|compute_command_pool_info| {
+                        let compute_command_pool = device
+                            .create_command_pool(&compute_command_pool_info)
+                            .unwrap();
+
+                        let compute_command_buffer_allocate_info =
+                            CommandBufferAllocateInfo::default()
+                                .command_pool(&compute_command_pool)
+                                .level(vulkanite::vk::CommandBufferLevel::Primary)
+                                .command_buffer_count(1);
+
+                        let compute_command_buffers: Vec<CommandBuffer> = device
+                            .allocate_command_buffers(&compute_command_buffer_allocate_info)
+                            .unwrap();
+
+                        AsyncComputeCommandGroup {
+                            command_pool: compute_command_pool,
+                            command_buffer: compute_command_buffers[0],
+                            present_command_buffer: command_buffers[1],
+                        }
+                    });
+
                 let fence_info = FenceCreateInfo::default().flags(FenceCreateFlags::Signaled);
                 let render_fence = device.create_fence(&fence_info).unwrap();
 
                 let semaphore_create_info = SemaphoreCreateInfo::default();
                 let swapchain_semaphore = device.create_semaphore(&semaphore_create_info).unwrap();
-                let render_semaphore = device.create_semaphore(&semaphore_create_info).unwrap();
 
                 let command_group = CommandGroup {
                     command_pool,
@@ -70,14 +94,25 @@ impl Engine {
                 FrameData {
                     command_group,
                     swapchain_semaphore,
-                    render_semaphore,
+                    is_swapchain_semaphore_pending: Default::default(),
                     draw_texture_reference: Default::default(),
                     depth_texture_reference: Default::default(),
+                    light_clusters_buffer_reference: Default::default(),
+                    foliage_instances_buffer_reference: Default::default(),
+                    foliage_draw_arguments_buffer_reference: Default::default(),
+                    visibility_query_results_buffer_reference: Default::default(),
+                    visibility_query_entities: Default::default(),
+                    depth_picks_buffer_reference: Default::default(),
+                    depth_pick_positions: Default::default(),
+                    texture_usage_counters_buffer_reference: Default::default(),
+                    is_texture_usage_counted: Default::default(),
+                    depth_pyramid: Default::default(),
+                    async_compute_command_group,
                 }
             })
             .collect();
+        let render_semaphores = Self::create_render_semaphores(device, images.len());
 
-        let surface_size = window.surface_size();
         let draw_extent = Extent2D {
             width: surface_size.width,
             height: surface_size.height,
@@ -98,6 +133,16 @@ impl Engine {
             .unwrap();
         let command_buffer = command_buffers[0];
 
+        let async_compute_semaphore = vulkan_context_resource.compute_queue.map(|_| {
+            let mut semaphore_type_create_info = SemaphoreTypeCreateInfo::default()
+                .semaphore_type(SemaphoreType::Timeline)
+                .initial_value(Default::default());
+            let semaphore_create_info =
+                SemaphoreCreateInfo::default().push_next(&mut semaphore_type_create_info);
+
+            device.create_semaphore(&semaphore_create_info).unwrap()
+        });
+
         let upload_context = UploadContext {
             command_group: CommandGroup {
                 command_pool,
@@ -109,11 +154,57 @@ impl Engine {
         RendererContext {
             images,
             image_views,
+            render_semaphores,
             frame_overlap,
             draw_extent,
+            render_extent: draw_extent,
             frames_data,
             frame_number: Default::default(),
             upload_context,
+            swapchain_status: Default::default(),
+            async_compute_semaphore,
         }
     }
+
+    pub(crate) fn create_swapchain_image_views(
+        vulkan_context_resource: &VulkanContextResource,
+    ) -> (Vec<Image>, Vec<ImageView>) {
+        let device = vulkan_context_resource.device;
+        // NOTE: Headless engine has no swapchain images, the frame ends in the draw texture.
+        let Some(swapchain) = vulkan_context_resource.swapchain else {
+            return Default::default();
+        };
+
+        let images: Vec<Image> = device.get_swapchain_images_khr(swapchain).unwrap();
+        let image_views: Vec<ImageView> = images
+            .iter()
+            .map(|img| {
+                device
+                    .create_image_view(
+                        &ImageViewCreateInfo::default()
+                            .image(img)
+                            .view_type(ImageViewType::Type2D)
+                            .format(vulkan_context_resource.surface_format.format)
+                            .subresource_range(ImageSubresourceRange {
+                                aspect_mask: ImageAspectFlags::Color,
+                                base_mip_level: 0,
+                                level_count: 1,
+                                base_array_layer: 0,
+                                layer_count: 1,
+                            }),
+                    )
+                    .unwrap()
+            })
+            .collect();
+
+        (images, image_views)
+    }
+
+    pub(crate) fn create_render_semaphores(device: &Device, images_count: usize) -> Vec<Semaphore> {
+        let semaphore_create_info = SemaphoreCreateInfo::default();
+
+        (0..images_count)
+            .map(|_| device.create_semaphore(&semaphore_create_info).unwrap())
+            .collect()
+    }
 }
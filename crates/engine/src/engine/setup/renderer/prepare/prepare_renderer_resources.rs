@@ -3,11 +3,13 @@ use vulkanite::vk::{rs::Device, *};
 
 use crate::engine::{
     Engine,
-    ecs::{audio::Audio, mesh_buffers_pool::MeshBuffersPool},
+    ecs::{
+        audio::{Audio, AudioVolumes},
+        mesh_buffers_pool::MeshBuffersPool,
+    },
     general::renderer::{DescriptorSetBuilder, DescriptorSetHandle},
     resources::{
-        buffers_pool::BuffersPool, model_loader::ModelLoader, samplers_pool::SamplersPool,
-        textures_pool::TexturesPool, *,
+        buffers_pool::BuffersPool, samplers_pool::SamplersPool, textures_pool::TexturesPool, *,
     },
 };
 
@@ -18,9 +20,21 @@ impl Engine {
         let device_properties_resource = world
             .get_resource_ref::<DevicePropertiesResource>()
             .unwrap();
+        let device_capabilities_resource = world
+            .get_resource_ref::<DeviceCapabilitiesResource>()
+            .unwrap();
 
         let resources_pool = ResourcesPool::new();
 
+        let push_constant_layout =
+            PushConstantLayout::new(device_properties_resource.max_push_constants_size);
+        if push_constant_layout.is_overflowing() {
+            println!(
+                "Push constants don't fit into {} bytes, moving the overflow into a uniform buffer.",
+                device_properties_resource.max_push_constants_size
+            );
+        }
+
         let upload_command_group = render_context.upload_context.command_group;
 
         let device = vulkan_context.device;
@@ -32,13 +46,30 @@ impl Engine {
             default_sampler_reference: Default::default(),
             mesh_objects_buffer_reference: Default::default(),
             gradient_compute_shader_object: Default::default(),
+            luminance_histogram_compute_shader_object: Default::default(),
+            exposure_adaptation_compute_shader_object: Default::default(),
+            tonemap_compute_shader_object: Default::default(),
+            post_effects_compute_shader_object: Default::default(),
+            depth_of_field_compute_shader_object: Default::default(),
+            screen_space_reflections_compute_shader_object: Default::default(),
+            light_culling_compute_shader_object: Default::default(),
+            water_compute_shader_object: Default::default(),
+            foliage_scatter_compute_shader_object: Default::default(),
+            visibility_queries_compute_shader_object: Default::default(),
+            light_shafts_compute_shader_object: Default::default(),
+            #[cfg(feature = "debug-tools")]
+            debug_overlay_compute_shader_object: Default::default(),
+            depth_pyramid_compute_shader_object: Default::default(),
+            depth_picks_compute_shader_object: Default::default(),
+            outlines_compute_shader_object: Default::default(),
             task_shader_object: Default::default(),
             mesh_shader_object: Default::default(),
             fragment_shader_object: Default::default(),
-            model_loader: ModelLoader::new(),
+            shader_objects: Default::default(),
             resources_pool,
-            is_printed_scene_hierarchy: true,
+            push_constant_layout,
             materials_data_buffer_reference: Default::default(),
+            exposure_state_buffer_reference: Default::default(),
         };
 
         let mut buffers_pool = BuffersPool::new(
@@ -46,9 +77,25 @@ impl Engine {
             allocator,
             upload_command_group,
             vulkan_context.transfer_queue,
+            std::iter::once(vulkan_context.queue_family_index)
+                .chain(vulkan_context.compute_queue_family_index)
+                .map(|queue_family_index| queue_family_index as _)
+                .collect(),
         );
-        let textures_pool = TexturesPool::new(device, vulkan_context.allocator);
-        let samplers_pool = SamplersPool::new(device);
+        let textures_pool = TexturesPool::new(
+            device,
+            vulkan_context.physical_device,
+            vulkan_context.allocator,
+            device_capabilities_resource.is_host_image_copy_supported,
+        );
+        // NOTE: Max anisotropy of 1.0 disables it for every sampler.
+        let max_sampler_anisotropy = if device_capabilities_resource.is_sampler_anisotropy_supported
+        {
+            device_properties_resource.max_sampler_anisotropy
+        } else {
+            1.0
+        };
+        let samplers_pool = SamplersPool::new(device, max_sampler_anisotropy);
         let mesh_buffers_pool = MeshBuffersPool::new(5_120);
 
         let push_constant_range = PushConstantRange {
@@ -57,7 +104,7 @@ impl Engine {
                 | ShaderStageFlags::Compute
                 | ShaderStageFlags::TaskEXT,
             offset: Default::default(),
-            size: std::mem::size_of::<GraphicsPushConstant>() as _,
+            size: push_constant_layout.get_pushed_size(),
         };
 
         let push_constant_ranges = [push_constant_range];
@@ -78,6 +125,7 @@ impl Engine {
         world.insert_resource(textures_pool);
         world.insert_resource(mesh_buffers_pool);
         world.insert_resource(audio);
+        world.init_resource::<AudioVolumes>();
     }
 
     fn create_descriptor_set_handle(
@@ -91,7 +139,7 @@ impl Engine {
         DescriptorSetBuilder::new()
             .add_binding(
                 DescriptorType::Sampler,
-                16,
+                SamplersPool::MAX_SAMPLERS_COUNT as _,
                 DescriptorBindingFlags::PartiallyBound,
             )
             // Storage Images (aka Draw Image)
@@ -100,14 +148,17 @@ impl Engine {
                 2048,
                 DescriptorBindingFlags::PartiallyBound,
             )
-            // Sampled Images (aka Textures), we can resize count of descriptors, we pre-alllocate N descriptors,
-            // but we specify that count as unbound (aka variable)
+            // Sampled Images (aka Textures), the layout has the upper bound of the variable count,
+            // descriptor buffer starts smaller and grows when a texture index doesn't fit.
             .add_binding(
                 DescriptorType::SampledImage,
-                30_000,
+                DescriptorSetHandle::MAX_SAMPLED_IMAGES_COUNT,
                 DescriptorBindingFlags::PartiallyBound
                     | DescriptorBindingFlags::VariableDescriptorCount,
             )
+            .with_initial_variable_descriptors_count(
+                DescriptorSetHandle::INITIAL_SAMPLED_IMAGES_COUNT,
+            )
             .build(
                 device,
                 allocator,
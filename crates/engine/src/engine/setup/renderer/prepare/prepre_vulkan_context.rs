@@ -5,19 +5,24 @@ use vma::{Allocator, AllocatorCreateFlags, AllocatorCreateInfo};
 use vulkanite::{
     DefaultAllocator, Dispatcher, DynamicDispatcher, flagbits, structure_chain,
     vk::{
-        self, EXT_DESCRIPTOR_BUFFER, EXT_MESH_SHADER, EXT_SHADER_OBJECT, KHR_UNIFIED_IMAGE_LAYOUTS,
-        PhysicalDeviceDescriptorBufferFeaturesEXT, PhysicalDeviceMeshShaderFeaturesEXT,
+        self, EXT_DESCRIPTOR_BUFFER, EXT_MESH_SHADER, EXT_PRESENT_MODE_FIFO_LATEST_READY,
+        EXT_SHADER_OBJECT, KHR_UNIFIED_IMAGE_LAYOUTS, PhysicalDeviceDescriptorBufferFeaturesEXT,
+        PhysicalDeviceMeshShaderFeaturesEXT, PhysicalDevicePresentModeFifoLatestReadyFeaturesEXT,
         PhysicalDeviceRobustness2FeaturesKHR, PhysicalDeviceShaderObjectFeaturesEXT,
         PhysicalDeviceUnifiedImageLayoutsFeaturesKHR, PhysicalDeviceVulkan11Features,
-        PhysicalDeviceVulkan12Features, PhysicalDeviceVulkan13Features, SurfaceFormatKHR,
-        ValidationFeatureEnableEXT, ValidationFeaturesEXT,
+        PhysicalDeviceVulkan12Features, PhysicalDeviceVulkan13Features,
+        PhysicalDeviceVulkan14Features, SurfaceFormatKHR, ValidationFeatureEnableEXT,
+        ValidationFeaturesEXT,
         rs::{PhysicalDevice, SwapchainKHR},
     },
     window,
 };
 use winit::{dpi::PhysicalSize, window::Window};
 
-use crate::engine::{Engine, resources::VulkanContextResource};
+use crate::engine::{
+    Engine,
+    resources::{DeviceCapabilitiesResource, EngineConfig, LatencyMode, VulkanContextResource},
+};
 
 extern "system" fn debug_callback(
     severity: vk::DebugUtilsMessageSeverityFlagsEXT,
@@ -134,38 +139,77 @@ extern "system" fn debug_callback(
 }
 
 impl Engine {
-    pub(crate) fn create_vulkan_context(window: &dyn Window) -> VulkanContextResource {
+    /// Without the window there is no surface and swapchain, see `Engine::new_headless`.
+    pub(crate) fn create_vulkan_context(
+        window: Option<&dyn Window>,
+        surface_size: PhysicalSize<u32>,
+        engine_config: &EngineConfig,
+    ) -> (VulkanContextResource, DeviceCapabilitiesResource) {
         let dispatcher = unsafe { DynamicDispatcher::new_loaded().unwrap() };
         let entry = vk::rs::Entry::new(dispatcher, DefaultAllocator);
-        let (instance, debug_utils_messenger) = Self::create_instance(
-            true,
-            &entry,
-            &window
+        let display_handle = window.map(|window| {
+            window
                 .rwh_06_display_handle()
                 .display_handle()
                 .unwrap()
-                .as_raw(),
-        );
-
-        let surface = window::rs::create_surface(
+                .as_raw()
+        });
+        let (instance, debug_utils_messenger) =
+            Self::create_instance(engine_config.validation, &entry, display_handle.as_ref());
+
+        let surface = window.map(|window| {
+            window::rs::create_surface(
+                &instance,
+                &window.display_handle().unwrap().as_raw(),
+                &window.window_handle().unwrap().as_raw(),
+            )
+            .unwrap()
+        });
+        let (
+            physical_device,
+            device,
+            queue_family_index,
+            graphics_queue,
+            transfer_queue,
+            compute_queue,
+            device_capabilities,
+        ) = Self::create_device(
             &instance,
-            &window.display_handle().unwrap().as_raw(),
-            &window.window_handle().unwrap().as_raw(),
-        )
-        .unwrap();
-        let (physical_device, device, queue_family_index, graphics_queue, transfer_queue) =
-            Self::create_device(&instance, &surface);
+            surface.as_ref(),
+            engine_config.async_compute,
+            engine_config.low_latency,
+        );
+        let (compute_queue_family_index, compute_queue) = compute_queue.unzip();
 
         let mut allocator_create_info =
             AllocatorCreateInfo::new(&instance, &device, &physical_device, &dispatcher);
         allocator_create_info.flags |= AllocatorCreateFlags::bufferDeviceAddress;
         let allocator = unsafe { Allocator::new(allocator_create_info).unwrap() };
 
-        let surface_size = window.surface_size();
-        let (swapchain, surface_format) =
-            Self::create_swapchain(&physical_device, &device, &surface, surface_size);
+        let (swapchain, surface_format) = match surface.as_ref() {
+            Some(surface) => {
+                let (swapchain, surface_format, _) = Self::create_swapchain(
+                    &physical_device,
+                    &device,
+                    surface,
+                    surface_size,
+                    engine_config.vsync,
+                    engine_config.get_latency_mode(),
+                    &device_capabilities,
+                );
+
+                (Some(swapchain), surface_format)
+            }
+            None => (
+                None,
+                SurfaceFormatKHR {
+                    format: vk::Format::B8G8R8A8Srgb,
+                    color_space: vk::ColorSpaceKHR::SrgbNonlinear,
+                },
+            ),
+        };
 
-        VulkanContextResource {
+        let vulkan_context_resource = VulkanContextResource {
             instance,
             debug_utils_messenger,
             surface,
@@ -175,29 +219,51 @@ impl Engine {
             graphics_queue,
             transfer_queue,
             queue_family_index,
+            compute_queue,
+            compute_queue_family_index,
             swapchain,
             surface_format,
-        }
+        };
+
+        (vulkan_context_resource, device_capabilities)
     }
 
     pub fn create_instance(
-        _do_enable_validation_layers: bool,
+        do_enable_validation_layers: bool,
         entry: &vk::rs::Entry,
-        display_handle: &RawDisplayHandle,
+        display_handle: Option<&RawDisplayHandle>,
     ) -> (vk::rs::Instance, Option<vk::rs::DebugUtilsMessengerEXT>) {
         const VALIDATION_LAYER: &CStr = c"VK_LAYER_KHRONOS_validation";
         let layers: Vec<_> = entry.enumerate_instance_layer_properties().unwrap();
-        let has_validation = layers
-            .into_iter()
-            .any(|layer| layer.get_layer_name() == VALIDATION_LAYER);
+        let has_validation = do_enable_validation_layers
+            && layers
+                .into_iter()
+                .any(|layer| layer.get_layer_name() == VALIDATION_LAYER);
         let enabled_layers = has_validation.then_some(VALIDATION_LAYER.as_ptr());
 
+        // NOTE: Surface extensions are needed only to present to the window.
+        let mut enabled_extensions = display_handle
+            .map(|display_handle| {
+                Vec::from(window::enumerate_required_extensions(display_handle).unwrap())
+            })
+            .unwrap_or_default();
         // enable VK_EXT_debug_utils only if the validation layer is enabled
-        let mut enabled_extensions =
-            Vec::from(window::enumerate_required_extensions(display_handle).unwrap());
         if has_validation {
             enabled_extensions.push(vk::EXT_DEBUG_UTILS.name);
         }
+        // NOTE: Portability implementations like MoltenVK are enumerated only when they're asked for.
+        let instance_extension_props = entry
+            .enumerate_instance_extension_properties::<Vec<_>>(None)
+            .unwrap();
+        let is_portability_enumeration_supported =
+            instance_extension_props.iter().any(|extension_prop| {
+                extension_prop.get_extension_name() == vk::KHR_PORTABILITY_ENUMERATION.name.get()
+            });
+        let mut instance_create_flags = vk::InstanceCreateFlags::empty();
+        if is_portability_enumeration_supported {
+            enabled_extensions.push(vk::KHR_PORTABILITY_ENUMERATION.name);
+            instance_create_flags |= vk::InstanceCreateFlags::EnumeratePortabilityKHR;
+        }
 
         let app_info = vk::ApplicationInfo::default()
             .application_name(Some(c"Hello Triangle"))
@@ -214,6 +280,7 @@ impl Engine {
         let mut validation_features = ValidationFeaturesEXT::default()
             .enabled_validation_features(enabled_validation_features.as_slice());
         let instance_info = vk::InstanceCreateInfo::default()
+            .flags(instance_create_flags)
             .application_info(Some(&app_info))
             .enabled_extension(&enabled_extensions)
             .enabled_layer(enabled_layers.as_slice())
@@ -242,13 +309,17 @@ impl Engine {
 
     pub fn create_device(
         instance: &vk::rs::Instance,
-        surface: &vk::rs::SurfaceKHR,
+        surface: Option<&vk::rs::SurfaceKHR>,
+        do_enable_async_compute: bool,
+        is_low_latency: bool,
     ) -> (
         vk::rs::PhysicalDevice,
         vk::rs::Device,
         usize,
         vk::rs::Queue,
         vk::rs::Queue,
+        Option<(usize, vk::rs::Queue)>,
+        DeviceCapabilitiesResource,
     ) {
         let physical_devices: Vec<PhysicalDevice> = instance.enumerate_physical_devices().unwrap();
 
@@ -266,57 +337,125 @@ impl Engine {
             .max_by_key(compute_device_score)
             .unwrap();
 
-        let (queue_family_index, _) = physical_device
-            .get_queue_family_properties::<Vec<_>>()
-            .into_iter()
+        let queue_families_properties = physical_device.get_queue_family_properties::<Vec<_>>();
+        let (queue_family_index, _) = queue_families_properties
+            .iter()
             .enumerate()
             .find(|(queue, props)| {
                 props.queue_flags.contains(vk::QueueFlags::Graphics)
-                    && physical_device
-                        .get_surface_support_khr(*queue as u32, *surface)
-                        .is_ok_and(|supported| supported)
+                    && surface.is_none_or(|surface| {
+                        physical_device
+                            .get_surface_support_khr(*queue as u32, *surface)
+                            .is_ok_and(|supported| supported)
+                    })
+            })
+            .unwrap();
+        // NOTE: Family without graphics is the one that runs in parallel with the graphics queue.
+        let compute_queue_family_index = queue_families_properties
+            .iter()
+            .position(|props| {
+                props.queue_flags.contains(vk::QueueFlags::Compute)
+                    && !props.queue_flags.contains(vk::QueueFlags::Graphics)
             })
+            .filter(|_| do_enable_async_compute);
+
+        let is_sampler_anisotropy_supported =
+            physical_device.get_features().sampler_anisotropy == vk::TRUE;
+        let features = vk::PhysicalDeviceFeatures::default()
+            .shader_int64(true)
+            .sampler_anisotropy(is_sampler_anisotropy_supported);
+
+        let extension_props = physical_device
+            .enumerate_device_extension_properties::<Vec<_>>(None)
             .unwrap();
+        let supported_extensions: HashSet<&CStr> = extension_props
+            .iter()
+            .map(|extension_prop| extension_prop.get_extension_name())
+            .collect();
+        let is_extension_supported = |extension: &CStr| supported_extensions.contains(extension);
+
+        if surface.is_some() && !is_extension_supported(vk::KHR_SWAPCHAIN.name.get()) {
+            panic!(
+                "Detected unsupported extension: {:?}",
+                vk::KHR_SWAPCHAIN.name
+            );
+        }
 
-        let features = vk::PhysicalDeviceFeatures::default().shader_int64(true);
+        // NOTE: Host image copy is a feature of Vulkan 1.4, older devices don't know its structures.
+        let is_vulkan14_supported =
+            physical_device.get_properties().api_version >= vk::API_VERSION_1_4;
+        let (_, vulkan14_features): (_, PhysicalDeviceVulkan14Features) =
+            physical_device.get_features2();
+
+        let device_capabilities = DeviceCapabilitiesResource {
+            is_mesh_shader_supported: is_extension_supported(EXT_MESH_SHADER.name.get()),
+            is_descriptor_buffer_supported: is_extension_supported(
+                EXT_DESCRIPTOR_BUFFER.name.get(),
+            ),
+            is_shader_object_supported: is_extension_supported(EXT_SHADER_OBJECT.name.get()),
+            is_unified_image_layouts_supported: is_extension_supported(
+                KHR_UNIFIED_IMAGE_LAYOUTS.name.get(),
+            ),
+            is_host_image_copy_supported: is_vulkan14_supported
+                && vulkan14_features.host_image_copy == vk::TRUE,
+            is_async_compute_supported: compute_queue_family_index.is_some(),
+            // NOTE: It depends on the swapchain extension, which is enabled only with the surface.
+            is_present_mode_fifo_latest_ready_supported: surface.is_some()
+                && is_extension_supported(EXT_PRESENT_MODE_FIFO_LATEST_READY.name.get()),
+            is_sampler_anisotropy_supported,
+        };
+        device_capabilities.report();
 
-        let required_extensions = [
-            vk::KHR_SWAPCHAIN.name,
+        let missing_required_features = device_capabilities.get_missing_required_features();
+        if !missing_required_features.is_empty() {
+            missing_required_features
+                .iter()
+                .for_each(|missing_feature| println!("Missing feature: {missing_feature}"));
+            panic!("Detected unsupported features.");
+        }
+
+        let mut enabled_extensions = vec![
             EXT_DESCRIPTOR_BUFFER.name,
-            KHR_UNIFIED_IMAGE_LAYOUTS.name,
             EXT_SHADER_OBJECT.name,
             EXT_MESH_SHADER.name,
             // KHR_SHADER_NON_SEMANTIC_INFO.name,
         ];
-        let mut missing_extensions: HashSet<&CStr> =
-            required_extensions.iter().map(|ext| ext.get()).collect();
-        for extension_prop in physical_device
-            .enumerate_device_extension_properties::<Vec<_>>(None)
-            .unwrap()
-        {
-            missing_extensions.remove(extension_prop.get_extension_name());
+        if surface.is_some() {
+            enabled_extensions.push(vk::KHR_SWAPCHAIN.name);
         }
-
-        if !missing_extensions.is_empty() {
-            missing_extensions
-                .iter()
-                .enumerate()
-                .for_each(|(index, missing_extension)| {
-                    println!("Missing Extension {index}: {:?}", missing_extension)
-                });
-            panic!("Detected unsupported extentions.");
+        if device_capabilities.is_unified_image_layouts_supported {
+            enabled_extensions.push(KHR_UNIFIED_IMAGE_LAYOUTS.name);
+        }
+        if device_capabilities.is_present_mode_fifo_latest_ready_supported {
+            enabled_extensions.push(EXT_PRESENT_MODE_FIFO_LATEST_READY.name);
+        }
+        // NOTE: Spec requires it to be enabled on devices that expose it, e.g. MoltenVK on macOS.
+        if is_extension_supported(vk::KHR_PORTABILITY_SUBSET.name.get()) {
+            enabled_extensions.push(vk::KHR_PORTABILITY_SUBSET.name);
         }
 
-        let queue_prio = [1.0f32, 0.5f32];
-        let queue_info = vk::DeviceQueueCreateInfo::default()
-            .queue_family_index(queue_family_index as u32)
-            .queue_priorities(&queue_prio);
+        // NOTE: Streaming uploads yield to the frame submissions, priorities are fixed with the device.
+        let transfer_queue_prio = if is_low_latency { 0.25f32 } else { 0.5f32 };
+        let queue_prio = [1.0f32, transfer_queue_prio];
+        let compute_queue_prio = [1.0f32];
+        let mut queue_infos = vec![
+            vk::DeviceQueueCreateInfo::default()
+                .queue_family_index(queue_family_index as u32)
+                .queue_priorities(&queue_prio),
+        ];
+        if let Some(compute_queue_family_index) = compute_queue_family_index {
+            queue_infos.push(
+                vk::DeviceQueueCreateInfo::default()
+                    .queue_family_index(compute_queue_family_index as u32)
+                    .queue_priorities(&compute_queue_prio),
+            );
+        }
 
-        let device_info = structure_chain!(
+        let mut device_info = structure_chain!(
             vk::DeviceCreateInfo::default()
-                .queue_create_infos(&queue_info)
+                .queue_create_infos(queue_infos.as_slice())
                 .enabled_features(Some(&features))
-                .enabled_extension(&required_extensions),
+                .enabled_extension(&enabled_extensions),
             PhysicalDeviceVulkan11Features::default().shader_draw_parameters(true),
             PhysicalDeviceVulkan12Features::default()
                 .buffer_device_address(true)
@@ -325,12 +464,17 @@ impl Engine {
                 .shader_int8(true)
                 .descriptor_binding_partially_bound(true)
                 .descriptor_binding_variable_descriptor_count(true)
-                .runtime_descriptor_array(true),
+                .runtime_descriptor_array(true)
+                .timeline_semaphore(true),
             PhysicalDeviceVulkan13Features::default()
                 .synchronization2(true)
                 .dynamic_rendering(true),
+            PhysicalDeviceVulkan14Features::default()
+                .host_image_copy(device_capabilities.is_host_image_copy_supported),
             PhysicalDeviceRobustness2FeaturesKHR::default().null_descriptor(true),
             PhysicalDeviceUnifiedImageLayoutsFeaturesKHR::default().unified_image_layouts(true),
+            PhysicalDevicePresentModeFifoLatestReadyFeaturesEXT::default()
+                .present_mode_fifo_latest_ready(true),
             PhysicalDeviceDescriptorBufferFeaturesEXT::default().descriptor_buffer(true),
             PhysicalDeviceShaderObjectFeaturesEXT::default().shader_object(true),
             PhysicalDeviceMeshShaderFeaturesEXT::default()
@@ -338,9 +482,25 @@ impl Engine {
                 .task_shader(true),
         );
 
+        if !is_vulkan14_supported {
+            device_info.unlink::<PhysicalDeviceVulkan14Features>();
+        }
+        if !device_capabilities.is_unified_image_layouts_supported {
+            device_info.unlink::<PhysicalDeviceUnifiedImageLayoutsFeaturesKHR>();
+        }
+        if !device_capabilities.is_present_mode_fifo_latest_ready_supported {
+            device_info.unlink::<PhysicalDevicePresentModeFifoLatestReadyFeaturesEXT>();
+        }
+
         let device = physical_device.create_device(device_info.as_ref()).unwrap();
         let graphics_queue = device.get_queue(queue_family_index as u32, 0);
         let transfer_queue = device.get_queue(queue_family_index as u32, 1);
+        let compute_queue = compute_queue_family_index.map(|compute_queue_family_index| {
+            (
+                compute_queue_family_index,
+                device.get_queue(compute_queue_family_index as u32, 0),
+            )
+        });
 
         (
             physical_device,
@@ -348,15 +508,20 @@ impl Engine {
             queue_family_index,
             graphics_queue,
             transfer_queue,
+            compute_queue,
+            device_capabilities,
         )
     }
 
-    fn create_swapchain(
+    pub(crate) fn create_swapchain(
         physical_device: &vk::rs::PhysicalDevice,
         device: &vk::rs::Device,
         surface: &vk::rs::SurfaceKHR,
         window_size: PhysicalSize<u32>,
-    ) -> (SwapchainKHR, SurfaceFormatKHR) {
+        vsync: bool,
+        latency_mode: LatencyMode,
+        device_capabilities: &DeviceCapabilitiesResource,
+    ) -> (SwapchainKHR, SurfaceFormatKHR, vk::Extent2D) {
         let capabilities = physical_device
             .get_surface_capabilities_khr(*surface)
             .unwrap();
@@ -375,16 +540,13 @@ impl Engine {
             })
             .unwrap();
 
-        // Only use FIFO for the time being
-        // The Vulkan spec guarantees that if the swapchain extension is supported
-        // then the FIFO present mode is too
-        if !physical_device
-            .get_surface_present_modes_khr::<Vec<_>>(Some(*surface))
-            .unwrap()
-            .contains(&vk::PresentModeKHR::Fifo)
-        {
-            panic!("Unsupported present mode: {:?}", vk::PresentModeKHR::Fifo);
-        }
+        let present_mode = Self::select_present_mode(
+            physical_device,
+            surface,
+            vsync,
+            latency_mode,
+            device_capabilities,
+        );
 
         let extent = if capabilities.current_extent.width != u32::MAX {
             capabilities.current_extent
@@ -402,6 +564,7 @@ impl Engine {
         } else {
             u32::MAX
         };
+        // NOTE: It's only the minimum, the driver may create more images, the renderer takes any count.
         let swapchain_count = (capabilities.min_image_count + 1).min(max_swap_count);
 
         let swapchain_info = vk::SwapchainCreateInfoKHR::default()
@@ -415,11 +578,50 @@ impl Engine {
             .image_sharing_mode(vk::SharingMode::Exclusive)
             .pre_transform(capabilities.current_transform)
             .composite_alpha(vk::CompositeAlphaFlagsKHR::Opaque)
-            .present_mode(vk::PresentModeKHR::Mailbox)
+            .present_mode(present_mode)
             .clipped(true);
 
         let swapchain = device.create_swapchain_khr(&swapchain_info).unwrap();
 
-        (swapchain, surface_format)
+        (swapchain, surface_format, extent)
+    }
+
+    fn select_present_mode(
+        physical_device: &vk::rs::PhysicalDevice,
+        surface: &vk::rs::SurfaceKHR,
+        vsync: bool,
+        latency_mode: LatencyMode,
+        device_capabilities: &DeviceCapabilitiesResource,
+    ) -> vk::PresentModeKHR {
+        // The Vulkan spec guarantees that if the swapchain extension is supported
+        // then the FIFO present mode is too.
+        if vsync && latency_mode == LatencyMode::Throughput {
+            return vk::PresentModeKHR::Fifo;
+        }
+
+        let present_modes = physical_device
+            .get_surface_present_modes_khr::<Vec<_>>(Some(*surface))
+            .unwrap();
+
+        // NOTE: Both vsync modes don't tear, but they replace the queued image with the newer one.
+        let preferred_present_modes: &[vk::PresentModeKHR] = match (vsync, latency_mode) {
+            (true, _) if device_capabilities.is_present_mode_fifo_latest_ready_supported => &[
+                vk::PresentModeKHR::FifoLatestReadyEXT,
+                vk::PresentModeKHR::Mailbox,
+            ],
+            (true, _) => &[vk::PresentModeKHR::Mailbox],
+            (false, LatencyMode::Throughput) => {
+                &[vk::PresentModeKHR::Mailbox, vk::PresentModeKHR::Immediate]
+            }
+            (false, LatencyMode::LowLatency) => {
+                &[vk::PresentModeKHR::Immediate, vk::PresentModeKHR::Mailbox]
+            }
+        };
+
+        preferred_present_modes
+            .iter()
+            .copied()
+            .find(|present_mode| present_modes.contains(present_mode))
+            .unwrap_or(vk::PresentModeKHR::Fifo)
     }
 }
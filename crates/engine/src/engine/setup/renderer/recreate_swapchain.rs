@@ -0,0 +1,197 @@
+use bevy_ecs::system::{Res, ResMut};
+use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
+use vulkanite::vk::Extent2D;
+use winit::{dpi::PhysicalSize, window::Window};
+
+use crate::engine::{
+    Engine,
+    ecs::setup::prepare_default_textures::create_draw_textures,
+    general::renderer::DescriptorSetHandle,
+    resources::{
+        DeferredResource, DeviceCapabilitiesResource, EngineState, LatencyMode,
+        RenderScaleSettings, RendererContext, SwapchainStatus, VulkanContextResource,
+        WindowSettings, buffers_pool::BuffersPool, textures_pool::TexturesPool,
+        transient_resources_pool::TransientResourcesPool,
+    },
+};
+
+impl Engine {
+    pub(crate) fn recreate_swapchain(
+        &mut self,
+        surface_size: PhysicalSize<u32>,
+        vsync: bool,
+        latency_mode: LatencyMode,
+    ) {
+        self.rebuild_swapchain(surface_size, vsync, latency_mode, None);
+    }
+
+    /// Recreates the surface after `SwapchainStatus::SurfaceLost`, rendering stays paused until then.
+    pub fn recreate_lost_surface(&mut self, window: &dyn Window) {
+        if self.world.resource::<RendererContext>().swapchain_status != SwapchainStatus::SurfaceLost
+        {
+            return;
+        }
+
+        let window_settings = self.world.resource::<WindowSettings>();
+        let vsync = window_settings.is_vsync_enabled();
+        let latency_mode = window_settings.get_latency_mode();
+        self.rebuild_swapchain(window.surface_size(), vsync, latency_mode, Some(window));
+    }
+
+    fn rebuild_swapchain(
+        &mut self,
+        surface_size: PhysicalSize<u32>,
+        vsync: bool,
+        latency_mode: LatencyMode,
+        window: Option<&dyn Window>,
+    ) {
+        let vulkan_context_resource = self.world.resource::<VulkanContextResource>();
+        // NOTE: Headless engine has nothing to present to, only the draw textures follow the size.
+        let Some(surface) = vulkan_context_resource.surface else {
+            if surface_size.width != Default::default() && surface_size.height != Default::default()
+            {
+                self.world.resource_mut::<RendererContext>().draw_extent = Extent2D {
+                    width: surface_size.width,
+                    height: surface_size.height,
+                };
+                self.recreate_draw_textures();
+            }
+
+            return;
+        };
+        // NOTE: Lost surface can't be queried, it's replaced below anyway.
+        let is_surface_empty = window.is_none()
+            && vulkan_context_resource
+                .physical_device
+                .get_surface_capabilities_khr(surface)
+                .map_or(true, |capabilities| {
+                    capabilities.current_extent.width == Default::default()
+                        || capabilities.current_extent.height == Default::default()
+                });
+        // NOTE: Minimized window, nothing to present to until it's restored.
+        if is_surface_empty
+            || surface_size.width == Default::default()
+            || surface_size.height == Default::default()
+        {
+            let mut renderer_context = self.world.resource_mut::<RendererContext>();
+            if renderer_context.swapchain_status == SwapchainStatus::Valid {
+                renderer_context.swapchain_status = SwapchainStatus::OutOfDate;
+            }
+            *self.world.resource_mut::<EngineState>() = EngineState::Paused;
+
+            return;
+        }
+
+        let device = vulkan_context_resource.device;
+        device.wait_idle().unwrap();
+
+        let mut renderer_context = self.world.resource_mut::<RendererContext>();
+        renderer_context
+            .image_views
+            .drain(..)
+            .for_each(|image_view| unsafe {
+                device.destroy_image_view(Some(image_view));
+            });
+        renderer_context
+            .render_semaphores
+            .drain(..)
+            .for_each(|render_semaphore| unsafe {
+                device.destroy_semaphore(Some(render_semaphore));
+            });
+
+        let device_capabilities = *self.world.resource::<DeviceCapabilitiesResource>();
+        let mut vulkan_context_resource = self.world.resource_mut::<VulkanContextResource>();
+        unsafe {
+            device.destroy_swapchain_khr(vulkan_context_resource.swapchain);
+        }
+
+        let surface = match window {
+            Some(window) => {
+                unsafe {
+                    vulkan_context_resource
+                        .instance
+                        .destroy_surface_khr(Some(surface));
+                }
+                let surface = vulkanite::window::rs::create_surface(
+                    &vulkan_context_resource.instance,
+                    &window.display_handle().unwrap().as_raw(),
+                    &window.window_handle().unwrap().as_raw(),
+                )
+                .unwrap();
+                vulkan_context_resource.surface = Some(surface);
+
+                surface
+            }
+            None => surface,
+        };
+
+        let (swapchain, surface_format, extent) = Self::create_swapchain(
+            &vulkan_context_resource.physical_device,
+            &device,
+            &surface,
+            surface_size,
+            vsync,
+            latency_mode,
+            &device_capabilities,
+        );
+        vulkan_context_resource.swapchain = Some(swapchain);
+        vulkan_context_resource.surface_format = surface_format;
+
+        let (images, image_views) = Self::create_swapchain_image_views(&vulkan_context_resource);
+        // NOTE: Images count may change with the new swapchain, frames in flight stay the same.
+        let render_semaphores = Self::create_render_semaphores(&device, images.len());
+
+        let mut renderer_context = self.world.resource_mut::<RendererContext>();
+        renderer_context.images = images;
+        renderer_context.image_views = image_views;
+        renderer_context.render_semaphores = render_semaphores;
+        renderer_context.draw_extent = extent;
+        renderer_context.swapchain_status = SwapchainStatus::Valid;
+        *self.world.resource_mut::<EngineState>() = EngineState::Running;
+
+        self.recreate_draw_textures();
+    }
+
+    pub(crate) fn recreate_draw_textures(&mut self) {
+        let device = self.world.resource::<VulkanContextResource>().device;
+        device.wait_idle().unwrap();
+
+        self.world
+            .run_system_cached(recreate_draw_textures_system)
+            .unwrap();
+    }
+}
+
+fn recreate_draw_textures_system(
+    mut renderer_context: ResMut<RendererContext>,
+    render_scale_settings: Res<RenderScaleSettings>,
+    mut descriptor_set_handle: ResMut<DescriptorSetHandle>,
+    mut textures_pool: ResMut<TexturesPool>,
+    mut buffers_pool: ResMut<BuffersPool>,
+    mut transient_resources_pool: ResMut<TransientResourcesPool>,
+) {
+    renderer_context.frames_data.iter().for_each(|frame_data| {
+        textures_pool.free_texture(frame_data.draw_texture_reference);
+        textures_pool.free_texture(frame_data.depth_texture_reference);
+    });
+    // NOTE: Device is idle, so resources of the old extent don't wait for `MAX_UNUSED_FRAMES`.
+    for transient_resource in transient_resources_pool.take_resources() {
+        match transient_resource {
+            DeferredResource::Texture(texture_reference) => {
+                textures_pool.free_texture(texture_reference);
+            }
+            DeferredResource::Buffer(buffer_reference) => unsafe {
+                buffers_pool.free_buffer(buffer_reference);
+            },
+            DeferredResource::MeshBuffer(_) => (),
+        }
+    }
+
+    create_draw_textures(
+        &mut renderer_context,
+        &render_scale_settings,
+        &mut descriptor_set_handle,
+        &mut textures_pool,
+        &mut buffers_pool,
+    );
+}
@@ -2,7 +2,8 @@ use vulkanite::vk::{rs::*, *};
 
 #[derive(Clone, Copy)]
 pub struct ShaderInfo<'a> {
-    pub path: &'a str,
+    /// File of the compiled shader, see `load_shader`.
+    pub name: &'a str,
     pub flags: ShaderCreateFlagsEXT,
     pub stage: ShaderStageFlags,
     pub next_stage: ShaderStageFlags,
@@ -48,6 +49,70 @@ pub fn transition_image(
     command_buffer.pipeline_barrier2(&dependency_info);
 }
 
+/// Half of a queue family ownership transfer, the release is recorded on the source queue
+/// and the acquire on the destination one, both with the same families and layouts.
+pub fn transfer_image_ownership(
+    command_buffer: CommandBuffer,
+    image: Image,
+    image_layout: ImageLayout,
+    src_stage_mask: PipelineStageFlags2,
+    dst_stage_mask: PipelineStageFlags2,
+    src_access_mask: AccessFlags2,
+    dst_access_mask: AccessFlags2,
+    src_queue_family_index: u32,
+    dst_queue_family_index: u32,
+    image_aspect_flags: ImageAspectFlags,
+    mip_levels_count: u32,
+) {
+    let image_memory_barrier = ImageMemoryBarrier2::default()
+        .src_stage_mask(src_stage_mask)
+        .src_access_mask(src_access_mask)
+        .dst_stage_mask(dst_stage_mask)
+        .dst_access_mask(dst_access_mask)
+        .old_layout(image_layout)
+        .new_layout(image_layout)
+        .src_queue_family_index(src_queue_family_index)
+        .dst_queue_family_index(dst_queue_family_index)
+        .image(&image)
+        .subresource_range(image_subresource_range(
+            image_aspect_flags,
+            mip_levels_count,
+        ));
+
+    let image_memory_barriers = [image_memory_barrier];
+    let dependency_info = DependencyInfo::default().image_memory_barriers(&image_memory_barriers);
+
+    command_buffer.pipeline_barrier2(&dependency_info);
+}
+
+/// Same as `transfer_image_ownership`, but for the whole buffer.
+pub fn transfer_buffer_ownership(
+    command_buffer: CommandBuffer,
+    buffer: Buffer,
+    src_stage_mask: PipelineStageFlags2,
+    dst_stage_mask: PipelineStageFlags2,
+    src_access_mask: AccessFlags2,
+    dst_access_mask: AccessFlags2,
+    src_queue_family_index: u32,
+    dst_queue_family_index: u32,
+) {
+    let buffer_memory_barrier = BufferMemoryBarrier2::default()
+        .src_stage_mask(src_stage_mask)
+        .src_access_mask(src_access_mask)
+        .dst_stage_mask(dst_stage_mask)
+        .dst_access_mask(dst_access_mask)
+        .src_queue_family_index(src_queue_family_index)
+        .dst_queue_family_index(dst_queue_family_index)
+        .buffer(&buffer)
+        .offset(Default::default())
+        .size(WHOLE_SIZE);
+
+    let buffer_memory_barriers = [buffer_memory_barrier];
+    let dependency_info = DependencyInfo::default().buffer_memory_barriers(&buffer_memory_barriers);
+
+    command_buffer.pipeline_barrier2(&dependency_info);
+}
+
 pub fn image_subresource_range(
     aspect_mask: ImageAspectFlags,
     mip_levels_count: u32,
@@ -140,3 +205,48 @@ pub fn copy_image_to_image(
 
     command_buffer.blit_image2(&image_blit_info);
 }
+
+/// Copies the same regions between images of the same format, both are in the general layout.
+pub fn copy_image_regions(
+    command_buffer: CommandBuffer,
+    source_image: Image,
+    destination_image: Image,
+    regions: &[Rect2D],
+) {
+    let subresource = ImageSubresourceLayers {
+        aspect_mask: ImageAspectFlags::Color,
+        mip_level: Default::default(),
+        base_array_layer: Default::default(),
+        layer_count: 1,
+    };
+    let copy_regions: Vec<_> = regions
+        .iter()
+        .map(|region| {
+            let offset = Offset3D {
+                x: region.offset.x,
+                y: region.offset.y,
+                z: Default::default(),
+            };
+
+            ImageCopy2::default()
+                .src_subresource(subresource)
+                .src_offset(offset)
+                .dst_subresource(subresource)
+                .dst_offset(offset)
+                .extent(Extent3D {
+                    width: region.extent.width,
+                    height: region.extent.height,
+                    depth: 1,
+                })
+        })
+        .collect();
+
+    let copy_image_info = CopyImageInfo2::default()
+        .src_image_layout(ImageLayout::General)
+        .dst_image_layout(ImageLayout::General)
+        .src_image(&source_image)
+        .dst_image(&destination_image)
+        .regions(&copy_regions);
+
+    command_buffer.copy_image2(&copy_image_info);
+}
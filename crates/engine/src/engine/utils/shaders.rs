@@ -1,5 +1,12 @@
-use std::fs;
+use std::{fs, path::PathBuf};
 
-pub fn load_shader(path: &str) -> Vec<u8> {
-    fs::read(path).unwrap()
+/// Compiled shaders are resolved against the working directory, `build.rs` writes them there.
+pub fn get_shader_path(name: &str) -> PathBuf {
+    PathBuf::from("intermediate").join("shaders").join(name)
+}
+
+pub fn load_shader(name: &str) -> Vec<u8> {
+    let path = get_shader_path(name);
+
+    fs::read(&path).unwrap_or_else(|error| panic!("Failed to read {}: {}", path.display(), error))
 }
@@ -1,9 +1,14 @@
 use bevy_ecs::schedule::Schedule;
 
 pub mod engine;
+pub mod prelude;
+
+pub use bevy_ecs;
 pub use engine::math;
+pub use winit;
 
 pub trait GamePlugin {
     fn add_systems_init(&self, schedule: &mut Schedule);
+    /// Systems run in parallel, those holding non-thread-safe state take `NonSendMarker`.
     fn add_systems_update(&self, schedule: &mut Schedule);
 }
@@ -0,0 +1,44 @@
+//! Surface game crates compile against, `use engine::prelude::*;` brings the engine, its components,
+//! events and resources with the ECS and math types they are used with.
+//! Renderer internals stay private, the asset cooker and golden images need the `tools` feature,
+//! everything else is under `engine::engine`.
+
+pub use crate::GamePlugin;
+pub use crate::engine::{Engine, EngineConfig, SchedulerGameInit, SchedulerGameUpdate};
+
+pub use crate::engine::{
+    AnimateMaterial, Billboard, BillboardMode, Camera, CameraRig, CameraRigMode, CameraShake,
+    CameraViewport, ClearBehavior, ClippingPlanes, Collider, DepthOfField, EulerTracker,
+    FoliageScatter, Highlighted, Impostor, LoadedModel, LocalTransform, MaterialCurve,
+    MaterialProperties, Mesh, Persistent, PointLight, ReflectionPlane, RigidBody, ScriptValue,
+    ScriptVars, Selected, StaticInstance, Terrain, Time, VisibilityQuery, WaterSettings, WorldCell,
+};
+
+pub use crate::engine::{
+    CVarChangedEvent, ConsoleCommandEvent, LoadLensFlareTexturesEvent, LoadModelEvent,
+};
+
+pub use crate::engine::{
+    AtmosphericScattering, Audio, AudioReference, CVarValue, CVars, Clipboard, Console,
+    CullingSettings, DepthPickResult, DepthPicking, EngineState, EnvironmentSettings, Gizmos,
+    Input, OutlineSettings, OutlineStyle, Physics, Prefab, Snapshots, TaskPool, TextInput,
+    TextureReference, TimeOfDay, Transform, TransformOverride, spawn_prefab, spawn_prefab_batch,
+    spawn_prefab_batch_with,
+};
+
+pub use crate::math::{
+    EulerRot, Mat4, Quat, Random, RandomState, SpringDamper, Vec2, Vec3, Vec4, damp, damp_rotation,
+    gradient_noise,
+};
+
+pub use bevy_ecs::{
+    component::Component,
+    entity::Entity,
+    hierarchy::{ChildOf, Children},
+    observer::On,
+    query::{With, Without},
+    resource::Resource,
+    schedule::{IntoScheduleConfigs, Schedule},
+    system::{Commands, Local, NonSendMut, Query, Res, ResMut},
+};
+pub use winit::keyboard::KeyCode;
@@ -1,24 +1,16 @@
 use core::panic;
-use std::path::PathBuf;
 
 use bevy_ecs::{
     component::Component,
-    entity::{Entity, EntityCloner},
-    entity_disabling::Disabled,
+    entity::Entity,
     hierarchy::Children,
-    query::{Has, With},
+    observer::On,
+    query::With,
     relationship::RelationshipTarget,
-    system::{Command, Commands, Local, Query, Res, ResMut},
-    world::World,
+    resource::Resource,
+    system::{Commands, Local, Query, Res, ResMut},
 };
-use engine::{
-    GamePlugin,
-    engine::{
-        AudioReference, Camera, ClippingPlanes, Input, LoadModelEvent, LocalTransform, Mesh,
-        Physics, Time, Transform,
-    },
-};
-use engine::{engine::Audio, math::*};
+use engine::prelude::*;
 use winit::keyboard::KeyCode;
 
 #[unsafe(no_mangle)]
@@ -30,7 +22,12 @@ struct Game;
 
 impl GamePlugin for Game {
     fn add_systems_init(&self, schedule: &mut bevy_ecs::schedule::Schedule) {
-        schedule.add_systems((spawn_planet, play_audio, spawn_player));
+        schedule.add_systems((
+            spawn_planet,
+            play_audio,
+            spawn_player,
+            register_console_commands,
+        ));
     }
 
     fn add_systems_update(&self, schedule: &mut bevy_ecs::schedule::Schedule) {
@@ -79,14 +76,13 @@ pub struct AsteroidInstance {
     rotation_axis: AsteroidRotationAxis,
 }
 
-pub trait Prefab {
-    fn instantiate(&self, commands: Commands) -> Entity;
-}
-
 #[derive(Component)]
 #[require(LocalTransform)]
 pub struct AsteroidPrefab;
 
+#[derive(Resource)]
+pub struct AsteroidPrefabHandle(Prefab);
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum AsteroidRotationAxis {
     X,
@@ -94,53 +90,11 @@ enum AsteroidRotationAxis {
     Z,
 }
 
-struct CloneHierarchyCommand {
-    pub source: Entity,
-    pub position: Vec3,
-    pub scale: Vec3,
-    pub rotation: Vec3,
-    pub asteroid_rotation_axis: AsteroidRotationAxis,
-}
-
-impl Command for CloneHierarchyCommand {
-    fn apply(self, world: &mut World) {
-        let mut entity_cloner_builder = EntityCloner::build_opt_out(world);
-        entity_cloner_builder.linked_cloning(true);
-        let mut entity_cloner = entity_cloner_builder.finish();
-
-        let entity = entity_cloner.spawn_clone(world, self.source);
-        let mut entity = world.entity_mut(entity);
-        let mut entity_transform = entity.get_mut::<LocalTransform>().unwrap();
-        entity_transform.local_position = self.position;
-        entity_transform.local_scale = self.scale;
-        entity_transform.set_local_euler_angles(self.rotation);
-
-        entity.insert(AsteroidInstance {
-            rotation_axis: self.asteroid_rotation_axis,
-        });
-        entity.remove_recursive::<Children, Disabled>();
-        entity.remove::<AsteroidPrefab>();
-    }
-}
-
-fn play_audio(mut commands: Commands, mut audio: ResMut<Audio>) {
-    // TODO: Deduplicate and simplify.
-    let mut exe_path = std::env::current_exe().unwrap();
-
-    exe_path.pop();
-    exe_path.pop();
-    exe_path.pop();
-
-    let _sound = audio.load_audio(&PathBuf::from(std::format!(
-        "{}/assets/audio/sound.mp3",
-        exe_path.as_os_str().display()
-    )));
+fn play_audio(mut commands: Commands, engine_config: Res<EngineConfig>, mut audio: ResMut<Audio>) {
+    let _sound = audio.load_audio(&engine_config.get_asset_path("audio/sound.mp3"));
     //audio.play_audio(sound, true);
 
-    let fire_sound = audio.load_audio(&PathBuf::from(std::format!(
-        "{}/assets/audio/fire.mp3",
-        exe_path.as_os_str().display()
-    )));
+    let fire_sound = audio.load_audio(&engine_config.get_asset_path("audio/fire.mp3"));
 
     let mut fire_audio_handle_entity = commands.spawn_empty();
     fire_audio_handle_entity.insert(FireAudioHandle {
@@ -160,26 +114,31 @@ fn fire_from_gun(
     }
 }
 
-fn spawn_planet(mut commands: Commands) {
-    // TODO: Deduplicate and simplify.
-    let mut exe_path = std::env::current_exe().unwrap();
-
-    exe_path.pop();
-    exe_path.pop();
-    exe_path.pop();
-
+fn spawn_planet(
+    mut commands: Commands,
+    engine_config: Res<EngineConfig>,
+    mut environment_settings: ResMut<EnvironmentSettings>,
+    mut time_of_day: ResMut<TimeOfDay>,
+) {
     let planet_scale = 20.0;
     let mut planet_transform = LocalTransform::IDENTITY;
     planet_transform.local_scale *= planet_scale;
 
+    let planet_radius = planet_scale * 2.0;
+    environment_settings.atmospheric_scattering = Some(AtmosphericScattering {
+        planet_center: planet_transform.local_position,
+        planet_radius,
+        atmosphere_radius: planet_radius * 1.15,
+        ..Default::default()
+    });
+    time_of_day.is_enabled = true;
+    time_of_day.set_hours(8.0);
+
     let planet_entity = commands.spawn((PlanetTag, planet_transform));
     let planet_entity_id = planet_entity.id();
 
     commands.trigger(LoadModelEvent {
-        path: PathBuf::from(std::format!(
-            "{}/assets/planet.glb",
-            exe_path.as_os_str().display()
-        )),
+        path: engine_config.get_asset_path("planet.glb"),
         parent_entity: Some(planet_entity_id),
     });
 
@@ -187,14 +146,13 @@ fn spawn_planet(mut commands: Commands) {
     let mut asteroid_transform = LocalTransform::IDENTITY;
     asteroid_transform.local_scale *= asteroid;
 
-    let asteroid_entity = commands.spawn((AsteroidPrefab, Disabled, asteroid_transform));
+    let asteroid_entity = commands.spawn((AsteroidPrefab, asteroid_transform));
     let asteroid_entity_id = asteroid_entity.id();
+    let asteroid_prefab = Prefab::register(&mut commands, asteroid_entity_id);
+    commands.insert_resource(AsteroidPrefabHandle(asteroid_prefab));
 
     commands.trigger(LoadModelEvent {
-        path: PathBuf::from(std::format!(
-            "{}/assets/asteroid.glb",
-            exe_path.as_os_str().display()
-        )),
+        path: engine_config.get_asset_path("asteroid.glb"),
         parent_entity: Some(asteroid_entity_id),
     });
 }
@@ -202,59 +160,128 @@ fn spawn_planet(mut commands: Commands) {
 fn spawn_asteroids(
     mut commands: Commands,
     planet_query: Query<&LocalTransform, With<PlanetTag>>,
-    asteroid_prefab_query: Query<(Entity, Option<&Children>, Has<Disabled>), With<AsteroidPrefab>>,
+    asteroid_prefab_query: Query<Option<&Children>, With<AsteroidPrefab>>,
+    asteroid_prefab_handle: Option<Res<AsteroidPrefabHandle>>,
     mut random: ResMut<Random>,
     mut has_spawned: Local<bool>,
 ) {
     if !*has_spawned
-        && let Ok((asteroid_prefab_entity, children, _)) = asteroid_prefab_query.single()
+        && let Some(asteroid_prefab_handle) = asteroid_prefab_handle
+        && let Ok(children) = asteroid_prefab_query.get(asteroid_prefab_handle.0.get_root())
         && children.is_some()
         && !children.unwrap().is_empty()
     {
         *has_spawned = true;
 
-        let mut inner_radius = 50.0;
-        let mut outer_radius = inner_radius * 10.0;
-        let belt_radius = outer_radius - inner_radius;
-        let belt_thicness = 4.5;
-
+        let random = random.stream("asteroids");
         let planet_transform = planet_query.single().unwrap();
-        let planet_radius = planet_transform.local_scale.x * 2.0;
-        if inner_radius <= planet_radius {
-            inner_radius = planet_radius + 1.0;
-            outer_radius = inner_radius + belt_radius;
-        }
 
-        for _ in 0..5_000 {
-            let random_direction = random.inside_unit_circle().normalize();
-            let random_distance = random
-                .range(inner_radius.powi(2)..outer_radius.powi(2))
-                .sqrt();
-
-            let mut position = vec3(random_direction.x, 0.0, random_direction.y) * random_distance;
-            position.y = random.range(-belt_thicness..belt_thicness);
-            let scale = random.range(0.25..1.0);
-
-            let asteroid_rotation_axis = match random.range(0..3) {
-                0 => AsteroidRotationAxis::X,
-                1 => AsteroidRotationAxis::Y,
-                2 => AsteroidRotationAxis::Z,
-                _ => panic!("Only X, Y, Z axis supported"),
-            };
+        const ASTEROIDS_COUNT: usize = 5_000;
 
-            commands.queue(CloneHierarchyCommand {
-                source: asteroid_prefab_entity,
-                position: planet_transform.local_position + position,
-                scale: vec3(scale, scale, scale),
-                rotation: vec3(
-                    random.range(-360.0..360.0),
-                    random.range(-360.0..360.0),
-                    random.range(-360.0..360.0),
-                ),
-                asteroid_rotation_axis,
-            });
-        }
+        let asteroid_instances =
+            create_asteroid_instances(random, planet_transform, ASTEROIDS_COUNT);
+        spawn_prefab_batch_with(&mut commands, asteroid_prefab_handle.0, asteroid_instances);
+    }
+}
+
+fn create_asteroid_instances(
+    random: &mut Random,
+    planet_transform: &LocalTransform,
+    asteroids_count: usize,
+) -> Vec<(TransformOverride, (AsteroidInstance, Impostor))> {
+    let mut inner_radius = 50.0;
+    let mut outer_radius = inner_radius * 10.0;
+    let belt_radius = outer_radius - inner_radius;
+    let belt_thicness = 4.5;
+
+    let planet_radius = planet_transform.local_scale.x * 2.0;
+    if inner_radius <= planet_radius {
+        inner_radius = planet_radius + 1.0;
+        outer_radius = inner_radius + belt_radius;
     }
+
+    const IMPOSTOR_DISTANCE: f32 = 150.0;
+
+    let mut asteroid_instances = Vec::with_capacity(asteroids_count);
+    for _ in 0..asteroids_count {
+        let random_direction = random.inside_unit_circle().normalize();
+        let random_distance = random
+            .range(inner_radius.powi(2)..outer_radius.powi(2))
+            .sqrt();
+
+        let mut position = vec3(random_direction.x, 0.0, random_direction.y) * random_distance;
+        position.y = random.range(-belt_thicness..belt_thicness);
+        let scale = random.range(0.25..1.0);
+
+        let asteroid_rotation_axis = match random.range(0..3) {
+            0 => AsteroidRotationAxis::X,
+            1 => AsteroidRotationAxis::Y,
+            2 => AsteroidRotationAxis::Z,
+            _ => panic!("Only X, Y, Z axis supported"),
+        };
+
+        let rotation = Quat::from_euler(
+            EulerRot::YXZ,
+            random.range(-360.0f32..360.0).to_radians(),
+            random.range(-360.0f32..360.0).to_radians(),
+            random.range(-360.0f32..360.0).to_radians(),
+        );
+
+        asteroid_instances.push((
+            TransformOverride::new(
+                planet_transform.local_position + position,
+                rotation,
+                vec3(scale, scale, scale),
+            ),
+            (
+                AsteroidInstance {
+                    rotation_axis: asteroid_rotation_axis,
+                },
+                Impostor::new(IMPOSTOR_DISTANCE),
+            ),
+        ));
+    }
+
+    asteroid_instances
+}
+
+fn register_console_commands(mut commands: Commands, mut console: ResMut<Console>) {
+    console.register_command(
+        "spawn_asteroids",
+        "<count>",
+        "Spawns asteroids into the belt around the planet.",
+    );
+    commands.add_observer(on_spawn_asteroids_command);
+}
+
+fn on_spawn_asteroids_command(
+    console_command_event: On<ConsoleCommandEvent>,
+    mut commands: Commands,
+    planet_query: Query<&LocalTransform, With<PlanetTag>>,
+    asteroid_prefab_handle: Option<Res<AsteroidPrefabHandle>>,
+    mut random: ResMut<Random>,
+    mut console: ResMut<Console>,
+) {
+    if console_command_event.name != "spawn_asteroids" {
+        return;
+    }
+
+    let Some(asteroids_count) = console_command_event.get_argument::<usize>(0) else {
+        console.log("Usage: spawn_asteroids <count>");
+        return;
+    };
+    let (Some(asteroid_prefab_handle), Ok(planet_transform)) =
+        (asteroid_prefab_handle, planet_query.single())
+    else {
+        console.log("Asteroids can't be spawned, the planet isn't loaded yet.");
+        return;
+    };
+
+    let random = random.stream("asteroids");
+    let asteroid_instances = create_asteroid_instances(random, planet_transform, asteroids_count);
+    spawn_prefab_batch_with(&mut commands, asteroid_prefab_handle.0, asteroid_instances);
+
+    console.log(std::format!("Spawned {} asteroids", asteroids_count));
 }
 
 fn create_rigidbody_for_planet(
@@ -308,21 +335,16 @@ fn rotate_asteroids(
     asteroids_query
         .par_iter_mut()
         .for_each(|(mut asteroid_transform, asteroid_instance)| {
-            let mut euler_angles = asteroid_transform.get_local_euler_angles();
-
-            match asteroid_instance.rotation_axis {
-                AsteroidRotationAxis::X => {
-                    euler_angles.x += asteroid_speed * delta_time;
-                }
-                AsteroidRotationAxis::Y => {
-                    euler_angles.y += asteroid_speed * delta_time;
-                }
-                AsteroidRotationAxis::Z => {
-                    euler_angles.z += asteroid_speed * delta_time;
-                }
-            }
+            let rotation_axis = match asteroid_instance.rotation_axis {
+                AsteroidRotationAxis::X => Vec3::X,
+                AsteroidRotationAxis::Y => Vec3::Y,
+                AsteroidRotationAxis::Z => Vec3::Z,
+            };
 
-            asteroid_transform.set_local_euler_angles(euler_angles);
+            asteroid_transform.rotate_local(Quat::from_axis_angle(
+                rotation_axis,
+                (asteroid_speed * delta_time).to_radians(),
+            ));
         });
 }
 
@@ -333,6 +355,7 @@ fn spawn_player(mut commands: Commands) {
             near: 0.1,
             far: 1000.0,
         },
+        ..Default::default()
     };
     let player_stats_component = PlayerStats {
         move_speed: 5.0,
@@ -356,6 +379,7 @@ fn spawn_player(mut commands: Commands) {
         player_stats_component,
         player_jump,
         player_transform,
+        EulerTracker::from_rotation(player_transform.local_rotation),
     ));
 }
 
@@ -394,23 +418,21 @@ fn move_player(
 }
 
 fn rotate_player(
-    mut player_query: Query<(&mut LocalTransform, &PlayerStats)>,
+    mut player_query: Query<(&mut LocalTransform, &mut EulerTracker, &PlayerStats)>,
     time: Res<Time>,
     input: Res<Input>,
 ) {
     let delta_time = time.get_delta_time();
     let mouse_axis = input.get_mouse_axis();
 
-    let (mut transform, player_stats) = player_query.single_mut().unwrap();
-
-    let mut angles = transform.get_local_euler_angles();
-
-    angles.y -= player_stats.rotation_speed * mouse_axis.x * delta_time;
+    let (mut transform, mut euler_tracker, player_stats) = player_query.single_mut().unwrap();
 
-    angles.x += player_stats.rotation_speed * mouse_axis.y * delta_time;
-    angles.x = angles.x.clamp(-90.0, 90.0);
+    euler_tracker.rotate(
+        -player_stats.rotation_speed * mouse_axis.x * delta_time,
+        player_stats.rotation_speed * mouse_axis.y * delta_time,
+    );
 
-    transform.set_local_euler_angles(angles);
+    transform.local_rotation = euler_tracker.get_rotation();
 }
 
 fn jump_player(
@@ -1,43 +1,44 @@
 //#![windows_subsystem = "windows"]
 
-use engine::{GamePlugin, engine::Engine};
+use std::path::PathBuf;
+
+use engine::prelude::{Engine, EngineConfig, GamePlugin};
 use libloading::{Library, Symbol};
 use winit::{
     application::ApplicationHandler,
-    dpi::PhysicalSize,
     event::{ElementState, KeyEvent},
     event_loop::EventLoop,
     keyboard::{KeyCode, PhysicalKey},
-    window::{Window, WindowAttributes},
+    window::Window,
 };
 
-#[derive(Default)]
+enum ReplayArgument {
+    Record(PathBuf),
+    Play(PathBuf),
+}
+
 struct Application {
     window: Option<Box<dyn Window>>,
     engine: Option<Engine>,
     game: Option<Box<dyn GamePlugin>>,
     lib: Option<Library>,
+    replay_arguments: Vec<ReplayArgument>,
 }
 
 impl ApplicationHandler for Application {
     fn can_create_surfaces(&mut self, event_loop: &dyn winit::event_loop::ActiveEventLoop) {
-        let surface_size = PhysicalSize::new(1700, 900);
-        let window_attributes = WindowAttributes::default()
-            .with_title("Vulkan Engine")
-            .with_surface_size(surface_size);
+        let engine_config = EngineConfig::load();
 
-        self.window = match event_loop.create_window(window_attributes) {
+        self.window = match event_loop.create_window(engine_config.get_window_attributes()) {
             Ok(window) => {
-                let mut engine = Engine::new(window.as_ref());
+                let mut engine = Engine::new(window.as_ref(), engine_config);
 
-                let lib_path = if cfg!(target_os = "windows") {
-                    "game_logic.dll"
-                } else {
-                    "libgame_logic.so"
-                };
+                let lib_path = Self::get_game_library_path();
 
                 unsafe {
-                    let lib = Library::new(lib_path).expect("Failed to load DLL.");
+                    let lib = Library::new(&lib_path).unwrap_or_else(|error| {
+                        panic!("Failed to load {}: {}", lib_path.display(), error)
+                    });
 
                     let get_game_func: Symbol<fn() -> Box<dyn GamePlugin>> =
                         lib.get(b"get_game").unwrap();
@@ -49,6 +50,13 @@ impl ApplicationHandler for Application {
                     self.game = Some(game_plugin);
                 }
 
+                for replay_argument in self.replay_arguments.drain(..) {
+                    match replay_argument {
+                        ReplayArgument::Record(path) => engine.start_replay_recording(path),
+                        ReplayArgument::Play(path) => engine.start_replay_playback(path),
+                    }
+                }
+
                 self.engine = Some(engine);
 
                 Some(window)
@@ -76,9 +84,14 @@ impl ApplicationHandler for Application {
         _: winit::window::WindowId,
         event: winit::event::WindowEvent,
     ) {
+        // NOTE: Escape closes the console first, if it's open.
+        let is_console_open = self.engine.as_ref().is_some_and(Engine::is_console_open);
+
         match event {
-            winit::event::WindowEvent::CloseRequested
-            | winit::event::WindowEvent::KeyboardInput {
+            winit::event::WindowEvent::CloseRequested => {
+                self.exit(event_loop);
+            }
+            winit::event::WindowEvent::KeyboardInput {
                 device_id: _,
                 event:
                     KeyEvent {
@@ -92,8 +105,8 @@ impl ApplicationHandler for Application {
                         key_without_modifiers: _,
                     },
                 is_synthetic: _,
-            } => {
-                event_loop.exit();
+            } if !is_console_open => {
+                self.exit(event_loop);
             }
             winit::event::WindowEvent::KeyboardInput {
                 device_id: _,
@@ -101,28 +114,45 @@ impl ApplicationHandler for Application {
                     KeyEvent {
                         physical_key,
                         logical_key: _,
-                        text: _,
+                        text,
                         location: _,
                         state,
-                        repeat: _,
+                        repeat,
                         text_with_all_modifiers: _,
                         key_without_modifiers: _,
                     },
 
                 is_synthetic: _,
-            } => match physical_key {
-                PhysicalKey::Code(code) => {
-                    if let Some(engine) = &mut self.engine {
-                        engine.process_input(code, state);
+            } => {
+                if let Some(engine) = &mut self.engine {
+                    if let PhysicalKey::Code(code) = physical_key {
+                        engine.process_input(code, state, repeat);
+                    }
+                    if let Some(text) = text
+                        && state == ElementState::Pressed
+                    {
+                        engine.process_text(text.as_str());
                     }
                 }
-                PhysicalKey::Unidentified(_) => {}
-            },
+            }
+            winit::event::WindowEvent::Ime(ime) => {
+                if let Some(engine) = &mut self.engine {
+                    engine.process_ime(ime);
+                }
+            }
+            winit::event::WindowEvent::SurfaceResized(surface_size) => {
+                if let Some(engine) = &mut self.engine {
+                    engine.resize(surface_size);
+                }
+            }
             winit::event::WindowEvent::RedrawRequested => {
                 let window = unsafe { self.window.as_ref().unwrap_unchecked() };
 
                 if let Some(engine) = &mut self.engine {
+                    engine.recreate_lost_surface(window.as_ref());
                     engine.update();
+                    engine.apply_window_settings(window.as_ref());
+                    engine.apply_text_input(window.as_ref());
                 }
 
                 window.request_redraw();
@@ -132,6 +162,67 @@ impl ApplicationHandler for Application {
     }
 }
 
+impl Application {
+    fn new(replay_arguments: Vec<ReplayArgument>) -> Self {
+        Self {
+            window: None,
+            engine: None,
+            game: None,
+            lib: None,
+            replay_arguments,
+        }
+    }
+
+    /// Engine is shut down while the window and its surface are still alive.
+    fn exit(&mut self, event_loop: &dyn winit::event_loop::ActiveEventLoop) {
+        if let Some(engine) = self.engine.take() {
+            engine.shutdown();
+        }
+        event_loop.exit();
+    }
+
+    /// Game library is built next to the runner, its name follows the platform:
+    /// `game_logic.dll`, `libgame_logic.so` or `libgame_logic.dylib`.
+    fn get_game_library_path() -> PathBuf {
+        let library_name = libloading::library_filename("game_logic");
+
+        match std::env::current_exe() {
+            Ok(mut executable_path) => {
+                executable_path.pop();
+
+                executable_path.join(library_name)
+            }
+            Err(_) => PathBuf::from(library_name),
+        }
+    }
+
+    const USAGE: &str = "Usage: runner [--record-replay <path>] [--replay <path>]";
+
+    /// `--record-replay <path>` records the session, `--replay <path>` plays it back.
+    /// Arguments are checked before the window is created, so typos don't start a plain session.
+    fn parse_replay_arguments() -> Result<Vec<ReplayArgument>, String> {
+        let mut replay_arguments = Vec::new();
+
+        let mut arguments = std::env::args().skip(1);
+        while let Some(argument) = arguments.next() {
+            match (argument.as_str(), arguments.next()) {
+                ("--record-replay", Some(path)) => {
+                    replay_arguments.push(ReplayArgument::Record(path.into()))
+                }
+                ("--replay", Some(path)) => {
+                    replay_arguments.push(ReplayArgument::Play(path.into()))
+                }
+                ("--record-replay" | "--replay", None) => {
+                    return Err(std::format!("Missing path of {}", argument));
+                }
+                (argument, _) => return Err(std::format!("Unknown argument {}", argument)),
+            }
+        }
+
+        Ok(replay_arguments)
+    }
+}
+
 impl Drop for Application {
     fn drop(&mut self) {
         self.game = None;
@@ -139,7 +230,17 @@ impl Drop for Application {
 }
 
 fn main() {
+    let replay_arguments = match Application::parse_replay_arguments() {
+        Ok(replay_arguments) => replay_arguments,
+        Err(error) => {
+            eprintln!("{}\n{}", error, Application::USAGE);
+            std::process::exit(2);
+        }
+    };
+
     let event_loop = EventLoop::new().unwrap();
 
-    event_loop.run_app(Application::default()).unwrap();
+    event_loop
+        .run_app(Application::new(replay_arguments))
+        .unwrap();
 }